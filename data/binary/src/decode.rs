@@ -0,0 +1,389 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{bitmap_len, Binary, TypeTag, FORMAT_VERSION, VERSION_MARKER};
+use repr::Datum;
+use std::fmt::{self, Display, Formatter};
+
+/// reasons why bytes could not be unpacked into datums, offsets are counted
+/// from the beginning of the packed value
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DecodeError {
+    /// value ends in the middle of a datum
+    UnexpectedEnd { offset: usize },
+    /// byte at the offset is not a type of datum
+    UnknownTag { offset: usize, tag: u8 },
+    /// value is packed with a layout newer than `FORMAT_VERSION`
+    UnsupportedVersion(u8),
+    /// string at the offset is not a valid utf-8
+    InvalidUtf8 { offset: usize },
+    /// bytes from the offset do not belong to any datum
+    TrailingBytes { offset: usize },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd { offset } => write!(f, "unexpected end of value at {}", offset),
+            DecodeError::UnknownTag { offset, tag } => write!(f, "unknown type tag {} at {}", tag, offset),
+            DecodeError::UnsupportedVersion(version) => write!(f, "unsupported format version {}", version),
+            DecodeError::InvalidUtf8 { offset } => write!(f, "invalid utf-8 string at {}", offset),
+            DecodeError::TrailingBytes { offset } => write!(f, "trailing bytes at {}", offset),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+macro_rules! read_ne {
+    ($reader:expr, $T:ty) => {{
+        let mut bytes = [0; std::mem::size_of::<$T>()];
+        bytes.copy_from_slice($reader.bytes(std::mem::size_of::<$T>())?);
+        <$T>::from_ne_bytes(bytes)
+    }};
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        match self.index.checked_add(len) {
+            Some(end) if end <= self.data.len() => {
+                let bytes = &self.data[self.index..end];
+                self.index = end;
+                Ok(bytes)
+            }
+            _ => Err(DecodeError::UnexpectedEnd { offset: self.index }),
+        }
+    }
+
+    fn tag(&mut self) -> Result<TypeTag, DecodeError> {
+        let offset = self.index;
+        let tag = match self.bytes(1)?[0] {
+            0 => TypeTag::Null,
+            1 => TypeTag::True,
+            2 => TypeTag::False,
+            3 => TypeTag::I16,
+            4 => TypeTag::I32,
+            5 => TypeTag::I64,
+            6 => TypeTag::F32,
+            7 => TypeTag::F64,
+            8 => TypeTag::Str,
+            tag => return Err(DecodeError::UnknownTag { offset, tag }),
+        };
+        Ok(tag)
+    }
+
+    fn fixed(&mut self, tag: TypeTag) -> Result<Datum<'a>, DecodeError> {
+        let datum = match tag {
+            TypeTag::Null => Datum::from_null(),
+            TypeTag::True => Datum::from_bool(true),
+            TypeTag::False => Datum::from_bool(false),
+            TypeTag::I16 => Datum::from_i16(read_ne!(self, i16)),
+            TypeTag::I32 => Datum::from_i32(read_ne!(self, i32)),
+            TypeTag::I64 => Datum::from_i64(read_ne!(self, i64)),
+            TypeTag::F32 => Datum::from_f32(read_ne!(self, f32)),
+            TypeTag::F64 => Datum::from_f64(read_ne!(self, f64)),
+            TypeTag::Str => unreachable!("strings are stored with their content"),
+        };
+        Ok(datum)
+    }
+
+    fn string(&mut self, len: usize) -> Result<Datum<'a>, DecodeError> {
+        let offset = self.index;
+        let bytes = self.bytes(len)?;
+        std::str::from_utf8(bytes)
+            .map(Datum::String)
+            .map_err(|_| DecodeError::InvalidUtf8 { offset })
+    }
+
+    fn finish(&self) -> Result<(), DecodeError> {
+        if self.index == self.data.len() {
+            Ok(())
+        } else {
+            Err(DecodeError::TrailingBytes { offset: self.index })
+        }
+    }
+}
+
+fn decode_raw(mut reader: Reader) -> Result<Vec<Datum>, DecodeError> {
+    let mut res = vec![];
+    while reader.index < reader.data.len() {
+        let datum = match reader.tag()? {
+            TypeTag::Str => {
+                let len = read_ne!(reader, usize);
+                reader.string(len)?
+            }
+            tag => reader.fixed(tag)?,
+        };
+        res.push(datum);
+    }
+    Ok(res)
+}
+
+fn decode_with_bitmap(data: &[u8]) -> Result<Vec<Datum>, DecodeError> {
+    let mut header = Reader { data, index: 2 };
+    let columns = read_ne!(header, usize);
+    let variable = read_ne!(header, usize);
+    let bitmap = header.bytes(bitmap_len(columns.min(usize::MAX - 7)))?;
+    let mut fixed = Reader {
+        data: data
+            .get(..variable)
+            .ok_or(DecodeError::UnexpectedEnd { offset: data.len() })?,
+        index: header.index,
+    };
+    let mut strings = Reader { data, index: variable };
+    let mut res = Vec::with_capacity(columns.min(bitmap.len() * 8));
+    for column in 0..columns {
+        if bitmap[column / 8] & (1 << (column % 8)) != 0 {
+            res.push(Datum::from_null());
+            continue;
+        }
+        let datum = match fixed.tag()? {
+            TypeTag::Str => {
+                let len = read_ne!(fixed, usize);
+                strings.string(len)?
+            }
+            tag => fixed.fixed(tag)?,
+        };
+        res.push(datum);
+    }
+    fixed.finish()?;
+    strings.finish()?;
+    Ok(res)
+}
+
+impl Binary {
+    /// unpacks datums like `Binary::unpack` does, but checks that bytes are
+    /// a valid packed value instead of trusting them. Should be used for
+    /// values that come from outside of the storage
+    pub fn try_unpack(&self) -> Result<Vec<Datum>, DecodeError> {
+        let data = self.to_bytes();
+        match self.format_version() {
+            0 => decode_raw(Reader { data, index: 0 }),
+            1 => decode_raw(Reader { data, index: 2 }),
+            FORMAT_VERSION => {
+                debug_assert_eq!(data[0], VERSION_MARKER);
+                decode_with_bitmap(data)
+            }
+            version => Err(DecodeError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// xorshift generator, so failures could be reproduced with the same seed
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+
+        fn datum(&mut self) -> Datum<'static> {
+            match self.below(8) {
+                0 => Datum::from_null(),
+                1 => Datum::from_bool(self.next() & 1 == 0),
+                2 => Datum::from_i16(self.next() as i16),
+                3 => Datum::from_i32(self.next() as i32),
+                4 => Datum::from_i64(self.next() as i64),
+                5 => Datum::from_f32(self.next() as i32 as f32 / 1000.0),
+                6 => Datum::from_f64(self.next() as i64 as f64 / 1000.0),
+                _ => {
+                    let len = self.below(10);
+                    Datum::from_string(
+                        (0..len)
+                            .map(|_| std::char::from_u32(self.below(0x1_0000) as u32).unwrap_or('?'))
+                            .collect(),
+                    )
+                }
+            }
+        }
+
+        fn row(&mut self) -> Vec<Datum<'static>> {
+            let len = self.below(20);
+            (0..len).map(|_| self.datum()).collect()
+        }
+    }
+
+    fn owned(datums: Vec<Datum>) -> Vec<Datum<'static>> {
+        datums
+            .into_iter()
+            .map(|datum| match datum {
+                Datum::String(val) => Datum::from_string(val.to_owned()),
+                Datum::OwnedString(val) => Datum::from_string(val),
+                Datum::Null => Datum::from_null(),
+                Datum::True => Datum::from_bool(true),
+                Datum::False => Datum::from_bool(false),
+                Datum::Int16(val) => Datum::from_i16(val),
+                Datum::Int32(val) => Datum::from_i32(val),
+                Datum::Int64(val) => Datum::from_i64(val),
+                Datum::Float32(val) => Datum::Float32(val),
+                Datum::Float64(val) => Datum::Float64(val),
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod round_trip {
+        use super::*;
+
+        #[test]
+        fn all_types() {
+            let datums = vec![
+                Datum::from_null(),
+                Datum::from_bool(true),
+                Datum::from_bool(false),
+                Datum::from_i16(-1),
+                Datum::from_i32(1_000),
+                Datum::from_i64(i64::MIN),
+                Datum::from_f32(0.5),
+                Datum::from_f64(-100.25),
+                Datum::from_str("hello"),
+                Datum::from_null(),
+            ];
+
+            assert_eq!(Binary::pack(&datums).try_unpack(), Ok(datums));
+        }
+
+        #[test]
+        fn empty_row() {
+            assert_eq!(Binary::pack(&[]).try_unpack(), Ok(vec![]));
+        }
+
+        #[test]
+        fn legacy_row() {
+            let mut data = vec![TypeTag::I16 as u8];
+            data.extend_from_slice(&7i16.to_ne_bytes());
+            data.push(TypeTag::Str as u8);
+            data.extend_from_slice(&2usize.to_ne_bytes());
+            data.extend_from_slice(b"ab");
+
+            assert_eq!(
+                Binary::with_data(data).try_unpack(),
+                Ok(vec![Datum::from_i16(7), Datum::from_str("ab")])
+            );
+        }
+
+        #[test]
+        fn random_rows() {
+            let mut random = Random(0x2545_f491_4f6c_dd1d);
+            for _ in 0..1_000 {
+                let row = random.row();
+                let packed = Binary::pack(&row);
+
+                assert_eq!(packed.try_unpack(), Ok(packed.unpack()));
+                assert_eq!(packed.try_unpack().map(owned), Ok(row));
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod malformed {
+        use super::*;
+
+        #[test]
+        fn truncated_value() {
+            let packed = Binary::pack(&[Datum::from_i64(1)]);
+            let truncated = packed.slice(0..packed.to_bytes().len() - 1);
+
+            assert!(matches!(truncated.try_unpack(), Err(DecodeError::UnexpectedEnd { .. })));
+        }
+
+        #[test]
+        fn unknown_tag() {
+            assert_eq!(
+                Binary::with_data(vec![TypeTag::True as u8, 42]).try_unpack(),
+                Err(DecodeError::UnknownTag { offset: 1, tag: 42 })
+            );
+        }
+
+        #[test]
+        fn newer_version() {
+            assert_eq!(
+                Binary::with_data(vec![VERSION_MARKER, FORMAT_VERSION + 1]).try_unpack(),
+                Err(DecodeError::UnsupportedVersion(FORMAT_VERSION + 1))
+            );
+        }
+
+        #[test]
+        fn invalid_utf8() {
+            let mut data = vec![TypeTag::Str as u8];
+            data.extend_from_slice(&2usize.to_ne_bytes());
+            data.extend_from_slice(&[0xC3, 0x28]);
+
+            assert_eq!(
+                Binary::with_data(data).try_unpack(),
+                Err(DecodeError::InvalidUtf8 {
+                    offset: 1 + std::mem::size_of::<usize>()
+                })
+            );
+        }
+
+        #[test]
+        fn trailing_bytes() {
+            let mut data = Binary::pack(&[Datum::from_str("a")]).to_bytes().to_vec();
+            data.push(0);
+
+            assert_eq!(
+                Binary::with_data(data.clone()).try_unpack(),
+                Err(DecodeError::TrailingBytes { offset: data.len() - 1 })
+            );
+        }
+
+        #[test]
+        fn huge_number_of_columns() {
+            let mut data = vec![VERSION_MARKER, FORMAT_VERSION];
+            data.extend_from_slice(&usize::MAX.to_ne_bytes());
+            data.extend_from_slice(&usize::MAX.to_ne_bytes());
+
+            assert!(matches!(
+                Binary::with_data(data).try_unpack(),
+                Err(DecodeError::UnexpectedEnd { .. })
+            ));
+        }
+
+        #[test]
+        fn random_corruptions_are_reported() {
+            let mut random = Random(0x9e37_79b9_7f4a_7c15);
+            for _ in 0..1_000 {
+                let mut data = Binary::pack(&random.row()).to_bytes().to_vec();
+                match random.below(3) {
+                    0 => data.truncate(random.below(data.len())),
+                    1 => {
+                        let index = random.below(data.len());
+                        data[index] = random.next() as u8;
+                    }
+                    _ => data.insert(random.below(data.len() + 1), random.next() as u8),
+                }
+
+                // either an error or datums, it must never panic
+                let _ = Binary::with_data(data).try_unpack();
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Binary;
+use repr::Datum;
+use std::ops::Deref;
+
+#[repr(u8)]
+enum KeyTag {
+    False = 1,
+    True,
+    Number,
+    Str,
+    Null,
+}
+
+const STR_ESCAPE: u8 = 0xFF;
+const STR_TERMINATOR: [u8; 2] = [0x00, 0x00];
+
+/// integers and floats are packed into the same space: the closest `f64`
+/// followed by the difference of the number from it. Rounding to `f64` keeps
+/// order of integers, the difference orders ones that are rounded to the same
+/// float, so equal numbers are packed the same way whatever their types are
+fn push_number(data: &mut Vec<u8>, approx: f64, diff: i64) {
+    let bits = approx.to_bits();
+    let bits = if bits >> 63 == 1 { !bits } else { bits ^ (1 << 63) };
+    data.push(KeyTag::Number as u8);
+    data.extend_from_slice(&bits.to_be_bytes());
+    data.extend_from_slice(&((diff as u64) ^ (1 << 63)).to_be_bytes());
+}
+
+fn push_int(data: &mut Vec<u8>, val: i64) {
+    let approx = val as f64;
+    push_number(data, approx, (val as i128 - approx as i128) as i64);
+}
+
+fn push_float(data: &mut Vec<u8>, val: f64) {
+    // `-0.0` is equal to `0.0` and all NaNs are equal to each other and greater
+    // than any other number as in PostgreSQL
+    let val = if val == 0.0 {
+        0.0
+    } else if val.is_nan() {
+        f64::NAN
+    } else {
+        val
+    };
+    push_number(data, val, 0);
+}
+
+fn read_number(data: &[u8], idx: &mut usize) -> Datum<'static> {
+    let bits = read_u64(data, idx);
+    let bits = if bits >> 63 == 1 { bits ^ (1 << 63) } else { !bits };
+    let approx = f64::from_bits(bits);
+    let diff = (read_u64(data, idx) ^ (1 << 63)) as i64;
+    if approx.fract() == 0.0 && approx.abs() <= i64::MAX as f64 {
+        let val = approx as i128 + diff as i128;
+        if val >= i64::MIN as i128 && val <= i64::MAX as i128 {
+            return Datum::from_i64(val as i64);
+        }
+    }
+    Datum::from_f64(approx)
+}
+
+fn push_str(data: &mut Vec<u8>, val: &str) {
+    data.push(KeyTag::Str as u8);
+    for byte in val.as_bytes() {
+        data.push(*byte);
+        if *byte == 0x00 {
+            data.push(STR_ESCAPE);
+        }
+    }
+    data.extend_from_slice(&STR_TERMINATOR);
+}
+
+fn read_u64(data: &[u8], idx: &mut usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&data[*idx..*idx + 8]);
+    *idx += 8;
+    u64::from_be_bytes(bytes)
+}
+
+fn read_str(data: &[u8], idx: &mut usize) -> String {
+    let mut bytes = vec![];
+    while data[*idx..*idx + 2] != STR_TERMINATOR {
+        bytes.push(data[*idx]);
+        if data[*idx] == 0x00 {
+            *idx += 1;
+        }
+        *idx += 1;
+    }
+    *idx += STR_TERMINATOR.len();
+    String::from_utf8(bytes).expect("keys are packed from valid utf-8 strings")
+}
+
+impl Binary {
+    /// packs datums into a key, byte-wise order of keys is the same as order
+    /// of packed values compared one by one. Keys packed from a prefix of
+    /// values are prefixes of keys packed from all of them, so composite keys
+    /// can be range scanned by their leading columns.
+    ///
+    /// Integers and floats of any width are compared as numbers, `NULL` is
+    /// greater than any other value.
+    pub fn pack_key<'a>(datums: &[Datum<'a>]) -> Binary {
+        let mut data = Vec::with_capacity(datums.iter().map(Datum::size).sum());
+        for datum in datums {
+            match datum {
+                Datum::<'a>::False => data.push(KeyTag::False as u8),
+                Datum::<'a>::True => data.push(KeyTag::True as u8),
+                Datum::<'a>::Int16(val) => push_int(&mut data, *val as i64),
+                Datum::<'a>::Int32(val) => push_int(&mut data, *val as i64),
+                Datum::<'a>::Int64(val) => push_int(&mut data, *val),
+                Datum::<'a>::Float32(val) => push_float(&mut data, *val.deref() as f64),
+                Datum::<'a>::Float64(val) => push_float(&mut data, *val.deref()),
+                Datum::<'a>::String(val) => push_str(&mut data, val),
+                Datum::<'a>::OwnedString(val) => push_str(&mut data, val.as_str()),
+                Datum::<'a>::Null => data.push(KeyTag::Null as u8),
+            }
+        }
+        Binary::with_data(data)
+    }
+
+    /// unpacks datums from a key created by `Binary::pack_key`. Numbers that
+    /// are integers fitting `i64` are unpacked as `Datum::Int64`, other ones as
+    /// `Datum::Float64`
+    pub fn unpack_key(&self) -> Vec<Datum<'static>> {
+        let data = self.to_bytes();
+        let mut index = 0;
+        let mut res = vec![];
+        while index < data.len() {
+            let tag = data[index];
+            index += 1;
+            let datum = match tag {
+                tag if tag == KeyTag::False as u8 => Datum::from_bool(false),
+                tag if tag == KeyTag::True as u8 => Datum::from_bool(true),
+                tag if tag == KeyTag::Number as u8 => read_number(data, &mut index),
+                tag if tag == KeyTag::Str as u8 => Datum::from_string(read_str(data, &mut index)),
+                tag if tag == KeyTag::Null as u8 => Datum::from_null(),
+                tag => unreachable!("unknown key tag {}", tag),
+            };
+            res.push(datum);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_ordered(datums: Vec<Vec<Datum>>) {
+        let keys = datums.iter().map(|key| Binary::pack_key(key)).collect::<Vec<Binary>>();
+        for pair in keys.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} should be less than {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[cfg(test)]
+    mod single_value {
+        use super::*;
+
+        #[test]
+        fn integers() {
+            assert_ordered(vec![
+                vec![Datum::from_i64(i64::MIN)],
+                vec![Datum::from_i32(-300)],
+                vec![Datum::from_i16(-1)],
+                vec![Datum::from_i64(0)],
+                vec![Datum::from_i16(1)],
+                vec![Datum::from_i32(256)],
+                vec![Datum::from_i64(i64::MAX)],
+            ]);
+        }
+
+        #[test]
+        fn integers_of_different_width() {
+            assert_eq!(
+                Binary::pack_key(&[Datum::from_i16(10)]),
+                Binary::pack_key(&[Datum::from_i64(10)])
+            );
+        }
+
+        #[test]
+        fn floats() {
+            assert_ordered(vec![
+                vec![Datum::from_f64(f64::NEG_INFINITY)],
+                vec![Datum::from_f64(-100.5)],
+                vec![Datum::from_f32(-0.25)],
+                vec![Datum::from_f64(0.0)],
+                vec![Datum::from_f32(0.25)],
+                vec![Datum::from_f64(100.5)],
+                vec![Datum::from_f64(f64::INFINITY)],
+            ]);
+        }
+
+        #[test]
+        fn integers_and_floats() {
+            assert_ordered(vec![
+                vec![Datum::from_f64(f64::NEG_INFINITY)],
+                vec![Datum::from_i64(i64::MIN)],
+                vec![Datum::from_f64(-1.5)],
+                vec![Datum::from_i16(-1)],
+                vec![Datum::from_f32(0.5)],
+                vec![Datum::from_i32(1)],
+                vec![Datum::from_i64(i64::MAX - 1)],
+                vec![Datum::from_i64(i64::MAX)],
+                vec![Datum::from_f64(i64::MAX as f64)],
+                vec![Datum::from_f64(f64::INFINITY)],
+                vec![Datum::from_f64(f64::NAN)],
+            ]);
+        }
+
+        #[test]
+        fn equal_numbers_of_different_types() {
+            assert_eq!(
+                Binary::pack_key(&[Datum::from_i32(10)]),
+                Binary::pack_key(&[Datum::from_f64(10.0)])
+            );
+            assert_eq!(
+                Binary::pack_key(&[Datum::from_f64(-0.0)]),
+                Binary::pack_key(&[Datum::from_i16(0)])
+            );
+        }
+
+        #[test]
+        fn strings() {
+            assert_ordered(vec![
+                vec![Datum::from_str("")],
+                vec![Datum::from_str("a")],
+                vec![Datum::from_str("a\u{0}")],
+                vec![Datum::from_str("aa")],
+                vec![Datum::from_str("b")],
+            ]);
+        }
+
+        #[test]
+        fn booleans_and_null() {
+            assert_ordered(vec![
+                vec![Datum::from_bool(false)],
+                vec![Datum::from_bool(true)],
+                vec![Datum::from_null()],
+            ]);
+        }
+    }
+
+    #[cfg(test)]
+    mod pack_unpack {
+        use super::*;
+
+        #[test]
+        fn all_types() {
+            let key = Binary::pack_key(&[
+                Datum::from_bool(false),
+                Datum::from_bool(true),
+                Datum::from_i16(-10),
+                Datum::from_i64(i64::MAX),
+                Datum::from_f32(-0.5),
+                Datum::from_f64(100.25),
+                Datum::from_f64(f64::INFINITY),
+                Datum::from_i64(i64::MIN),
+                Datum::from_str("a\u{0}b"),
+                Datum::from_null(),
+            ]);
+
+            assert_eq!(
+                key.unpack_key(),
+                vec![
+                    Datum::from_bool(false),
+                    Datum::from_bool(true),
+                    Datum::from_i64(-10),
+                    Datum::from_i64(i64::MAX),
+                    Datum::from_f64(-0.5),
+                    Datum::from_f64(100.25),
+                    Datum::from_f64(f64::INFINITY),
+                    Datum::from_i64(i64::MIN),
+                    Datum::from_string("a\u{0}b".to_owned()),
+                    Datum::from_null(),
+                ]
+            );
+        }
+
+        #[test]
+        fn sorted_by_value_in_btree_map() {
+            use std::collections::BTreeMap;
+
+            let values = [300, -1, 256, 0, -300, 1, 255];
+            let map = values
+                .iter()
+                .map(|val| (Binary::pack_key(&[Datum::from_i64(*val)]), *val))
+                .collect::<BTreeMap<Binary, i64>>();
+
+            assert_eq!(
+                map.keys().map(Binary::unpack_key).collect::<Vec<Vec<Datum>>>(),
+                vec![-300, -1, 0, 1, 255, 256, 300]
+                    .into_iter()
+                    .map(|val| vec![Datum::from_i64(val)])
+                    .collect::<Vec<Vec<Datum>>>()
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod composite {
+        use super::*;
+
+        #[test]
+        fn ordered_by_leading_value_first() {
+            assert_ordered(vec![
+                vec![Datum::from_i32(-1), Datum::from_str("z")],
+                vec![Datum::from_i32(1), Datum::from_str("a")],
+                vec![Datum::from_i32(1), Datum::from_str("b")],
+                vec![Datum::from_i32(2), Datum::from_str("a")],
+            ]);
+        }
+
+        #[test]
+        fn strings_do_not_leak_into_next_value() {
+            assert_ordered(vec![
+                vec![Datum::from_str("a"), Datum::from_str("b")],
+                vec![Datum::from_str("ab"), Datum::from_str("a")],
+            ]);
+        }
+
+        #[test]
+        fn prefix() {
+            let prefix = Binary::pack_key(&[Datum::from_i32(1)]);
+
+            assert!(Binary::pack_key(&[Datum::from_i32(1), Datum::from_str("a")]).start_with(&prefix));
+            assert!(!Binary::pack_key(&[Datum::from_i32(2), Datum::from_str("a")]).start_with(&prefix));
+        }
+    }
+}
@@ -13,7 +13,18 @@
 // limitations under the License.
 
 use repr::Datum;
-use std::io;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Formatter},
+    io,
+    ops::Range,
+    sync::Arc,
+};
+
+mod decode;
+mod key;
+
+pub use decode::DecodeError;
 
 pub type Row = (Key, Values);
 pub type Key = Binary;
@@ -26,10 +37,19 @@ pub enum StorageError {
     Io,
     CascadeIo(Vec<String>),
     Storage,
+    Corrupted(Key),
 }
 
+/// version of the layout written by `Binary::pack`. Rows written before
+/// versioning was introduced have no header and are treated as version `0`
+pub const FORMAT_VERSION: u8 = 2;
+const VERSION_MARKER: u8 = 0xFF;
+
 #[repr(u8)]
 enum TypeTag {
+    // NULLs are kept in a bitmap since `FORMAT_VERSION` 2, the tag is only
+    // read from rows packed with older versions
+    #[allow(dead_code)]
     Null = 0,
     True,
     False,
@@ -73,11 +93,35 @@ fn read_tag(data: &[u8], idx: &mut usize) -> TypeTag {
     unsafe { read::<TypeTag>(data, idx) }
 }
 
+fn read_fixed<'a>(tag: TypeTag, data: &[u8], idx: &mut usize) -> Datum<'a> {
+    match tag {
+        TypeTag::Null => Datum::from_null(),
+        TypeTag::True => Datum::from_bool(true),
+        TypeTag::False => Datum::from_bool(false),
+        TypeTag::I16 => Datum::from_i16(unsafe { read::<i16>(data, idx) }),
+        TypeTag::I32 => Datum::from_i32(unsafe { read::<i32>(data, idx) }),
+        TypeTag::I64 => Datum::from_i64(unsafe { read::<i64>(data, idx) }),
+        TypeTag::F32 => Datum::from_f32(unsafe { read::<f32>(data, idx) }),
+        TypeTag::F64 => Datum::from_f64(unsafe { read::<f64>(data, idx) }),
+        TypeTag::Str => unreachable!("strings are stored with their content"),
+    }
+}
+
+fn bitmap_len(columns: usize) -> usize {
+    (columns + 7) / 8
+}
+
 /// in-memory runtime representation of a table row. It is unable to deserialize
 /// the row without knowing the types of each column, which makes this unsafe
 /// however it is more memory efficient.
-#[derive(Debug, Clone, PartialEq, Eq, Default, PartialOrd, Ord)]
-pub struct Binary(Vec<u8>);
+///
+/// Bytes are kept in a shared buffer, clones and slices of a value point to
+/// the same buffer instead of copying it.
+#[derive(Clone)]
+pub struct Binary {
+    buffer: Arc<[u8]>,
+    range: Range<usize>,
+}
 
 impl Binary {
     pub fn new() -> Binary {
@@ -86,68 +130,171 @@ impl Binary {
 
     #[allow(clippy::wrong_self_convention)]
     pub fn to_bytes(&self) -> &[u8] {
-        self.0.as_slice()
+        &self.buffer[self.range.clone()]
     }
 
     pub fn with_data(data: Vec<u8>) -> Binary {
-        Binary(data)
+        let len = data.len();
+        Binary {
+            buffer: Arc::from(data),
+            range: 0..len,
+        }
     }
 
+    /// part of the value that shares its buffer
+    pub fn slice(&self, range: Range<usize>) -> Binary {
+        assert!(
+            range.start <= range.end && range.end <= self.range.len(),
+            "range {:?} is out of value bounds {}",
+            range,
+            self.range.len()
+        );
+        Binary {
+            buffer: self.buffer.clone(),
+            range: self.range.start + range.start..self.range.start + range.end,
+        }
+    }
+
+    /// packs datums as a null bitmap followed by a section of fixed size
+    /// values and a section of string contents. `NULL`s take no space besides
+    /// their bit in the bitmap
     pub fn pack<'a>(other: &[Datum<'a>]) -> Binary {
         use std::ops::Deref;
-        let size = other.iter().map(Datum::size).sum();
-        let mut data = Vec::with_capacity(size);
-        for datum in other {
+        let mut bitmap = vec![0; bitmap_len(other.len())];
+        let mut fixed = Vec::with_capacity(other.iter().map(Datum::size).sum());
+        let mut variable = vec![];
+        for (column, datum) in other.iter().enumerate() {
             match datum {
                 Datum::<'a>::True => {
-                    push_tag(&mut data, TypeTag::True);
+                    push_tag(&mut fixed, TypeTag::True);
                 }
                 Datum::<'a>::False => {
-                    push_tag(&mut data, TypeTag::False);
+                    push_tag(&mut fixed, TypeTag::False);
                 }
                 Datum::<'a>::Int16(val) => {
-                    push_tag(&mut data, TypeTag::I16);
-                    push_copy!(&mut data, *val, i16);
+                    push_tag(&mut fixed, TypeTag::I16);
+                    push_copy!(&mut fixed, *val, i16);
                 }
                 Datum::<'a>::Int32(val) => {
-                    push_tag(&mut data, TypeTag::I32);
-                    push_copy!(&mut data, *val, i32);
+                    push_tag(&mut fixed, TypeTag::I32);
+                    push_copy!(&mut fixed, *val, i32);
                 }
                 Datum::<'a>::Int64(val) => {
-                    push_tag(&mut data, TypeTag::I64);
-                    push_copy!(&mut data, *val, i64);
+                    push_tag(&mut fixed, TypeTag::I64);
+                    push_copy!(&mut fixed, *val, i64);
                 }
                 Datum::<'a>::Float32(val) => {
-                    push_tag(&mut data, TypeTag::F32);
-                    push_copy!(&mut data, *val.deref(), f32)
+                    push_tag(&mut fixed, TypeTag::F32);
+                    push_copy!(&mut fixed, *val.deref(), f32)
                 }
                 Datum::<'a>::Float64(val) => {
-                    push_tag(&mut data, TypeTag::F64);
-                    push_copy!(&mut data, *val.deref(), f64)
+                    push_tag(&mut fixed, TypeTag::F64);
+                    push_copy!(&mut fixed, *val.deref(), f64)
                 }
                 Datum::<'a>::String(val) => {
-                    push_tag(&mut data, TypeTag::Str);
-                    push_copy!(&mut data, val.len(), usize);
-                    data.extend_from_slice(val.as_bytes());
+                    push_tag(&mut fixed, TypeTag::Str);
+                    push_copy!(&mut fixed, val.len(), usize);
+                    variable.extend_from_slice(val.as_bytes());
                 }
                 Datum::<'a>::OwnedString(val) => {
-                    push_tag(&mut data, TypeTag::Str);
-                    push_copy!(&mut data, val.len(), usize);
-                    data.extend_from_slice(val.as_bytes());
+                    push_tag(&mut fixed, TypeTag::Str);
+                    push_copy!(&mut fixed, val.len(), usize);
+                    variable.extend_from_slice(val.as_bytes());
                 }
-                Datum::<'a>::Null => push_tag(&mut data, TypeTag::Null),
+                Datum::<'a>::Null => bitmap[column / 8] |= 1 << (column % 8),
             }
         }
 
-        Self(data)
+        let header_size = 2 + 2 * std::mem::size_of::<usize>();
+        let mut data = Vec::with_capacity(header_size + bitmap.len() + fixed.len() + variable.len());
+        data.push(VERSION_MARKER);
+        data.push(FORMAT_VERSION);
+        push_copy!(&mut data, other.len(), usize);
+        push_copy!(&mut data, header_size + bitmap.len() + fixed.len(), usize);
+        data.extend_from_slice(&bitmap);
+        data.extend_from_slice(&fixed);
+        data.extend_from_slice(&variable);
+        Self::with_data(data)
     }
 
     pub fn unpack(&self) -> Vec<Datum> {
-        unpack_raw(self.0.as_slice())
+        match self.format_version() {
+            0 => unpack_raw(self.to_bytes()),
+            1 => unpack_raw(&self.to_bytes()[2..]),
+            _ => unpack_with_bitmap(self.to_bytes()),
+        }
+    }
+
+    /// unpacks datums of the given columns in the given order, strings of
+    /// other columns are skipped without being read
+    pub fn unpack_columns(&self, columns: &[usize]) -> Vec<Datum> {
+        match self.format_version() {
+            0 | 1 => {
+                let datums = self.unpack();
+                columns.iter().map(|column| datums[*column].clone()).collect()
+            }
+            _ => unpack_columns_with_bitmap(self.to_bytes(), columns),
+        }
+    }
+
+    /// checks whether leading datums of the row are the ones packed into
+    /// `prefix`, unlike `start_with` it does not depend on the packing layout
+    pub fn start_with_row(&self, prefix: &Binary) -> bool {
+        self.unpack().starts_with(&prefix.unpack())
+    }
+
+    /// version of the layout that datums were packed with
+    pub fn format_version(&self) -> u8 {
+        match self.to_bytes() {
+            [VERSION_MARKER, version, ..] => *version,
+            _ => 0,
+        }
+    }
+
+    /// repacks datums with the current `FORMAT_VERSION`, `None` if they are
+    /// already packed with it
+    pub fn upgrade(&self) -> Option<Binary> {
+        if self.format_version() < FORMAT_VERSION {
+            Some(Binary::pack(&self.unpack()))
+        } else {
+            None
+        }
     }
 
     pub fn start_with(&self, other: &Binary) -> bool {
-        self.0.starts_with(&other.0)
+        self.to_bytes().starts_with(other.to_bytes())
+    }
+}
+
+impl Default for Binary {
+    fn default() -> Binary {
+        Binary::with_data(vec![])
+    }
+}
+
+impl Debug for Binary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Binary").field(&self.to_bytes()).finish()
+    }
+}
+
+impl PartialEq for Binary {
+    fn eq(&self, other: &Binary) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for Binary {}
+
+impl PartialOrd for Binary {
+    fn partial_cmp(&self, other: &Binary) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Binary {
+    fn cmp(&self, other: &Binary) -> Ordering {
+        self.to_bytes().cmp(other.to_bytes())
     }
 }
 
@@ -155,41 +302,75 @@ fn unpack_raw(data: &[u8]) -> Vec<Datum> {
     let mut index = 0;
     let mut res = Vec::new();
     while index < data.len() {
-        let tag = read_tag(data, &mut index);
-        let datum = match tag {
-            TypeTag::Null => Datum::from_null(),
-            TypeTag::True => Datum::from_bool(true),
-            TypeTag::False => Datum::from_bool(false),
+        let datum = match read_tag(data, &mut index) {
             TypeTag::Str => {
                 let val = unsafe { read_string(data, &mut index) };
                 Datum::String(val)
             }
-            TypeTag::I16 => {
-                let val = unsafe { read::<i16>(data, &mut index) };
-                Datum::from_i16(val)
-            }
-            TypeTag::I32 => {
-                let val = unsafe { read::<i32>(data, &mut index) };
-                Datum::from_i32(val)
-            }
-            TypeTag::I64 => {
-                let val = unsafe { read::<i64>(data, &mut index) };
-                Datum::from_i64(val)
-            }
-            TypeTag::F32 => {
-                let val = unsafe { read::<f32>(data, &mut index) };
-                Datum::from_f32(val)
-            }
-            TypeTag::F64 => {
-                let val = unsafe { read::<f64>(data, &mut index) };
-                Datum::from_f64(val)
+            tag => read_fixed(tag, data, &mut index),
+        };
+        res.push(datum)
+    }
+    res
+}
+
+fn unpack_with_bitmap(data: &[u8]) -> Vec<Datum> {
+    let mut index = 2;
+    let columns = unsafe { read::<usize>(data, &mut index) };
+    let mut variable = unsafe { read::<usize>(data, &mut index) };
+    let bitmap = &data[index..index + bitmap_len(columns)];
+    index += bitmap.len();
+    let mut res = Vec::with_capacity(columns);
+    for column in 0..columns {
+        if bitmap[column / 8] & (1 << (column % 8)) != 0 {
+            res.push(Datum::from_null());
+            continue;
+        }
+        let datum = match read_tag(data, &mut index) {
+            TypeTag::Str => {
+                let len = unsafe { read::<usize>(data, &mut index) };
+                let val = unsafe { std::str::from_utf8_unchecked(&data[variable..variable + len]) };
+                variable += len;
+                Datum::String(val)
             }
+            tag => read_fixed(tag, data, &mut index),
         };
         res.push(datum)
     }
     res
 }
 
+fn unpack_columns_with_bitmap<'a>(data: &'a [u8], columns: &[usize]) -> Vec<Datum<'a>> {
+    let mut index = 2;
+    let count = unsafe { read::<usize>(data, &mut index) };
+    let mut variable = unsafe { read::<usize>(data, &mut index) };
+    let bitmap = &data[index..index + bitmap_len(count)];
+    index += bitmap.len();
+    let mut res = vec![Datum::from_null(); columns.len()];
+    let last = columns.iter().max().map(|column| column + 1).unwrap_or(0);
+    for column in 0..last {
+        if bitmap[column / 8] & (1 << (column % 8)) != 0 {
+            continue;
+        }
+        let datum = match read_tag(data, &mut index) {
+            TypeTag::Str => {
+                let len = unsafe { read::<usize>(data, &mut index) };
+                let start = variable;
+                variable += len;
+                if !columns.contains(&column) {
+                    continue;
+                }
+                Datum::String(unsafe { std::str::from_utf8_unchecked(&data[start..variable]) })
+            }
+            tag => read_fixed(tag, data, &mut index),
+        };
+        for (position, _) in columns.iter().enumerate().filter(|(_, c)| **c == column) {
+            res[position] = datum.clone();
+        }
+    }
+    res
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,4 +421,215 @@ mod tests {
             assert_eq!(vec![Datum::from_str("string"), Datum::from_str("hello")], row.unpack());
         }
     }
+
+    #[cfg(test)]
+    mod format_version {
+        use super::*;
+
+        fn legacy(datums: &[Datum]) -> Vec<u8> {
+            let mut data = vec![];
+            for datum in datums {
+                match datum {
+                    Datum::Null => push_tag(&mut data, TypeTag::Null),
+                    Datum::True => push_tag(&mut data, TypeTag::True),
+                    Datum::False => push_tag(&mut data, TypeTag::False),
+                    Datum::Int32(val) => {
+                        push_tag(&mut data, TypeTag::I32);
+                        data.extend_from_slice(&val.to_ne_bytes());
+                    }
+                    Datum::Int64(val) => {
+                        push_tag(&mut data, TypeTag::I64);
+                        data.extend_from_slice(&val.to_ne_bytes());
+                    }
+                    Datum::String(val) => {
+                        push_tag(&mut data, TypeTag::Str);
+                        data.extend_from_slice(&val.len().to_ne_bytes());
+                        data.extend_from_slice(val.as_bytes());
+                    }
+                    _ => unimplemented!(),
+                }
+            }
+            data
+        }
+
+        #[test]
+        fn packed_with_current_version() {
+            assert_eq!(Binary::pack(&[Datum::from_i32(1)]).format_version(), FORMAT_VERSION);
+        }
+
+        #[test]
+        fn unpack_legacy_row() {
+            let data = vec![Datum::from_i32(1), Datum::from_str("string"), Datum::from_null()];
+            let row = Binary::with_data(legacy(&data));
+
+            assert_eq!(row.format_version(), 0);
+            assert_eq!(row.unpack(), data);
+        }
+
+        #[test]
+        fn unpack_row_without_bitmap() {
+            let data = vec![Datum::from_str("string"), Datum::from_null(), Datum::from_i64(10)];
+            let row = Binary::with_data([vec![VERSION_MARKER, 1], legacy(&data)].concat());
+
+            assert_eq!(row.format_version(), 1);
+            assert_eq!(row.unpack(), data);
+        }
+
+        #[test]
+        fn upgrade_legacy_row() {
+            let data = vec![Datum::from_bool(true), Datum::from_i64(100)];
+
+            assert_eq!(Binary::with_data(legacy(&data)).upgrade(), Some(Binary::pack(&data)));
+        }
+
+        #[test]
+        fn current_row_is_not_upgraded() {
+            assert_eq!(Binary::pack(&[Datum::from_i16(1)]).upgrade(), None);
+        }
+
+        #[test]
+        fn prefix_of_packed_row() {
+            let prefix = Binary::pack(&[Datum::from_str("schema")]);
+
+            assert!(Binary::pack(&[Datum::from_str("schema"), Datum::from_str("table")]).start_with_row(&prefix));
+            assert!(!Binary::pack(&[Datum::from_str("other"), Datum::from_str("table")]).start_with_row(&prefix));
+        }
+    }
+
+    #[cfg(test)]
+    mod null_bitmap {
+        use super::*;
+
+        #[test]
+        fn nulls_take_no_space_besides_bitmap() {
+            let row = Binary::pack(&vec![Datum::from_null(); 9]);
+
+            assert_eq!(row.to_bytes().len(), 2 + 2 * std::mem::size_of::<usize>() + 2);
+            assert_eq!(row.unpack(), vec![Datum::from_null(); 9]);
+        }
+
+        #[test]
+        fn nulls_between_values() {
+            let data = vec![
+                Datum::from_null(),
+                Datum::from_str("first"),
+                Datum::from_i16(1),
+                Datum::from_null(),
+                Datum::from_string("second".to_owned()),
+                Datum::from_f64(0.5),
+                Datum::from_null(),
+                Datum::from_bool(false),
+                Datum::from_null(),
+                Datum::from_str(""),
+            ];
+            let row = Binary::pack(&data);
+
+            assert_eq!(
+                row.unpack(),
+                vec![
+                    Datum::from_null(),
+                    Datum::from_str("first"),
+                    Datum::from_i16(1),
+                    Datum::from_null(),
+                    Datum::from_str("second"),
+                    Datum::from_f64(0.5),
+                    Datum::from_null(),
+                    Datum::from_bool(false),
+                    Datum::from_null(),
+                    Datum::from_str(""),
+                ]
+            );
+        }
+
+        #[test]
+        fn empty_row() {
+            assert_eq!(Binary::pack(&[]).unpack(), vec![]);
+        }
+    }
+
+    #[cfg(test)]
+    mod unpack_columns {
+        use super::*;
+
+        fn row() -> Vec<Datum<'static>> {
+            vec![
+                Datum::from_str("first"),
+                Datum::from_null(),
+                Datum::from_i32(1),
+                Datum::from_str("second"),
+                Datum::from_bool(true),
+            ]
+        }
+
+        #[test]
+        fn selected_columns_in_given_order() {
+            assert_eq!(
+                Binary::pack(&row()).unpack_columns(&[3, 0, 2]),
+                vec![Datum::from_str("second"), Datum::from_str("first"), Datum::from_i32(1)]
+            );
+        }
+
+        #[test]
+        fn null_and_repeated_columns() {
+            assert_eq!(
+                Binary::pack(&row()).unpack_columns(&[1, 4, 4]),
+                vec![Datum::from_null(), Datum::from_bool(true), Datum::from_bool(true)]
+            );
+        }
+
+        #[test]
+        fn no_columns() {
+            assert_eq!(Binary::pack(&row()).unpack_columns(&[]), vec![]);
+        }
+
+        #[test]
+        fn legacy_row() {
+            let mut data = vec![VERSION_MARKER, 1];
+            data.extend_from_slice(&[TypeTag::I32 as u8]);
+            data.extend_from_slice(&5i32.to_ne_bytes());
+            data.extend_from_slice(&[TypeTag::True as u8]);
+
+            assert_eq!(
+                Binary::with_data(data).unpack_columns(&[1]),
+                vec![Datum::from_bool(true)]
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod shared_buffer {
+        use super::*;
+
+        #[test]
+        fn slice_shares_buffer() {
+            let value = Binary::with_data(vec![1, 2, 3, 4, 5]);
+            let slice = value.slice(1..4);
+
+            assert_eq!(slice.to_bytes(), &[2, 3, 4]);
+            assert!(Arc::ptr_eq(&value.buffer, &slice.buffer));
+        }
+
+        #[test]
+        fn slice_of_slice() {
+            let value = Binary::with_data(vec![1, 2, 3, 4, 5]);
+
+            assert_eq!(value.slice(1..5).slice(1..3).to_bytes(), &[3, 4]);
+        }
+
+        #[test]
+        fn compared_by_content() {
+            let value = Binary::with_data(vec![0, 1, 2, 1, 2]);
+
+            assert_eq!(value.slice(1..3), value.slice(3..5));
+            assert_eq!(value.slice(1..3), Binary::with_data(vec![1, 2]));
+            assert!(value.slice(0..2) < value.slice(1..3));
+            assert_eq!(value.slice(2..2), Binary::new());
+        }
+
+        #[test]
+        #[should_panic]
+        fn slice_out_of_bounds() {
+            Binary::with_data(vec![1, 2, 3]).slice(1..3).slice(0..3);
+        }
+    }
 }
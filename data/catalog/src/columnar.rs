@@ -0,0 +1,339 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{outdated_rows, Cursor, DataTable, Key, Value, WriteOp};
+use binary::Binary;
+use repr::Datum;
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+#[derive(Default, Debug)]
+struct ColumnChunks {
+    keys: Vec<Key>,
+    columns: Vec<Vec<Binary>>,
+}
+
+impl ColumnChunks {
+    fn position(&self, key: &Key) -> Option<usize> {
+        self.keys.binary_search(key).ok()
+    }
+
+    fn lower_bound(&self, key: &Key) -> usize {
+        match self.keys.binary_search(key) {
+            Ok(position) | Err(position) => position,
+        }
+    }
+
+    fn row(&self, position: usize) -> Value {
+        self.project(position, &(0..self.columns.len()).collect::<Vec<usize>>())
+    }
+
+    fn project(&self, position: usize, columns: &[usize]) -> Value {
+        let cells = columns
+            .iter()
+            .map(|column| self.columns[*column][position].unpack())
+            .collect::<Vec<Vec<Datum>>>();
+        Binary::pack(&cells.into_iter().flatten().collect::<Vec<Datum>>())
+    }
+
+    fn cells(&mut self, value: &Value) -> Vec<Binary> {
+        let datums = value.unpack();
+        while self.columns.len() < datums.len() {
+            self.columns
+                .push(vec![Binary::pack(&[Datum::from_null()]); self.keys.len()]);
+        }
+        (0..self.columns.len())
+            .map(|column| match datums.get(column) {
+                Some(datum) => Binary::pack(std::slice::from_ref(datum)),
+                None => Binary::pack(&[Datum::from_null()]),
+            })
+            .collect()
+    }
+
+    fn push(&mut self, key: Key, value: &Value) {
+        let cells = self.cells(value);
+        for (chunk, cell) in self.columns.iter_mut().zip(cells) {
+            chunk.push(cell);
+        }
+        self.keys.push(key);
+    }
+
+    fn replace(&mut self, position: usize, value: &Value) {
+        let cells = self.cells(value);
+        for (chunk, cell) in self.columns.iter_mut().zip(cells) {
+            chunk[position] = cell;
+        }
+    }
+
+    fn remove(&mut self, position: usize) {
+        self.keys.remove(position);
+        for cells in self.columns.iter_mut() {
+            cells.remove(position);
+        }
+    }
+
+    fn rows(&self, positions: Range<usize>) -> Cursor {
+        positions
+            .map(|position| (self.keys[position].clone(), self.row(position)))
+            .collect::<Cursor>()
+    }
+}
+
+// TODO: tables are always created as row oriented InMemoryTableHandle,
+//       sqlparser does not support `CREATE TABLE ... USING columnar` to choose
+//       this storage for analytical workloads
+/// table that keeps values of each column contiguously in a column chunk, so
+/// scans of a few columns don't touch values of the others
+#[derive(Default, Debug)]
+pub struct ColumnarTableHandle {
+    chunks: RwLock<ColumnChunks>,
+    record_ids: AtomicU64,
+    column_ords: AtomicU64,
+}
+
+impl ColumnarTableHandle {
+    /// scans only values of the `columns` packed in the specified order
+    pub fn scan_columns(&self, columns: &[usize]) -> Cursor {
+        let chunks = self.chunks.read().unwrap();
+        (0..chunks.keys.len())
+            .map(|position| (chunks.keys[position].clone(), chunks.project(position, columns)))
+            .collect::<Cursor>()
+    }
+}
+
+impl DataTable for ColumnarTableHandle {
+    fn select(&self) -> Cursor {
+        let chunks = self.chunks.read().unwrap();
+        chunks.rows(0..chunks.keys.len())
+    }
+
+    fn scan_range(&self, range: Range<Key>) -> Cursor {
+        let chunks = self.chunks.read().unwrap();
+        let start = chunks.lower_bound(&range.start);
+        let end = chunks.lower_bound(&range.end);
+        chunks.rows(start..end.max(start))
+    }
+
+    fn scan_prefix(&self, prefix: &Key) -> Cursor {
+        let chunks = self.chunks.read().unwrap();
+        let start = chunks.lower_bound(prefix);
+        let end = start
+            + chunks.keys[start..]
+                .iter()
+                .take_while(|key| key.start_with(prefix))
+                .count();
+        chunks.rows(start..end)
+    }
+
+    fn get(&self, key: &Key) -> Option<Value> {
+        let chunks = self.chunks.read().unwrap();
+        chunks.position(key).map(|position| chunks.row(position))
+    }
+
+    fn exists(&self, key: &Key) -> bool {
+        self.chunks.read().unwrap().position(key).is_some()
+    }
+
+    fn insert(&self, data: Vec<Value>) -> usize {
+        let len = data.len();
+        let mut chunks = self.chunks.write().unwrap();
+        for value in data {
+            let record_id = self.record_ids.fetch_add(1, Ordering::SeqCst);
+            chunks.push(Binary::pack_key(&[Datum::from_u64(record_id)]), &value);
+        }
+        len
+    }
+
+    fn update(&self, data: Vec<(Key, Value)>) -> usize {
+        let len = data.len();
+        let mut chunks = self.chunks.write().unwrap();
+        for (key, value) in data {
+            let position = chunks.position(&key);
+            debug_assert!(position.is_some(), "update operation should change already existed key");
+            if let Some(position) = position {
+                chunks.replace(position, &value);
+            }
+        }
+        len
+    }
+
+    fn delete(&self, data: Vec<Key>) -> usize {
+        let mut chunks = self.chunks.write().unwrap();
+        let mut size = 0;
+        for key in data.iter() {
+            if let Some(position) = chunks.position(key) {
+                chunks.remove(position);
+                size += 1;
+            }
+        }
+        size
+    }
+
+    fn write_batch(&self, ops: Vec<WriteOp>) -> usize {
+        let mut chunks = self.chunks.write().unwrap();
+        let mut size = 0;
+        for op in ops {
+            match op {
+                WriteOp::Insert(value) => {
+                    let record_id = self.record_ids.fetch_add(1, Ordering::SeqCst);
+                    chunks.push(Binary::pack_key(&[Datum::from_u64(record_id)]), &value);
+                    size += 1;
+                }
+                WriteOp::Update(key, value) => {
+                    if let Some(position) = chunks.position(&key) {
+                        chunks.replace(position, &value);
+                        size += 1;
+                    }
+                }
+                WriteOp::Delete(key) => {
+                    if let Some(position) = chunks.position(&key) {
+                        chunks.remove(position);
+                        size += 1;
+                    }
+                }
+            }
+        }
+        size
+    }
+
+    fn record_count(&self) -> usize {
+        self.chunks.read().unwrap().keys.len()
+    }
+
+    fn size(&self) -> usize {
+        let chunks = self.chunks.read().unwrap();
+        chunks.keys.iter().map(|key| key.to_bytes().len()).sum::<usize>()
+            + chunks
+                .columns
+                .iter()
+                .flatten()
+                .map(|cell| cell.to_bytes().len())
+                .sum::<usize>()
+    }
+
+    fn next_column_ord(&self) -> u64 {
+        self.column_ords.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn upgrade_rows(&self, batch_size: usize) -> usize {
+        self.write_batch(outdated_rows(self.select(), batch_size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_with(rows: Vec<Vec<Datum>>) -> ColumnarTableHandle {
+        let table = ColumnarTableHandle::default();
+        table.insert(rows.iter().map(|row| Binary::pack(row)).collect());
+        table
+    }
+
+    fn key(record_id: u64) -> Key {
+        Binary::pack_key(&[Datum::from_u64(record_id)])
+    }
+
+    #[test]
+    fn insert_and_select() {
+        let table = table_with(vec![
+            vec![Datum::from_i32(1), Datum::from_str("a")],
+            vec![Datum::from_i32(2), Datum::from_str("b")],
+        ]);
+
+        assert_eq!(
+            table.select().collect::<Vec<(Key, Value)>>(),
+            vec![
+                (key(0), Binary::pack(&[Datum::from_i32(1), Datum::from_str("a")])),
+                (key(1), Binary::pack(&[Datum::from_i32(2), Datum::from_str("b")])),
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_projected_columns() {
+        let table = table_with(vec![
+            vec![Datum::from_i32(1), Datum::from_str("a"), Datum::from_bool(true)],
+            vec![Datum::from_i32(2), Datum::from_str("b"), Datum::from_bool(false)],
+        ]);
+
+        assert_eq!(
+            table.scan_columns(&[2, 0]).collect::<Vec<(Key, Value)>>(),
+            vec![
+                (key(0), Binary::pack(&[Datum::from_bool(true), Datum::from_i32(1)])),
+                (key(1), Binary::pack(&[Datum::from_bool(false), Datum::from_i32(2)])),
+            ]
+        );
+    }
+
+    #[test]
+    fn update_and_delete() {
+        let table = table_with(vec![
+            vec![Datum::from_i32(1)],
+            vec![Datum::from_i32(2)],
+            vec![Datum::from_i32(3)],
+        ]);
+
+        assert_eq!(table.update(vec![(key(2), Binary::pack(&[Datum::from_i32(30)]))]), 1);
+        assert_eq!(table.delete(vec![key(0), key(10)]), 1);
+
+        assert_eq!(
+            table.select().collect::<Vec<(Key, Value)>>(),
+            vec![
+                (key(1), Binary::pack(&[Datum::from_i32(2)])),
+                (key(2), Binary::pack(&[Datum::from_i32(30)])),
+            ]
+        );
+        assert_eq!(table.record_count(), 2);
+    }
+
+    #[test]
+    fn rows_with_more_columns() {
+        let table = table_with(vec![
+            vec![Datum::from_i32(1)],
+            vec![Datum::from_i32(2), Datum::from_str("b")],
+        ]);
+
+        assert_eq!(
+            table.get(&key(0)),
+            Some(Binary::pack(&[Datum::from_i32(1), Datum::from_null()]))
+        );
+    }
+
+    #[test]
+    fn scan_range_of_keys() {
+        let table = table_with(vec![
+            vec![Datum::from_i32(1)],
+            vec![Datum::from_i32(2)],
+            vec![Datum::from_i32(3)],
+        ]);
+
+        assert_eq!(
+            table.scan_range(key(1)..key(3)).collect::<Vec<(Key, Value)>>(),
+            vec![
+                (key(1), Binary::pack(&[Datum::from_i32(2)])),
+                (key(2), Binary::pack(&[Datum::from_i32(3)])),
+            ]
+        );
+        assert_eq!(
+            table.scan_prefix(&key(1)).collect::<Vec<(Key, Value)>>(),
+            vec![(key(1), Binary::pack(&[Datum::from_i32(2)]))]
+        );
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Cursor, DataTable, Key, StorageError, Value, WriteOp};
+use binary::Binary;
+use repr::Datum;
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    iter,
+    ops::Range,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use types::SqlType;
+
+/// format of a file that a foreign table reads
+// TODO: Parquet files could be read too, but there is no reader of the format
+//       among dependencies
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ForeignFormat {
+    /// comma separated values, the first line is skipped if it is a header
+    Csv { header: bool },
+}
+
+// TODO: there is no `CREATE FOREIGN TABLE`, sqlparser does not parse it, so
+//       foreign tables could be created only with the schema handle
+// TODO: tables of remote PostgreSQL servers need `CREATE SERVER`, which sqlparser
+//       does not parse either, and a planner that pushes predicates down to the
+//       server and fails the statement if it is unavailable. Statements are planned
+//       against the deprecated data manager that doesn't see tables of the catalog
+/// read only table which rows come from an external file. The file is read
+/// on every scan, so changes of the file are visible to later scans. Writes
+/// don't change anything
+#[derive(Debug)]
+pub struct ForeignTableHandle {
+    path: PathBuf,
+    format: ForeignFormat,
+    columns: Vec<SqlType>,
+    column_ords: AtomicU64,
+}
+
+impl ForeignTableHandle {
+    pub fn new(path: PathBuf, format: ForeignFormat, columns: Vec<SqlType>) -> ForeignTableHandle {
+        ForeignTableHandle {
+            path,
+            format,
+            columns,
+            column_ords: AtomicU64::default(),
+        }
+    }
+}
+
+impl DataTable for ForeignTableHandle {
+    // the error is logged by `try_select`, scans that can't fail see no records
+    fn select(&self) -> Cursor {
+        self.try_select().unwrap_or_else(|_| Cursor::new(iter::empty()))
+    }
+
+    fn try_select(&self) -> Result<Cursor, StorageError> {
+        let ForeignFormat::Csv { header } = self.format;
+        let read = File::open(&self.path).and_then(|file| {
+            BufReader::new(file)
+                .lines()
+                .skip(if header { 1 } else { 0 })
+                .collect::<io::Result<Vec<String>>>()
+        });
+        let lines = match read {
+            Ok(lines) => lines,
+            Err(io_error) => {
+                log::error!(
+                    "Could not read file {:?} of foreign table due to error {:?}",
+                    self.path,
+                    io_error
+                );
+                return Err(StorageError);
+            }
+        };
+        let columns = self.columns.clone();
+        let records =
+            lines
+                .into_iter()
+                .enumerate()
+                .filter_map(move |(index, line)| match csv_row(&columns, csv_fields(&line)) {
+                    Ok(value) => Some((Binary::pack_key(&[Datum::from_u64(index as u64)]), value)),
+                    Err(message) => {
+                        log::warn!("record {} of foreign table is skipped: {}", index, message);
+                        None
+                    }
+                });
+        Ok(Cursor::new(records))
+    }
+
+    fn scan_range(&self, range: Range<Key>) -> Cursor {
+        Cursor::new(self.select().filter(move |(key, _value)| range.contains(key)))
+    }
+
+    fn scan_prefix(&self, prefix: &Key) -> Cursor {
+        let prefix = prefix.clone();
+        Cursor::new(self.select().filter(move |(key, _value)| key.start_with(&prefix)))
+    }
+
+    fn get(&self, key: &Key) -> Option<Value> {
+        self.select()
+            .find(|(record_key, _value)| record_key == key)
+            .map(|(_key, value)| value)
+    }
+
+    fn insert(&self, _data: Vec<Value>) -> usize {
+        0
+    }
+
+    fn update(&self, _data: Vec<(Key, Value)>) -> usize {
+        0
+    }
+
+    fn delete(&self, _data: Vec<Key>) -> usize {
+        0
+    }
+
+    fn write_batch(&self, _ops: Vec<WriteOp>) -> usize {
+        0
+    }
+
+    fn record_count(&self) -> usize {
+        self.select().count()
+    }
+
+    fn size(&self) -> usize {
+        fs::metadata(&self.path).map_or(0, |metadata| metadata.len() as usize)
+    }
+
+    fn next_column_ord(&self) -> u64 {
+        self.column_ords.fetch_add(1, Ordering::SeqCst)
+    }
+
+    fn upgrade_rows(&self, _batch_size: usize) -> usize {
+        0
+    }
+}
+
+/// fields of a CSV line, unquoted empty fields are NULLs the way `COPY`
+/// reads them
+fn csv_fields(line: &str) -> Vec<Option<String>> {
+    fn field(value: String, quoted: bool) -> Option<String> {
+        if value.is_empty() && !quoted {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    let mut fields = vec![];
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                value.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if !quoted && value.is_empty() => {
+                quoted = true;
+                in_quotes = true;
+            }
+            ',' if !in_quotes => {
+                fields.push(field(std::mem::take(&mut value), quoted));
+                quoted = false;
+            }
+            c => value.push(c),
+        }
+    }
+    fields.push(field(value, quoted));
+    fields
+}
+
+fn csv_row(columns: &[SqlType], fields: Vec<Option<String>>) -> Result<Value, String> {
+    if fields.len() != columns.len() {
+        return Err(format!("expected {} fields but found {}", columns.len(), fields.len()));
+    }
+    let mut datums = Vec::with_capacity(columns.len());
+    for (sql_type, field) in columns.iter().zip(fields) {
+        datums.push(match field {
+            None => Datum::from_null(),
+            Some(field) => datum(sql_type, field)?,
+        });
+    }
+    Ok(Binary::pack(&datums))
+}
+
+fn datum(sql_type: &SqlType, field: String) -> Result<Datum<'static>, String> {
+    let invalid = |field: &str| format!("invalid value {:?} of {:?} type", field, sql_type);
+    match sql_type {
+        SqlType::Bool => match field.to_lowercase().as_str() {
+            "t" | "true" | "yes" | "on" | "1" => Ok(Datum::from_bool(true)),
+            "f" | "false" | "no" | "off" | "0" => Ok(Datum::from_bool(false)),
+            _ => Err(invalid(&field)),
+        },
+        SqlType::Char(_) | SqlType::VarChar(_) => Ok(Datum::from_string(field)),
+        SqlType::SmallInt => field.trim().parse().map(Datum::from_i16).map_err(|_| invalid(&field)),
+        SqlType::Integer => field.trim().parse().map(Datum::from_i32).map_err(|_| invalid(&field)),
+        SqlType::BigInt => field.trim().parse().map(Datum::from_i64).map_err(|_| invalid(&field)),
+        SqlType::Real => field.trim().parse().map(Datum::from_f32).map_err(|_| invalid(&field)),
+        SqlType::DoublePrecision => field.trim().parse().map(Datum::from_f64).map_err(|_| invalid(&field)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn csv_file(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().expect("to create temporary file");
+        file.write_all(content.as_bytes()).expect("to write file");
+        file
+    }
+
+    fn key(record_id: u64) -> Key {
+        Binary::pack_key(&[Datum::from_u64(record_id)])
+    }
+
+    fn table(file: &NamedTempFile, header: bool) -> ForeignTableHandle {
+        ForeignTableHandle::new(
+            file.path().to_path_buf(),
+            ForeignFormat::Csv { header },
+            vec![SqlType::Integer, SqlType::VarChar(10), SqlType::Bool],
+        )
+    }
+
+    #[test]
+    fn fields_of_csv_line() {
+        assert_eq!(
+            csv_fields("1,,\"\",\"a,\"\"b\"\"\",c"),
+            vec![
+                Some("1".to_owned()),
+                None,
+                Some("".to_owned()),
+                Some("a,\"b\"".to_owned()),
+                Some("c".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn select_rows_of_file() {
+        let file = csv_file("id,name,active\n1,alice,true\n2,,f\n");
+        let table = table(&file, true);
+
+        assert_eq!(
+            table.select().collect::<Vec<(Key, Value)>>(),
+            vec![
+                (
+                    key(0),
+                    Binary::pack(&[Datum::from_i32(1), Datum::from_str("alice"), Datum::from_bool(true)])
+                ),
+                (
+                    key(1),
+                    Binary::pack(&[Datum::from_i32(2), Datum::from_null(), Datum::from_bool(false)])
+                ),
+            ]
+        );
+        assert_eq!(table.record_count(), 2);
+    }
+
+    #[test]
+    fn invalid_records_are_skipped() {
+        let file = csv_file("one,alice,true\n2,bob\n3,carol,no\n");
+        let table = table(&file, false);
+
+        assert_eq!(
+            table.select().collect::<Vec<(Key, Value)>>(),
+            vec![(
+                key(2),
+                Binary::pack(&[Datum::from_i32(3), Datum::from_str("carol"), Datum::from_bool(false)])
+            )]
+        );
+    }
+
+    #[test]
+    fn changes_of_file_are_visible_to_next_scan() {
+        let mut file = csv_file("1,alice,true\n");
+        let table = table(&file, false);
+        assert_eq!(table.record_count(), 1);
+
+        file.write_all(b"2,bob,false\n").expect("to write file");
+
+        assert_eq!(table.record_count(), 2);
+        assert_eq!(
+            table.get(&key(1)),
+            Some(Binary::pack(&[
+                Datum::from_i32(2),
+                Datum::from_str("bob"),
+                Datum::from_bool(false)
+            ]))
+        );
+    }
+
+    #[test]
+    fn scan_range_of_records() {
+        let file = csv_file("1,a,t\n2,b,t\n3,c,t\n");
+        let table = table(&file, false);
+
+        assert_eq!(
+            table
+                .scan_range(key(1)..key(2))
+                .map(|(key, _value)| key)
+                .collect::<Vec<Key>>(),
+            vec![key(1)]
+        );
+    }
+
+    #[test]
+    fn writes_do_not_change_file() {
+        let file = csv_file("1,alice,true\n");
+        let table = table(&file, false);
+
+        assert_eq!(
+            table.insert(vec![Binary::pack(&[
+                Datum::from_i32(2),
+                Datum::from_str("bob"),
+                Datum::from_bool(false)
+            ])]),
+            0
+        );
+        assert_eq!(table.delete(vec![key(0)]), 0);
+
+        assert_eq!(table.record_count(), 1);
+    }
+
+    #[test]
+    fn missing_file_has_no_records() {
+        let table = ForeignTableHandle::new(
+            PathBuf::from("does_not_exist.csv"),
+            ForeignFormat::Csv { header: false },
+            vec![SqlType::Integer],
+        );
+
+        assert_eq!(table.record_count(), 0);
+        assert_eq!(table.size(), 0);
+        assert!(table.try_select().is_err());
+    }
+}
@@ -12,63 +12,256 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Cursor, DataCatalog, DataTable, Key, SchemaHandle, Value};
+use crate::{
+    foreign::ForeignTableHandle, outdated_rows, CatalogError, Cursor, DataCatalog, DataTable, Guard, Key, OutOfMemory,
+    SchemaHandle, StorageError, Value, WriteOp,
+};
 use binary::Binary;
 use dashmap::DashMap;
 use repr::Datum;
 use std::{
     collections::BTreeMap,
+    ops::{Bound, Range},
     sync::{
-        atomic::{AtomicU64, Ordering},
-        RwLock,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, RwLock,
     },
 };
 
+#[derive(Default, Debug)]
+struct MemoryUsage {
+    used: AtomicUsize,
+    limit: Option<usize>,
+}
+
+impl MemoryUsage {
+    fn new(limit: Option<usize>) -> Arc<MemoryUsage> {
+        Arc::new(MemoryUsage {
+            used: AtomicUsize::default(),
+            limit,
+        })
+    }
+
+    fn reserve(&self, bytes: usize) -> bool {
+        let mut used = self.used.load(Ordering::SeqCst);
+        loop {
+            if matches!(self.limit, Some(limit) if used + bytes > limit) {
+                return false;
+            }
+            match self
+                .used
+                .compare_exchange(used, used + bytes, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return true,
+                Err(current) => used = current,
+            }
+        }
+    }
+
+    fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::SeqCst);
+    }
+}
+
+fn record_size(key: &Key, value: &Value) -> usize {
+    key.to_bytes().len() + value.to_bytes().len()
+}
+
+type Records = BTreeMap<Binary, Binary>;
+
+/// iterates over records of a snapshot
+struct SnapshotScan {
+    records: Arc<Records>,
+    from: Bound<Key>,
+    to: Bound<Key>,
+}
+
+impl Iterator for SnapshotScan {
+    type Item = (Key, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.records.range((self.from.clone(), self.to.clone())).next()?;
+        self.from = Bound::Excluded(key.clone());
+        Some((key.clone(), value.clone()))
+    }
+}
+
+/// records of a table at the point in time it was taken. Tables copy their
+/// records on write while a snapshot holds them, so all reads of the snapshot
+/// and cursors created from it see the same records whatever other sessions
+/// write in the meantime
+#[derive(Clone, Debug)]
+pub struct TableSnapshot {
+    records: Arc<Records>,
+}
+
+impl TableSnapshot {
+    fn scan(&self, from: Bound<Key>, to: Bound<Key>) -> SnapshotScan {
+        SnapshotScan {
+            records: self.records.clone(),
+            from,
+            to,
+        }
+    }
+
+    pub fn select(&self) -> Cursor {
+        Cursor::new(self.scan(Bound::Unbounded, Bound::Unbounded))
+    }
+
+    pub fn scan_range(&self, range: Range<Key>) -> Cursor {
+        Cursor::new(self.scan(Bound::Included(range.start), Bound::Excluded(range.end)))
+    }
+
+    pub fn scan_prefix(&self, prefix: &Key) -> Cursor {
+        let prefix = prefix.clone();
+        Cursor::new(
+            self.scan(Bound::Included(prefix.clone()), Bound::Unbounded)
+                .take_while(move |(key, _value)| key.start_with(&prefix)),
+        )
+    }
+
+    pub fn get(&self, key: &Key) -> Option<Value> {
+        self.records.get(key).cloned()
+    }
+
+    pub fn record_count(&self) -> usize {
+        self.records.len()
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct InMemoryTableHandle {
-    records: RwLock<BTreeMap<Binary, Binary>>,
+    records: RwLock<Arc<Records>>,
     record_ids: AtomicU64,
     column_ords: AtomicU64,
+    memory: Arc<MemoryUsage>,
+    catalog_memory: Arc<MemoryUsage>,
+}
+
+impl InMemoryTableHandle {
+    fn new(limit: Option<usize>, catalog_memory: Arc<MemoryUsage>) -> InMemoryTableHandle {
+        InMemoryTableHandle {
+            records: RwLock::default(),
+            record_ids: AtomicU64::default(),
+            column_ords: AtomicU64::default(),
+            memory: MemoryUsage::new(limit),
+            catalog_memory,
+        }
+    }
+
+    fn reserve(&self, bytes: usize) -> bool {
+        if !self.memory.reserve(bytes) {
+            false
+        } else if !self.catalog_memory.reserve(bytes) {
+            self.memory.release(bytes);
+            false
+        } else {
+            true
+        }
+    }
+
+    fn release(&self, bytes: usize) {
+        self.memory.release(bytes);
+        self.catalog_memory.release(bytes);
+    }
+
+    /// records as they are at the moment, cursors of the table are created
+    /// from a snapshot taken when they are requested
+    pub fn snapshot(&self) -> TableSnapshot {
+        TableSnapshot {
+            records: self.records.read().unwrap().clone(),
+        }
+    }
+}
+
+impl Drop for InMemoryTableHandle {
+    fn drop(&mut self) {
+        self.catalog_memory.release(self.memory.used.load(Ordering::SeqCst));
+    }
 }
 
 impl DataTable for InMemoryTableHandle {
     fn select(&self) -> Cursor {
-        self.records
-            .read()
-            .unwrap()
-            .iter()
-            .map(|(key, value)| (key.clone(), value.clone()))
-            .collect::<Cursor>()
+        self.snapshot().select()
+    }
+
+    fn scan_range(&self, range: Range<Key>) -> Cursor {
+        self.snapshot().scan_range(range)
+    }
+
+    fn scan_prefix(&self, prefix: &Key) -> Cursor {
+        self.snapshot().scan_prefix(prefix)
+    }
+
+    fn get(&self, key: &Key) -> Option<Value> {
+        self.records.read().unwrap().get(key).cloned()
+    }
+
+    fn exists(&self, key: &Key) -> bool {
+        self.records.read().unwrap().contains_key(key)
     }
 
     fn insert(&self, data: Vec<Value>) -> usize {
+        match self.try_insert(data) {
+            Ok(len) => len,
+            Err(OutOfMemory) => {
+                log::error!("rows were not inserted as they exceed memory limit");
+                0
+            }
+        }
+    }
+
+    fn try_insert(&self, data: Vec<Value>) -> Result<usize, OutOfMemory> {
         let len = data.len();
-        let mut rw = self.records.write().unwrap();
-        for value in data {
-            let record_id = self.record_ids.fetch_add(1, Ordering::SeqCst);
-            let key = Binary::pack(&[Datum::from_u64(record_id)]);
+        let first_id = self.record_ids.fetch_add(len as u64, Ordering::SeqCst);
+        let records = data
+            .into_iter()
+            .zip(first_id..)
+            .map(|(value, record_id)| (Binary::pack_key(&[Datum::from_u64(record_id)]), value))
+            .collect::<Vec<(Key, Value)>>();
+        if !self.reserve(records.iter().map(|(key, value)| record_size(key, value)).sum()) {
+            return Err(OutOfMemory);
+        }
+        let mut guard = self.records.write().unwrap();
+        let rw = Arc::make_mut(&mut guard);
+        for (key, value) in records {
             debug_assert!(
                 matches!(rw.insert(key, value), None),
                 "insert operation should insert nonexistent key"
             );
         }
-        len
+        Ok(len)
     }
 
     fn update(&self, data: Vec<(Key, Value)>) -> usize {
         let len = data.len();
-        let mut rw = self.records.write().unwrap();
+        let mut guard = self.records.write().unwrap();
+        let rw = Arc::make_mut(&mut guard);
+        let (added, removed) = data
+            .iter()
+            .fold((0, 0), |(added, removed), (key, value)| match rw.get(key) {
+                Some(current) => (added + value.to_bytes().len(), removed + current.to_bytes().len()),
+                None => (added, removed),
+            });
+        if added > removed && !self.reserve(added - removed) {
+            log::error!("rows were not updated as they exceed memory limit");
+            return 0;
+        }
         for (key, value) in data {
             debug_assert!(
                 matches!(rw.insert(key, value), Some(_)),
                 "update operation should change already existed key"
             );
         }
+        if removed > added {
+            self.release(removed - added);
+        }
         len
     }
 
     fn delete(&self, data: Vec<Key>) -> usize {
-        let mut rw = self.records.write().unwrap();
+        let mut guard = self.records.write().unwrap();
+        let rw = Arc::make_mut(&mut guard);
         let mut size = 0;
         let keys = rw
             .iter()
@@ -76,52 +269,251 @@ impl DataTable for InMemoryTableHandle {
             .map(|(key, _value)| key.clone())
             .collect::<Vec<Binary>>();
         for key in keys.iter() {
-            debug_assert!(matches!(rw.remove(key), Some(_)), "delete operation delete existed key");
+            let removed = rw.remove(key);
+            debug_assert!(matches!(removed, Some(_)), "delete operation delete existed key");
+            if let Some(value) = removed {
+                self.release(record_size(key, &value));
+            }
             size += 1;
         }
         size
     }
 
+    fn write_batch(&self, ops: Vec<WriteOp>) -> usize {
+        let mut guard = self.records.write().unwrap();
+        let rw = Arc::make_mut(&mut guard);
+        let mut size = 0;
+        for op in ops {
+            match op {
+                WriteOp::Insert(value) => {
+                    let record_id = self.record_ids.fetch_add(1, Ordering::SeqCst);
+                    let key = Binary::pack_key(&[Datum::from_u64(record_id)]);
+                    if self.reserve(record_size(&key, &value)) {
+                        rw.insert(key, value);
+                        size += 1;
+                    }
+                }
+                WriteOp::Update(key, value) => {
+                    if let Some(current) = rw.get_mut(&key) {
+                        let (added, removed) = (value.to_bytes().len(), current.to_bytes().len());
+                        if added <= removed || self.reserve(added - removed) {
+                            *current = value;
+                            self.release(removed.saturating_sub(added));
+                            size += 1;
+                        }
+                    }
+                }
+                WriteOp::Delete(key) => {
+                    if let Some(value) = rw.remove(&key) {
+                        self.release(record_size(&key, &value));
+                        size += 1;
+                    }
+                }
+            }
+        }
+        size
+    }
+
+    fn record_count(&self) -> usize {
+        self.records.read().unwrap().len()
+    }
+
+    fn size(&self) -> usize {
+        self.records
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| key.to_bytes().len() + value.to_bytes().len())
+            .sum()
+    }
+
     fn next_column_ord(&self) -> u64 {
         self.column_ords.fetch_add(1, Ordering::SeqCst)
     }
+
+    fn upgrade_rows(&self, batch_size: usize) -> usize {
+        self.write_batch(outdated_rows(self.select(), batch_size))
+    }
+}
+
+fn sorted_names<V>(map: &DashMap<String, V>) -> Vec<String> {
+    let mut names = map.iter().map(|entry| entry.key().clone()).collect::<Vec<String>>();
+    names.sort();
+    names
+}
+
+/// table of an in-memory schema, it either stores its records or reads them
+/// from an external file
+#[derive(Debug)]
+pub enum InMemorySchemaTable {
+    Stored(InMemoryTableHandle),
+    Foreign(ForeignTableHandle),
+}
+
+impl InMemorySchemaTable {
+    /// records as they are at the moment, records of a foreign table are read
+    /// from its file at once
+    pub fn snapshot(&self) -> TableSnapshot {
+        match self {
+            InMemorySchemaTable::Stored(table) => table.snapshot(),
+            InMemorySchemaTable::Foreign(table) => TableSnapshot {
+                records: Arc::new(table.select().collect()),
+            },
+        }
+    }
+
+    fn as_data_table(&self) -> &dyn DataTable {
+        match self {
+            InMemorySchemaTable::Stored(table) => table,
+            InMemorySchemaTable::Foreign(table) => table,
+        }
+    }
+}
+
+impl DataTable for InMemorySchemaTable {
+    fn select(&self) -> Cursor {
+        self.as_data_table().select()
+    }
+
+    fn try_select(&self) -> Result<Cursor, StorageError> {
+        self.as_data_table().try_select()
+    }
+
+    fn scan_range(&self, range: Range<Key>) -> Cursor {
+        self.as_data_table().scan_range(range)
+    }
+
+    fn scan_prefix(&self, prefix: &Key) -> Cursor {
+        self.as_data_table().scan_prefix(prefix)
+    }
+
+    fn get(&self, key: &Key) -> Option<Value> {
+        self.as_data_table().get(key)
+    }
+
+    fn exists(&self, key: &Key) -> bool {
+        self.as_data_table().exists(key)
+    }
+
+    fn insert(&self, data: Vec<Value>) -> usize {
+        self.as_data_table().insert(data)
+    }
+
+    fn try_insert(&self, data: Vec<Value>) -> Result<usize, OutOfMemory> {
+        self.as_data_table().try_insert(data)
+    }
+
+    fn update(&self, data: Vec<(Key, Value)>) -> usize {
+        self.as_data_table().update(data)
+    }
+
+    fn delete(&self, data: Vec<Key>) -> usize {
+        self.as_data_table().delete(data)
+    }
+
+    fn write_batch(&self, ops: Vec<WriteOp>) -> usize {
+        self.as_data_table().write_batch(ops)
+    }
+
+    fn record_count(&self) -> usize {
+        self.as_data_table().record_count()
+    }
+
+    fn size(&self) -> usize {
+        self.as_data_table().size()
+    }
+
+    fn next_column_ord(&self) -> u64 {
+        self.as_data_table().next_column_ord()
+    }
+
+    fn upgrade_rows(&self, batch_size: usize) -> usize {
+        self.as_data_table().upgrade_rows(batch_size)
+    }
 }
 
 #[derive(Default, Debug)]
 pub struct InMemorySchemaHandle {
-    tables: DashMap<String, InMemoryTableHandle>,
+    name: String,
+    tables: DashMap<String, InMemorySchemaTable>,
+    table_memory_limit: Option<usize>,
+    catalog_memory: Arc<MemoryUsage>,
 }
 
-impl SchemaHandle for InMemorySchemaHandle {
-    type Table = InMemoryTableHandle;
+impl InMemorySchemaHandle {
+    pub fn table(&self, table_name: &str) -> Result<Guard<'_, InMemorySchemaTable>, CatalogError> {
+        self.tables
+            .get(table_name)
+            .ok_or_else(|| CatalogError::TableNotFound(self.name.clone(), table_name.to_owned()))
+    }
 
-    fn create_table(&self, table_name: &str) -> bool {
+    /// registers a table that reads rows of an external file, returns `false`
+    /// if the schema already has a table with the name
+    pub fn create_foreign_table(&self, table_name: &str, table: ForeignTableHandle) -> bool {
         if self.tables.contains_key(table_name) {
             false
         } else {
             self.tables
-                .insert(table_name.to_owned(), InMemoryTableHandle::default());
+                .insert(table_name.to_owned(), InMemorySchemaTable::Foreign(table));
             true
         }
     }
+}
 
-    fn drop_table(&self, table_name: &str) -> bool {
-        if !self.tables.contains_key(table_name) {
+impl SchemaHandle for InMemorySchemaHandle {
+    type Table = InMemorySchemaTable;
+
+    fn create_table(&self, table_name: &str) -> bool {
+        if self.tables.contains_key(table_name) {
             false
         } else {
-            self.tables.remove(table_name);
+            self.tables.insert(
+                table_name.to_owned(),
+                InMemorySchemaTable::Stored(InMemoryTableHandle::new(
+                    self.table_memory_limit,
+                    self.catalog_memory.clone(),
+                )),
+            );
             true
         }
     }
 
+    fn drop_table(&self, table_name: &str) -> bool {
+        self.tables.remove(table_name).is_some()
+    }
+
     fn work_with<T, F: Fn(&Self::Table) -> T>(&self, table_name: &str, operation: F) -> Option<T> {
-        self.tables.get(table_name).map(|table| operation(&*table))
+        self.table(table_name).ok().map(|table| operation(&*table))
+    }
+
+    fn list_tables(&self) -> Vec<String> {
+        sorted_names(&self.tables)
     }
 }
 
 #[derive(Default)]
 pub struct InMemoryCatalogHandle {
     schemas: DashMap<String, InMemorySchemaHandle>,
+    table_memory_limit: Option<usize>,
+    memory: Arc<MemoryUsage>,
+}
+
+impl InMemoryCatalogHandle {
+    /// catalog which tables can't occupy more than `table_limit` bytes each and
+    /// more than `limit` bytes all together
+    pub fn with_memory_limits(limit: Option<usize>, table_limit: Option<usize>) -> InMemoryCatalogHandle {
+        InMemoryCatalogHandle {
+            schemas: DashMap::default(),
+            table_memory_limit: table_limit,
+            memory: MemoryUsage::new(limit),
+        }
+    }
+
+    pub fn schema(&self, schema_name: &str) -> Result<Guard<'_, InMemorySchemaHandle>, CatalogError> {
+        self.schemas
+            .get(schema_name)
+            .ok_or_else(|| CatalogError::SchemaNotFound(schema_name.to_owned()))
+    }
 }
 
 impl DataCatalog for InMemoryCatalogHandle {
@@ -131,8 +523,15 @@ impl DataCatalog for InMemoryCatalogHandle {
         if self.schemas.contains_key(schema_name) {
             false
         } else {
-            self.schemas
-                .insert(schema_name.to_owned(), InMemorySchemaHandle::default());
+            self.schemas.insert(
+                schema_name.to_owned(),
+                InMemorySchemaHandle {
+                    name: schema_name.to_owned(),
+                    table_memory_limit: self.table_memory_limit,
+                    catalog_memory: self.memory.clone(),
+                    ..InMemorySchemaHandle::default()
+                },
+            );
             true
         }
     }
@@ -147,7 +546,11 @@ impl DataCatalog for InMemoryCatalogHandle {
     }
 
     fn work_with<T, F: Fn(&Self::Schema) -> T>(&self, schema_name: &str, operation: F) -> Option<T> {
-        self.schemas.get(schema_name).map(|schema| operation(&*schema))
+        self.schema(schema_name).ok().map(|schema| operation(&*schema))
+    }
+
+    fn list_schemas(&self) -> Vec<String> {
+        sorted_names(&self.schemas)
     }
 }
 
@@ -233,12 +636,85 @@ mod general_cases {
 
             assert_eq!(catalog_handle.drop_schema(SCHEMA), false);
         }
+
+        #[test]
+        fn list_schemas_in_alphabetical_order() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.list_schemas(), Vec::<String>::new());
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA_2), true);
+            assert_eq!(catalog_handle.create_schema(SCHEMA_1), true);
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(catalog_handle.drop_schema(SCHEMA_1), true);
+
+            assert_eq!(
+                catalog_handle.list_schemas(),
+                vec![SCHEMA.to_owned(), SCHEMA_2.to_owned()]
+            );
+        }
     }
 
     #[cfg(test)]
     mod create_table {
         use super::*;
 
+        #[test]
+        fn list_tables_in_alphabetical_order() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.list_tables()),
+                Some(vec![])
+            );
+
+            for table in &[TABLE_2, TABLE, TABLE_1] {
+                assert_eq!(
+                    catalog_handle.work_with(SCHEMA, |schema| schema.create_table(table)),
+                    Some(true)
+                );
+            }
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.drop_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.list_tables()),
+                Some(vec![TABLE_1.to_owned(), TABLE_2.to_owned()])
+            );
+        }
+
+        #[test]
+        fn metadata_of_table() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            let value = Binary::pack(&[Datum::from_u64(1)]);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.insert(vec![value.clone()]))),
+                Some(Some(1))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.table_metadata(TABLE)),
+                Some(Some(crate::TableStats {
+                    records: 1,
+                    size: Binary::pack_key(&[Datum::from_u64(0)]).to_bytes().len() + value.to_bytes().len()
+                }))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.table_metadata(DOES_NOT_EXIST)),
+                Some(None)
+            );
+        }
+
         #[test]
         fn create_tables_with_different_names() {
             let catalog_handle = catalog();
@@ -410,7 +886,10 @@ mod general_cases {
                     .unwrap()
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
-                vec![(Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)]))]
+                vec![(
+                    Binary::pack_key(&[Datum::from_u64(0)]),
+                    Binary::pack(&[Datum::from_u64(1)])
+                )]
             );
         }
 
@@ -439,37 +918,50 @@ mod general_cases {
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
                 vec![
-                    (Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)])),
-                    (Binary::pack(&[Datum::from_u64(1)]), Binary::pack(&[Datum::from_u64(2)]))
+                    (
+                        Binary::pack_key(&[Datum::from_u64(0)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(2)])
+                    )
                 ]
             );
         }
 
         #[test]
-        fn delete_from_table_that_in_schema_that_does_not_exist() {
+        fn scan_returns_records_in_insertion_order() {
             let catalog_handle = catalog();
 
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
             assert_eq!(
-                catalog_handle.work_with(DOES_NOT_EXIST, |schema| schema
-                    .work_with(TABLE, |table| table.delete(vec![]))),
-                None
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
             );
-        }
-
-        #[test]
-        fn delete_from_table_that_does_not_exist() {
-            let catalog_handle = catalog();
 
-            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            let values = (0..300)
+                .map(|id| Binary::pack(&[Datum::from_u64(id)]))
+                .collect::<Vec<Value>>();
             assert_eq!(
                 catalog_handle.work_with(SCHEMA, |schema| schema
-                    .work_with(DOES_NOT_EXIST, |table| table.delete(vec![]))),
-                Some(None)
+                    .work_with(TABLE, |table| table.insert(values.clone()))),
+                Some(Some(300))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select()))
+                    .unwrap()
+                    .unwrap()
+                    .map(|(_key, value)| value)
+                    .collect::<Vec<Value>>(),
+                values
             );
         }
 
         #[test]
-        fn insert_delete_scan_records_from_table() {
+        fn scan_range_of_records() {
             let catalog_handle = catalog();
 
             assert_eq!(catalog_handle.create_schema(SCHEMA), true);
@@ -479,42 +971,437 @@ mod general_cases {
             );
 
             assert_eq!(
-                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
-                    Binary::pack(&[Datum::from_u64(1)]),
-                    Binary::pack(&[Datum::from_u64(2)])
-                ]))),
-                Some(Some(2))
-            );
-
-            assert_eq!(
-                catalog_handle.work_with(SCHEMA, |schema| schema
-                    .work_with(TABLE, |table| table.delete(vec![Binary::pack(&[Datum::from_u64(1)])]))),
-                Some(Some(1))
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .insert((0..5).map(|id| Binary::pack(&[Datum::from_u64(id)])).collect()))),
+                Some(Some(5))
             );
 
             assert_eq!(
                 catalog_handle
-                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select()))
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.scan_range(
+                        Binary::pack_key(&[Datum::from_u64(1)])..Binary::pack_key(&[Datum::from_u64(3)])
+                    )))
                     .unwrap()
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
-                vec![(Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)]))]
+                vec![
+                    (
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(2)]),
+                        Binary::pack(&[Datum::from_u64(2)])
+                    ),
+                ]
             );
         }
 
         #[test]
-        fn update_table_that_in_schema_that_does_not_exist() {
+        fn scan_records_by_prefix() {
             let catalog_handle = catalog();
 
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
             assert_eq!(
-                catalog_handle.work_with(DOES_NOT_EXIST, |schema| schema
-                    .work_with(TABLE, |table| table.update(vec![]))),
-                None
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
             );
-        }
 
-        #[test]
-        fn update_table_that_does_not_exist() {
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .insert((0..5).map(|id| Binary::pack(&[Datum::from_u64(id)])).collect()))),
+                Some(Some(5))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                        .scan_prefix(&Binary::pack_key(&[Datum::from_u64(3)]))))
+                    .unwrap()
+                    .unwrap()
+                    .collect::<Vec<(Key, Value)>>(),
+                vec![(
+                    Binary::pack_key(&[Datum::from_u64(3)]),
+                    Binary::pack(&[Datum::from_u64(3)])
+                )]
+            );
+        }
+
+        #[test]
+        fn reads_of_snapshot_do_not_observe_later_writes() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .insert((0..3).map(|id| Binary::pack(&[Datum::from_u64(id)])).collect()))),
+                Some(Some(3))
+            );
+
+            let snapshot = catalog_handle
+                .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.snapshot()))
+                .unwrap()
+                .unwrap();
+            let mut cursor = snapshot.select();
+            assert_eq!(
+                cursor.next(),
+                Some((
+                    Binary::pack_key(&[Datum::from_u64(0)]),
+                    Binary::pack(&[Datum::from_u64(0)])
+                ))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.write_batch(
+                    vec![
+                        WriteOp::Delete(Binary::pack_key(&[Datum::from_u64(1)])),
+                        WriteOp::Update(
+                            Binary::pack_key(&[Datum::from_u64(2)]),
+                            Binary::pack(&[Datum::from_u64(20)])
+                        ),
+                        WriteOp::Insert(Binary::pack(&[Datum::from_u64(3)])),
+                    ]
+                ))),
+                Some(Some(3))
+            );
+
+            assert_eq!(
+                cursor.collect::<Vec<(Key, Value)>>(),
+                vec![
+                    (
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(2)]),
+                        Binary::pack(&[Datum::from_u64(2)])
+                    ),
+                ]
+            );
+            assert_eq!(
+                snapshot
+                    .scan_range(Binary::pack_key(&[Datum::from_u64(1)])..Binary::pack_key(&[Datum::from_u64(4)]))
+                    .count(),
+                2
+            );
+            assert_eq!(
+                snapshot.get(&Binary::pack_key(&[Datum::from_u64(2)])),
+                Some(Binary::pack(&[Datum::from_u64(2)]))
+            );
+            assert_eq!(snapshot.record_count(), 3);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select().count())),
+                Some(Some(3))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .snapshot()
+                    .get(&Binary::pack_key(&[Datum::from_u64(2)])))),
+                Some(Some(Some(Binary::pack(&[Datum::from_u64(20)]))))
+            );
+        }
+
+        #[test]
+        fn get_record_by_key() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)])
+                ]))),
+                Some(Some(2))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.get(&Binary::pack_key(&[Datum::from_u64(1)])))),
+                Some(Some(Some(Binary::pack(&[Datum::from_u64(2)]))))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.get(&Binary::pack_key(&[Datum::from_u64(2)])))),
+                Some(Some(None))
+            );
+        }
+
+        #[test]
+        fn record_exists() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.insert(vec![Binary::pack(&[Datum::from_u64(1)])]))),
+                Some(Some(1))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.exists(&Binary::pack_key(&[Datum::from_u64(0)])))),
+                Some(Some(true))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.exists(&Binary::pack_key(&[Datum::from_u64(1)])))),
+                Some(Some(false))
+            );
+        }
+
+        #[test]
+        fn batches_of_records_take_consecutive_keys() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)]),
+                    Binary::pack(&[Datum::from_u64(3)]),
+                ]))),
+                Some(Some(3))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(4)]),
+                    Binary::pack(&[Datum::from_u64(5)]),
+                ]))),
+                Some(Some(2))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .select()
+                    .map(|(key, value)| (key.unpack_key(), value))
+                    .collect::<Vec<(Vec<Datum>, Binary)>>())),
+                Some(Some(
+                    (1..=5)
+                        .map(|value| (
+                            vec![Datum::from_i64(value as i64 - 1)],
+                            Binary::pack(&[Datum::from_u64(value)])
+                        ))
+                        .collect()
+                ))
+            );
+        }
+
+        #[test]
+        fn scan_is_not_affected_by_later_writes() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            catalog_handle.work_with(SCHEMA, |schema| {
+                schema.work_with(TABLE, |table| {
+                    table.insert(vec![
+                        Binary::pack(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(2)]),
+                    ])
+                })
+            });
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| {
+                    let mut cursor = table.select();
+                    let first = cursor.next();
+                    table.delete(vec![Binary::pack_key(&[Datum::from_u64(1)])]);
+                    table.insert(vec![Binary::pack(&[Datum::from_u64(3)])]);
+                    (first, cursor.collect::<Vec<(Binary, Binary)>>(), table.record_count())
+                })),
+                Some(Some((
+                    Some((
+                        Binary::pack_key(&[Datum::from_u64(0)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    )),
+                    vec![(
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(2)])
+                    )],
+                    2
+                )))
+            );
+        }
+
+        #[test]
+        fn delete_from_table_that_in_schema_that_does_not_exist() {
+            let catalog_handle = catalog();
+
+            assert_eq!(
+                catalog_handle.work_with(DOES_NOT_EXIST, |schema| schema
+                    .work_with(TABLE, |table| table.delete(vec![]))),
+                None
+            );
+        }
+
+        #[test]
+        fn delete_from_table_that_does_not_exist() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(DOES_NOT_EXIST, |table| table.delete(vec![]))),
+                Some(None)
+            );
+        }
+
+        #[test]
+        fn insert_delete_scan_records_from_table() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)])
+                ]))),
+                Some(Some(2))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .delete(vec![Binary::pack_key(&[Datum::from_u64(1)])]))),
+                Some(Some(1))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select()))
+                    .unwrap()
+                    .unwrap()
+                    .collect::<Vec<(Key, Value)>>(),
+                vec![(
+                    Binary::pack_key(&[Datum::from_u64(0)]),
+                    Binary::pack(&[Datum::from_u64(1)])
+                )]
+            );
+        }
+
+        #[test]
+        fn write_batch_of_mixed_operations() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)])
+                ]))),
+                Some(Some(2))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.write_batch(
+                    vec![
+                        WriteOp::Update(
+                            Binary::pack_key(&[Datum::from_u64(0)]),
+                            Binary::pack(&[Datum::from_u64(10)])
+                        ),
+                        WriteOp::Delete(Binary::pack_key(&[Datum::from_u64(1)])),
+                        WriteOp::Insert(Binary::pack(&[Datum::from_u64(3)])),
+                        WriteOp::Update(
+                            Binary::pack_key(&[Datum::from_u64(5)]),
+                            Binary::pack(&[Datum::from_u64(50)])
+                        ),
+                    ]
+                ))),
+                Some(Some(3))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select()))
+                    .unwrap()
+                    .unwrap()
+                    .collect::<Vec<(Key, Value)>>(),
+                vec![
+                    (
+                        Binary::pack_key(&[Datum::from_u64(0)]),
+                        Binary::pack(&[Datum::from_u64(10)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(2)]),
+                        Binary::pack(&[Datum::from_u64(3)])
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn table_statistics() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| (table.record_count(), table.size()))),
+                Some(Some((0, 0)))
+            );
+
+            let value = Binary::pack(&[Datum::from_u64(1)]);
+            let key = Binary::pack_key(&[Datum::from_u64(0)]);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.insert(vec![value.clone()]))),
+                Some(Some(1))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| (table.record_count(), table.size()))),
+                Some(Some((1, key.to_bytes().len() + value.to_bytes().len())))
+            );
+        }
+
+        #[test]
+        fn update_table_that_in_schema_that_does_not_exist() {
+            let catalog_handle = catalog();
+
+            assert_eq!(
+                catalog_handle.work_with(DOES_NOT_EXIST, |schema| schema
+                    .work_with(TABLE, |table| table.update(vec![]))),
+                None
+            );
+        }
+
+        #[test]
+        fn update_table_that_does_not_exist() {
             let catalog_handle = catalog();
 
             assert_eq!(catalog_handle.create_schema(SCHEMA), true);
@@ -545,7 +1432,7 @@ mod general_cases {
 
             assert_eq!(
                 catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.update(vec![(
-                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack_key(&[Datum::from_u64(1)]),
                     Binary::pack(&[Datum::from_u64(4)])
                 )]))),
                 Some(Some(1))
@@ -558,10 +1445,351 @@ mod general_cases {
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
                 vec![
-                    (Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)])),
-                    (Binary::pack(&[Datum::from_u64(1)]), Binary::pack(&[Datum::from_u64(4)])),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(0)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(4)])
+                    ),
                 ]
             );
         }
+
+        #[test]
+        fn upgrade_rows_in_batches() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            // Int64 tag followed by the value as it was packed before rows got a format version
+            let legacy = |value: u64| Binary::with_data([&[5][..], &value.to_ne_bytes()].concat());
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    legacy(1),
+                    legacy(2),
+                    legacy(3)
+                ]))),
+                Some(Some(3))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.upgrade_rows(2))),
+                Some(Some(2))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.upgrade_rows(2))),
+                Some(Some(1))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.upgrade_rows(2))),
+                Some(Some(0))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select()))
+                    .unwrap()
+                    .unwrap()
+                    .map(|(_key, value)| value)
+                    .collect::<Vec<Value>>(),
+                vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)]),
+                    Binary::pack(&[Datum::from_u64(3)]),
+                ]
+            );
+        }
+    }
+    #[cfg(test)]
+    mod memory_limits {
+        use super::*;
+
+        fn row(value: &str) -> Value {
+            Binary::pack(&[Datum::from_str(value)])
+        }
+
+        fn size_of(value: &str) -> usize {
+            record_size(&Binary::pack_key(&[Datum::from_u64(0)]), &row(value))
+        }
+
+        #[test]
+        fn insert_within_table_limit() {
+            let catalog_handle = InMemoryCatalogHandle::with_memory_limits(None, Some(2 * size_of("a")));
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.try_insert(vec![row("a"), row("b")]))),
+                Some(Some(Ok(2)))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.try_insert(vec![row("c")]))),
+                Some(Some(Err(OutOfMemory)))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.record_count())),
+                Some(Some(2))
+            );
+        }
+
+        #[test]
+        fn catalog_limit_is_shared_by_tables() {
+            let catalog_handle = InMemoryCatalogHandle::with_memory_limits(Some(size_of("a")), None);
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE_1)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE_2)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE_1, |table| table.try_insert(vec![row("a")]))),
+                Some(Some(Ok(1)))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE_2, |table| table.try_insert(vec![row("b")]))),
+                Some(Some(Err(OutOfMemory)))
+            );
+        }
+
+        #[test]
+        fn deleted_rows_free_memory() {
+            let catalog_handle = InMemoryCatalogHandle::with_memory_limits(None, Some(size_of("a")));
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.try_insert(vec![row("a")]))),
+                Some(Some(Ok(1)))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .delete(vec![Binary::pack_key(&[Datum::from_u64(0)])]))),
+                Some(Some(1))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.try_insert(vec![row("b")]))),
+                Some(Some(Ok(1)))
+            );
+        }
+
+        #[test]
+        fn dropped_table_frees_catalog_memory() {
+            let catalog_handle = InMemoryCatalogHandle::with_memory_limits(Some(size_of("a")), None);
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE_1)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE_1, |table| table.try_insert(vec![row("a")]))),
+                Some(Some(Ok(1)))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.drop_table(TABLE_1)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE_2)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE_2, |table| table.try_insert(vec![row("b")]))),
+                Some(Some(Ok(1)))
+            );
+        }
+
+        #[test]
+        fn update_that_does_not_fit() {
+            let catalog_handle = InMemoryCatalogHandle::with_memory_limits(None, Some(size_of("a")));
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.try_insert(vec![row("a")]))),
+                Some(Some(Ok(1)))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .update(vec![(Binary::pack_key(&[Datum::from_u64(0)]), row("longer"))]))),
+                Some(Some(0))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .update(vec![(Binary::pack_key(&[Datum::from_u64(0)]), row("b"))]))),
+                Some(Some(1))
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod handles {
+        use super::*;
+        use std::io::Write;
+
+        #[test]
+        fn schema_that_does_not_exist() {
+            let catalog_handle = catalog();
+
+            assert!(matches!(
+                catalog_handle.schema(DOES_NOT_EXIST),
+                Err(CatalogError::SchemaNotFound(schema_name)) if schema_name == DOES_NOT_EXIST
+            ));
+            assert_eq!(
+                catalog_handle.with_table(DOES_NOT_EXIST, TABLE, |table| table.record_count()),
+                Err(CatalogError::SchemaNotFound(DOES_NOT_EXIST.to_owned()))
+            );
+        }
+
+        #[test]
+        fn table_that_does_not_exist() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+
+            let schema = catalog_handle.schema(SCHEMA).expect("schema exists");
+            assert!(matches!(
+                schema.table(DOES_NOT_EXIST),
+                Err(CatalogError::TableNotFound(schema_name, table_name)) if schema_name == SCHEMA && table_name == DOES_NOT_EXIST
+            ));
+            drop(schema);
+            assert_eq!(
+                catalog_handle.with_table(SCHEMA, DOES_NOT_EXIST, |table| table.record_count()),
+                Err(CatalogError::TableNotFound(
+                    SCHEMA.to_owned(),
+                    DOES_NOT_EXIST.to_owned()
+                ))
+            );
+        }
+
+        #[test]
+        fn write_through_acquired_handles() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.with_schema(SCHEMA, |schema| schema.create_table(TABLE)),
+                Ok(true)
+            );
+
+            let schema = catalog_handle.schema(SCHEMA).expect("schema exists");
+            let table = schema.table(TABLE).expect("table exists");
+            assert_eq!(table.insert(vec![Binary::pack(&[Datum::from_u64(1)])]), 1);
+            drop(table);
+            drop(schema);
+
+            assert_eq!(
+                catalog_handle.with_table(SCHEMA, TABLE, |table| table.record_count()),
+                Ok(1)
+            );
+        }
+
+        #[test]
+        fn foreign_tables() {
+            let catalog_handle = catalog();
+            let foreign_table = || {
+                ForeignTableHandle::new(
+                    "does_not_exist.csv".into(),
+                    crate::ForeignFormat::Csv { header: false },
+                    vec![],
+                )
+            };
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            let schema = catalog_handle.schema(SCHEMA).expect("schema exists");
+            assert_eq!(schema.create_table(TABLE_1), true);
+            assert_eq!(schema.create_foreign_table(TABLE_2, foreign_table()), true);
+
+            assert_eq!(schema.create_foreign_table(TABLE_1, foreign_table()), false);
+            assert_eq!(schema.create_table(TABLE_2), false);
+            assert_eq!(schema.list_tables(), vec![TABLE_1.to_owned(), TABLE_2.to_owned()]);
+            assert_eq!(schema.table(TABLE_2).map(|table| table.record_count()), Ok(0));
+            assert_eq!(
+                schema.work_with(TABLE_2, |table| table.try_select().is_err()),
+                Some(true)
+            );
+
+            assert_eq!(schema.drop_table(TABLE_2), true);
+            assert!(matches!(
+                schema.table(TABLE_2),
+                Err(CatalogError::TableNotFound(_, table_name)) if table_name == TABLE_2
+            ));
+        }
+
+        #[test]
+        fn scan_foreign_table() {
+            let mut file = tempfile::NamedTempFile::new().expect("to create temporary file");
+            file.write_all(b"1\n2\n").expect("to write file");
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.with_schema(SCHEMA, |schema| schema.create_foreign_table(
+                    TABLE,
+                    ForeignTableHandle::new(
+                        file.path().to_path_buf(),
+                        crate::ForeignFormat::Csv { header: false },
+                        vec![types::SqlType::Integer],
+                    )
+                )),
+                Ok(true)
+            );
+
+            assert_eq!(
+                catalog_handle.with_table(SCHEMA, TABLE, |table| table
+                    .try_select()
+                    .map(|cursor| cursor.map(|(_key, value)| value).collect::<Vec<Value>>())),
+                Ok(Ok(vec![
+                    Binary::pack(&[Datum::from_i32(1)]),
+                    Binary::pack(&[Datum::from_i32(2)])
+                ]))
+            );
+        }
+
+        #[test]
+        fn error_messages() {
+            assert_eq!(
+                CatalogError::SchemaNotFound(SCHEMA.to_owned()).to_string(),
+                "schema \"schema_name\" does not exist"
+            );
+            assert_eq!(
+                CatalogError::TableNotFound(SCHEMA.to_owned(), TABLE.to_owned()).to_string(),
+                "table \"schema_name.table_name\" does not exist"
+            );
+        }
     }
 }
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod columnar;
+mod foreign;
 mod in_memory;
 mod on_disk;
 mod sql;
@@ -20,25 +22,57 @@ use binary::Binary;
 use std::{
     fmt::{self, Debug, Formatter},
     iter::FromIterator,
+    ops::Range,
+    sync::Arc,
 };
 
+pub use columnar::ColumnarTableHandle;
 use definition::{FullTableName, TableDef};
 use definition_operations::{ExecutionError, ExecutionOutcome, SystemOperation};
-pub use in_memory::InMemoryCatalogHandle;
+pub use foreign::{ForeignFormat, ForeignTableHandle};
+pub use in_memory::{InMemoryCatalogHandle, InMemorySchemaTable, InMemoryTableHandle, TableSnapshot};
 pub use on_disk::OnDiskCatalogHandle;
 pub use sql::{in_memory::InMemoryDatabase, on_disk::OnDiskDatabase};
 
 pub type Key = Binary;
 pub type Value = Binary;
+/// handle of a schema or a table acquired from a catalog, dropping the object
+/// waits until the guard is released
+pub type Guard<'c, H> = dashmap::mapref::one::Ref<'c, String, H>;
 
 const DEFINITION_SCHEMA: &str = "DEFINITION_SCHEMA";
 const SCHEMATA_TABLE: &str = "SCHEMATA";
 const TABLES_TABLE: &str = "TABLES";
 const COLUMNS_TABLE: &str = "COLUMNS";
+const DESCRIPTIONS_TABLE: &str = "DESCRIPTIONS";
 
 #[derive(Debug, PartialEq)]
 pub struct StorageError;
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum CatalogError {
+    SchemaNotFound(String),
+    /// schema and table names
+    TableNotFound(String, String),
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogError::SchemaNotFound(schema_name) => write!(f, "schema \"{}\" does not exist", schema_name),
+            CatalogError::TableNotFound(schema_name, table_name) => {
+                write!(f, "table \"{}.{}\" does not exist", schema_name, table_name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+/// write would exceed memory limit of a table or of the whole catalog
+#[derive(Debug, PartialEq)]
+pub struct OutOfMemory;
+
 pub struct Cursor {
     source: Box<dyn Iterator<Item = (Binary, Binary)>>,
 }
@@ -49,6 +83,16 @@ impl Debug for Cursor {
     }
 }
 
+impl Cursor {
+    /// cursor that pulls rows from the source while it is iterated, unlike
+    /// collecting into a cursor that reads all of them upfront
+    pub fn new<I: Iterator<Item = (Binary, Binary)> + 'static>(source: I) -> Cursor {
+        Cursor {
+            source: Box::new(source),
+        }
+    }
+}
+
 impl FromIterator<(Binary, Binary)> for Cursor {
     fn from_iter<T: IntoIterator<Item = (Binary, Binary)>>(iter: T) -> Self {
         Self {
@@ -65,12 +109,50 @@ impl Iterator for Cursor {
     }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum WriteOp {
+    Insert(Value),
+    Update(Key, Value),
+    Delete(Key),
+}
+
 pub trait DataTable {
     fn select(&self) -> Cursor;
+    /// scan of all records that fails if they can't be read, e.g. the file of
+    /// a foreign table does not exist
+    fn try_select(&self) -> Result<Cursor, StorageError> {
+        Ok(self.select())
+    }
+    fn scan_range(&self, range: Range<Key>) -> Cursor;
+    fn scan_prefix(&self, prefix: &Key) -> Cursor;
+    fn get(&self, key: &Key) -> Option<Value>;
+    /// point lookup of a key for checks that don't need the value, e.g.
+    /// enforcement of unique keys
+    fn exists(&self, key: &Key) -> bool {
+        self.get(key).is_some()
+    }
     fn insert(&self, data: Vec<Value>) -> usize;
+    /// inserts rows unless they don't fit into memory limits of the storage
+    fn try_insert(&self, data: Vec<Value>) -> Result<usize, OutOfMemory> {
+        Ok(self.insert(data))
+    }
     fn update(&self, data: Vec<(Key, Value)>) -> usize;
     fn delete(&self, data: Vec<Key>) -> usize;
+    fn write_batch(&self, ops: Vec<WriteOp>) -> usize;
+    fn record_count(&self) -> usize;
+    fn size(&self) -> usize;
     fn next_column_ord(&self) -> u64;
+    /// rewrites up to `batch_size` rows packed with an outdated format version
+    /// and returns how many were rewritten, rows are upgraded lazily by calling
+    /// it until nothing is left
+    fn upgrade_rows(&self, batch_size: usize) -> usize;
+}
+
+fn outdated_rows(cursor: Cursor, batch_size: usize) -> Vec<WriteOp> {
+    cursor
+        .filter_map(|(key, value)| value.upgrade().map(|value| WriteOp::Update(key, value)))
+        .take(batch_size)
+        .collect()
 }
 
 pub trait SchemaHandle {
@@ -78,6 +160,18 @@ pub trait SchemaHandle {
     fn create_table(&self, table_name: &str) -> bool;
     fn drop_table(&self, table_name: &str) -> bool;
     fn work_with<T, F: Fn(&Self::Table) -> T>(&self, table_name: &str, operation: F) -> Option<T>;
+    /// names of tables of the schema in alphabetical order
+    fn list_tables(&self) -> Vec<String>;
+    /// number of records and bytes of the table, `None` if it does not exist
+    // TODO: pg_relation_size() and pg_table_size() could report it, but node keeps
+    //       rows of user tables in data_manager, tables of catalogs hold only
+    //       definitions, so the sizes would not cover the data users see growing
+    fn table_metadata(&self, table_name: &str) -> Option<TableStats> {
+        self.work_with(table_name, |table| TableStats {
+            records: table.record_count(),
+            size: table.size(),
+        })
+    }
 }
 
 pub trait DataCatalog {
@@ -85,6 +179,26 @@ pub trait DataCatalog {
     fn create_schema(&self, schema_name: &str) -> bool;
     fn drop_schema(&self, schema_name: &str) -> bool;
     fn work_with<T, F: Fn(&Self::Schema) -> T>(&self, schema_name: &str, operation: F) -> Option<T>;
+    /// names of schemas of the catalog in alphabetical order
+    fn list_schemas(&self) -> Vec<String>;
+    fn with_schema<T, F: Fn(&Self::Schema) -> T>(&self, schema_name: &str, operation: F) -> Result<T, CatalogError> {
+        self.work_with(schema_name, operation)
+            .ok_or_else(|| CatalogError::SchemaNotFound(schema_name.to_owned()))
+    }
+    fn with_table<T, F: Fn(&<Self::Schema as SchemaHandle>::Table) -> T>(
+        &self,
+        schema_name: &str,
+        table_name: &str,
+        operation: F,
+    ) -> Result<T, CatalogError> {
+        match self.with_schema(schema_name, |schema| schema.work_with(table_name, &operation))? {
+            Some(result) => Ok(result),
+            None => Err(CatalogError::TableNotFound(
+                schema_name.to_owned(),
+                table_name.to_owned(),
+            )),
+        }
+    }
 }
 
 pub trait SqlTable {}
@@ -101,3 +215,42 @@ pub trait Database {
 pub trait CatalogDefinition {
     fn table_definition(&self, table_full_name: &FullTableName) -> Option<Option<TableDef>>;
 }
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TableStats {
+    /// number of records stored in a table
+    pub records: usize,
+    /// number of bytes occupied by keys and values of a table
+    pub size: usize,
+}
+
+pub trait ObjectComments {
+    /// sets comment on a table or on its column if `column_name` is specified,
+    /// `None` comment removes the existing one. Returns `false` if the object
+    /// does not exist
+    fn comment_on(&self, table_full_name: &FullTableName, column_name: Option<&str>, comment: Option<&str>) -> bool;
+    fn comment(&self, table_full_name: &FullTableName, column_name: Option<&str>) -> Option<String>;
+}
+
+/// change of schemas, tables or columns made by an executed system operation
+#[derive(Debug, PartialEq, Clone)]
+pub enum CatalogEvent {
+    SchemaCreated(String),
+    SchemaDropped(String),
+    /// schema and table names
+    TableCreated(String, String),
+    /// schema and table names
+    TableDropped(String, String),
+    /// schema, table and column names
+    ColumnAdded(String, String, String),
+}
+
+pub trait CatalogSubscriber: Send + Sync {
+    fn notify(&self, event: &CatalogEvent);
+}
+
+pub trait CatalogEvents {
+    /// subscriber is notified about changes of the catalog in the order they
+    /// were made, after the system operation that made them succeeded
+    fn subscribe(&self, subscriber: Arc<dyn CatalogSubscriber>);
+}
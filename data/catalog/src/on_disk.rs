@@ -12,14 +12,52 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Cursor, DataCatalog, DataTable, Key, SchemaHandle, Value};
-use binary::Binary;
+use crate::{outdated_rows, CatalogError, Cursor, DataCatalog, DataTable, Guard, Key, SchemaHandle, Value, WriteOp};
+use binary::{Binary, FORMAT_VERSION};
 use dashmap::DashMap;
 use repr::Datum;
-use std::{convert::TryInto, path::PathBuf};
+use sled::{transaction::ConflictableTransactionResult, Transactional};
+use std::{
+    convert::TryInto,
+    ops::Range,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 
 const TABLE_RECORD_IDS_KEY: &str = "__record_counter";
 const STARTING_RECORD_ID: [u8; 8] = 0u64.to_be_bytes();
+const TABLE_FORMAT_VERSION_KEY: &str = "__format_version";
+const TABLE_KEY_FORMAT_KEY: &str = "__key_format";
+/// version of record keys layout. Keys of tables that have no version recorded
+/// are packed with `Binary::pack`, since version `1` with `Binary::pack_key`
+const KEY_FORMAT_VERSION: u8 = 1;
+const TABLE_COLUMN_ORDS_KEY: &str = "__column_ord_counter";
+
+/// rows packed with an outdated format version are upgraded in batches of
+/// the size when a table is opened
+const UPGRADE_BATCH_SIZE: usize = 1024;
+
+const SCHEMA_OPEN_ATTEMPTS: usize = 10;
+
+/// tree that sled creates in every db
+const SLED_DEFAULT_TREE: &str = "__sled__default";
+
+fn metadata_tree_name(table_name: &str) -> String {
+    "__system_metadata_".to_owned() + table_name
+}
+
+/// sled unlocks files of a db from its background io threads after the last
+/// handle is dropped, so reopening right after that is retried for a while
+fn open_schema(path_to_schema: &Path) -> sled::Db {
+    for _ in 1..SCHEMA_OPEN_ATTEMPTS {
+        if let Ok(sled_db) = sled::open(path_to_schema) {
+            return sled_db;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    sled::open(path_to_schema).unwrap()
+}
 
 #[derive(Debug)]
 pub struct OnDiskTableHandle {
@@ -36,10 +74,68 @@ impl OnDiskTableHandle {
                 TABLE_RECORD_IDS_KEY
             )
         );
+        metadata.insert(TABLE_FORMAT_VERSION_KEY, &[FORMAT_VERSION]).unwrap();
+        metadata.insert(TABLE_KEY_FORMAT_KEY, &[KEY_FORMAT_VERSION]).unwrap();
         OnDiskTableHandle { metadata, data }
     }
 
-    fn next_id(&self) -> u64 {
+    /// handle of a table that was created before the schema was opened, record
+    /// keys and rows packed with older layouts are repacked
+    // TODO: rows are upgraded before the table can be used, a large table would
+    //       be better upgraded in background while it is being read and written
+    fn open(metadata: sled::Tree, data: sled::Tree) -> OnDiskTableHandle {
+        let table = OnDiskTableHandle { metadata, data };
+        let key_format_version = match table.metadata.get(TABLE_KEY_FORMAT_KEY) {
+            Ok(Some(version)) => version[0],
+            _ => 0,
+        };
+        if key_format_version < KEY_FORMAT_VERSION {
+            table.repack_keys();
+        }
+        if table.format_version() < FORMAT_VERSION {
+            while table.upgrade_rows(UPGRADE_BATCH_SIZE) == UPGRADE_BATCH_SIZE {}
+        }
+        table
+    }
+
+    /// repacks record keys written with `Binary::pack` by `Binary::pack_key`,
+    /// keys and their format version are written in one transaction
+    // TODO: the transaction keeps all rows of the table in memory
+    fn repack_keys(&self) {
+        let rows = self
+            .data
+            .iter()
+            .map(Result::unwrap)
+            .collect::<Vec<(sled::IVec, sled::IVec)>>();
+        let repacked =
+            (&self.data, &self.metadata).transaction(|(data, metadata)| -> ConflictableTransactionResult<()> {
+                for (key, _value) in rows.iter() {
+                    data.remove(key)?;
+                }
+                for (key, value) in rows.iter() {
+                    let key = Binary::pack_key(&Binary::with_data(key.to_vec()).unpack());
+                    data.insert(key.to_bytes(), value)?;
+                }
+                metadata.insert(TABLE_KEY_FORMAT_KEY, &[KEY_FORMAT_VERSION])?;
+                Ok(())
+            });
+        if let Err(error) = repacked {
+            log::error!("could not repack keys of {} records due to {:?}", rows.len(), error);
+        }
+    }
+
+    /// row format version that all rows of the table are packed with, tables
+    /// created before versioning have no version recorded and report `0`. It
+    /// is updated once `upgrade_rows` finds no outdated rows
+    pub fn format_version(&self) -> u8 {
+        match self.metadata.get(TABLE_FORMAT_VERSION_KEY) {
+            Ok(Some(version)) => version[0],
+            _ => 0,
+        }
+    }
+
+    /// reserves `count` record ids at once, returns the first of them
+    fn next_ids(&self, count: u64) -> u64 {
         let current = match self.metadata.get(TABLE_RECORD_IDS_KEY) {
             Ok(Some(current)) => u64::from_be_bytes(current[0..8].try_into().unwrap()),
             Ok(None) => {
@@ -59,7 +155,7 @@ impl OnDiskTableHandle {
             }
         };
         self.metadata
-            .insert(TABLE_RECORD_IDS_KEY, &((current + 1).to_be_bytes()))
+            .insert(TABLE_RECORD_IDS_KEY, &((current + count).to_be_bytes()))
             .unwrap();
         current
     }
@@ -67,23 +163,60 @@ impl OnDiskTableHandle {
 
 impl DataTable for OnDiskTableHandle {
     fn select(&self) -> Cursor {
+        Cursor::new(
+            self.data
+                .iter()
+                .map(Result::unwrap)
+                .map(|(key, value)| (Binary::with_data(key.to_vec()), Binary::with_data(value.to_vec()))),
+        )
+    }
+
+    fn scan_range(&self, range: Range<Key>) -> Cursor {
+        Cursor::new(
+            self.data
+                .range(range.start.to_bytes()..range.end.to_bytes())
+                .map(Result::unwrap)
+                .map(|(key, value)| (Binary::with_data(key.to_vec()), Binary::with_data(value.to_vec()))),
+        )
+    }
+
+    fn scan_prefix(&self, prefix: &Key) -> Cursor {
+        Cursor::new(
+            self.data
+                .scan_prefix(prefix.to_bytes())
+                .map(Result::unwrap)
+                .map(|(key, value)| (Binary::with_data(key.to_vec()), Binary::with_data(value.to_vec()))),
+        )
+    }
+
+    fn get(&self, key: &Key) -> Option<Value> {
         self.data
-            .iter()
-            .map(Result::unwrap)
-            .map(|(key, value)| (Binary::with_data(key.to_vec()), Binary::with_data(value.to_vec())))
-            .collect::<Cursor>()
+            .get(key.to_bytes())
+            .unwrap()
+            .map(|value| Binary::with_data(value.to_vec()))
+    }
+
+    fn exists(&self, key: &Key) -> bool {
+        self.data.contains_key(key.to_bytes()).unwrap()
     }
 
+    // TODO: records are keyed by their ids, so sled keeps them in insertion order.
+    //       CLUSTER could rewrite them keyed by values of an index, once there are
+    //       indexes, and catalog would need to remember which one a table is clustered on
     fn insert(&self, data: Vec<Value>) -> usize {
-        let mut size = 0;
-        for value in data {
-            let record_id = self.next_id();
-            let key = Binary::pack(&[Datum::from_u64(record_id)]);
-            if self.data.insert(key.to_bytes(), value.to_bytes()).is_ok() {
-                size += 1;
+        let size = data.len();
+        let mut batch = sled::Batch::default();
+        for (value, record_id) in data.into_iter().zip(self.next_ids(size as u64)..) {
+            let key = Binary::pack_key(&[Datum::from_u64(record_id)]);
+            batch.insert(key.to_bytes(), value.to_bytes());
+        }
+        match self.data.apply_batch(batch) {
+            Ok(()) => size,
+            Err(error) => {
+                log::error!("could not insert {} records due to {:?}", size, error);
+                0
             }
         }
-        size
     }
 
     fn update(&self, data: Vec<(Key, Value)>) -> usize {
@@ -114,8 +247,79 @@ impl DataTable for OnDiskTableHandle {
         size
     }
 
+    fn write_batch(&self, ops: Vec<WriteOp>) -> usize {
+        let inserts = ops.iter().filter(|op| matches!(op, WriteOp::Insert(_))).count();
+        let first_id = if inserts > 0 { self.next_ids(inserts as u64) } else { 0 };
+        // keys are checked in the same transaction they are written, so that
+        // updates and deletes of missing keys are skipped as by in memory tables
+        let applied = self.data.transaction(|tx| -> ConflictableTransactionResult<usize> {
+            let mut record_ids = first_id..;
+            let mut applied = 0;
+            for op in ops.iter() {
+                match op {
+                    WriteOp::Insert(value) => {
+                        let record_id = record_ids.next().expect("id is reserved for every insert");
+                        let key = Binary::pack_key(&[Datum::from_u64(record_id)]);
+                        tx.insert(key.to_bytes(), value.to_bytes())?;
+                        applied += 1;
+                    }
+                    WriteOp::Update(key, value) => {
+                        if tx.get(key.to_bytes())?.is_some() {
+                            tx.insert(key.to_bytes(), value.to_bytes())?;
+                            applied += 1;
+                        }
+                    }
+                    WriteOp::Delete(key) => {
+                        if tx.remove(key.to_bytes())?.is_some() {
+                            applied += 1;
+                        }
+                    }
+                }
+            }
+            Ok(applied)
+        });
+        match applied {
+            Ok(applied) => applied,
+            Err(error) => {
+                log::error!("could not apply batch of {} operations due to {:?}", ops.len(), error);
+                0
+            }
+        }
+    }
+
+    fn record_count(&self) -> usize {
+        self.data.len()
+    }
+
+    fn size(&self) -> usize {
+        self.data
+            .iter()
+            .map(Result::unwrap)
+            .map(|(key, value)| key.len() + value.len())
+            .sum()
+    }
+
     fn next_column_ord(&self) -> u64 {
-        unimplemented!()
+        let current = self
+            .metadata
+            .fetch_and_update(TABLE_COLUMN_ORDS_KEY, |current| {
+                let next = current.map_or(1, |current| u64::from_be_bytes(current[0..8].try_into().unwrap()) + 1);
+                Some(next.to_be_bytes().to_vec())
+            })
+            .unwrap();
+        current.map_or(0, |current| u64::from_be_bytes(current[0..8].try_into().unwrap()))
+    }
+
+    fn upgrade_rows(&self, batch_size: usize) -> usize {
+        let outdated = outdated_rows(self.select(), batch_size);
+        let found = outdated.len();
+        let upgraded = self.write_batch(outdated);
+        if found < batch_size && upgraded == found {
+            self.metadata
+                .insert(TABLE_FORMAT_VERSION_KEY, &[FORMAT_VERSION])
+                .unwrap();
+        }
+        upgraded
     }
 }
 
@@ -134,20 +338,36 @@ impl OnDiskSchemaHandle {
             tables: DashMap::default(),
         }
     }
+
+    fn table_exists(&self, table_name: &str) -> bool {
+        self.tables.contains_key(table_name) || self.sled_db.tree_names().contains(&sled::IVec::from(table_name))
+    }
+
+    /// opens the table if it was created before the schema was loaded from disk
+    pub fn table(&self, table_name: &str) -> Result<Guard<'_, OnDiskTableHandle>, CatalogError> {
+        if !self.tables.contains_key(table_name) && self.table_exists(table_name) {
+            let data_tree = self.sled_db.open_tree(table_name).unwrap();
+            let metadata_tree = self.sled_db.open_tree(metadata_tree_name(table_name)).unwrap();
+            self.tables
+                .insert(table_name.to_owned(), OnDiskTableHandle::open(metadata_tree, data_tree));
+        }
+        self.tables
+            .get(table_name)
+            .ok_or_else(|| CatalogError::TableNotFound(self.name.clone(), table_name.to_owned()))
+    }
 }
 
 impl SchemaHandle for OnDiskSchemaHandle {
     type Table = OnDiskTableHandle;
 
+    // TODO: durability is not configurable per table, every tree is journaled by sled.
+    //       UNLOGGED tables could be added once the database has its own WAL to skip
     fn create_table(&self, table_name: &str) -> bool {
-        if self.tables.contains_key(table_name) || self.sled_db.tree_names().contains(&sled::IVec::from(table_name)) {
+        if self.table_exists(table_name) {
             false
         } else {
             let data_tree = self.sled_db.open_tree(table_name).unwrap();
-            let metadata_tree = self
-                .sled_db
-                .open_tree("__system_metadata_".to_owned() + table_name)
-                .unwrap();
+            let metadata_tree = self.sled_db.open_tree(metadata_tree_name(table_name)).unwrap();
             self.tables
                 .insert(table_name.to_owned(), OnDiskTableHandle::new(metadata_tree, data_tree));
             true
@@ -155,27 +375,44 @@ impl SchemaHandle for OnDiskSchemaHandle {
     }
 
     fn drop_table(&self, table_name: &str) -> bool {
-        if !self.tables.contains_key(table_name) {
+        if !self.table_exists(table_name) {
             false
         } else {
             self.tables.remove(table_name);
-            if let Err(sled_error) = self.sled_db.drop_tree(table_name) {
-                log::error!(
-                    "Could not remove table {:?} from schema {:?} due to error {:?}",
-                    table_name,
-                    self.name,
-                    sled_error
-                );
+            for tree in &[table_name.to_owned(), metadata_tree_name(table_name)] {
+                if let Err(sled_error) = self.sled_db.drop_tree(tree) {
+                    log::error!(
+                        "Could not remove table {:?} from schema {:?} due to error {:?}",
+                        table_name,
+                        self.name,
+                        sled_error
+                    );
+                }
             }
             true
         }
     }
 
     fn work_with<T, F: Fn(&Self::Table) -> T>(&self, table_name: &str, operation: F) -> Option<T> {
-        self.tables.get(table_name).map(|table| operation(&*table))
+        self.table(table_name).ok().map(|table| operation(&*table))
+    }
+
+    fn list_tables(&self) -> Vec<String> {
+        let mut names = self
+            .sled_db
+            .tree_names()
+            .into_iter()
+            .filter_map(|name| String::from_utf8(name.to_vec()).ok())
+            .filter(|name| name != SLED_DEFAULT_TREE && !name.starts_with(&metadata_tree_name("")))
+            .collect::<Vec<String>>();
+        names.sort();
+        names
     }
 }
 
+// TODO: there is no hot backup of schemas. sled export iterates trees one by one
+//       and is not a consistent snapshot, point in time restore also requires
+//       archived WAL segments that are not produced yet
 pub struct OnDiskCatalogHandle {
     path_to_catalog: PathBuf,
     schemas: DashMap<String, OnDiskSchemaHandle>,
@@ -189,9 +426,30 @@ impl OnDiskCatalogHandle {
         }
     }
 
+    // TODO: schemas could be placed into other directories if their location
+    //       was kept in catalog metadata instead of derived from the name.
+    //       Tables can't be relocated separately while all of them share the
+    //       schema sled db, and sqlparser has no TABLESPACE clause to set it
     fn path_to_schema(&self, schema_name: &str) -> PathBuf {
         PathBuf::from(&self.path_to_catalog).join(&schema_name)
     }
+
+    /// opens the schema if it was created before the catalog was loaded from disk
+    pub fn schema(&self, schema_name: &str) -> Result<Guard<'_, OnDiskSchemaHandle>, CatalogError> {
+        if !self.schemas.contains_key(schema_name) {
+            let path_to_schema = self.path_to_schema(schema_name);
+            if path_to_schema.exists() {
+                let sled_db = open_schema(&path_to_schema);
+                self.schemas.insert(
+                    schema_name.to_owned(),
+                    OnDiskSchemaHandle::new(schema_name.to_owned(), sled_db),
+                );
+            }
+        }
+        self.schemas
+            .get(schema_name)
+            .ok_or_else(|| CatalogError::SchemaNotFound(schema_name.to_owned()))
+    }
 }
 
 impl DataCatalog for OnDiskCatalogHandle {
@@ -205,7 +463,10 @@ impl DataCatalog for OnDiskCatalogHandle {
             if path_to_schema.exists() {
                 false
             } else {
-                let sled_db = sled::open(path_to_schema).unwrap();
+                // TODO: values are stored uncompressed. sled can compress with zstd
+                //       behind its `compression` feature but only for a whole db,
+                //       per table settings need compression of values in DataTable
+                let sled_db = open_schema(&path_to_schema);
                 self.schemas.insert(
                     schema_name.to_owned(),
                     OnDiskSchemaHandle::new(schema_name.to_owned(), sled_db),
@@ -236,19 +497,20 @@ impl DataCatalog for OnDiskCatalogHandle {
     }
 
     fn work_with<T, F: Fn(&Self::Schema) -> T>(&self, schema_name: &str, operation: F) -> Option<T> {
-        if !self.schemas.contains_key(schema_name) {
-            let path_to_schema = self.path_to_schema(schema_name);
-            if path_to_schema.exists() {
-                let sled_db = sled::open(path_to_schema).unwrap();
-                self.schemas.insert(
-                    schema_name.to_owned(),
-                    OnDiskSchemaHandle::new(schema_name.to_owned(), sled_db),
-                );
-            } else {
-                return None;
-            }
-        }
-        self.schemas.get(schema_name).map(|schema| operation(&*schema))
+        self.schema(schema_name).ok().map(|schema| operation(&*schema))
+    }
+
+    fn list_schemas(&self) -> Vec<String> {
+        let mut names = match std::fs::read_dir(&self.path_to_catalog) {
+            Ok(entries) => entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect::<Vec<String>>(),
+            Err(_) => vec![],
+        };
+        names.sort();
+        names
     }
 }
 
@@ -290,6 +552,249 @@ mod catalog_persistence_cases {
 
         assert_eq!(catalog.work_with("schema_name", |_schema| 1), Some(1));
     }
+
+    #[test]
+    fn handles_are_acquired_after_catalog_recreation() {
+        let (catalog, path) = catalog_and_path();
+
+        assert_eq!(catalog.create_schema("schema_name"), true);
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(true)
+        );
+
+        drop(catalog);
+
+        let catalog = OnDiskCatalogHandle::new(path);
+
+        let schema = catalog.schema("schema_name").expect("schema exists");
+        assert_eq!(schema.table("table_name").map(|table| table.record_count()), Ok(0));
+        assert!(matches!(
+            schema.table("does_not_exist"),
+            Err(CatalogError::TableNotFound(schema_name, table_name)) if schema_name == "schema_name" && table_name == "does_not_exist"
+        ));
+    }
+
+    #[test]
+    fn keys_packed_before_key_format_are_repacked() {
+        let (catalog, path) = catalog_and_path();
+
+        assert_eq!(catalog.create_schema("schema_name"), true);
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(true)
+        );
+        // keys of tables without key format are packed by `Binary::pack` without
+        // row format version, `5` tags `i64` values
+        let legacy_key = |record_id: u64| {
+            let mut key = vec![5];
+            key.extend_from_slice(&record_id.to_ne_bytes());
+            key
+        };
+        {
+            let schema = catalog.schema("schema_name").expect("schema exists");
+            let table = schema.table("table_name").expect("table exists");
+            table.metadata.remove(TABLE_KEY_FORMAT_KEY).expect("no io error");
+            for record_id in &[256, 1, 2] {
+                table
+                    .data
+                    .insert(
+                        legacy_key(*record_id),
+                        Binary::pack(&[Datum::from_u64(*record_id)]).to_bytes(),
+                    )
+                    .expect("no io error");
+            }
+        }
+
+        drop(catalog);
+
+        let catalog = OnDiskCatalogHandle::new(path);
+
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema
+                .work_with("table_name", |table| table.select().collect::<Vec<(Key, Value)>>())),
+            Some(Some(
+                [1, 2, 256]
+                    .iter()
+                    .map(|record_id| (
+                        Binary::pack_key(&[Datum::from_u64(*record_id)]),
+                        Binary::pack(&[Datum::from_u64(*record_id)])
+                    ))
+                    .collect::<Vec<(Key, Value)>>()
+            ))
+        );
+    }
+
+    #[test]
+    fn rows_of_outdated_format_are_upgraded_at_open() {
+        let (catalog, path) = catalog_and_path();
+
+        assert_eq!(catalog.create_schema("schema_name"), true);
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(true)
+        );
+        // Int64 tag followed by the value as it was packed before rows got a format version
+        let legacy = |value: u64| Binary::with_data([&[5][..], &value.to_ne_bytes()].concat());
+        {
+            let schema = catalog.schema("schema_name").expect("schema exists");
+            let table = schema.table("table_name").expect("table exists");
+            table.metadata.remove(TABLE_FORMAT_VERSION_KEY).expect("no io error");
+            assert_eq!(table.insert(vec![legacy(1), legacy(2)]), 2);
+            assert_eq!(table.format_version(), 0);
+        }
+
+        drop(catalog);
+
+        let catalog = OnDiskCatalogHandle::new(path);
+
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema
+                .work_with("table_name", |table| table.format_version())),
+            Some(Some(FORMAT_VERSION))
+        );
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.work_with("table_name", |table| table
+                .select()
+                .map(|(_key, value)| value)
+                .collect::<Vec<Value>>())),
+            Some(Some(vec![
+                Binary::pack(&[Datum::from_u64(1)]),
+                Binary::pack(&[Datum::from_u64(2)])
+            ]))
+        );
+    }
+
+    #[test]
+    fn schemas_and_tables_are_listed_after_handle_recreation() {
+        let (catalog, path) = catalog_and_path();
+
+        assert_eq!(catalog.create_schema("schema_2"), true);
+        assert_eq!(catalog.create_schema("schema_1"), true);
+        assert_eq!(
+            catalog.work_with("schema_1", |schema| schema.create_table("table_2")),
+            Some(true)
+        );
+        assert_eq!(
+            catalog.work_with("schema_1", |schema| schema.create_table("table_1")),
+            Some(true)
+        );
+
+        drop(catalog);
+
+        let catalog = OnDiskCatalogHandle::new(path);
+
+        assert_eq!(
+            catalog.list_schemas(),
+            vec!["schema_1".to_owned(), "schema_2".to_owned()]
+        );
+        assert_eq!(
+            catalog.work_with("schema_1", |schema| schema.list_tables()),
+            Some(vec!["table_1".to_owned(), "table_2".to_owned()])
+        );
+        assert_eq!(
+            catalog.work_with("schema_2", |schema| schema.list_tables()),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn tables_should_exist_after_handle_recreation() {
+        let (catalog, path) = catalog_and_path();
+
+        assert_eq!(catalog.create_schema("schema_name"), true);
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(true)
+        );
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.work_with("table_name", |table| table
+                .insert(vec![Binary::pack(&[Datum::from_u64(1)])]))),
+            Some(Some(1))
+        );
+
+        drop(catalog);
+
+        let catalog = OnDiskCatalogHandle::new(path);
+
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(false)
+        );
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.work_with("table_name", |table| table
+                .insert(vec![Binary::pack(&[Datum::from_u64(2)])]))),
+            Some(Some(1))
+        );
+        assert_eq!(
+            catalog
+                .work_with("schema_name", |schema| schema
+                    .work_with("table_name", |table| table.select()))
+                .unwrap()
+                .unwrap()
+                .collect::<Vec<(Key, Value)>>(),
+            vec![
+                (
+                    Binary::pack_key(&[Datum::from_u64(0)]),
+                    Binary::pack(&[Datum::from_u64(1)])
+                ),
+                (
+                    Binary::pack_key(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)])
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn column_ords_should_continue_after_handle_recreation() {
+        let (catalog, path) = catalog_and_path();
+
+        assert_eq!(catalog.create_schema("schema_name"), true);
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(true)
+        );
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema
+                .work_with("table_name", |table| table.next_column_ord())),
+            Some(Some(0))
+        );
+
+        drop(catalog);
+
+        let catalog = OnDiskCatalogHandle::new(path);
+
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema
+                .work_with("table_name", |table| table.next_column_ord())),
+            Some(Some(1))
+        );
+    }
+
+    #[test]
+    fn recreate_table_dropped_after_handle_recreation() {
+        let (catalog, path) = catalog_and_path();
+
+        assert_eq!(catalog.create_schema("schema_name"), true);
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(true)
+        );
+
+        drop(catalog);
+
+        let catalog = OnDiskCatalogHandle::new(path);
+
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.drop_table("table_name")),
+            Some(true)
+        );
+        assert_eq!(
+            catalog.work_with("schema_name", |schema| schema.create_table("table_name")),
+            Some(true)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -553,7 +1058,10 @@ mod general_cases {
                     .unwrap()
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
-                vec![(Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)]))]
+                vec![(
+                    Binary::pack_key(&[Datum::from_u64(0)]),
+                    Binary::pack(&[Datum::from_u64(1)])
+                )]
             );
         }
 
@@ -582,12 +1090,183 @@ mod general_cases {
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
                 vec![
-                    (Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)])),
-                    (Binary::pack(&[Datum::from_u64(1)]), Binary::pack(&[Datum::from_u64(2)]))
+                    (
+                        Binary::pack_key(&[Datum::from_u64(0)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(2)])
+                    )
+                ]
+            );
+        }
+
+        #[test]
+        fn scan_range_of_records() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .insert((0..5).map(|id| Binary::pack(&[Datum::from_u64(id)])).collect()))),
+                Some(Some(5))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.scan_range(
+                        Binary::pack_key(&[Datum::from_u64(1)])..Binary::pack_key(&[Datum::from_u64(3)])
+                    )))
+                    .unwrap()
+                    .unwrap()
+                    .collect::<Vec<(Key, Value)>>(),
+                vec![
+                    (
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(2)]),
+                        Binary::pack(&[Datum::from_u64(2)])
+                    ),
                 ]
             );
         }
 
+        #[test]
+        fn scan_records_by_prefix() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .insert((0..5).map(|id| Binary::pack(&[Datum::from_u64(id)])).collect()))),
+                Some(Some(5))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                        .scan_prefix(&Binary::pack_key(&[Datum::from_u64(3)]))))
+                    .unwrap()
+                    .unwrap()
+                    .collect::<Vec<(Key, Value)>>(),
+                vec![(
+                    Binary::pack_key(&[Datum::from_u64(3)]),
+                    Binary::pack(&[Datum::from_u64(3)])
+                )]
+            );
+        }
+
+        #[test]
+        fn get_record_by_key() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)])
+                ]))),
+                Some(Some(2))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.get(&Binary::pack_key(&[Datum::from_u64(1)])))),
+                Some(Some(Some(Binary::pack(&[Datum::from_u64(2)]))))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.get(&Binary::pack_key(&[Datum::from_u64(2)])))),
+                Some(Some(None))
+            );
+        }
+
+        #[test]
+        fn record_exists() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.insert(vec![Binary::pack(&[Datum::from_u64(1)])]))),
+                Some(Some(1))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.exists(&Binary::pack_key(&[Datum::from_u64(0)])))),
+                Some(Some(true))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.exists(&Binary::pack_key(&[Datum::from_u64(1)])))),
+                Some(Some(false))
+            );
+        }
+
+        #[test]
+        fn batches_of_records_take_consecutive_keys() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)]),
+                    Binary::pack(&[Datum::from_u64(3)]),
+                ]))),
+                Some(Some(3))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(4)]),
+                    Binary::pack(&[Datum::from_u64(5)]),
+                ]))),
+                Some(Some(2))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table
+                    .select()
+                    .map(|(key, value)| (key.unpack_key(), value))
+                    .collect::<Vec<(Vec<Datum>, Binary)>>())),
+                Some(Some(
+                    (1..=5)
+                        .map(|value| (
+                            vec![Datum::from_i64(value as i64 - 1)],
+                            Binary::pack(&[Datum::from_u64(value)])
+                        ))
+                        .collect()
+                ))
+            );
+        }
+
         #[test]
         fn delete_from_table_that_in_schema_that_does_not_exist() {
             let catalog_handle = catalog();
@@ -641,7 +1320,96 @@ mod general_cases {
                     .unwrap()
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
-                vec![(Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)])),]
+                vec![(
+                    Binary::pack_key(&[Datum::from_u64(0)]),
+                    Binary::pack(&[Datum::from_u64(1)])
+                ),]
+            );
+        }
+
+        #[test]
+        fn write_batch_of_mixed_operations() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)])
+                ]))),
+                Some(Some(2))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.write_batch(
+                    vec![
+                        WriteOp::Update(
+                            Binary::pack_key(&[Datum::from_u64(0)]),
+                            Binary::pack(&[Datum::from_u64(10)])
+                        ),
+                        WriteOp::Delete(Binary::pack_key(&[Datum::from_u64(1)])),
+                        WriteOp::Insert(Binary::pack(&[Datum::from_u64(3)])),
+                        WriteOp::Update(
+                            Binary::pack_key(&[Datum::from_u64(5)]),
+                            Binary::pack(&[Datum::from_u64(50)])
+                        ),
+                    ]
+                ))),
+                Some(Some(3))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select()))
+                    .unwrap()
+                    .unwrap()
+                    .collect::<Vec<(Key, Value)>>(),
+                vec![
+                    (
+                        Binary::pack_key(&[Datum::from_u64(0)]),
+                        Binary::pack(&[Datum::from_u64(10)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(2)]),
+                        Binary::pack(&[Datum::from_u64(3)])
+                    ),
+                ]
+            );
+        }
+
+        #[test]
+        fn table_statistics() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| (table.record_count(), table.size()))),
+                Some(Some((0, 0)))
+            );
+
+            let value = Binary::pack(&[Datum::from_u64(1)]);
+            let key = Binary::pack_key(&[Datum::from_u64(0)]);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| table.insert(vec![value.clone()]))),
+                Some(Some(1))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema
+                    .work_with(TABLE, |table| (table.record_count(), table.size()))),
+                Some(Some((1, key.to_bytes().len() + value.to_bytes().len())))
             );
         }
 
@@ -688,7 +1456,7 @@ mod general_cases {
 
             assert_eq!(
                 catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.update(vec![(
-                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack_key(&[Datum::from_u64(1)]),
                     Binary::pack(&[Datum::from_u64(4)])
                 )]))),
                 Some(Some(1))
@@ -701,10 +1469,81 @@ mod general_cases {
                     .unwrap()
                     .collect::<Vec<(Key, Value)>>(),
                 vec![
-                    (Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_u64(1)])),
-                    (Binary::pack(&[Datum::from_u64(1)]), Binary::pack(&[Datum::from_u64(4)])),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(0)]),
+                        Binary::pack(&[Datum::from_u64(1)])
+                    ),
+                    (
+                        Binary::pack_key(&[Datum::from_u64(1)]),
+                        Binary::pack(&[Datum::from_u64(4)])
+                    ),
                 ]
             );
         }
+
+        #[test]
+        fn upgrade_rows_in_batches() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            // Int64 tag followed by the value as it was packed before rows got a format version
+            let legacy = |value: u64| Binary::with_data([&[5][..], &value.to_ne_bytes()].concat());
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.insert(vec![
+                    legacy(1),
+                    legacy(2),
+                    legacy(3)
+                ]))),
+                Some(Some(3))
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.upgrade_rows(2))),
+                Some(Some(2))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.upgrade_rows(2))),
+                Some(Some(1))
+            );
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.upgrade_rows(2))),
+                Some(Some(0))
+            );
+
+            assert_eq!(
+                catalog_handle
+                    .work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.select()))
+                    .unwrap()
+                    .unwrap()
+                    .map(|(_key, value)| value)
+                    .collect::<Vec<Value>>(),
+                vec![
+                    Binary::pack(&[Datum::from_u64(1)]),
+                    Binary::pack(&[Datum::from_u64(2)]),
+                    Binary::pack(&[Datum::from_u64(3)]),
+                ]
+            );
+        }
+
+        #[test]
+        fn format_version_of_created_table() {
+            let catalog_handle = catalog();
+
+            assert_eq!(catalog_handle.create_schema(SCHEMA), true);
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.create_table(TABLE)),
+                Some(true)
+            );
+
+            assert_eq!(
+                catalog_handle.work_with(SCHEMA, |schema| schema.work_with(TABLE, |table| table.format_version())),
+                Some(Some(FORMAT_VERSION))
+            );
+        }
     }
 }
@@ -0,0 +1,565 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    CatalogEvent, CatalogSubscriber, DataCatalog, DataTable, SchemaHandle, WriteOp, COLUMNS_TABLE, DEFINITION_SCHEMA,
+    DESCRIPTIONS_TABLE, SCHEMATA_TABLE, TABLES_TABLE,
+};
+use binary::Binary;
+use definition::{ColumnDef, FullTableName, TableDef};
+use definition_operations::{
+    ExecutionError, ExecutionOutcome, Kind, ObjectState, Record, Step, SystemObject, SystemOperation,
+};
+use repr::Datum;
+use std::sync::{Arc, RwLock};
+use types::{Collation, SqlType};
+
+fn create_public_schema() -> SystemOperation {
+    SystemOperation {
+        kind: Kind::Create(SystemObject::Schema),
+        skip_steps_if: None,
+        steps: vec![vec![
+            Step::CheckExistence {
+                system_object: SystemObject::Schema,
+                object_name: vec!["public".to_owned()],
+            },
+            Step::CreateFolder {
+                name: "public".to_owned(),
+            },
+            Step::CreateRecord {
+                system_schema: DEFINITION_SCHEMA.to_owned(),
+                system_table: SCHEMATA_TABLE.to_owned(),
+                record: Record::Schema {
+                    catalog_name: "".to_owned(),
+                    schema_name: "public".to_owned(),
+                },
+            },
+        ]],
+    }
+}
+
+fn described_object<'o>(
+    catalog: Datum<'o>,
+    full_table_name: &'o FullTableName,
+    column_name: Option<&'o str>,
+) -> Vec<Datum<'o>> {
+    let mut object = full_table_name.raw(catalog);
+    object.push(match column_name {
+        Some(column_name) => Datum::from_str(column_name),
+        None => Datum::from_null(),
+    });
+    object
+}
+
+/// keeps definitions of schemas, tables and their columns as records of
+/// system tables in `DEFINITION_SCHEMA` of the underlying data catalog, so
+/// metadata is stored the same way as user data
+pub(crate) struct DataDefinition<C: DataCatalog> {
+    catalog_name: String,
+    catalog: C,
+    subscribers: RwLock<Vec<Arc<dyn CatalogSubscriber>>>,
+}
+
+impl<C: DataCatalog> DataDefinition<C> {
+    pub(crate) fn new(catalog_name: &str, catalog: C) -> DataDefinition<C> {
+        DataDefinition {
+            catalog_name: catalog_name.to_owned(),
+            catalog,
+            subscribers: RwLock::default(),
+        }
+    }
+
+    pub(crate) fn subscribe(&self, subscriber: Arc<dyn CatalogSubscriber>) {
+        self.subscribers
+            .write()
+            .expect("subscribers lock is not poisoned")
+            .push(subscriber);
+    }
+
+    /// creates system tables and `public` schema unless the catalog already
+    /// has them, e.g. when it was opened from disk
+    pub(crate) fn bootstrap(self) -> DataDefinition<C> {
+        if !self.catalog.create_schema(DEFINITION_SCHEMA) {
+            return self;
+        }
+        let system_tables = self.catalog.with_schema(DEFINITION_SCHEMA, |schema| {
+            schema.create_table(SCHEMATA_TABLE);
+            schema.create_table(TABLES_TABLE);
+            schema.create_table(COLUMNS_TABLE);
+            schema.create_table(DESCRIPTIONS_TABLE);
+        });
+        debug_assert!(
+            matches!(system_tables, Ok(())),
+            "System tables have to be created, but failed due to {:?}",
+            system_tables
+        );
+        let public_schema = self.execute(create_public_schema());
+        debug_assert!(
+            matches!(public_schema, Ok(_)),
+            "Default `public` schema has to be created, but failed due to {:?}",
+            public_schema
+        );
+        self
+    }
+
+    fn catalog_name(&self) -> Datum {
+        Datum::from_str(&self.catalog_name)
+    }
+
+    /// system tables are created on bootstrap and are never dropped
+    fn system_table<T, F: Fn(&<C::Schema as SchemaHandle>::Table) -> T>(&self, table_name: &str, operation: F) -> T {
+        match self.catalog.with_table(DEFINITION_SCHEMA, table_name, operation) {
+            Ok(result) => result,
+            Err(error) => panic!("system table has to be bootstrapped, but {}", error),
+        }
+    }
+
+    fn schema_exists(&self, schema_name: &str) -> bool {
+        let full_schema_name = Binary::pack(&[self.catalog_name(), Datum::from_str(schema_name)]);
+        self.system_table(SCHEMATA_TABLE, |table| {
+            table.select().any(|(_key, value)| value == full_schema_name)
+        })
+    }
+
+    fn table_exists(&self, full_table_name: &FullTableName) -> bool {
+        let full_table_name = Binary::pack(&full_table_name.raw(self.catalog_name()));
+        self.system_table(TABLES_TABLE, |table| {
+            table.select().any(|(_key, value)| value == full_table_name)
+        })
+    }
+
+    fn table_columns(&self, full_table_name: &FullTableName) -> Vec<ColumnDef> {
+        let full_table_name = Binary::pack(&full_table_name.raw(self.catalog_name()));
+        self.system_table(COLUMNS_TABLE, |table| {
+            table
+                .select()
+                .filter(|(_key, value)| value.start_with_row(&full_table_name))
+                .map(|(_key, value)| {
+                    let row = value.unpack();
+                    let name = row[3].as_str().to_owned();
+                    let sql_type = SqlType::from_type_id(row[4].as_u64(), row[5].as_u64());
+                    let ord_num = row[6].as_u64() as usize;
+                    let collation = match &row[7] {
+                        Datum::Null => None,
+                        name => Collation::from_name(name.as_str()),
+                    };
+                    ColumnDef::new(name, sql_type, ord_num, collation)
+                })
+                .collect()
+        })
+    }
+
+    pub(crate) fn table_definition(&self, full_table_name: &FullTableName) -> Option<Option<TableDef>> {
+        if !(self.schema_exists(full_table_name.schema())) {
+            return None;
+        }
+        if !(self.table_exists(full_table_name)) {
+            return Some(None);
+        }
+        let column_info = self.table_columns(full_table_name);
+        Some(Some(TableDef::new(full_table_name, column_info)))
+    }
+
+    pub(crate) fn comment_on(
+        &self,
+        full_table_name: &FullTableName,
+        column_name: Option<&str>,
+        comment: Option<&str>,
+    ) -> bool {
+        match (self.table_definition(full_table_name), column_name) {
+            (Some(Some(table_def)), Some(column_name)) if table_def.has_column(column_name) => {}
+            (Some(Some(_)), None) => {}
+            _ => return false,
+        }
+        let object = described_object(self.catalog_name(), full_table_name, column_name);
+        let object_id = Binary::pack(&object);
+        self.system_table(DESCRIPTIONS_TABLE, |table| {
+            let current = table
+                .select()
+                .find(|(_key, value)| value.start_with_row(&object_id))
+                .map(|(key, _value)| key);
+            let mut row = object.clone();
+            match (current, comment) {
+                (Some(key), Some(comment)) => {
+                    row.push(Datum::from_str(comment));
+                    table.write_batch(vec![WriteOp::Update(key, Binary::pack(&row))]);
+                }
+                (None, Some(comment)) => {
+                    row.push(Datum::from_str(comment));
+                    table.insert(vec![Binary::pack(&row)]);
+                }
+                (Some(key), None) => {
+                    table.write_batch(vec![WriteOp::Delete(key)]);
+                }
+                (None, None) => {}
+            }
+        });
+        true
+    }
+
+    pub(crate) fn comment(&self, full_table_name: &FullTableName, column_name: Option<&str>) -> Option<String> {
+        let object_id = Binary::pack(&described_object(self.catalog_name(), full_table_name, column_name));
+        self.system_table(DESCRIPTIONS_TABLE, |table| {
+            table
+                .select()
+                .find(|(_key, value)| value.start_with_row(&object_id))
+                .map(|(_key, value)| value.unpack()[4].as_str().to_owned())
+        })
+    }
+
+    pub(crate) fn execute(&self, operation: SystemOperation) -> Result<ExecutionOutcome, ExecutionError> {
+        let mut events = vec![];
+        let outcome = self.apply(operation, &mut events)?;
+        let subscribers = self.subscribers.read().expect("subscribers lock is not poisoned");
+        for event in events {
+            for subscriber in subscribers.iter() {
+                subscriber.notify(&event);
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn apply(
+        &self,
+        operation: SystemOperation,
+        events: &mut Vec<CatalogEvent>,
+    ) -> Result<ExecutionOutcome, ExecutionError> {
+        let SystemOperation {
+            kind,
+            skip_steps_if,
+            steps,
+        } = operation;
+        let end = steps.len();
+        let mut index = 0;
+        while index < end {
+            let operations = &steps[index];
+            index += 1;
+            for operation in operations {
+                println!("{:?}", operation);
+                match operation {
+                    Step::CheckExistence {
+                        system_object,
+                        object_name,
+                    } => match system_object {
+                        SystemObject::Schema => {
+                            let result = self.system_table(SCHEMATA_TABLE, |table| {
+                                table.select().any(|(_key, value)| {
+                                    value == Binary::pack(&[self.catalog_name(), Datum::from_str(&object_name[0])])
+                                })
+                            });
+                            match skip_steps_if {
+                                None => {
+                                    if let (&Kind::Create(SystemObject::Schema), true) = (&kind, result) {
+                                        return Err(ExecutionError::SchemaAlreadyExists(object_name[0].to_owned()));
+                                    }
+                                    if let (&Kind::Drop(SystemObject::Schema), false) = (&kind, result) {
+                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
+                                    }
+                                    if let (&Kind::Create(SystemObject::Table), false) = (&kind, result) {
+                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
+                                    }
+                                    if let (&Kind::Drop(SystemObject::Table), false) = (&kind, result) {
+                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
+                                    }
+                                    if let (&Kind::Alter(SystemObject::Table), false) = (&kind, result) {
+                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
+                                    }
+                                }
+                                Some(ObjectState::NotExists) => {
+                                    if !result {
+                                        break;
+                                    }
+                                }
+                                Some(ObjectState::Exists) => {
+                                    if let (&Kind::Create(SystemObject::Schema), true) = (&kind, result) {
+                                        break;
+                                    }
+                                    if let (&Kind::Create(SystemObject::Table), false) = (&kind, result) {
+                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
+                                    }
+                                }
+                            }
+                        }
+                        SystemObject::Table => {
+                            let result = self.system_table(TABLES_TABLE, |table| {
+                                table.select().any(|(_key, value)| {
+                                    value
+                                        == Binary::pack(&[
+                                            self.catalog_name(),
+                                            Datum::from_str(&object_name[0]),
+                                            Datum::from_str(&object_name[1]),
+                                        ])
+                                })
+                            });
+                            match skip_steps_if {
+                                None => {
+                                    if let (&Kind::Create(SystemObject::Table), true) = (&kind, result) {
+                                        return Err(ExecutionError::TableAlreadyExists(
+                                            object_name[0].to_owned(),
+                                            object_name[1].to_owned(),
+                                        ));
+                                    }
+                                    if let (&Kind::Drop(SystemObject::Table), false) = (&kind, result) {
+                                        return Err(ExecutionError::TableDoesNotExist(
+                                            object_name[0].to_owned(),
+                                            object_name[1].to_owned(),
+                                        ));
+                                    }
+                                    if let (&Kind::Alter(SystemObject::Table), false) = (&kind, result) {
+                                        return Err(ExecutionError::TableDoesNotExist(
+                                            object_name[0].to_owned(),
+                                            object_name[1].to_owned(),
+                                        ));
+                                    }
+                                }
+                                Some(ObjectState::NotExists) => {
+                                    if !result {
+                                        break;
+                                    }
+                                }
+                                Some(ObjectState::Exists) => {
+                                    if result {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    Step::CheckDependants {
+                        system_object,
+                        object_name,
+                    } => match system_object {
+                        SystemObject::Schema => {
+                            let schema_id = Binary::pack(&[self.catalog_name(), Datum::from_str(&object_name[0])]);
+                            let has_tables = self.system_table(TABLES_TABLE, |table| {
+                                table.select().any(|(_key, value)| value.start_with_row(&schema_id))
+                            });
+
+                            if has_tables {
+                                return Err(ExecutionError::SchemaHasDependentObjects(object_name[0].to_owned()));
+                            }
+                        }
+                        SystemObject::Table => {}
+                    },
+                    Step::RemoveDependants { .. } => {}
+                    Step::RemoveColumns { .. } => {}
+                    Step::CreateFolder { name } => {
+                        self.catalog.create_schema(&name);
+                    }
+                    Step::RemoveFolder { name } => {
+                        self.catalog.drop_schema(&name);
+                        return Ok(ExecutionOutcome::SchemaDropped);
+                    }
+                    Step::CreateFile { folder_name, name } => {
+                        let created = self
+                            .catalog
+                            .with_schema(folder_name, |schema| schema.create_table(name));
+                        debug_assert!(
+                            matches!(created, Ok(_)),
+                            "file {:?} has to be created in existing folder, but failed due to {:?}",
+                            name,
+                            created
+                        );
+                    }
+                    Step::RemoveFile { .. } => {}
+                    Step::RemoveRecord {
+                        system_schema: _system_schema,
+                        system_table: _system_table,
+                        record,
+                    } => match record {
+                        Record::Schema {
+                            catalog_name: _catalog_name,
+                            schema_name,
+                        } => {
+                            let full_schema_name = Binary::pack(&[self.catalog_name(), Datum::from_str(&schema_name)]);
+                            self.system_table(SCHEMATA_TABLE, |table| {
+                                let schema_id = table
+                                    .select()
+                                    .find(|(_key, value)| value == &full_schema_name)
+                                    .map(|(key, _value)| key);
+                                debug_assert!(
+                                    matches!(schema_id, Some(_)),
+                                    "record for {:?} schema had to be found in {:?} system table",
+                                    schema_name,
+                                    SCHEMATA_TABLE
+                                );
+                                let schema_id = schema_id.unwrap();
+                                table.write_batch(vec![WriteOp::Delete(schema_id)]);
+                            });
+                            events.push(CatalogEvent::SchemaDropped(schema_name.to_owned()));
+                        }
+                        Record::Table {
+                            catalog_name: _catalog_name,
+                            schema_name,
+                            table_name,
+                        } => {
+                            let full_table_name = Binary::pack(&[
+                                self.catalog_name(),
+                                Datum::from_str(schema_name),
+                                Datum::from_str(table_name),
+                            ]);
+                            self.system_table(TABLES_TABLE, |table| {
+                                let table_id = table
+                                    .select()
+                                    .find(|(_key, value)| value == &full_table_name)
+                                    .map(|(key, _value)| key);
+                                debug_assert!(
+                                    matches!(table_id, Some(_)),
+                                    "record for {:?}.{:?} table had to be found in {:?} system table",
+                                    schema_name,
+                                    table_name,
+                                    TABLES_TABLE
+                                );
+                                println!("FOUND TABLE ID - {:?}", table_id);
+                                let table_id = table_id.unwrap();
+                                table.write_batch(vec![WriteOp::Delete(table_id)]);
+                                let table_id = table
+                                    .select()
+                                    .find(|(_key, value)| value == &full_table_name)
+                                    .map(|(key, _value)| key);
+                                println!("TABLE ID AFTER DROP - {:?}", table_id);
+                            });
+                            self.system_table(DESCRIPTIONS_TABLE, |table| {
+                                let comments = table
+                                    .select()
+                                    .filter(|(_key, value)| value.start_with_row(&full_table_name))
+                                    .map(|(key, _value)| WriteOp::Delete(key))
+                                    .collect();
+                                table.write_batch(comments);
+                            });
+                            events.push(CatalogEvent::TableDropped(
+                                schema_name.to_owned(),
+                                table_name.to_owned(),
+                            ));
+                        }
+                        Record::Column {
+                            catalog_name: _catalog_name,
+                            schema_name,
+                            table_name,
+                            column_name,
+                            ..
+                        } => {
+                            let column_id = Binary::pack(&[
+                                self.catalog_name(),
+                                Datum::from_str(schema_name),
+                                Datum::from_str(table_name),
+                                Datum::from_str(column_name),
+                            ]);
+                            self.system_table(COLUMNS_TABLE, |table| {
+                                let columns = table
+                                    .select()
+                                    .filter(|(_key, value)| value.start_with_row(&column_id))
+                                    .map(|(key, _value)| WriteOp::Delete(key))
+                                    .collect();
+                                table.write_batch(columns);
+                            });
+                        }
+                    },
+                    Step::CreateRecord {
+                        system_schema: _system_schema,
+                        system_table: _system_table,
+                        record,
+                    } => match record {
+                        Record::Schema {
+                            catalog_name: _catalog_name,
+                            schema_name,
+                        } => {
+                            self.system_table(SCHEMATA_TABLE, |table| {
+                                table.insert(vec![Binary::pack(&[
+                                    self.catalog_name(),
+                                    Datum::from_str(&schema_name),
+                                ])])
+                            });
+                            events.push(CatalogEvent::SchemaCreated(schema_name.to_owned()));
+                            return Ok(ExecutionOutcome::SchemaCreated);
+                        }
+                        Record::Table {
+                            catalog_name: _catalog_name,
+                            schema_name,
+                            table_name,
+                        } => {
+                            let full_table_name = Binary::pack(&[
+                                self.catalog_name(),
+                                Datum::from_str(&schema_name),
+                                Datum::from_str(&table_name),
+                            ]);
+                            self.system_table(TABLES_TABLE, |table| {
+                                table.insert(vec![Binary::pack(&[
+                                    self.catalog_name(),
+                                    Datum::from_str(&schema_name),
+                                    Datum::from_str(&table_name),
+                                ])]);
+                                let table_id = table
+                                    .select()
+                                    .find(|(_key, value)| value == &full_table_name)
+                                    .map(|(key, _value)| key);
+                                println!("GENERATED TABLE ID - {:?}", table_id);
+                            });
+                            events.push(CatalogEvent::TableCreated(
+                                schema_name.to_owned(),
+                                table_name.to_owned(),
+                            ));
+                        }
+                        Record::Column {
+                            catalog_name: _catalog_name,
+                            schema_name,
+                            table_name,
+                            column_name,
+                            sql_type,
+                            collation,
+                        } => {
+                            let ord_num = self
+                                .catalog
+                                .with_table(schema_name, table_name, |table| table.next_column_ord());
+                            debug_assert!(
+                                matches!(ord_num, Ok(_)),
+                                "column ord num has to be generated for {:?}.{:?} but value was {:?}",
+                                schema_name,
+                                table_name,
+                                ord_num
+                            );
+                            let ord_num = ord_num.unwrap();
+
+                            let row = Binary::pack(&[
+                                self.catalog_name(),
+                                Datum::from_str(&schema_name),
+                                Datum::from_str(&table_name),
+                                Datum::from_str(&column_name),
+                                Datum::from_u64(sql_type.type_id()),
+                                Datum::from_optional_u64(sql_type.chars_len()),
+                                Datum::from_u64(ord_num),
+                                collation.map_or_else(Datum::from_null, |collation| Datum::from_str(collation.name())),
+                            ]);
+
+                            self.system_table(COLUMNS_TABLE, |table| table.insert(vec![row.clone()]));
+                            events.push(CatalogEvent::ColumnAdded(
+                                schema_name.to_owned(),
+                                table_name.to_owned(),
+                                column_name.to_owned(),
+                            ));
+                        }
+                    },
+                }
+            }
+        }
+        match kind {
+            Kind::Create(SystemObject::Schema) => Ok(ExecutionOutcome::SchemaCreated),
+            Kind::Drop(SystemObject::Schema) => Ok(ExecutionOutcome::SchemaDropped),
+            Kind::Create(SystemObject::Table) => Ok(ExecutionOutcome::TableCreated),
+            Kind::Drop(SystemObject::Table) => Ok(ExecutionOutcome::TableDropped),
+            Kind::Alter(SystemObject::Table) => Ok(ExecutionOutcome::TableAltered),
+            Kind::Alter(SystemObject::Schema) => unimplemented!("schemas can't be altered"),
+        }
+    }
+}
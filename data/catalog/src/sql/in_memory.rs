@@ -13,128 +13,44 @@
 // limitations under the License.
 
 use crate::{
-    CatalogDefinition, DataCatalog, DataTable, Database, InMemoryCatalogHandle, SchemaHandle, SqlSchema, SqlTable,
-    COLUMNS_TABLE, DEFINITION_SCHEMA, SCHEMATA_TABLE, TABLES_TABLE,
+    sql::data_definition::DataDefinition, CatalogDefinition, CatalogEvents, CatalogSubscriber, Database,
+    InMemoryCatalogHandle, ObjectComments, SqlSchema, SqlTable,
 };
-use binary::Binary;
-use definition::{ColumnDef, FullTableName, TableDef};
-use definition_operations::{
-    ExecutionError, ExecutionOutcome, Kind, ObjectState, Record, Step, SystemObject, SystemOperation,
-};
-use repr::Datum;
+use definition::{FullTableName, TableDef};
+use definition_operations::{ExecutionError, ExecutionOutcome, SystemOperation};
 use std::sync::Arc;
-use types::SqlType;
-
-const CATALOG: Datum = Datum::from_str("IN_MEMORY");
-
-fn create_public_schema() -> SystemOperation {
-    SystemOperation {
-        kind: Kind::Create(SystemObject::Schema),
-        skip_steps_if: None,
-        steps: vec![vec![
-            Step::CheckExistence {
-                system_object: SystemObject::Schema,
-                object_name: vec!["public".to_owned()],
-            },
-            Step::CreateFolder {
-                name: "public".to_owned(),
-            },
-            Step::CreateRecord {
-                system_schema: DEFINITION_SCHEMA.to_owned(),
-                system_table: SCHEMATA_TABLE.to_owned(),
-                record: Record::Schema {
-                    catalog_name: "".to_owned(),
-                    schema_name: "public".to_owned(),
-                },
-            },
-        ]],
-    }
-}
 
 pub struct InMemoryDatabase {
-    catalog: InMemoryCatalogHandle,
+    definition: DataDefinition<InMemoryCatalogHandle>,
 }
 
 impl InMemoryDatabase {
     pub fn new() -> Arc<InMemoryDatabase> {
-        Arc::new(InMemoryDatabase::create().bootstrap())
-    }
-
-    fn create() -> InMemoryDatabase {
-        InMemoryDatabase {
-            catalog: InMemoryCatalogHandle::default(),
-        }
-    }
-
-    fn bootstrap(self) -> InMemoryDatabase {
-        self.catalog.create_schema(DEFINITION_SCHEMA);
-        self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-            schema.create_table(SCHEMATA_TABLE);
-            schema.create_table(TABLES_TABLE);
-            schema.create_table(COLUMNS_TABLE);
-        });
-        let public_schema = self.execute(create_public_schema());
-        debug_assert!(
-            matches!(public_schema, Ok(_)),
-            "Default `public` schema has to be created, but failed due to {:?}",
-            public_schema
-        );
-        self
+        Arc::new(InMemoryDatabase {
+            definition: DataDefinition::new("IN_MEMORY", InMemoryCatalogHandle::default()).bootstrap(),
+        })
     }
+}
 
-    fn schema_exists(&self, schema_name: &str) -> bool {
-        let full_schema_name = Binary::pack(&[CATALOG, Datum::from_str(schema_name)]);
-        let schema = self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-            schema.work_with(SCHEMATA_TABLE, |table| {
-                table.select().any(|(_key, value)| value == full_schema_name)
-            })
-        });
-        schema == Some(Some(true))
+impl CatalogDefinition for InMemoryDatabase {
+    fn table_definition(&self, full_table_name: &FullTableName) -> Option<Option<TableDef>> {
+        self.definition.table_definition(full_table_name)
     }
+}
 
-    fn table_exists(&self, full_table_name: &FullTableName) -> bool {
-        let full_table_name = Binary::pack(&full_table_name.raw(CATALOG));
-        let table = self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-            schema.work_with(TABLES_TABLE, |table| {
-                table.select().any(|(_key, value)| value == full_table_name)
-            })
-        });
-        table == Some(Some(true))
+impl ObjectComments for InMemoryDatabase {
+    fn comment_on(&self, full_table_name: &FullTableName, column_name: Option<&str>, comment: Option<&str>) -> bool {
+        self.definition.comment_on(full_table_name, column_name, comment)
     }
 
-    fn table_columns(&self, full_table_name: &FullTableName) -> Vec<ColumnDef> {
-        let full_table_name = Binary::pack(&full_table_name.raw(CATALOG));
-        self.catalog
-            .work_with(DEFINITION_SCHEMA, |schema| {
-                schema.work_with(COLUMNS_TABLE, |table| {
-                    table
-                        .select()
-                        .filter(|(_key, value)| value.start_with(&full_table_name))
-                        .map(|(_key, value)| {
-                            let row = value.unpack();
-                            let name = row[3].as_str().to_owned();
-                            let sql_type = SqlType::from_type_id(row[4].as_u64(), row[5].as_u64());
-                            let ord_num = row[6].as_u64() as usize;
-                            ColumnDef::new(name, sql_type, ord_num)
-                        })
-                        .collect()
-                })
-            })
-            .unwrap()
-            .unwrap()
+    fn comment(&self, full_table_name: &FullTableName, column_name: Option<&str>) -> Option<String> {
+        self.definition.comment(full_table_name, column_name)
     }
 }
 
-impl CatalogDefinition for InMemoryDatabase {
-    fn table_definition(&self, full_table_name: &FullTableName) -> Option<Option<TableDef>> {
-        if !(self.schema_exists(full_table_name.schema())) {
-            return None;
-        }
-        if !(self.table_exists(full_table_name)) {
-            return Some(None);
-        }
-        let column_info = self.table_columns(full_table_name);
-        Some(Some(TableDef::new(full_table_name, column_info)))
+impl CatalogEvents for InMemoryDatabase {
+    fn subscribe(&self, subscriber: Arc<dyn CatalogSubscriber>) {
+        self.definition.subscribe(subscriber)
     }
 }
 
@@ -143,255 +59,7 @@ impl Database for InMemoryDatabase {
     type Table = InMemoryTable;
 
     fn execute(&self, operation: SystemOperation) -> Result<ExecutionOutcome, ExecutionError> {
-        let SystemOperation {
-            kind,
-            skip_steps_if,
-            steps,
-        } = operation;
-        let end = steps.len();
-        let mut index = 0;
-        while index < end {
-            let operations = &steps[index];
-            index += 1;
-            for operation in operations {
-                println!("{:?}", operation);
-                match operation {
-                    Step::CheckExistence {
-                        system_object,
-                        object_name,
-                    } => match system_object {
-                        SystemObject::Schema => {
-                            let result = self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                schema.work_with(SCHEMATA_TABLE, |table| {
-                                    table.select().any(|(_key, value)| {
-                                        value == Binary::pack(&[CATALOG, Datum::from_str(&object_name[0])])
-                                    })
-                                })
-                            });
-                            match skip_steps_if {
-                                None => {
-                                    if let (&Kind::Create(SystemObject::Schema), Some(Some(true))) = (&kind, result) {
-                                        return Err(ExecutionError::SchemaAlreadyExists(object_name[0].to_owned()));
-                                    }
-                                    if let (&Kind::Drop(SystemObject::Schema), Some(Some(false))) = (&kind, result) {
-                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
-                                    }
-                                    if let (&Kind::Create(SystemObject::Table), Some(Some(false))) = (&kind, result) {
-                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
-                                    }
-                                    if let (&Kind::Drop(SystemObject::Table), Some(Some(false))) = (&kind, result) {
-                                        return Err(ExecutionError::SchemaDoesNotExist(object_name[0].to_owned()));
-                                    }
-                                }
-                                Some(ObjectState::NotExists) => break,
-                                Some(ObjectState::Exists) => {}
-                            }
-                        }
-                        SystemObject::Table => {
-                            let result = self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                schema.work_with(TABLES_TABLE, |table| {
-                                    table.select().any(|(_key, value)| {
-                                        value
-                                            == Binary::pack(&[
-                                                CATALOG,
-                                                Datum::from_str(&object_name[0]),
-                                                Datum::from_str(&object_name[1]),
-                                            ])
-                                    })
-                                })
-                            });
-                            match skip_steps_if {
-                                None => {
-                                    if let (&Kind::Create(SystemObject::Table), Some(Some(true))) = (&kind, result) {
-                                        return Err(ExecutionError::TableAlreadyExists(
-                                            object_name[0].to_owned(),
-                                            object_name[1].to_owned(),
-                                        ));
-                                    }
-                                    if let (&Kind::Drop(SystemObject::Table), Some(Some(false))) = (&kind, result) {
-                                        return Err(ExecutionError::TableDoesNotExist(
-                                            object_name[0].to_owned(),
-                                            object_name[1].to_owned(),
-                                        ));
-                                    }
-                                }
-                                Some(ObjectState::NotExists) => unimplemented!(),
-                                Some(ObjectState::Exists) => break,
-                            }
-                        }
-                    },
-                    Step::CheckDependants {
-                        system_object,
-                        object_name,
-                    } => match system_object {
-                        SystemObject::Schema => {
-                            let result = self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                let schema_id = Binary::pack(&[CATALOG, Datum::from_str(&object_name[0])]);
-                                schema.work_with(TABLES_TABLE, |table| {
-                                    table.select().any(|(_key, value)| value.start_with(&schema_id))
-                                })
-                            });
-
-                            if let Some(Some(true)) = result {
-                                return Err(ExecutionError::SchemaHasDependentObjects(object_name[0].to_owned()));
-                            }
-                        }
-                        SystemObject::Table => {}
-                    },
-                    Step::RemoveDependants { .. } => {}
-                    Step::RemoveColumns { .. } => {}
-                    Step::CreateFolder { name } => {
-                        self.catalog.create_schema(&name);
-                    }
-                    Step::RemoveFolder { name } => {
-                        self.catalog.drop_schema(&name);
-                        return Ok(ExecutionOutcome::SchemaDropped);
-                    }
-                    Step::CreateFile { folder_name, name } => {
-                        self.catalog.work_with(folder_name, |schema| schema.create_table(name));
-                    }
-                    Step::RemoveFile { .. } => {}
-                    Step::RemoveRecord {
-                        system_schema: _system_schema,
-                        system_table: _system_table,
-                        record,
-                    } => match record {
-                        Record::Schema {
-                            catalog_name: _catalog_name,
-                            schema_name,
-                        } => {
-                            let full_schema_name = Binary::pack(&[CATALOG, Datum::from_str(&schema_name)]);
-                            self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                schema.work_with(SCHEMATA_TABLE, |table| {
-                                    let schema_id = table
-                                        .select()
-                                        .find(|(_key, value)| value == &full_schema_name)
-                                        .map(|(key, _value)| key);
-                                    debug_assert!(
-                                        matches!(schema_id, Some(_)),
-                                        "record for {:?} schema had to be found in {:?} system table",
-                                        schema_name,
-                                        SCHEMATA_TABLE
-                                    );
-                                    let schema_id = schema_id.unwrap();
-                                    table.delete(vec![schema_id]);
-                                });
-                            });
-                        }
-                        Record::Table {
-                            catalog_name: _catalog_name,
-                            schema_name,
-                            table_name,
-                        } => {
-                            let full_table_name =
-                                Binary::pack(&[CATALOG, Datum::from_str(schema_name), Datum::from_str(table_name)]);
-                            self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                schema.work_with(TABLES_TABLE, |table| {
-                                    let table_id = table
-                                        .select()
-                                        .find(|(_key, value)| value == &full_table_name)
-                                        .map(|(key, _value)| key);
-                                    debug_assert!(
-                                        matches!(table_id, Some(_)),
-                                        "record for {:?}.{:?} table had to be found in {:?} system table",
-                                        schema_name,
-                                        table_name,
-                                        TABLES_TABLE
-                                    );
-                                    println!("FOUND TABLE ID - {:?}", table_id);
-                                    let table_id = table_id.unwrap();
-                                    table.delete(vec![table_id]);
-                                    let table_id = table
-                                        .select()
-                                        .find(|(_key, value)| value == &full_table_name)
-                                        .map(|(key, _value)| key);
-                                    println!("TABLE ID AFTER DROP - {:?}", table_id);
-                                });
-                            });
-                        }
-                        Record::Column { .. } => unimplemented!(),
-                    },
-                    Step::CreateRecord {
-                        system_schema: _system_schema,
-                        system_table: _system_table,
-                        record,
-                    } => match record {
-                        Record::Schema {
-                            catalog_name: _catalog_name,
-                            schema_name,
-                        } => {
-                            self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                schema.work_with(SCHEMATA_TABLE, |table| {
-                                    table.insert(vec![Binary::pack(&[CATALOG, Datum::from_str(&schema_name)])])
-                                })
-                            });
-                            return Ok(ExecutionOutcome::SchemaCreated);
-                        }
-                        Record::Table {
-                            catalog_name: _catalog_name,
-                            schema_name,
-                            table_name,
-                        } => {
-                            let full_table_name =
-                                Binary::pack(&[CATALOG, Datum::from_str(&schema_name), Datum::from_str(&table_name)]);
-                            self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                schema.work_with(TABLES_TABLE, |table| {
-                                    table.insert(vec![Binary::pack(&[
-                                        CATALOG,
-                                        Datum::from_str(&schema_name),
-                                        Datum::from_str(&table_name),
-                                    ])]);
-                                    let table_id = table
-                                        .select()
-                                        .find(|(_key, value)| value == &full_table_name)
-                                        .map(|(key, _value)| key);
-                                    println!("GENERATED TABLE ID - {:?}", table_id);
-                                })
-                            });
-                        }
-                        Record::Column {
-                            catalog_name: _catalog_name,
-                            schema_name,
-                            table_name,
-                            column_name,
-                            sql_type,
-                        } => {
-                            let ord_num = self.catalog.work_with(schema_name, |schema| {
-                                schema.work_with(table_name, |table| table.next_column_ord())
-                            });
-                            debug_assert!(
-                                matches!(ord_num, Some(Some(_))),
-                                "column ord num has to be generated for {:?}.{:?} but value was {:?}",
-                                schema_name,
-                                table_name,
-                                ord_num
-                            );
-                            let ord_num = ord_num.unwrap().unwrap();
-
-                            let row = Binary::pack(&[
-                                CATALOG,
-                                Datum::from_str(&schema_name),
-                                Datum::from_str(&table_name),
-                                Datum::from_str(&column_name),
-                                Datum::from_u64(sql_type.type_id()),
-                                Datum::from_optional_u64(sql_type.chars_len()),
-                                Datum::from_u64(ord_num),
-                            ]);
-
-                            self.catalog.work_with(DEFINITION_SCHEMA, |schema| {
-                                schema.work_with(COLUMNS_TABLE, |table| table.insert(vec![row.clone()]))
-                            });
-                        }
-                    },
-                }
-            }
-        }
-        match kind {
-            Kind::Create(SystemObject::Schema) => Ok(ExecutionOutcome::SchemaCreated),
-            Kind::Drop(SystemObject::Schema) => Ok(ExecutionOutcome::SchemaDropped),
-            Kind::Create(SystemObject::Table) => Ok(ExecutionOutcome::TableCreated),
-            Kind::Drop(SystemObject::Table) => Ok(ExecutionOutcome::TableDropped),
-        }
+        self.definition.execute(operation)
     }
 }
 
@@ -406,6 +74,8 @@ impl SqlTable for InMemoryTable {}
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{COLUMNS_TABLE, DEFINITION_SCHEMA, SCHEMATA_TABLE, TABLES_TABLE};
+    use definition_operations::{Kind, ObjectState, Record, Step, SystemObject};
     use types::SqlType;
 
     const DEFAULT_CATALOG: &str = "public";
@@ -534,6 +204,7 @@ mod test {
                         table_name: table_name.to_owned(),
                         column_name: "col_1".to_owned(),
                         sql_type: SqlType::SmallInt,
+                        collation: None,
                     },
                 },
                 Step::CreateRecord {
@@ -545,6 +216,7 @@ mod test {
                         table_name: table_name.to_owned(),
                         column_name: "col_2".to_owned(),
                         sql_type: SqlType::BigInt,
+                        collation: None,
                     },
                 },
             ]],
@@ -904,4 +576,236 @@ mod test {
             );
         }
     }
+
+    mod comments {
+        use super::*;
+
+        fn with_table() -> Arc<InMemoryDatabase> {
+            let executor = executor();
+            assert_eq!(
+                executor.execute(create_schema_ops(SCHEMA)),
+                Ok(ExecutionOutcome::SchemaCreated)
+            );
+            assert_eq!(
+                executor.execute(create_table_ops(SCHEMA, TABLE)),
+                Ok(ExecutionOutcome::TableCreated)
+            );
+            executor
+        }
+
+        #[test]
+        fn comment_on_nonexistent_table() {
+            let executor = executor();
+
+            assert_eq!(
+                executor.comment_on(&FullTableName::from((&SCHEMA, &TABLE)), None, Some("comment")),
+                false
+            );
+        }
+
+        #[test]
+        fn comment_on_nonexistent_column() {
+            let executor = with_table();
+
+            assert_eq!(
+                executor.comment_on(&FullTableName::from((&SCHEMA, &TABLE)), Some("col_3"), Some("comment")),
+                false
+            );
+        }
+
+        #[test]
+        fn comment_on_table() {
+            let executor = with_table();
+            let full_table_name = FullTableName::from((&SCHEMA, &TABLE));
+
+            assert_eq!(executor.comment(&full_table_name, None), None);
+            assert_eq!(executor.comment_on(&full_table_name, None, Some("table comment")), true);
+            assert_eq!(
+                executor.comment(&full_table_name, None),
+                Some("table comment".to_owned())
+            );
+            assert_eq!(executor.comment(&full_table_name, Some("col_1")), None);
+        }
+
+        #[test]
+        fn comment_on_columns() {
+            let executor = with_table();
+            let full_table_name = FullTableName::from((&SCHEMA, &TABLE));
+
+            assert_eq!(
+                executor.comment_on(&full_table_name, Some("col_1"), Some("first")),
+                true
+            );
+            assert_eq!(
+                executor.comment_on(&full_table_name, Some("col_2"), Some("second")),
+                true
+            );
+
+            assert_eq!(executor.comment(&full_table_name, None), None);
+            assert_eq!(
+                executor.comment(&full_table_name, Some("col_1")),
+                Some("first".to_owned())
+            );
+            assert_eq!(
+                executor.comment(&full_table_name, Some("col_2")),
+                Some("second".to_owned())
+            );
+        }
+
+        #[test]
+        fn replace_and_remove_comment() {
+            let executor = with_table();
+            let full_table_name = FullTableName::from((&SCHEMA, &TABLE));
+
+            assert_eq!(executor.comment_on(&full_table_name, None, Some("old")), true);
+            assert_eq!(executor.comment_on(&full_table_name, None, Some("new")), true);
+            assert_eq!(executor.comment(&full_table_name, None), Some("new".to_owned()));
+
+            assert_eq!(executor.comment_on(&full_table_name, None, None), true);
+            assert_eq!(executor.comment(&full_table_name, None), None);
+        }
+
+        #[test]
+        fn comments_are_removed_with_table() {
+            let executor = with_table();
+            let full_table_name = FullTableName::from((&SCHEMA, &TABLE));
+            assert_eq!(executor.comment_on(&full_table_name, None, Some("table")), true);
+            assert_eq!(
+                executor.comment_on(&full_table_name, Some("col_1"), Some("column")),
+                true
+            );
+
+            assert_eq!(
+                executor.execute(drop_tables_ops(SCHEMA, vec![TABLE])),
+                Ok(ExecutionOutcome::TableDropped)
+            );
+            assert_eq!(
+                executor.execute(create_table_ops(SCHEMA, TABLE)),
+                Ok(ExecutionOutcome::TableCreated)
+            );
+
+            assert_eq!(executor.comment(&full_table_name, None), None);
+            assert_eq!(executor.comment(&full_table_name, Some("col_1")), None);
+        }
+    }
+
+    mod events {
+        use super::*;
+        use crate::CatalogEvent;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Recorder {
+            events: Mutex<Vec<CatalogEvent>>,
+        }
+
+        impl Recorder {
+            fn events(&self) -> Vec<CatalogEvent> {
+                self.events.lock().unwrap().clone()
+            }
+        }
+
+        impl CatalogSubscriber for Recorder {
+            fn notify(&self, event: &CatalogEvent) {
+                self.events.lock().unwrap().push(event.clone());
+            }
+        }
+
+        fn subscribed() -> (Arc<InMemoryDatabase>, Arc<Recorder>) {
+            let executor = executor();
+            let recorder = Arc::new(Recorder::default());
+            executor.subscribe(recorder.clone());
+            (executor, recorder)
+        }
+
+        #[test]
+        fn create_and_drop_schema() {
+            let (executor, recorder) = subscribed();
+
+            assert_eq!(
+                executor.execute(create_schema_ops(SCHEMA)),
+                Ok(ExecutionOutcome::SchemaCreated)
+            );
+            assert_eq!(
+                executor.execute(drop_schemas_ops(vec![SCHEMA])),
+                Ok(ExecutionOutcome::SchemaDropped)
+            );
+
+            assert_eq!(
+                recorder.events(),
+                vec![
+                    CatalogEvent::SchemaCreated(SCHEMA.to_owned()),
+                    CatalogEvent::SchemaDropped(SCHEMA.to_owned()),
+                ]
+            );
+        }
+
+        #[test]
+        fn create_and_drop_table() {
+            let (executor, recorder) = subscribed();
+
+            assert_eq!(
+                executor.execute(create_schema_ops(SCHEMA)),
+                Ok(ExecutionOutcome::SchemaCreated)
+            );
+            assert_eq!(
+                executor.execute(create_table_ops(SCHEMA, TABLE)),
+                Ok(ExecutionOutcome::TableCreated)
+            );
+            assert_eq!(
+                executor.execute(drop_tables_ops(SCHEMA, vec![TABLE])),
+                Ok(ExecutionOutcome::TableDropped)
+            );
+
+            assert_eq!(
+                recorder.events(),
+                vec![
+                    CatalogEvent::SchemaCreated(SCHEMA.to_owned()),
+                    CatalogEvent::TableCreated(SCHEMA.to_owned(), TABLE.to_owned()),
+                    CatalogEvent::ColumnAdded(SCHEMA.to_owned(), TABLE.to_owned(), "col_1".to_owned()),
+                    CatalogEvent::ColumnAdded(SCHEMA.to_owned(), TABLE.to_owned(), "col_2".to_owned()),
+                    CatalogEvent::TableDropped(SCHEMA.to_owned(), TABLE.to_owned()),
+                ]
+            );
+        }
+
+        #[test]
+        fn failed_and_skipped_operations_do_not_notify() {
+            let (executor, recorder) = subscribed();
+
+            assert_eq!(
+                executor.execute(create_schema_ops(SCHEMA)),
+                Ok(ExecutionOutcome::SchemaCreated)
+            );
+            assert_eq!(
+                executor.execute(create_schema_ops(SCHEMA)),
+                Err(ExecutionError::SchemaAlreadyExists(SCHEMA.to_owned()))
+            );
+            assert_eq!(
+                executor.execute(create_schema_if_not_exists_ops(SCHEMA)),
+                Ok(ExecutionOutcome::SchemaCreated)
+            );
+            assert_eq!(
+                executor.execute(drop_schemas_ops(vec![OTHER_SCHEMA])),
+                Err(ExecutionError::SchemaDoesNotExist(OTHER_SCHEMA.to_owned()))
+            );
+
+            assert_eq!(recorder.events(), vec![CatalogEvent::SchemaCreated(SCHEMA.to_owned())]);
+        }
+
+        #[test]
+        fn every_subscriber_is_notified() {
+            let (executor, recorder) = subscribed();
+            let other = Arc::new(Recorder::default());
+            executor.subscribe(other.clone());
+
+            assert_eq!(
+                executor.execute(create_schema_ops(SCHEMA)),
+                Ok(ExecutionOutcome::SchemaCreated)
+            );
+
+            assert_eq!(recorder.events(), vec![CatalogEvent::SchemaCreated(SCHEMA.to_owned())]);
+            assert_eq!(other.events(), vec![CatalogEvent::SchemaCreated(SCHEMA.to_owned())]);
+        }
+    }
 }
@@ -12,5 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod data_definition;
 pub mod in_memory;
 pub mod on_disk;
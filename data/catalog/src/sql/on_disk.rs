@@ -12,22 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{CatalogDefinition, Database, SqlSchema, SqlTable};
+use crate::{
+    sql::data_definition::DataDefinition, CatalogDefinition, CatalogEvents, CatalogSubscriber, Database,
+    ObjectComments, OnDiskCatalogHandle, SqlSchema, SqlTable,
+};
 use definition::{FullTableName, TableDef};
 use definition_operations::{ExecutionError, ExecutionOutcome, SystemOperation};
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
-pub struct OnDiskDatabase;
+pub struct OnDiskDatabase {
+    definition: DataDefinition<OnDiskCatalogHandle>,
+}
 
 impl OnDiskDatabase {
-    pub fn new(_name: &str) -> Arc<OnDiskDatabase> {
-        Arc::new(OnDiskDatabase)
+    /// opens database stored in `name` directory, schemas and tables created
+    /// before are read back from its system tables
+    pub fn new(name: &str) -> Arc<OnDiskDatabase> {
+        let catalog = OnDiskCatalogHandle::new(PathBuf::from(name));
+        Arc::new(OnDiskDatabase {
+            definition: DataDefinition::new("ON_DISK", catalog).bootstrap(),
+        })
     }
 }
 
 impl CatalogDefinition for OnDiskDatabase {
-    fn table_definition(&self, _table_full_name: &FullTableName) -> Option<Option<TableDef>> {
-        unimplemented!()
+    fn table_definition(&self, full_table_name: &FullTableName) -> Option<Option<TableDef>> {
+        self.definition.table_definition(full_table_name)
+    }
+}
+
+impl ObjectComments for OnDiskDatabase {
+    fn comment_on(&self, full_table_name: &FullTableName, column_name: Option<&str>, comment: Option<&str>) -> bool {
+        self.definition.comment_on(full_table_name, column_name, comment)
+    }
+
+    fn comment(&self, full_table_name: &FullTableName, column_name: Option<&str>) -> Option<String> {
+        self.definition.comment(full_table_name, column_name)
+    }
+}
+
+impl CatalogEvents for OnDiskDatabase {
+    fn subscribe(&self, subscriber: Arc<dyn CatalogSubscriber>) {
+        self.definition.subscribe(subscriber)
     }
 }
 
@@ -35,8 +61,8 @@ impl Database for OnDiskDatabase {
     type Schema = OnDiskSchema;
     type Table = OnDiskTable;
 
-    fn execute(&self, _operation: SystemOperation) -> Result<ExecutionOutcome, ExecutionError> {
-        unimplemented!()
+    fn execute(&self, operation: SystemOperation) -> Result<ExecutionOutcome, ExecutionError> {
+        self.definition.execute(operation)
     }
 }
 
@@ -47,3 +73,228 @@ impl SqlSchema for OnDiskSchema {}
 pub struct OnDiskTable;
 
 impl SqlTable for OnDiskTable {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{COLUMNS_TABLE, DEFINITION_SCHEMA, SCHEMATA_TABLE, TABLES_TABLE};
+    use definition::ColumnDef;
+    use definition_operations::{Kind, Record, Step, SystemObject};
+    use types::SqlType;
+
+    const DEFAULT_CATALOG: &str = "public";
+    const SCHEMA: &str = "schema_name";
+    const TABLE: &str = "table_name";
+
+    fn database_path() -> String {
+        let temp_dir = tempfile::tempdir().expect("to create temporary folder");
+        temp_dir.into_path().to_str().unwrap().to_owned()
+    }
+
+    fn create_schema_ops(schema_name: &str) -> SystemOperation {
+        SystemOperation {
+            kind: Kind::Create(SystemObject::Schema),
+            skip_steps_if: None,
+            steps: vec![vec![
+                Step::CheckExistence {
+                    system_object: SystemObject::Schema,
+                    object_name: vec![schema_name.to_owned()],
+                },
+                Step::CreateFolder {
+                    name: schema_name.to_owned(),
+                },
+                Step::CreateRecord {
+                    system_schema: DEFINITION_SCHEMA.to_owned(),
+                    system_table: SCHEMATA_TABLE.to_owned(),
+                    record: Record::Schema {
+                        catalog_name: DEFAULT_CATALOG.to_owned(),
+                        schema_name: schema_name.to_owned(),
+                    },
+                },
+            ]],
+        }
+    }
+
+    fn drop_schema_ops(schema_name: &str) -> SystemOperation {
+        SystemOperation {
+            kind: Kind::Drop(SystemObject::Schema),
+            skip_steps_if: None,
+            steps: vec![vec![
+                Step::CheckExistence {
+                    system_object: SystemObject::Schema,
+                    object_name: vec![schema_name.to_owned()],
+                },
+                Step::CheckDependants {
+                    system_object: SystemObject::Schema,
+                    object_name: vec![schema_name.to_owned()],
+                },
+                Step::RemoveRecord {
+                    system_schema: DEFINITION_SCHEMA.to_owned(),
+                    system_table: SCHEMATA_TABLE.to_owned(),
+                    record: Record::Schema {
+                        catalog_name: DEFAULT_CATALOG.to_owned(),
+                        schema_name: schema_name.to_owned(),
+                    },
+                },
+                Step::RemoveFolder {
+                    name: schema_name.to_owned(),
+                },
+            ]],
+        }
+    }
+
+    fn create_table_ops(schema_name: &str, table_name: &str) -> SystemOperation {
+        let column = |column_name: &str, sql_type: SqlType| Step::CreateRecord {
+            system_schema: DEFINITION_SCHEMA.to_owned(),
+            system_table: COLUMNS_TABLE.to_owned(),
+            record: Record::Column {
+                catalog_name: DEFAULT_CATALOG.to_owned(),
+                schema_name: schema_name.to_owned(),
+                table_name: table_name.to_owned(),
+                column_name: column_name.to_owned(),
+                sql_type,
+                collation: None,
+            },
+        };
+        SystemOperation {
+            kind: Kind::Create(SystemObject::Table),
+            skip_steps_if: None,
+            steps: vec![vec![
+                Step::CheckExistence {
+                    system_object: SystemObject::Schema,
+                    object_name: vec![schema_name.to_owned()],
+                },
+                Step::CheckExistence {
+                    system_object: SystemObject::Table,
+                    object_name: vec![schema_name.to_owned(), table_name.to_owned()],
+                },
+                Step::CreateFile {
+                    folder_name: schema_name.to_owned(),
+                    name: table_name.to_owned(),
+                },
+                Step::CreateRecord {
+                    system_schema: DEFINITION_SCHEMA.to_owned(),
+                    system_table: TABLES_TABLE.to_owned(),
+                    record: Record::Table {
+                        catalog_name: DEFAULT_CATALOG.to_owned(),
+                        schema_name: schema_name.to_owned(),
+                        table_name: table_name.to_owned(),
+                    },
+                },
+                column("col_1", SqlType::SmallInt),
+                column("col_2", SqlType::BigInt),
+            ]],
+        }
+    }
+
+    fn column_types(database: &OnDiskDatabase, full_table_name: &FullTableName) -> Option<Option<Vec<SqlType>>> {
+        database
+            .table_definition(full_table_name)
+            .map(|table_def| table_def.map(|table_def| table_def.columns().iter().map(ColumnDef::sql_type).collect()))
+    }
+
+    #[test]
+    fn public_schema_is_created_once() {
+        let path = database_path();
+        drop(OnDiskDatabase::new(&path));
+
+        let database = OnDiskDatabase::new(&path);
+
+        assert_eq!(
+            database.execute(create_schema_ops("public")),
+            Err(ExecutionError::SchemaAlreadyExists("public".to_owned()))
+        );
+    }
+
+    #[test]
+    fn schema_exists_after_reopening() {
+        let path = database_path();
+        let database = OnDiskDatabase::new(&path);
+        assert_eq!(
+            database.execute(create_schema_ops(SCHEMA)),
+            Ok(ExecutionOutcome::SchemaCreated)
+        );
+        drop(database);
+
+        let database = OnDiskDatabase::new(&path);
+
+        assert_eq!(
+            database.execute(create_schema_ops(SCHEMA)),
+            Err(ExecutionError::SchemaAlreadyExists(SCHEMA.to_owned()))
+        );
+    }
+
+    #[test]
+    fn dropped_schema_does_not_exist_after_reopening() {
+        let path = database_path();
+        let database = OnDiskDatabase::new(&path);
+        assert_eq!(
+            database.execute(create_schema_ops(SCHEMA)),
+            Ok(ExecutionOutcome::SchemaCreated)
+        );
+        assert_eq!(
+            database.execute(drop_schema_ops(SCHEMA)),
+            Ok(ExecutionOutcome::SchemaDropped)
+        );
+        drop(database);
+
+        let database = OnDiskDatabase::new(&path);
+
+        assert_eq!(
+            database.execute(drop_schema_ops(SCHEMA)),
+            Err(ExecutionError::SchemaDoesNotExist(SCHEMA.to_owned()))
+        );
+    }
+
+    #[test]
+    fn table_definition_after_reopening() {
+        let path = database_path();
+        let database = OnDiskDatabase::new(&path);
+        assert_eq!(
+            database.execute(create_schema_ops(SCHEMA)),
+            Ok(ExecutionOutcome::SchemaCreated)
+        );
+        assert_eq!(
+            database.execute(create_table_ops(SCHEMA, TABLE)),
+            Ok(ExecutionOutcome::TableCreated)
+        );
+        drop(database);
+
+        let database = OnDiskDatabase::new(&path);
+
+        assert_eq!(
+            column_types(&database, &FullTableName::from((&SCHEMA, &TABLE))),
+            Some(Some(vec![SqlType::SmallInt, SqlType::BigInt]))
+        );
+    }
+
+    #[test]
+    fn comments_after_reopening() {
+        let path = database_path();
+        let database = OnDiskDatabase::new(&path);
+        let full_table_name = FullTableName::from((&SCHEMA, &TABLE));
+        assert_eq!(
+            database.execute(create_schema_ops(SCHEMA)),
+            Ok(ExecutionOutcome::SchemaCreated)
+        );
+        assert_eq!(
+            database.execute(create_table_ops(SCHEMA, TABLE)),
+            Ok(ExecutionOutcome::TableCreated)
+        );
+        assert_eq!(database.comment_on(&full_table_name, None, Some("old")), true);
+        assert_eq!(database.comment_on(&full_table_name, None, Some("new")), true);
+        assert_eq!(
+            database.comment_on(&full_table_name, Some("col_1"), Some("column")),
+            true
+        );
+        drop(database);
+
+        let database = OnDiskDatabase::new(&path);
+
+        assert_eq!(database.comment(&full_table_name, None), Some("new".to_owned()));
+        assert_eq!(
+            database.comment(&full_table_name, Some("col_1")),
+            Some("column".to_owned())
+        );
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use types::SqlType;
+use types::{Collation, SqlType};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct SystemOperation {
@@ -25,6 +25,7 @@ pub struct SystemOperation {
 pub enum Kind {
     Create(SystemObject),
     Drop(SystemObject),
+    Alter(SystemObject),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -100,6 +101,7 @@ pub enum Record {
         table_name: String,
         column_name: String,
         sql_type: SqlType,
+        collation: Option<Collation>,
     },
 }
 
@@ -109,6 +111,7 @@ pub enum ExecutionOutcome {
     SchemaDropped,
     TableCreated,
     TableDropped,
+    TableAltered,
 }
 
 #[derive(Debug, PartialEq)]
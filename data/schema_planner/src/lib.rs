@@ -13,9 +13,11 @@
 // limitations under the License.
 
 use analysis_tree::{
-    ColumnInfo, CreateSchemaQuery, CreateTableQuery, DropSchemasQuery, DropTablesQuery, SchemaChange, TableInfo,
+    AddColumnQuery, ColumnInfo, CreateSchemaQuery, CreateTableQuery, DropSchemasQuery, DropTablesQuery, SchemaChange,
+    TableInfo,
 };
 use data_manager::{COLUMNS_TABLE, DEFAULT_CATALOG, DEFINITION_SCHEMA, SCHEMATA_TABLE, TABLES_TABLE};
+use definition::SchemaName;
 use definition_operations::{Kind, ObjectState, Record, Step, SystemObject, SystemOperation};
 
 pub struct SystemSchemaPlanner;
@@ -131,7 +133,12 @@ impl SystemSchemaPlanner {
                         table_name: table_name.clone(),
                     },
                 });
-                for ColumnInfo { name, sql_type } in column_defs {
+                for ColumnInfo {
+                    name,
+                    sql_type,
+                    collation,
+                } in column_defs
+                {
                     steps.push(Step::CreateRecord {
                         system_schema: DEFINITION_SCHEMA.to_owned(),
                         system_table: COLUMNS_TABLE.to_owned(),
@@ -141,6 +148,7 @@ impl SystemSchemaPlanner {
                             table_name: table_name.clone(),
                             column_name: name.clone(),
                             sql_type: *sql_type,
+                            collation: *collation,
                         },
                     })
                 }
@@ -198,6 +206,103 @@ impl SystemSchemaPlanner {
                     steps,
                 }
             }
+            SchemaChange::AddColumn(AddColumnQuery {
+                table_info:
+                    TableInfo {
+                        schema_id: _schema_id,
+                        schema_name,
+                        table_name,
+                    },
+                column_def:
+                    ColumnInfo {
+                        name,
+                        sql_type,
+                        collation,
+                    },
+            }) => SystemOperation {
+                kind: Kind::Alter(SystemObject::Table),
+                skip_steps_if: None,
+                steps: vec![vec![
+                    Step::CheckExistence {
+                        system_object: SystemObject::Schema,
+                        object_name: vec![schema_name.clone()],
+                    },
+                    Step::CheckExistence {
+                        system_object: SystemObject::Table,
+                        object_name: vec![schema_name.clone(), table_name.clone()],
+                    },
+                    Step::CreateRecord {
+                        system_schema: DEFINITION_SCHEMA.to_owned(),
+                        system_table: COLUMNS_TABLE.to_owned(),
+                        record: Record::Column {
+                            catalog_name: DEFAULT_CATALOG.to_owned(),
+                            schema_name: schema_name.clone(),
+                            table_name: table_name.clone(),
+                            column_name: name.clone(),
+                            sql_type: *sql_type,
+                            collation: *collation,
+                        },
+                    },
+                ]],
+            },
+        }
+    }
+
+    /// plan that undoes creation of an object or a column when the change could
+    /// not be applied to all places where the catalog is kept. Drops can't be
+    /// undone
+    pub fn rollback_plan(&self, schema_change: &SchemaChange) -> Option<SystemOperation> {
+        match schema_change {
+            SchemaChange::CreateSchema(CreateSchemaQuery { schema_name, .. }) => {
+                Some(self.schema_change_plan(&SchemaChange::DropSchemas(DropSchemasQuery {
+                    schema_names: vec![SchemaName::from(&schema_name.as_ref())],
+                    cascade: false,
+                    if_exists: true,
+                })))
+            }
+            SchemaChange::CreateTable(CreateTableQuery {
+                table_info:
+                    TableInfo {
+                        schema_id,
+                        schema_name,
+                        table_name,
+                    },
+                ..
+            }) => Some(self.schema_change_plan(&SchemaChange::DropTables(DropTablesQuery {
+                table_infos: vec![TableInfo::new(*schema_id, schema_name, table_name)],
+                cascade: false,
+                if_exists: true,
+            }))),
+            SchemaChange::AddColumn(AddColumnQuery {
+                table_info:
+                    TableInfo {
+                        schema_name,
+                        table_name,
+                        ..
+                    },
+                column_def:
+                    ColumnInfo {
+                        name,
+                        sql_type,
+                        collation,
+                    },
+            }) => Some(SystemOperation {
+                kind: Kind::Alter(SystemObject::Table),
+                skip_steps_if: None,
+                steps: vec![vec![Step::RemoveRecord {
+                    system_schema: DEFINITION_SCHEMA.to_owned(),
+                    system_table: COLUMNS_TABLE.to_owned(),
+                    record: Record::Column {
+                        catalog_name: DEFAULT_CATALOG.to_owned(),
+                        schema_name: schema_name.clone(),
+                        table_name: table_name.clone(),
+                        column_name: name.clone(),
+                        sql_type: *sql_type,
+                        collation: *collation,
+                    },
+                }]],
+            }),
+            SchemaChange::DropSchemas(_) | SchemaChange::DropTables(_) => None,
         }
     }
 }
@@ -205,7 +310,6 @@ impl SystemSchemaPlanner {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use definition::SchemaName;
     use types::SqlType;
 
     const SCHEMA: &str = "schema";
@@ -583,11 +687,13 @@ mod tests {
                     column_defs: vec![
                         ColumnInfo {
                             name: "col_1".to_owned(),
-                            sql_type: SqlType::SmallInt
+                            sql_type: SqlType::SmallInt,
+                            collation: None,
                         },
                         ColumnInfo {
                             name: "col_2".to_owned(),
-                            sql_type: SqlType::BigInt
+                            sql_type: SqlType::BigInt,
+                            collation: None,
                         }
                     ],
                     if_not_exists: false,
@@ -625,7 +731,8 @@ mod tests {
                                 schema_name: SCHEMA.to_string(),
                                 table_name: TABLE.to_string(),
                                 column_name: "col_1".to_string(),
-                                sql_type: SqlType::SmallInt
+                                sql_type: SqlType::SmallInt,
+                                collation: None,
                             }
                         },
                         Step::CreateRecord {
@@ -636,7 +743,8 @@ mod tests {
                                 schema_name: SCHEMA.to_string(),
                                 table_name: TABLE.to_string(),
                                 column_name: "col_2".to_string(),
-                                sql_type: SqlType::BigInt
+                                sql_type: SqlType::BigInt,
+                                collation: None,
                             }
                         }
                     ]]
@@ -865,5 +973,127 @@ mod tests {
                 }
             );
         }
+
+        #[test]
+        fn add_column() {
+            assert_eq!(
+                QUERY_PLANNER.schema_change_plan(&SchemaChange::AddColumn(AddColumnQuery {
+                    table_info: TableInfo::new(0, &SCHEMA, &TABLE),
+                    column_def: ColumnInfo {
+                        name: "col".to_owned(),
+                        sql_type: SqlType::Integer,
+                        collation: None,
+                    },
+                })),
+                SystemOperation {
+                    kind: Kind::Alter(SystemObject::Table),
+                    skip_steps_if: None,
+                    steps: vec![vec![
+                        Step::CheckExistence {
+                            system_object: SystemObject::Schema,
+                            object_name: vec![SCHEMA.to_owned()],
+                        },
+                        Step::CheckExistence {
+                            system_object: SystemObject::Table,
+                            object_name: vec![SCHEMA.to_owned(), TABLE.to_owned()],
+                        },
+                        Step::CreateRecord {
+                            system_schema: DEFINITION_SCHEMA.to_owned(),
+                            system_table: COLUMNS_TABLE.to_owned(),
+                            record: Record::Column {
+                                catalog_name: DEFAULT_CATALOG.to_owned(),
+                                schema_name: SCHEMA.to_owned(),
+                                table_name: TABLE.to_owned(),
+                                column_name: "col".to_owned(),
+                                sql_type: SqlType::Integer,
+                                collation: None,
+                            },
+                        },
+                    ]],
+                }
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod rollback {
+        use super::*;
+
+        #[test]
+        fn create_schema() {
+            assert_eq!(
+                QUERY_PLANNER.rollback_plan(&SchemaChange::CreateSchema(CreateSchemaQuery {
+                    schema_name: SchemaName::from(&SCHEMA),
+                    if_not_exists: false,
+                })),
+                Some(
+                    QUERY_PLANNER.schema_change_plan(&SchemaChange::DropSchemas(DropSchemasQuery {
+                        schema_names: vec![SchemaName::from(&SCHEMA)],
+                        cascade: false,
+                        if_exists: true,
+                    }))
+                )
+            );
+        }
+
+        #[test]
+        fn create_table() {
+            assert_eq!(
+                QUERY_PLANNER.rollback_plan(&SchemaChange::CreateTable(CreateTableQuery {
+                    table_info: TableInfo::new(0, &SCHEMA, &TABLE),
+                    column_defs: vec![],
+                    if_not_exists: false,
+                })),
+                Some(
+                    QUERY_PLANNER.schema_change_plan(&SchemaChange::DropTables(DropTablesQuery {
+                        table_infos: vec![TableInfo::new(0, &SCHEMA, &TABLE)],
+                        cascade: false,
+                        if_exists: true,
+                    }))
+                )
+            );
+        }
+
+        #[test]
+        fn add_column() {
+            assert_eq!(
+                QUERY_PLANNER.rollback_plan(&SchemaChange::AddColumn(AddColumnQuery {
+                    table_info: TableInfo::new(0, &SCHEMA, &TABLE),
+                    column_def: ColumnInfo {
+                        name: "col".to_owned(),
+                        sql_type: SqlType::Integer,
+                        collation: None,
+                    },
+                })),
+                Some(SystemOperation {
+                    kind: Kind::Alter(SystemObject::Table),
+                    skip_steps_if: None,
+                    steps: vec![vec![Step::RemoveRecord {
+                        system_schema: DEFINITION_SCHEMA.to_owned(),
+                        system_table: COLUMNS_TABLE.to_owned(),
+                        record: Record::Column {
+                            catalog_name: DEFAULT_CATALOG.to_owned(),
+                            schema_name: SCHEMA.to_owned(),
+                            table_name: TABLE.to_owned(),
+                            column_name: "col".to_owned(),
+                            sql_type: SqlType::Integer,
+                            collation: None,
+                        },
+                    }]],
+                })
+            );
+        }
+
+        #[test]
+        fn drops_are_not_undone() {
+            assert_eq!(
+                QUERY_PLANNER.rollback_plan(&SchemaChange::DropTables(DropTablesQuery {
+                    table_infos: vec![TableInfo::new(0, &SCHEMA, &TABLE)],
+                    cascade: false,
+                    if_exists: false,
+                })),
+                None
+            );
+        }
     }
 }
@@ -136,6 +136,26 @@ impl ScalarValue {
             (ScalarValue::Bool(Bool(boolean)), SqlType::Bool) => Ok(ScalarValue::Bool(Bool(*boolean))),
         }
     }
+
+    /// the same as `cast` but values are converted only to types of the same
+    /// kind, e.g. a string could not be assigned to a number column
+    pub fn strict_cast(&self, to_type: &SqlType) -> Result<ScalarValue, OperationError> {
+        match (self, to_type) {
+            (ScalarValue::Null, _)
+            | (ScalarValue::String(_), SqlType::Char(_))
+            | (ScalarValue::String(_), SqlType::VarChar(_))
+            | (ScalarValue::Number(_), SqlType::SmallInt)
+            | (ScalarValue::Number(_), SqlType::Integer)
+            | (ScalarValue::Number(_), SqlType::BigInt)
+            | (ScalarValue::Number(_), SqlType::Real)
+            | (ScalarValue::Number(_), SqlType::DoublePrecision)
+            | (ScalarValue::Bool(_), SqlType::Bool) => self.cast(to_type),
+            _ => Err(OperationError(NotSupportedOperation::ImplicitCast(
+                self.clone(),
+                *to_type,
+            ))),
+        }
+    }
 }
 
 impl Display for ScalarValue {
@@ -551,4 +571,59 @@ mod tests {
             );
         }
     }
+
+    #[cfg(test)]
+    mod strict_type_casting {
+        use super::*;
+
+        #[test]
+        fn same_kind_of_types() {
+            assert_eq!(
+                ScalarValue::Number(BigDecimal::from(123)).strict_cast(&SqlType::SmallInt),
+                Ok(ScalarValue::Number(BigDecimal::from(123)))
+            );
+            assert_eq!(
+                ScalarValue::String("abc ".to_owned()).strict_cast(&SqlType::VarChar(5)),
+                Ok(ScalarValue::String("abc".to_owned()))
+            );
+            assert_eq!(
+                ScalarValue::Bool(Bool(false)).strict_cast(&SqlType::Bool),
+                Ok(ScalarValue::Bool(Bool(false)))
+            );
+            assert_eq!(ScalarValue::Null.strict_cast(&SqlType::Integer), Ok(ScalarValue::Null));
+        }
+
+        #[test]
+        fn string_to_number() {
+            assert_eq!(
+                ScalarValue::String("123".to_owned()).strict_cast(&SqlType::Integer),
+                Err(OperationError(NotSupportedOperation::ImplicitCast(
+                    ScalarValue::String("123".to_owned()),
+                    SqlType::Integer
+                )))
+            );
+        }
+
+        #[test]
+        fn number_to_string() {
+            assert_eq!(
+                ScalarValue::Number(BigDecimal::from(123)).strict_cast(&SqlType::Char(3)),
+                Err(OperationError(NotSupportedOperation::ImplicitCast(
+                    ScalarValue::Number(BigDecimal::from(123)),
+                    SqlType::Char(3)
+                )))
+            );
+        }
+
+        #[test]
+        fn bool_to_number() {
+            assert_eq!(
+                ScalarValue::Bool(Bool(true)).strict_cast(&SqlType::BigInt),
+                Err(OperationError(NotSupportedOperation::ImplicitCast(
+                    ScalarValue::Bool(Bool(true)),
+                    SqlType::BigInt
+                )))
+            );
+        }
+    }
 }
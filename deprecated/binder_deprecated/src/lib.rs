@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use sql_ast::{Assignment, Expr, Ident, ObjectType, Query, Select, SetExpr, Statement};
+use sql_ast::{
+    Assignment, Expr, Function, FunctionArg, Ident, ObjectType, OrderByExpr, Query, Select, SelectItem, SetExpr,
+    Statement,
+};
 
 pub struct ParamBinder;
 
@@ -23,6 +26,7 @@ impl ParamBinder {
         match stmt {
             Statement::Insert { .. } => bind_insert(stmt, params),
             Statement::Update { .. } => bind_update(stmt, params),
+            Statement::Delete { .. } => bind_delete(stmt, params),
             Statement::Query(_) => bind_select(stmt, params),
             Statement::SetVariable { .. } => Ok(()),
             Statement::CreateSchema { .. } => Ok(()),
@@ -35,22 +39,9 @@ impl ParamBinder {
 }
 
 fn bind_insert(stmt: &mut Statement, params: &[Expr]) -> Result<(), ()> {
-    let mut body = match stmt {
-        Statement::Insert { source, .. } => {
-            let source: &mut Query = source;
-            let Query { body, .. } = source;
-            body
-        }
+    match stmt {
+        Statement::Insert { source, .. } => bind_query(source, params),
         _ => return Err(()),
-    };
-
-    if let SetExpr::Values(values) = &mut body {
-        let values = &mut values.0;
-        for line in values {
-            for col in line {
-                replace_expr_with_params(col, params);
-            }
-        }
     }
 
     log::debug!("bound insert SQL: {}", stmt);
@@ -58,29 +49,9 @@ fn bind_insert(stmt: &mut Statement, params: &[Expr]) -> Result<(), ()> {
 }
 
 fn bind_select(stmt: &mut Statement, params: &[Expr]) -> Result<(), ()> {
-    let mut body = match stmt {
-        Statement::Query(query) => {
-            let query: &mut Query = query;
-            let Query { body, .. } = query;
-            body
-        }
+    match stmt {
+        Statement::Query(query) => bind_query(query, params),
         _ => return Err(()),
-    };
-
-    let selection = match &mut body {
-        SetExpr::Select(select) => {
-            let select: &mut Select = select;
-            let Select { selection, .. } = select;
-            selection
-        }
-        _ => return Ok(()),
-    };
-
-    if let Some(Expr::BinaryOp { left, right, .. }) = selection {
-        let left: &mut Expr = left;
-        replace_expr_with_params(left, params);
-        let right: &mut Expr = right;
-        replace_expr_with_params(right, params);
     }
 
     log::debug!("bound select SQL: {}", stmt);
@@ -88,20 +59,157 @@ fn bind_select(stmt: &mut Statement, params: &[Expr]) -> Result<(), ()> {
 }
 
 fn bind_update(stmt: &mut Statement, params: &[Expr]) -> Result<(), ()> {
-    let assignments = match stmt {
-        Statement::Update { assignments, .. } => assignments,
+    match stmt {
+        Statement::Update {
+            assignments, selection, ..
+        } => {
+            for assignment in assignments {
+                let Assignment { value, .. } = assignment;
+                bind_expr(value, params);
+            }
+            if let Some(selection) = selection {
+                bind_expr(selection, params);
+            }
+        }
         _ => return Err(()),
-    };
-
-    for assignment in assignments {
-        let Assignment { value, .. } = assignment;
-        replace_expr_with_params(value, params);
     }
 
     log::debug!("bound update SQL: {}", stmt);
     Ok(())
 }
 
+fn bind_delete(stmt: &mut Statement, params: &[Expr]) -> Result<(), ()> {
+    match stmt {
+        Statement::Delete { selection, .. } => {
+            if let Some(selection) = selection {
+                bind_expr(selection, params);
+            }
+        }
+        _ => return Err(()),
+    }
+
+    log::debug!("bound delete SQL: {}", stmt);
+    Ok(())
+}
+
+fn bind_query(query: &mut Query, params: &[Expr]) {
+    let Query {
+        body, order_by, limit, ..
+    } = query;
+    bind_set_expr(body, params);
+    for OrderByExpr { expr, .. } in order_by {
+        bind_expr(expr, params);
+    }
+    if let Some(limit) = limit {
+        bind_expr(limit, params);
+    }
+}
+
+fn bind_set_expr(set_expr: &mut SetExpr, params: &[Expr]) {
+    match set_expr {
+        SetExpr::Select(select) => {
+            let select: &mut Select = select;
+            let Select {
+                projection,
+                selection,
+                group_by,
+                having,
+                ..
+            } = select;
+            for item in projection {
+                match item {
+                    SelectItem::UnnamedExpr(expr) => bind_expr(expr, params),
+                    SelectItem::ExprWithAlias { expr, .. } => bind_expr(expr, params),
+                    _ => {}
+                }
+            }
+            if let Some(selection) = selection {
+                bind_expr(selection, params);
+            }
+            for expr in group_by {
+                bind_expr(expr, params);
+            }
+            if let Some(having) = having {
+                bind_expr(having, params);
+            }
+        }
+        SetExpr::Query(query) => bind_query(query, params),
+        SetExpr::SetOperation { left, right, .. } => {
+            bind_set_expr(left, params);
+            bind_set_expr(right, params);
+        }
+        SetExpr::Values(values) => {
+            for line in values.0.iter_mut() {
+                for col in line {
+                    bind_expr(col, params);
+                }
+            }
+        }
+    }
+}
+
+fn bind_expr(expr: &mut Expr, params: &[Expr]) {
+    match expr {
+        Expr::Identifier(_) => replace_expr_with_params(expr, params),
+        Expr::BinaryOp { left, right, .. } => {
+            bind_expr(left, params);
+            bind_expr(right, params);
+        }
+        Expr::UnaryOp { expr, .. } => bind_expr(expr, params),
+        Expr::Nested(expr) => bind_expr(expr, params),
+        Expr::IsNull(expr) => bind_expr(expr, params),
+        Expr::IsNotNull(expr) => bind_expr(expr, params),
+        Expr::Cast { expr, .. } => bind_expr(expr, params),
+        Expr::Collate { expr, .. } => bind_expr(expr, params),
+        Expr::Extract { expr, .. } => bind_expr(expr, params),
+        Expr::Between { expr, low, high, .. } => {
+            bind_expr(expr, params);
+            bind_expr(low, params);
+            bind_expr(high, params);
+        }
+        Expr::InList { expr, list, .. } => {
+            bind_expr(expr, params);
+            for item in list {
+                bind_expr(item, params);
+            }
+        }
+        Expr::InSubquery { expr, subquery, .. } => {
+            bind_expr(expr, params);
+            bind_query(subquery, params);
+        }
+        Expr::Function(Function { args, .. }) => {
+            for arg in args {
+                match arg {
+                    FunctionArg::Named { arg, .. } => bind_expr(arg, params),
+                    FunctionArg::Unnamed(arg) => bind_expr(arg, params),
+                }
+            }
+        }
+        Expr::Case {
+            operand,
+            conditions,
+            results,
+            else_result,
+        } => {
+            if let Some(operand) = operand {
+                bind_expr(operand, params);
+            }
+            for condition in conditions {
+                bind_expr(condition, params);
+            }
+            for result in results {
+                bind_expr(result, params);
+            }
+            if let Some(else_result) = else_result {
+                bind_expr(else_result, params);
+            }
+        }
+        Expr::Exists(query) => bind_query(query, params),
+        Expr::Subquery(query) => bind_query(query, params),
+        _ => {}
+    }
+}
+
 fn parse_param_index(value: &str) -> Option<usize> {
     let mut chars = value.chars();
     if chars.next() != Some('$') || !chars.all(|c| c.is_digit(10)) {
@@ -385,4 +493,129 @@ mod tests {
             "UPDATE schema_name.table_name SET column_1 = 1, column_2 = 'abc'"
         );
     }
+
+    fn select_all_from_table(selection: Option<Expr>, limit: Option<Expr>) -> Statement {
+        Statement::Query(Box::new(Query {
+            with: None,
+            body: SetExpr::Select(Box::new(Select {
+                distinct: false,
+                top: None,
+                projection: vec![SelectItem::Wildcard],
+                from: vec![TableWithJoins {
+                    relation: TableFactor::Table {
+                        name: ObjectName(vec![ident("schema_name"), ident("table_name")]),
+                        alias: None,
+                        args: vec![],
+                        with_hints: vec![],
+                    },
+                    joins: vec![],
+                }],
+                selection,
+                group_by: vec![],
+                having: None,
+            })),
+            order_by: vec![],
+            limit,
+            offset: None,
+            fetch: None,
+        }))
+    }
+
+    #[test]
+    fn bind_select_statement_with_parameters_in_nested_predicates() {
+        let mut statement = select_all_from_table(
+            Some(Expr::BinaryOp {
+                left: Box::new(Expr::BinaryOp {
+                    left: Box::new(Expr::Identifier(ident("column_1"))),
+                    op: BinaryOperator::Gt,
+                    right: Box::new(Expr::Identifier(ident("$1"))),
+                }),
+                op: BinaryOperator::And,
+                right: Box::new(Expr::BinaryOp {
+                    left: Box::new(Expr::Identifier(ident("column_2"))),
+                    op: BinaryOperator::Eq,
+                    right: Box::new(Expr::Identifier(ident("$2"))),
+                }),
+            }),
+            None,
+        );
+
+        ParamBinder
+            .bind(
+                &mut statement,
+                &[
+                    Expr::Value(Value::Number(BigDecimal::from(123))),
+                    Expr::Value(Value::SingleQuotedString("abc".into())),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            statement.to_string(),
+            "SELECT * FROM schema_name.table_name WHERE column_1 > 123 AND column_2 = 'abc'"
+        );
+    }
+
+    #[test]
+    fn bind_select_statement_with_parameter_in_limit() {
+        let mut statement = select_all_from_table(None, Some(Expr::Identifier(ident("$1"))));
+
+        ParamBinder
+            .bind(&mut statement, &[Expr::Value(Value::Number(BigDecimal::from(10)))])
+            .unwrap();
+
+        assert_eq!(statement.to_string(), "SELECT * FROM schema_name.table_name LIMIT 10");
+    }
+
+    #[test]
+    fn bind_update_statement_with_parameter_in_where_clause() {
+        let mut statement = Statement::Update {
+            table_name: ObjectName(vec![ident("schema_name"), ident("table_name")]),
+            assignments: vec![Assignment {
+                id: ident("column_1"),
+                value: Expr::Identifier(ident("$1")),
+            }],
+            selection: Some(Expr::BinaryOp {
+                left: Box::new(Expr::Identifier(ident("column_2"))),
+                op: BinaryOperator::Eq,
+                right: Box::new(Expr::Identifier(ident("$2"))),
+            }),
+        };
+
+        ParamBinder
+            .bind(
+                &mut statement,
+                &[
+                    Expr::Value(Value::Number(BigDecimal::from(1))),
+                    Expr::Value(Value::Number(BigDecimal::from(2))),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            statement.to_string(),
+            "UPDATE schema_name.table_name SET column_1 = 1 WHERE column_2 = 2"
+        );
+    }
+
+    #[test]
+    fn bind_delete_statement() {
+        let mut statement = Statement::Delete {
+            table_name: ObjectName(vec![ident("schema_name"), ident("table_name")]),
+            selection: Some(Expr::BinaryOp {
+                left: Box::new(Expr::Identifier(ident("column_1"))),
+                op: BinaryOperator::Eq,
+                right: Box::new(Expr::Identifier(ident("$1"))),
+            }),
+        };
+
+        ParamBinder
+            .bind(&mut statement, &[Expr::Value(Value::Number(BigDecimal::from(1)))])
+            .unwrap();
+
+        assert_eq!(
+            statement.to_string(),
+            "DELETE FROM schema_name.table_name WHERE column_1 = 1"
+        );
+    }
 }
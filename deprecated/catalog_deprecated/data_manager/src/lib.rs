@@ -12,19 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use binary::{Binary, Key, ReadCursor, Values};
+use binary::{Binary, Key, ReadCursor, RowResult, Values};
 use definition_operations::{Record, Step, SystemObject};
 use repr::Datum;
-use sql_model::{DropSchemaError, DropStrategy};
+use sql_model::{sql_errors::DefinitionError, DropSchemaError, DropStrategy};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     fmt,
     fmt::{Display, Formatter},
+    mem,
     ops::Deref,
     path::PathBuf,
-    sync::Arc,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex, MutexGuard, PoisonError, RwLock,
+    },
+    time::{Duration, Instant},
 };
-use storage::{Database, InMemoryDatabase, InitStatus, PersistentDatabase};
+use storage::{Database, InMemoryDatabase, InitStatus, PersistentDatabase, Sequence, OIDS_SEQUENCE};
 use types::SqlType;
 
 pub const DEFAULT_CATALOG: &'_ str = "default_catalog";
@@ -50,6 +55,24 @@ pub const TABLES_TABLE: &'_ str = "TABLES";
 /// CHARACTER_MAXIMUM_LENGTH    integer CHECK (VALUE >= 0),
 /// NUMERIC_PRECISION           integer CHECK (VALUE >= 0),
 pub const COLUMNS_TABLE: &'_ str = "COLUMNS";
+/// **TABLE_OPTIONS** sql type definition
+/// TABLE_CATALOG   varchar(255)
+/// TABLE_SCHEMA    varchar(255)
+/// TABLE_NAME      varchar(255)
+/// OPTION_NAME     varchar(255)
+/// OPTION_VALUE    varchar(255)
+pub const TABLE_OPTIONS_TABLE: &'_ str = "TABLE_OPTIONS";
+/// identifiers of schemas and tables start from the first one that
+/// PostgreSQL gives to user defined objects
+pub const FIRST_OBJECT_ID: Id = 16384;
+/// rows are rewritten, swapped and restored by `rewrite_table` in batches
+/// of the size
+const REWRITE_BATCH_SIZE: usize = 1024;
+/// suffix of the object that holds rewritten rows of a table
+const REWRITE_SHADOW_SUFFIX: &str = "#rewrite";
+/// how long `rewrite_table` waits for open scans of other threads before
+/// it gives up the switch
+const REWRITE_SCANS_TIMEOUT: Duration = Duration::from_secs(1);
 
 use meta_def::{ColumnDefinition, Id};
 
@@ -97,8 +120,110 @@ pub trait DataDefOperationExecutor {
     fn execute(&self, operation: &Step) -> Result<(), ()>;
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ChangeKind {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// row change written to a table, `old` is `None` for inserts and `new` is
+/// `None` for deletes
+#[derive(Debug, PartialEq, Clone)]
+pub struct RowChange {
+    pub schema_name: String,
+    pub table_name: String,
+    pub kind: ChangeKind,
+    pub key: Key,
+    pub old: Option<Values>,
+    pub new: Option<Values>,
+}
+
+/// options a table was created with, e.g. `WITH (ttl = '1 day')`
+#[derive(Debug, PartialEq, Clone)]
+pub struct TableOptions {
+    pub full_table_id: (Id, Id),
+    pub schema_name: String,
+    pub table_name: String,
+    pub options: Vec<(String, String)>,
+}
+
+/// number of cursors returned by `full_scan` that are not dropped yet.
+/// `rewrite_table` waits until all of them are closed before it switches
+/// rows and column definitions of a table
+#[derive(Default)]
+struct OpenScans {
+    count: Mutex<usize>,
+    closed: Condvar,
+}
+
+impl OpenScans {
+    fn open(self: &Arc<Self>, cursor: ReadCursor) -> ReadCursor {
+        *self.count.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+        Box::new(ScanCursor {
+            cursor,
+            scans: self.clone(),
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        *self.count.lock().unwrap_or_else(PoisonError::into_inner) == 0
+    }
+
+    /// waits until all scans are closed, `false` if some are still open at
+    /// `deadline`
+    fn wait_closed(&self, deadline: Instant) -> bool {
+        let mut count = self.count.lock().unwrap_or_else(PoisonError::into_inner);
+        while *count > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            count = self
+                .closed
+                .wait_timeout(count, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        true
+    }
+}
+
+struct ScanCursor {
+    cursor: ReadCursor,
+    scans: Arc<OpenScans>,
+}
+
+impl Iterator for ScanCursor {
+    type Item = RowResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.next()
+    }
+}
+
+impl Drop for ScanCursor {
+    fn drop(&mut self) {
+        *self.scans.count.lock().unwrap_or_else(PoisonError::into_inner) -= 1;
+        self.scans.closed.notify_all();
+    }
+}
+
+/// reasons `rewrite_table` leaves a table intact other than storage failures
+#[derive(Debug, PartialEq)]
+pub enum RewriteError<E> {
+    /// rewrite of a row failed
+    Row(E),
+    /// scans of the table were not closed in time for the switch
+    ScansOpen,
+}
+
 pub struct DatabaseHandle {
     inner: DatabaseHandleInner,
+    subscribers: Mutex<Vec<Sender<RowChange>>>,
+    catalog_lock: Mutex<()>,
+    rewrite_lock: RwLock<()>,
+    open_scans: Arc<OpenScans>,
 }
 
 enum DatabaseHandleInner {
@@ -127,9 +252,18 @@ impl DatabaseHandle {
         database_instance.bootstrap();
         DatabaseHandle {
             inner: DatabaseHandleInner::InMemory(Arc::new(database_instance)),
+            subscribers: Mutex::default(),
+            catalog_lock: Mutex::default(),
+            rewrite_lock: RwLock::default(),
+            open_scans: Arc::default(),
         }
     }
 
+    /// whether the catalog could be read from the storage
+    pub fn is_available(&self) -> bool {
+        matches!(self.inner.read(DEFINITION_SCHEMA, SCHEMATA_TABLE), Ok(Ok(Ok(_))))
+    }
+
     #[allow(clippy::result_unit_err)]
     pub fn persistent(path: PathBuf) -> Result<DatabaseHandle, ()> {
         let database_instance = PersistentDatabase::new(path.join(DEFAULT_CATALOG));
@@ -157,6 +291,14 @@ impl DatabaseHandle {
                 .filter(|(catalog_id, _schema)| catalog_id == &DEFAULT_CATALOG_ID)
                 .map(|(_catalog_id, schema)| schema)
                 .collect::<Vec<String>>();
+            // catalogs created before options of tables were stored don't have their table
+            match database_instance.create_object(DEFINITION_SCHEMA, TABLE_OPTIONS_TABLE) {
+                Ok(Ok(Ok(()))) | Ok(Ok(Err(DefinitionError::ObjectAlreadyExists))) => {}
+                error => {
+                    log::error!("Error during {:?} initialization {:?}", TABLE_OPTIONS_TABLE, error);
+                    return Err(());
+                }
+            }
             for schema_name in schema_names {
                 match database_instance.init(&schema_name) {
                     Ok(Ok(InitStatus::Loaded)) => {}
@@ -177,10 +319,41 @@ impl DatabaseHandle {
         }
         Ok(DatabaseHandle {
             inner: DatabaseHandleInner::Persistent(Arc::new(database_instance)),
+            subscribers: Mutex::default(),
+            catalog_lock: Mutex::default(),
+            rewrite_lock: RwLock::default(),
+            open_scans: Arc::default(),
         })
     }
 
+    /// serializes changes of the catalog made by concurrent connections. Steps
+    /// of a schema change, from checks of objects existence to writes of their
+    /// records, have to be executed while the lock is held
+    pub fn lock_catalog(&self) -> MutexGuard<'_, ()> {
+        self.catalog_lock.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// allocates identifier of a new schema or table, ids are kept in the
+    /// catalog so they are unique among all objects and stable across restarts
+    fn next_oid(&self) -> Id {
+        FIRST_OBJECT_ID
+            + self
+                .inner
+                .get_sequence(DEFINITION_SCHEMA, OIDS_SEQUENCE)
+                .expect("to have OIDS sequence")
+                .next()
+    }
+
     pub fn next_key_id(&self, full_table_id: &(Id, Id)) -> Id {
+        self.records_sequence(full_table_id).next()
+    }
+
+    /// reserves keys for `count` records of the table at once
+    pub fn next_key_ids(&self, full_table_id: &(Id, Id), count: usize) -> Vec<Id> {
+        self.records_sequence(full_table_id).next_values(count)
+    }
+
+    fn records_sequence(&self, full_table_id: &(Id, Id)) -> Arc<dyn Sequence> {
         let (schema_name, table_name) = self
             .inner
             .read(DEFINITION_SCHEMA, TABLES_TABLE)
@@ -205,16 +378,11 @@ impl DatabaseHandle {
         self.inner
             .get_sequence(&schema_name, &(table_name + ".records"))
             .unwrap()
-            .next()
     }
 
     #[allow(clippy::result_unit_err)]
     pub fn create_schema(&self, schema_name: &str) -> Result<Id, ()> {
-        let schema_id = self
-            .inner
-            .get_sequence(DEFINITION_SCHEMA, &(SCHEMATA_TABLE.to_owned() + ".records"))
-            .unwrap()
-            .next();
+        let schema_id = self.next_oid();
         self.inner
             .write(
                 DEFINITION_SCHEMA,
@@ -386,6 +554,7 @@ impl DatabaseHandle {
                             .expect("no io error")
                             .expect("no platform error")
                             .expect("to have remove tables columns under catalog");
+                        self.delete_table_options(|ids| ids[1].as_u64() == schema_id);
                         Ok(())
                     }
                 }
@@ -418,11 +587,7 @@ impl DatabaseHandle {
         match schema {
             None => unimplemented!(),
             Some(schema_name) => {
-                let table_id = self
-                    .inner
-                    .get_sequence(DEFINITION_SCHEMA, &(TABLES_TABLE.to_owned() + ".records"))
-                    .unwrap()
-                    .next();
+                let table_id = self.next_oid();
                 self.inner
                     .write(
                         DEFINITION_SCHEMA,
@@ -544,6 +709,7 @@ impl DatabaseHandle {
                     .expect("no io error")
                     .expect("no platform error")
                     .expect("to remove table");
+                self.delete_table_options(|ids| (ids[1].as_u64(), ids[2].as_u64()) == *full_table_id);
                 match self.inner.drop_object(full_name.0.as_str(), full_name.1.as_str()) {
                     Ok(Ok(Ok(()))) => Ok(()),
                     _ => {
@@ -580,8 +746,13 @@ impl DatabaseHandle {
         match full_table_name {
             Some(full_name) => {
                 log::trace!("values to write {:#?}", values);
+                let _rewrite = self.rewrite_lock.read().unwrap_or_else(PoisonError::into_inner);
+                let changes = self.changes_of(&full_name, values.iter().map(|(key, values)| (key, Some(values))));
                 match self.inner.write(full_name.0.as_str(), full_name.1.as_str(), values) {
-                    Ok(Ok(Ok(size))) => Ok(size),
+                    Ok(Ok(Ok(size))) => {
+                        self.publish(changes);
+                        Ok(size)
+                    }
                     _ => {
                         let (schema_id, table_id) = full_table_id;
                         engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
@@ -599,6 +770,8 @@ impl DatabaseHandle {
 
     #[allow(clippy::result_unit_err)]
     pub fn full_scan(&self, full_table_id: &(Id, Id)) -> Result<ReadCursor, ()> {
+        // scans open either before or after rows and columns of the table are switched
+        let _rewrite = self.rewrite_lock.read().unwrap_or_else(PoisonError::into_inner);
         let full_table_name = self
             .inner
             .read(DEFINITION_SCHEMA, TABLES_TABLE)
@@ -620,7 +793,7 @@ impl DatabaseHandle {
             .map(|(_schema_id, _table_id, schema_name, table_name)| (schema_name, table_name));
         match full_table_name {
             Some(full_name) => match self.inner.read(full_name.0.as_str(), full_name.1.as_str()) {
-                Ok(Ok(Ok(read))) => Ok(read),
+                Ok(Ok(Ok(read))) => Ok(self.open_scans.open(read)),
                 _ => {
                     let (schema_id, table_id) = full_table_id;
                     engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
@@ -657,14 +830,21 @@ impl DatabaseHandle {
             .find(|(schema_id, table_id, _schema_name, _table_name)| full_table_id == &(*schema_id, *table_id))
             .map(|(_schema_id, _table_id, schema_name, table_name)| (schema_name, table_name));
         match full_table_name {
-            Some(full_name) => match self.inner.delete(full_name.0.as_str(), full_name.1.as_str(), keys) {
-                Ok(Ok(Ok(len))) => Ok(len),
-                _ => {
-                    let (schema_id, table_id) = full_table_id;
-                    engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
-                    Err(())
+            Some(full_name) => {
+                let _rewrite = self.rewrite_lock.read().unwrap_or_else(PoisonError::into_inner);
+                let changes = self.changes_of(&full_name, keys.iter().map(|key| (key, None)));
+                match self.inner.delete(full_name.0.as_str(), full_name.1.as_str(), keys) {
+                    Ok(Ok(Ok(len))) => {
+                        self.publish(changes);
+                        Ok(len)
+                    }
+                    _ => {
+                        let (schema_id, table_id) = full_table_id;
+                        engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+                        Err(())
+                    }
                 }
-            },
+            }
             None => {
                 let (schema_id, table_id) = full_table_id;
                 engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
@@ -672,6 +852,583 @@ impl DatabaseHandle {
             }
         }
     }
+
+    /// deletes rows with `keys` whose current values still satisfy
+    /// `predicate`, so rows changed after they were read are kept. Writers of
+    /// the table are blocked while the rows are checked and deleted. Returns
+    /// number of deleted rows
+    #[allow(clippy::result_unit_err)]
+    pub fn delete_from_if<P: Fn(&Values) -> bool>(
+        &self,
+        full_table_id: &(Id, Id),
+        keys: Vec<Key>,
+        predicate: P,
+    ) -> Result<usize, ()> {
+        let (schema_id, table_id) = full_table_id;
+        let full_name = match self.full_table_name(full_table_id) {
+            Some(full_name) => full_name,
+            None => {
+                engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+                return Err(());
+            }
+        };
+        let _rewrite = self.rewrite_lock.write().unwrap_or_else(PoisonError::into_inner);
+        let mut keys = keys.into_iter().collect::<BTreeSet<Key>>();
+        let mut to_delete = vec![];
+        match self.inner.read(full_name.0.as_str(), full_name.1.as_str()) {
+            Ok(Ok(Ok(read))) => {
+                for row in read {
+                    if keys.is_empty() {
+                        break;
+                    }
+                    match row {
+                        Ok(Ok((key, values))) => {
+                            if keys.remove(&key) && predicate(&values) {
+                                to_delete.push(key);
+                            }
+                        }
+                        _ => {
+                            engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+                            return Err(());
+                        }
+                    }
+                }
+            }
+            _ => {
+                engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+                return Err(());
+            }
+        }
+        if to_delete.is_empty() {
+            return Ok(0);
+        }
+        let changes = self.changes_of(&full_name, to_delete.iter().map(|key| (key, None)));
+        match self.inner.delete(full_name.0.as_str(), full_name.1.as_str(), to_delete) {
+            Ok(Ok(Ok(len))) => {
+                self.publish(changes);
+                Ok(len)
+            }
+            _ => {
+                engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+                Err(())
+            }
+        }
+    }
+
+    /// stores `options` of the table, values of options that are already
+    /// stored are replaced
+    #[allow(clippy::result_unit_err)]
+    pub fn set_table_options(&self, full_table_id: &(Id, Id), options: Vec<(String, String)>) -> Result<(), ()> {
+        let (schema_id, table_id) = full_table_id;
+        let full_name = match self.full_table_name(full_table_id) {
+            Some(full_name) => full_name,
+            None => {
+                engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+                return Err(());
+            }
+        };
+        let records = options
+            .into_iter()
+            .map(|(name, value)| {
+                (
+                    Binary::pack(&[
+                        DEFAULT_CATALOG_ID,
+                        Datum::from_u64(*schema_id),
+                        Datum::from_u64(*table_id),
+                        Datum::from_str(&name),
+                    ]),
+                    Binary::pack(&[
+                        Datum::from_str(DEFAULT_CATALOG),
+                        Datum::from_str(&full_name.0),
+                        Datum::from_str(&full_name.1),
+                        Datum::from_str(&name),
+                        Datum::from_str(&value),
+                    ]),
+                )
+            })
+            .collect::<Vec<(Key, Values)>>();
+        if self.write_rows(DEFINITION_SCHEMA, TABLE_OPTIONS_TABLE, records) {
+            Ok(())
+        } else {
+            engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+            Err(())
+        }
+    }
+
+    /// options of all tables that have any
+    #[allow(clippy::result_unit_err)]
+    pub fn table_options(&self) -> Result<Vec<TableOptions>, ()> {
+        let records = match self.inner.read(DEFINITION_SCHEMA, TABLE_OPTIONS_TABLE) {
+            Ok(Ok(Ok(read))) => read
+                .map(|row| row.ok().and_then(Result::ok))
+                .collect::<Option<Vec<(Key, Values)>>>()
+                .ok_or(())?,
+            _ => return Err(()),
+        };
+        let mut tables = BTreeMap::<(Id, Id), TableOptions>::new();
+        for (record_id, columns) in records {
+            let ids = record_id.unpack();
+            let data = columns.unpack();
+            let full_table_id = (ids[1].as_u64(), ids[2].as_u64());
+            tables
+                .entry(full_table_id)
+                .or_insert_with(|| TableOptions {
+                    full_table_id,
+                    schema_name: data[1].as_str().to_owned(),
+                    table_name: data[2].as_str().to_owned(),
+                    options: vec![],
+                })
+                .options
+                .push((data[3].as_str().to_owned(), data[4].as_str().to_owned()));
+        }
+        Ok(tables.into_values().collect())
+    }
+
+    /// removes stored options of tables with ids of records matching `filter`
+    fn delete_table_options<F: Fn(&[Datum]) -> bool>(&self, filter: F) {
+        let record_ids = self
+            .inner
+            .read(DEFINITION_SCHEMA, TABLE_OPTIONS_TABLE)
+            .expect("no io error")
+            .expect("no platform error")
+            .expect("to have TABLE_OPTIONS table")
+            .map(Result::unwrap)
+            .map(Result::unwrap)
+            .map(|(record_id, _data)| record_id)
+            .filter(|record_id| filter(&record_id.unpack()))
+            .collect::<Vec<Key>>();
+        if !record_ids.is_empty() {
+            self.inner
+                .delete(DEFINITION_SCHEMA, TABLE_OPTIONS_TABLE, record_ids)
+                .expect("no io error")
+                .expect("no platform error")
+                .expect("to remove table options");
+        }
+    }
+
+    fn full_table_name(&self, full_table_id: &(Id, Id)) -> Option<(String, String)> {
+        self.inner
+            .read(DEFINITION_SCHEMA, TABLES_TABLE)
+            .expect("no io error")
+            .expect("no platform error")
+            .expect("to have TABLES table")
+            .map(Result::unwrap)
+            .map(Result::unwrap)
+            .map(|(record_id, columns)| {
+                let ids = record_id.unpack();
+                let data = columns.unpack();
+                let schema_name = data[1].as_str().to_owned();
+                let table_name = data[2].as_str().to_owned();
+                ((ids[1].as_u64(), ids[2].as_u64()), (schema_name, table_name))
+            })
+            .find(|(id, _full_name)| id == full_table_id)
+            .map(|(_id, full_name)| full_name)
+    }
+
+    /// rewrites every row of the table with `rewrite` and replaces its column
+    /// definitions with `columns`, position by position. Rewritten rows are
+    /// written batch by batch into a shadow object while the table stays
+    /// writable and changes made meanwhile are replayed on it. The switch
+    /// blocks writers, waits for open scans and swaps rows of the table with
+    /// the shadow ones before column definitions are written. If `rewrite` or
+    /// any write fails the table and its columns are left intact. Scans that
+    /// are still open after `REWRITE_SCANS_TIMEOUT` fail the rewrite with
+    /// `RewriteError::ScansOpen`, as scans of statements suspended on the
+    /// thread that runs the rewrite can't be closed while it waits.
+    ///
+    /// The switch is not crash-safe: rows are swapped batch by batch, a crash
+    /// in the middle of it leaves the table with rows of both versions and
+    /// with the original column definitions.
+    ///
+    /// The caller has to hold `lock_catalog` for the whole rewrite
+    // TODO: storage can't rename objects or write into several of them at
+    //       once, so rows are swapped in place while writers are blocked.
+    //       Shadow object of a rewrite interrupted by a crash is dropped only
+    //       by the next rewrite of the table.
+    //       Subscribers are not notified about rewritten rows
+    #[allow(clippy::result_unit_err)]
+    pub fn rewrite_table<E, F: Fn(&Values) -> Result<Values, E>>(
+        &self,
+        full_table_id: &(Id, Id),
+        columns: &[ColumnDefinition],
+        rewrite: F,
+    ) -> Result<Result<usize, RewriteError<E>>, ()> {
+        let (schema_id, table_id) = full_table_id;
+        let full_name = match self
+            .inner
+            .read(DEFINITION_SCHEMA, TABLES_TABLE)
+            .expect("no io error")
+            .expect("no platform error")
+            .expect("to have TABLES table")
+            .map(Result::unwrap)
+            .map(Result::unwrap)
+            .map(|(record_id, columns)| {
+                let ids = record_id.unpack();
+                let data = columns.unpack();
+                let schema_name = data[1].as_str().to_owned();
+                let table_name = data[2].as_str().to_owned();
+                ((ids[1].as_u64(), ids[2].as_u64()), (schema_name, table_name))
+            })
+            .find(|(id, _full_name)| id == full_table_id)
+        {
+            Some((_id, full_name)) => full_name,
+            None => {
+                engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+                return Err(());
+            }
+        };
+
+        let shadow = format!("{}{}", full_name.1, REWRITE_SHADOW_SUFFIX);
+        // shadow object can be left by a rewrite interrupted by a crash
+        let _ = self.inner.drop_object(full_name.0.as_str(), shadow.as_str());
+        if !matches!(
+            self.inner.create_object(full_name.0.as_str(), shadow.as_str()),
+            Ok(Ok(Ok(())))
+        ) {
+            engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+            return Err(());
+        }
+        let rewritten = self.rewrite_through_shadow(full_table_id, &full_name, shadow.as_str(), columns, rewrite);
+        if !matches!(
+            self.inner.drop_object(full_name.0.as_str(), shadow.as_str()),
+            Ok(Ok(Ok(())))
+        ) {
+            log::warn!("could not drop shadow object {}.{}", full_name.0, shadow);
+        }
+        rewritten
+    }
+
+    fn rewrite_through_shadow<E, F: Fn(&Values) -> Result<Values, E>>(
+        &self,
+        full_table_id: &(Id, Id),
+        full_name: &(String, String),
+        shadow: &str,
+        columns: &[ColumnDefinition],
+        rewrite: F,
+    ) -> Result<Result<usize, RewriteError<E>>, ()> {
+        let (schema_id, table_id) = full_table_id;
+        let schema_name = full_name.0.as_str();
+        let bug = || engine_bug_reporter(Operation::Access, Object::Table(*schema_id, *table_id));
+
+        let changes = self.subscribe_to_changes();
+        let mut batch = Vec::with_capacity(REWRITE_BATCH_SIZE);
+        for row in self.full_scan(full_table_id)? {
+            let (key, values) = match row {
+                Ok(Ok(row)) => row,
+                _ => {
+                    bug();
+                    return Err(());
+                }
+            };
+            match rewrite(&values) {
+                Ok(values) => batch.push((key, values)),
+                Err(error) => return Ok(Err(RewriteError::Row(error))),
+            }
+            if batch.len() == REWRITE_BATCH_SIZE && !self.write_rows(schema_name, shadow, mem::take(&mut batch)) {
+                bug();
+                return Err(());
+            }
+        }
+        if !self.write_rows(schema_name, shadow, batch) {
+            bug();
+            return Err(());
+        }
+        let catch_up = || -> Result<Result<(), E>, ()> {
+            let mut pending = BTreeMap::new();
+            for change in changes.try_iter() {
+                if change.schema_name == full_name.0 && change.table_name == full_name.1 {
+                    pending.insert(change.key, change.new);
+                }
+            }
+            let mut written = vec![];
+            let mut deleted = vec![];
+            for (key, values) in pending {
+                match values {
+                    Some(values) => match rewrite(&values) {
+                        Ok(values) => written.push((key, values)),
+                        Err(error) => return Ok(Err(error)),
+                    },
+                    None => deleted.push(key),
+                }
+            }
+            let applied = self.write_rows(schema_name, shadow, written)
+                && (deleted.is_empty() || matches!(self.inner.delete(schema_name, shadow, deleted), Ok(Ok(Ok(_)))));
+            if applied {
+                Ok(Ok(()))
+            } else {
+                bug();
+                Err(())
+            }
+        };
+        if let Err(error) = catch_up()? {
+            return Ok(Err(RewriteError::Row(error)));
+        }
+
+        // blocking writers while a scan is open would deadlock with a statement
+        // that scans and then writes, so the lock is released until scans close
+        let deadline = Instant::now() + REWRITE_SCANS_TIMEOUT;
+        let _switch = loop {
+            let switch = self.rewrite_lock.write().unwrap_or_else(PoisonError::into_inner);
+            if self.open_scans.is_empty() {
+                break switch;
+            }
+            drop(switch);
+            if !self.open_scans.wait_closed(deadline) {
+                return Ok(Err(RewriteError::ScansOpen));
+            }
+        };
+        if let Err(error) = catch_up()? {
+            return Ok(Err(RewriteError::Row(error)));
+        }
+        let swapped = match self.swap_rows(schema_name, full_name.1.as_str(), shadow) {
+            Ok(swapped) => swapped,
+            Err(()) => {
+                bug();
+                return Err(());
+            }
+        };
+        if self.replace_columns(full_table_id, full_name, columns).is_err() {
+            // shadow holds original rows of the table after the swap
+            if !self.copy_rows(schema_name, shadow, full_name.1.as_str(), swapped) {
+                log::error!("could not restore rows of {}.{}", full_name.0, full_name.1);
+            }
+            bug();
+            return Err(());
+        }
+        Ok(Ok(swapped))
+    }
+
+    /// writes rows of `shadow` into the table and original rows of the table
+    /// into `shadow`, batch by batch. Both have to have the same keys. If the
+    /// swap fails rows that are already swapped are restored
+    fn swap_rows(&self, schema_name: &str, table_name: &str, shadow: &str) -> Result<usize, ()> {
+        let (mut originals, mut rewritten) = match (
+            self.inner.read(schema_name, table_name),
+            self.inner.read(schema_name, shadow),
+        ) {
+            (Ok(Ok(Ok(originals))), Ok(Ok(Ok(rewritten)))) => (originals, rewritten),
+            _ => return Err(()),
+        };
+        let mut swapped = 0;
+        let mut to_restore = 0;
+        let succeeded = loop {
+            let mut original_batch = Vec::with_capacity(REWRITE_BATCH_SIZE);
+            let mut rewritten_batch = Vec::with_capacity(REWRITE_BATCH_SIZE);
+            let mut keys_match = true;
+            while original_batch.len() < REWRITE_BATCH_SIZE {
+                match (originals.next(), rewritten.next()) {
+                    (None, None) => break,
+                    (Some(Ok(Ok(original))), Some(Ok(Ok(row)))) if original.0 == row.0 => {
+                        original_batch.push(original);
+                        rewritten_batch.push(row);
+                    }
+                    _ => {
+                        keys_match = false;
+                        break;
+                    }
+                }
+            }
+            let size = original_batch.len();
+            if size > 0 {
+                if !self.write_rows(schema_name, shadow, original_batch) {
+                    break false;
+                }
+                to_restore += size;
+                if !self.write_rows(schema_name, table_name, rewritten_batch) {
+                    break false;
+                }
+                swapped += size;
+            }
+            if !keys_match {
+                break false;
+            }
+            if size < REWRITE_BATCH_SIZE {
+                break true;
+            }
+        };
+        if succeeded {
+            Ok(swapped)
+        } else {
+            if !self.copy_rows(schema_name, shadow, table_name, to_restore) {
+                log::error!("could not restore rows of {}.{}", schema_name, table_name);
+            }
+            Err(())
+        }
+    }
+
+    /// copies first `count` rows of `from` into `to` batch by batch
+    fn copy_rows(&self, schema_name: &str, from: &str, to: &str, count: usize) -> bool {
+        let mut rows = match self.inner.read(schema_name, from) {
+            Ok(Ok(Ok(rows))) => rows.take(count),
+            _ => return false,
+        };
+        loop {
+            let batch = rows
+                .by_ref()
+                .take(REWRITE_BATCH_SIZE)
+                .map(|row| row.ok().and_then(Result::ok))
+                .collect::<Option<Vec<(Key, Values)>>>();
+            match batch {
+                Some(batch) if batch.is_empty() => return true,
+                Some(batch) => {
+                    if !self.write_rows(schema_name, to, batch) {
+                        return false;
+                    }
+                }
+                None => return false,
+            }
+        }
+    }
+
+    fn write_rows(&self, schema_name: &str, object_name: &str, rows: Vec<(Key, Values)>) -> bool {
+        rows.is_empty() || matches!(self.inner.write(schema_name, object_name, rows), Ok(Ok(Ok(_))))
+    }
+
+    /// replaces column records of the table with `columns`, records of the
+    /// columns are restored if they can't be replaced
+    fn replace_columns(
+        &self,
+        full_table_id: &(Id, Id),
+        full_name: &(String, String),
+        columns: &[ColumnDefinition],
+    ) -> Result<(), ()> {
+        let (schema_id, table_id) = full_table_id;
+        let column_records = match self.inner.read(DEFINITION_SCHEMA, COLUMNS_TABLE) {
+            Ok(Ok(Ok(read))) => read
+                .map(|row| row.ok().and_then(Result::ok))
+                .collect::<Option<Vec<(Key, Values)>>>()
+                .ok_or(())?
+                .into_iter()
+                .filter(|(record_id, _columns)| {
+                    let ids = record_id.unpack();
+                    (ids[1].as_u64(), ids[2].as_u64()) == *full_table_id
+                })
+                .collect::<BTreeMap<Key, Values>>(),
+            _ => return Err(()),
+        };
+        let mut column_ids = column_records
+            .keys()
+            .map(|record_id| record_id.unpack()[3].as_u64())
+            .collect::<Vec<Id>>();
+        column_ids.sort_unstable();
+        let next_column_id = column_ids.last().map(|id| id + 1).unwrap_or_default();
+        let column_key = |column_id: Id| {
+            Binary::pack(&[
+                DEFAULT_CATALOG_ID,
+                Datum::from_u64(*schema_id),
+                Datum::from_u64(*table_id),
+                Datum::from_u64(column_id),
+            ])
+        };
+        let surplus = column_ids
+            .iter()
+            .skip(columns.len())
+            .map(|id| column_key(*id))
+            .collect::<Vec<Key>>();
+        let definitions = columns
+            .iter()
+            .zip(column_ids.iter().copied().chain(next_column_id..))
+            .enumerate()
+            .map(|(index, (column, column_id))| {
+                let chars_len = match column.sql_type() {
+                    SqlType::Char(len) | SqlType::VarChar(len) => Datum::from_u64(len),
+                    _ => Datum::from_null(),
+                };
+                (
+                    column_key(column_id),
+                    Binary::pack(&[
+                        Datum::from_str(DEFAULT_CATALOG),
+                        Datum::from_str(&full_name.0),
+                        Datum::from_str(&full_name.1),
+                        Datum::from_u64(index as u64),
+                        Datum::from_str(column.name().as_str()),
+                        Datum::from_u64(column.sql_type().type_id()),
+                        chars_len,
+                    ]),
+                )
+            })
+            .collect::<Vec<(Key, Values)>>();
+        let added = definitions
+            .iter()
+            .map(|(key, _values)| key.clone())
+            .filter(|key| !column_records.contains_key(key))
+            .collect::<Vec<Key>>();
+        let replaced = self.write_rows(DEFINITION_SCHEMA, COLUMNS_TABLE, definitions)
+            && (surplus.is_empty()
+                || matches!(
+                    self.inner.delete(DEFINITION_SCHEMA, COLUMNS_TABLE, surplus),
+                    Ok(Ok(Ok(_)))
+                ));
+        if replaced {
+            return Ok(());
+        }
+        let restored = (added.is_empty()
+            || matches!(
+                self.inner.delete(DEFINITION_SCHEMA, COLUMNS_TABLE, added),
+                Ok(Ok(Ok(_)))
+            ))
+            && self.write_rows(DEFINITION_SCHEMA, COLUMNS_TABLE, column_records.into_iter().collect());
+        if !restored {
+            log::error!("could not restore columns of {}.{}", full_name.0, full_name.1);
+        }
+        Err(())
+    }
+
+    /// subscribes to changes of table rows, a change is sent only after it
+    /// was successfully written
+    pub fn subscribe_to_changes(&self) -> Receiver<RowChange> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    fn changes_of<'r, R: Iterator<Item = (&'r Key, Option<&'r Values>)>>(
+        &self,
+        full_name: &(String, String),
+        rows: R,
+    ) -> Vec<RowChange> {
+        if self.subscribers.lock().unwrap().is_empty() {
+            return vec![];
+        }
+        let mut current = match self.inner.read(full_name.0.as_str(), full_name.1.as_str()) {
+            Ok(Ok(Ok(read))) => read
+                .filter_map(|row| row.ok().and_then(Result::ok))
+                .collect::<BTreeMap<Key, Values>>(),
+            _ => BTreeMap::new(),
+        };
+        let mut changes = vec![];
+        for (key, new) in rows {
+            let old = match new {
+                Some(new) => current.insert(key.clone(), new.clone()),
+                None => current.remove(key),
+            };
+            let kind = match (&old, new) {
+                (None, Some(_)) => ChangeKind::Insert,
+                (Some(_), Some(_)) => ChangeKind::Update,
+                (Some(_), None) => ChangeKind::Delete,
+                (None, None) => continue,
+            };
+            changes.push(RowChange {
+                schema_name: full_name.0.clone(),
+                table_name: full_name.1.clone(),
+                kind,
+                key: key.clone(),
+                old,
+                new: new.cloned(),
+            });
+        }
+        changes
+    }
+
+    fn publish(&self, changes: Vec<RowChange>) {
+        if changes.is_empty() {
+            return;
+        }
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| changes.iter().all(|change| subscriber.send(change.clone()).is_ok()));
+    }
 }
 
 impl DataDefOperationExecutor for DatabaseHandle {
@@ -707,8 +1464,16 @@ impl DataDefOperationExecutor for DatabaseHandle {
                         .expect("to have COLUMNS table")
                         .map(Result::unwrap)
                         .map(Result::unwrap)
-                        .map(|(_record_id, columns)| columns.unpack()[2].as_str().to_owned())
-                        .any(|name| name == object_name[0]);
+                        .map(|(_record_id, columns)| {
+                            let columns = columns.unpack();
+                            (columns[1].as_str().to_owned(), columns[2].as_str().to_owned())
+                        })
+                        // tables are named either with their schema or by name only
+                        .any(|(schema, table)| match object_name.as_slice() {
+                            [schema_name, table_name] => &schema == schema_name && &table == table_name,
+                            [table_name] => &table == table_name,
+                            _ => false,
+                        });
                     if table_exists {
                         Ok(())
                     } else {
@@ -937,11 +1702,21 @@ impl DataDefOperationExecutor for DatabaseHandle {
                         .unwrap(),
                     Record::Column { .. } => unreachable!(),
                 };
+                let full_table_id = match record {
+                    Record::Table { .. } => {
+                        let ids = binary_record.unpack();
+                        Some((ids[1].as_u64(), ids[2].as_u64()))
+                    }
+                    _ => None,
+                };
                 self.inner
                     .delete(&system_schema, &system_table, vec![binary_record])
                     .expect("no io error")
                     .expect("no platform error")
                     .expect("to remove object");
+                if let Some(full_table_id) = full_table_id {
+                    self.delete_table_options(|ids| (ids[1].as_u64(), ids[2].as_u64()) == full_table_id);
+                }
                 Ok(())
             }
             Step::CreateRecord {
@@ -955,11 +1730,7 @@ impl DataDefOperationExecutor for DatabaseHandle {
                         catalog_name,
                         schema_name,
                     } => {
-                        let schema_id = self
-                            .inner
-                            .get_sequence(DEFINITION_SCHEMA, &(SCHEMATA_TABLE.to_owned() + ".records"))
-                            .unwrap()
-                            .next();
+                        let schema_id = self.next_oid();
                         vec![(
                             Binary::pack(&[DEFAULT_CATALOG_ID, Datum::from_u64(schema_id)]),
                             Binary::pack(&[Datum::from_str(&catalog_name), Datum::from_str(&schema_name)]),
@@ -989,11 +1760,7 @@ impl DataDefOperationExecutor for DatabaseHandle {
                             Some(schema_id) => schema_id,
                             None => return Err(()),
                         };
-                        let table_id = self
-                            .inner
-                            .get_sequence(DEFINITION_SCHEMA, &(TABLES_TABLE.to_owned() + ".records"))
-                            .unwrap()
-                            .next();
+                        let table_id = self.next_oid();
                         self.inner
                             .create_sequence(
                                 DEFINITION_SCHEMA,
@@ -1021,6 +1788,7 @@ impl DataDefOperationExecutor for DatabaseHandle {
                         table_name,
                         column_name,
                         sql_type,
+                        collation: _collation,
                     } => {
                         let full_table_id = self
                             .inner
@@ -1138,6 +1906,7 @@ impl DataDefReader for DatabaseHandle {
     }
 
     fn table_columns(&self, table_id: &(Id, Id)) -> Result<Vec<(Id, ColumnDefinition)>, ()> {
+        let _rewrite = self.rewrite_lock.read().unwrap_or_else(PoisonError::into_inner);
         log::debug!("FULL TABLE ID {:?}", table_id);
         match self
             .inner
@@ -1188,6 +1957,7 @@ impl DataDefReader for DatabaseHandle {
     }
 
     fn column_ids(&self, table_id: &(Id, Id), names: &[String]) -> Result<(Vec<Id>, Vec<String>), ()> {
+        let _rewrite = self.rewrite_lock.read().unwrap_or_else(PoisonError::into_inner);
         match self
             .inner
             .read(DEFINITION_SCHEMA, TABLES_TABLE)
@@ -1227,7 +1997,7 @@ impl DataDefReader for DatabaseHandle {
                 ((schema_id, table_id), column_id, name)
             })
             .filter(|(full_table_id, _column_id, _name)| full_table_id == table_id)
-            .map(|(_full_table_id, column_id, name)| (name.to_lowercase(), column_id))
+            .map(|(_full_table_id, column_id, name)| (name, column_id))
             .collect::<HashMap<_, _>>();
         log::debug!("FOUND COLUMNS: {:?}", columns);
         log::debug!("COLUMNS TO FIND: {:?}", names);
@@ -1241,6 +2011,7 @@ impl DataDefReader for DatabaseHandle {
     }
 
     fn column_defs(&self, table_id: &(Id, Id), ids: &[Id]) -> Vec<ColumnDefinition> {
+        let _rewrite = self.rewrite_lock.read().unwrap_or_else(PoisonError::into_inner);
         match self
             .inner
             .read(DEFINITION_SCHEMA, TABLES_TABLE)
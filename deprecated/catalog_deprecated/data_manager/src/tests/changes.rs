@@ -0,0 +1,135 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use binary::Binary;
+use repr::Datum;
+use types::SqlType;
+
+#[rstest::fixture]
+fn with_table(data_manager_with_schema: InMemory) -> (InMemory, (Id, Id)) {
+    for op in create_table(SCHEMA, TABLE, &[("column_1", SqlType::SmallInt)]) {
+        if data_manager_with_schema.execute(&op).is_ok() {}
+    }
+    let full_table_id = match data_manager_with_schema.table_exists(SCHEMA, TABLE) {
+        Some((schema_id, Some(table_id))) => (schema_id, table_id),
+        _ => panic!(),
+    };
+    (data_manager_with_schema, full_table_id)
+}
+
+fn key(id: u64) -> Binary {
+    Binary::pack(&[Datum::from_u64(id)])
+}
+
+fn value(val: i16) -> Binary {
+    Binary::pack(&[Datum::from_i16(val)])
+}
+
+fn change(kind: ChangeKind, id: u64, old: Option<i16>, new: Option<i16>) -> RowChange {
+    RowChange {
+        schema_name: SCHEMA.to_owned(),
+        table_name: TABLE.to_owned(),
+        kind,
+        key: key(id),
+        old: old.map(value),
+        new: new.map(value),
+    }
+}
+
+#[rstest::rstest]
+fn inserts(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    let changes = data_manager.subscribe_to_changes();
+
+    data_manager
+        .write_into(&full_table_id, vec![(key(1), value(10)), (key(2), value(20))])
+        .expect("values are inserted");
+
+    assert_eq!(
+        changes.try_iter().collect::<Vec<RowChange>>(),
+        vec![
+            change(ChangeKind::Insert, 1, None, Some(10)),
+            change(ChangeKind::Insert, 2, None, Some(20))
+        ]
+    );
+}
+
+#[rstest::rstest]
+fn updates(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    data_manager
+        .write_into(&full_table_id, vec![(key(1), value(10))])
+        .expect("values are inserted");
+    let changes = data_manager.subscribe_to_changes();
+
+    data_manager
+        .write_into(&full_table_id, vec![(key(1), value(11))])
+        .expect("values are updated");
+
+    assert_eq!(
+        changes.try_iter().collect::<Vec<RowChange>>(),
+        vec![change(ChangeKind::Update, 1, Some(10), Some(11))]
+    );
+}
+
+#[rstest::rstest]
+fn deletes(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    data_manager
+        .write_into(&full_table_id, vec![(key(1), value(10))])
+        .expect("values are inserted");
+    let changes = data_manager.subscribe_to_changes();
+
+    data_manager
+        .delete_from(&full_table_id, vec![key(1), key(2)])
+        .expect("values are deleted");
+
+    assert_eq!(
+        changes.try_iter().collect::<Vec<RowChange>>(),
+        vec![change(ChangeKind::Delete, 1, Some(10), None)]
+    );
+}
+
+#[rstest::rstest]
+fn every_subscriber_receives_changes(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    let first = data_manager.subscribe_to_changes();
+    let second = data_manager.subscribe_to_changes();
+
+    data_manager
+        .write_into(&full_table_id, vec![(key(1), value(10))])
+        .expect("values are inserted");
+
+    assert_eq!(
+        first.try_iter().collect::<Vec<RowChange>>(),
+        vec![change(ChangeKind::Insert, 1, None, Some(10))]
+    );
+    assert_eq!(
+        second.try_iter().collect::<Vec<RowChange>>(),
+        vec![change(ChangeKind::Insert, 1, None, Some(10))]
+    );
+}
+
+#[rstest::rstest]
+fn dropped_subscriber_is_removed(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    drop(data_manager.subscribe_to_changes());
+
+    data_manager
+        .write_into(&full_table_id, vec![(key(1), value(10))])
+        .expect("values are inserted");
+
+    assert!(data_manager.subscribers.lock().unwrap().is_empty());
+}
@@ -14,11 +14,15 @@
 
 use super::*;
 
+#[cfg(test)]
+mod changes;
 #[cfg(test)]
 mod persistence;
 #[cfg(test)]
 mod queries;
 #[cfg(test)]
+mod rewrite;
+#[cfg(test)]
 mod system_schema;
 
 const SCHEMA: &str = "schema_name";
@@ -67,6 +71,7 @@ fn create_table(schema_name: &str, table_name: &str, columns: &[(&str, SqlType)]
                 table_name: table_name.to_owned(),
                 column_name: column_name.to_string(),
                 sql_type: *column_type,
+                collation: None,
             },
         })
         .collect::<Vec<Step>>();
@@ -127,6 +132,7 @@ fn create_table_ops(schema_name: &str, table_name: &str, column_name: &str, colu
                 table_name: table_name.to_owned(),
                 column_name: column_name.to_owned(),
                 sql_type: column_type,
+                collation: None,
             },
         },
     ]
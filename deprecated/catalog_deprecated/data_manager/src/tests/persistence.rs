@@ -79,6 +79,40 @@ fn created_table_is_preserved_after_restart(persistent: (Persistent, TempDir)) {
     )
 }
 
+#[rstest::rstest]
+fn object_ids_are_not_reused_after_restart(persistent: (Persistent, TempDir)) {
+    let (data_manager, root_path) = persistent;
+
+    for op in create_schema_ops(SCHEMA) {
+        if data_manager.execute(&op).is_ok() {}
+    }
+    for op in create_table_ops(SCHEMA, TABLE, "col_test", SqlType::Bool) {
+        if data_manager.execute(&op).is_ok() {}
+    }
+    let schema_id = data_manager.schema_exists(SCHEMA).expect("to create a schema");
+    let table_id = match data_manager.table_exists(SCHEMA, TABLE) {
+        Some((_, Some(table_id))) => table_id,
+        _ => panic!(),
+    };
+    assert_ne!(schema_id, table_id);
+
+    drop(data_manager);
+
+    let data_manager = Persistent::persistent(root_path.path().into()).expect("to create catalog manager");
+
+    assert_eq!(data_manager.schema_exists(SCHEMA), Some(schema_id));
+    assert_eq!(
+        data_manager.table_exists(SCHEMA, TABLE),
+        Some((schema_id, Some(table_id)))
+    );
+
+    for op in create_schema_ops("other_schema") {
+        if data_manager.execute(&op).is_ok() {}
+    }
+    let other_schema_id = data_manager.schema_exists("other_schema").expect("to create a schema");
+    assert!(![schema_id, table_id].contains(&other_schema_id));
+}
+
 #[rstest::rstest]
 fn stored_data_is_preserved_after_restart(persistent: (Persistent, TempDir)) {
     let (data_manager, root_path) = persistent;
@@ -136,3 +170,44 @@ fn stored_data_is_preserved_after_restart(persistent: (Persistent, TempDir)) {
         )],
     );
 }
+
+#[rstest::rstest]
+fn table_options_are_preserved_after_restart(persistent: (Persistent, TempDir)) {
+    let (data_manager, root_path) = persistent;
+
+    for op in create_schema_ops(SCHEMA) {
+        if data_manager.execute(&op).is_ok() {}
+    }
+    for op in create_table_ops(SCHEMA, TABLE, "col_test", SqlType::BigInt) {
+        if data_manager.execute(&op).is_ok() {}
+    }
+    let full_table_id = match data_manager.table_exists(SCHEMA, TABLE) {
+        Some((schema_id, Some(table_id))) => (schema_id, table_id),
+        _ => panic!(),
+    };
+
+    data_manager
+        .set_table_options(&full_table_id, vec![("ttl".to_owned(), "60 seconds".to_owned())])
+        .expect("options are stored");
+
+    drop(data_manager);
+
+    let data_manager = Persistent::persistent(root_path.path().into()).expect("to create catalog manager");
+
+    assert_eq!(
+        data_manager.table_options(),
+        Ok(vec![TableOptions {
+            full_table_id,
+            schema_name: SCHEMA.to_owned(),
+            table_name: TABLE.to_owned(),
+            options: vec![("ttl".to_owned(), "60 seconds".to_owned())],
+        }])
+    );
+}
+
+#[rstest::rstest]
+fn persistent_storage_is_available(persistent: (Persistent, TempDir)) {
+    let (data_manager, _root_path) = persistent;
+
+    assert!(data_manager.is_available());
+}
@@ -93,6 +93,63 @@ fn delete_all_from_table(data_manager_with_schema: InMemory) {
     );
 }
 
+#[rstest::rstest]
+fn delete_rows_that_still_match(data_manager_with_schema: InMemory) {
+    let schema_id = data_manager_with_schema.schema_exists(SCHEMA).expect("schema exists");
+    for op in create_table(SCHEMA, TABLE, &[("column_test", SqlType::SmallInt)]) {
+        if data_manager_with_schema.execute(&op).is_ok() {}
+    }
+
+    let table_id = match data_manager_with_schema.table_exists(SCHEMA, TABLE) {
+        Some((_, Some(table_id))) => table_id,
+        _ => panic!(),
+    };
+
+    data_manager_with_schema
+        .write_into(
+            &(schema_id, table_id),
+            vec![
+                (Binary::pack(&[Datum::from_u64(1)]), Binary::pack(&[Datum::from_i16(1)])),
+                (Binary::pack(&[Datum::from_u64(2)]), Binary::pack(&[Datum::from_i16(2)])),
+            ],
+        )
+        .expect("values are inserted");
+
+    // the second row is updated after it was read to be deleted
+    data_manager_with_schema
+        .write_into(
+            &(schema_id, table_id),
+            vec![(
+                Binary::pack(&[Datum::from_u64(2)]),
+                Binary::pack(&[Datum::from_i16(200)]),
+            )],
+        )
+        .expect("values are updated");
+
+    assert_eq!(
+        data_manager_with_schema.delete_from_if(
+            &(schema_id, table_id),
+            vec![
+                Binary::pack(&[Datum::from_u64(1)]),
+                Binary::pack(&[Datum::from_u64(2)]),
+                Binary::pack(&[Datum::from_u64(3)])
+            ],
+            |values| values.unpack()[0] != Datum::from_i16(200),
+        ),
+        Ok(1)
+    );
+
+    assert_eq!(
+        data_manager_with_schema
+            .full_scan(&(schema_id, table_id))
+            .map(|iter| iter.map(Result::unwrap).map(Result::unwrap).collect()),
+        Ok(vec![(
+            Binary::pack(&[Datum::from_u64(2)]),
+            Binary::pack(&[Datum::from_i16(200)]),
+        )])
+    );
+}
+
 #[rstest::rstest]
 fn select_all_from_table_with_many_columns(with_small_ints_table: InMemory) {
     let (schema_id, table_id) = match with_small_ints_table.table_exists(SCHEMA, "table_name") {
@@ -0,0 +1,284 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use binary::Binary;
+use repr::Datum;
+use std::{
+    cell::Cell,
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+use types::SqlType;
+
+#[rstest::fixture]
+fn with_table(data_manager_with_schema: InMemory) -> (InMemory, (Id, Id)) {
+    for op in create_table(SCHEMA, TABLE, &[("column_1", SqlType::SmallInt)]) {
+        if data_manager_with_schema.execute(&op).is_ok() {}
+    }
+    let full_table_id = match data_manager_with_schema.table_exists(SCHEMA, TABLE) {
+        Some((schema_id, Some(table_id))) => (schema_id, table_id),
+        _ => panic!(),
+    };
+    data_manager_with_schema
+        .write_into(&full_table_id, vec![(key(1), small_int(10)), (key(2), small_int(-20))])
+        .expect("values are inserted");
+    (data_manager_with_schema, full_table_id)
+}
+
+fn key(id: u64) -> Binary {
+    Binary::pack(&[Datum::from_u64(id)])
+}
+
+fn small_int(val: i16) -> Binary {
+    Binary::pack(&[Datum::from_i16(val)])
+}
+
+fn integer(val: i32) -> Binary {
+    Binary::pack(&[Datum::from_i32(val)])
+}
+
+fn widen(values: &Values) -> Result<Values, String> {
+    match values.unpack()[0] {
+        Datum::Int16(val) if val >= 0 => Ok(integer(val as i32)),
+        ref other => Err(format!("{:?}", other)),
+    }
+}
+
+fn rows(data_manager: &InMemory, full_table_id: &(Id, Id)) -> Vec<(Key, Values)> {
+    data_manager
+        .full_scan(full_table_id)
+        .expect("table exists")
+        .map(Result::unwrap)
+        .map(Result::unwrap)
+        .collect()
+}
+
+#[rstest::rstest]
+fn rewrites_rows_and_column_types(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+
+    assert_eq!(
+        data_manager.rewrite_table(
+            &full_table_id,
+            &[ColumnDefinition::new("column_1", SqlType::Integer)],
+            |values| match values.unpack()[0] {
+                Datum::Int16(val) => Ok::<Values, ()>(integer(val as i32 * 2)),
+                _ => Err(()),
+            }
+        ),
+        Ok(Ok(2))
+    );
+
+    assert_eq!(
+        rows(&data_manager, &full_table_id),
+        vec![(key(1), integer(20)), (key(2), integer(-40))]
+    );
+    assert_eq!(
+        data_manager
+            .table_columns(&full_table_id)
+            .expect("table exists")
+            .into_iter()
+            .map(|(_id, column)| column)
+            .collect::<Vec<ColumnDefinition>>(),
+        vec![ColumnDefinition::new("column_1", SqlType::Integer)]
+    );
+}
+
+#[rstest::rstest]
+fn adds_column(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+
+    assert_eq!(
+        data_manager.rewrite_table(
+            &full_table_id,
+            &[
+                ColumnDefinition::new("column_1", SqlType::SmallInt),
+                ColumnDefinition::new("column_2", SqlType::Integer)
+            ],
+            |values| {
+                let mut datums = values.unpack();
+                datums.push(Datum::from_i32(0));
+                Ok::<Values, ()>(Binary::pack(&datums))
+            }
+        ),
+        Ok(Ok(2))
+    );
+
+    assert_eq!(
+        data_manager
+            .table_columns(&full_table_id)
+            .expect("table exists")
+            .into_iter()
+            .map(|(_id, column)| column)
+            .collect::<Vec<ColumnDefinition>>(),
+        vec![
+            ColumnDefinition::new("column_1", SqlType::SmallInt),
+            ColumnDefinition::new("column_2", SqlType::Integer)
+        ]
+    );
+    assert_eq!(
+        rows(&data_manager, &full_table_id),
+        vec![
+            (key(1), Binary::pack(&[Datum::from_i16(10), Datum::from_i32(0)])),
+            (key(2), Binary::pack(&[Datum::from_i16(-20), Datum::from_i32(0)]))
+        ]
+    );
+}
+
+#[rstest::rstest]
+fn failed_rewrite_leaves_table_intact(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+
+    assert_eq!(
+        data_manager.rewrite_table(
+            &full_table_id,
+            &[ColumnDefinition::new("column_1", SqlType::Integer)],
+            widen
+        ),
+        Ok(Err(RewriteError::Row(format!("{:?}", Datum::from_i16(-20)))))
+    );
+
+    assert_eq!(
+        rows(&data_manager, &full_table_id),
+        vec![(key(1), small_int(10)), (key(2), small_int(-20))]
+    );
+    assert_eq!(
+        data_manager
+            .table_columns(&full_table_id)
+            .expect("table exists")
+            .into_iter()
+            .map(|(_id, column)| column)
+            .collect::<Vec<ColumnDefinition>>(),
+        vec![ColumnDefinition::new("column_1", SqlType::SmallInt)]
+    );
+}
+
+#[rstest::rstest]
+fn applies_changes_written_during_backfill(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    data_manager
+        .write_into(&full_table_id, vec![(key(2), small_int(20))])
+        .expect("values are updated");
+    let written = Cell::new(false);
+
+    assert_eq!(
+        data_manager.rewrite_table(
+            &full_table_id,
+            &[ColumnDefinition::new("column_1", SqlType::Integer)],
+            |values| {
+                if !written.replace(true) {
+                    data_manager
+                        .write_into(&full_table_id, vec![(key(3), small_int(30))])
+                        .expect("values are inserted");
+                    data_manager
+                        .delete_from(&full_table_id, vec![key(2)])
+                        .expect("values are deleted");
+                }
+                widen(values)
+            }
+        ),
+        Ok(Ok(2))
+    );
+
+    assert_eq!(
+        rows(&data_manager, &full_table_id),
+        vec![(key(1), integer(10)), (key(3), integer(30))]
+    );
+}
+
+#[rstest::rstest]
+fn switch_waits_for_open_scans(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    let data_manager = Arc::new(data_manager);
+    let scan = data_manager.full_scan(&full_table_id).expect("table exists");
+
+    let (sender, receiver) = mpsc::channel();
+    let rewriter = {
+        let data_manager = data_manager.clone();
+        thread::spawn(move || {
+            sender
+                .send(data_manager.rewrite_table(
+                    &full_table_id,
+                    &[ColumnDefinition::new("column_1", SqlType::Integer)],
+                    |values| match values.unpack()[0] {
+                        Datum::Int16(val) => Ok::<Values, ()>(integer(val as i32)),
+                        _ => Err(()),
+                    },
+                ))
+                .expect("result is sent")
+        })
+    };
+
+    assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+    assert_eq!(
+        scan.map(Result::unwrap)
+            .map(Result::unwrap)
+            .collect::<Vec<(Key, Values)>>(),
+        vec![(key(1), small_int(10)), (key(2), small_int(-20))]
+    );
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(5)), Ok(Ok(Ok(2))));
+    rewriter.join().expect("rewrite is finished");
+    assert_eq!(
+        rows(&data_manager, &full_table_id),
+        vec![(key(1), integer(10)), (key(2), integer(-20))]
+    );
+}
+
+#[rstest::rstest]
+fn switch_gives_up_on_scans_left_open(with_table: (InMemory, (Id, Id))) {
+    let (data_manager, full_table_id) = with_table;
+    // scan of a portal that is suspended on the thread running the rewrite
+    let scan = data_manager.full_scan(&full_table_id).expect("table exists");
+
+    assert_eq!(
+        data_manager.rewrite_table(
+            &full_table_id,
+            &[ColumnDefinition::new("column_1", SqlType::Integer)],
+            |values| match values.unpack()[0] {
+                Datum::Int16(val) => Ok::<Values, ()>(integer(val as i32)),
+                _ => Err(()),
+            },
+        ),
+        Ok(Err(RewriteError::ScansOpen))
+    );
+    assert_eq!(
+        data_manager
+            .table_columns(&full_table_id)
+            .expect("table exists")
+            .into_iter()
+            .map(|(_id, column)| column)
+            .collect::<Vec<ColumnDefinition>>(),
+        vec![ColumnDefinition::new("column_1", SqlType::SmallInt)]
+    );
+    assert_eq!(
+        scan.map(Result::unwrap)
+            .map(Result::unwrap)
+            .collect::<Vec<(Key, Values)>>(),
+        vec![(key(1), small_int(10)), (key(2), small_int(-20))]
+    );
+
+    assert_eq!(
+        data_manager.rewrite_table(
+            &full_table_id,
+            &[ColumnDefinition::new("column_1", SqlType::Integer)],
+            |values| match values.unpack()[0] {
+                Datum::Int16(val) => Ok::<Values, ()>(integer(val as i32)),
+                _ => Err(()),
+            },
+        ),
+        Ok(Ok(2))
+    );
+}
@@ -126,6 +126,36 @@ fn drop_table(data_manager_with_schema: InMemory) -> Result<(), ()> {
     Ok(())
 }
 
+#[rstest::rstest]
+fn options_of_dropped_table_are_removed(data_manager_with_schema: InMemory) -> Result<(), ()> {
+    for op in create_table_ops(SCHEMA, TABLE, "column_test", SqlType::SmallInt) {
+        if data_manager_with_schema.execute(&op).is_ok() {}
+    }
+    let full_table_id = match data_manager_with_schema.table_exists(SCHEMA, TABLE) {
+        Some((schema_id, Some(table_id))) => (schema_id, table_id),
+        _ => panic!(),
+    };
+    data_manager_with_schema.set_table_options(&full_table_id, vec![("ttl".to_owned(), "1 day".to_owned())])?;
+
+    data_manager_with_schema.execute(&Step::RemoveColumns {
+        schema_name: SCHEMA.to_owned(),
+        table_name: TABLE.to_owned(),
+    })?;
+    data_manager_with_schema.execute(&Step::RemoveRecord {
+        system_schema: DEFINITION_SCHEMA.to_owned(),
+        system_table: TABLES_TABLE.to_owned(),
+        record: Record::Table {
+            catalog_name: DEFAULT_CATALOG.to_owned(),
+            schema_name: SCHEMA.to_owned(),
+            table_name: TABLE.to_owned(),
+        },
+    })?;
+
+    assert_eq!(data_manager_with_schema.table_options(), Ok(vec![]));
+
+    Ok(())
+}
+
 #[rstest::rstest]
 fn table_ids_for_existing_columns(data_manager_with_schema: InMemory) -> Result<(), ()> {
     let schema_id = data_manager_with_schema.schema_exists(SCHEMA).expect("schema exists");
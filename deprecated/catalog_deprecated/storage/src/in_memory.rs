@@ -54,6 +54,15 @@ impl Sequence for InMemorySequence {
     fn next(&self) -> u64 {
         self.counter.fetch_add(self.step, Ordering::SeqCst)
     }
+
+    fn next_values(&self, count: usize) -> Vec<u64> {
+        let first = self
+            .counter
+            .fetch_add(self.step.wrapping_mul(count as u64), Ordering::SeqCst);
+        (0..count as u64)
+            .map(|index| first.wrapping_add(self.step.wrapping_mul(index)))
+            .collect()
+    }
 }
 
 #[derive(Default, Debug)]
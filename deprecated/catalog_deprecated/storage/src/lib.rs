@@ -44,6 +44,9 @@ impl From<bool> for InitStatus {
 
 pub trait Sequence {
     fn next(&self) -> Identifier;
+
+    /// reserves `count` consecutive values of the sequence at once
+    fn next_values(&self, count: usize) -> Vec<Identifier>;
 }
 
 pub trait Schema {}
@@ -68,6 +71,16 @@ pub const TABLES_TABLE: &'_ str = "TABLES";
 /// CHARACTER_MAXIMUM_LENGTH    integer CHECK (VALUE >= 0),
 /// NUMERIC_PRECISION           integer CHECK (VALUE >= 0),
 pub const COLUMNS_TABLE: &'_ str = "COLUMNS";
+/// **TABLE_OPTIONS** sql type definition
+/// TABLE_CATALOG   varchar(255)
+/// TABLE_SCHEMA    varchar(255)
+/// TABLE_NAME      varchar(255)
+/// OPTION_NAME     varchar(255)
+/// OPTION_VALUE    varchar(255)
+pub const TABLE_OPTIONS_TABLE: &'_ str = "TABLE_OPTIONS";
+/// sequence of object identifiers shared by schemas and tables, values are
+/// taken from it once and never reused even after restart
+pub const OIDS_SEQUENCE: &'_ str = "OIDS";
 
 pub trait Database {
     fn bootstrap(&self) {
@@ -89,6 +102,14 @@ pub trait Database {
             .expect("table COLUMNS is created");
         self.create_sequence(DEFINITION_SCHEMA, &(COLUMNS_TABLE.to_owned() + ".records"))
             .expect("to create sequence");
+        self.create_object(DEFINITION_SCHEMA, TABLE_OPTIONS_TABLE)
+            .expect("no io error")
+            .expect("no platform error")
+            .expect("table TABLE_OPTIONS is created");
+        self.create_sequence(DEFINITION_SCHEMA, &(TABLE_OPTIONS_TABLE.to_owned() + ".records"))
+            .expect("to create sequence");
+        self.create_sequence(DEFINITION_SCHEMA, OIDS_SEQUENCE)
+            .expect("to create sequence");
     }
 
     fn create_sequence(&self, schema_name: &str, sequence_name: &str) -> Result<Arc<dyn Sequence>, DefinitionError> {
@@ -28,6 +28,11 @@ use std::{
     sync::Arc,
 };
 
+const CHECKSUM_SIZE: usize = 4;
+/// prefix of keys in default tree of a schema that mark objects which rows
+/// are stored with checksums, rows of objects created before are read as is
+const CHECKSUMS_PREFIX: &[u8] = b"checksums/";
+
 #[derive(Debug)]
 pub struct PersistentSequence {
     name: IVec,
@@ -63,6 +68,24 @@ impl Sequence for PersistentSequence {
             Err(_) => unimplemented!(),
         }
     }
+
+    fn next_values(&self, count: usize) -> Vec<u64> {
+        let current = match self.source.get(self.name.clone()) {
+            Ok(Some(value)) => u64::from_be_bytes(value[0..8].try_into().unwrap()),
+            Ok(None) => 0,
+            Err(_) => unimplemented!(),
+        };
+        let result = self.source.insert(
+            self.name.clone(),
+            IVec::from(&(current.wrapping_add(self.step.wrapping_mul(count as u64))).to_be_bytes()),
+        );
+        match result {
+            Ok(_) => (0..count as u64)
+                .map(|index| current.wrapping_add(self.step.wrapping_mul(index)))
+                .collect(),
+            Err(_) => unimplemented!(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -88,7 +111,7 @@ impl Schema for PersistentSchema {}
 
 pub struct PersistentDatabase {
     path: PathBuf,
-    schemas: DashMap<Name, Arc<PersistentSchema>>,
+    pub(crate) schemas: DashMap<Name, Arc<PersistentSchema>>,
 }
 
 impl PersistentDatabase {
@@ -216,9 +239,19 @@ impl PersistentDatabase {
         tree: &Tree,
         key: &Binary,
         values: &Binary,
+        checksums: bool,
     ) -> Result<Option<IVec>, SledError> {
         fail::fail_point!("sled-fail-to-insert-into-tree", |kind| Err(sled_error(kind)));
-        tree.insert(key.to_bytes(), values.to_bytes())
+        if checksums {
+            tree.insert(key.to_bytes(), with_checksum(values))
+        } else {
+            tree.insert(key.to_bytes(), values.to_bytes())
+        }
+    }
+
+    /// marks the object, so that its rows are written and verified with checksums
+    fn mark_checksums(&self, schema: &PersistentSchema, object_name: ObjectName) -> Result<Option<IVec>, SledError> {
+        schema.insert(checksums_mark(object_name), vec![])
     }
 
     fn tree_flush(
@@ -443,8 +476,13 @@ impl Database for PersistentDatabase {
                         if schema.tree_names().contains(&(object_name.into())) {
                             Ok(Ok(Err(DefinitionError::ObjectAlreadyExists)))
                         } else {
-                            self.open_tree(schema, object_name)
-                                .map(|io| io.map(|storage| storage.map(|_object| ())))
+                            match self.mark_checksums(&schema, object_name) {
+                                Ok(_) => self
+                                    .open_tree(schema, object_name)
+                                    .map(|io| io.map(|storage| storage.map(|_object| ()))),
+                                Err(SledError::Io(io_error)) => Err(io_error),
+                                Err(_) => Ok(Err(StorageError::Storage)),
+                            }
                         }
                     }
                     e => {
@@ -456,8 +494,13 @@ impl Database for PersistentDatabase {
                     if schema.tree_names().contains(&(object_name.into())) {
                         Ok(Ok(Err(DefinitionError::ObjectAlreadyExists)))
                     } else {
-                        self.open_tree(schema.clone(), object_name)
-                            .map(|io| io.map(|storage| storage.map(|_object| ())))
+                        match self.mark_checksums(&schema, object_name) {
+                            Ok(_) => self
+                                .open_tree(schema.clone(), object_name)
+                                .map(|io| io.map(|storage| storage.map(|_object| ()))),
+                            Err(SledError::Io(io_error)) => Err(io_error),
+                            Err(_) => Ok(Err(StorageError::Storage)),
+                        }
                     }
                 }
             }
@@ -518,11 +561,12 @@ impl Database for PersistentDatabase {
                 None => match self.open_schema(self.path_to_schema(schema_name)) {
                     Ok(Ok(schema)) => {
                         if schema.tree_names().contains(&(object_name.into())) {
+                            let checksums = has_checksums(&schema, object_name);
                             match self.open_tree(schema, object_name) {
                                 Ok(Ok(Ok(object))) => {
                                     let mut written_rows = 0;
                                     for (key, values) in rows.iter() {
-                                        match self.insert_into_tree_with_failpoint(&object, key, values) {
+                                        match self.insert_into_tree_with_failpoint(&object, key, values, checksums) {
                                             Ok(_) => written_rows += 1,
                                             Err(error) => match error {
                                                 SledError::Io(io_error) => return Err(io_error),
@@ -547,11 +591,12 @@ impl Database for PersistentDatabase {
                 },
                 Some(schema) => {
                     if schema.tree_names().contains(&(object_name.into())) {
+                        let checksums = has_checksums(&schema, object_name);
                         match self.open_tree(schema.clone(), object_name) {
                             Ok(Ok(Ok(object))) => {
                                 let mut written_rows = 0;
                                 for (key, values) in rows.iter() {
-                                    match self.insert_into_tree_with_failpoint(&object, key, values) {
+                                    match self.insert_into_tree_with_failpoint(&object, key, values, checksums) {
                                         Ok(_) => written_rows += 1,
                                         Err(error) => match error {
                                             SledError::Io(io_error) => return Err(io_error),
@@ -588,21 +633,20 @@ impl Database for PersistentDatabase {
                 None => match self.open_schema(self.path_to_schema(schema_name)) {
                     Ok(Ok(schema)) => {
                         if schema.tree_names().contains(&(object_name.into())) {
+                            let checksums = has_checksums(&schema, object_name);
                             match self.open_tree(schema, object_name) {
                                 Ok(Ok(Ok(object))) => Ok(Ok(Ok(Box::new(
-                                    self.iterator_over_tree_with_failpoint(object).map(|item| match item {
-                                        Ok((key, values)) => Ok(Ok((
-                                            Binary::with_data(key.to_vec()),
-                                            Binary::with_data(values.to_vec()),
-                                        ))),
-                                        Err(error) => match error {
-                                            SledError::Io(io_error) => Err(io_error),
-                                            SledError::Corruption { .. } => Ok(Err(StorageError::Storage)),
-                                            SledError::ReportableBug(_) => Ok(Err(StorageError::Storage)),
-                                            SledError::Unsupported(_) => Ok(Err(StorageError::Storage)),
-                                            SledError::CollectionNotFound(_) => Ok(Err(StorageError::Storage)),
-                                        },
-                                    }),
+                                    self.iterator_over_tree_with_failpoint(object)
+                                        .map(move |item| match item {
+                                            Ok((key, values)) => Ok(verified(key, values, checksums)),
+                                            Err(error) => match error {
+                                                SledError::Io(io_error) => Err(io_error),
+                                                SledError::Corruption { .. } => Ok(Err(StorageError::Storage)),
+                                                SledError::ReportableBug(_) => Ok(Err(StorageError::Storage)),
+                                                SledError::Unsupported(_) => Ok(Err(StorageError::Storage)),
+                                                SledError::CollectionNotFound(_) => Ok(Err(StorageError::Storage)),
+                                            },
+                                        }),
                                 )))),
                                 otherwise => {
                                     otherwise.map(|io| io.map(|storage| storage.map(|_object| self.empty_iterator())))
@@ -624,21 +668,20 @@ impl Database for PersistentDatabase {
                 },
                 Some(schema) => {
                     if schema.tree_names().contains(&(object_name.into())) {
+                        let checksums = has_checksums(&schema, object_name);
                         match self.open_tree(schema.clone(), object_name) {
                             Ok(Ok(Ok(object))) => Ok(Ok(Ok(Box::new(
-                                self.iterator_over_tree_with_failpoint(object).map(|item| match item {
-                                    Ok((key, values)) => Ok(Ok((
-                                        Binary::with_data(key.to_vec()),
-                                        Binary::with_data(values.to_vec()),
-                                    ))),
-                                    Err(error) => match error {
-                                        SledError::Io(io_error) => Err(io_error),
-                                        SledError::Corruption { .. } => Ok(Err(StorageError::Storage)),
-                                        SledError::ReportableBug(_) => Ok(Err(StorageError::Storage)),
-                                        SledError::Unsupported(_) => Ok(Err(StorageError::Storage)),
-                                        SledError::CollectionNotFound(_) => Ok(Err(StorageError::Storage)),
-                                    },
-                                }),
+                                self.iterator_over_tree_with_failpoint(object)
+                                    .map(move |item| match item {
+                                        Ok((key, values)) => Ok(verified(key, values, checksums)),
+                                        Err(error) => match error {
+                                            SledError::Io(io_error) => Err(io_error),
+                                            SledError::Corruption { .. } => Ok(Err(StorageError::Storage)),
+                                            SledError::ReportableBug(_) => Ok(Err(StorageError::Storage)),
+                                            SledError::Unsupported(_) => Ok(Err(StorageError::Storage)),
+                                            SledError::CollectionNotFound(_) => Ok(Err(StorageError::Storage)),
+                                        },
+                                    }),
                             )))),
                             otherwise => {
                                 otherwise.map(|io| io.map(|storage| storage.map(|_object| self.empty_iterator())))
@@ -731,6 +774,52 @@ impl Database for PersistentDatabase {
     }
 }
 
+/// Adler-32 checksum of stored values
+fn checksum(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % MOD_ADLER;
+        (a, (b + a) % MOD_ADLER)
+    });
+    (b << 16) | a
+}
+
+fn checksums_mark(object_name: ObjectName) -> Vec<u8> {
+    let mut mark = CHECKSUMS_PREFIX.to_vec();
+    mark.extend_from_slice(object_name.as_bytes());
+    mark
+}
+
+/// whether rows of the object are stored with checksums
+fn has_checksums(schema: &PersistentSchema, object_name: ObjectName) -> bool {
+    matches!(schema.contains_key(checksums_mark(object_name)), Ok(true))
+}
+
+fn with_checksum(values: &Binary) -> Vec<u8> {
+    let mut data = values.to_bytes().to_vec();
+    data.extend_from_slice(&checksum(values.to_bytes()).to_be_bytes());
+    data
+}
+
+fn verified(key: IVec, stored: IVec, checksums: bool) -> Result<(Key, Values), StorageError> {
+    let key = Binary::with_data(key.to_vec());
+    if !checksums {
+        return Ok((key, Binary::with_data(stored.to_vec())));
+    }
+    if stored.len() < CHECKSUM_SIZE {
+        log::error!("row {:?} is too short to contain checksum", key);
+        return Err(StorageError::Corrupted(key));
+    }
+    let (data, stored_checksum) = stored.split_at(stored.len() - CHECKSUM_SIZE);
+    if checksum(data).to_be_bytes() == stored_checksum {
+        let values = Binary::with_data(stored.to_vec());
+        Ok((key, values.slice(0..data.len())))
+    } else {
+        log::error!("checksum mismatch of row {:?}", key);
+        Err(StorageError::Corrupted(key))
+    }
+}
+
 fn sled_error(kind: Option<String>) -> SledError {
     match kind.as_deref() {
         Some("io") => SledError::Io(ErrorKind::Other.into()),
@@ -84,6 +84,18 @@ mod sequences {
         assert_eq!(sequence.next(), 10);
     }
 
+    #[rstest::rstest]
+    fn reserve_many_identifiers_at_once(with_schema: Storage, schema_name: SchemaName) {
+        let sequence = with_schema
+            .create_sequence_with_step(schema_name, "sequence", 2)
+            .expect("schema exists");
+
+        assert_eq!(sequence.next_values(3), vec![0, 2, 4]);
+        assert_eq!(sequence.next(), 6);
+        assert_eq!(sequence.next_values(0), vec![]);
+        assert_eq!(sequence.next(), 8);
+    }
+
     #[rstest::rstest]
     fn overflow(with_schema: Storage, schema_name: SchemaName) {
         let sequence = with_schema
@@ -99,6 +99,18 @@ mod sequences {
         assert_eq!(sequence.next(), 10);
     }
 
+    #[rstest::rstest]
+    fn reserve_many_identifiers_at_once(with_schema: Storage, schema_name: SchemaName) {
+        let sequence = with_schema
+            .create_sequence_with_step(schema_name, "sequence", 2)
+            .expect("schema exists");
+
+        assert_eq!(sequence.next_values(3), vec![0, 2, 4]);
+        assert_eq!(sequence.next(), 6);
+        assert_eq!(sequence.next_values(0), vec![]);
+        assert_eq!(sequence.next(), 8);
+    }
+
     #[rstest::rstest]
     fn overflow(with_schema: Storage, schema_name: SchemaName) {
         let sequence = with_schema
@@ -481,4 +493,83 @@ mod operations_on_object {
             .collect()),
         );
     }
+
+    #[rstest::rstest]
+    fn read_corrupted_row(with_object: Storage, schema_name: SchemaName, object_name: ObjectName) {
+        with_object
+            .write(
+                schema_name,
+                object_name,
+                as_rows(vec![(1u8, vec!["123"]), (2u8, vec!["456"])]),
+            )
+            .expect("no io error")
+            .expect("no platform error")
+            .expect("values are written");
+
+        with_object
+            .schemas
+            .get(schema_name)
+            .expect("schema exists")
+            .open_tree(object_name)
+            .expect("object exists")
+            .insert(2u8.to_be_bytes(), b"457\0\0\0\0".to_vec())
+            .expect("row is overwritten");
+
+        assert_eq!(
+            with_object
+                .read(schema_name, object_name)
+                .expect("no io error")
+                .expect("no platform error")
+                .map(|iter| iter
+                    .map(|ok| ok.expect("no io error"))
+                    .collect::<Vec<Result<Row, StorageError>>>()),
+            Ok(vec![
+                Ok((
+                    Binary::with_data(1u8.to_be_bytes().to_vec()),
+                    Binary::with_data(b"123".to_vec())
+                )),
+                Err(StorageError::Corrupted(Binary::with_data(2u8.to_be_bytes().to_vec())))
+            ])
+        );
+    }
+
+    #[rstest::rstest]
+    fn read_rows_written_without_checksums(with_schema: Storage, schema_name: SchemaName, object_name: ObjectName) {
+        // objects created before rows were stored with checksums have no mark
+        let object = with_schema
+            .schemas
+            .get(schema_name)
+            .expect("schema exists")
+            .open_tree(object_name)
+            .expect("object is created");
+        object
+            .insert(1u8.to_be_bytes(), b"123".to_vec())
+            .expect("row is written");
+
+        with_schema
+            .write(schema_name, object_name, as_rows(vec![(2u8, vec!["456"])]))
+            .expect("no io error")
+            .expect("no platform error")
+            .expect("values are written");
+
+        assert_eq!(
+            with_schema
+                .read(schema_name, object_name)
+                .expect("no io error")
+                .expect("no platform error")
+                .map(|iter| iter
+                    .map(|ok| ok.expect("no io error"))
+                    .collect::<Vec<Result<Row, StorageError>>>()),
+            Ok(vec![
+                Ok((
+                    Binary::with_data(1u8.to_be_bytes().to_vec()),
+                    Binary::with_data(b"123".to_vec())
+                )),
+                Ok((
+                    Binary::with_data(2u8.to_be_bytes().to_vec()),
+                    Binary::with_data(b"456".to_vec())
+                ))
+            ])
+        );
+    }
 }
@@ -102,7 +102,7 @@ impl Constraint for TypeConstraint {
             TypeConstraint::Char(len) => match &in_value {
                 ScalarValue::String(in_value) => {
                     let trimmed = in_value.trim_end();
-                    if trimmed.len() > *len as usize {
+                    if trimmed.chars().count() > *len as usize {
                         Err(ConstraintError::ValueTooLong(*len))
                     } else {
                         Ok(Datum::OwnedString(trimmed.to_owned()))
@@ -113,7 +113,7 @@ impl Constraint for TypeConstraint {
             TypeConstraint::VarChar(len) => match &in_value {
                 ScalarValue::String(in_value) => {
                     let trimmed = in_value.trim_end();
-                    if trimmed.len() > *len as usize {
+                    if trimmed.chars().count() > *len as usize {
                         Err(ConstraintError::ValueTooLong(*len))
                     } else {
                         Ok(Datum::OwnedString(trimmed.to_owned()))
@@ -394,6 +394,14 @@ mod tests {
                         Err(ConstraintError::ValueTooLong(10))
                     )
                 }
+
+                #[rstest::rstest]
+                fn multi_byte_characters_in_length(constraint: TypeConstraint) {
+                    assert_eq!(
+                        constraint.validate(ScalarValue::String("ж".repeat(10))),
+                        Ok(Datum::OwnedString("ж".repeat(10)))
+                    )
+                }
             }
         }
 
@@ -425,6 +433,14 @@ mod tests {
                         Err(ConstraintError::ValueTooLong(10))
                     )
                 }
+
+                #[rstest::rstest]
+                fn multi_byte_characters_in_length(constraint: TypeConstraint) {
+                    assert_eq!(
+                        constraint.validate(ScalarValue::String("ж".repeat(10))),
+                        Ok(Datum::OwnedString("ж".repeat(10)))
+                    )
+                }
             }
         }
     }
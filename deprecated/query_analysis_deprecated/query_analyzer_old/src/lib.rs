@@ -51,8 +51,7 @@ impl Analyzer {
                         } else {
                             let mut col_types = vec![];
                             for col in columns {
-                                let Ident { value, .. } = col;
-                                let col_name = value.to_lowercase();
+                                let col_name = sql_ast::ident_name(col);
                                 match table_columns.iter().find(|col_def| col_def.has_name(&col_name)) {
                                     Some(col_def) => col_types.push(col_def.sql_type()),
                                     None => return Err(DescriptionError::column_does_not_exist(&col_name)),
@@ -93,10 +92,9 @@ impl Analyzer {
                                                 let mut names: Vec<String> = vec![];
                                                 for item in projection {
                                                     match item {
-                                                        SelectItem::UnnamedExpr(Expr::Identifier(Ident {
-                                                            value,
-                                                            ..
-                                                        })) => names.push(value.to_lowercase()),
+                                                        SelectItem::UnnamedExpr(Expr::Identifier(ident)) => {
+                                                            names.push(sql_ast::ident_name(ident))
+                                                        }
                                                         SelectItem::Wildcard => {
                                                             for (_col_id, col_def) in self
                                                                 .metadata
@@ -158,31 +156,21 @@ impl Analyzer {
                             let Assignment { id, value } = assignment;
                             if let Expr::Identifier(Ident { value, .. }) = value {
                                 if let Some(param_index) = parse_param_index(value) {
-                                    let Ident { value, .. } = id;
-                                    parse_param_type_by_column(&mut param_types, &table_columns, param_index, value)?;
+                                    parse_param_type_by_column(&mut param_types, &table_columns, param_index, id)?;
                                 }
                             }
                         }
                         if let Some(Expr::BinaryOp { left, right, .. }) = selection {
-                            if let (
-                                Expr::Identifier(Ident { value: left_val, .. }),
-                                Expr::Identifier(Ident { value: right_val, .. }),
-                            ) = (left.deref(), right.deref())
-                            {
-                                let pair = if let Some(param_index) = parse_param_index(left_val) {
-                                    Some((param_index, right_val))
-                                } else if let Some(param_index) = parse_param_index(right_val) {
-                                    Some((param_index, left_val))
+                            if let (Expr::Identifier(left), Expr::Identifier(right)) = (left.deref(), right.deref()) {
+                                let pair = if let Some(param_index) = parse_param_index(&left.value) {
+                                    Some((param_index, right))
+                                } else if let Some(param_index) = parse_param_index(&right.value) {
+                                    Some((param_index, left))
                                 } else {
                                     None
                                 };
-                                if let Some((param_index, col_name)) = pair {
-                                    parse_param_type_by_column(
-                                        &mut param_types,
-                                        &table_columns,
-                                        param_index,
-                                        col_name,
-                                    )?;
+                                if let Some((param_index, column)) = pair {
+                                    parse_param_type_by_column(&mut param_types, &table_columns, param_index, column)?;
                                 }
                             }
                         }
@@ -341,9 +329,9 @@ fn parse_param_type_by_column(
     param_types: &mut ParamTypes,
     columns: &[ColumnDefinition],
     param_index: ParamIndex,
-    col_name: &str,
+    column: &Ident,
 ) -> Result<(), DescriptionError> {
-    let col_name = col_name.to_lowercase();
+    let col_name = sql_ast::ident_name(column);
     let col_type = match columns.iter().find(|col_def| col_def.has_name(&col_name)) {
         Some(col_def) => col_def.sql_type(),
         None => return Err(DescriptionError::column_does_not_exist(&col_name)),
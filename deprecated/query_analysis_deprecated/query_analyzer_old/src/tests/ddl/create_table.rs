@@ -135,7 +135,7 @@ fn successfully_create_table() {
     assert_eq!(
         description,
         Ok(Description::CreateTable(TableCreationInfo {
-            schema_id: 0,
+            schema_id: FIRST_OBJECT_ID,
             table_name: TABLE.to_owned(),
             columns: vec![ColumnDesc {
                 name: "column_name".to_owned(),
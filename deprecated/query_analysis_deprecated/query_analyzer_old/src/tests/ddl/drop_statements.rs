@@ -67,7 +67,7 @@ fn drop_schema() {
     assert_eq!(
         description,
         Ok(Description::DropSchemas(DropSchemasInfo {
-            schema_ids: vec![SchemaId::from(0)],
+            schema_ids: vec![SchemaId::from(FIRST_OBJECT_ID)],
             cascade: false,
             if_exists: false,
         }))
@@ -148,7 +148,7 @@ fn drop_table_with_unsupported_name() {
 fn drop_table() {
     let metadata = Arc::new(DatabaseHandle::in_memory());
     let schema_id = metadata.create_schema(SCHEMA).expect("schema created");
-    metadata.create_table(schema_id, TABLE, &[]).expect("table created");
+    let table_id = metadata.create_table(schema_id, TABLE, &[]).expect("table created");
     let analyzer = Analyzer::new(metadata);
     let description = analyzer.describe(&drop(
         vec![ObjectName(vec![ident(SCHEMA), ident(TABLE)])],
@@ -157,7 +157,7 @@ fn drop_table() {
     assert_eq!(
         description,
         Ok(Description::DropTables(DropTablesInfo {
-            full_table_ids: vec![FullTableId::from((0, 0))],
+            full_table_ids: vec![FullTableId::from((schema_id, table_id))],
             cascade: false,
             if_exists: false
         }))
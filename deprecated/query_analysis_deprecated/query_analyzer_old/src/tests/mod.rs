@@ -18,7 +18,7 @@ mod select;
 mod update;
 
 use super::*;
-use data_manager::DatabaseHandle;
+use data_manager::{DatabaseHandle, FIRST_OBJECT_ID};
 use meta_def::ColumnDefinition;
 use sql_ast::{Expr, Ident, ObjectName, Query, SetExpr, Value, Values};
 use std::sync::Arc;
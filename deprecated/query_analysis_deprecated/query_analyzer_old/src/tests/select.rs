@@ -111,13 +111,13 @@ fn select_from_table_with_unsupported_name() {
 fn select_from_table() {
     let metadata = Arc::new(DatabaseHandle::in_memory());
     let schema_id = metadata.create_schema(SCHEMA).expect("schema created");
-    metadata.create_table(schema_id, TABLE, &[]).expect("table created");
+    let table_id = metadata.create_table(schema_id, TABLE, &[]).expect("table created");
     let analyzer = Analyzer::new(metadata);
     let description = analyzer.describe(&select(ObjectName(vec![ident(SCHEMA), ident(TABLE)])));
     assert_eq!(
         description,
         Ok(Description::Select(SelectStatement {
-            full_table_id: FullTableId::from((0, 0)),
+            full_table_id: FullTableId::from((schema_id, table_id)),
             projection_items: vec![],
         }))
     );
@@ -127,7 +127,7 @@ fn select_from_table() {
 fn select_from_table_with_column() {
     let metadata = Arc::new(DatabaseHandle::in_memory());
     let schema_id = metadata.create_schema(SCHEMA).expect("schema created");
-    metadata
+    let table_id = metadata
         .create_table(schema_id, TABLE, &[ColumnDefinition::new("col1", SqlType::Integer)])
         .expect("table created");
     let analyzer = Analyzer::new(metadata);
@@ -138,7 +138,7 @@ fn select_from_table_with_column() {
     assert_eq!(
         description,
         Ok(Description::Select(SelectStatement {
-            full_table_id: FullTableId::from((0, 0)),
+            full_table_id: FullTableId::from((schema_id, table_id)),
             projection_items: vec![ProjectionItem::Column(0, SqlType::Integer)],
         }))
     );
@@ -149,7 +149,7 @@ fn select_from_table_with_column() {
 fn select_from_table_with_constant() {
     let metadata = Arc::new(DatabaseHandle::in_memory());
     let schema_id = metadata.create_schema(SCHEMA).expect("schema created");
-    metadata
+    let table_id = metadata
         .create_table(schema_id, TABLE, &[ColumnDefinition::new("col1", SqlType::Integer)])
         .expect("schema created");
     let analyzer = Analyzer::new(metadata);
@@ -160,7 +160,7 @@ fn select_from_table_with_constant() {
     assert_eq!(
         description,
         Ok(Description::Select(SelectStatement {
-            full_table_id: FullTableId::from((0, 0)),
+            full_table_id: FullTableId::from((schema_id, table_id)),
             projection_items: vec![ProjectionItem::Const(1)],
         }))
     );
@@ -13,15 +13,18 @@
 // limitations under the License.
 
 mod dynamic_expr;
+mod pattern;
 mod static_expr;
 
 pub use dynamic_expr::DynamicExpressionEvaluation;
+pub use pattern::PatternCache;
 pub use static_expr::StaticExpressionEvaluation;
 
 #[derive(Debug, PartialEq)]
 pub enum EvalError {
     UndefinedFunction(String, String, String),
     NonValue(String),
+    InvalidRegularExpression(String),
 }
 
 impl EvalError {
@@ -32,6 +35,10 @@ impl EvalError {
     fn not_a_value<V: ToString>(v: &V) -> EvalError {
         EvalError::NonValue(v.to_string())
     }
+
+    fn invalid_regular_expression<M: ToString>(message: &M) -> EvalError {
+        EvalError::InvalidRegularExpression(message.to_string())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,219 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::EvalError;
+use regex::{Regex, RegexBuilder};
+use repr::Datum;
+use std::{borrow::Cow, collections::HashMap};
+
+const CACHED_PATTERNS: usize = 256;
+
+/// compiled regular expressions keyed by their pattern and case sensitivity,
+/// so that a literal pattern is compiled once for all rows of a statement
+#[derive(Default)]
+pub struct PatternCache {
+    patterns: HashMap<(String, bool), Regex>,
+}
+
+impl PatternCache {
+    /// `~` and `~*` operators, `NULL` if any of operands is `NULL`
+    pub fn regex_match(
+        &mut self,
+        text: &Datum,
+        pattern: &Datum,
+        case_insensitive: bool,
+    ) -> Result<Datum<'static>, EvalError> {
+        match (as_text(text), as_text(pattern)) {
+            (Some(text), Some(pattern)) => {
+                let regex = self.compile(&pattern, case_insensitive)?;
+                Ok(Datum::from_bool(regex.is_match(&text)))
+            }
+            _ => Ok(Datum::from_null()),
+        }
+    }
+
+    /// `regexp_replace(text, pattern, replacement [, flags])` replaces the
+    /// first match, or all of them with `g` flag. `\1` to `\9` of replacement
+    /// refer to captured groups and `&` to the whole match
+    pub fn regexp_replace(
+        &mut self,
+        text: &Datum,
+        pattern: &Datum,
+        replacement: &Datum,
+        flags: &Datum,
+    ) -> Result<Datum<'static>, EvalError> {
+        match (as_text(text), as_text(pattern), as_text(replacement), flags_of(flags)) {
+            (Some(text), Some(pattern), Some(replacement), Some(flags)) => {
+                let (global, case_insensitive) = parse_flags(&flags)?;
+                let regex = self.compile(&pattern, case_insensitive)?;
+                let replacement = replacement_template(&replacement);
+                let replaced = if global {
+                    regex.replace_all(&text, replacement.as_str())
+                } else {
+                    regex.replace(&text, replacement.as_str())
+                };
+                Ok(Datum::from_string(replaced.into_owned()))
+            }
+            _ => Ok(Datum::from_null()),
+        }
+    }
+
+    /// `regexp_matches(text, pattern [, flags])` returns captured groups of
+    /// the first match, or of all matches with `g` flag, one text array per
+    /// match. The whole match is returned if pattern has no groups
+    // TODO: there are no array types, arrays are returned in their text form
+    pub fn regexp_matches(
+        &mut self,
+        text: &Datum,
+        pattern: &Datum,
+        flags: &Datum,
+    ) -> Result<Vec<Datum<'static>>, EvalError> {
+        match (as_text(text), as_text(pattern), flags_of(flags)) {
+            (Some(text), Some(pattern), Some(flags)) => {
+                let (global, case_insensitive) = parse_flags(&flags)?;
+                let regex = self.compile(&pattern, case_insensitive)?;
+                let limit = if global { usize::MAX } else { 1 };
+                Ok(regex
+                    .captures_iter(&text)
+                    .take(limit)
+                    .map(|captures| {
+                        let groups = if captures.len() == 1 {
+                            vec![captures.get(0).map(|group| group.as_str())]
+                        } else {
+                            (1..captures.len())
+                                .map(|index| captures.get(index).map(|group| group.as_str()))
+                                .collect()
+                        };
+                        Datum::from_string(text_array(&groups))
+                    })
+                    .collect())
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn compile(&mut self, pattern: &str, case_insensitive: bool) -> Result<&Regex, EvalError> {
+        let key = (pattern.to_owned(), case_insensitive);
+        if !self.patterns.contains_key(&key) {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(case_insensitive)
+                .build()
+                .map_err(|error| EvalError::invalid_regular_expression(&syntax_error(&error)))?;
+            if self.patterns.len() >= CACHED_PATTERNS {
+                self.patterns.clear();
+            }
+            self.patterns.insert(key.clone(), regex);
+        }
+        Ok(&self.patterns[&key])
+    }
+}
+
+/// the last line of syntax errors, other lines quote the pattern
+fn syntax_error(error: &regex::Error) -> String {
+    match error {
+        regex::Error::Syntax(message) => message
+            .lines()
+            .last()
+            .map(|line| line.trim_start_matches("error: ").to_owned())
+            .unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+fn as_text<'a>(datum: &'a Datum) -> Option<Cow<'a, str>> {
+    match datum {
+        Datum::Null => None,
+        Datum::String(value) => Some(Cow::Borrowed(*value)),
+        Datum::OwnedString(value) => Some(Cow::Borrowed(value.as_str())),
+        other => Some(Cow::Owned(other.to_string())),
+    }
+}
+
+/// omitted flags are passed as `NULL` and mean no flags
+fn flags_of<'a>(flags: &'a Datum) -> Option<Cow<'a, str>> {
+    match flags {
+        Datum::Null => Some(Cow::Borrowed("")),
+        flags => as_text(flags),
+    }
+}
+
+/// whether all matches are used and whether case is ignored
+fn parse_flags(flags: &str) -> Result<(bool, bool), EvalError> {
+    let mut global = false;
+    let mut case_insensitive = false;
+    for flag in flags.chars() {
+        match flag {
+            'g' => global = true,
+            'i' => case_insensitive = true,
+            'c' => case_insensitive = false,
+            other => {
+                return Err(EvalError::invalid_regular_expression(&format!(
+                    "invalid option \"{}\"",
+                    other
+                )))
+            }
+        }
+    }
+    Ok((global, case_insensitive))
+}
+
+/// translates PostgreSQL replacement to the syntax of `regex` crate
+fn replacement_template(replacement: &str) -> String {
+    let mut template = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek().copied() {
+                Some(digit @ '1'..='9') => {
+                    chars.next();
+                    template.push_str(&format!("${{{}}}", digit));
+                }
+                Some('&') => {
+                    chars.next();
+                    template.push('&');
+                }
+                Some('\\') => {
+                    chars.next();
+                    template.push('\\');
+                }
+                _ => template.push('\\'),
+            },
+            '&' => template.push_str("${0}"),
+            '$' => template.push_str("$$"),
+            c => template.push(c),
+        }
+    }
+    template
+}
+
+/// text form of an array of strings, e.g. `{abc,NULL,"a b"}`
+fn text_array(elements: &[Option<&str>]) -> String {
+    let elements = elements
+        .iter()
+        .map(|element| match element {
+            None => "NULL".to_owned(),
+            Some(element)
+                if element.is_empty()
+                    || element.eq_ignore_ascii_case("null")
+                    || element
+                        .chars()
+                        .any(|c| c.is_whitespace() || matches!(c, ',' | '{' | '}' | '"' | '\\')) =>
+            {
+                format!("\"{}\"", element.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            Some(element) => (*element).to_owned(),
+        })
+        .collect::<Vec<String>>();
+    format!("{{{}}}", elements.join(","))
+}
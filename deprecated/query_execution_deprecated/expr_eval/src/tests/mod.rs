@@ -15,6 +15,8 @@
 #[cfg(test)]
 mod dynamic_expressions;
 #[cfg(test)]
+mod pattern;
+#[cfg(test)]
 mod static_expressions;
 
 use super::*;
@@ -0,0 +1,180 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+#[test]
+fn regex_match() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regex_match(&Datum::from_str("thomas"), &Datum::from_str(".*thom.*"), false),
+        Ok(Datum::from_bool(true))
+    );
+    assert_eq!(
+        patterns.regex_match(&Datum::from_str("thomas"), &Datum::from_str("^THOM"), false),
+        Ok(Datum::from_bool(false))
+    );
+    assert_eq!(
+        patterns.regex_match(&Datum::from_str("thomas"), &Datum::from_str("^THOM"), true),
+        Ok(Datum::from_bool(true))
+    );
+}
+
+#[test]
+fn regex_match_null() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regex_match(&Datum::from_null(), &Datum::from_str("a"), false),
+        Ok(Datum::from_null())
+    );
+    assert_eq!(
+        patterns.regex_match(&Datum::from_str("a"), &Datum::from_null(), false),
+        Ok(Datum::from_null())
+    );
+}
+
+#[test]
+fn invalid_pattern() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regex_match(&Datum::from_str("a"), &Datum::from_str("(a"), false),
+        Err(EvalError::InvalidRegularExpression("unclosed group".to_owned()))
+    );
+}
+
+#[test]
+fn replace_first_match() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_replace(
+            &Datum::from_str("foobarbaz"),
+            &Datum::from_str("b.."),
+            &Datum::from_str("X"),
+            &Datum::from_null()
+        ),
+        Ok(Datum::from_string("fooXbaz".to_owned()))
+    );
+}
+
+#[test]
+fn replace_all_matches_with_groups() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_replace(
+            &Datum::from_str("foobarbaz"),
+            &Datum::from_str("b(..)"),
+            &Datum::from_str("X\\1Y&$"),
+            &Datum::from_str("g")
+        ),
+        Ok(Datum::from_string("fooXarYbar$XazYbaz$".to_owned()))
+    );
+}
+
+#[test]
+fn replace_ignoring_case() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_replace(
+            &Datum::from_str("Hello hello"),
+            &Datum::from_str("HELLO"),
+            &Datum::from_str("bye"),
+            &Datum::from_str("gi")
+        ),
+        Ok(Datum::from_string("bye bye".to_owned()))
+    );
+}
+
+#[test]
+fn invalid_flag() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_replace(
+            &Datum::from_str("a"),
+            &Datum::from_str("a"),
+            &Datum::from_str("b"),
+            &Datum::from_str("z")
+        ),
+        Err(EvalError::InvalidRegularExpression("invalid option \"z\"".to_owned()))
+    );
+}
+
+#[test]
+fn first_match_groups() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_matches(
+            &Datum::from_str("foobarbequebaz"),
+            &Datum::from_str("(bar)(beque)"),
+            &Datum::from_null()
+        ),
+        Ok(vec![Datum::from_string("{bar,beque}".to_owned())])
+    );
+}
+
+#[test]
+fn all_matches() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_matches(
+            &Datum::from_str("foobarbequebazilbarfbonk"),
+            &Datum::from_str("(b[^b]+)(b[^b]+)"),
+            &Datum::from_str("g")
+        ),
+        Ok(vec![
+            Datum::from_string("{bar,beque}".to_owned()),
+            Datum::from_string("{bazil,barf}".to_owned())
+        ])
+    );
+}
+
+#[test]
+fn whole_match_and_quoted_elements() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_matches(
+            &Datum::from_str("say a b"),
+            &Datum::from_str("a b"),
+            &Datum::from_null()
+        ),
+        Ok(vec![Datum::from_string("{\"a b\"}".to_owned())])
+    );
+    assert_eq!(
+        patterns.regexp_matches(
+            &Datum::from_str("abc"),
+            &Datum::from_str("(x)?abc"),
+            &Datum::from_null()
+        ),
+        Ok(vec![Datum::from_string("{NULL}".to_owned())])
+    );
+}
+
+#[test]
+fn no_matches() {
+    let mut patterns = PatternCache::default();
+
+    assert_eq!(
+        patterns.regexp_matches(&Datum::from_str("abc"), &Datum::from_str("x"), &Datum::from_str("g")),
+        Ok(vec![])
+    );
+}
@@ -12,9 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::dml::{scanned_rows, MemoryAccountant, ScanError};
 use connection::Sender;
 use data_manager::DatabaseHandle;
-use pg_model::results::QueryEvent;
+use pg_model::results::{QueryError, QueryEvent};
 use plan::TableDeletes;
 use std::sync::Arc;
 
@@ -22,6 +23,7 @@ pub(crate) struct DeleteCommand {
     table_deletes: TableDeletes,
     data_manager: Arc<DatabaseHandle>,
     sender: Arc<dyn Sender>,
+    work_mem: usize,
 }
 
 impl DeleteCommand {
@@ -29,11 +31,13 @@ impl DeleteCommand {
         table_deletes: TableDeletes,
         data_manager: Arc<DatabaseHandle>,
         sender: Arc<dyn Sender>,
+        work_mem: usize,
     ) -> DeleteCommand {
         DeleteCommand {
             table_deletes,
             data_manager,
             sender,
+            work_mem,
         }
     }
 
@@ -45,11 +49,22 @@ impl DeleteCommand {
             }
             Ok(reads) => reads,
         };
-        let keys = reads
-            .map(Result::unwrap)
-            .map(Result::unwrap)
-            .map(|(key, _)| key)
-            .collect();
+        let keys = match scanned_rows(reads, &mut MemoryAccountant::new(self.work_mem)) {
+            Ok(rows) => rows.into_iter().map(|(key, _)| key).collect(),
+            Err(ScanError::Corrupted(key)) => {
+                log::error!("row {:?} of {:?} is corrupted", key, self.table_deletes.table_id);
+                self.sender
+                    .send(Err(QueryError::data_corrupted()))
+                    .expect("To Send Query Result to Client");
+                return;
+            }
+            Err(ScanError::OutOfMemory) => {
+                self.sender
+                    .send(Err(QueryError::out_of_memory()))
+                    .expect("To Send Query Result to Client");
+                return;
+            }
+        };
 
         let size = match self.data_manager.delete_from(&self.table_deletes.table_id, keys) {
             Err(()) => {
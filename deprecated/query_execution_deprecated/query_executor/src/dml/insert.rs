@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::dml::MemoryAccountant;
 use ast::operations::ScalarOp;
 use binary::{Binary, Row};
 use connection::Sender;
@@ -28,6 +29,8 @@ pub(crate) struct InsertCommand {
     table_inserts: TableInserts,
     data_manager: Arc<DatabaseHandle>,
     sender: Arc<dyn Sender>,
+    work_mem: usize,
+    strict_casts: bool,
 }
 
 impl InsertCommand {
@@ -35,11 +38,15 @@ impl InsertCommand {
         table_inserts: TableInserts,
         data_manager: Arc<DatabaseHandle>,
         sender: Arc<dyn Sender>,
+        work_mem: usize,
+        strict_casts: bool,
     ) -> InsertCommand {
         InsertCommand {
             table_inserts,
             data_manager,
             sender,
+            work_mem,
+            strict_casts,
         }
     }
 
@@ -69,6 +76,12 @@ impl InsertCommand {
                         log::error!("not a value {} was accessed during expression evaluation", not_a_value);
                         return;
                     }
+                    Err(EvalError::InvalidRegularExpression(message)) => {
+                        self.sender
+                            .send(Err(QueryError::invalid_regular_expression(message)))
+                            .expect("To Send Query Result to Client");
+                        return;
+                    }
                 };
                 row.push(value);
             }
@@ -77,8 +90,10 @@ impl InsertCommand {
 
         log::debug!("rows {:?}", rows);
 
-        let mut to_write: Vec<Row> = vec![];
-        for (row_index, row) in rows.iter().enumerate() {
+        let mut memory = MemoryAccountant::new(self.work_mem);
+        let mut to_write: Vec<Row> = Vec::with_capacity(rows.len());
+        let keys = self.data_manager.next_key_ids(&self.table_inserts.table_id, rows.len());
+        for ((row_index, row), key) in rows.iter().enumerate().zip(keys) {
             if row.len() > self.table_inserts.column_indices.len() {
                 self.sender
                     .send(Err(QueryError::too_many_insert_expressions()))
@@ -86,11 +101,7 @@ impl InsertCommand {
                 return;
             }
 
-            let key = self
-                .data_manager
-                .next_key_id(&self.table_inserts.table_id)
-                .to_be_bytes()
-                .to_vec();
+            let key = key.to_be_bytes().to_vec();
 
             // TODO: The default value or NULL should be initialized for SQL types of all columns.
             let mut record = vec![Datum::from_null(); self.table_inserts.column_indices.len()];
@@ -98,7 +109,12 @@ impl InsertCommand {
             for (item, (index, name, sql_type, type_constraint)) in
                 row.iter().zip(self.table_inserts.column_indices.iter())
             {
-                match item.cast(sql_type) {
+                let cast = if self.strict_casts {
+                    item.strict_cast(sql_type)
+                } else {
+                    item.cast(sql_type)
+                };
+                match cast {
                     Ok(item) => match type_constraint.validate(item) {
                         Ok(datum) => {
                             record[*index] = datum;
@@ -107,6 +123,10 @@ impl InsertCommand {
                             errors.push((error, ColumnDefinition::new(name, *sql_type)));
                         }
                     },
+                    Err(_err) if self.strict_casts => errors.push((
+                        ConstraintError::TypeMismatch(item.to_string()),
+                        ColumnDefinition::new(name, *sql_type),
+                    )),
                     Err(_err) => {
                         self.sender
                             .send(Err(QueryError::invalid_text_representation(sql_type.into(), item)))
@@ -142,7 +162,14 @@ impl InsertCommand {
                 }
                 return;
             }
-            to_write.push((Binary::with_data(key), Binary::pack(&record)));
+            let row = (Binary::with_data(key), Binary::pack(&record));
+            if memory.track(&row).is_err() {
+                self.sender
+                    .send(Err(QueryError::out_of_memory()))
+                    .expect("To Send Result to Client");
+                return;
+            }
+            to_write.push(row);
         }
 
         let size = match self.data_manager.write_into(&self.table_inserts.table_id, to_write) {
@@ -12,7 +12,54 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use binary::{Key, ReadCursor, Row, StorageError};
+
 pub(crate) mod delete;
 pub(crate) mod insert;
 pub(crate) mod select;
 pub(crate) mod update;
+
+pub(crate) struct OutOfMemory;
+
+/// bytes of rows that a command holds at once, bounded by `work_mem`
+pub(crate) struct MemoryAccountant {
+    used: usize,
+    limit: usize,
+}
+
+impl MemoryAccountant {
+    pub(crate) fn new(limit: usize) -> MemoryAccountant {
+        MemoryAccountant { used: 0, limit }
+    }
+
+    pub(crate) fn track(&mut self, (key, value): &Row) -> Result<(), OutOfMemory> {
+        self.used += key.to_bytes().len() + value.to_bytes().len();
+        if self.used > self.limit {
+            Err(OutOfMemory)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub(crate) enum ScanError {
+    Corrupted(Key),
+    OutOfMemory,
+}
+
+/// collects rows of a table scan, fails with a key of the first corrupted
+/// row or when the rows do not fit into `work_mem`
+pub(crate) fn scanned_rows(reads: ReadCursor, memory: &mut MemoryAccountant) -> Result<Vec<Row>, ScanError> {
+    let mut rows = vec![];
+    for row in reads.map(Result::unwrap) {
+        match row {
+            Err(StorageError::Corrupted(key)) => return Err(ScanError::Corrupted(key)),
+            row => {
+                let row = row.unwrap();
+                memory.track(&row).map_err(|OutOfMemory| ScanError::OutOfMemory)?;
+                rows.push(row);
+            }
+        }
+    }
+    Ok(rows)
+}
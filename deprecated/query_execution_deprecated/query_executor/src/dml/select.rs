@@ -16,27 +16,44 @@ use ast::{
     predicates::{PredicateOp, PredicateValue},
     values::ScalarValue,
 };
-use binary::ReadCursor;
+use binary::{ReadCursor, StorageError};
 use connection::Sender;
 use data_manager::{DataDefReader, DatabaseHandle};
 use meta_def::Id;
-use pg_model::results::QueryEvent;
+use pg_model::results::{QueryError, QueryEvent};
 use pg_wire::{ColumnMetadata, PgType};
 use plan::{FullTableId, SelectInput};
-use std::{convert::TryInto, sync::Arc};
+use std::{
+    cell::Cell,
+    convert::TryInto,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 struct Source {
     table_id: FullTableId,
+    columns: Vec<usize>,
     cursor: Option<ReadCursor>,
     data_manager: Arc<DatabaseHandle>,
+    corrupted: Rc<Cell<bool>>,
 }
 
 impl Source {
-    fn new(table_id: FullTableId, data_manager: Arc<DatabaseHandle>) -> Source {
+    fn new(
+        table_id: FullTableId,
+        columns: Vec<usize>,
+        data_manager: Arc<DatabaseHandle>,
+        corrupted: Rc<Cell<bool>>,
+    ) -> Source {
         Source {
             table_id,
+            columns,
             cursor: None,
             data_manager,
+            corrupted,
         }
     }
 }
@@ -49,16 +66,23 @@ impl Iterator for Source {
             self.cursor = self.data_manager.full_scan(&self.table_id).ok();
         }
         if let Some(cursor) = self.cursor.as_mut() {
-            if let Some((_key, value)) = cursor.next().map(Result::unwrap).map(Result::unwrap) {
-                Some(
-                    value
-                        .unpack()
-                        .iter()
-                        .map(|d| d.try_into().unwrap())
-                        .collect::<Vec<ScalarValue>>(),
-                )
-            } else {
-                None
+            match cursor.next().map(Result::unwrap) {
+                Some(Err(StorageError::Corrupted(key))) => {
+                    log::error!("row {:?} of {:?} is corrupted", key, self.table_id);
+                    self.corrupted.set(true);
+                    None
+                }
+                Some(row) => {
+                    let (_key, value) = row.unwrap();
+                    Some(
+                        value
+                            .unpack_columns(&self.columns)
+                            .iter()
+                            .map(|d| d.try_into().unwrap())
+                            .collect::<Vec<ScalarValue>>(),
+                    )
+                }
+                None => None,
             }
         } else {
             None
@@ -66,6 +90,47 @@ impl Iterator for Source {
     }
 }
 
+/// columns that the query reads, sorted by their position in the table.
+/// Scanned rows contain only them, so selected columns and the predicate
+/// are remapped to positions in the list
+struct ReferencedColumns(Vec<usize>);
+
+impl ReferencedColumns {
+    fn new(select_input: &SelectInput) -> ReferencedColumns {
+        let mut columns = select_input
+            .selected_columns
+            .iter()
+            .map(|column| *column as usize)
+            .collect::<Vec<usize>>();
+        if let Some((PredicateValue::Column(column), _, _)) = &select_input.predicate {
+            columns.push(*column as usize);
+        }
+        columns.sort_unstable();
+        columns.dedup();
+        ReferencedColumns(columns)
+    }
+
+    fn position(&self, column: Id) -> Id {
+        self.0
+            .binary_search(&(column as usize))
+            .expect("column is referenced by the query") as Id
+    }
+
+    fn remap(&self, selected_columns: &[Id]) -> Vec<Id> {
+        selected_columns.iter().map(|column| self.position(*column)).collect()
+    }
+
+    fn remap_predicate(
+        &self,
+        predicate: (PredicateValue, PredicateOp, PredicateValue),
+    ) -> (PredicateValue, PredicateOp, PredicateValue) {
+        match predicate {
+            (PredicateValue::Column(column), op, right) => (PredicateValue::Column(self.position(column)), op, right),
+            predicate => predicate,
+        }
+    }
+}
+
 struct Projection<'p> {
     selected_columns: Vec<Id>,
     input: Box<dyn Iterator<Item = Vec<ScalarValue>> + 'p>,
@@ -136,6 +201,7 @@ pub(crate) struct SelectCommand {
     select_input: SelectInput,
     data_manager: Arc<DatabaseHandle>,
     sender: Arc<dyn Sender>,
+    abort: Arc<AtomicBool>,
 }
 
 impl SelectCommand {
@@ -143,11 +209,13 @@ impl SelectCommand {
         select_input: SelectInput,
         data_manager: Arc<DatabaseHandle>,
         sender: Arc<dyn Sender>,
+        abort: Arc<AtomicBool>,
     ) -> SelectCommand {
         SelectCommand {
             select_input,
             data_manager,
             sender,
+            abort,
         }
     }
 
@@ -165,23 +233,45 @@ impl SelectCommand {
             )))
             .expect("To Send Query Result to Client");
 
-        let source = Source::new(self.select_input.table_id, self.data_manager.clone());
-        let mut projection = match self.select_input.predicate {
-            None => Projection::new(self.select_input.selected_columns, Box::new(source)),
+        let corrupted = Rc::new(Cell::new(false));
+        let referenced = ReferencedColumns::new(&self.select_input);
+        let selected_columns = referenced.remap(&self.select_input.selected_columns);
+        let predicate = self
+            .select_input
+            .predicate
+            .map(|predicate| referenced.remap_predicate(predicate));
+        let source = Source::new(
+            self.select_input.table_id,
+            referenced.0,
+            self.data_manager.clone(),
+            corrupted.clone(),
+        );
+        let mut projection = match predicate {
+            None => Projection::new(selected_columns, Box::new(source)),
             Some(predicate) => {
                 let predicate = Filter::new(Box::new(source), predicate);
-                Projection::new(self.select_input.selected_columns, Box::new(predicate))
+                Projection::new(selected_columns, Box::new(predicate))
             }
         };
 
         for tuple in &mut projection {
+            if self.abort.load(Ordering::Relaxed) {
+                log::warn!("select is aborted, client does not read its rows");
+                return;
+            }
             self.sender
                 .send(Ok(QueryEvent::DataRow(tuple)))
                 .expect("To Send Query Result to Client");
         }
 
-        self.sender
-            .send(Ok(QueryEvent::RecordsSelected(projection.consumed)))
-            .expect("To Send Query Result to Client");
+        if corrupted.get() {
+            self.sender
+                .send(Err(QueryError::data_corrupted()))
+                .expect("To Send Query Result to Client");
+        } else {
+            self.sender
+                .send(Ok(QueryEvent::RecordsSelected(projection.consumed)))
+                .expect("To Send Query Result to Client");
+        }
     }
 }
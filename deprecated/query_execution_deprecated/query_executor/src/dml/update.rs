@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::dml::{scanned_rows, MemoryAccountant, ScanError};
 use ast::operations::ScalarOp;
 use binary::Binary;
 use connection::Sender;
@@ -26,6 +27,8 @@ pub(crate) struct UpdateCommand {
     table_update: TableUpdates,
     data_manager: Arc<DatabaseHandle>,
     sender: Arc<dyn Sender>,
+    work_mem: usize,
+    strict_casts: bool,
 }
 
 impl UpdateCommand {
@@ -33,11 +36,15 @@ impl UpdateCommand {
         table_update: TableUpdates,
         data_manager: Arc<DatabaseHandle>,
         sender: Arc<dyn Sender>,
+        work_mem: usize,
+        strict_casts: bool,
     ) -> UpdateCommand {
         UpdateCommand {
             table_update,
             data_manager,
             sender,
+            work_mem,
+            strict_casts,
         }
     }
 
@@ -87,19 +94,41 @@ impl UpdateCommand {
                     log::error!("not a value {} was accessed during expression evaluation", not_a_value);
                     return;
                 }
+                Err(EvalError::InvalidRegularExpression(message)) => {
+                    self.sender
+                        .send(Err(QueryError::invalid_regular_expression(message)))
+                        .expect("To Send Query Result to Client");
+                    return;
+                }
             }
         }
 
+        let mut memory = MemoryAccountant::new(self.work_mem);
         let reads = match self.data_manager.full_scan(&self.table_update.table_id) {
             Err(()) => {
                 log::error!("Error while scanning {:?}", self.table_update.table_id);
                 return;
             }
-            Ok(reads) => reads,
+            Ok(reads) => match scanned_rows(reads, &mut memory) {
+                Ok(rows) => rows,
+                Err(ScanError::Corrupted(key)) => {
+                    log::error!("row {:?} of {:?} is corrupted", key, self.table_update.table_id);
+                    self.sender
+                        .send(Err(QueryError::data_corrupted()))
+                        .expect("To Send Query Result to Client");
+                    return;
+                }
+                Err(ScanError::OutOfMemory) => {
+                    self.sender
+                        .send(Err(QueryError::out_of_memory()))
+                        .expect("To Send Query Result to Client");
+                    return;
+                }
+            },
         };
         let expr_eval = DynamicExpressionEvaluation::new(all_columns);
         let mut to_update = Vec::new();
-        for (row_idx, (key, values)) in reads.map(Result::unwrap).map(Result::unwrap).enumerate() {
+        for (row_idx, (key, values)) in reads.into_iter().enumerate() {
             let data = values.unpack();
             let mut updated = values.unpack();
 
@@ -119,9 +148,32 @@ impl UpdateCommand {
                         log::error!("not a value {} was accessed during expression evaluation", not_a_value);
                         return;
                     }
+                    Err(EvalError::InvalidRegularExpression(message)) => {
+                        self.sender
+                            .send(Err(QueryError::invalid_regular_expression(message)))
+                            .expect("To Send Query Result to Client");
+                        return;
+                    }
                 };
-                let value = match value.cast(&sql_type) {
+                let cast = if self.strict_casts {
+                    value.strict_cast(&sql_type)
+                } else {
+                    value.cast(&sql_type)
+                };
+                let value = match cast {
                     Ok(value) => value,
+                    Err(_err) if self.strict_casts => {
+                        self.sender
+                            .send(Err(QueryError::type_mismatch(
+                                &value.to_string(),
+                                sql_type.into(),
+                                column_name,
+                                row_idx + 1,
+                            )))
+                            .expect("To Send Query Result to client");
+                        has_err = true;
+                        continue;
+                    }
                     Err(_err) => {
                         self.sender
                             .send(Err(QueryError::invalid_text_representation(sql_type.into(), value)))
@@ -166,7 +218,14 @@ impl UpdateCommand {
                 return;
             }
 
-            to_update.push((key, Binary::pack(&updated)));
+            let row = (key, Binary::pack(&updated));
+            if memory.track(&row).is_err() {
+                self.sender
+                    .send(Err(QueryError::out_of_memory()))
+                    .expect("To Send Query Result to Client");
+                return;
+            }
+            to_update.push(row);
         }
         let size = match self.data_manager.write_into(&self.table_update.table_id, to_update) {
             Err(()) => {
@@ -18,34 +18,81 @@ use data_manager::DatabaseHandle;
 use pg_model::results::{QueryError, QueryEvent};
 use plan::Plan;
 use sql_ast::Statement;
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 
 mod dml;
 
+// TODO: results could be handed to analytics consumers as Arrow IPC buffers,
+//       but there is neither `COPY TO` nor an embedded API to return them
 pub struct QueryExecutor {
     data_manager: Arc<DatabaseHandle>,
     sender: Arc<dyn Sender>,
+    work_mem: usize,
+    strict_casts: bool,
+    abort: Arc<AtomicBool>,
 }
 
 impl QueryExecutor {
     pub fn new(data_manager: Arc<DatabaseHandle>, sender: Arc<dyn Sender>) -> Self {
-        Self { data_manager, sender }
+        Self {
+            data_manager,
+            sender,
+            work_mem: usize::MAX,
+            strict_casts: false,
+            abort: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// limits bytes of rows that a single command keeps in memory, a command
+    /// that needs more fails with `out of memory for query`
+    pub fn set_work_mem(&mut self, work_mem: usize) {
+        self.work_mem = work_mem;
+    }
+
+    /// assigns to columns only values of the same kind of type instead of
+    /// casting them as PostgreSQL does, e.g. strings to numbers
+    pub fn set_strict_casts(&mut self, strict_casts: bool) {
+        self.strict_casts = strict_casts;
+    }
+
+    /// selects stop producing rows once the switch is on, e.g. when client
+    /// does not read them anymore
+    pub fn set_abort(&mut self, abort: Arc<AtomicBool>) {
+        self.abort = abort;
     }
 
     pub fn execute(&self, plan: Plan) {
         match plan {
-            Plan::Insert(table_insert) => {
-                InsertCommand::new(table_insert, self.data_manager.clone(), self.sender.clone()).execute()
-            }
-            Plan::Update(table_update) => {
-                UpdateCommand::new(table_update, self.data_manager.clone(), self.sender.clone()).execute()
-            }
-            Plan::Delete(table_delete) => {
-                DeleteCommand::new(table_delete, self.data_manager.clone(), self.sender.clone()).execute()
-            }
-            Plan::Select(select_input) => {
-                SelectCommand::new(select_input, self.data_manager.clone(), self.sender.clone()).execute()
-            }
+            Plan::Insert(table_insert) => InsertCommand::new(
+                table_insert,
+                self.data_manager.clone(),
+                self.sender.clone(),
+                self.work_mem,
+                self.strict_casts,
+            )
+            .execute(),
+            Plan::Update(table_update) => UpdateCommand::new(
+                table_update,
+                self.data_manager.clone(),
+                self.sender.clone(),
+                self.work_mem,
+                self.strict_casts,
+            )
+            .execute(),
+            Plan::Delete(table_delete) => DeleteCommand::new(
+                table_delete,
+                self.data_manager.clone(),
+                self.sender.clone(),
+                self.work_mem,
+            )
+            .execute(),
+            Plan::Select(select_input) => SelectCommand::new(
+                select_input,
+                self.data_manager.clone(),
+                self.sender.clone(),
+                self.abort.clone(),
+            )
+            .execute(),
             Plan::NotProcessed(statement) => match *statement {
                 Statement::StartTransaction { .. } => {
                     self.sender
@@ -80,7 +80,7 @@ impl TryFrom<&ObjectName> for SchemaName {
         if object.0.len() != 1 {
             Err(SchemaNamingError(object.to_string()))
         } else {
-            Ok(SchemaName(object.to_string().to_lowercase()))
+            Ok(SchemaName(sql_ast::ident_name(&object.0[0])))
         }
     }
 }
@@ -124,12 +124,9 @@ impl TryFrom<&ObjectName> for FullTableName {
         } else if object.0.len() != 2 {
             Err(TableNamingError::NotProcessed(object.to_string()))
         } else {
-            let table_name = object.0.last().unwrap().value.clone();
-            let schema_name = object.0.first().unwrap().value.clone();
-            Ok(FullTableName(
-                SchemaName(schema_name.to_lowercase()),
-                table_name.to_lowercase(),
-            ))
+            let table_name = sql_ast::ident_name(object.0.last().unwrap());
+            let schema_name = sql_ast::ident_name(object.0.first().unwrap());
+            Ok(FullTableName(SchemaName(schema_name), table_name))
         }
     }
 }
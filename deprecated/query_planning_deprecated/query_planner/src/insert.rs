@@ -84,8 +84,7 @@ impl Planner for InsertPlanner<'_> {
                                 } else {
                                     let mut columns = HashSet::new();
                                     let mut index_cols = vec![];
-                                    for col_name in self.columns.iter().map(|id| id.value.as_str()) {
-                                        let column_name = col_name.to_lowercase();
+                                    for column_name in self.columns.iter().map(sql_ast::ident_name) {
                                         let mut found = None;
                                         for (index, (_col_id, column_definition)) in all_columns.iter().enumerate() {
                                             if column_definition.has_name(&column_name) {
@@ -15,11 +15,13 @@
 ///! Module for transforming the input Query AST into representation the engine can process.
 mod delete;
 mod insert;
+mod optimizer;
 mod select;
 mod update;
 
 use crate::{delete::DeletePlanner, insert::InsertPlanner, select::SelectPlanner, update::UpdatePlanner};
 use data_manager::DataDefReader;
+pub use optimizer::{ConstantFolding, Optimizer, Rule};
 use plan::Plan;
 use sql_ast::Statement;
 use std::sync::Arc;
@@ -68,14 +70,23 @@ trait Planner {
 
 pub struct QueryPlanner {
     metadata: Arc<dyn DataDefReader>,
+    optimizer: Optimizer,
 }
 
 impl QueryPlanner {
     pub fn new(metadata: Arc<dyn DataDefReader>) -> Self {
-        Self { metadata }
+        Self::with_optimizer(metadata, Optimizer::default())
+    }
+
+    pub fn with_optimizer(metadata: Arc<dyn DataDefReader>, optimizer: Optimizer) -> Self {
+        Self { metadata, optimizer }
     }
 
     pub fn plan(&self, statement: &Statement) -> Result<Plan> {
+        self.build(statement).map(|plan| self.optimizer.optimize(plan))
+    }
+
+    fn build(&self, statement: &Statement) -> Result<Plan> {
         match statement {
             Statement::Insert {
                 table_name,
@@ -0,0 +1,122 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+///! Rewrite rules applied to a plan after it was built from the query AST.
+use ast::{
+    operations::{BinaryOp, ScalarOp},
+    values::ScalarValue,
+};
+use plan::Plan;
+
+/// a single rewrite of a plan. Rules are independent from each other and
+/// have to return a plan that produces the same result as the given one
+pub trait Rule {
+    fn name(&self) -> &'static str;
+
+    fn apply(&self, plan: Plan) -> Plan;
+}
+
+/// applies rules one after another in the order they were added
+pub struct Optimizer {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+// TODO: rewriting correlated EXISTS/IN subqueries into semi-joins and NOT
+//       EXISTS into anti-joins needs plans with subqueries and a join operator
+//       to run them, the analyzer rejects both subqueries and joins for now
+// TODO: `LIKE 'abc%'` could be rewritten into a range scan over keys that
+//       start with the literal prefix of the pattern, but predicates of plans
+//       are only equalities and rows of tables are keyed by their ids rather
+//       than by values of columns
+impl Default for Optimizer {
+    fn default() -> Optimizer {
+        Optimizer::empty().with_rule(ConstantFolding)
+    }
+}
+
+impl Optimizer {
+    pub fn empty() -> Optimizer {
+        Optimizer { rules: vec![] }
+    }
+
+    pub fn with_rule<R: Rule + 'static>(mut self, rule: R) -> Optimizer {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn rules(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    pub fn optimize(&self, plan: Plan) -> Plan {
+        self.rules.iter().fold(plan, |plan, rule| {
+            log::trace!("applying {} rule", rule.name());
+            rule.apply(plan)
+        })
+    }
+}
+
+/// evaluates operations over literals once during planning instead of
+/// doing it for every row. Operations that could fail, like division by
+/// zero, are left for the executor to report
+pub struct ConstantFolding;
+
+impl ConstantFolding {
+    fn fold(&self, op: ScalarOp) -> ScalarOp {
+        match op {
+            ScalarOp::Binary(operator, left, right) => match (operator, self.fold(*left), self.fold(*right)) {
+                (BinaryOp::Add, ScalarOp::Value(ScalarValue::Number(l)), ScalarOp::Value(ScalarValue::Number(r))) => {
+                    ScalarOp::Value(ScalarValue::Number(l + r))
+                }
+                (BinaryOp::Sub, ScalarOp::Value(ScalarValue::Number(l)), ScalarOp::Value(ScalarValue::Number(r))) => {
+                    ScalarOp::Value(ScalarValue::Number(l - r))
+                }
+                (BinaryOp::Mul, ScalarOp::Value(ScalarValue::Number(l)), ScalarOp::Value(ScalarValue::Number(r))) => {
+                    ScalarOp::Value(ScalarValue::Number(l * r))
+                }
+                (
+                    BinaryOp::Concat,
+                    ScalarOp::Value(ScalarValue::String(l)),
+                    ScalarOp::Value(ScalarValue::String(r)),
+                ) => ScalarOp::Value(ScalarValue::String(l + r.as_str())),
+                (operator, left, right) => ScalarOp::Binary(operator, Box::new(left), Box::new(right)),
+            },
+            op => op,
+        }
+    }
+}
+
+impl Rule for ConstantFolding {
+    fn name(&self) -> &'static str {
+        "constant folding"
+    }
+
+    fn apply(&self, plan: Plan) -> Plan {
+        match plan {
+            Plan::Insert(mut inserts) => {
+                inserts.input = inserts
+                    .input
+                    .into_iter()
+                    .map(|row| row.into_iter().map(|op| self.fold(op)).collect())
+                    .collect();
+                Plan::Insert(inserts)
+            }
+            Plan::Update(mut updates) => {
+                updates.input = updates.input.into_iter().map(|op| self.fold(op)).collect();
+                Plan::Update(updates)
+            }
+            plan => plan,
+        }
+    }
+}
@@ -73,8 +73,8 @@ impl Planner for SelectPlanner {
                                                     .collect::<Vec<String>>(),
                                             )
                                         }
-                                        SelectItem::UnnamedExpr(Expr::Identifier(Ident { value, .. })) => {
-                                            names.push(value.to_lowercase())
+                                        SelectItem::UnnamedExpr(Expr::Identifier(ident)) => {
+                                            names.push(sql_ast::ident_name(ident))
                                         }
                                         _ => {
                                             return Err(PlanError::feature_not_supported(&*self.query));
@@ -90,12 +90,16 @@ impl Planner for SelectPlanner {
                                 ids
                             };
 
+                            // TODO: `created_at > now() - interval '1 day'` needs timestamp types,
+                            //       `now()` and interval literals folded at plan time. Only equality
+                            //       to a number or a parameter is planned, and tables are neither
+                            //       partitioned nor have range indexes to prune by the predicate
                             let predicate = match selection {
                                 Some(Expr::BinaryOp { left, op, right }) => {
                                     let l = match left.deref() {
                                         Expr::Identifier(ident) => {
                                             let (ids, _not_found) = metadata
-                                                .column_ids(&full_table_id, &[ident.to_string()])
+                                                .column_ids(&full_table_id, &[sql_ast::ident_name(ident)])
                                                 .expect("table exists");
                                             PredicateValue::Column(ids[0])
                                         }
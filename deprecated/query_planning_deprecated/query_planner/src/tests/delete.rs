@@ -80,7 +80,7 @@ fn delete_from_table(planner_with_table: QueryPlanner) {
             selection: None
         }),
         Ok(Plan::Delete(TableDeletes {
-            table_id: FullTableId::from((0, 0))
+            table_id: FullTableId::from((SCHEMA_ID, TABLE_ID))
         }))
     );
 }
@@ -122,7 +122,7 @@ fn insert_into_table(planner_with_table: QueryPlanner) {
             SetExpr::Values(Values(vec![]))
         )),
         Ok(Plan::Insert(TableInserts {
-            table_id: FullTableId::from((0, 0)),
+            table_id: FullTableId::from((SCHEMA_ID, TABLE_ID)),
             column_indices: vec![
                 (0, "small_int".to_owned(), SqlType::SmallInt, TypeConstraint::SmallInt),
                 (1, "integer".to_owned(), SqlType::Integer, TypeConstraint::Integer),
@@ -142,7 +142,7 @@ fn insert_into_table_without_columns(planner_with_no_column_table: QueryPlanner)
     assert_eq!(
         planner_with_no_column_table.plan(&insert_into(ObjectName(vec![ident(SCHEMA), ident(TABLE)]), vec![])),
         Ok(Plan::Insert(TableInserts {
-            table_id: FullTableId::from((0, 0)),
+            table_id: FullTableId::from((SCHEMA_ID, TABLE_ID)),
             column_indices: vec![],
             input: vec![]
         }))
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use super::*;
-use data_manager::DatabaseHandle;
+use data_manager::{DatabaseHandle, FIRST_OBJECT_ID};
 use meta_def::ColumnDefinition;
 use sql_ast::Ident;
 use std::sync::Arc;
@@ -24,6 +24,8 @@ mod delete;
 #[cfg(test)]
 mod insert;
 #[cfg(test)]
+mod optimizer;
+#[cfg(test)]
 mod select;
 #[cfg(test)]
 mod update;
@@ -32,6 +34,8 @@ mod where_clause;
 
 const SCHEMA: &str = "schema_name";
 const TABLE: &str = "table_name";
+const SCHEMA_ID: u64 = FIRST_OBJECT_ID;
+const TABLE_ID: u64 = FIRST_OBJECT_ID + 1;
 
 #[rstest::fixture]
 fn planner() -> QueryPlanner {
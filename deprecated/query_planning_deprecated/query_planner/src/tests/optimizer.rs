@@ -0,0 +1,144 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::optimizer::{ConstantFolding, Optimizer, Rule};
+use ast::{
+    operations::{BinaryOp, ScalarOp},
+    values::ScalarValue,
+};
+use bigdecimal::BigDecimal;
+use plan::{FullTableId, TableDeletes, TableInserts, TableUpdates};
+
+fn number(value: i32) -> ScalarOp {
+    ScalarOp::Value(ScalarValue::Number(BigDecimal::from(value)))
+}
+
+fn string(value: &str) -> ScalarOp {
+    ScalarOp::Value(ScalarValue::String(value.to_owned()))
+}
+
+fn binary(op: BinaryOp, left: ScalarOp, right: ScalarOp) -> ScalarOp {
+    ScalarOp::Binary(op, Box::new(left), Box::new(right))
+}
+
+fn inserts(input: Vec<Vec<ScalarOp>>) -> Plan {
+    Plan::Insert(TableInserts {
+        table_id: FullTableId::from((0, 0)),
+        column_indices: vec![],
+        input,
+    })
+}
+
+fn updates(input: Vec<ScalarOp>) -> Plan {
+    Plan::Update(TableUpdates {
+        table_id: FullTableId::from((0, 0)),
+        column_indices: vec![],
+        input,
+    })
+}
+
+struct DeleteEverything;
+
+impl Rule for DeleteEverything {
+    fn name(&self) -> &'static str {
+        "delete everything"
+    }
+
+    fn apply(&self, _plan: Plan) -> Plan {
+        Plan::Delete(TableDeletes {
+            table_id: FullTableId::from((0, 0)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod pipeline {
+    use super::*;
+
+    #[test]
+    fn default_rules() {
+        assert_eq!(Optimizer::default().rules(), vec!["constant folding"]);
+    }
+
+    #[test]
+    fn rules_applied_in_order() {
+        let optimizer = Optimizer::empty()
+            .with_rule(DeleteEverything)
+            .with_rule(ConstantFolding);
+
+        assert_eq!(optimizer.rules(), vec!["delete everything", "constant folding"]);
+        assert_eq!(
+            optimizer.optimize(inserts(vec![vec![number(1)]])),
+            Plan::Delete(TableDeletes {
+                table_id: FullTableId::from((0, 0))
+            })
+        );
+    }
+
+    #[test]
+    fn empty_optimizer_keeps_plan() {
+        let plan = inserts(vec![vec![binary(BinaryOp::Add, number(1), number(2))]]);
+
+        assert_eq!(Optimizer::empty().optimize(plan.clone()), plan);
+    }
+}
+
+#[cfg(test)]
+mod constant_folding {
+    use super::*;
+
+    #[test]
+    fn arithmetic_over_numbers() {
+        assert_eq!(
+            ConstantFolding.apply(inserts(vec![vec![
+                binary(BinaryOp::Add, number(1), number(2)),
+                binary(BinaryOp::Sub, number(1), number(2)),
+                binary(BinaryOp::Mul, number(3), binary(BinaryOp::Add, number(1), number(2))),
+            ]])),
+            inserts(vec![vec![number(3), number(-1), number(9)]])
+        );
+    }
+
+    #[test]
+    fn concatenation_of_strings() {
+        assert_eq!(
+            ConstantFolding.apply(inserts(vec![vec![binary(BinaryOp::Concat, string("ab"), string("c"))]])),
+            inserts(vec![vec![string("abc")]])
+        );
+    }
+
+    #[test]
+    fn division_is_left_for_executor() {
+        let plan = inserts(vec![vec![binary(BinaryOp::Div, number(1), number(0))]]);
+
+        assert_eq!(ConstantFolding.apply(plan.clone()), plan);
+    }
+
+    #[test]
+    fn constant_part_of_update_with_column() {
+        assert_eq!(
+            ConstantFolding.apply(updates(vec![binary(
+                BinaryOp::Add,
+                ScalarOp::Column("col".to_owned()),
+                binary(BinaryOp::Mul, number(2), number(5))
+            )])),
+            updates(vec![binary(
+                BinaryOp::Add,
+                ScalarOp::Column("col".to_owned()),
+                number(10)
+            )])
+        );
+    }
+}
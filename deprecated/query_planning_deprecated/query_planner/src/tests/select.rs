@@ -180,7 +180,7 @@ fn select_from_table(planner_with_no_column_table: QueryPlanner) {
             fetch: None,
         }))),
         Ok(Plan::Select(SelectInput {
-            table_id: FullTableId::from((0, 0)),
+            table_id: FullTableId::from((SCHEMA_ID, TABLE_ID)),
             selected_columns: vec![],
             predicate: None
         }))
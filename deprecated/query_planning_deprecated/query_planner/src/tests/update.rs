@@ -97,7 +97,7 @@ fn update_table(planner_with_table: QueryPlanner) {
             selection: None
         }),
         Ok(Plan::Update(TableUpdates {
-            table_id: FullTableId::from((0, 0)),
+            table_id: FullTableId::from((SCHEMA_ID, TABLE_ID)),
             column_indices: vec![(0, "small_int".to_owned(), SqlType::SmallInt, TypeConstraint::SmallInt)],
             input: vec![ScalarOp::Value(ScalarValue::String("".to_string()))],
         }))
@@ -53,7 +53,7 @@ fn select_from_table(planner_with_table: QueryPlanner) {
             fetch: None,
         }))),
         Ok(Plan::Select(SelectInput {
-            table_id: FullTableId::from((0, 0)),
+            table_id: FullTableId::from((SCHEMA_ID, TABLE_ID)),
             selected_columns: vec![0, 1, 2],
             predicate: Some((
                 PredicateValue::Column(0),
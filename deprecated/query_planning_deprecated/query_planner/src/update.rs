@@ -50,7 +50,7 @@ impl Planner for UpdatePlanner<'_> {
                         let mut columns = HashSet::new();
                         for Assignment { id, value } in self.assignments.iter() {
                             let mut found = None;
-                            let column_name = id.to_string().to_lowercase();
+                            let column_name = sql_ast::ident_name(id);
                             for (index, (_column_id, column_definition)) in all_columns.iter().enumerate() {
                                 if column_definition.has_name(&column_name) {
                                     match ScalarOp::transform(&value) {
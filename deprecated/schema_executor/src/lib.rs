@@ -12,10 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use analysis_tree::{CreateTableQuery, DropSchemasQuery, DropTablesQuery, SchemaChange, TableInfo};
-use data_manager::{DataDefOperationExecutor, DatabaseHandle};
+use analysis_tree::{
+    AddColumnQuery, ColumnInfo, CreateTableQuery, DropSchemasQuery, DropTablesQuery, SchemaChange, TableInfo,
+};
+use binary::{Binary, Values};
+use data_manager::{DataDefOperationExecutor, DataDefReader, DatabaseHandle, RewriteError};
 use definition_operations::{Step, SystemObject, SystemOperation};
-use std::sync::Arc;
+use meta_def::ColumnDefinition;
+use repr::Datum;
+use std::{convert::Infallible, sync::Arc};
 
 pub struct SystemSchemaExecutor {
     data_manager: Arc<DatabaseHandle>,
@@ -36,6 +41,10 @@ impl SystemSchemaExecutor {
             skip_steps_if: _skip_steps_if,
             steps,
         } = operation;
+        let _catalog = self.data_manager.lock_catalog();
+        if let SchemaChange::AddColumn(AddColumnQuery { table_info, column_def }) = change {
+            return self.add_column(table_info, column_def);
+        }
         let operations = steps;
         for operation in operations {
             for operation in operation {
@@ -125,6 +134,7 @@ impl SystemSchemaExecutor {
                         break;
                     }
                     (SchemaChange::DropTables(_), _, _) => {}
+                    (SchemaChange::AddColumn(_), _, _) => unreachable!("column is added by rewrite of the table"),
                 }
             }
         }
@@ -133,6 +143,56 @@ impl SystemSchemaExecutor {
             SchemaChange::DropSchemas(_) => Ok(ExecutionOutcome::SchemaDropped),
             SchemaChange::CreateTable(_) => Ok(ExecutionOutcome::TableCreated),
             SchemaChange::DropTables(_) => Ok(ExecutionOutcome::TableDropped),
+            SchemaChange::AddColumn(_) => Ok(ExecutionOutcome::TableAltered),
+        }
+    }
+
+    /// rewrites every row of the table with `NULL` appended as the value of
+    /// the new column, rows and the column are switched together
+    fn add_column(&self, table_info: &TableInfo, column_def: &ColumnInfo) -> Result<ExecutionOutcome, ExecutionError> {
+        let TableInfo {
+            schema_name,
+            table_name,
+            ..
+        } = table_info;
+        let full_table_id = match self.data_manager.table_exists(schema_name, table_name) {
+            None => return Err(ExecutionError::SchemaDoesNotExist(schema_name.to_owned())),
+            Some((_schema_id, None)) => {
+                return Err(ExecutionError::TableDoesNotExists(
+                    schema_name.to_owned(),
+                    table_name.to_owned(),
+                ))
+            }
+            Some((schema_id, Some(table_id))) => (schema_id, table_id),
+        };
+        let mut columns = match self.data_manager.table_columns(&full_table_id) {
+            Ok(columns) => columns
+                .into_iter()
+                .map(|(_id, column)| column)
+                .collect::<Vec<ColumnDefinition>>(),
+            Err(()) => {
+                return Err(ExecutionError::TableDoesNotExists(
+                    schema_name.to_owned(),
+                    table_name.to_owned(),
+                ))
+            }
+        };
+        columns.push(ColumnDefinition::new(&column_def.name, column_def.sql_type));
+        match self.data_manager.rewrite_table(&full_table_id, &columns, |values| {
+            let mut datums = values.unpack();
+            datums.push(Datum::from_null());
+            Ok::<Values, Infallible>(Binary::pack(&datums))
+        }) {
+            Ok(Ok(_rows)) => Ok(ExecutionOutcome::TableAltered),
+            Ok(Err(RewriteError::Row(never))) => match never {},
+            Ok(Err(RewriteError::ScansOpen)) => Err(ExecutionError::TableInUse(
+                schema_name.to_owned(),
+                table_name.to_owned(),
+            )),
+            Err(()) => Err(ExecutionError::TableNotRewritten(
+                schema_name.to_owned(),
+                table_name.to_owned(),
+            )),
         }
     }
 }
@@ -143,6 +203,7 @@ pub enum ExecutionOutcome {
     SchemaDropped,
     TableCreated,
     TableDropped,
+    TableAltered,
 }
 
 #[derive(Debug, PartialEq)]
@@ -151,6 +212,8 @@ pub enum ExecutionError {
     SchemaDoesNotExist(String),
     TableAlreadyExists(String, String),
     TableDoesNotExists(String, String),
+    TableNotRewritten(String, String),
+    TableInUse(String, String),
 }
 
 #[cfg(test)]
@@ -160,6 +223,7 @@ mod tests {
     use data_manager::{DEFAULT_CATALOG, DEFINITION_SCHEMA, SCHEMATA_TABLE, TABLES_TABLE};
     use definition::SchemaName;
     use definition_operations::{Kind, Record, Step, SystemObject};
+    use types::SqlType;
 
     const SCHEMA: &str = "schema_name";
     const TABLE: &str = "table_name";
@@ -564,4 +628,146 @@ mod tests {
             Err(ExecutionError::TableDoesNotExists(SCHEMA.to_owned(), TABLE.to_owned()))
         );
     }
+
+    #[test]
+    fn create_same_schema_concurrently() {
+        let data_manager = Arc::new(DatabaseHandle::in_memory());
+
+        let threads = (0..8)
+            .map(|_| {
+                let executor = SystemSchemaExecutor::new(data_manager.clone());
+                std::thread::spawn(move || {
+                    executor.execute(
+                        &SchemaChange::CreateSchema(CreateSchemaQuery {
+                            schema_name: SchemaName::from(&SCHEMA),
+                            if_not_exists: false,
+                        }),
+                        &SystemOperation {
+                            kind: Kind::Create(SystemObject::Schema),
+                            skip_steps_if: None,
+                            steps: vec![vec![
+                                Step::CheckExistence {
+                                    system_object: SystemObject::Schema,
+                                    object_name: vec![SCHEMA.to_owned()],
+                                },
+                                Step::CreateFolder {
+                                    name: SCHEMA.to_owned(),
+                                },
+                                Step::CreateRecord {
+                                    system_schema: DEFINITION_SCHEMA.to_owned(),
+                                    system_table: SCHEMATA_TABLE.to_owned(),
+                                    record: Record::Schema {
+                                        catalog_name: DEFAULT_CATALOG.to_owned(),
+                                        schema_name: SCHEMA.to_owned(),
+                                    },
+                                },
+                            ]],
+                        },
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        let results = threads
+            .into_iter()
+            .map(|thread| thread.join().expect("no panics"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            results
+                .iter()
+                .filter(|result| result == &&Ok(ExecutionOutcome::SchemaCreated))
+                .count(),
+            1
+        );
+        assert_eq!(
+            results
+                .iter()
+                .filter(|result| result == &&Err(ExecutionError::SchemaAlreadyExists(SCHEMA.to_owned())))
+                .count(),
+            7
+        );
+    }
+
+    #[test]
+    fn add_column_to_nonexistent_table() {
+        let data_manager = Arc::new(DatabaseHandle::in_memory());
+        data_manager.create_schema(SCHEMA).expect("schema created");
+        let executor = SystemSchemaExecutor::new(data_manager);
+
+        assert_eq!(
+            executor.execute(
+                &SchemaChange::AddColumn(AddColumnQuery {
+                    table_info: TableInfo::new(0, &SCHEMA, &TABLE),
+                    column_def: ColumnInfo {
+                        name: "col_2".to_owned(),
+                        sql_type: SqlType::Integer,
+                        collation: None,
+                    },
+                }),
+                &SystemOperation {
+                    kind: Kind::Alter(SystemObject::Table),
+                    skip_steps_if: None,
+                    steps: vec![],
+                }
+            ),
+            Err(ExecutionError::TableDoesNotExists(SCHEMA.to_owned(), TABLE.to_owned()))
+        );
+    }
+
+    #[test]
+    fn add_column_to_table_with_rows() {
+        let data_manager = Arc::new(DatabaseHandle::in_memory());
+        let schema_id = data_manager.create_schema(SCHEMA).expect("schema created");
+        let table_id = data_manager
+            .create_table(schema_id, TABLE, &[ColumnDefinition::new("col_1", SqlType::SmallInt)])
+            .expect("table created");
+        data_manager
+            .write_into(
+                &(schema_id, table_id),
+                vec![(Binary::pack(&[Datum::from_u64(0)]), Binary::pack(&[Datum::from_i16(1)]))],
+            )
+            .expect("row is written");
+        let executor = SystemSchemaExecutor::new(data_manager.clone());
+
+        assert_eq!(
+            executor.execute(
+                &SchemaChange::AddColumn(AddColumnQuery {
+                    table_info: TableInfo::new(schema_id, &SCHEMA, &TABLE),
+                    column_def: ColumnInfo {
+                        name: "col_2".to_owned(),
+                        sql_type: SqlType::Integer,
+                        collation: None,
+                    },
+                }),
+                &SystemOperation {
+                    kind: Kind::Alter(SystemObject::Table),
+                    skip_steps_if: None,
+                    steps: vec![],
+                }
+            ),
+            Ok(ExecutionOutcome::TableAltered)
+        );
+        assert_eq!(
+            data_manager
+                .table_columns(&(schema_id, table_id))
+                .expect("table exists")
+                .into_iter()
+                .map(|(_id, column)| column)
+                .collect::<Vec<ColumnDefinition>>(),
+            vec![
+                ColumnDefinition::new("col_1", SqlType::SmallInt),
+                ColumnDefinition::new("col_2", SqlType::Integer)
+            ]
+        );
+        assert_eq!(
+            data_manager
+                .full_scan(&(schema_id, table_id))
+                .expect("table exists")
+                .map(Result::unwrap)
+                .map(Result::unwrap)
+                .map(|(_key, values)| values)
+                .collect::<Vec<Values>>(),
+            vec![Binary::pack(&[Datum::from_i16(1), Datum::from_null()])]
+        );
+    }
 }
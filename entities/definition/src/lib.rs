@@ -17,7 +17,7 @@ use std::{
     convert::TryFrom,
     fmt::{self, Display, Formatter},
 };
-use types::SqlType;
+use types::{Collation, SqlType};
 
 #[derive(Debug, PartialEq)]
 pub struct FullTableName((String, String));
@@ -64,9 +64,9 @@ impl<'o> TryFrom<&'o sql_ast::ObjectName> for FullTableName {
         } else if object.0.len() != 2 {
             Err(TableNamingError::NotProcessed(object.to_string()))
         } else {
-            let table_name = object.0.last().unwrap().value.clone();
-            let schema_name = object.0.first().unwrap().value.clone();
-            Ok(FullTableName((schema_name.to_lowercase(), table_name.to_lowercase())))
+            let table_name = sql_ast::ident_name(object.0.last().unwrap());
+            let schema_name = sql_ast::ident_name(object.0.first().unwrap());
+            Ok(FullTableName((schema_name, table_name)))
         }
     }
 }
@@ -106,7 +106,7 @@ impl<'o> TryFrom<&'o sql_ast::ObjectName> for SchemaName {
         if object.0.len() != 1 {
             Err(SchemaNamingError(object.to_string()))
         } else {
-            Ok(SchemaName(object.to_string().to_lowercase()))
+            Ok(SchemaName(sql_ast::ident_name(&object.0[0])))
         }
     }
 }
@@ -130,20 +130,26 @@ pub struct ColumnDef {
     name: String,
     sql_type: SqlType,
     ord_num: usize,
+    collation: Option<Collation>,
 }
 
 impl ColumnDef {
-    pub fn new(name: String, sql_type: SqlType, ord_num: usize) -> ColumnDef {
+    pub fn new(name: String, sql_type: SqlType, ord_num: usize, collation: Option<Collation>) -> ColumnDef {
         ColumnDef {
             name,
             sql_type,
             ord_num,
+            collation,
         }
     }
 
     pub fn sql_type(&self) -> SqlType {
         self.sql_type
     }
+
+    pub fn collation(&self) -> Option<Collation> {
+        self.collation
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,120 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+};
+
+/// defines how values of string columns are compared and ordered
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Collation {
+    /// byte-wise order of UTF-8 encoded strings, the same as `"C"` collation
+    /// of PostgreSQL
+    C,
+    /// case-insensitive order of characters, lower case goes first when strings
+    /// differ only by case. Accents are compared as different characters
+    Unicode,
+}
+
+impl Default for Collation {
+    fn default() -> Collation {
+        Collation::C
+    }
+}
+
+impl Collation {
+    /// finds collation by one of names PostgreSQL knows it
+    pub fn from_name(name: &str) -> Option<Collation> {
+        match name {
+            "C" | "POSIX" | "ucs_basic" => Some(Collation::C),
+            "unicode" | "und-x-icu" => Some(Collation::Unicode),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Collation::C => "C",
+            Collation::Unicode => "unicode",
+        }
+    }
+
+    pub fn compare(&self, left: &str, right: &str) -> Ordering {
+        match self {
+            Collation::C => left.as_bytes().cmp(right.as_bytes()),
+            Collation::Unicode => left
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(right.chars().flat_map(char::to_lowercase))
+                .then_with(|| {
+                    left.chars()
+                        .map(char::is_uppercase)
+                        .cmp(right.chars().map(char::is_uppercase))
+                })
+                .then_with(|| left.cmp(right)),
+        }
+    }
+}
+
+impl Display for Collation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(collation: Collation, mut strings: Vec<&'static str>) -> Vec<&'static str> {
+        strings.sort_by(|left, right| collation.compare(left, right));
+        strings
+    }
+
+    #[test]
+    fn c_collation_orders_by_bytes() {
+        assert_eq!(
+            sorted(Collation::C, vec!["b", "a", "B", "ä", "A"]),
+            vec!["A", "B", "a", "b", "ä"]
+        );
+    }
+
+    #[test]
+    fn unicode_collation_ignores_case() {
+        assert_eq!(
+            sorted(Collation::Unicode, vec!["b", "A", "B", "a", "ab"]),
+            vec!["a", "A", "ab", "b", "B"]
+        );
+    }
+
+    #[test]
+    fn unicode_collation_orders_multi_byte_characters() {
+        assert_eq!(sorted(Collation::Unicode, vec!["Я", "б", "а"]), vec!["а", "б", "Я"]);
+    }
+
+    #[test]
+    fn equal_strings() {
+        assert_eq!(Collation::Unicode.compare("Straße", "Straße"), Ordering::Equal);
+        assert_eq!(Collation::C.compare("Straße", "Straße"), Ordering::Equal);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Collation::from_name("C"), Some(Collation::C));
+        assert_eq!(Collation::from_name("POSIX"), Some(Collation::C));
+        assert_eq!(Collation::from_name("und-x-icu"), Some(Collation::Unicode));
+        assert_eq!(Collation::from_name("en_US"), None);
+    }
+}
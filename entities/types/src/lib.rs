@@ -19,6 +19,10 @@ use std::{
     fmt::{self, Display, Formatter},
 };
 
+mod collation;
+
+pub use collation::Collation;
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum GeneralType {
     String,
@@ -26,7 +26,7 @@ pub struct ColumnDefinition {
 impl ColumnDefinition {
     pub fn new(name: &str, sql_type: SqlType) -> Self {
         Self {
-            name: name.to_lowercase(),
+            name: name.to_owned(),
             sql_type,
         }
     }
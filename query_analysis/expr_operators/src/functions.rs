@@ -0,0 +1,354 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+use types::GeneralType;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FunctionKind {
+    Scalar,
+    Aggregate,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionSignature {
+    name: String,
+    kind: FunctionKind,
+    arg_types: Vec<GeneralType>,
+    return_type: GeneralType,
+}
+
+impl FunctionSignature {
+    pub fn new<N: ToString>(
+        name: N,
+        kind: FunctionKind,
+        arg_types: Vec<GeneralType>,
+        return_type: GeneralType,
+    ) -> FunctionSignature {
+        FunctionSignature {
+            name: name.to_string(),
+            kind,
+            arg_types,
+            return_type,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn kind(&self) -> FunctionKind {
+        self.kind
+    }
+
+    pub fn arg_types(&self) -> &[GeneralType] {
+        &self.arg_types
+    }
+
+    pub fn return_type(&self) -> GeneralType {
+        self.return_type
+    }
+
+    /// argument type `None` is unknown (e.g. `NULL` literal or a parameter)
+    /// and matches any type of the signature
+    fn accepts(&self, arg_types: &[Option<GeneralType>]) -> bool {
+        self.arg_types.len() == arg_types.len()
+            && self
+                .arg_types
+                .iter()
+                .zip(arg_types.iter())
+                .all(|(expected, actual)| actual.map(|actual| actual == *expected).unwrap_or(true))
+    }
+}
+
+impl Display for FunctionSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}({})",
+            self.name,
+            self.arg_types
+                .iter()
+                .map(|arg_type| type_name(Some(*arg_type)))
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    }
+}
+
+/// formats function call the same way as `FunctionSignature` is displayed
+pub fn call_signature(name: &str, arg_types: &[Option<GeneralType>]) -> String {
+    format!(
+        "{}({})",
+        name,
+        arg_types
+            .iter()
+            .map(|arg_type| type_name(*arg_type))
+            .collect::<Vec<&str>>()
+            .join(", ")
+    )
+}
+
+fn type_name(general_type: Option<GeneralType>) -> &'static str {
+    match general_type {
+        Some(GeneralType::String) => "string",
+        Some(GeneralType::Number) => "number",
+        Some(GeneralType::Bool) => "bool",
+        None => "unknown",
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct FunctionDefinition {
+    signature: FunctionSignature,
+    sql_body: Option<sql_ast::Expr>,
+}
+
+/// schema of built-in functions
+pub const PG_CATALOG: &str = "pg_catalog";
+/// schema of functions registered with `register_sql`
+pub const PUBLIC_SCHEMA: &str = "public";
+/// schemas that are searched for functions called without a schema name
+const SEARCH_PATH: [&str; 2] = [PG_CATALOG, PUBLIC_SCHEMA];
+
+#[derive(Debug, Default)]
+pub struct FunctionCatalog {
+    functions: HashMap<(String, String), Vec<FunctionDefinition>>,
+}
+
+impl FunctionCatalog {
+    pub fn builtin() -> FunctionCatalog {
+        let mut catalog = FunctionCatalog::default();
+        let all_types = [GeneralType::String, GeneralType::Number, GeneralType::Bool];
+
+        catalog.register(FunctionSignature::new(
+            "count",
+            FunctionKind::Aggregate,
+            vec![],
+            GeneralType::Number,
+        ));
+        for arg_type in all_types.iter() {
+            catalog.register(FunctionSignature::new(
+                "count",
+                FunctionKind::Aggregate,
+                vec![*arg_type],
+                GeneralType::Number,
+            ));
+            catalog.register(FunctionSignature::new(
+                "min",
+                FunctionKind::Aggregate,
+                vec![*arg_type],
+                *arg_type,
+            ));
+            catalog.register(FunctionSignature::new(
+                "max",
+                FunctionKind::Aggregate,
+                vec![*arg_type],
+                *arg_type,
+            ));
+        }
+        for name in &["sum", "avg"] {
+            catalog.register(FunctionSignature::new(
+                name,
+                FunctionKind::Aggregate,
+                vec![GeneralType::Number],
+                GeneralType::Number,
+            ));
+        }
+        // TODO: approx_count_distinct and approx_percentile backed by sketches
+        //       need an aggregation operator in the executor, there is none yet
+
+        catalog.register(FunctionSignature::new(
+            "abs",
+            FunctionKind::Scalar,
+            vec![GeneralType::Number],
+            GeneralType::Number,
+        ));
+        catalog.register(FunctionSignature::new(
+            "length",
+            FunctionKind::Scalar,
+            vec![GeneralType::String],
+            GeneralType::Number,
+        ));
+        for name in &["lower", "upper"] {
+            catalog.register(FunctionSignature::new(
+                name,
+                FunctionKind::Scalar,
+                vec![GeneralType::String],
+                GeneralType::String,
+            ));
+        }
+        // third argument of `regexp_matches` and fourth of `regexp_replace` are flags
+        for arg_count in 3..=4 {
+            catalog.register(FunctionSignature::new(
+                "regexp_replace",
+                FunctionKind::Scalar,
+                vec![GeneralType::String; arg_count],
+                GeneralType::String,
+            ));
+        }
+        for arg_count in 2..=3 {
+            catalog.register(FunctionSignature::new(
+                "regexp_matches",
+                FunctionKind::Scalar,
+                vec![GeneralType::String; arg_count],
+                GeneralType::String,
+            ));
+        }
+        // values of these come from node configuration and session state
+        for name in &["version", "current_user", "current_database", "current_schema"] {
+            catalog.register(FunctionSignature::new(
+                name,
+                FunctionKind::Scalar,
+                vec![],
+                GeneralType::String,
+            ));
+        }
+        catalog.register(FunctionSignature::new(
+            "random",
+            FunctionKind::Scalar,
+            vec![],
+            GeneralType::Number,
+        ));
+        catalog.register(FunctionSignature::new(
+            "setseed",
+            FunctionKind::Scalar,
+            vec![GeneralType::Number],
+            GeneralType::String,
+        ));
+        catalog.register(FunctionSignature::new(
+            "current_setting",
+            FunctionKind::Scalar,
+            vec![GeneralType::String],
+            GeneralType::String,
+        ));
+        catalog.register(FunctionSignature::new(
+            "current_setting",
+            FunctionKind::Scalar,
+            vec![GeneralType::String, GeneralType::Bool],
+            GeneralType::String,
+        ));
+        catalog.register(FunctionSignature::new(
+            "set_config",
+            FunctionKind::Scalar,
+            vec![GeneralType::String, GeneralType::String, GeneralType::Bool],
+            GeneralType::String,
+        ));
+        // `pg_sleep` returns nothing, like `setseed`
+        catalog.register(FunctionSignature::new(
+            "pg_sleep",
+            FunctionKind::Scalar,
+            vec![GeneralType::Number],
+            GeneralType::String,
+        ));
+        catalog.register(FunctionSignature::new(
+            "pg_backend_pid",
+            FunctionKind::Scalar,
+            vec![],
+            GeneralType::Number,
+        ));
+        for name in &["pg_cancel_backend", "pg_terminate_backend"] {
+            catalog.register(FunctionSignature::new(
+                name,
+                FunctionKind::Scalar,
+                vec![GeneralType::Number],
+                GeneralType::Bool,
+            ));
+        }
+        // TODO: currval(regclass) and lastval() report values a session got from
+        //       user sequences, there are no CREATE SEQUENCE or serial columns yet,
+        //       only internal sequences of object and record ids
+        // TODO: txid_current() and xmin/xmax system columns need transaction ids
+        //       stamped on row versions, there is no MVCC and every statement
+        //       writes rows in place without a transaction
+        catalog
+    }
+
+    pub fn register(&mut self, signature: FunctionSignature) {
+        self.functions
+            .entry((PG_CATALOG.to_owned(), signature.name.clone()))
+            .or_default()
+            .push(FunctionDefinition {
+                signature,
+                sql_body: None,
+            });
+    }
+
+    /// registers a function in the `public` schema defined by a single SQL
+    /// expression that refers to its arguments as `$1`, `$2` and so on.
+    /// Returns `false` if a function with the same name and argument types is
+    /// already visible without a schema name
+    pub fn register_sql(&mut self, signature: FunctionSignature, body: sql_ast::Expr) -> bool {
+        if self
+            .visible(None, signature.name())
+            .into_iter()
+            .any(|definition| definition.signature.arg_types == signature.arg_types)
+        {
+            return false;
+        }
+        let definitions = self
+            .functions
+            .entry((PUBLIC_SCHEMA.to_owned(), signature.name.clone()))
+            .or_default();
+        definitions.push(FunctionDefinition {
+            signature,
+            sql_body: Some(body),
+        });
+        true
+    }
+
+    /// body of the function if it was registered with `register_sql`
+    pub fn sql_body(&self, signature: &FunctionSignature) -> Option<&sql_ast::Expr> {
+        self.functions
+            .get(&(PUBLIC_SCHEMA.to_owned(), signature.name.clone()))
+            .and_then(|definitions| definitions.iter().find(|definition| &definition.signature == signature))
+            .and_then(|definition| definition.sql_body.as_ref())
+    }
+
+    /// returns the first signature that accepts the argument types or, if
+    /// there is none, all signatures registered under the name as candidates.
+    /// Names are compared as is, callers normalize identifiers. Without a
+    /// schema name `pg_catalog` is searched first and then `public`
+    pub fn resolve(
+        &self,
+        schema: Option<&str>,
+        name: &str,
+        arg_types: &[Option<GeneralType>],
+    ) -> Result<&FunctionSignature, Vec<&FunctionSignature>> {
+        let signatures = self
+            .visible(schema, name)
+            .into_iter()
+            .map(|definition| &definition.signature)
+            .collect::<Vec<&FunctionSignature>>();
+        match signatures.iter().find(|signature| signature.accepts(arg_types)) {
+            Some(signature) => Ok(signature),
+            None => Err(signatures),
+        }
+    }
+
+    fn visible(&self, schema: Option<&str>, name: &str) -> Vec<&FunctionDefinition> {
+        let schemas = match schema {
+            Some(schema) => vec![schema],
+            None => SEARCH_PATH.to_vec(),
+        };
+        schemas
+            .into_iter()
+            .filter_map(|schema| self.functions.get(&(schema.to_owned(), name.to_owned())))
+            .flatten()
+            .collect()
+    }
+}
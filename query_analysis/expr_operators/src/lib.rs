@@ -19,6 +19,10 @@ use std::{
 };
 use types::{GeneralType, SqlType};
 
+pub use functions::{call_signature, FunctionCatalog, FunctionKind, FunctionSignature, PG_CATALOG, PUBLIC_SCHEMA};
+
+mod functions;
+
 #[derive(Debug, PartialEq)]
 pub enum Arithmetic {
     Add,
@@ -58,6 +62,14 @@ pub enum Logical {
 pub enum PatternMatching {
     Like,
     NotLike,
+    /// `~` and `~*` operators
+    Regex {
+        case_insensitive: bool,
+    },
+    /// `!~` and `!~*` operators
+    NotRegex {
+        case_insensitive: bool,
+    },
 }
 
 #[derive(Debug, PartialEq)]
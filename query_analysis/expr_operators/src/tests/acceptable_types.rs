@@ -65,3 +65,11 @@ fn pattern_matching() {
         vec![(GeneralType::String, GeneralType::String)]
     );
 }
+
+#[test]
+fn regex_matching() {
+    assert_eq!(
+        Operation::PatternMatching(PatternMatching::Regex { case_insensitive: true }).acceptable_operand_types(),
+        vec![(GeneralType::String, GeneralType::String)]
+    );
+}
@@ -0,0 +1,374 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+#[test]
+fn resolve_builtin_function() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog.resolve(None, "abs", &[Some(GeneralType::Number)]),
+        Ok(&FunctionSignature::new(
+            "abs",
+            FunctionKind::Scalar,
+            vec![GeneralType::Number],
+            GeneralType::Number
+        ))
+    );
+}
+
+#[test]
+fn names_are_compared_as_is() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog.resolve(None, "UPPER", &[Some(GeneralType::String)]),
+        Err(vec![])
+    );
+}
+
+#[test]
+fn resolve_in_schema() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog
+            .resolve(Some(PG_CATALOG), "upper", &[Some(GeneralType::String)])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::String)
+    );
+    assert_eq!(
+        catalog.resolve(Some(PUBLIC_SCHEMA), "upper", &[Some(GeneralType::String)]),
+        Err(vec![])
+    );
+    assert_eq!(
+        catalog.resolve(Some("schema_name"), "upper", &[Some(GeneralType::String)]),
+        Err(vec![])
+    );
+}
+
+#[test]
+fn resolve_overloaded_function() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog
+            .resolve(None, "max", &[Some(GeneralType::String)])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::String)
+    );
+    assert_eq!(
+        catalog
+            .resolve(None, "max", &[Some(GeneralType::Number)])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::Number)
+    );
+}
+
+#[test]
+fn unknown_argument_type_matches_any() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog.resolve(None, "length", &[None]).map(FunctionSignature::kind),
+        Ok(FunctionKind::Scalar)
+    );
+}
+
+#[test]
+fn session_information_functions() {
+    let catalog = FunctionCatalog::builtin();
+
+    for name in &["version", "current_user", "current_database", "current_schema"] {
+        assert_eq!(
+            catalog.resolve(None, name, &[]).map(FunctionSignature::return_type),
+            Ok(GeneralType::String)
+        );
+    }
+}
+
+#[test]
+fn setting_functions() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog
+            .resolve(None, "current_setting", &[Some(GeneralType::String)])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::String)
+    );
+    assert_eq!(
+        catalog
+            .resolve(
+                None,
+                "current_setting",
+                &[Some(GeneralType::String), Some(GeneralType::Bool)]
+            )
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::String)
+    );
+    assert_eq!(
+        catalog
+            .resolve(
+                None,
+                "set_config",
+                &[
+                    Some(GeneralType::String),
+                    Some(GeneralType::String),
+                    Some(GeneralType::Bool)
+                ]
+            )
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::String)
+    );
+}
+
+#[test]
+fn random_functions() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog.resolve(None, "random", &[]).map(FunctionSignature::return_type),
+        Ok(GeneralType::Number)
+    );
+    assert_eq!(
+        catalog
+            .resolve(None, "setseed", &[Some(GeneralType::Number)])
+            .map(FunctionSignature::kind),
+        Ok(FunctionKind::Scalar)
+    );
+}
+
+#[test]
+fn administration_functions() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog
+            .resolve(None, "pg_backend_pid", &[])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::Number)
+    );
+    assert_eq!(
+        catalog
+            .resolve(None, "pg_sleep", &[Some(GeneralType::Number)])
+            .map(FunctionSignature::kind),
+        Ok(FunctionKind::Scalar)
+    );
+    for name in &["pg_cancel_backend", "pg_terminate_backend"] {
+        assert_eq!(
+            catalog
+                .resolve(None, name, &[Some(GeneralType::Number)])
+                .map(FunctionSignature::return_type),
+            Ok(GeneralType::Bool)
+        );
+    }
+}
+
+#[test]
+fn regular_expression_functions() {
+    let catalog = FunctionCatalog::builtin();
+    let string = Some(GeneralType::String);
+
+    assert_eq!(
+        catalog
+            .resolve(None, "regexp_replace", &[string, string, string])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::String)
+    );
+    assert_eq!(
+        catalog
+            .resolve(None, "regexp_replace", &[string, string, string, string])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::String)
+    );
+    assert_eq!(
+        catalog
+            .resolve(None, "regexp_matches", &[string, string])
+            .map(FunctionSignature::kind),
+        Ok(FunctionKind::Scalar)
+    );
+    assert_eq!(
+        catalog
+            .resolve(None, "regexp_matches", &[string, string, string])
+            .map(FunctionSignature::kind),
+        Ok(FunctionKind::Scalar)
+    );
+}
+
+#[test]
+fn undefined_function() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(catalog.resolve(None, "not_a_function", &[]), Err(vec![]));
+}
+
+#[test]
+fn wrong_argument_types_returns_candidates() {
+    let catalog = FunctionCatalog::builtin();
+
+    assert_eq!(
+        catalog.resolve(None, "sum", &[Some(GeneralType::String)]),
+        Err(vec![&FunctionSignature::new(
+            "sum",
+            FunctionKind::Aggregate,
+            vec![GeneralType::Number],
+            GeneralType::Number
+        )])
+    );
+}
+
+#[test]
+fn register_function() {
+    let mut catalog = FunctionCatalog::default();
+    catalog.register(FunctionSignature::new(
+        "is_empty",
+        FunctionKind::Scalar,
+        vec![GeneralType::String],
+        GeneralType::Bool,
+    ));
+
+    assert_eq!(
+        catalog
+            .resolve(None, "is_empty", &[Some(GeneralType::String)])
+            .map(FunctionSignature::return_type),
+        Ok(GeneralType::Bool)
+    );
+}
+
+#[test]
+fn display_signatures() {
+    assert_eq!(
+        FunctionSignature::new(
+            "f",
+            FunctionKind::Scalar,
+            vec![GeneralType::Number, GeneralType::String],
+            GeneralType::Bool
+        )
+        .to_string(),
+        "f(number, string)"
+    );
+    assert_eq!(
+        call_signature("f", &[Some(GeneralType::Bool), None]),
+        "f(bool, unknown)"
+    );
+}
+
+#[cfg(test)]
+mod sql_functions {
+    use super::*;
+
+    fn add_one() -> FunctionSignature {
+        FunctionSignature::new(
+            "add_one",
+            FunctionKind::Scalar,
+            vec![GeneralType::Number],
+            GeneralType::Number,
+        )
+    }
+
+    fn body() -> sql_ast::Expr {
+        sql_ast::Expr::BinaryOp {
+            left: Box::new(sql_ast::Expr::Identifier(sql_ast::Ident {
+                value: "$1".to_owned(),
+                quote_style: None,
+            })),
+            op: sql_ast::BinaryOperator::Plus,
+            right: Box::new(sql_ast::Expr::Value(sql_ast::Value::Number(BigDecimal::from(1)))),
+        }
+    }
+
+    #[test]
+    fn register_and_resolve() {
+        let mut catalog = FunctionCatalog::builtin();
+
+        assert!(catalog.register_sql(add_one(), body()));
+        assert_eq!(
+            catalog.resolve(None, "add_one", &[Some(GeneralType::Number)]),
+            Ok(&add_one())
+        );
+        assert_eq!(catalog.sql_body(&add_one()), Some(&body()));
+    }
+
+    #[test]
+    fn registered_in_public_schema() {
+        let mut catalog = FunctionCatalog::builtin();
+
+        assert!(catalog.register_sql(add_one(), body()));
+        assert_eq!(
+            catalog.resolve(Some(PUBLIC_SCHEMA), "add_one", &[Some(GeneralType::Number)]),
+            Ok(&add_one())
+        );
+        assert_eq!(
+            catalog.resolve(Some(PG_CATALOG), "add_one", &[Some(GeneralType::Number)]),
+            Err(vec![])
+        );
+    }
+
+    #[test]
+    fn builtin_functions_do_not_have_body() {
+        let catalog = FunctionCatalog::builtin();
+
+        assert_eq!(
+            catalog.sql_body(&FunctionSignature::new(
+                "abs",
+                FunctionKind::Scalar,
+                vec![GeneralType::Number],
+                GeneralType::Number
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn register_function_with_same_argument_types_twice() {
+        let mut catalog = FunctionCatalog::builtin();
+
+        assert!(catalog.register_sql(add_one(), body()));
+        assert!(!catalog.register_sql(add_one(), body()));
+    }
+
+    #[test]
+    fn overload_builtin_function() {
+        let mut catalog = FunctionCatalog::builtin();
+        let signature = FunctionSignature::new(
+            "abs",
+            FunctionKind::Scalar,
+            vec![GeneralType::String],
+            GeneralType::Number,
+        );
+
+        assert!(catalog.register_sql(signature.clone(), body()));
+        assert_eq!(
+            catalog.resolve(None, "abs", &[Some(GeneralType::String)]),
+            Ok(&signature)
+        );
+    }
+
+    #[test]
+    fn can_not_redefine_builtin_function() {
+        let mut catalog = FunctionCatalog::builtin();
+
+        assert!(!catalog.register_sql(
+            FunctionSignature::new(
+                "abs",
+                FunctionKind::Scalar,
+                vec![GeneralType::Number],
+                GeneralType::Number
+            ),
+            body()
+        ));
+    }
+}
@@ -19,4 +19,6 @@ mod acceptable_types;
 #[cfg(test)]
 mod bool_parser;
 #[cfg(test)]
+mod functions;
+#[cfg(test)]
 mod result_types;
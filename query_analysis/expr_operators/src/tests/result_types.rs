@@ -52,3 +52,14 @@ fn pattern_matching() {
         GeneralType::Bool
     );
 }
+
+#[test]
+fn regex_matching() {
+    assert_eq!(
+        Operation::PatternMatching(PatternMatching::NotRegex {
+            case_insensitive: false
+        })
+        .result_type(),
+        GeneralType::Bool
+    );
+}
@@ -0,0 +1,99 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::parse_param_index;
+use analysis_tree::{AnalysisError, AnalysisResult};
+use meta_def::ColumnDefinition;
+use std::collections::BTreeMap;
+use types::SqlType;
+
+/// collects `$n` parameters of a statement with types of columns they are
+/// assigned to or compared with
+#[derive(Default)]
+pub(crate) struct ParamTypes(BTreeMap<usize, Option<SqlType>>);
+
+impl ParamTypes {
+    /// parameters in the expression are typed by the column it is assigned to
+    pub(crate) fn assigned(&mut self, expr: &sql_ast::Expr, sql_type: SqlType) -> AnalysisResult<()> {
+        match expr {
+            sql_ast::Expr::Identifier(sql_ast::Ident { value, .. }) => match parse_param_index(value) {
+                Some(index) => self.bind(index, Some(sql_type)),
+                None => Ok(()),
+            },
+            sql_ast::Expr::BinaryOp { left, right, .. } => {
+                self.assigned(left, sql_type)?;
+                self.assigned(right, sql_type)
+            }
+            sql_ast::Expr::UnaryOp { expr, .. } | sql_ast::Expr::Nested(expr) => self.assigned(expr, sql_type),
+            _ => Ok(()),
+        }
+    }
+
+    /// parameters compared with a column in the predicate are typed by the
+    /// column, others are left untyped
+    pub(crate) fn compared(&mut self, predicate: &sql_ast::Expr, columns: &[ColumnDefinition]) -> AnalysisResult<()> {
+        match predicate {
+            sql_ast::Expr::BinaryOp { left, right, .. } => {
+                if let (sql_ast::Expr::Identifier(left), sql_ast::Expr::Identifier(right)) = (&**left, &**right) {
+                    let param = match (parse_param_index(&left.value), parse_param_index(&right.value)) {
+                        (Some(index), None) => Some((index, right)),
+                        (None, Some(index)) => Some((index, left)),
+                        _ => None,
+                    };
+                    if let Some((index, column)) = param {
+                        return self.bind(index, Some(column_type(columns, &sql_ast::ident_name(column))?));
+                    }
+                }
+                self.compared(left, columns)?;
+                self.compared(right, columns)
+            }
+            sql_ast::Expr::UnaryOp { expr, .. } | sql_ast::Expr::Nested(expr) => self.compared(expr, columns),
+            sql_ast::Expr::Identifier(sql_ast::Ident { value, .. }) => match parse_param_index(value) {
+                Some(index) => self.bind(index, None),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn bind(&mut self, index: usize, sql_type: Option<SqlType>) -> AnalysisResult<()> {
+        match (self.0.get(&index), sql_type) {
+            (Some(Some(bound)), Some(sql_type)) if *bound != sql_type => Err(AnalysisError::syntax_error(format!(
+                "Parameter ${} cannot be bound to different SQL types",
+                index + 1
+            ))),
+            (Some(Some(_)), _) => Ok(()),
+            _ => {
+                self.0.insert(index, sql_type);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<ParamTypes> for Vec<Option<SqlType>> {
+    fn from(params: ParamTypes) -> Vec<Option<SqlType>> {
+        let ParamTypes(params) = params;
+        let count = params.keys().max().map_or(0, |max_index| max_index + 1);
+        (0..count).map(|index| params.get(&index).copied().flatten()).collect()
+    }
+}
+
+pub(crate) fn column_type(columns: &[ColumnDefinition], column_name: &str) -> AnalysisResult<SqlType> {
+    columns
+        .iter()
+        .find(|column| column.has_name(column_name))
+        .map(ColumnDefinition::sql_type)
+        .ok_or_else(|| AnalysisError::column_not_found(column_name))
+}
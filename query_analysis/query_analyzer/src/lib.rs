@@ -13,21 +13,25 @@
 // limitations under the License.
 
 use crate::{
-    insert_tree_builder::InsertTreeBuilder, projection_tree_builder::ProjectionTreeBuilder,
+    description::{column_type, ParamTypes},
+    insert_tree_builder::InsertTreeBuilder,
+    projection_tree_builder::ProjectionTreeBuilder,
     update_tree_builder::UpdateTreeBuilder,
 };
 use analysis_tree::{
-    AnalysisError, ColumnInfo, CreateSchemaQuery, CreateTableQuery, DeleteQuery, DropSchemasQuery, DropTablesQuery,
-    Feature, FullTableId, InsertQuery, ProjectionTreeNode, QueryAnalysis, SchemaChange, SelectQuery, TableInfo,
-    UpdateQuery, Write,
+    AddColumnQuery, AnalysisError, AnalysisResult, ColumnInfo, CreateSchemaQuery, CreateTableQuery, DeleteQuery,
+    DropSchemasQuery, DropTablesQuery, EvaluationQuery, Feature, FullTableId, InsertQuery, ProjectionTreeNode,
+    QueryAnalysis, SchemaChange, SelectQuery, StatementDescription, TableInfo, UpdateQuery, Write,
 };
 use catalog::CatalogDefinition;
 use data_manager::DataDefReader;
 use definition::{FullTableName, SchemaName};
-use expr_operators::Operator;
+use expr_operators::{FunctionCatalog, FunctionSignature, Operator};
+use meta_def::ColumnDefinition;
 use std::{convert::TryFrom, sync::Arc};
-use types::SqlType;
+use types::{Collation, GeneralType, SqlType};
 
+mod description;
 mod insert_tree_builder;
 mod operation_mapper;
 mod projection_tree_builder;
@@ -36,6 +40,7 @@ mod update_tree_builder;
 pub struct Analyzer<CD: CatalogDefinition> {
     data_definition: Arc<dyn DataDefReader>,
     database: Arc<CD>,
+    functions: FunctionCatalog,
 }
 
 impl<CD: CatalogDefinition> Analyzer<CD> {
@@ -43,11 +48,22 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
         Analyzer {
             data_definition,
             database,
+            functions: FunctionCatalog::builtin(),
         }
     }
 
+    /// registers SQL function that is inlined at its call sites, returns
+    /// `false` if function with the same signature already exists.
+    /// `CREATE FUNCTION ... LANGUAGE SQL` is not yet recognized by the parser
+    pub fn register_sql_function(&mut self, signature: FunctionSignature, body: sql_ast::Expr) -> bool {
+        self.functions.register_sql(signature, body)
+    }
+
     pub fn analyze(&self, statement: sql_ast::Statement) -> Result<QueryAnalysis, AnalysisError> {
+        check_created_names(&statement)?;
         match &statement {
+            // TODO: `INSERT ... ON CONFLICT` could be analyzed only when catalog keeps unique constraints
+            //       and the parser understands the clause; neither is there yet
             sql_ast::Statement::Insert {
                 table_name,
                 source,
@@ -59,8 +75,9 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
                     Some(None) => Err(AnalysisError::table_does_not_exist(full_table_name)),
                     Some(Some(table_info)) => {
                         for column in columns.iter() {
-                            if !table_info.has_column(&column.to_string()) {
-                                return Err(AnalysisError::column_not_found(column));
+                            let column_name = sql_ast::ident_name(column);
+                            if !table_info.has_column(&column_name) {
+                                return Err(AnalysisError::column_not_found(column_name));
                             }
                         }
                         let column_types: Vec<SqlType> =
@@ -108,7 +125,7 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
                         let mut assignments = vec![];
                         for assignment in stmt_assignments {
                             let sql_ast::Assignment { id, value } = assignment;
-                            let name = id.to_string();
+                            let name = sql_ast::ident_name(id);
                             let mut found = None;
                             for table_column in &table_columns {
                                 if table_column.has_name(&name) {
@@ -141,6 +158,9 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
             sql_ast::Statement::Query(query) => {
                 let sql_ast::Query { body, .. } = &**query;
                 match body {
+                    sql_ast::SetExpr::Select(select) if select.from.is_empty() => {
+                        self.evaluation(query, select, &statement)
+                    }
                     sql_ast::SetExpr::Query(_) => Err(AnalysisError::feature_not_supported(Feature::SubQueries)),
                     sql_ast::SetExpr::SetOperation { .. } => {
                         Err(AnalysisError::feature_not_supported(Feature::SetOperations))
@@ -167,6 +187,10 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
                                 return Err(AnalysisError::feature_not_supported(Feature::NestedJoin))
                             }
                         };
+                        // TODO: row level security policies would add their USING predicates to
+                        //       the filter of a scan here. sqlparser has no CREATE POLICY, catalog
+                        //       keeps no policies and there are no roles to enable RLS for, only a
+                        //       user name sent by a client on connection
                         match FullTableName::try_from(name) {
                             Ok(full_table_name) => match self.data_definition.table_desc((&full_table_name).into()) {
                                 None => Err(AnalysisError::schema_does_not_exist(full_table_name.schema())),
@@ -190,6 +214,7 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
                                                     &statement,
                                                     &SqlType::SmallInt,
                                                     &table_columns,
+                                                    &self.functions,
                                                 )?)
                                             }
                                             sql_ast::SelectItem::ExprWithAlias { .. } => {
@@ -233,11 +258,15 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
                     None => Err(AnalysisError::schema_does_not_exist(full_table_name.schema())),
                     Some(schema_id) => {
                         let mut column_defs = Vec::new();
+                        // TODO: column options are ignored. Identity columns need
+                        //       `GENERATED ... AS IDENTITY`, `OVERRIDING SYSTEM VALUE`
+                        //       and `RETURNING` support in the parser
                         for column in columns {
                             match SqlType::try_from(&column.data_type) {
                                 Ok(sql_type) => column_defs.push(ColumnInfo {
-                                    name: column.name.value.as_str().to_owned(),
+                                    name: sql_ast::ident_name(&column.name),
                                     sql_type,
+                                    collation: column_collation(&column.collation, sql_type)?,
                                 }),
                                 Err(_not_supported_type_error) => {
                                     return Err(AnalysisError::type_is_not_supported(&column.data_type));
@@ -320,17 +349,72 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
                     )))
                 }
                 sql_ast::ObjectType::View => unimplemented!("VIEWs are not implemented yet"),
+                // TODO: REINDEX and checks of an index against its table could come
+                //       together with indexes, sqlparser has no REINDEX statement
                 sql_ast::ObjectType::Index => unimplemented!("INDEXes are not implemented yet"),
             },
             sql_ast::Statement::Copy { .. } => unimplemented!(),
             sql_ast::Statement::CreateView { .. } => unimplemented!(),
             sql_ast::Statement::CreateVirtualTable { .. } => unimplemented!(),
+            // TODO: there is no secondary index storage yet. A concurrent build would
+            //       scan a snapshot of the table, catch up on writes made meanwhile
+            //       and only then mark the index valid, which needs MVCC snapshots
             sql_ast::Statement::CreateIndex { .. } => unimplemented!(),
-            sql_ast::Statement::AlterTable { .. } => unimplemented!(),
+            sql_ast::Statement::AlterTable {
+                name,
+                operation: sql_ast::AlterTableOperation::AddColumn { column_def },
+            } => match FullTableName::try_from(name) {
+                Ok(full_table_name) => match self.data_definition.table_exists_tuple((&full_table_name).into()) {
+                    None => Err(AnalysisError::schema_does_not_exist(full_table_name.schema())),
+                    Some((_schema_id, None)) => Err(AnalysisError::table_does_not_exist(&full_table_name)),
+                    Some((schema_id, Some(table_id))) => {
+                        let column_name = sql_ast::ident_name(&column_def.name);
+                        match self
+                            .data_definition
+                            .column_ids(&(schema_id, table_id), &[column_name.clone()])
+                        {
+                            Ok((found, _not_found)) if found.is_empty() => {}
+                            Ok(_) => return Err(AnalysisError::duplicate_column(column_name)),
+                            Err(()) => return Err(AnalysisError::table_does_not_exist(&full_table_name)),
+                        }
+                        let sql_type = match SqlType::try_from(&column_def.data_type) {
+                            Ok(sql_type) => sql_type,
+                            Err(_not_supported_type_error) => {
+                                return Err(AnalysisError::type_is_not_supported(&column_def.data_type))
+                            }
+                        };
+                        Ok(QueryAnalysis::DataDefinition(SchemaChange::AddColumn(AddColumnQuery {
+                            table_info: TableInfo::new(schema_id, &full_table_name.schema(), &full_table_name.table()),
+                            column_def: ColumnInfo {
+                                name: column_name,
+                                sql_type,
+                                collation: column_collation(&column_def.collation, sql_type)?,
+                            },
+                        })))
+                    }
+                },
+                Err(error) => Err(AnalysisError::table_naming_error(&error)),
+            },
+            // TODO: ADD/DROP CONSTRAINT needs table constraints first, CREATE TABLE
+            //       accepts none of PRIMARY KEY, UNIQUE, CHECK or REFERENCES and writes
+            //       do not enforce any but column types. Unique keys would also need index
+            //       storage to validate existing rows other than by a full scan.
+            //       sqlparser has no ALTER COLUMN ... TYPE ... USING operation, once it does
+            //       the USING expression (or a cast of the column) could be evaluated by
+            //       `DatabaseHandle::rewrite_table` the same way ADD COLUMN fills new column
+            sql_ast::Statement::AlterTable { .. } => {
+                Err(AnalysisError::feature_not_supported(Feature::AlterTableOperations))
+            }
             sql_ast::Statement::SetVariable { .. } => unimplemented!(),
             sql_ast::Statement::ShowVariable { .. } => unimplemented!(),
             sql_ast::Statement::ShowColumns { .. } => unimplemented!(),
+            // TODO: FOR UPDATE/FOR SHARE row locks, with NOWAIT and SKIP LOCKED, are held
+            //       until commit of a transaction, so they need transactions first. sqlparser
+            //       does not parse locking clauses of a query either
             sql_ast::Statement::StartTransaction { .. } => unimplemented!(),
+            // TODO: isolation levels need transactions that span statements, SERIALIZABLE
+            //       also needs tracking of rw-antidependencies between them to abort one with
+            //       serialization_failure (40001)
             sql_ast::Statement::SetTransaction { .. } => unimplemented!(),
             sql_ast::Statement::Commit { .. } => unimplemented!(),
             sql_ast::Statement::Rollback { .. } => unimplemented!(),
@@ -338,10 +422,290 @@ impl<CD: CatalogDefinition> Analyzer<CD> {
             sql_ast::Statement::Deallocate { .. } => unimplemented!(),
             sql_ast::Statement::Execute { .. } => unimplemented!(),
             sql_ast::Statement::Prepare { .. } => unimplemented!(),
+            // TODO: row count estimates of ANALYZE are worth keeping once the planner
+            //       chooses between access paths by cost and system catalog has views like
+            //       pg_class, EXPLAIN then needs printable plans of the new query engine
             sql_ast::Statement::Analyze { .. } => unimplemented!(),
             sql_ast::Statement::Explain { .. } => unimplemented!(),
         }
     }
+
+    /// describes parameters and result columns of a statement, it is shared
+    /// by `Describe` of extended query protocol and prepared statements of
+    /// embedded API. Statements other than DML and selects neither take
+    /// parameters nor return rows
+    pub fn describe(&self, statement: &sql_ast::Statement) -> AnalysisResult<StatementDescription> {
+        let mut params = ParamTypes::default();
+        let mut result_columns = vec![];
+        match statement {
+            sql_ast::Statement::Insert {
+                table_name,
+                columns,
+                source,
+            } => {
+                let table_columns = self.table_columns(table_name)?;
+                let column_types = if columns.is_empty() {
+                    table_columns.iter().map(ColumnDefinition::sql_type).collect()
+                } else {
+                    columns
+                        .iter()
+                        .map(|column| column_type(&table_columns, &sql_ast::ident_name(column)))
+                        .collect::<AnalysisResult<Vec<SqlType>>>()?
+                };
+                if let sql_ast::SetExpr::Values(sql_ast::Values(rows)) = &source.body {
+                    for row in rows {
+                        for (value, sql_type) in row.iter().zip(column_types.iter()) {
+                            params.assigned(value, *sql_type)?;
+                        }
+                    }
+                }
+            }
+            sql_ast::Statement::Update {
+                table_name,
+                assignments,
+                selection,
+            } => {
+                let table_columns = self.table_columns(table_name)?;
+                for sql_ast::Assignment { id, value } in assignments {
+                    params.assigned(value, column_type(&table_columns, &sql_ast::ident_name(id))?)?;
+                }
+                if let Some(predicate) = selection {
+                    params.compared(predicate, &table_columns)?;
+                }
+            }
+            sql_ast::Statement::Delete { table_name, selection } => {
+                let table_columns = self.table_columns(table_name)?;
+                if let Some(predicate) = selection {
+                    params.compared(predicate, &table_columns)?;
+                }
+            }
+            sql_ast::Statement::Query(query) => match &query.body {
+                sql_ast::SetExpr::Select(select) => {
+                    let sql_ast::Select {
+                        projection,
+                        from,
+                        selection,
+                        ..
+                    } = &**select;
+                    let relation = match from.as_slice() {
+                        [] => return Err(AnalysisError::feature_not_supported(Feature::ComputedColumns)),
+                        [sql_ast::TableWithJoins { relation, joins }] if joins.is_empty() => relation,
+                        _ => return Err(AnalysisError::feature_not_supported(Feature::Joins)),
+                    };
+                    let name = match relation {
+                        sql_ast::TableFactor::Table { name, .. } => name,
+                        sql_ast::TableFactor::Derived { .. } => {
+                            return Err(AnalysisError::feature_not_supported(Feature::FromSubQuery))
+                        }
+                        sql_ast::TableFactor::TableFunction { .. } => {
+                            return Err(AnalysisError::feature_not_supported(Feature::TableFunctions))
+                        }
+                        sql_ast::TableFactor::NestedJoin(_) => {
+                            return Err(AnalysisError::feature_not_supported(Feature::NestedJoin))
+                        }
+                    };
+                    let table_columns = self.table_columns(name)?;
+                    for item in projection {
+                        match item {
+                            sql_ast::SelectItem::Wildcard => {
+                                result_columns.extend(table_columns.iter().map(|column| ColumnInfo {
+                                    name: column.name(),
+                                    sql_type: column.sql_type(),
+                                    collation: None,
+                                }));
+                            }
+                            sql_ast::SelectItem::UnnamedExpr(sql_ast::Expr::Identifier(ident))
+                                if parse_param_index(&ident.value).is_none() =>
+                            {
+                                let name = sql_ast::ident_name(ident);
+                                let sql_type = column_type(&table_columns, &name)?;
+                                result_columns.push(ColumnInfo {
+                                    name,
+                                    sql_type,
+                                    collation: None,
+                                })
+                            }
+                            sql_ast::SelectItem::UnnamedExpr(_) => {
+                                return Err(AnalysisError::feature_not_supported(Feature::ComputedColumns))
+                            }
+                            sql_ast::SelectItem::ExprWithAlias { .. } => {
+                                return Err(AnalysisError::feature_not_supported(Feature::Aliases))
+                            }
+                            sql_ast::SelectItem::QualifiedWildcard(_) => {
+                                return Err(AnalysisError::feature_not_supported(Feature::QualifiedAliases))
+                            }
+                        }
+                    }
+                    if let Some(predicate) = selection {
+                        params.compared(predicate, &table_columns)?;
+                    }
+                }
+                sql_ast::SetExpr::Query(_) => return Err(AnalysisError::feature_not_supported(Feature::SubQueries)),
+                sql_ast::SetExpr::SetOperation { .. } => {
+                    return Err(AnalysisError::feature_not_supported(Feature::SetOperations))
+                }
+                value_expr @ sql_ast::SetExpr::Values(_) => {
+                    return Err(AnalysisError::syntax_error(format!(
+                        "Syntax error in {}\naround {}",
+                        statement, value_expr
+                    )))
+                }
+            },
+            sql_ast::Statement::CreateSchema { .. }
+            | sql_ast::Statement::CreateTable { .. }
+            | sql_ast::Statement::Drop { .. } => {
+                self.analyze(statement.clone())?;
+            }
+            _ => {}
+        }
+        Ok(StatementDescription {
+            param_types: params.into(),
+            result_columns,
+        })
+    }
+
+    fn evaluation(
+        &self,
+        query: &sql_ast::Query,
+        select: &sql_ast::Select,
+        statement: &sql_ast::Statement,
+    ) -> AnalysisResult<QueryAnalysis> {
+        let sql_ast::Select {
+            projection,
+            selection,
+            group_by,
+            having,
+            ..
+        } = select;
+        // TODO: a select without tables returns a single row that could be
+        //       filtered out or limited, none of the clauses is evaluated yet
+        if selection.is_some()
+            || !group_by.is_empty()
+            || having.is_some()
+            || !query.order_by.is_empty()
+            || query.limit.is_some()
+            || query.offset.is_some()
+            || query.fetch.is_some()
+        {
+            return Err(AnalysisError::feature_not_supported(Feature::ClausesWithoutTable));
+        }
+        let mut column_names = vec![];
+        let mut projection_items = vec![];
+        for item in projection {
+            let (name, expr) = match item {
+                sql_ast::SelectItem::UnnamedExpr(expr) => (projection_column_name(expr), expr),
+                sql_ast::SelectItem::ExprWithAlias { expr, alias } => (sql_ast::ident_name(alias), expr),
+                sql_ast::SelectItem::Wildcard | sql_ast::SelectItem::QualifiedWildcard(_) => {
+                    return Err(AnalysisError::syntax_error(
+                        "SELECT * with no tables specified is not valid".to_owned(),
+                    ))
+                }
+            };
+            column_names.push(name);
+            projection_items.push(ProjectionTreeBuilder::build_from(
+                expr,
+                statement,
+                &SqlType::SmallInt,
+                &[],
+                &self.functions,
+            )?);
+        }
+        Ok(QueryAnalysis::Evaluation(EvaluationQuery {
+            column_names,
+            projection_items,
+        }))
+    }
+
+    fn table_columns(&self, table_name: &sql_ast::ObjectName) -> AnalysisResult<Vec<ColumnDefinition>> {
+        let full_table_name = FullTableName::try_from(table_name).map_err(AnalysisError::table_naming_error)?;
+        match self.data_definition.table_desc((&full_table_name).into()) {
+            None => Err(AnalysisError::schema_does_not_exist(full_table_name.schema())),
+            Some((_schema_id, None)) => Err(AnalysisError::table_does_not_exist(full_table_name)),
+            Some((_schema_id, Some((_table_id, table_columns)))) => Ok(table_columns),
+        }
+    }
+}
+
+/// names of created schemas, tables and columns are stored as they are, so
+/// empty ones and ones with `NUL` characters are rejected
+fn check_created_names(statement: &sql_ast::Statement) -> AnalysisResult<()> {
+    for ident in created_names(statement) {
+        if ident.value.is_empty() {
+            return Err(AnalysisError::invalid_name("zero-length delimited identifier"));
+        }
+        if ident.value.contains('\0') {
+            return Err(AnalysisError::invalid_name(format!(
+                "identifier \"{}\" contains NUL character",
+                ident.value.escape_default()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// names of created schemas, tables and columns that are longer than
+/// `MAX_IDENT_LENGTH` along with names they are truncated to, clients are
+/// notified about them
+pub fn truncated_names(statement: &sql_ast::Statement) -> Vec<(String, String)> {
+    created_names(statement)
+        .into_iter()
+        .filter(|ident| ident.value.len() > sql_ast::MAX_IDENT_LENGTH)
+        .map(|ident| (ident.value.clone(), sql_ast::ident_name(ident)))
+        .collect()
+}
+
+fn created_names(statement: &sql_ast::Statement) -> Vec<&sql_ast::Ident> {
+    match statement {
+        sql_ast::Statement::CreateSchema { schema_name, .. } => schema_name.0.iter().collect(),
+        sql_ast::Statement::CreateTable { name, columns, .. } => {
+            name.0.iter().chain(columns.iter().map(|column| &column.name)).collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// values of string columns are compared byte-wise unless other collation is
+/// given, columns of other types can't have collation
+fn column_collation(collation: &Option<sql_ast::ObjectName>, sql_type: SqlType) -> AnalysisResult<Option<Collation>> {
+    match (collation, sql_type.general_type()) {
+        (None, GeneralType::String) => Ok(Some(Collation::default())),
+        (None, _) => Ok(None),
+        (Some(name), GeneralType::String) => {
+            let collation = match name.0.as_slice() {
+                [collation] => Collation::from_name(&sql_ast::ident_name(collation)),
+                [schema, collation] if sql_ast::ident_name(schema) == "pg_catalog" => {
+                    Collation::from_name(&sql_ast::ident_name(collation))
+                }
+                _ => None,
+            };
+            collation.map(Some).ok_or_else(|| {
+                AnalysisError::undefined_collation(
+                    name.0
+                        .iter()
+                        .map(sql_ast::ident_name)
+                        .collect::<Vec<String>>()
+                        .join("."),
+                )
+            })
+        }
+        (Some(_), _) => Err(AnalysisError::collation_not_supported(sql_type)),
+    }
+}
+
+/// function calls and niladic functions are named after the function, other
+/// expressions as `?column?`
+fn projection_column_name(expr: &sql_ast::Expr) -> String {
+    match expr {
+        sql_ast::Expr::Function(sql_ast::Function {
+            name: sql_ast::ObjectName(parts),
+            ..
+        }) => parts
+            .last()
+            .map(sql_ast::ident_name)
+            .unwrap_or_else(|| "?column?".to_owned()),
+        sql_ast::Expr::Identifier(ident) => sql_ast::ident_name(ident),
+        _ => "?column?".to_owned(),
+    }
 }
 
 fn parse_param_index(value: &str) -> Option<usize> {
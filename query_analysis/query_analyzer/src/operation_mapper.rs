@@ -17,6 +17,8 @@ use expr_operators::{Arithmetic, Bitwise, Comparison, Logical, Operation, Patter
 pub(crate) struct OperationMapper;
 
 impl OperationMapper {
+    // TODO: `~`, `~*`, `!~` and `!~*` are not tokenized by the parser, they
+    //       have to map to `PatternMatching::Regex` and `PatternMatching::NotRegex`
     pub(crate) fn binary_operation(binary_op: &sql_ast::BinaryOperator) -> Operation {
         match binary_op {
             sql_ast::BinaryOperator::Plus => Operation::Arithmetic(Arithmetic::Add),
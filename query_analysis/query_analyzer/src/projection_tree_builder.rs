@@ -14,9 +14,12 @@
 
 use crate::{operation_mapper::OperationMapper, parse_param_index};
 use analysis_tree::{AnalysisError, AnalysisResult, Feature, ProjectionTreeNode};
-use expr_operators::{Bool, Operator, ScalarValue};
+use expr_operators::{call_signature, Bool, FunctionCatalog, FunctionKind, FunctionSignature, Operator, ScalarValue};
 use meta_def::ColumnDefinition;
-use types::SqlType;
+use types::{GeneralType, SqlType};
+
+/// SQL functions that are called without parentheses
+const NILADIC_FUNCTIONS: &[&str] = &["current_user", "current_schema"];
 
 pub(crate) struct ProjectionTreeBuilder;
 
@@ -26,23 +29,59 @@ impl ProjectionTreeBuilder {
         original: &sql_ast::Statement,
         column_type: &SqlType,
         table_columns: &[ColumnDefinition],
+        functions: &FunctionCatalog,
     ) -> AnalysisResult<ProjectionTreeNode> {
-        Self::inner_build(root_expr, original, column_type, 0, table_columns)
+        Self::inner_build(root_expr, original, column_type, 0, table_columns, functions, &[])
     }
 
+    /// `inlined` are SQL functions whose bodies the expression is part of
     fn inner_build(
         root_expr: &sql_ast::Expr,
         original: &sql_ast::Statement,
         column_type: &SqlType,
         level: usize,
         table_columns: &[ColumnDefinition],
+        functions: &FunctionCatalog,
+        inlined: &[&FunctionSignature],
     ) -> AnalysisResult<ProjectionTreeNode> {
         match root_expr {
-            sql_ast::Expr::Value(value) => Self::value(value),
-            sql_ast::Expr::Identifier(ident) => Self::ident(ident, table_columns),
-            sql_ast::Expr::BinaryOp { left, op, right } => {
-                Self::op(op, &**left, &**right, original, column_type, level, table_columns)
+            sql_ast::Expr::Nested(expr) => {
+                Self::inner_build(expr, original, column_type, level, table_columns, functions, inlined)
             }
+            sql_ast::Expr::Value(value) => Self::value(value),
+            sql_ast::Expr::Identifier(ident) => Self::ident(ident, table_columns, functions),
+            sql_ast::Expr::UnaryOp {
+                op: sql_ast::UnaryOperator::Minus,
+                expr,
+            } => match &**expr {
+                sql_ast::Expr::Value(sql_ast::Value::Number(num)) => Ok(ProjectionTreeNode::Item(Operator::Const(
+                    ScalarValue::Number(-num.clone()),
+                ))),
+                expr => Err(AnalysisError::syntax_error(format!(
+                    "Syntax error in {}\naround {}",
+                    original, expr
+                ))),
+            },
+            sql_ast::Expr::BinaryOp { left, op, right } => Self::op(
+                op,
+                &**left,
+                &**right,
+                original,
+                column_type,
+                level,
+                table_columns,
+                functions,
+                inlined,
+            ),
+            sql_ast::Expr::Function(function) => Self::function(
+                function,
+                original,
+                column_type,
+                level,
+                table_columns,
+                functions,
+                inlined,
+            ),
             expr => Err(AnalysisError::syntax_error(format!(
                 "Syntax error in {}\naround {}",
                 original, expr
@@ -50,6 +89,7 @@ impl ProjectionTreeBuilder {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn op(
         op: &sql_ast::BinaryOperator,
         left: &sql_ast::Expr,
@@ -58,10 +98,28 @@ impl ProjectionTreeBuilder {
         column_type: &SqlType,
         level: usize,
         table_columns: &[ColumnDefinition],
+        functions: &FunctionCatalog,
+        inlined: &[&FunctionSignature],
     ) -> AnalysisResult<ProjectionTreeNode> {
         let operation = OperationMapper::binary_operation(op);
-        let left_item = Self::inner_build(left, original, column_type, level + 1, table_columns)?;
-        let right_item = Self::inner_build(right, original, column_type, level + 1, table_columns)?;
+        let left_item = Self::inner_build(
+            left,
+            original,
+            column_type,
+            level + 1,
+            table_columns,
+            functions,
+            inlined,
+        )?;
+        let right_item = Self::inner_build(
+            right,
+            original,
+            column_type,
+            level + 1,
+            table_columns,
+            functions,
+            inlined,
+        )?;
         Ok(ProjectionTreeNode::Operation {
             left: Box::new(left_item),
             op: operation,
@@ -69,20 +127,218 @@ impl ProjectionTreeBuilder {
         })
     }
 
-    fn ident(ident: &sql_ast::Ident, table_columns: &[ColumnDefinition]) -> AnalysisResult<ProjectionTreeNode> {
-        let sql_ast::Ident { value, .. } = ident;
-        match parse_param_index(value.as_str()) {
+    fn function(
+        function: &sql_ast::Function,
+        original: &sql_ast::Statement,
+        column_type: &SqlType,
+        level: usize,
+        table_columns: &[ColumnDefinition],
+        functions: &FunctionCatalog,
+        inlined: &[&FunctionSignature],
+    ) -> AnalysisResult<ProjectionTreeNode> {
+        let sql_ast::Function {
+            name, args, distinct, ..
+        } = function;
+        let (schema, name) = match name.0.as_slice() {
+            [name] => (None, sql_ast::ident_name(name)),
+            [schema, name] => (Some(sql_ast::ident_name(schema)), sql_ast::ident_name(name)),
+            _ => {
+                return Err(AnalysisError::syntax_error(format!(
+                    "improper qualified name (too many dotted names): {}",
+                    name
+                )))
+            }
+        };
+        let mut arg_exprs = vec![];
+        for arg in args.iter() {
+            match arg {
+                // count(*) is resolved as a function without arguments
+                sql_ast::FunctionArg::Unnamed(sql_ast::Expr::Wildcard) => {}
+                sql_ast::FunctionArg::Unnamed(arg) => arg_exprs.push(arg),
+                sql_ast::FunctionArg::Named { .. } => {
+                    return Err(AnalysisError::feature_not_supported(Feature::NamedArguments))
+                }
+            }
+        }
+        let args = arg_exprs;
+        let mut arg_items = vec![];
+        for arg in args.iter() {
+            arg_items.push(Self::inner_build(
+                arg,
+                original,
+                column_type,
+                level + 1,
+                table_columns,
+                functions,
+                inlined,
+            )?);
+        }
+        let arg_types = arg_items
+            .iter()
+            .map(Self::general_type)
+            .collect::<Vec<Option<GeneralType>>>();
+        match functions.resolve(schema.as_deref(), name.as_str(), &arg_types) {
+            Ok(signature) if *distinct && signature.kind() != FunctionKind::Aggregate => {
+                Err(AnalysisError::wrong_object_type(format!(
+                    "DISTINCT specified, but {} is not an aggregate function",
+                    name
+                )))
+            }
+            Ok(signature) => match functions.sql_body(signature) {
+                None => Ok(ProjectionTreeNode::Function {
+                    signature: signature.clone(),
+                    args: arg_items,
+                    distinct: *distinct,
+                }),
+                Some(_) if inlined.contains(&signature) => Err(AnalysisError::invalid_function_definition(format!(
+                    "function {} calls itself and can't be inlined",
+                    signature
+                ))),
+                Some(body) => {
+                    let mut inlined = inlined.to_vec();
+                    inlined.push(signature);
+                    let item = Self::inner_build(
+                        &Self::inline(body, &args),
+                        original,
+                        column_type,
+                        level + 1,
+                        table_columns,
+                        functions,
+                        &inlined,
+                    )?;
+                    match Self::general_type(&item) {
+                        Some(general_type) if general_type != signature.return_type() => {
+                            Err(AnalysisError::invalid_function_definition(format!(
+                                "return type mismatch in function {} declared to return {:?}",
+                                signature,
+                                signature.return_type()
+                            )))
+                        }
+                        _ => Ok(item),
+                    }
+                }
+            },
+            Err(candidates) => Err(AnalysisError::undefined_function_call(
+                match schema {
+                    Some(schema) => call_signature(format!("{}.{}", schema, name).as_str(), &arg_types),
+                    None => call_signature(name.as_str(), &arg_types),
+                },
+                &candidates,
+            )),
+        }
+    }
+
+    /// substitutes `$n` references in the SQL function body with expressions
+    /// the function is called with
+    fn inline(body: &sql_ast::Expr, args: &[&sql_ast::Expr]) -> sql_ast::Expr {
+        let mut inlined = body.clone();
+        Self::substitute(&mut inlined, args);
+        inlined
+    }
+
+    // subqueries are not supported by the builder, their bodies are left as is
+    fn substitute(expr: &mut sql_ast::Expr, args: &[&sql_ast::Expr]) {
+        match expr {
+            sql_ast::Expr::Identifier(ident) => match parse_param_index(ident.value.as_str()) {
+                Some(index) if index < args.len() => *expr = args[index].clone(),
+                _ => {}
+            },
+            sql_ast::Expr::BinaryOp { left, right, .. } => {
+                Self::substitute(left, args);
+                Self::substitute(right, args);
+            }
+            sql_ast::Expr::UnaryOp { expr, .. } => Self::substitute(expr, args),
+            sql_ast::Expr::Nested(expr) => Self::substitute(expr, args),
+            sql_ast::Expr::IsNull(expr) => Self::substitute(expr, args),
+            sql_ast::Expr::IsNotNull(expr) => Self::substitute(expr, args),
+            sql_ast::Expr::Cast { expr, .. } => Self::substitute(expr, args),
+            sql_ast::Expr::Collate { expr, .. } => Self::substitute(expr, args),
+            sql_ast::Expr::Extract { expr, .. } => Self::substitute(expr, args),
+            sql_ast::Expr::Between { expr, low, high, .. } => {
+                Self::substitute(expr, args);
+                Self::substitute(low, args);
+                Self::substitute(high, args);
+            }
+            sql_ast::Expr::InList { expr, list, .. } => {
+                Self::substitute(expr, args);
+                for item in list {
+                    Self::substitute(item, args);
+                }
+            }
+            sql_ast::Expr::InSubquery { expr, .. } => Self::substitute(expr, args),
+            sql_ast::Expr::Function(sql_ast::Function {
+                args: function_args, ..
+            }) => {
+                for arg in function_args {
+                    match arg {
+                        sql_ast::FunctionArg::Named { arg, .. } => Self::substitute(arg, args),
+                        sql_ast::FunctionArg::Unnamed(arg) => Self::substitute(arg, args),
+                    }
+                }
+            }
+            sql_ast::Expr::Case {
+                operand,
+                conditions,
+                results,
+                else_result,
+            } => {
+                if let Some(operand) = operand {
+                    Self::substitute(operand, args);
+                }
+                for condition in conditions {
+                    Self::substitute(condition, args);
+                }
+                for result in results {
+                    Self::substitute(result, args);
+                }
+                if let Some(else_result) = else_result {
+                    Self::substitute(else_result, args);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn general_type(node: &ProjectionTreeNode) -> Option<GeneralType> {
+        match node {
+            ProjectionTreeNode::Operation { op, .. } => Some(op.result_type()),
+            ProjectionTreeNode::Function { signature, .. } => Some(signature.return_type()),
+            ProjectionTreeNode::Item(Operator::Column { sql_type, .. }) => Some(sql_type.general_type()),
+            ProjectionTreeNode::Item(Operator::Const(ScalarValue::String(_))) => Some(GeneralType::String),
+            ProjectionTreeNode::Item(Operator::Const(ScalarValue::Number(_))) => Some(GeneralType::Number),
+            ProjectionTreeNode::Item(Operator::Const(ScalarValue::Bool(_))) => Some(GeneralType::Bool),
+            ProjectionTreeNode::Item(Operator::Const(ScalarValue::Null))
+            | ProjectionTreeNode::Item(Operator::Param(_)) => None,
+        }
+    }
+
+    fn ident(
+        ident: &sql_ast::Ident,
+        table_columns: &[ColumnDefinition],
+        functions: &FunctionCatalog,
+    ) -> AnalysisResult<ProjectionTreeNode> {
+        match parse_param_index(ident.value.as_str()) {
             Some(index) => Ok(ProjectionTreeNode::Item(Operator::Param(index))),
             None => {
+                let name = sql_ast::ident_name(ident);
+                if ident.quote_style.is_none() && NILADIC_FUNCTIONS.contains(&name.as_str()) {
+                    if let Ok(signature) = functions.resolve(None, name.as_str(), &[]) {
+                        return Ok(ProjectionTreeNode::Function {
+                            signature: signature.clone(),
+                            args: vec![],
+                            distinct: false,
+                        });
+                    }
+                }
                 for (index, table_column) in table_columns.iter().enumerate() {
-                    if table_column.has_name(value.as_str()) {
+                    if table_column.has_name(&name) {
                         return Ok(ProjectionTreeNode::Item(Operator::Column {
                             sql_type: table_column.sql_type(),
                             index,
                         }));
                     }
                 }
-                Err(AnalysisError::column_not_found(value))
+                Err(AnalysisError::column_not_found(name))
             }
         }
     }
@@ -0,0 +1,118 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+fn add_column(name: Vec<&str>, column_name: &str, data_type: sql_ast::DataType) -> sql_ast::Statement {
+    sql_ast::Statement::AlterTable {
+        name: sql_ast::ObjectName(name.into_iter().map(ident).collect()),
+        operation: sql_ast::AlterTableOperation::AddColumn {
+            column_def: sql_ast::ColumnDef {
+                name: ident(column_name),
+                data_type,
+                collation: None,
+                options: vec![],
+            },
+        },
+    }
+}
+
+#[test]
+fn add_column_to_table_of_nonexistent_schema() {
+    let data_definition = Arc::new(DatabaseHandle::in_memory());
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(add_column(
+            vec!["non_existent_schema", TABLE],
+            "col",
+            sql_ast::DataType::Int
+        )),
+        Err(AnalysisError::schema_does_not_exist(&"non_existent_schema"))
+    );
+}
+
+#[test]
+fn add_column_to_nonexistent_table() {
+    let data_definition = Arc::new(DatabaseHandle::in_memory());
+    data_definition.create_schema(SCHEMA).expect("schema created");
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(add_column(vec![SCHEMA, TABLE], "col", sql_ast::DataType::Int)),
+        Err(AnalysisError::table_does_not_exist(format!("{}.{}", SCHEMA, TABLE)))
+    );
+}
+
+#[test]
+fn add_existing_column() {
+    let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col", SqlType::SmallInt)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(add_column(vec![SCHEMA, TABLE], "COL", sql_ast::DataType::Int)),
+        Err(AnalysisError::duplicate_column("col"))
+    );
+}
+
+#[test]
+fn add_column_of_unsupported_type() {
+    let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col", SqlType::SmallInt)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(add_column(
+            vec![SCHEMA, TABLE],
+            "new_col",
+            sql_ast::DataType::Custom(sql_ast::ObjectName(vec![ident("strange_type_name_whatever")])),
+        )),
+        Err(AnalysisError::type_is_not_supported(&"strange_type_name_whatever"))
+    );
+}
+
+#[test]
+fn add_new_column() {
+    let (data_definition, schema_id, _table_id) = with_table(&[ColumnDefinition::new("col", SqlType::SmallInt)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(add_column(vec![SCHEMA, TABLE], "NEW_COL", sql_ast::DataType::Int)),
+        Ok(QueryAnalysis::DataDefinition(SchemaChange::AddColumn(AddColumnQuery {
+            table_info: TableInfo::new(schema_id, &SCHEMA, &TABLE),
+            column_def: ColumnInfo {
+                name: "new_col".to_owned(),
+                sql_type: SqlType::Integer,
+                collation: None,
+            },
+        })))
+    );
+}
+
+#[test]
+fn drop_column_is_not_supported() {
+    let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col", SqlType::SmallInt)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(sql_ast::Statement::AlterTable {
+            name: sql_ast::ObjectName(vec![ident(SCHEMA), ident(TABLE)]),
+            operation: sql_ast::AlterTableOperation::DropColumn {
+                column_name: ident("col"),
+                if_exists: false,
+                cascade: false,
+            },
+        }),
+        Err(AnalysisError::feature_not_supported(Feature::AlterTableOperations))
+    );
+}
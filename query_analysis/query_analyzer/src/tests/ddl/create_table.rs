@@ -117,7 +117,7 @@ fn create_table_with_the_same_name() {
         analyzer.analyze(create_table(vec![SCHEMA, TABLE], vec![])),
         Ok(QueryAnalysis::DataDefinition(SchemaChange::CreateTable(
             CreateTableQuery {
-                table_info: TableInfo::new(0, &SCHEMA, &TABLE),
+                table_info: TableInfo::new(schema_id, &SCHEMA, &TABLE),
                 column_defs: vec![],
                 if_not_exists: false,
             }
@@ -128,7 +128,7 @@ fn create_table_with_the_same_name() {
 #[test]
 fn create_new_table_if_not_exist() {
     let data_definition = Arc::new(DatabaseHandle::in_memory());
-    data_definition.create_schema(SCHEMA).expect("schema created");
+    let schema_id = data_definition.create_schema(SCHEMA).expect("schema created");
     let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
     assert_eq!(
         analyzer.analyze(create_table_if_not_exists(
@@ -138,10 +138,11 @@ fn create_new_table_if_not_exist() {
         )),
         Ok(QueryAnalysis::DataDefinition(SchemaChange::CreateTable(
             CreateTableQuery {
-                table_info: TableInfo::new(0, &SCHEMA, &TABLE),
+                table_info: TableInfo::new(schema_id, &SCHEMA, &TABLE),
                 column_defs: vec![ColumnInfo {
                     name: "column_name".to_owned(),
-                    sql_type: SqlType::SmallInt
+                    sql_type: SqlType::SmallInt,
+                    collation: None,
                 }],
                 if_not_exists: true,
             }
@@ -152,7 +153,7 @@ fn create_new_table_if_not_exist() {
 #[test]
 fn successfully_create_table() {
     let data_definition = Arc::new(DatabaseHandle::in_memory());
-    data_definition.create_schema(SCHEMA).expect("schema created");
+    let schema_id = data_definition.create_schema(SCHEMA).expect("schema created");
     let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
     assert_eq!(
         analyzer.analyze(create_table(
@@ -161,13 +162,93 @@ fn successfully_create_table() {
         )),
         Ok(QueryAnalysis::DataDefinition(SchemaChange::CreateTable(
             CreateTableQuery {
-                table_info: TableInfo::new(0, &SCHEMA, &TABLE),
+                table_info: TableInfo::new(schema_id, &SCHEMA, &TABLE),
                 column_defs: vec![ColumnInfo {
                     name: "column_name".to_owned(),
-                    sql_type: SqlType::SmallInt
+                    sql_type: SqlType::SmallInt,
+                    collation: None,
                 }],
                 if_not_exists: false,
             }
         )))
     );
 }
+
+fn string_column(name: &str, collation: Option<Vec<sql_ast::Ident>>) -> sql_ast::ColumnDef {
+    sql_ast::ColumnDef {
+        name: ident(name),
+        data_type: sql_ast::DataType::Varchar(Some(10)),
+        collation: collation.map(sql_ast::ObjectName),
+        options: vec![],
+    }
+}
+
+#[test]
+fn string_columns_with_collations() {
+    let data_definition = Arc::new(DatabaseHandle::in_memory());
+    let schema_id = data_definition.create_schema(SCHEMA).expect("schema created");
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+    assert_eq!(
+        analyzer.analyze(create_table(
+            vec![SCHEMA, TABLE],
+            vec![
+                string_column("col_1", None),
+                string_column("col_2", Some(vec![ident("unicode")])),
+                string_column(
+                    "col_3",
+                    Some(vec![ident("pg_catalog"), sql_ast::Ident::with_quote('"', "POSIX")]),
+                ),
+            ],
+        )),
+        Ok(QueryAnalysis::DataDefinition(SchemaChange::CreateTable(
+            CreateTableQuery {
+                table_info: TableInfo::new(schema_id, &SCHEMA, &TABLE),
+                column_defs: vec![
+                    ColumnInfo {
+                        name: "col_1".to_owned(),
+                        sql_type: SqlType::VarChar(10),
+                        collation: Some(Collation::C),
+                    },
+                    ColumnInfo {
+                        name: "col_2".to_owned(),
+                        sql_type: SqlType::VarChar(10),
+                        collation: Some(Collation::Unicode),
+                    },
+                    ColumnInfo {
+                        name: "col_3".to_owned(),
+                        sql_type: SqlType::VarChar(10),
+                        collation: Some(Collation::C),
+                    },
+                ],
+                if_not_exists: false,
+            }
+        )))
+    );
+}
+
+#[test]
+fn undefined_collation() {
+    let data_definition = Arc::new(DatabaseHandle::in_memory());
+    data_definition.create_schema(SCHEMA).expect("schema created");
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+    assert_eq!(
+        analyzer.analyze(create_table(
+            vec![SCHEMA, TABLE],
+            vec![string_column("col", Some(vec![ident("de_DE")]))],
+        )),
+        Err(AnalysisError::undefined_collation("de_de"))
+    );
+}
+
+#[test]
+fn collation_of_non_string_column() {
+    let data_definition = Arc::new(DatabaseHandle::in_memory());
+    data_definition.create_schema(SCHEMA).expect("schema created");
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+    let mut column = column("col", sql_ast::DataType::SmallInt);
+    column.collation = Some(sql_ast::ObjectName(vec![ident("unicode")]));
+    assert_eq!(
+        analyzer.analyze(create_table(vec![SCHEMA, TABLE], vec![column])),
+        Err(AnalysisError::collation_not_supported(SqlType::SmallInt))
+    );
+}
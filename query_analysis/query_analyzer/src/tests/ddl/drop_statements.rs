@@ -188,7 +188,7 @@ mod table {
             analyzer.analyze(drop_statement(vec![vec![SCHEMA, TABLE]], TABLE_TYPE)),
             Ok(QueryAnalysis::DataDefinition(SchemaChange::DropTables(
                 DropTablesQuery {
-                    table_infos: vec![TableInfo::new(0, &SCHEMA, &TABLE)],
+                    table_infos: vec![TableInfo::new(schema_id, &SCHEMA, &TABLE)],
                     cascade: false,
                     if_exists: false
                 }
@@ -199,13 +199,13 @@ mod table {
     #[test]
     fn drop_nonexistent_table() {
         let data_definition = Arc::new(DatabaseHandle::in_memory());
-        data_definition.create_schema(SCHEMA).expect("schema created");
+        let schema_id = data_definition.create_schema(SCHEMA).expect("schema created");
         let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
         assert_eq!(
             analyzer.analyze(drop_statement(vec![vec![SCHEMA, "non_existent_table"]], TABLE_TYPE)),
             Ok(QueryAnalysis::DataDefinition(SchemaChange::DropTables(
                 DropTablesQuery {
-                    table_infos: vec![TableInfo::new(0, &SCHEMA, &"non_existent_table")],
+                    table_infos: vec![TableInfo::new(schema_id, &SCHEMA, &"non_existent_table")],
                     cascade: false,
                     if_exists: false
                 }
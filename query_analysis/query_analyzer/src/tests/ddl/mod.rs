@@ -14,6 +14,8 @@
 
 use super::*;
 
+#[cfg(test)]
+mod alter_table;
 #[cfg(test)]
 mod create_schema;
 #[cfg(test)]
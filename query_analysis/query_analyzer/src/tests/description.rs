@@ -0,0 +1,273 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+fn param(index: usize) -> sql_ast::Expr {
+    sql_ast::Expr::Identifier(ident(format!("${}", index)))
+}
+
+fn column(name: &'static str) -> sql_ast::Expr {
+    sql_ast::Expr::Identifier(ident(name))
+}
+
+fn equals(left: sql_ast::Expr, right: sql_ast::Expr) -> sql_ast::Expr {
+    sql_ast::Expr::BinaryOp {
+        left: Box::new(left),
+        op: sql_ast::BinaryOperator::Eq,
+        right: Box::new(right),
+    }
+}
+
+fn insert(columns: Vec<&'static str>, values: Vec<sql_ast::Expr>) -> sql_ast::Statement {
+    sql_ast::Statement::Insert {
+        table_name: sql_ast::ObjectName(vec![ident(SCHEMA), ident(TABLE)]),
+        columns: columns.into_iter().map(ident).collect(),
+        source: Box::new(sql_ast::Query {
+            with: None,
+            body: sql_ast::SetExpr::Values(sql_ast::Values(vec![values])),
+            order_by: vec![],
+            limit: None,
+            offset: None,
+            fetch: None,
+        }),
+    }
+}
+
+fn update(assignments: Vec<(&'static str, sql_ast::Expr)>, selection: Option<sql_ast::Expr>) -> sql_ast::Statement {
+    sql_ast::Statement::Update {
+        table_name: sql_ast::ObjectName(vec![ident(SCHEMA), ident(TABLE)]),
+        assignments: assignments
+            .into_iter()
+            .map(|(id, value)| sql_ast::Assignment { id: ident(id), value })
+            .collect(),
+        selection,
+    }
+}
+
+fn select(projection: Vec<sql_ast::SelectItem>, selection: Option<sql_ast::Expr>) -> sql_ast::Statement {
+    sql_ast::Statement::Query(Box::new(sql_ast::Query {
+        with: None,
+        body: sql_ast::SetExpr::Select(Box::new(sql_ast::Select {
+            distinct: false,
+            top: None,
+            projection,
+            from: vec![sql_ast::TableWithJoins {
+                relation: sql_ast::TableFactor::Table {
+                    name: sql_ast::ObjectName(vec![ident(SCHEMA), ident(TABLE)]),
+                    alias: None,
+                    args: vec![],
+                    with_hints: vec![],
+                },
+                joins: vec![],
+            }],
+            selection,
+            group_by: vec![],
+            having: None,
+        })),
+        order_by: vec![],
+        limit: None,
+        offset: None,
+        fetch: None,
+    }))
+}
+
+fn analyzer() -> Analyzer<InMemoryDatabase> {
+    let (data_definition, _schema_id, _table_id) = with_table(&[
+        ColumnDefinition::new("col_1", SqlType::SmallInt),
+        ColumnDefinition::new("col_2", SqlType::Integer),
+        ColumnDefinition::new("col_3", SqlType::VarChar(10)),
+    ]);
+    Analyzer::new(data_definition, InMemoryDatabase::new())
+}
+
+#[test]
+fn insert_into_all_columns() {
+    assert_eq!(
+        analyzer().describe(&insert(vec![], vec![param(1), param(2), param(3)])),
+        Ok(StatementDescription {
+            param_types: vec![
+                Some(SqlType::SmallInt),
+                Some(SqlType::Integer),
+                Some(SqlType::VarChar(10))
+            ],
+            result_columns: vec![],
+        })
+    );
+}
+
+#[test]
+fn insert_into_listed_columns_in_any_case() {
+    assert_eq!(
+        analyzer().describe(&insert(vec!["col_3", "COL_1"], vec![param(2), param(1)])),
+        Ok(StatementDescription {
+            param_types: vec![Some(SqlType::SmallInt), Some(SqlType::VarChar(10))],
+            result_columns: vec![],
+        })
+    );
+}
+
+#[test]
+fn insert_into_nonexistent_column() {
+    assert_eq!(
+        analyzer().describe(&insert(vec!["non_existent"], vec![param(1)])),
+        Err(AnalysisError::column_not_found(&"non_existent"))
+    );
+}
+
+#[test]
+fn gaps_between_parameters_are_not_typed() {
+    assert_eq!(
+        analyzer().describe(&insert(vec![], vec![sql_ast::Expr::Value(number(1)), param(3)])),
+        Ok(StatementDescription {
+            param_types: vec![None, None, Some(SqlType::Integer)],
+            result_columns: vec![],
+        })
+    );
+}
+
+#[test]
+fn update_with_parameters_in_assignments_and_predicate() {
+    assert_eq!(
+        analyzer().describe(&update(
+            vec![("col_2", param(1)), ("col_3", param(2))],
+            Some(equals(column("COL_1"), param(3)))
+        )),
+        Ok(StatementDescription {
+            param_types: vec![
+                Some(SqlType::Integer),
+                Some(SqlType::VarChar(10)),
+                Some(SqlType::SmallInt)
+            ],
+            result_columns: vec![],
+        })
+    );
+}
+
+#[test]
+fn update_nonexistent_column() {
+    assert_eq!(
+        analyzer().describe(&update(vec![("col_1", param(1)), ("non_existent", param(2))], None)),
+        Err(AnalysisError::column_not_found(&"non_existent"))
+    );
+}
+
+#[test]
+fn update_nonexistent_table() {
+    let data_definition = Arc::new(DatabaseHandle::in_memory());
+    data_definition.create_schema(SCHEMA).expect("schema created");
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.describe(&update(vec![("col_1", param(1))], None)),
+        Err(AnalysisError::table_does_not_exist(format!("{}.{}", SCHEMA, TABLE)))
+    );
+}
+
+#[test]
+fn parameter_bound_to_different_types() {
+    assert_eq!(
+        analyzer().describe(&update(vec![("col_1", param(1)), ("col_3", param(1))], None)),
+        Err(AnalysisError::syntax_error(
+            "Parameter $1 cannot be bound to different SQL types".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn delete_with_parameter_in_predicate() {
+    let statement = sql_ast::Statement::Delete {
+        table_name: sql_ast::ObjectName(vec![ident(SCHEMA), ident(TABLE)]),
+        selection: Some(equals(param(1), column("col_3"))),
+    };
+
+    assert_eq!(
+        analyzer().describe(&statement),
+        Ok(StatementDescription {
+            param_types: vec![Some(SqlType::VarChar(10))],
+            result_columns: vec![],
+        })
+    );
+}
+
+#[test]
+fn select_all_columns() {
+    assert_eq!(
+        analyzer().describe(&select(vec![sql_ast::SelectItem::Wildcard], None)),
+        Ok(StatementDescription {
+            param_types: vec![],
+            result_columns: vec![
+                ColumnInfo {
+                    name: "col_1".to_owned(),
+                    sql_type: SqlType::SmallInt,
+                    collation: None,
+                },
+                ColumnInfo {
+                    name: "col_2".to_owned(),
+                    sql_type: SqlType::Integer,
+                    collation: None,
+                },
+                ColumnInfo {
+                    name: "col_3".to_owned(),
+                    sql_type: SqlType::VarChar(10),
+                    collation: None,
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn select_named_columns_with_parameter_in_predicate() {
+    assert_eq!(
+        analyzer().describe(&select(
+            vec![sql_ast::SelectItem::UnnamedExpr(column("COL_3"))],
+            Some(equals(column("col_2"), param(1)))
+        )),
+        Ok(StatementDescription {
+            param_types: vec![Some(SqlType::Integer)],
+            result_columns: vec![ColumnInfo {
+                name: "col_3".to_owned(),
+                sql_type: SqlType::VarChar(10),
+                collation: None,
+            }],
+        })
+    );
+}
+
+#[test]
+fn select_nonexistent_column() {
+    assert_eq!(
+        analyzer().describe(&select(
+            vec![
+                sql_ast::SelectItem::UnnamedExpr(column("col_1")),
+                sql_ast::SelectItem::UnnamedExpr(column("non_existent"))
+            ],
+            None
+        )),
+        Err(AnalysisError::column_not_found(&"non_existent"))
+    );
+}
+
+#[test]
+fn select_from_nonexistent_table() {
+    let data_definition = Arc::new(DatabaseHandle::in_memory());
+    data_definition.create_schema(SCHEMA).expect("schema created");
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.describe(&select(vec![sql_ast::SelectItem::Wildcard], None)),
+        Err(AnalysisError::table_does_not_exist(format!("{}.{}", SCHEMA, TABLE)))
+    );
+}
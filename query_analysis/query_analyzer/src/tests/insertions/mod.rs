@@ -61,6 +61,7 @@ fn create_table(schema_name: &str, table_name: &str, columns: Vec<(&str, SqlType
                 table_name: table_name.to_owned(),
                 column_name: name.to_owned(),
                 sql_type,
+                collation: None,
             },
         })
         .collect::<Vec<Step>>();
@@ -17,6 +17,8 @@ mod ddl;
 #[cfg(test)]
 mod delete;
 #[cfg(test)]
+mod description;
+#[cfg(test)]
 mod insertions;
 #[cfg(test)]
 mod operation_mapping;
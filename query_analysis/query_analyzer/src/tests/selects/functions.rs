@@ -0,0 +1,473 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use expr_operators::{FunctionKind, FunctionSignature};
+use types::GeneralType;
+
+fn function_call(name: &str, args: Vec<sql_ast::Expr>) -> sql_ast::Statement {
+    call(vec![ident(name)], args, false)
+}
+
+fn distinct_function_call(name: &str, args: Vec<sql_ast::Expr>) -> sql_ast::Statement {
+    call(vec![ident(name)], args, true)
+}
+
+fn qualified_function_call(name: Vec<sql_ast::Ident>, args: Vec<sql_ast::Expr>) -> sql_ast::Statement {
+    call(name, args, false)
+}
+
+fn call(name: Vec<sql_ast::Ident>, args: Vec<sql_ast::Expr>, distinct: bool) -> sql_ast::Statement {
+    select_with_columns(
+        vec![SCHEMA, TABLE],
+        vec![sql_ast::SelectItem::UnnamedExpr(sql_ast::Expr::Function(
+            sql_ast::Function {
+                name: sql_ast::ObjectName(name),
+                args: args.into_iter().map(sql_ast::FunctionArg::Unnamed).collect(),
+                over: None,
+                distinct,
+            },
+        ))],
+    )
+}
+
+#[test]
+fn scalar_function_of_column() {
+    let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::VarChar(10))]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(function_call("UPPER", vec![sql_ast::Expr::Identifier(ident("col1"))])),
+        Ok(QueryAnalysis::Read(SelectQuery {
+            full_table_id: FullTableId::from((schema_id, table_id)),
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new(
+                    "upper",
+                    FunctionKind::Scalar,
+                    vec![GeneralType::String],
+                    GeneralType::String
+                ),
+                args: vec![ProjectionTreeNode::Item(Operator::Column {
+                    index: 0,
+                    sql_type: SqlType::VarChar(10)
+                })],
+                distinct: false,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn count_all_records() {
+    let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(function_call("count", vec![sql_ast::Expr::Wildcard])),
+        Ok(QueryAnalysis::Read(SelectQuery {
+            full_table_id: FullTableId::from((schema_id, table_id)),
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new("count", FunctionKind::Aggregate, vec![], GeneralType::Number),
+                args: vec![],
+                distinct: false,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn function_of_parameter() {
+    let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(function_call("abs", vec![sql_ast::Expr::Identifier(ident("$1"))])),
+        Ok(QueryAnalysis::Read(SelectQuery {
+            full_table_id: FullTableId::from((schema_id, table_id)),
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new(
+                    "abs",
+                    FunctionKind::Scalar,
+                    vec![GeneralType::Number],
+                    GeneralType::Number
+                ),
+                args: vec![ProjectionTreeNode::Item(Operator::Param(0))],
+                distinct: false,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn count_distinct_values() {
+    let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(distinct_function_call(
+            "count",
+            vec![sql_ast::Expr::Identifier(ident("col1"))]
+        )),
+        Ok(QueryAnalysis::Read(SelectQuery {
+            full_table_id: FullTableId::from((schema_id, table_id)),
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new(
+                    "count",
+                    FunctionKind::Aggregate,
+                    vec![GeneralType::Number],
+                    GeneralType::Number
+                ),
+                args: vec![ProjectionTreeNode::Item(Operator::Column {
+                    index: 0,
+                    sql_type: SqlType::Integer
+                })],
+                distinct: true,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn distinct_of_scalar_function() {
+    let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::VarChar(10))]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(distinct_function_call(
+            "upper",
+            vec![sql_ast::Expr::Identifier(ident("col1"))]
+        )),
+        Err(AnalysisError::wrong_object_type(
+            "DISTINCT specified, but upper is not an aggregate function"
+        ))
+    );
+}
+
+#[test]
+fn undefined_function() {
+    let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(function_call(
+            "not_a_function",
+            vec![sql_ast::Expr::Identifier(ident("col1"))]
+        )),
+        Err(AnalysisError::undefined_function_call(
+            "not_a_function(number)".to_owned(),
+            &[]
+        ))
+    );
+}
+
+#[test]
+fn function_with_wrong_argument_types() {
+    let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+    let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+    assert_eq!(
+        analyzer.analyze(function_call("length", vec![sql_ast::Expr::Identifier(ident("col1"))])),
+        Err(AnalysisError::UndefinedFunctionCall {
+            call: "length(number)".to_owned(),
+            candidates: vec!["length(string)".to_owned()],
+        })
+    );
+}
+
+#[cfg(test)]
+mod qualified_names {
+    use super::*;
+
+    #[test]
+    fn function_from_pg_catalog() {
+        let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::VarChar(10))]);
+        let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+        assert_eq!(
+            analyzer.analyze(qualified_function_call(
+                vec![ident("PG_CATALOG"), ident("Upper")],
+                vec![sql_ast::Expr::Identifier(ident("col1"))]
+            )),
+            Ok(QueryAnalysis::Read(SelectQuery {
+                full_table_id: FullTableId::from((schema_id, table_id)),
+                projection_items: vec![ProjectionTreeNode::Function {
+                    signature: FunctionSignature::new(
+                        "upper",
+                        FunctionKind::Scalar,
+                        vec![GeneralType::String],
+                        GeneralType::String
+                    ),
+                    args: vec![ProjectionTreeNode::Item(Operator::Column {
+                        index: 0,
+                        sql_type: SqlType::VarChar(10)
+                    })],
+                    distinct: false,
+                }],
+            }))
+        );
+    }
+
+    #[test]
+    fn builtin_function_is_not_in_public_schema() {
+        let (data_definition, _schema_id, _table_id) =
+            with_table(&[ColumnDefinition::new("col1", SqlType::VarChar(10))]);
+        let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+        assert_eq!(
+            analyzer.analyze(qualified_function_call(
+                vec![ident("public"), ident("upper")],
+                vec![sql_ast::Expr::Identifier(ident("col1"))]
+            )),
+            Err(AnalysisError::undefined_function_call(
+                "public.upper(string)".to_owned(),
+                &[]
+            ))
+        );
+    }
+
+    #[test]
+    fn quoted_name_is_case_sensitive() {
+        let (data_definition, _schema_id, _table_id) =
+            with_table(&[ColumnDefinition::new("col1", SqlType::VarChar(10))]);
+        let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+        assert_eq!(
+            analyzer.analyze(qualified_function_call(
+                vec![sql_ast::Ident::with_quote('"', "UPPER")],
+                vec![sql_ast::Expr::Identifier(ident("col1"))]
+            )),
+            Err(AnalysisError::undefined_function_call("UPPER(string)".to_owned(), &[]))
+        );
+    }
+
+    #[test]
+    fn too_many_dotted_names() {
+        let (data_definition, _schema_id, _table_id) =
+            with_table(&[ColumnDefinition::new("col1", SqlType::VarChar(10))]);
+        let analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+
+        assert_eq!(
+            analyzer.analyze(qualified_function_call(
+                vec![ident("db"), ident("pg_catalog"), ident("upper")],
+                vec![sql_ast::Expr::Identifier(ident("col1"))]
+            )),
+            Err(AnalysisError::syntax_error(
+                "improper qualified name (too many dotted names): db.pg_catalog.upper".to_owned()
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod sql_functions {
+    use super::*;
+
+    fn add_one() -> FunctionSignature {
+        FunctionSignature::new(
+            "add_one",
+            FunctionKind::Scalar,
+            vec![GeneralType::Number],
+            GeneralType::Number,
+        )
+    }
+
+    fn plus_one() -> sql_ast::Expr {
+        sql_ast::Expr::BinaryOp {
+            left: Box::new(sql_ast::Expr::Identifier(ident("$1"))),
+            op: sql_ast::BinaryOperator::Plus,
+            right: Box::new(sql_ast::Expr::Value(number(1))),
+        }
+    }
+
+    #[test]
+    fn inline_function_body() {
+        let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+        let mut analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+        assert!(analyzer.register_sql_function(add_one(), plus_one()));
+
+        assert_eq!(
+            analyzer.analyze(function_call("add_one", vec![sql_ast::Expr::Identifier(ident("col1"))])),
+            Ok(QueryAnalysis::Read(SelectQuery {
+                full_table_id: FullTableId::from((schema_id, table_id)),
+                projection_items: vec![ProjectionTreeNode::Operation {
+                    left: Box::new(ProjectionTreeNode::Item(Operator::Column {
+                        index: 0,
+                        sql_type: SqlType::Integer
+                    })),
+                    op: Operation::Arithmetic(Arithmetic::Add),
+                    right: Box::new(ProjectionTreeNode::Item(Operator::Const(ScalarValue::Number(
+                        BigDecimal::from(1)
+                    )))),
+                }],
+            }))
+        );
+    }
+
+    #[test]
+    fn inline_function_with_query_parameter() {
+        let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+        let mut analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+        assert!(analyzer.register_sql_function(add_one(), plus_one()));
+
+        assert_eq!(
+            analyzer.analyze(function_call("add_one", vec![sql_ast::Expr::Identifier(ident("$2"))])),
+            Ok(QueryAnalysis::Read(SelectQuery {
+                full_table_id: FullTableId::from((schema_id, table_id)),
+                projection_items: vec![ProjectionTreeNode::Operation {
+                    left: Box::new(ProjectionTreeNode::Item(Operator::Param(1))),
+                    op: Operation::Arithmetic(Arithmetic::Add),
+                    right: Box::new(ProjectionTreeNode::Item(Operator::Const(ScalarValue::Number(
+                        BigDecimal::from(1)
+                    )))),
+                }],
+            }))
+        );
+    }
+
+    #[test]
+    fn function_calls_are_checked_against_declared_argument_types() {
+        let (data_definition, _schema_id, _table_id) =
+            with_table(&[ColumnDefinition::new("col1", SqlType::VarChar(10))]);
+        let mut analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+        assert!(analyzer.register_sql_function(add_one(), plus_one()));
+
+        assert_eq!(
+            analyzer.analyze(function_call("add_one", vec![sql_ast::Expr::Identifier(ident("col1"))])),
+            Err(AnalysisError::UndefinedFunctionCall {
+                call: "add_one(string)".to_owned(),
+                candidates: vec!["add_one(number)".to_owned()],
+            })
+        );
+    }
+
+    #[test]
+    fn body_does_not_match_return_type() {
+        let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+        let mut analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+        assert!(analyzer.register_sql_function(
+            FunctionSignature::new(
+                "as_text",
+                FunctionKind::Scalar,
+                vec![GeneralType::Number],
+                GeneralType::String
+            ),
+            plus_one()
+        ));
+
+        assert_eq!(
+            analyzer.analyze(function_call("as_text", vec![sql_ast::Expr::Identifier(ident("col1"))])),
+            Err(AnalysisError::invalid_function_definition(
+                "return type mismatch in function as_text(number) declared to return String"
+            ))
+        );
+    }
+
+    fn call_of(name: &str, arg: &str) -> sql_ast::Expr {
+        sql_ast::Expr::Function(sql_ast::Function {
+            name: sql_ast::ObjectName(vec![ident(name)]),
+            args: vec![sql_ast::FunctionArg::Unnamed(sql_ast::Expr::Identifier(ident(arg)))],
+            over: None,
+            distinct: false,
+        })
+    }
+
+    #[test]
+    fn parameters_in_nested_expressions() {
+        let (data_definition, schema_id, table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+        let mut analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+        assert!(analyzer.register_sql_function(
+            FunctionSignature::new(
+                "abs_plus_one",
+                FunctionKind::Scalar,
+                vec![GeneralType::Number],
+                GeneralType::Number
+            ),
+            sql_ast::Expr::Nested(Box::new(sql_ast::Expr::BinaryOp {
+                left: Box::new(call_of("abs", "$1")),
+                op: sql_ast::BinaryOperator::Plus,
+                right: Box::new(sql_ast::Expr::Nested(Box::new(sql_ast::Expr::Value(number(1))))),
+            }))
+        ));
+
+        assert_eq!(
+            analyzer.analyze(function_call(
+                "abs_plus_one",
+                vec![sql_ast::Expr::Identifier(ident("col1"))]
+            )),
+            Ok(QueryAnalysis::Read(SelectQuery {
+                full_table_id: FullTableId::from((schema_id, table_id)),
+                projection_items: vec![ProjectionTreeNode::Operation {
+                    left: Box::new(ProjectionTreeNode::Function {
+                        signature: FunctionSignature::new(
+                            "abs",
+                            FunctionKind::Scalar,
+                            vec![GeneralType::Number],
+                            GeneralType::Number
+                        ),
+                        args: vec![ProjectionTreeNode::Item(Operator::Column {
+                            index: 0,
+                            sql_type: SqlType::Integer
+                        })],
+                        distinct: false,
+                    }),
+                    op: Operation::Arithmetic(Arithmetic::Add),
+                    right: Box::new(ProjectionTreeNode::Item(Operator::Const(ScalarValue::Number(
+                        BigDecimal::from(1)
+                    )))),
+                }],
+            }))
+        );
+    }
+
+    #[test]
+    fn recursive_function() {
+        let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+        let mut analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+        let number_function = |name: &str| {
+            FunctionSignature::new(
+                name,
+                FunctionKind::Scalar,
+                vec![GeneralType::Number],
+                GeneralType::Number,
+            )
+        };
+        assert!(analyzer.register_sql_function(number_function("ping"), call_of("pong", "$1")));
+        assert!(analyzer.register_sql_function(number_function("pong"), call_of("ping", "$1")));
+
+        assert_eq!(
+            analyzer.analyze(function_call("ping", vec![sql_ast::Expr::Identifier(ident("col1"))])),
+            Err(AnalysisError::invalid_function_definition(
+                "function ping(number) calls itself and can't be inlined"
+            ))
+        );
+    }
+
+    #[test]
+    fn same_function_in_both_operands() {
+        let (data_definition, _schema_id, _table_id) = with_table(&[ColumnDefinition::new("col1", SqlType::Integer)]);
+        let mut analyzer = Analyzer::new(data_definition, InMemoryDatabase::new());
+        assert!(analyzer.register_sql_function(add_one(), plus_one()));
+
+        assert!(analyzer
+            .analyze(function_call(
+                "abs",
+                vec![sql_ast::Expr::BinaryOp {
+                    left: Box::new(call_of("add_one", "col1")),
+                    op: sql_ast::BinaryOperator::Multiply,
+                    right: Box::new(call_of("add_one", "col1")),
+                }]
+            ))
+            .is_ok());
+    }
+}
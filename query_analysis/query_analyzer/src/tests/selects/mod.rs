@@ -17,7 +17,11 @@ use super::*;
 #[cfg(test)]
 mod expressions;
 #[cfg(test)]
+mod functions;
+#[cfg(test)]
 mod general_cases;
+#[cfg(test)]
+mod without_table;
 
 fn select_with_columns(name: Vec<&'static str>, projection: Vec<sql_ast::SelectItem>) -> sql_ast::Statement {
     sql_ast::Statement::Query(Box::new(sql_ast::Query {
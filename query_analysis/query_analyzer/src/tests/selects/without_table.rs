@@ -0,0 +1,230 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::*;
+use expr_operators::{FunctionKind, FunctionSignature};
+use types::GeneralType;
+
+fn select_without_table(projection: Vec<sql_ast::SelectItem>) -> sql_ast::Statement {
+    select_without_table_where(projection, None)
+}
+
+fn select_without_table_where(
+    projection: Vec<sql_ast::SelectItem>,
+    selection: Option<sql_ast::Expr>,
+) -> sql_ast::Statement {
+    sql_ast::Statement::Query(Box::new(sql_ast::Query {
+        with: None,
+        body: sql_ast::SetExpr::Select(Box::new(sql_ast::Select {
+            distinct: false,
+            top: None,
+            projection,
+            from: vec![],
+            selection,
+            group_by: vec![],
+            having: None,
+        })),
+        order_by: vec![],
+        limit: None,
+        offset: None,
+        fetch: None,
+    }))
+}
+
+fn function(name: &str, args: Vec<sql_ast::Expr>) -> sql_ast::Expr {
+    sql_ast::Expr::Function(sql_ast::Function {
+        name: sql_ast::ObjectName(vec![ident(name)]),
+        args: args.into_iter().map(sql_ast::FunctionArg::Unnamed).collect(),
+        over: None,
+        distinct: false,
+    })
+}
+
+fn analyzer() -> Analyzer<InMemoryDatabase> {
+    Analyzer::new(Arc::new(DatabaseHandle::in_memory()), InMemoryDatabase::new())
+}
+
+#[test]
+fn function_call() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::UnnamedExpr(function(
+            "VERSION",
+            vec![]
+        ))])),
+        Ok(QueryAnalysis::Evaluation(EvaluationQuery {
+            column_names: vec!["version".to_owned()],
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new("version", FunctionKind::Scalar, vec![], GeneralType::String),
+                args: vec![],
+                distinct: false,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn nested_function_calls() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::UnnamedExpr(function(
+            "upper",
+            vec![function("current_database", vec![])]
+        ))])),
+        Ok(QueryAnalysis::Evaluation(EvaluationQuery {
+            column_names: vec!["upper".to_owned()],
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new(
+                    "upper",
+                    FunctionKind::Scalar,
+                    vec![GeneralType::String],
+                    GeneralType::String
+                ),
+                args: vec![ProjectionTreeNode::Function {
+                    signature: FunctionSignature::new(
+                        "current_database",
+                        FunctionKind::Scalar,
+                        vec![],
+                        GeneralType::String
+                    ),
+                    args: vec![],
+                    distinct: false,
+                }],
+                distinct: false,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn niladic_function() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::UnnamedExpr(
+            sql_ast::Expr::Identifier(ident("CURRENT_USER"))
+        )])),
+        Ok(QueryAnalysis::Evaluation(EvaluationQuery {
+            column_names: vec!["current_user".to_owned()],
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new("current_user", FunctionKind::Scalar, vec![], GeneralType::String),
+                args: vec![],
+                distinct: false,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn negative_argument_and_alias() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::ExprWithAlias {
+            expr: function(
+                "setseed",
+                vec![sql_ast::Expr::UnaryOp {
+                    op: sql_ast::UnaryOperator::Minus,
+                    expr: Box::new(sql_ast::Expr::Value(number(1))),
+                }]
+            ),
+            alias: ident("seed"),
+        }])),
+        Ok(QueryAnalysis::Evaluation(EvaluationQuery {
+            column_names: vec!["seed".to_owned()],
+            projection_items: vec![ProjectionTreeNode::Function {
+                signature: FunctionSignature::new(
+                    "setseed",
+                    FunctionKind::Scalar,
+                    vec![GeneralType::Number],
+                    GeneralType::String
+                ),
+                args: vec![ProjectionTreeNode::Item(Operator::Const(ScalarValue::Number(
+                    BigDecimal::from(-1)
+                )))],
+                distinct: false,
+            }],
+        }))
+    );
+}
+
+#[test]
+fn expression() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::UnnamedExpr(
+            sql_ast::Expr::BinaryOp {
+                left: Box::new(sql_ast::Expr::Value(number(1))),
+                op: sql_ast::BinaryOperator::Plus,
+                right: Box::new(function("random", vec![])),
+            }
+        )])),
+        Ok(QueryAnalysis::Evaluation(EvaluationQuery {
+            column_names: vec!["?column?".to_owned()],
+            projection_items: vec![ProjectionTreeNode::Operation {
+                left: Box::new(ProjectionTreeNode::Item(Operator::Const(ScalarValue::Number(
+                    BigDecimal::from(1)
+                )))),
+                op: Operation::Arithmetic(Arithmetic::Add),
+                right: Box::new(ProjectionTreeNode::Function {
+                    signature: FunctionSignature::new(
+                        "random",
+                        FunctionKind::Scalar,
+                        vec![],
+                        GeneralType::Number
+                    ),
+                    args: vec![],
+                    distinct: false,
+                }),
+            }],
+        }))
+    );
+}
+
+#[test]
+fn function_with_wrong_argument_types() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::UnnamedExpr(function(
+            "setseed",
+            vec![string("1")]
+        ))])),
+        Err(AnalysisError::UndefinedFunctionCall {
+            call: "setseed(string)".to_owned(),
+            candidates: vec!["setseed(number)".to_owned()],
+        })
+    );
+}
+
+#[test]
+fn not_a_function() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::UnnamedExpr(
+            sql_ast::Expr::Identifier(ident("col1"))
+        )])),
+        Err(AnalysisError::column_not_found("col1"))
+    );
+}
+
+#[test]
+fn wildcard() {
+    assert_eq!(
+        analyzer().analyze(select_without_table(vec![sql_ast::SelectItem::Wildcard])),
+        Err(AnalysisError::syntax_error(
+            "SELECT * with no tables specified is not valid".to_owned()
+        ))
+    );
+}
+
+#[test]
+fn where_clause() {
+    assert_eq!(
+        analyzer().analyze(select_without_table_where(
+            vec![sql_ast::SelectItem::UnnamedExpr(function("version", vec![]))],
+            Some(boolean(true))
+        )),
+        Err(AnalysisError::feature_not_supported(Feature::ClausesWithoutTable))
+    );
+}
@@ -97,19 +97,19 @@ impl UpdateTreeBuilder {
     }
 
     fn ident(ident: &sql_ast::Ident, table_columns: &[ColumnDefinition]) -> AnalysisResult<UpdateTreeNode> {
-        let sql_ast::Ident { value, .. } = ident;
-        match parse_param_index(value.as_str()) {
+        match parse_param_index(ident.value.as_str()) {
             Some(index) => Ok(UpdateTreeNode::Item(Operator::Param(index))),
             None => {
+                let name = sql_ast::ident_name(ident);
                 for (index, table_column) in table_columns.iter().enumerate() {
-                    if table_column.has_name(value.as_str()) {
+                    if table_column.has_name(&name) {
                         return Ok(UpdateTreeNode::Item(Operator::Column {
                             sql_type: table_column.sql_type(),
                             index,
                         }));
                     }
                 }
-                Err(AnalysisError::column_not_found(value))
+                Err(AnalysisError::column_not_found(name))
             }
         }
     }
@@ -13,9 +13,9 @@
 // limitations under the License.
 
 use definition::{FullTableName, SchemaName};
-use expr_operators::{Operation, Operator};
+use expr_operators::{FunctionSignature, Operation, Operator};
 use meta_def::Id;
-use types::SqlType;
+use types::{Collation, SqlType};
 
 pub type AnalysisResult<A> = Result<A, AnalysisError>;
 
@@ -68,6 +68,8 @@ pub struct DropSchemasQuery {
 pub struct ColumnInfo {
     pub name: String,
     pub sql_type: SqlType,
+    /// collation of created string columns, `None` for other columns
+    pub collation: Option<Collation>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -77,6 +79,12 @@ pub struct CreateTableQuery {
     pub if_not_exists: bool,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct AddColumnQuery {
+    pub table_info: TableInfo,
+    pub column_def: ColumnInfo,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct DropTablesQuery {
     pub table_infos: Vec<TableInfo>,
@@ -142,6 +150,13 @@ pub struct SelectQuery {
     pub projection_items: Vec<ProjectionTreeNode>,
 }
 
+/// select that doesn't read a table, e.g. `SELECT version()`
+#[derive(Debug, PartialEq)]
+pub struct EvaluationQuery {
+    pub column_names: Vec<String>,
+    pub projection_items: Vec<ProjectionTreeNode>,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ProjectionTreeNode {
     Operation {
@@ -149,6 +164,12 @@ pub enum ProjectionTreeNode {
         op: Operation,
         right: Box<ProjectionTreeNode>,
     },
+    Function {
+        signature: FunctionSignature,
+        args: Vec<ProjectionTreeNode>,
+        /// aggregate function is applied only to distinct values of its arguments
+        distinct: bool,
+    },
     Item(Operator),
 }
 
@@ -163,6 +184,7 @@ pub enum SchemaChange {
     DropSchemas(DropSchemasQuery),
     CreateTable(CreateTableQuery),
     DropTables(DropTablesQuery),
+    AddColumn(AddColumnQuery),
 }
 
 #[derive(Debug, PartialEq)]
@@ -177,6 +199,16 @@ pub enum QueryAnalysis {
     DataDefinition(SchemaChange),
     Write(Write),
     Read(SelectQuery),
+    Evaluation(EvaluationQuery),
+}
+
+/// parameters a statement takes and columns of rows it returns
+#[derive(Debug, PartialEq, Default)]
+pub struct StatementDescription {
+    /// type of `$n` parameter is at `n - 1` index, `None` if it can't be
+    /// inferred from columns the parameter is assigned to or compared with
+    pub param_types: Vec<Option<SqlType>>,
+    pub result_columns: Vec<ColumnInfo>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -190,12 +222,19 @@ pub enum AnalysisError {
     TypeIsNotSupported(String),
     SyntaxError(String),
     ColumnNotFound(String),
+    DuplicateColumn(String),                                         // Error code: 42701
     ColumnCantBeReferenced(String),                                  // Error code: 42703
     InvalidInputSyntaxForType { sql_type: SqlType, value: String },  // Error code: 22P02
     StringDataRightTruncation(SqlType),                              // Error code: 22001
     DatatypeMismatch { column_type: SqlType, source_type: SqlType }, // Error code: 42804
     AmbiguousFunction(Operation),                                    // Error code: 42725
     UndefinedFunction(Operation),                                    // Error code: 42883
+    UndefinedFunctionCall { call: String, candidates: Vec<String> }, // Error code: 42883
+    InvalidFunctionDefinition(String),                               // Error code: 42P13
+    InvalidName(String),                                             // Error code: 42602
+    UndefinedCollation(String),                                      // Error code: 42704
+    CollationNotSupported(SqlType),                                  // Error code: 42804
+    WrongObjectType(String),                                         // Error code: 42809
     FeatureNotSupported(Feature),
 }
 
@@ -236,6 +275,10 @@ impl AnalysisError {
         AnalysisError::ColumnNotFound(column_name.to_string())
     }
 
+    pub fn duplicate_column<C: ToString>(column_name: C) -> AnalysisError {
+        AnalysisError::DuplicateColumn(column_name.to_string())
+    }
+
     pub fn column_cant_be_referenced<C: ToString>(column_name: C) -> AnalysisError {
         AnalysisError::ColumnCantBeReferenced(column_name.to_string())
     }
@@ -258,6 +301,33 @@ impl AnalysisError {
         }
     }
 
+    pub fn undefined_function_call(call: String, candidates: &[&FunctionSignature]) -> AnalysisError {
+        AnalysisError::UndefinedFunctionCall {
+            call,
+            candidates: candidates.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    pub fn invalid_function_definition<M: ToString>(message: M) -> AnalysisError {
+        AnalysisError::InvalidFunctionDefinition(message.to_string())
+    }
+
+    pub fn invalid_name<M: ToString>(message: M) -> AnalysisError {
+        AnalysisError::InvalidName(message.to_string())
+    }
+
+    pub fn undefined_collation<C: ToString>(collation_name: C) -> AnalysisError {
+        AnalysisError::UndefinedCollation(collation_name.to_string())
+    }
+
+    pub fn collation_not_supported(sql_type: SqlType) -> AnalysisError {
+        AnalysisError::CollationNotSupported(sql_type)
+    }
+
+    pub fn wrong_object_type<M: ToString>(message: M) -> AnalysisError {
+        AnalysisError::WrongObjectType(message.to_string())
+    }
+
     pub fn feature_not_supported(feature: Feature) -> AnalysisError {
         AnalysisError::FeatureNotSupported(feature)
     }
@@ -277,4 +347,8 @@ pub enum Feature {
     Aliases,
     QualifiedAliases,
     InsertIntoSelect,
+    ComputedColumns,
+    AlterTableOperations,
+    ClausesWithoutTable,
+    NamedArguments,
 }
@@ -12,7 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub use sqlparser::{dialect::Dialect, parser::*};
+mod literals;
+mod normalize;
+mod split;
+
+pub use literals::standard_literals;
+pub use normalize::{fingerprint, normalize};
+pub use split::split_statements;
+pub use sqlparser::{ast::Statement, dialect::Dialect, parser::*};
+
+/// parses statements with string literals of any syntax that PostgreSQL supports
+pub fn parse_statements(sql: &str) -> Result<Vec<Statement>, ParserError> {
+    match reset_as_set(sql) {
+        Some(set) => Parser::parse_sql(&PreparedStatementDialect, &set),
+        None => Parser::parse_sql(&PreparedStatementDialect, &standard_literals(sql)?),
+    }
+}
+
+/// sqlparser has no RESET statement, `RESET name` and `RESET ALL` are parsed
+/// as `SET name TO DEFAULT` that brings a setting back to its default as well
+fn reset_as_set(sql: &str) -> Option<String> {
+    let mut words = sql.trim().trim_end_matches(';').split_whitespace();
+    match (words.next(), words.next(), words.next()) {
+        (Some(reset), Some(name), None) if reset.eq_ignore_ascii_case("reset") => {
+            Some(format!("SET {} TO default", name))
+        }
+        _ => None,
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct PreparedStatementDialect;
@@ -26,3 +53,26 @@ impl Dialect for PreparedStatementDialect {
         ('a'..='z').contains(&ch) || ('A'..='Z').contains(&ch) || ('0'..='9').contains(&ch) || ch == '$' || ch == '_'
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_setting() {
+        assert_eq!(
+            parse_statements("RESET work_mem;"),
+            parse_statements("set work_mem to default")
+        );
+    }
+
+    #[test]
+    fn reset_all() {
+        assert_eq!(parse_statements("reset all"), parse_statements("set all to default"));
+    }
+
+    #[test]
+    fn reset_without_name() {
+        assert!(parse_statements("reset;").is_err());
+    }
+}
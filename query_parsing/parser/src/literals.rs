@@ -0,0 +1,416 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::ParserError;
+use std::borrow::Cow;
+
+/// rewrites dollar quoted strings, `E''` strings with C-style escapes and
+/// `U&''` strings with unicode escapes into standard string literals that
+/// sqlparser understands
+pub fn standard_literals(sql: &str) -> Result<Cow<'_, str>, ParserError> {
+    let chars = sql.chars().collect::<Vec<char>>();
+    let mut rewritten: Option<String> = None;
+    let mut copied = 0;
+    let mut index = 0;
+    while index < chars.len() {
+        let preceded_by_word = index > 0 && is_word_char(chars[index - 1]);
+        let (end, value) = match chars[index] {
+            '-' if chars.get(index + 1) == Some(&'-') => {
+                index = chars[index..]
+                    .iter()
+                    .position(|c| *c == '\n')
+                    .map(|end| index + end)
+                    .unwrap_or_else(|| chars.len());
+                continue;
+            }
+            '/' if chars.get(index + 1) == Some(&'*') => {
+                index = block_comment_end(&chars, index);
+                continue;
+            }
+            '\'' | '"' => {
+                index = quoted_end(&chars, index)?;
+                continue;
+            }
+            'e' | 'E' if !preceded_by_word && chars.get(index + 1) == Some(&'\'') => escaped(&chars, index + 1)?,
+            'u' | 'U'
+                if !preceded_by_word && chars.get(index + 1) == Some(&'&') && chars.get(index + 2) == Some(&'\'') =>
+            {
+                unicode_escaped(&chars, index + 2)?
+            }
+            '$' if !preceded_by_word => match dollar_quoted(&chars, index)? {
+                Some(literal) => literal,
+                None => {
+                    index += 1;
+                    continue;
+                }
+            },
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+        let rewritten = rewritten.get_or_insert_with(|| String::with_capacity(sql.len()));
+        rewritten.extend(&chars[copied..index]);
+        rewritten.push('\'');
+        rewritten.push_str(&value.replace('\'', "''"));
+        rewritten.push('\'');
+        copied = end;
+        index = end;
+    }
+    match rewritten {
+        Some(mut rewritten) => {
+            rewritten.extend(&chars[copied..]);
+            Ok(Cow::Owned(rewritten))
+        }
+        None => Ok(Cow::Borrowed(sql)),
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn unterminated() -> ParserError {
+    ParserError::TokenizerError("unterminated quoted string".to_owned())
+}
+
+/// index after `*/` that closes the comment, comments can be nested
+fn block_comment_end(chars: &[char], start: usize) -> usize {
+    let mut depth = 0;
+    let mut index = start;
+    while index < chars.len() {
+        match (chars[index], chars.get(index + 1)) {
+            ('/', Some('*')) => {
+                depth += 1;
+                index += 2;
+            }
+            ('*', Some('/')) => {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => index += 1,
+        }
+    }
+    chars.len()
+}
+
+/// index after the closing quote of a standard string or a quoted identifier
+fn quoted_end(chars: &[char], start: usize) -> Result<usize, ParserError> {
+    let quote = chars[start];
+    let mut index = start + 1;
+    while index < chars.len() {
+        if chars[index] == quote {
+            if chars.get(index + 1) != Some(&quote) {
+                return Ok(index + 1);
+            }
+            index += 1;
+        }
+        index += 1;
+    }
+    Err(unterminated())
+}
+
+/// content of a string between quotes that starts at `start` and index after
+/// the closing quote, doubled quotes are unescaped
+fn quoted(chars: &[char], start: usize) -> Result<(usize, Vec<char>), ParserError> {
+    let end = quoted_end(chars, start)?;
+    let mut content = vec![];
+    let mut index = start + 1;
+    while index < end - 1 {
+        content.push(chars[index]);
+        index += if chars[index] == '\'' { 2 } else { 1 };
+    }
+    Ok((end, content))
+}
+
+fn hex_code(chars: &[char], index: usize, max_digits: usize) -> (usize, u32) {
+    let mut code = 0;
+    let mut len = 0;
+    while len < max_digits {
+        match chars.get(index + len).and_then(|c| c.to_digit(16)) {
+            Some(digit) => code = code * 16 + digit,
+            None => break,
+        }
+        len += 1;
+    }
+    (len, code)
+}
+
+fn code_point(code: u32) -> Result<char, ParserError> {
+    match std::char::from_u32(code) {
+        Some(c) if c != '\0' => Ok(c),
+        _ => Err(ParserError::TokenizerError(format!(
+            "invalid Unicode escape value {:x}",
+            code
+        ))),
+    }
+}
+
+/// `E'...'` string where a quote is at `start`
+fn escaped(chars: &[char], start: usize) -> Result<(usize, String), ParserError> {
+    let mut value = String::new();
+    let mut index = start + 1;
+    loop {
+        match chars.get(index) {
+            None => return Err(unterminated()),
+            Some('\'') if chars.get(index + 1) == Some(&'\'') => {
+                value.push('\'');
+                index += 2;
+            }
+            Some('\'') => return Ok((index + 1, value)),
+            Some('\\') => {
+                let escape = *chars.get(index + 1).ok_or_else(unterminated)?;
+                index += 2;
+                match escape {
+                    'b' => value.push('\u{8}'),
+                    'f' => value.push('\u{c}'),
+                    'n' => value.push('\n'),
+                    'r' => value.push('\r'),
+                    't' => value.push('\t'),
+                    'x' => {
+                        let (len, code) = hex_code(chars, index, 2);
+                        if len == 0 {
+                            value.push('x');
+                        } else {
+                            value.push(code_point(code)?);
+                            index += len;
+                        }
+                    }
+                    'u' | 'U' => {
+                        let digits = if escape == 'u' { 4 } else { 8 };
+                        let (len, code) = hex_code(chars, index, digits);
+                        if len != digits {
+                            return Err(ParserError::TokenizerError(
+                                "invalid Unicode escape, Unicode escapes must be \\uXXXX or \\UXXXXXXXX".to_owned(),
+                            ));
+                        }
+                        value.push(code_point(code)?);
+                        index += len;
+                    }
+                    '0'..='7' => {
+                        let mut code = escape.to_digit(8).unwrap_or_default();
+                        let mut len = 0;
+                        while len < 2 {
+                            match chars.get(index).and_then(|c| c.to_digit(8)) {
+                                Some(digit) => code = code * 8 + digit,
+                                None => break,
+                            }
+                            index += 1;
+                            len += 1;
+                        }
+                        value.push(code_point(code)?);
+                    }
+                    other => value.push(other),
+                }
+            }
+            Some(c) => {
+                value.push(*c);
+                index += 1;
+            }
+        }
+    }
+}
+
+/// `U&'...'` string where a quote is at `start`, it can be followed by
+/// `UESCAPE 'c'` clause that changes the escape character
+fn unicode_escaped(chars: &[char], start: usize) -> Result<(usize, String), ParserError> {
+    let (mut end, content) = quoted(chars, start)?;
+    let mut escape = '\\';
+    let mut index = end;
+    while chars.get(index).map(|c| c.is_whitespace()).unwrap_or(false) {
+        index += 1;
+    }
+    if chars.len() >= index + 7
+        && chars[index..index + 7]
+            .iter()
+            .collect::<String>()
+            .eq_ignore_ascii_case("uescape")
+    {
+        index += 7;
+        while chars.get(index).map(|c| c.is_whitespace()).unwrap_or(false) {
+            index += 1;
+        }
+        match (chars.get(index), chars.get(index + 1), chars.get(index + 2)) {
+            (Some('\''), Some(c), Some('\''))
+                if !c.is_ascii_hexdigit() && !"+'\"".contains(*c) && !c.is_whitespace() =>
+            {
+                escape = *c;
+                end = index + 3;
+            }
+            _ => {
+                return Err(ParserError::TokenizerError(
+                    "invalid Unicode escape character".to_owned(),
+                ))
+            }
+        }
+    }
+    let mut value = String::new();
+    let mut index = 0;
+    while index < content.len() {
+        if content[index] != escape {
+            value.push(content[index]);
+            index += 1;
+        } else if content.get(index + 1) == Some(&escape) {
+            value.push(escape);
+            index += 2;
+        } else {
+            let (skip, digits) = if content.get(index + 1) == Some(&'+') {
+                (2, 6)
+            } else {
+                (1, 4)
+            };
+            let (len, code) = hex_code(&content, index + skip, digits);
+            if len != digits {
+                return Err(ParserError::TokenizerError("invalid Unicode escape".to_owned()));
+            }
+            value.push(code_point(code)?);
+            index += skip + len;
+        }
+    }
+    Ok((end, value))
+}
+
+/// `$tag$...$tag$` string starting at `start`, `None` if it is a parameter
+/// or there is no opening tag
+fn dollar_quoted(chars: &[char], start: usize) -> Result<Option<(usize, String)>, ParserError> {
+    let mut index = start + 1;
+    if chars.get(index).map(char::is_ascii_digit).unwrap_or(false) {
+        return Ok(None);
+    }
+    while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+        index += 1;
+    }
+    if chars.get(index) != Some(&'$') {
+        return Ok(None);
+    }
+    let tag = &chars[start..=index];
+    let content_start = index + 1;
+    let mut index = content_start;
+    while index < chars.len() {
+        if chars[index..].starts_with(tag) {
+            return Ok(Some((index + tag.len(), chars[content_start..index].iter().collect())));
+        }
+        index += 1;
+    }
+    Err(ParserError::TokenizerError(
+        "unterminated dollar-quoted string".to_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewritten(sql: &str) -> String {
+        standard_literals(sql).expect("rewritten").into_owned()
+    }
+
+    #[test]
+    fn standard_sql_is_not_copied() {
+        assert!(matches!(
+            standard_literals("select 'it''s', \"e'\" -- $$ E'\n /* /* $$ */ ' */ from t where c$ = $1"),
+            Ok(Cow::Borrowed(_))
+        ));
+    }
+
+    #[cfg(test)]
+    mod dollar_quoted {
+        use super::*;
+
+        #[test]
+        fn without_tag() {
+            assert_eq!(rewritten("select $$it's$$;"), "select 'it''s';");
+        }
+
+        #[test]
+        fn with_tag() {
+            assert_eq!(rewritten("select $body$ $$ \\n $body$"), "select ' $$ \\n '");
+        }
+
+        #[test]
+        fn unterminated() {
+            assert_eq!(
+                standard_literals("select $a$ text $b$"),
+                Err(ParserError::TokenizerError(
+                    "unterminated dollar-quoted string".to_owned()
+                ))
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod escape_strings {
+        use super::*;
+
+        #[test]
+        fn c_style_escapes() {
+            assert_eq!(
+                rewritten("select E'a\\tb\\nc\\\\d\\'e''f', e'\\x41\\101\\u00e9\\U0001F600\\q'"),
+                "select 'a\tb\nc\\d''e''f', 'AAé😀q'"
+            );
+        }
+
+        #[test]
+        fn prefix_of_identifier() {
+            assert_eq!(rewritten("select name'x'"), "select name'x'");
+        }
+
+        #[test]
+        fn invalid_unicode_escape() {
+            assert_eq!(
+                standard_literals("select E'\\u12'"),
+                Err(ParserError::TokenizerError(
+                    "invalid Unicode escape, Unicode escapes must be \\uXXXX or \\UXXXXXXXX".to_owned()
+                ))
+            );
+        }
+
+        #[test]
+        fn unterminated() {
+            assert_eq!(
+                standard_literals("select E'a\\'"),
+                Err(ParserError::TokenizerError("unterminated quoted string".to_owned()))
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod unicode_escapes {
+        use super::*;
+
+        #[test]
+        fn default_escape() {
+            assert_eq!(
+                rewritten("select U&'d\\0061t\\+000061 \\\\ it''s'"),
+                "select 'data \\ it''s'"
+            );
+        }
+
+        #[test]
+        fn custom_escape() {
+            assert_eq!(rewritten("select u&'d!0061t!+000061' UESCAPE '!';"), "select 'data';");
+        }
+
+        #[test]
+        fn invalid_escape() {
+            assert_eq!(
+                standard_literals("select U&'\\00zz'"),
+                Err(ParserError::TokenizerError("invalid Unicode escape".to_owned()))
+            );
+        }
+    }
+}
@@ -0,0 +1,303 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Space,
+    Literal,
+    Param(String, u32),
+    Text(String),
+}
+
+/// statement text that is the same for statements which differ only in values
+/// of literals, case of keywords and unquoted identifiers, whitespaces or comments.
+/// Literals are replaced with `$n` numbered after parameters of the statement
+pub fn normalize(sql: &str) -> String {
+    let tokens = tokens(sql);
+    let mut next_param = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Param(_, index) => Some(*index),
+            _ => None,
+        })
+        .max()
+        .unwrap_or_default();
+    let mut normalized = String::with_capacity(sql.len());
+    for token in tokens {
+        match token {
+            Token::Space => normalized.push(' '),
+            Token::Literal => {
+                next_param += 1;
+                normalized.push('$');
+                normalized.push_str(&next_param.to_string());
+            }
+            Token::Param(text, _) | Token::Text(text) => normalized.push_str(&text),
+        }
+    }
+    normalized
+}
+
+/// hash of normalized statement text, it does not change between releases
+/// and can be used to aggregate statements outside of the node
+pub fn fingerprint(sql: &str) -> u64 {
+    normalize(sql).bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn tokens(sql: &str) -> Vec<Token> {
+    let chars = sql.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut index = 0;
+    while index < chars.len() {
+        let start = index;
+        let token = match chars[index] {
+            c if c.is_whitespace() => {
+                while index < chars.len() && chars[index].is_whitespace() {
+                    index += 1;
+                }
+                Token::Space
+            }
+            '-' if chars.get(index + 1) == Some(&'-') => {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+                Token::Space
+            }
+            '/' if chars.get(index + 1) == Some(&'*') => {
+                index = block_comment_end(&chars, index);
+                Token::Space
+            }
+            '\'' => {
+                index = quoted_end(&chars, index, '\'', false);
+                Token::Literal
+            }
+            'e' | 'E' if chars.get(index + 1) == Some(&'\'') => {
+                index = quoted_end(&chars, index + 1, '\'', true);
+                Token::Literal
+            }
+            'n' | 'N' | 'b' | 'B' | 'x' | 'X' if chars.get(index + 1) == Some(&'\'') => {
+                index = quoted_end(&chars, index + 1, '\'', false);
+                Token::Literal
+            }
+            '"' => {
+                index = quoted_end(&chars, index, '"', false);
+                Token::Text(chars[start..index].iter().collect())
+            }
+            '$' if chars.get(index + 1).map(char::is_ascii_digit).unwrap_or(false) => {
+                index += 1;
+                while index < chars.len() && chars[index].is_ascii_digit() {
+                    index += 1;
+                }
+                let text = chars[start..index].iter().collect::<String>();
+                let param = text[1..].parse().unwrap_or(u32::MAX);
+                Token::Param(text, param)
+            }
+            '$' => match dollar_quoted_end(&chars, index) {
+                Some(end) => {
+                    index = end;
+                    Token::Literal
+                }
+                None => {
+                    index += 1;
+                    Token::Text("$".to_owned())
+                }
+            },
+            c if c.is_ascii_digit()
+                || (c == '.' && chars.get(index + 1).map(char::is_ascii_digit).unwrap_or(false)) =>
+            {
+                index = number_end(&chars, index);
+                Token::Literal
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while index < chars.len()
+                    && (chars[index].is_alphanumeric() || chars[index] == '_' || chars[index] == '$')
+                {
+                    index += 1;
+                }
+                Token::Text(chars[start..index].iter().collect::<String>().to_lowercase())
+            }
+            c => {
+                index += 1;
+                Token::Text(c.to_string())
+            }
+        };
+        tokens.push(token);
+    }
+    while matches!(tokens.last(), Some(Token::Space)) || tokens.last() == Some(&Token::Text(";".to_owned())) {
+        tokens.pop();
+    }
+    let mut tokens = tokens
+        .into_iter()
+        .skip_while(|token| token == &Token::Space)
+        .collect::<Vec<Token>>();
+    tokens.dedup_by(|next, previous| next == &Token::Space && previous == &Token::Space);
+    tokens
+}
+
+/// index after the closing quote, doubled quote is an escaped one as well as
+/// a quote after backslash in `E''` strings
+fn quoted_end(chars: &[char], start: usize, quote: char, backslash_escapes: bool) -> usize {
+    let mut index = start + 1;
+    while index < chars.len() {
+        match chars[index] {
+            '\\' if backslash_escapes => index += 2,
+            c if c == quote && chars.get(index + 1) == Some(&quote) => index += 2,
+            c if c == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    chars.len()
+}
+
+/// index after `*/` that closes the comment, comments can be nested
+fn block_comment_end(chars: &[char], start: usize) -> usize {
+    let mut depth = 0;
+    let mut index = start;
+    while index < chars.len() {
+        match (chars[index], chars.get(index + 1)) {
+            ('/', Some('*')) => {
+                depth += 1;
+                index += 2;
+            }
+            ('*', Some('/')) => {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => index += 1,
+        }
+    }
+    chars.len()
+}
+
+/// index after the closing `$tag$` of a dollar quoted string that starts
+/// with `$tag$` at `start`, `None` if there is no opening tag
+fn dollar_quoted_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut index = start + 1;
+    while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+        index += 1;
+    }
+    if chars.get(index) != Some(&'$') {
+        return None;
+    }
+    let tag = &chars[start..=index];
+    index += 1;
+    while index < chars.len() {
+        if chars[index..].starts_with(tag) {
+            return Some(index + tag.len());
+        }
+        index += 1;
+    }
+    Some(chars.len())
+}
+
+fn number_end(chars: &[char], start: usize) -> usize {
+    let mut index = start;
+    while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+        index += 1;
+    }
+    if index < chars.len() && (chars[index] == 'e' || chars[index] == 'E') {
+        let mut exponent = index + 1;
+        if exponent < chars.len() && (chars[exponent] == '+' || chars[exponent] == '-') {
+            exponent += 1;
+        }
+        if exponent < chars.len() && chars[exponent].is_ascii_digit() {
+            index = exponent;
+            while index < chars.len() && chars[index].is_ascii_digit() {
+                index += 1;
+            }
+        }
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(test)]
+    mod normalization {
+        use super::*;
+
+        #[test]
+        fn literals_are_replaced() {
+            assert_eq!(
+                normalize("INSERT INTO schema_name.table_name VALUES (1, 'a''b', -2.5e3), (.5, E'\\'', X'1F')"),
+                "insert into schema_name.table_name values ($1, $2, -$3), ($4, $5, $6)"
+            );
+        }
+
+        #[test]
+        fn literals_are_numbered_after_parameters() {
+            assert_eq!(
+                normalize("select * from t where a = $2 and b = 10 and c = $1"),
+                "select * from t where a = $2 and b = $3 and c = $1"
+            );
+        }
+
+        #[test]
+        fn dollar_quoted_strings() {
+            assert_eq!(normalize("select $$it's$$, $tag$a $$ b$tag$"), "select $1, $2");
+        }
+
+        #[test]
+        fn identifiers() {
+            assert_eq!(
+                normalize("SELECT Col_1, \"Col 2\", t1.c$ FROM Schema_Name.T1"),
+                "select col_1, \"Col 2\", t1.c$ from schema_name.t1"
+            );
+        }
+
+        #[test]
+        fn whitespaces_and_comments() {
+            assert_eq!(
+                normalize("  select /* outer /* inner */ */ 1 -- comment\n\t from\n t ;  "),
+                "select $1 from t"
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod fingerprints {
+        use super::*;
+
+        #[test]
+        fn same_for_different_literals() {
+            assert_eq!(
+                fingerprint("select * from t where id = 1;"),
+                fingerprint("SELECT *  FROM t WHERE id = 1000")
+            );
+        }
+
+        #[test]
+        fn different_for_different_statements() {
+            assert_ne!(
+                fingerprint("select * from t where id = 1"),
+                fingerprint("select * from t where key = 1")
+            );
+        }
+
+        #[test]
+        fn stable() {
+            assert_eq!(fingerprint(""), FNV_OFFSET_BASIS);
+            assert_eq!(fingerprint("a"), 0xaf63_dc4c_8601_ec8c);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// statements of a script separated by semicolons with byte offsets they start
+/// at. Semicolons inside of string literals, quoted identifiers and comments do
+/// not separate statements. Comments before a statement are skipped as well
+/// as statements that contain only comments
+pub fn split_statements(sql: &str) -> Vec<(usize, &str)> {
+    let chars = sql.char_indices().collect::<Vec<(usize, char)>>();
+    let mut statements = vec![];
+    let mut start = None;
+    let mut index = 0;
+    while index < chars.len() {
+        let (offset, c) = chars[index];
+        let next = chars.get(index + 1).map(|(_, c)| *c);
+        let preceded_by_word = index > 0 && is_word_char(chars[index - 1].1);
+        index = match c {
+            '-' if next == Some('-') => {
+                while index < chars.len() && chars[index].1 != '\n' {
+                    index += 1;
+                }
+                continue;
+            }
+            '/' if next == Some('*') => {
+                let mut depth = 0;
+                while index < chars.len() {
+                    match (chars[index].1, chars.get(index + 1).map(|(_, c)| *c)) {
+                        ('/', Some('*')) => {
+                            depth += 1;
+                            index += 1;
+                        }
+                        ('*', Some('/')) => {
+                            depth -= 1;
+                            index += 1;
+                            if depth == 0 {
+                                index += 1;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    index += 1;
+                }
+                continue;
+            }
+            ';' => {
+                if let Some(start) = start.take() {
+                    statements.push((start, sql[start..offset].trim_end()));
+                }
+                index += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            '\'' => {
+                let backslash_escapes = index > 0
+                    && matches!(chars[index - 1].1, 'e' | 'E')
+                    && (index < 2 || !is_word_char(chars[index - 2].1));
+                quoted_end(&chars, index, '\'', backslash_escapes)
+            }
+            '"' => quoted_end(&chars, index, '"', false),
+            '$' if !preceded_by_word => dollar_quoted_end(&chars, index).unwrap_or(index + 1),
+            _ => index + 1,
+        };
+        start.get_or_insert(offset);
+    }
+    if let Some(start) = start {
+        statements.push((start, sql[start..].trim_end()));
+    }
+    statements
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn quoted_end(chars: &[(usize, char)], start: usize, quote: char, backslash_escapes: bool) -> usize {
+    let mut index = start + 1;
+    while index < chars.len() {
+        match chars[index].1 {
+            '\\' if backslash_escapes => index += 2,
+            c if c == quote && chars.get(index + 1).map(|(_, c)| *c) == Some(quote) => index += 2,
+            c if c == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    chars.len()
+}
+
+fn dollar_quoted_end(chars: &[(usize, char)], start: usize) -> Option<usize> {
+    let mut index = start + 1;
+    if chars.get(index).map(|(_, c)| c.is_ascii_digit()).unwrap_or(true) {
+        return None;
+    }
+    while index < chars.len() && (chars[index].1.is_alphanumeric() || chars[index].1 == '_') {
+        index += 1;
+    }
+    if chars.get(index).map(|(_, c)| *c) != Some('$') {
+        return None;
+    }
+    let tag = chars[start..=index].iter().map(|(_, c)| *c).collect::<Vec<char>>();
+    index += 1;
+    while index < chars.len() {
+        if chars[index..]
+            .iter()
+            .map(|(_, c)| *c)
+            .take(tag.len())
+            .eq(tag.iter().copied())
+        {
+            return Some(index + tag.len());
+        }
+        index += 1;
+    }
+    Some(chars.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statements_with_offsets() {
+        assert_eq!(
+            split_statements("create schema s;  select 1 ;\nselect 2"),
+            vec![(0, "create schema s"), (18, "select 1"), (29, "select 2")]
+        );
+    }
+
+    #[test]
+    fn semicolons_in_literals_identifiers_and_comments() {
+        let sql = "select 'a;''b', E'\\';', \"c;\", $$d;$$, $t$ $$; $t$ -- e;\n from t; /* f; /* g; */ */ select 1";
+        assert_eq!(
+            split_statements(sql),
+            vec![
+                (0, "select 'a;''b', E'\\';', \"c;\", $$d;$$, $t$ $$; $t$ -- e;\n from t"),
+                (sql.rfind("select").unwrap(), "select 1")
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_statements_and_comments_are_skipped() {
+        assert_eq!(split_statements(" ; -- only comment;\n ; /* ; */ ;"), vec![]);
+        assert_eq!(split_statements(""), vec![]);
+    }
+
+    #[test]
+    fn parameters_and_identifiers_with_dollar() {
+        assert_eq!(
+            split_statements("select c$ from t where a = $1; select $2"),
+            vec![(0, "select c$ from t where a = $1"), (31, "select $2")]
+        );
+    }
+}
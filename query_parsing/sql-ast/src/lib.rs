@@ -13,3 +13,24 @@
 // limitations under the License.
 
 pub use sqlparser::ast::*;
+
+/// identifiers longer than that number of bytes are truncated, the same as
+/// `NAMEDATALEN - 1` of PostgreSQL
+pub const MAX_IDENT_LENGTH: usize = 63;
+
+/// name the identifier refers to. Unquoted identifiers are folded to lower
+/// case, quoted ones keep their case as PostgreSQL does
+pub fn ident_name(ident: &Ident) -> String {
+    let mut name = match ident.quote_style {
+        None => ident.value.to_lowercase(),
+        Some(_) => ident.value.clone(),
+    };
+    if name.len() > MAX_IDENT_LENGTH {
+        let mut end = MAX_IDENT_LENGTH;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        name.truncate(end);
+    }
+    name
+}
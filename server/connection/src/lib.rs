@@ -12,31 +12,53 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use async_io::Timer;
 use async_mutex::Mutex as AsyncMutex;
 use async_native_tls::TlsStream;
 use blocking::Unblock;
 use byteorder::{ByteOrder, NetworkEndian};
-use futures_lite::{future::block_on, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use pg_model::{results::QueryResult, Command, ConnSupervisor, Encryption, ProtocolConfiguration};
+use futures_lite::{
+    future::{self, block_on},
+    AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
+};
+use pg_model::{
+    encoding,
+    hba::AuthMethod,
+    results::{self, QueryError, QueryEvent, QueryResult},
+    Command, ConnSupervisor, Encryption, ProtocolConfiguration, SERVER_VERSION,
+};
 use pg_wire::{
     BackendMessage, ConnId, Error, FrontendMessage, HandShakeProcess, HandShakeRequest, HandShakeStatus,
     MessageDecoder, MessageDecoderStatus, Result,
 };
 use std::{
+    fmt::Debug,
     fs::File,
-    io,
+    io::{self, IoSlice},
+    iter,
     net::SocketAddr,
     pin::Pin,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     task::{Context, Poll},
+    time::Duration,
 };
 
 type Props = Vec<(String, String)>;
 
+/// the same limit as PostgreSQL has for a startup packet
+const MAX_STARTUP_MESSAGE_LEN: usize = 10_000;
+/// the same limit as PostgreSQL has for messages with query text or
+/// parameters, lengths of other messages are checked by the decoder
+const MAX_MESSAGE_LEN: usize = 0x3fff_ffff;
+
 /// Client request accepted from a client
 pub enum ClientRequest {
-    /// Connection to perform queries
-    Connection(Box<dyn Receiver>, Arc<dyn Sender>),
+    /// Connection to perform queries, its ID is reported to the client as
+    /// the backend process ID
+    Connection(ConnId, Box<dyn Receiver>, Arc<dyn Sender>),
     /// Connection to cancel queries of another client
     QueryCancellation(ConnId),
 }
@@ -63,6 +85,9 @@ where
     loop {
         match process.next_stage(current.as_deref()) {
             Ok(HandShakeStatus::Requesting(HandShakeRequest::Buffer(len))) => {
+                if len > MAX_STARTUP_MESSAGE_LEN {
+                    return Err(invalid_length(len));
+                }
                 let mut local = vec![b'0'; len];
                 local = channel.read_exact(&mut local).await.map(|_| local)?;
                 current = Some(local);
@@ -88,32 +113,47 @@ where
                 }
             }
             Ok(HandShakeStatus::Done(props)) => {
-                channel
-                    .write_all(BackendMessage::AuthenticationCleartextPassword.as_vec().as_slice())
-                    .await?;
-                channel.flush().await?;
-                let mut tag_buffer = [0u8; 1];
-                let tag = channel.read_exact(&mut tag_buffer).await.map(|_| tag_buffer[0]);
-                log::debug!("client message response tag {:?}", tag);
-                log::debug!("waiting for authentication response");
-                let mut len_buffer = [0u8; 4];
-                let len = channel
-                    .read_exact(&mut len_buffer)
-                    .await
-                    .map(|_| NetworkEndian::read_u32(&len_buffer) as usize)?;
-                let len = len - 4;
-                let mut message_buffer = Vec::with_capacity(len);
-                message_buffer.resize(len, b'0');
-                let _message = channel.read_exact(&mut message_buffer).await.map(|_| message_buffer)?;
+                if let Some((_, client_encoding)) = props.iter().find(|(key, _)| key == "client_encoding") {
+                    if !encoding::is_supported(client_encoding) {
+                        log::warn!(
+                            "client requested {:?} encoding, text is exchanged in {}",
+                            client_encoding,
+                            encoding::CLIENT_ENCODING
+                        );
+                    }
+                }
+                let user = property(&props, "user").unwrap_or_default();
+                let database = property(&props, "database").unwrap_or_else(|| user.clone());
+                let ssl = matches!(channel, Channel::Secure(_));
+                match config.hba().method(address.ip(), ssl, &database, &user) {
+                    Some(AuthMethod::Trust) => {}
+                    Some(AuthMethod::Password) => read_password(&mut channel).await?,
+                    method => {
+                        let error = if method.is_none() {
+                            QueryError::no_hba_entry(address.ip(), &user, &database)
+                        } else {
+                            QueryError::hba_rejects_connection(address.ip(), &user, &database)
+                        };
+                        let refused = format!("connection of {:?} to {:?} from {} is refused", user, database, address);
+                        log::warn!("{}", refused);
+                        let message: BackendMessage = error.into();
+                        channel.write_all(message.as_vec().as_slice()).await?;
+                        channel.flush().await?;
+                        return Err(io::Error::new(io::ErrorKind::PermissionDenied, refused));
+                    }
+                }
                 channel
                     .write_all(BackendMessage::AuthenticationOk.as_vec().as_slice())
                     .await?;
 
                 channel
                     .write_all(
-                        BackendMessage::ParameterStatus("client_encoding".to_owned(), "UTF8".to_owned())
-                            .as_vec()
-                            .as_slice(),
+                        BackendMessage::ParameterStatus(
+                            "client_encoding".to_owned(),
+                            encoding::CLIENT_ENCODING.to_owned(),
+                        )
+                        .as_vec()
+                        .as_slice(),
                     )
                     .await?;
 
@@ -135,7 +175,7 @@ where
 
                 channel
                     .write_all(
-                        BackendMessage::ParameterStatus("server_version".to_owned(), "12.4".to_owned())
+                        BackendMessage::ParameterStatus("server_version".to_owned(), SERVER_VERSION.to_owned())
                             .as_vec()
                             .as_slice(),
                     )
@@ -157,14 +197,19 @@ where
                     .await?;
 
                 let channel = Arc::new(AsyncMutex::new(channel));
+                let receiver = RequestReceiver::new(conn_id, props.clone(), channel.clone(), conn_supervisor);
+                let sender = ResponseSender::new(
+                    conn_id,
+                    props,
+                    channel,
+                    receiver.protocol_trace(),
+                    receiver.stalled(),
+                    config.send_timeout(),
+                );
                 return Ok(Ok(ClientRequest::Connection(
-                    Box::new(RequestReceiver::new(
-                        conn_id,
-                        props.clone(),
-                        channel.clone(),
-                        conn_supervisor,
-                    )),
-                    Arc::new(ResponseSender::new(props, channel)),
+                    conn_id,
+                    Box::new(receiver),
+                    Arc::new(sender),
                 )));
             }
             Err(error) => return Ok(Err(error)),
@@ -172,6 +217,45 @@ where
     }
 }
 
+fn property(props: &[(String, String)], name: &str) -> Option<String> {
+    props
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.clone())
+}
+
+async fn read_password<RW>(channel: &mut Channel<RW>) -> io::Result<()>
+where
+    RW: AsyncRead + AsyncWrite + Unpin,
+{
+    // TODO: there are no roles with passwords to check it against yet
+    channel
+        .write_all(BackendMessage::AuthenticationCleartextPassword.as_vec().as_slice())
+        .await?;
+    channel.flush().await?;
+    let mut tag_buffer = [0u8; 1];
+    let tag = channel.read_exact(&mut tag_buffer).await.map(|_| tag_buffer[0]);
+    log::debug!("client message response tag {:?}", tag);
+    log::debug!("waiting for authentication response");
+    let mut len_buffer = [0u8; 4];
+    let len = channel
+        .read_exact(&mut len_buffer)
+        .await
+        .map(|_| NetworkEndian::read_u32(&len_buffer) as usize)?;
+    if len < 4 || len > MAX_STARTUP_MESSAGE_LEN {
+        return Err(invalid_length(len));
+    }
+    let len = len - 4;
+    let mut message_buffer = Vec::with_capacity(len);
+    message_buffer.resize(len, b'0');
+    let _message = channel.read_exact(&mut message_buffer).await.map(|_| message_buffer)?;
+    Ok(())
+}
+
+fn invalid_length(len: usize) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid message length {}", len))
+}
+
 async fn tls_channel<RW>(tcp_channel: RW, config: &ProtocolConfiguration) -> io::Result<TlsStream<RW>>
 where
     RW: AsyncRead + AsyncWrite + Unpin,
@@ -193,6 +277,9 @@ struct RequestReceiver<RW: AsyncRead + AsyncWrite + Unpin> {
     channel: Arc<AsyncMutex<Channel<RW>>>,
     conn_supervisor: Arc<Mutex<ConnSupervisor>>,
     message_decoder: MessageDecoder,
+    protocol_trace: Arc<AtomicBool>,
+    stalled: Arc<AtomicBool>,
+    closed: bool,
 }
 
 impl<RW: AsyncRead + AsyncWrite + Unpin> RequestReceiver<RW> {
@@ -209,38 +296,110 @@ impl<RW: AsyncRead + AsyncWrite + Unpin> RequestReceiver<RW> {
             channel,
             conn_supervisor,
             message_decoder: MessageDecoder::new(),
+            protocol_trace: Arc::new(AtomicBool::new(false)),
+            stalled: Arc::new(AtomicBool::new(false)),
+            closed: false,
         }
     }
 
-    /// connection properties tuple
-    pub fn properties(&self) -> &Props {
-        &self.properties
-    }
-
-    async fn read_frontend_message(&mut self) -> io::Result<Result<FrontendMessage>> {
+    async fn read_frontend_message(&mut self) -> io::Result<Result<Incoming>> {
         let mut current: Option<Vec<u8>> = None;
+        let mut raw = vec![];
         loop {
             log::debug!("Read bytes from connection {:?}", current);
             match self.message_decoder.next_stage(current.take().as_deref()) {
                 Ok(MessageDecoderStatus::Requesting(len)) => {
+                    if len > MAX_MESSAGE_LEN {
+                        return Ok(Ok(Incoming::Malformed(len)));
+                    }
                     let mut buffer = vec![b'0'; len];
                     self.channel.lock().await.read_exact(&mut buffer).await?;
+                    raw.extend_from_slice(&buffer);
+                    if let Some(len) = invalid_declared_length(&raw) {
+                        return Ok(Ok(Incoming::Malformed(len)));
+                    }
+                    if let Some(command) = rejected_text(&raw) {
+                        // the whole message is read, the decoder starts over from the next one
+                        self.message_decoder = MessageDecoder::new();
+                        return Ok(Ok(Incoming::Rejected(command)));
+                    }
                     current = Some(buffer);
                 }
                 Ok(MessageDecoderStatus::Decoding) => {}
-                Ok(MessageDecoderStatus::Done(message)) => return Ok(Ok(message)),
+                Ok(MessageDecoderStatus::Done(message)) => {
+                    if self.protocol_trace.load(Ordering::Relaxed) {
+                        trace(self.conn_id, 'F', raw.len(), &message);
+                    }
+                    return Ok(Ok(Incoming::Message(message)));
+                }
                 Err(error) => return Ok(Err(error)),
             }
         }
     }
 }
 
+// protocol messages are logged the same way as `PQtrace` of libpq does, `F`
+// marks messages sent by frontend and `B` the ones sent by backend
+fn trace<M: Debug>(conn_id: ConnId, direction: char, len: usize, message: &M) {
+    log::info!("connection-{} {} {} {:?}", conn_id, direction, len, message);
+}
+
+enum Incoming {
+    Message(FrontendMessage),
+    Rejected(Command),
+    Malformed(usize),
+}
+
+// checks length that a message declares before the decoder relies on it
+fn invalid_declared_length(raw: &[u8]) -> Option<usize> {
+    if raw.len() < 5 {
+        return None;
+    }
+    match NetworkEndian::read_u32(&raw[1..5]) as usize {
+        len if len < 4 || len > MAX_MESSAGE_LEN => Some(len),
+        _ => None,
+    }
+}
+
+const QUERY: u8 = b'Q';
+const PARSE: u8 = b'P';
+
+// checks text of a fully read `Query` or `Parse` message before it is decoded
+fn rejected_text(raw: &[u8]) -> Option<Command> {
+    if raw.len() < 5 || raw.len() != 1 + NetworkEndian::read_u32(&raw[1..5]) as usize {
+        return None;
+    }
+    let (texts, simple_query) = match raw[0] {
+        QUERY => (1, true),
+        PARSE => (2, false),
+        _ => return None,
+    };
+    raw[5..]
+        .splitn(texts + 1, |byte| *byte == 0)
+        .take(texts)
+        .find_map(|text| encoding::decode(text).err())
+        .map(|error| Command::Reject { error, simple_query })
+}
+
 #[async_trait::async_trait]
 impl<RW: AsyncRead + AsyncWrite + Unpin> Receiver for RequestReceiver<RW> {
     // TODO: currently it uses protocol::Result
     async fn receive(&mut self) -> io::Result<Result<Command>> {
+        if self.closed || self.stalled.load(Ordering::Relaxed) {
+            return Ok(Ok(Command::Terminate));
+        }
         let message = match self.read_frontend_message().await {
-            Ok(Ok(message)) => message,
+            Ok(Ok(Incoming::Message(message))) => message,
+            Ok(Ok(Incoming::Rejected(command))) => return Ok(Ok(command)),
+            Ok(Ok(Incoming::Malformed(len))) => {
+                log::warn!("connection-{} received message of invalid length {}", self.conn_id, len);
+                // the rest of the stream can't be split into messages
+                self.closed = true;
+                return Ok(Ok(Command::Reject {
+                    error: QueryError::invalid_message_length(len),
+                    simple_query: false,
+                }));
+            }
             Ok(Err(err)) => return Ok(Err(err)),
             Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
                 // Client disconnected the socket immediately without sending a
@@ -283,13 +442,25 @@ impl<RW: AsyncRead + AsyncWrite + Unpin> Receiver for RequestReceiver<RW> {
             FrontendMessage::Terminate => Ok(Ok(Command::Terminate)),
             FrontendMessage::Sync => Ok(Ok(Command::Continue)),
             FrontendMessage::DescribePortal { name } => Ok(Ok(Command::DescribePortal { name })),
-            FrontendMessage::CloseStatement { name: _ } => Ok(Ok(Command::Continue)),
-            FrontendMessage::ClosePortal { name: _ } => Ok(Ok(Command::Continue)),
+            FrontendMessage::CloseStatement { name } => Ok(Ok(Command::CloseStatement { name })),
+            FrontendMessage::ClosePortal { name } => Ok(Ok(Command::ClosePortal { name })),
             FrontendMessage::Setup { .. } => Ok(Ok(Command::Continue)),
             FrontendMessage::SslRequest => Ok(Ok(Command::Continue)),
             FrontendMessage::GssencRequest => Ok(Ok(Command::Continue)),
         }
     }
+
+    fn properties(&self) -> &[(String, String)] {
+        &self.properties
+    }
+
+    fn protocol_trace(&self) -> Arc<AtomicBool> {
+        self.protocol_trace.clone()
+    }
+
+    fn stalled(&self) -> Arc<AtomicBool> {
+        self.stalled.clone()
+    }
 }
 
 impl<RW: AsyncRead + AsyncWrite + Unpin> Drop for RequestReceiver<RW> {
@@ -304,23 +475,146 @@ impl<RW: AsyncRead + AsyncWrite + Unpin> Drop for RequestReceiver<RW> {
 pub trait Receiver: Send + Sync {
     /// receives and decodes a command from remote client
     async fn receive(&mut self) -> io::Result<Result<Command>>;
+
+    /// properties that client sent on startup, e.g. `user` and `database`
+    fn properties(&self) -> &[(String, String)];
+
+    /// switch of logging of every message that the connection receives and
+    /// sends, shared with its `Sender`
+    fn protocol_trace(&self) -> Arc<AtomicBool>;
+
+    /// set by the `Sender` of the connection when client stops reading its
+    /// results, then the running statement is aborted and the connection is
+    /// closed
+    fn stalled(&self) -> Arc<AtomicBool>;
+}
+
+/// rows of a result set are encoded into a batch that is written at once
+/// with the message that ends the result set, unless batch gets this size
+const MAX_BATCH_ROWS: usize = 256;
+const MAX_BATCH_BYTES: usize = 64 * 1024;
+
+#[derive(Default)]
+struct Batch {
+    messages: Vec<Vec<u8>>,
+    bytes: usize,
+}
+
+impl Batch {
+    fn push(&mut self, message: Vec<u8>) {
+        self.bytes += message.len();
+        self.messages.push(message);
+    }
+
+    fn is_full(&self) -> bool {
+        self.messages.len() >= MAX_BATCH_ROWS || self.bytes >= MAX_BATCH_BYTES
+    }
+
+    fn take(&mut self) -> Vec<Vec<u8>> {
+        self.bytes = 0;
+        std::mem::take(&mut self.messages)
+    }
 }
 
 struct ResponseSender<RW: AsyncRead + AsyncWrite + Unpin> {
+    conn_id: ConnId,
     #[allow(dead_code)]
     properties: Props,
     channel: Arc<AsyncMutex<Channel<RW>>>,
+    protocol_trace: Arc<AtomicBool>,
+    stalled: Arc<AtomicBool>,
+    send_timeout: Option<Duration>,
+    batch: Mutex<Batch>,
 }
 
 impl<RW: AsyncRead + AsyncWrite + Unpin> ResponseSender<RW> {
     /// Creates new Connection with properties and read-write socket
-    pub(crate) fn new(properties: Props, channel: Arc<AsyncMutex<Channel<RW>>>) -> ResponseSender<RW> {
-        ResponseSender { properties, channel }
+    pub(crate) fn new(
+        conn_id: ConnId,
+        properties: Props,
+        channel: Arc<AsyncMutex<Channel<RW>>>,
+        protocol_trace: Arc<AtomicBool>,
+        stalled: Arc<AtomicBool>,
+        send_timeout: Option<Duration>,
+    ) -> ResponseSender<RW> {
+        ResponseSender {
+            conn_id,
+            properties,
+            channel,
+            protocol_trace,
+            stalled,
+            send_timeout,
+            batch: Mutex::new(Batch::default()),
+        }
+    }
+
+    /// blocks until client reads messages, so a statement that produces rows
+    /// faster than client reads them is paused. Once client does not read them
+    /// during `send_timeout` the connection is marked as stalled, which aborts
+    /// the statement, and the rest of messages is dropped, as the stream can't
+    /// be framed anymore and the connection is going to be closed
+    fn write(&self, messages: Vec<Vec<u8>>) -> io::Result<()> {
+        if messages.is_empty() || self.stalled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let written = block_on(async {
+            let mut channel = self.channel.lock().await;
+            let write = write_all_vectored(&mut *channel, &messages);
+            match self.send_timeout {
+                None => write.await,
+                Some(timeout) => {
+                    future::or(write, async {
+                        Timer::after(timeout).await;
+                        Err(io::Error::from(io::ErrorKind::TimedOut))
+                    })
+                    .await
+                }
+            }
+        });
+        match written {
+            Err(error) if error.kind() == io::ErrorKind::TimedOut => {
+                log::warn!(
+                    "connection-{} client does not read results for {:?}, statement is aborted",
+                    self.conn_id,
+                    self.send_timeout.unwrap_or_default()
+                );
+                self.stalled.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            written => written,
+        }
     }
 }
 
+/// writes messages with as few calls to the socket as it accepts
+async fn write_all_vectored<W: AsyncWrite + Unpin>(writer: &mut W, messages: &[Vec<u8>]) -> io::Result<()> {
+    let mut message = 0;
+    let mut offset = 0;
+    while message < messages.len() {
+        let slices = iter::once(IoSlice::new(&messages[message][offset..]))
+            .chain(messages[message + 1..].iter().map(|next| IoSlice::new(next)))
+            .collect::<Vec<IoSlice>>();
+        let mut written = writer.write_vectored(&slices).await?;
+        if written == 0 {
+            return Err(io::Error::from(io::ErrorKind::WriteZero));
+        }
+        while message < messages.len() && written >= messages[message].len() - offset {
+            written -= messages[message].len() - offset;
+            message += 1;
+            offset = 0;
+        }
+        offset += written;
+    }
+    Ok(())
+}
+
 impl<RW: AsyncRead + AsyncWrite + Unpin> Sender for ResponseSender<RW> {
     fn flush(&self) -> io::Result<()> {
+        let messages = self.batch.lock().expect("batch lock is not poisoned").take();
+        self.write(messages)?;
+        if self.stalled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
         block_on(async {
             self.channel.lock().await.flush().await.expect("OK");
         });
@@ -329,20 +623,44 @@ impl<RW: AsyncRead + AsyncWrite + Unpin> Sender for ResponseSender<RW> {
     }
 
     fn send(&self, query_result: QueryResult) -> io::Result<()> {
-        block_on(async {
-            let message: BackendMessage = match query_result {
-                Ok(event) => event.into(),
-                Err(error) => error.into(),
-            };
-            log::debug!("response message {:?}", message);
-            self.channel
-                .lock()
-                .await
-                .write_all(message.as_vec().as_slice())
-                .await
-                .expect("OK");
+        let (bytes, row) = match query_result {
+            Ok(QueryEvent::Notice(notice)) => {
+                log::debug!("response notice {:?}", notice);
+                let bytes = notice.as_vec();
+                if self.protocol_trace.load(Ordering::Relaxed) {
+                    trace(self.conn_id, 'B', bytes.len(), &notice);
+                }
+                (bytes, false)
+            }
+            Ok(QueryEvent::NullableDataRow(values)) => {
+                log::debug!("response data row {:?}", values);
+                let bytes = results::nullable_data_row(&values);
+                if self.protocol_trace.load(Ordering::Relaxed) {
+                    trace(self.conn_id, 'B', bytes.len(), &values);
+                }
+                (bytes, true)
+            }
+            query_result => {
+                let message: BackendMessage = match query_result {
+                    Ok(event) => event.into(),
+                    Err(error) => error.into(),
+                };
+                log::debug!("response message {:?}", message);
+                let bytes = message.as_vec();
+                if self.protocol_trace.load(Ordering::Relaxed) {
+                    trace(self.conn_id, 'B', bytes.len(), &message);
+                }
+                (bytes, matches!(message, BackendMessage::DataRow(_)))
+            }
+        };
+        let mut batch = self.batch.lock().expect("batch lock is not poisoned");
+        batch.push(bytes);
+        if !row || batch.is_full() {
+            let messages = batch.take();
+            drop(batch);
+            self.write(messages)?;
             log::trace!("end of the command is sent");
-        });
+        }
         Ok(())
     }
 }
@@ -390,6 +708,17 @@ impl<RW: AsyncRead + AsyncWrite + Unpin> AsyncWrite for Channel<RW> {
         }
     }
 
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Channel::Plain(tcp) => Pin::new(tcp).poll_write_vectored(cx, bufs),
+            Channel::Secure(tls) => Pin::new(tls).poll_write_vectored(cx, bufs),
+        }
+    }
+
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         match self.get_mut() {
             Channel::Plain(tcp) => Pin::new(tcp).poll_flush(cx),
@@ -405,5 +734,35 @@ impl<RW: AsyncRead + AsyncWrite + Unpin> AsyncWrite for Channel<RW> {
     }
 }
 
+/// decodes frontend messages from `bytes` the same way as a connection does
+/// till the end of them or the first message that could not be decoded. It
+/// is an entry point for fuzzing the decoder, so it must not panic whatever
+/// the bytes are
+pub fn decode_frontend_messages(bytes: &[u8]) -> Vec<Result<Command>> {
+    let channel = Arc::new(AsyncMutex::new(Channel::Plain(futures_lite::io::Cursor::new(
+        bytes.to_vec(),
+    ))));
+    let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 1)));
+    let conn_id = match conn_supervisor.lock().unwrap().alloc() {
+        Ok((conn_id, _secret_key)) => conn_id,
+        Err(error) => return vec![Err(error)],
+    };
+    let mut receiver = RequestReceiver::new(conn_id, vec![], channel, conn_supervisor);
+    let mut commands = vec![];
+    block_on(async {
+        loop {
+            match receiver.receive().await {
+                Ok(Ok(Command::Terminate)) | Err(_) => break,
+                Ok(Err(error)) => {
+                    commands.push(Err(error));
+                    break;
+                }
+                Ok(Ok(command)) => commands.push(Ok(command)),
+            }
+        }
+    });
+    commands
+}
+
 #[cfg(test)]
 mod tests;
@@ -18,6 +18,7 @@ use super::{
 };
 use crate::{accept_client_request, ClientRequest, ConnSupervisor, Encryption, Error, ProtocolConfiguration};
 use futures_lite::future::block_on;
+use pg_model::{hba::HostBasedAccess, results::QueryError};
 use pg_wire::BackendMessage;
 
 use std::{
@@ -289,3 +290,94 @@ fn verification_failed_cancel_request_connection() {
         assert!(matches!(result, Ok(Err(Error::VerificationFailed))));
     });
 }
+
+#[test]
+fn trusted_client_is_not_asked_for_password() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![
+            pg_frontend::Message::Setup(vec![("user", "username"), ("database", "database_name")])
+                .as_vec()
+                .as_slice(),
+            &[],
+        ]);
+
+        let config = ProtocolConfiguration::none()
+            .with_hba(HostBasedAccess::parse("host all all 127.0.0.1/32 trust").expect("valid rules"));
+        let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+
+        let result = accept_client_request(
+            test_case.clone(),
+            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 8080)),
+            &config,
+            conn_supervisor,
+        )
+        .await;
+
+        assert!(matches!(result, Ok(Ok(ClientRequest::Connection(..)))));
+
+        let actual_content = test_case.read_result().await;
+        let expected_content = BackendMessage::AuthenticationOk.as_vec();
+        assert_eq!(actual_content[..expected_content.len()], expected_content[..]);
+    });
+}
+
+#[test]
+fn client_without_matching_rule_is_refused() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![
+            pg_frontend::Message::Setup(vec![("user", "username"), ("database", "database_name")])
+                .as_vec()
+                .as_slice(),
+            &[],
+        ]);
+
+        let config = ProtocolConfiguration::none()
+            .with_hba(HostBasedAccess::parse("host all all 10.0.0.0/8 trust").expect("valid rules"));
+        let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+
+        let result = accept_client_request(
+            test_case.clone(),
+            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 8080)),
+            &config,
+            conn_supervisor,
+        )
+        .await;
+
+        assert!(matches!(result, Err(_)));
+
+        let actual_content = test_case.read_result().await;
+        let expected: BackendMessage = QueryError::no_hba_entry("127.0.0.1", "username", "database_name").into();
+        assert_eq!(actual_content, expected.as_vec());
+    });
+}
+
+#[test]
+fn rejected_client_is_refused() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![
+            pg_frontend::Message::Setup(vec![("user", "username")])
+                .as_vec()
+                .as_slice(),
+            &[],
+        ]);
+
+        let config = ProtocolConfiguration::none().with_hba(
+            HostBasedAccess::parse("host all username all reject\nhost all all all trust").expect("valid rules"),
+        );
+        let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+
+        let result = accept_client_request(
+            test_case.clone(),
+            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 8080)),
+            &config,
+            conn_supervisor,
+        )
+        .await;
+
+        assert!(matches!(result, Err(_)));
+
+        let actual_content = test_case.read_result().await;
+        let expected: BackendMessage = QueryError::hba_rejects_connection("127.0.0.1", "username", "username").into();
+        assert_eq!(actual_content, expected.as_vec());
+    });
+}
@@ -12,12 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::{Arc, Mutex};
+use std::sync::{atomic::Ordering, Arc, Mutex};
 
 use async_mutex::Mutex as AsyncMutex;
 use futures_lite::future::block_on;
 
-use crate::{tests::async_io::TestCase, Channel, Command, ConnSupervisor, Receiver, RequestReceiver};
+use crate::{
+    decode_frontend_messages, tests::async_io::TestCase, Channel, Command, ConnSupervisor, Receiver, RequestReceiver,
+};
+use pg_model::results::QueryError;
 
 #[cfg(test)]
 mod read_query {
@@ -37,6 +40,30 @@ mod read_query {
         });
     }
 
+    #[test]
+    fn read_query_with_protocol_trace() {
+        block_on(async {
+            let test_case = TestCase::with_content(vec![&[81], &[0, 0, 0, 14], b"select 1;\0"]);
+            let channel = Arc::new(AsyncMutex::new(Channel::Plain(test_case.clone())));
+            let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+            let (conn_id, _) = conn_supervisor.lock().unwrap().alloc().unwrap();
+            let mut receiver = RequestReceiver::new(conn_id, vec![], channel, conn_supervisor);
+            let protocol_trace = receiver.protocol_trace();
+            assert!(!protocol_trace.load(Ordering::SeqCst));
+
+            protocol_trace.store(true, Ordering::SeqCst);
+
+            assert!(receiver.protocol_trace().load(Ordering::SeqCst));
+            let query = receiver.receive().await.expect("no io errors");
+            assert_eq!(
+                query,
+                Ok(Command::Query {
+                    sql: "select 1;".to_owned()
+                })
+            );
+        });
+    }
+
     #[test]
     fn read_query_successfully() {
         block_on(async {
@@ -56,6 +83,99 @@ mod read_query {
         });
     }
 
+    #[test]
+    fn read_close_commands() {
+        block_on(async {
+            let test_case =
+                TestCase::with_content(vec![&[67], &[0, 0, 0, 8], b"Ss1\0", &[67], &[0, 0, 0, 8], b"Pp1\0"]);
+            let channel = Arc::new(AsyncMutex::new(Channel::Plain(test_case.clone())));
+            let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+            let (conn_id, _) = conn_supervisor.lock().unwrap().alloc().unwrap();
+            let mut receiver = RequestReceiver::new(conn_id, vec![], channel, conn_supervisor);
+
+            let close_statement = receiver.receive().await.expect("no io errors");
+            assert_eq!(close_statement, Ok(Command::CloseStatement { name: "s1".to_owned() }));
+            let close_portal = receiver.receive().await.expect("no io errors");
+            assert_eq!(close_portal, Ok(Command::ClosePortal { name: "p1".to_owned() }));
+        });
+    }
+
+    #[test]
+    fn query_with_invalid_utf8() {
+        block_on(async {
+            let test_case = TestCase::with_content(vec![
+                &[81],
+                &[0, 0, 0, 16],
+                &[115, 101, 108, 101, 99, 116, 32, 39, 0xC3, 0x28, 39, 0],
+                &[88],
+                &[0, 0, 0, 4],
+            ]);
+            let channel = Arc::new(AsyncMutex::new(Channel::Plain(test_case)));
+            let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+            let (conn_id, _) = conn_supervisor.lock().unwrap().alloc().unwrap();
+            let mut receiver = RequestReceiver::new(conn_id, vec![], channel, conn_supervisor);
+
+            let query = receiver.receive().await.expect("no io errors");
+            assert_eq!(
+                query,
+                Ok(Command::Reject {
+                    error: QueryError::invalid_byte_sequence("UTF8", &[0xC3, 0x28]),
+                    simple_query: true
+                })
+            );
+
+            let query = receiver.receive().await.expect("no io errors");
+            assert_eq!(query, Ok(Command::Terminate));
+        });
+    }
+
+    #[test]
+    fn parse_with_invalid_utf8() {
+        block_on(async {
+            let test_case = TestCase::with_content(vec![
+                &[80],
+                &[0, 0, 0, 16],
+                &[0, 115, 101, 108, 101, 99, 116, 32, 0xFF, 0, 0, 0],
+            ]);
+            let channel = Arc::new(AsyncMutex::new(Channel::Plain(test_case)));
+            let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+            let (conn_id, _) = conn_supervisor.lock().unwrap().alloc().unwrap();
+            let mut receiver = RequestReceiver::new(conn_id, vec![], channel, conn_supervisor);
+
+            let query = receiver.receive().await.expect("no io errors");
+            assert_eq!(
+                query,
+                Ok(Command::Reject {
+                    error: QueryError::invalid_byte_sequence("UTF8", &[0xFF]),
+                    simple_query: false
+                })
+            );
+        });
+    }
+
+    #[test]
+    fn message_shorter_than_its_length() {
+        block_on(async {
+            let test_case = TestCase::with_content(vec![&[81], &[0, 0, 0, 2], &[88], &[0, 0, 0, 4]]);
+            let channel = Arc::new(AsyncMutex::new(Channel::Plain(test_case)));
+            let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+            let (conn_id, _) = conn_supervisor.lock().unwrap().alloc().unwrap();
+            let mut receiver = RequestReceiver::new(conn_id, vec![], channel, conn_supervisor);
+
+            let query = receiver.receive().await.expect("no io errors");
+            assert_eq!(
+                query,
+                Ok(Command::Reject {
+                    error: QueryError::invalid_message_length(2),
+                    simple_query: false
+                })
+            );
+
+            let query = receiver.receive().await.expect("no io errors");
+            assert_eq!(query, Ok(Command::Terminate));
+        });
+    }
+
     #[test]
     fn client_disconnected_immediately() {
         block_on(async {
@@ -70,3 +190,46 @@ mod read_query {
         });
     }
 }
+
+#[cfg(test)]
+mod decode_frontend_messages {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        assert_eq!(decode_frontend_messages(&[]), vec![]);
+    }
+
+    #[test]
+    fn several_messages() {
+        let mut bytes = vec![81, 0, 0, 0, 14];
+        bytes.extend_from_slice(b"select 1;\0");
+        bytes.extend_from_slice(&[83, 0, 0, 0, 4]);
+
+        assert_eq!(
+            decode_frontend_messages(&bytes),
+            vec![
+                Ok(Command::Query {
+                    sql: "select 1;".to_owned()
+                }),
+                Ok(Command::Continue)
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_message() {
+        assert_eq!(decode_frontend_messages(&[81, 0, 0, 0, 14, 115]), vec![]);
+    }
+
+    #[test]
+    fn huge_declared_length() {
+        assert_eq!(
+            decode_frontend_messages(&[81, 0x7f, 0xff, 0xff, 0xff, 115]),
+            vec![Ok(Command::Reject {
+                error: QueryError::invalid_message_length(0x7fff_ffff),
+                simple_query: false
+            })]
+        );
+    }
+}
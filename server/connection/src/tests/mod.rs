@@ -19,6 +19,8 @@ mod async_io;
 mod connection;
 #[cfg(test)]
 mod pg_frontend;
+#[cfg(test)]
+mod response_sender;
 
 // tests/fixtures/identity.pfx dumped and stored here for tests
 fn certificate_content() -> Vec<u8> {
@@ -0,0 +1,213 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use async_mutex::Mutex as AsyncMutex;
+use futures_lite::{future::block_on, AsyncRead, AsyncWrite};
+
+use crate::{
+    tests::async_io::TestCase, write_all_vectored, Channel, Command, ConnSupervisor, Receiver, RequestReceiver,
+    ResponseSender, Sender, MAX_BATCH_ROWS,
+};
+use pg_model::results::{self, QueryEvent, QueryNotice};
+use pg_wire::BackendMessage;
+
+fn sender(test_case: &TestCase) -> ResponseSender<TestCase> {
+    ResponseSender::new(
+        1,
+        vec![],
+        Arc::new(AsyncMutex::new(Channel::Plain(test_case.clone()))),
+        Arc::new(AtomicBool::new(false)),
+        Arc::new(AtomicBool::new(false)),
+        None,
+    )
+}
+
+fn row(value: &str) -> Vec<u8> {
+    BackendMessage::DataRow(vec![value.to_owned()]).as_vec()
+}
+
+#[test]
+fn rows_are_sent_with_end_of_result() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![]);
+        let sender = sender(&test_case);
+
+        sender
+            .send(Ok(QueryEvent::DataRow(vec!["1".to_owned()])))
+            .expect("sent");
+        sender
+            .send(Ok(QueryEvent::DataRow(vec!["2".to_owned()])))
+            .expect("sent");
+        assert_eq!(test_case.read_result().await, Vec::<u8>::new());
+
+        sender.send(Ok(QueryEvent::RecordsSelected(2))).expect("sent");
+
+        let mut expected = row("1");
+        expected.extend(row("2"));
+        let end: BackendMessage = QueryEvent::RecordsSelected(2).into();
+        expected.extend(end.as_vec());
+        assert_eq!(test_case.read_result().await, expected);
+    });
+}
+
+#[test]
+fn full_batch_is_sent() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![]);
+        let sender = sender(&test_case);
+
+        for _ in 0..MAX_BATCH_ROWS {
+            sender
+                .send(Ok(QueryEvent::DataRow(vec!["1".to_owned()])))
+                .expect("sent");
+        }
+
+        assert_eq!(test_case.read_result().await, row("1").repeat(MAX_BATCH_ROWS));
+    });
+}
+
+#[test]
+fn flush_sends_batch() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![]);
+        let sender = sender(&test_case);
+
+        sender
+            .send(Ok(QueryEvent::DataRow(vec!["1".to_owned()])))
+            .expect("sent");
+        sender.flush().expect("flushed");
+
+        assert_eq!(test_case.read_result().await, row("1"));
+    });
+}
+
+#[test]
+fn notice_is_sent_with_its_fields() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![]);
+        let sender = sender(&test_case);
+        let notice = QueryNotice::identifier_truncated("t".repeat(70), "t".repeat(63));
+
+        sender.send(Ok(QueryEvent::Notice(notice.clone()))).expect("sent");
+
+        assert_eq!(test_case.read_result().await, notice.as_vec());
+    });
+}
+
+#[test]
+fn null_values_are_sent_without_bytes() {
+    block_on(async {
+        let test_case = TestCase::with_content(vec![]);
+        let sender = sender(&test_case);
+
+        sender
+            .send(Ok(QueryEvent::NullableDataRow(vec![None, Some("1".to_owned())])))
+            .expect("sent");
+        sender.send(Ok(QueryEvent::RecordsSelected(1))).expect("sent");
+
+        let mut expected = results::nullable_data_row(&[None, Some("1".to_owned())]);
+        let end: BackendMessage = QueryEvent::RecordsSelected(1).into();
+        expected.extend(end.as_vec());
+        assert_eq!(test_case.read_result().await, expected);
+    });
+}
+
+struct Slow(Vec<u8>);
+
+impl AsyncWrite for Slow {
+    fn poll_write(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let len = buf.len().min(3);
+        self.0.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn partially_written_messages() {
+    block_on(async {
+        let mut writer = Slow(vec![]);
+
+        write_all_vectored(&mut writer, &[vec![1, 2], vec![], vec![3, 4, 5, 6, 7], vec![8]])
+            .await
+            .expect("written");
+
+        assert_eq!(writer.0, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    });
+}
+
+struct Stalled;
+
+impl AsyncRead for Stalled {
+    fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, _buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Poll::Pending
+    }
+}
+
+impl AsyncWrite for Stalled {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, _buf: &[u8]) -> Poll<io::Result<usize>> {
+        Poll::Pending
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Pending
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[test]
+fn client_that_does_not_read_results_is_disconnected() {
+    block_on(async {
+        let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(1, 2)));
+        let (conn_id, _) = conn_supervisor.lock().unwrap().alloc().unwrap();
+        let channel = Arc::new(AsyncMutex::new(Channel::Plain(Stalled)));
+        let mut receiver = RequestReceiver::new(conn_id, vec![], channel.clone(), conn_supervisor);
+        let sender = ResponseSender::new(
+            conn_id,
+            vec![],
+            channel,
+            receiver.protocol_trace(),
+            receiver.stalled(),
+            Some(Duration::from_millis(10)),
+        );
+
+        sender.send(Ok(QueryEvent::RecordsSelected(0))).expect("sent");
+        assert!(receiver.stalled().load(Ordering::SeqCst));
+
+        sender.send(Ok(QueryEvent::QueryComplete)).expect("discarded");
+        sender.flush().expect("discarded");
+        assert_eq!(receiver.receive().await.expect("no io errors"), Ok(Command::Terminate));
+    });
+}
@@ -0,0 +1,75 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use node::bench::{self, BenchSession};
+
+const ACCOUNTS: usize = 10_000;
+const BATCH_SIZE: usize = 1_000;
+
+fn sessions() -> Vec<(&'static str, BenchSession)> {
+    let mut sessions = vec![
+        ("in_memory", BenchSession::in_memory()),
+        ("persistent", BenchSession::persistent().expect("session created")),
+    ];
+    for (_, session) in sessions.iter_mut() {
+        bench::create_accounts(session, ACCOUNTS, BATCH_SIZE).expect("accounts created");
+    }
+    sessions
+}
+
+fn point_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("point_lookup");
+    for (storage, mut session) in sessions() {
+        let mut aid = 0;
+        group.bench_function(BenchmarkId::new(storage, ACCOUNTS), |b| {
+            b.iter(|| {
+                aid = aid % ACCOUNTS + 1;
+                session.execute(&bench::point_lookup(aid)).expect("account selected")
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bulk_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bulk_insert");
+    for (storage, mut session) in sessions() {
+        let mut aid = ACCOUNTS + 1;
+        group.bench_function(BenchmarkId::new(storage, BATCH_SIZE), |b| {
+            b.iter(|| {
+                let inserted = session
+                    .execute(&bench::insert_accounts(aid, BATCH_SIZE))
+                    .expect("accounts inserted");
+                aid += BATCH_SIZE;
+                inserted
+            })
+        });
+    }
+    group.finish();
+}
+
+fn analytic_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("analytic_scan");
+    group.sample_size(10);
+    for (storage, mut session) in sessions() {
+        group.bench_function(BenchmarkId::new(storage, ACCOUNTS), |b| {
+            b.iter(|| session.execute(&bench::analytic_scan()).expect("accounts selected"))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(workloads, point_lookup, bulk_insert, analytic_scan);
+criterion_main!(workloads);
@@ -0,0 +1,435 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use hmac::{Hmac, Mac, NewMac};
+use pg_model::ConnId;
+use sha2::Sha256;
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// kind of events written to the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum AuditCategory {
+    /// clients that connected or were refused by host based access rules
+    Authentication,
+    /// successful changes of schemas and tables
+    Ddl,
+    /// statements rejected because they are not allowed to the session
+    Permission,
+}
+
+impl AuditCategory {
+    const ALL: [AuditCategory; 3] = [
+        AuditCategory::Authentication,
+        AuditCategory::Ddl,
+        AuditCategory::Permission,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            AuditCategory::Authentication => "authentication",
+            AuditCategory::Ddl => "ddl",
+            AuditCategory::Permission => "permission",
+        }
+    }
+
+    /// comma separated category names, `all` enables every category
+    pub(crate) fn parse_list(names: &str) -> Result<HashSet<AuditCategory>, String> {
+        let mut categories = HashSet::new();
+        for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if name == "all" {
+                categories.extend(AuditCategory::ALL.iter());
+                continue;
+            }
+            match AuditCategory::ALL.iter().find(|category| category.name() == name) {
+                Some(category) => categories.insert(*category),
+                None => return Err(format!("unknown audit category \"{}\"", name)),
+            };
+        }
+        Ok(categories)
+    }
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+struct Chain {
+    output: Box<dyn Write + Send>,
+    head: Option<PathBuf>,
+    checksum: String,
+}
+
+/// append only log of security relevant events. Every record ends with an
+/// HMAC of its content chained with the checksum of the previous record, so
+/// that an edited, removed or reordered record breaks the chain from that
+/// record on. Checksum of the last record is also kept in the head file,
+/// outside of the log, so that removed trailing records are detected too
+#[derive(Default)]
+pub(crate) struct AuditLog {
+    categories: HashSet<AuditCategory>,
+    key: Vec<u8>,
+    chain: Mutex<Option<Chain>>,
+}
+
+impl AuditLog {
+    /// appends records to the file, the chain continues from the last record
+    /// that the head file has
+    pub(crate) fn open<P: AsRef<Path>, H: AsRef<Path>>(
+        path: P,
+        head: H,
+        key: Vec<u8>,
+        categories: HashSet<AuditCategory>,
+    ) -> io::Result<AuditLog> {
+        let path = path.as_ref();
+        let head = head.as_ref();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(error),
+        };
+        let checksum = match fs::read_to_string(head) {
+            Ok(checksum) => Some(checksum.trim().to_owned()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error),
+        };
+        if let Err(line) = verify(&key, &content, checksum.as_deref()) {
+            log::error!("audit log {:?} was tampered with at line {}", path, line);
+        }
+        let checksum = checksum.unwrap_or_else(|| last_checksum(&content).to_owned());
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut audit_log = AuditLog::new(Box::new(file), key, checksum, categories);
+        if let Some(chain) = audit_log.chain.get_mut().expect("audit log lock is not poisoned") {
+            chain.head = Some(head.to_owned());
+        }
+        Ok(audit_log)
+    }
+
+    fn new(
+        output: Box<dyn Write + Send>,
+        key: Vec<u8>,
+        checksum: String,
+        categories: HashSet<AuditCategory>,
+    ) -> AuditLog {
+        AuditLog {
+            categories,
+            key,
+            chain: Mutex::new(Some(Chain {
+                output,
+                head: None,
+                checksum,
+            })),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self, category: AuditCategory) -> bool {
+        self.categories.contains(&category)
+    }
+
+    pub(crate) fn record(&self, category: AuditCategory, conn_id: Option<ConnId>, user: &str, message: &str) {
+        if !self.is_enabled(category) {
+            return;
+        }
+        let mut chain = self.chain.lock().expect("audit log lock is not poisoned");
+        if let Some(chain) = chain.as_mut() {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let conn = match conn_id {
+                Some(conn_id) => format!("connection-{}", conn_id),
+                None => "-".to_owned(),
+            };
+            let content = format!(
+                "{}\t{}\t{}\t{}\t{}",
+                timestamp,
+                category.name(),
+                conn,
+                escape(user),
+                escape(message)
+            );
+            let checksum = chained(&self.key, &chain.checksum, &content);
+            let written = writeln!(chain.output, "{}\t{}", content, checksum)
+                .and_then(|()| chain.output.flush())
+                .and_then(|()| match &chain.head {
+                    Some(head) => fs::write(head, &checksum),
+                    None => Ok(()),
+                });
+            match written {
+                Ok(()) => chain.checksum = checksum,
+                Err(error) => log::error!("could not write to audit log {:?}", error),
+            }
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// hex encoded HMAC-SHA256 of the previous checksum and the record content
+fn chained(key: &[u8], previous: &str, content: &str) -> String {
+    let mut mac = HmacSha256::new_varkey(key).expect("HMAC accepts keys of any size");
+    mac.update(previous.as_bytes());
+    mac.update(b"\t");
+    mac.update(content.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn last_checksum(content: &str) -> &str {
+    content
+        .lines()
+        .last()
+        .and_then(|line| line.rsplit('\t').next())
+        .unwrap_or_default()
+}
+
+/// checks the chain of records and that the last one has the `head`
+/// checksum, returns number of the first line that does not match its
+/// checksum
+pub(crate) fn verify(key: &[u8], content: &str, head: Option<&str>) -> Result<(), usize> {
+    let mut previous = "";
+    let mut lines = 0;
+    for (index, line) in content.lines().enumerate() {
+        match line.rfind('\t') {
+            Some(at) if chained(key, previous, &line[..at]) == line[at + 1..] => previous = &line[at + 1..],
+            _ => return Err(index + 1),
+        }
+        lines = index + 1;
+    }
+    match head {
+        Some(head) if head != previous => Err(lines + 1),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    pub(crate) struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Output {
+        pub(crate) fn content(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    const KEY: &[u8] = b"secret";
+
+    pub(crate) fn audit_log(categories: &str) -> (AuditLog, Output) {
+        let output = Output::default();
+        let audit_log = AuditLog::new(
+            Box::new(output.clone()),
+            KEY.to_vec(),
+            String::new(),
+            AuditCategory::parse_list(categories).unwrap(),
+        );
+        (audit_log, output)
+    }
+
+    /// category, connection, user and message of every record
+    pub(crate) fn fields(content: &str) -> Vec<Vec<String>> {
+        content
+            .lines()
+            .map(|line| line.split('\t').skip(1).take(4).map(ToOwned::to_owned).collect())
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod categories {
+        use super::*;
+
+        #[test]
+        fn all() {
+            assert_eq!(
+                AuditCategory::parse_list("all"),
+                Ok(AuditCategory::ALL.iter().cloned().collect())
+            );
+        }
+
+        #[test]
+        fn list() {
+            assert_eq!(
+                AuditCategory::parse_list("ddl, permission"),
+                Ok(vec![AuditCategory::Ddl, AuditCategory::Permission]
+                    .into_iter()
+                    .collect())
+            );
+        }
+
+        #[test]
+        fn unknown() {
+            assert_eq!(
+                AuditCategory::parse_list("ddl,queries"),
+                Err("unknown audit category \"queries\"".to_owned())
+            );
+        }
+
+        #[test]
+        fn disabled_category_is_not_recorded() {
+            let (audit_log, output) = audit_log("ddl");
+
+            audit_log.record(AuditCategory::Permission, Some(1), "user", "denied");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+
+            assert_eq!(
+                fields(&output.content()),
+                vec![vec!["ddl", "connection-1", "user", "create schema s"]]
+            );
+        }
+
+        #[test]
+        fn disabled_log() {
+            let audit_log = AuditLog::default();
+
+            assert!(!audit_log.is_enabled(AuditCategory::Ddl));
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+        }
+    }
+
+    #[cfg(test)]
+    mod chain {
+        use super::*;
+
+        #[test]
+        fn records_are_verified() {
+            let (audit_log, output) = audit_log("all");
+
+            audit_log.record(AuditCategory::Authentication, None, "user", "refused");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "drop table t");
+
+            assert_eq!(verify(KEY, &output.content(), None), Ok(()));
+        }
+
+        #[test]
+        fn special_characters_are_escaped() {
+            let (audit_log, output) = audit_log("all");
+
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create table t (\n\tc int\n)");
+
+            assert_eq!(
+                fields(&output.content()),
+                vec![vec!["ddl", "connection-1", "user", "create table t (\\n\\tc int\\n)"]]
+            );
+        }
+
+        #[test]
+        fn edited_record() {
+            let (audit_log, output) = audit_log("all");
+
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "drop schema s");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema t");
+
+            let content = output.content().replace("drop schema s", "drop schema x");
+            assert_eq!(verify(KEY, &content, None), Err(2));
+        }
+
+        #[test]
+        fn removed_record() {
+            let (audit_log, output) = audit_log("all");
+
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "drop schema s");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema t");
+
+            let content = output.content().lines().skip(1).collect::<Vec<&str>>().join("\n");
+            assert_eq!(verify(KEY, &content, None), Err(1));
+        }
+
+        #[test]
+        fn continued_chain() {
+            let (audit_log, output) = audit_log("all");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+            let content = output.content();
+
+            let more = Output::default();
+            let continued = AuditLog::new(
+                Box::new(more.clone()),
+                KEY.to_vec(),
+                last_checksum(&content).to_owned(),
+                AuditCategory::parse_list("all").unwrap(),
+            );
+            continued.record(AuditCategory::Ddl, Some(2), "user", "drop schema s");
+
+            assert_eq!(verify(KEY, &(content + &more.content()), None), Ok(()));
+        }
+
+        #[test]
+        fn another_key() {
+            let (audit_log, output) = audit_log("all");
+
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+
+            assert_eq!(verify(b"another secret", &output.content(), None), Err(1));
+        }
+
+        #[test]
+        fn removed_last_record() {
+            let (audit_log, output) = audit_log("all");
+
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "drop schema s");
+            let content = output.content();
+            let head = last_checksum(&content).to_owned();
+
+            let content = content.lines().take(1).collect::<Vec<&str>>().join("\n");
+            assert_eq!(verify(KEY, &content, Some(&head)), Err(2));
+        }
+
+        #[test]
+        fn head_is_written_outside_of_log() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("audit.log");
+            let head = dir.path().join("audit.head");
+
+            let audit_log =
+                AuditLog::open(&path, &head, KEY.to_vec(), AuditCategory::parse_list("all").unwrap()).unwrap();
+            audit_log.record(AuditCategory::Ddl, Some(1), "user", "create schema s");
+            drop(audit_log);
+            let audit_log =
+                AuditLog::open(&path, &head, KEY.to_vec(), AuditCategory::parse_list("all").unwrap()).unwrap();
+            audit_log.record(AuditCategory::Ddl, Some(2), "user", "drop schema s");
+
+            let content = fs::read_to_string(&path).unwrap();
+            let head = fs::read_to_string(&head).unwrap();
+            assert_eq!(head, last_checksum(&content));
+            assert_eq!(verify(KEY, &content, Some(&head)), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_executor::Executor;
+use async_io::Timer;
+use bigdecimal::BigDecimal;
+use expr_operators::ScalarValue;
+use pg_model::{results::QueryError, ConnId};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+const SLEEP_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// interruption requests to a connection sent by other connections or by a
+/// client with a cancel request
+#[derive(Debug, Default)]
+pub(crate) struct Backend {
+    cancel: AtomicBool,
+    terminate: AtomicBool,
+}
+
+impl Backend {
+    /// error that interrupts current statement, cancel request is reset once
+    /// it is reported
+    pub(crate) fn interruption(&self) -> Option<QueryError> {
+        if self.terminated() {
+            Some(QueryError::admin_shutdown())
+        } else if self.cancel.swap(false, Ordering::SeqCst) {
+            Some(QueryError::query_canceled())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn terminated(&self) -> bool {
+        self.terminate.load(Ordering::SeqCst)
+    }
+
+    /// sleeps for `seconds` unless the statement is interrupted, `executor`
+    /// runs tasks of other connections meanwhile
+    // TODO: the sleep waits on the stack of the executor thread, once a statement
+    //       of other connection sleeps too the one that started first ends only
+    //       after it. Statements have to be futures to be suspended on their own
+    pub(crate) fn sleep(&self, seconds: f64, executor: &Executor) -> Result<(), QueryError> {
+        // longer sleeps could not be represented with `Duration`, anyway they last until interrupted
+        let deadline = Instant::now() + Duration::from_secs_f64(seconds.max(0.0).min(u32::MAX as f64));
+        async_io::block_on(executor.run(async {
+            loop {
+                if let Some(error) = self.interruption() {
+                    return Err(error);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    return Ok(());
+                }
+                Timer::after(SLEEP_CHECK_INTERVAL.min(deadline - now)).await;
+            }
+        }))
+    }
+}
+
+// TODO: pg_locks and logging of lock waits longer than deadlock_timeout need a
+//       lock manager. Statements of all connections run one at a time on the single
+//       executor thread, so there are no relation or row locks to wait for yet
+/// connections served by the node, that could be interrupted from other ones
+#[derive(Debug, Default)]
+pub(crate) struct Backends {
+    backends: Mutex<HashMap<ConnId, Arc<Backend>>>,
+}
+
+impl Backends {
+    pub(crate) fn register(&self, conn_id: ConnId) -> Arc<Backend> {
+        let backend = Arc::new(Backend::default());
+        self.backends.lock().unwrap().insert(conn_id, backend.clone());
+        backend
+    }
+
+    /// removes the connection unless its ID was already given to a new one
+    pub(crate) fn remove(&self, conn_id: ConnId, backend: &Arc<Backend>) {
+        let mut backends = self.backends.lock().unwrap();
+        if backends.get(&conn_id).map(|current| Arc::ptr_eq(current, backend)) == Some(true) {
+            backends.remove(&conn_id);
+        }
+    }
+
+    /// requests cancellation of a running statement, `false` if there is no
+    /// such connection
+    pub(crate) fn cancel(&self, conn_id: ConnId) -> bool {
+        match self.backends.lock().unwrap().get(&conn_id) {
+            Some(backend) => {
+                backend.cancel.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// requests termination of a connection, `false` if there is no such
+    /// connection
+    pub(crate) fn terminate(&self, conn_id: ConnId) -> bool {
+        match self.backends.lock().unwrap().get(&conn_id) {
+            Some(backend) => {
+                backend.terminate.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// server administration functions
+#[derive(Debug, PartialEq)]
+pub(crate) enum AdminFunction {
+    Sleep(f64),
+    BackendPid,
+    CancelBackend(ConnId),
+    TerminateBackend(ConnId),
+}
+
+impl AdminFunction {
+    /// administration function called with the arguments of types its
+    /// signature has, `None` if the function isn't an administration one
+    pub(crate) fn from_call(name: &str, args: &[ScalarValue]) -> Result<Option<AdminFunction>, QueryError> {
+        match (name, args) {
+            ("pg_sleep", [ScalarValue::Number(seconds)]) => {
+                Ok(seconds.to_string().parse().ok().map(AdminFunction::Sleep))
+            }
+            ("pg_backend_pid", []) => Ok(Some(AdminFunction::BackendPid)),
+            ("pg_cancel_backend", [ScalarValue::Number(pid)]) => Ok(Some(AdminFunction::CancelBackend(conn_id(pid)?))),
+            ("pg_terminate_backend", [ScalarValue::Number(pid)]) => {
+                Ok(Some(AdminFunction::TerminateBackend(conn_id(pid)?)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// process ID that could be given to a connection
+fn conn_id(pid: &BigDecimal) -> Result<ConnId, QueryError> {
+    let conn_id = if pid.is_integer() {
+        pid.with_scale(0).to_string().parse::<ConnId>().ok()
+    } else {
+        None
+    };
+    match conn_id {
+        Some(conn_id) if conn_id >= 0 => Ok(conn_id),
+        _ => Err(QueryError::invalid_parameter_value(format!(
+            "invalid process ID: {}",
+            pid
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn cancel_running_sleep() {
+        let backends = Backends::default();
+        let backend = backends.register(1);
+
+        assert!(backends.cancel(1));
+
+        assert_eq!(backend.sleep(10.0, &Executor::new()), Err(QueryError::query_canceled()));
+        assert_eq!(backend.sleep(0.0, &Executor::new()), Ok(()));
+    }
+
+    #[test]
+    fn terminate_connection() {
+        let backends = Backends::default();
+        let backend = backends.register(1);
+
+        assert!(backends.terminate(1));
+
+        assert!(backend.terminated());
+        assert_eq!(backend.interruption(), Some(QueryError::admin_shutdown()));
+    }
+
+    #[test]
+    fn id_reused_by_new_connection() {
+        let backends = Backends::default();
+        let old = backends.register(1);
+        backends.register(1);
+        backends.remove(1, &old);
+
+        assert!(backends.cancel(1));
+    }
+
+    #[test]
+    fn removed_connection() {
+        let backends = Backends::default();
+        let backend = backends.register(1);
+        backends.remove(1, &backend);
+
+        assert!(!backends.cancel(1));
+        assert!(!backends.terminate(1));
+    }
+
+    #[test]
+    fn sleep_runs_other_tasks() {
+        let backends = Backends::default();
+        let backend = backends.register(1);
+        let executor = Executor::new();
+        let task = executor.spawn(async { 1 });
+
+        assert_eq!(backend.sleep(0.05, &executor), Ok(()));
+
+        assert_eq!(
+            futures_lite::future::block_on(futures_lite::future::poll_once(task)),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn admin_function_call() {
+        assert_eq!(
+            AdminFunction::from_call("pg_cancel_backend", &[ScalarValue::Number(BigDecimal::from(2))]),
+            Ok(Some(AdminFunction::CancelBackend(2)))
+        );
+        assert_eq!(
+            AdminFunction::from_call("pg_backend_pid", &[]),
+            Ok(Some(AdminFunction::BackendPid))
+        );
+        assert_eq!(AdminFunction::from_call("version", &[]), Ok(None));
+    }
+
+    #[test]
+    fn invalid_process_ids() {
+        for pid in &["-1", "1.5", "2147483648"] {
+            assert_eq!(
+                AdminFunction::from_call(
+                    "pg_terminate_backend",
+                    &[ScalarValue::Number(BigDecimal::from_str(pid).expect("number"))]
+                ),
+                Err(QueryError::invalid_parameter_value(format!(
+                    "invalid process ID: {}",
+                    pid
+                )))
+            );
+        }
+        assert_eq!(
+            AdminFunction::from_call(
+                "pg_cancel_backend",
+                &[ScalarValue::Number(BigDecimal::from_str("3.0").expect("number"))]
+            ),
+            Ok(Some(AdminFunction::CancelBackend(3)))
+        );
+    }
+}
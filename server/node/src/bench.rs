@@ -0,0 +1,200 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{query_engine::QueryEngine, Node};
+use catalog::InMemoryDatabase;
+use connection::Sender;
+use data_manager::DatabaseHandle;
+use pg_model::{
+    results::{QueryError, QueryEvent, QueryResult},
+    Command,
+};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+use tempfile::TempDir;
+
+/// rows of `bench.accounts` of a single branch as in TPC-B
+pub const ACCOUNTS_PER_BRANCH: usize = 100_000;
+
+/// session of a node that runs statements of benchmark workloads, results are
+/// counted instead of being sent to a client
+pub struct BenchSession {
+    query_engine: QueryEngine<InMemoryDatabase>,
+    outcome: Arc<Outcome>,
+    _data_dir: Option<TempDir>,
+}
+
+impl BenchSession {
+    pub fn in_memory() -> BenchSession {
+        BenchSession::new(DatabaseHandle::in_memory(), None)
+    }
+
+    /// node storage is kept in a temporary directory removed with the session
+    pub fn persistent() -> io::Result<BenchSession> {
+        let data_dir = tempfile::tempdir()?;
+        let storage = DatabaseHandle::persistent(data_dir.path().join("root_directory"))
+            .map_err(|()| io::Error::new(io::ErrorKind::Other, "storage could not be initialized"))?;
+        Ok(BenchSession::new(storage, Some(data_dir)))
+    }
+
+    fn new(storage: DatabaseHandle, data_dir: Option<TempDir>) -> BenchSession {
+        let outcome = Arc::new(Outcome::default());
+        BenchSession {
+            query_engine: Node::new(storage).query_engine(outcome.clone()),
+            outcome,
+            _data_dir: data_dir,
+        }
+    }
+
+    /// number of selected, inserted, updated or deleted rows
+    pub fn execute(&mut self, sql: &str) -> Result<usize, QueryError> {
+        if self
+            .query_engine
+            .execute(Command::Query { sql: sql.to_owned() })
+            .is_err()
+        {
+            log::debug!("bench session is terminated");
+        }
+        self.outcome.take()
+    }
+}
+
+#[derive(Default)]
+struct Outcome(Mutex<Option<Result<usize, QueryError>>>);
+
+impl Outcome {
+    fn take(&self) -> Result<usize, QueryError> {
+        self.0.lock().unwrap().take().unwrap_or(Ok(0))
+    }
+}
+
+impl Sender for Outcome {
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn send(&self, query_result: QueryResult) -> io::Result<()> {
+        let rows = match query_result {
+            Ok(QueryEvent::RecordsSelected(rows))
+            | Ok(QueryEvent::RecordsInserted(rows))
+            | Ok(QueryEvent::RecordsUpdated(rows))
+            | Ok(QueryEvent::RecordsDeleted(rows)) => Ok(rows),
+            Ok(_) => return Ok(()),
+            Err(error) => Err(error),
+        };
+        let mut outcome = self.0.lock().unwrap();
+        if !matches!(*outcome, Some(Err(_))) {
+            *outcome = Some(rows);
+        }
+        Ok(())
+    }
+}
+
+/// creates `bench.accounts` table with `accounts` rows inserted by `batch_size`
+/// rows at once
+pub fn create_accounts(session: &mut BenchSession, accounts: usize, batch_size: usize) -> Result<(), QueryError> {
+    session.execute("create schema bench;")?;
+    session.execute("create table bench.accounts (aid integer, bid integer, abalance integer, filler varchar(84));")?;
+    let mut aid = 1;
+    while aid <= accounts {
+        let count = batch_size.min(accounts - aid + 1);
+        session.execute(&insert_accounts(aid, count))?;
+        aid += count;
+    }
+    Ok(())
+}
+
+/// `INSERT` of `count` accounts with consecutive ids starting from `first`
+pub fn insert_accounts(first: usize, count: usize) -> String {
+    let rows = (first..first + count)
+        .map(|aid| {
+            format!(
+                "({}, {}, {}, '{:0>84}')",
+                aid,
+                (aid - 1) / ACCOUNTS_PER_BRANCH + 1,
+                aid * 7 % 10_000,
+                aid
+            )
+        })
+        .collect::<Vec<String>>();
+    format!("insert into bench.accounts values {};", rows.join(", "))
+}
+
+/// selects a single account by its id
+pub fn point_lookup(aid: usize) -> String {
+    format!("select * from bench.accounts where aid = {};", aid)
+}
+
+/// reads balances of all accounts
+// TODO: aggregates and GROUP BY are not supported, balances can't be summed
+//       up by branches as analytic queries usually do
+pub fn analytic_scan() -> String {
+    "select aid, bid, abalance from bench.accounts;".to_owned()
+}
+
+// TODO: join workload of accounts with branches and tellers as in TPC-B needs
+//       joins that the analyzer rejects for now
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workloads(mut session: BenchSession) {
+        assert_eq!(create_accounts(&mut session, 25, 10), Ok(()));
+
+        assert_eq!(session.execute(&point_lookup(13)), Ok(1));
+        assert_eq!(session.execute(&point_lookup(26)), Ok(0));
+        assert_eq!(session.execute(&analytic_scan()), Ok(25));
+        assert_eq!(session.execute(&insert_accounts(26, 5)), Ok(5));
+        assert_eq!(session.execute(&analytic_scan()), Ok(30));
+    }
+
+    #[test]
+    fn in_memory() {
+        workloads(BenchSession::in_memory());
+    }
+
+    #[test]
+    fn persistent() {
+        workloads(BenchSession::persistent().expect("session created"));
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        let mut session = BenchSession::in_memory();
+
+        assert_eq!(
+            session.execute(&analytic_scan()),
+            Err(QueryError::schema_does_not_exist("bench"))
+        );
+    }
+
+    #[test]
+    fn accounts_of_branches() {
+        assert_eq!(
+            insert_accounts(ACCOUNTS_PER_BRANCH, 2),
+            format!(
+                "insert into bench.accounts values ({}, 1, {}, '{:0>84}'), ({}, 2, {}, '{:0>84}');",
+                ACCOUNTS_PER_BRANCH,
+                ACCOUNTS_PER_BRANCH * 7 % 10_000,
+                ACCOUNTS_PER_BRANCH,
+                ACCOUNTS_PER_BRANCH + 1,
+                (ACCOUNTS_PER_BRANCH + 1) * 7 % 10_000,
+                ACCOUNTS_PER_BRANCH + 1
+            )
+        );
+    }
+}
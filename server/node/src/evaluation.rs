@@ -0,0 +1,344 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    backends::{AdminFunction, Backend, Backends},
+    session_functions::SessionFunction,
+    statistics::single_select,
+    GLOBAL,
+};
+use analysis_tree::ProjectionTreeNode;
+use ast::{
+    operations::{BinaryOp, ScalarOp},
+    values,
+};
+use bigdecimal::{BigDecimal, Zero};
+use expr_eval::{EvalError, PatternCache, StaticExpressionEvaluation};
+use expr_operators::{Arithmetic, Bitwise, Bool, FunctionKind, Operation, Operator, ScalarValue, StringOp};
+use pg_model::{results::QueryError, session::Session, ConnId};
+use pg_wire::PgType;
+use repr::Datum;
+use sql_ast::{Select, Statement};
+use types::GeneralType;
+
+/// whether the statement is a select that doesn't read any table
+pub(crate) fn without_table(statement: &Statement) -> bool {
+    matches!(single_select(statement), Some(Select { from, .. }) if from.is_empty())
+}
+
+/// computes items of a select without tables. Functions are resolved by
+/// the analyzer, some of them read or change state of the session or of
+/// other connections
+pub(crate) struct Evaluation<'e> {
+    session: &'e mut Session<Statement>,
+    conn_id: ConnId,
+    backend: &'e Backend,
+    backends: &'e Backends,
+    patterns: PatternCache,
+    changed_settings: Vec<String>,
+}
+
+impl<'e> Evaluation<'e> {
+    pub(crate) fn new(
+        session: &'e mut Session<Statement>,
+        conn_id: ConnId,
+        backend: &'e Backend,
+        backends: &'e Backends,
+    ) -> Evaluation<'e> {
+        Evaluation {
+            session,
+            conn_id,
+            backend,
+            backends,
+            patterns: PatternCache::default(),
+            changed_settings: vec![],
+        }
+    }
+
+    /// lower case names of settings `set_config()` changed
+    pub(crate) fn changed_settings(self) -> Vec<String> {
+        self.changed_settings
+    }
+
+    pub(crate) fn eval(&mut self, node: &ProjectionTreeNode) -> Result<ScalarValue, QueryError> {
+        match node {
+            ProjectionTreeNode::Item(Operator::Const(value)) => Ok(value.clone()),
+            ProjectionTreeNode::Item(Operator::Param(index)) => Err(QueryError::syntax_error(format!(
+                "there is no parameter ${}",
+                index + 1
+            ))),
+            ProjectionTreeNode::Item(Operator::Column { .. }) => unreachable!("select without tables has no columns"),
+            ProjectionTreeNode::Operation { left, op, right } => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                operation(op, left, right)
+            }
+            // TODO: aggregates of a select without tables are computed over its single row
+            ProjectionTreeNode::Function { signature, .. } if signature.kind() == FunctionKind::Aggregate => Err(
+                QueryError::feature_not_supported(format!("aggregate function {} without tables", signature)),
+            ),
+            ProjectionTreeNode::Function { signature, args, .. } => {
+                let mut values = vec![];
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                // all of the functions are strict, `NULL` argument gives `NULL` value
+                if values.contains(&ScalarValue::Null) {
+                    return Ok(ScalarValue::Null);
+                }
+                self.function(signature.name(), &values)
+            }
+        }
+    }
+
+    fn function(&mut self, name: &str, args: &[ScalarValue]) -> Result<ScalarValue, QueryError> {
+        if let Some(function) = SessionFunction::from_call(name, args) {
+            let value = function.value(self.session)?;
+            if let SessionFunction::SetConfig { name: setting, .. } = function {
+                self.changed_settings.push(setting.to_lowercase());
+            }
+            return Ok(value);
+        }
+        if let Some(function) = AdminFunction::from_call(name, args)? {
+            return self.admin_function(function);
+        }
+        match (name, args) {
+            ("abs", [ScalarValue::Number(value)]) => Ok(ScalarValue::Number(value.abs())),
+            ("length", [ScalarValue::String(value)]) => {
+                Ok(ScalarValue::Number(BigDecimal::from(value.chars().count() as u64)))
+            }
+            ("lower", [ScalarValue::String(value)]) => Ok(ScalarValue::String(value.to_lowercase())),
+            ("upper", [ScalarValue::String(value)]) => Ok(ScalarValue::String(value.to_uppercase())),
+            ("regexp_replace", [text, pattern, replacement]) => self.regexp_replace(text, pattern, replacement, None),
+            ("regexp_replace", [text, pattern, replacement, flags]) => {
+                self.regexp_replace(text, pattern, replacement, Some(flags))
+            }
+            ("regexp_matches", [text, pattern]) => self.regexp_matches(text, pattern, None),
+            ("regexp_matches", [text, pattern, flags]) => self.regexp_matches(text, pattern, Some(flags)),
+            _ => Err(QueryError::feature_not_supported(format!(
+                "function {} without tables",
+                name
+            ))),
+        }
+    }
+
+    fn admin_function(&self, function: AdminFunction) -> Result<ScalarValue, QueryError> {
+        match function {
+            AdminFunction::Sleep(seconds) => self.backend.sleep(seconds, &GLOBAL).map(|()| ScalarValue::Null),
+            AdminFunction::BackendPid => Ok(ScalarValue::Number(BigDecimal::from(self.conn_id))),
+            AdminFunction::CancelBackend(pid) => Ok(signaled(self.backends.cancel(pid), pid)),
+            AdminFunction::TerminateBackend(pid) => Ok(signaled(self.backends.terminate(pid), pid)),
+        }
+    }
+
+    fn regexp_replace(
+        &mut self,
+        text: &ScalarValue,
+        pattern: &ScalarValue,
+        replacement: &ScalarValue,
+        flags: Option<&ScalarValue>,
+    ) -> Result<ScalarValue, QueryError> {
+        self.patterns
+            .regexp_replace(
+                &datum(text),
+                &datum(pattern),
+                &datum(replacement),
+                &optional_datum(flags),
+            )
+            .map(|replaced| ScalarValue::String(replaced.to_string()))
+            .map_err(eval_error)
+    }
+
+    // TODO: `regexp_matches()` returns a row per match, only the first one is
+    //       a value of a select without tables
+    fn regexp_matches(
+        &mut self,
+        text: &ScalarValue,
+        pattern: &ScalarValue,
+        flags: Option<&ScalarValue>,
+    ) -> Result<ScalarValue, QueryError> {
+        self.patterns
+            .regexp_matches(&datum(text), &datum(pattern), &optional_datum(flags))
+            .map(|matches| match matches.first() {
+                Some(first) => ScalarValue::String(first.to_string()),
+                None => ScalarValue::Null,
+            })
+            .map_err(eval_error)
+    }
+}
+
+/// `pg_wire` type of a column with the item. Functions have the type their
+/// signature returns, numbers of a function have the type of its number
+/// argument or are integers, operators have the type of their operands
+// TODO: signatures have general types and pg_wire has no types of fractional
+//       numbers, values of `random()`, of division and other fractions are
+//       sent as text
+pub(crate) fn pg_type(item: &ProjectionTreeNode) -> PgType {
+    match item {
+        ProjectionTreeNode::Item(Operator::Const(value)) => literal_type(value),
+        ProjectionTreeNode::Item(_) => PgType::VarChar,
+        ProjectionTreeNode::Operation { left, op, right } => match op {
+            Operation::Arithmetic(Arithmetic::Add)
+            | Operation::Arithmetic(Arithmetic::Sub)
+            | Operation::Arithmetic(Arithmetic::Mul)
+            | Operation::Arithmetic(Arithmetic::Mod)
+            | Operation::Bitwise(_) => match (pg_type(left), pg_type(right)) {
+                (PgType::Integer, PgType::Integer) => PgType::Integer,
+                (PgType::Integer, PgType::BigInt)
+                | (PgType::BigInt, PgType::Integer)
+                | (PgType::BigInt, PgType::BigInt) => PgType::BigInt,
+                _ => PgType::VarChar,
+            },
+            _ => PgType::VarChar,
+        },
+        ProjectionTreeNode::Function { signature, args, .. } => match signature.return_type() {
+            GeneralType::Bool => PgType::Bool,
+            GeneralType::String => PgType::VarChar,
+            GeneralType::Number if FRACTION_FUNCTIONS.contains(&signature.name()) => PgType::VarChar,
+            GeneralType::Number => signature
+                .arg_types()
+                .iter()
+                .zip(args.iter())
+                .find(|(arg_type, _arg)| **arg_type == GeneralType::Number)
+                .map(|(_arg_type, arg)| pg_type(arg))
+                .unwrap_or(PgType::Integer),
+        },
+    }
+}
+
+/// functions with numbers that aren't integers
+const FRACTION_FUNCTIONS: &[&str] = &["random"];
+
+fn literal_type(value: &ScalarValue) -> PgType {
+    match value {
+        ScalarValue::Bool(_) => PgType::Bool,
+        ScalarValue::Number(number) if number.to_string().parse::<i32>().is_ok() => PgType::Integer,
+        ScalarValue::Number(number) if number.to_string().parse::<i64>().is_ok() => PgType::BigInt,
+        ScalarValue::Number(_) | ScalarValue::String(_) | ScalarValue::Null => PgType::VarChar,
+    }
+}
+
+/// text of the value in a data row, `None` is `NULL`
+pub(crate) fn text(value: ScalarValue) -> Option<String> {
+    match value {
+        ScalarValue::Null => None,
+        value => Some(value.to_string()),
+    }
+}
+
+/// operators the expression evaluator computes, `NULL` operand gives `NULL`
+// TODO: comparisons, logical and pattern matching operators are computed only
+//       by filters of table scans
+fn operation(op: &Operation, left: ScalarValue, right: ScalarValue) -> Result<ScalarValue, QueryError> {
+    let binary_op = match op {
+        Operation::Arithmetic(Arithmetic::Add) => BinaryOp::Add,
+        Operation::Arithmetic(Arithmetic::Sub) => BinaryOp::Sub,
+        Operation::Arithmetic(Arithmetic::Mul) => BinaryOp::Mul,
+        Operation::Arithmetic(Arithmetic::Div) => BinaryOp::Div,
+        Operation::Arithmetic(Arithmetic::Mod) => BinaryOp::Mod,
+        Operation::Bitwise(Bitwise::And) => BinaryOp::BitwiseAnd,
+        Operation::Bitwise(Bitwise::Or) => BinaryOp::BitwiseOr,
+        Operation::StringOp(StringOp::Concat) => BinaryOp::Concat,
+        op => {
+            return Err(QueryError::feature_not_supported(format!(
+                "operator {:?} without tables",
+                op
+            )))
+        }
+    };
+    if left == ScalarValue::Null || right == ScalarValue::Null {
+        return Ok(ScalarValue::Null);
+    }
+    match (&binary_op, &right) {
+        (BinaryOp::Div, ScalarValue::Number(divisor)) | (BinaryOp::Mod, ScalarValue::Number(divisor))
+            if divisor.is_zero() =>
+        {
+            return Err(QueryError::division_by_zero())
+        }
+        _ => {}
+    }
+    let expr = ScalarOp::Binary(
+        binary_op,
+        Box::new(ScalarOp::Value(evaluator_value(left))),
+        Box::new(ScalarOp::Value(evaluator_value(right))),
+    );
+    match StaticExpressionEvaluation::default().eval(&expr) {
+        Ok(ScalarOp::Value(value)) => Ok(scalar_value(value)),
+        Ok(ScalarOp::Binary(op, left, right)) => Err(QueryError::undefined_function(
+            op,
+            operand_type(&left),
+            operand_type(&right),
+        )),
+        Ok(ScalarOp::Column(_)) => unreachable!("select without tables has no columns"),
+        Err(error) => Err(eval_error(error)),
+    }
+}
+
+fn operand_type(operand: &ScalarOp) -> &'static str {
+    match operand {
+        ScalarOp::Value(values::ScalarValue::Bool(_)) => "BOOL",
+        ScalarOp::Value(values::ScalarValue::Number(_)) => "NUMBER",
+        _ => "STRING",
+    }
+}
+
+fn evaluator_value(value: ScalarValue) -> values::ScalarValue {
+    match value {
+        ScalarValue::String(value) => values::ScalarValue::String(value),
+        ScalarValue::Number(value) => values::ScalarValue::Number(value),
+        ScalarValue::Bool(Bool(value)) => values::ScalarValue::Bool(values::Bool(value)),
+        ScalarValue::Null => values::ScalarValue::Null,
+    }
+}
+
+fn scalar_value(value: values::ScalarValue) -> ScalarValue {
+    match value {
+        values::ScalarValue::String(value) => ScalarValue::String(value),
+        values::ScalarValue::Number(value) => ScalarValue::Number(value),
+        values::ScalarValue::Bool(values::Bool(value)) => ScalarValue::Bool(Bool(value)),
+        values::ScalarValue::Null => ScalarValue::Null,
+    }
+}
+
+fn datum(value: &ScalarValue) -> Datum<'_> {
+    match value {
+        ScalarValue::String(value) => Datum::String(value.as_str()),
+        ScalarValue::Null => Datum::from_null(),
+        value => Datum::from_string(value.to_string()),
+    }
+}
+
+fn optional_datum(value: Option<&ScalarValue>) -> Datum<'_> {
+    match value {
+        Some(value) => datum(value),
+        None => Datum::from_null(),
+    }
+}
+
+fn eval_error(error: EvalError) -> QueryError {
+    match error {
+        EvalError::UndefinedFunction(op, left_type, right_type) => {
+            QueryError::undefined_function(op, left_type, right_type)
+        }
+        EvalError::InvalidRegularExpression(message) => QueryError::invalid_regular_expression(message),
+        EvalError::NonValue(not_a_value) => QueryError::syntax_error(not_a_value),
+    }
+}
+
+fn signaled(signaled: bool, pid: ConnId) -> ScalarValue {
+    if !signaled {
+        log::warn!("PID {} is not a server process", pid);
+    }
+    ScalarValue::Bool(Bool(signaled))
+}
@@ -0,0 +1,167 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_io::Async;
+use data_manager::DatabaseHandle;
+use futures_lite::{AsyncReadExt, AsyncWriteExt};
+use std::{
+    io,
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+/// state of the node reported on the admin port to orchestration platforms
+pub(crate) struct Health {
+    storage: Arc<DatabaseHandle>,
+    accepting_connections: AtomicBool,
+}
+
+impl Health {
+    pub(crate) fn new(storage: Arc<DatabaseHandle>) -> Health {
+        Health {
+            storage,
+            accepting_connections: AtomicBool::new(false),
+        }
+    }
+
+    /// set once all listen addresses are bound
+    pub(crate) fn set_accepting_connections(&self, accepting: bool) {
+        self.accepting_connections.store(accepting, Ordering::SeqCst);
+    }
+
+    /// `/healthz` fails when the storage can't be read, `/readyz` also
+    /// fails until the node accepts connections
+    fn respond(&self, path: &str) -> (&'static str, String) {
+        let storage = self.storage.is_available();
+        let accepting_connections = self.accepting_connections.load(Ordering::SeqCst);
+        let (healthy, body) = match path {
+            "/healthz" => (storage, format!("{{\"storage_available\":{}}}", storage)),
+            // TODO: wal_lag is always null, there is no WAL of the node and no
+            //       standby to stream it to
+            "/readyz" => (
+                storage && accepting_connections,
+                format!(
+                    "{{\"storage_available\":{},\"accepting_connections\":{},\"wal_lag\":null}}",
+                    storage, accepting_connections
+                ),
+            ),
+            _ => return ("404 Not Found", "{}".to_owned()),
+        };
+        if healthy {
+            ("200 OK", body)
+        } else {
+            ("503 Service Unavailable", body)
+        }
+    }
+
+    /// answers a single HTTP request and closes the stream
+    pub(crate) async fn handle(&self, mut stream: Async<TcpStream>) -> io::Result<()> {
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+        while !request.windows(4).any(|end| end == b"\r\n\r\n") && request.len() < MAX_REQUEST_LEN {
+            let read = stream.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..read]);
+        }
+        let (status, body) = match request_path(&request) {
+            Some(path) => self.respond(path),
+            None => ("400 Bad Request", "{}".to_owned()),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.flush().await
+    }
+}
+
+/// path of a `GET` request without a query string
+fn request_path(request: &[u8]) -> Option<&str> {
+    let request_line = request.split(|byte| *byte == b'\r').next()?;
+    let mut parts = std::str::from_utf8(request_line).ok()?.split(' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("GET"), Some(target), Some(version)) if version.starts_with("HTTP/") => target.split('?').next(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_of_get_request() {
+        assert_eq!(
+            request_path(b"GET /readyz?verbose HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+            Some("/readyz")
+        );
+    }
+
+    #[test]
+    fn other_requests() {
+        assert_eq!(request_path(b"POST /healthz HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(request_path(b"GET /healthz\r\n\r\n"), None);
+        assert_eq!(request_path(b""), None);
+    }
+
+    #[test]
+    fn healthy_node() {
+        let health = Health::new(Arc::new(DatabaseHandle::in_memory()));
+
+        assert_eq!(
+            health.respond("/healthz"),
+            ("200 OK", "{\"storage_available\":true}".to_owned())
+        );
+    }
+
+    #[test]
+    fn not_ready_until_accepting_connections() {
+        let health = Health::new(Arc::new(DatabaseHandle::in_memory()));
+
+        assert_eq!(
+            health.respond("/readyz"),
+            (
+                "503 Service Unavailable",
+                "{\"storage_available\":true,\"accepting_connections\":false,\"wal_lag\":null}".to_owned()
+            )
+        );
+
+        health.set_accepting_connections(true);
+
+        assert_eq!(
+            health.respond("/readyz"),
+            (
+                "200 OK",
+                "{\"storage_available\":true,\"accepting_connections\":true,\"wal_lag\":null}".to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_path() {
+        let health = Health::new(Arc::new(DatabaseHandle::in_memory()));
+
+        assert_eq!(health.respond("/metrics"), ("404 Not Found", "{}".to_owned()));
+    }
+}
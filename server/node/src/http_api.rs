@@ -0,0 +1,510 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{audit_log::AuditCategory, Node};
+use async_io::Async;
+use connection::Sender;
+use futures_lite::{AsyncReadExt, AsyncWriteExt};
+use pg_model::{
+    hba::{AuthMethod, HostBasedAccess},
+    results::{QueryError, QueryEvent, QueryResult},
+    Command,
+};
+use pg_wire::BackendMessage;
+use std::{
+    io,
+    net::{SocketAddr, TcpStream},
+    sync::{Arc, Mutex},
+};
+
+const MAX_HEAD_LEN: usize = 8 * 1024;
+const MAX_BODY_LEN: usize = 1024 * 1024;
+
+/// runs SQL sent with `POST /query` for clients that have no PostgreSQL driver
+pub(crate) struct HttpApi {
+    node: Node,
+    hba: HostBasedAccess,
+}
+
+impl HttpApi {
+    pub(crate) fn new(node: Node, hba: HostBasedAccess) -> HttpApi {
+        HttpApi { node, hba }
+    }
+
+    /// checks the client against host based access rules, there is no way to
+    /// ask for a password over HTTP so only `trust` rules let it in
+    // TODO: HTTP Basic credentials could be accepted by `password` rules once
+    //       there are roles with passwords to check them against
+    fn authorize(&self, address: SocketAddr, head: &[u8]) -> Result<(), QueryError> {
+        let (user, database) = user_and_database(head);
+        let refused = match self.hba.method(address.ip(), false, &database, &user) {
+            Some(AuthMethod::Trust) => None,
+            None => Some(QueryError::no_hba_entry(address.ip(), &user, &database)),
+            Some(_) => Some(QueryError::hba_rejects_connection(address.ip(), &user, &database)),
+        };
+        let message = match refused {
+            None => format!(
+                "HTTP query of {:?} to {:?} from {} is authorized",
+                user, database, address
+            ),
+            Some(_) => format!("HTTP query of {:?} to {:?} from {} is refused", user, database, address),
+        };
+        self.node
+            .audit_log
+            .record(AuditCategory::Authentication, None, &user, &message);
+        match refused {
+            None => Ok(()),
+            Some(error) => Err(error),
+        }
+    }
+
+    /// executes SQL of a request body in a new session
+    fn query(&self, sql: String) -> (&'static str, String) {
+        let results = Arc::new(Results::default());
+        let mut query_engine = self.node.query_engine(results.clone());
+        if query_engine.execute(Command::Query { sql }).is_err() {
+            log::debug!("HTTP query session is terminated");
+        }
+        drop(query_engine);
+        results.to_json()
+    }
+
+    /// answers a single HTTP request and closes the stream
+    pub(crate) async fn handle(&self, mut stream: Async<TcpStream>, address: SocketAddr) -> io::Result<()> {
+        let mut request = vec![];
+        let mut buf = [0; 1024];
+        let head_len = loop {
+            if let Some(end) = request.windows(4).position(|end| end == b"\r\n\r\n") {
+                break Some(end + 4);
+            }
+            if request.len() >= MAX_HEAD_LEN {
+                break None;
+            }
+            let read = stream.read(&mut buf).await?;
+            if read == 0 {
+                break None;
+            }
+            request.extend_from_slice(&buf[..read]);
+        };
+        let (status, body) = match head_len.map(|head_len| (head_len, body_len(&request[..head_len]))) {
+            Some((head_len, Ok(body_len))) => match self.authorize(address, &request[..head_len]) {
+                Err(error) => refused(error),
+                Ok(()) => {
+                    while request.len() < head_len + body_len {
+                        let read = stream.read(&mut buf).await?;
+                        if read == 0 {
+                            break;
+                        }
+                        request.extend_from_slice(&buf[..read]);
+                    }
+                    match request.get(head_len..head_len + body_len).map(std::str::from_utf8) {
+                        Some(Ok(sql)) => self.query(sql.to_owned()),
+                        _ => ("400 Bad Request", "{}".to_owned()),
+                    }
+                }
+            },
+            Some((_, Err(status))) => (status, "{}".to_owned()),
+            None => ("400 Bad Request", "{}".to_owned()),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.flush().await
+    }
+}
+
+/// `Content-Length` of a `POST /query` request
+fn body_len(head: &[u8]) -> Result<usize, &'static str> {
+    let head = std::str::from_utf8(head).map_err(|_| "400 Bad Request")?;
+    let mut lines = head.split("\r\n");
+    let mut parts = lines.next().unwrap_or_default().split(' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/") => {
+            if target.split('?').next() != Some("/query") {
+                return Err("404 Not Found");
+            }
+            if method != "POST" {
+                return Err("405 Method Not Allowed");
+            }
+        }
+        _ => return Err("400 Bad Request"),
+    }
+    // TODO: chunked bodies are not supported, clients have to send the length of SQL
+    let content_length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse::<usize>().map_err(|_| "400 Bad Request"))
+        .unwrap_or(Err("411 Length Required"))?;
+    if content_length > MAX_BODY_LEN {
+        Err("413 Payload Too Large")
+    } else {
+        Ok(content_length)
+    }
+}
+
+/// user and database of a request are passed as `user` and `database`
+/// parameters of `/query`, database defaults to the user name as it does for
+/// PostgreSQL clients
+fn user_and_database(head: &[u8]) -> (String, String) {
+    let head = String::from_utf8_lossy(head);
+    let target = head.split(' ').nth(1).unwrap_or_default();
+    let params = target.split_once('?').map(|(_, params)| params).unwrap_or_default();
+    let param = |name: &str| {
+        params
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .find(|(key, _)| percent_decoded(key) == name)
+            .map(|(_, value)| percent_decoded(value))
+    };
+    let user = param("user").unwrap_or_default();
+    let database = param("database").unwrap_or_else(|| user.clone());
+    (user, database)
+}
+
+/// value of a query parameter with `+` replaced by space and `%XX` escapes by
+/// the bytes they encode, malformed escapes are kept as they are
+fn percent_decoded(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = match bytes[index] {
+            b'%' => bytes
+                .get(index + 1..index + 3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match (bytes[index], escaped) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (b'+', None) => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            (byte, None) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// `403 Forbidden` response with an error of a refused client
+fn refused(error: QueryError) -> (&'static str, String) {
+    let results = Results::default();
+    results.0.lock().unwrap().push(Err(error));
+    let (_, body) = results.to_json();
+    ("403 Forbidden", body)
+}
+
+/// collects results that query engine sends to a client
+#[derive(Default)]
+struct Results(Mutex<Vec<QueryResult>>);
+
+impl Sender for Results {
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn send(&self, query_result: QueryResult) -> io::Result<()> {
+        self.0.lock().unwrap().push(query_result);
+        Ok(())
+    }
+}
+
+impl Results {
+    /// each statement is reported with its command tag and selected rows
+    /// if there are any. Response is `400 Bad Request` if any statement failed
+    fn to_json(&self) -> (&'static str, String) {
+        let mut status = "200 OK";
+        let mut statements = vec![];
+        let mut columns: Option<Vec<String>> = None;
+        let mut rows = vec![];
+        for result in self.0.lock().unwrap().drain(..) {
+            let message: BackendMessage = match result {
+                Ok(QueryEvent::NullableDataRow(values)) => {
+                    rows.push(json_array(values.iter().map(|value| match value {
+                        Some(value) => json_string(value),
+                        None => "null".to_owned(),
+                    })));
+                    continue;
+                }
+                Ok(event) => event.into(),
+                Err(error) => error.into(),
+            };
+            match message {
+                BackendMessage::RowDescription(description) => {
+                    columns = Some(
+                        description
+                            .into_iter()
+                            .map(|column| json_string(&column.name))
+                            .collect(),
+                    );
+                    rows.clear();
+                }
+                // TODO: values are sent in text format as strings, `DataRow` of query
+                //       engine does not carry types, only selects without tables send NULL
+                BackendMessage::DataRow(values) => rows.push(json_array(values.iter().map(|value| json_string(value)))),
+                BackendMessage::CommandComplete(command) => {
+                    let statement = match columns.take() {
+                        Some(columns) => format!(
+                            "{{\"command\":{},\"columns\":{},\"rows\":{}}}",
+                            json_string(&command),
+                            json_array(columns.into_iter()),
+                            json_array(rows.drain(..))
+                        ),
+                        None => format!("{{\"command\":{}}}", json_string(&command)),
+                    };
+                    statements.push(statement);
+                }
+                BackendMessage::ErrorResponse(severity, code, message) => {
+                    status = "400 Bad Request";
+                    columns = None;
+                    rows.clear();
+                    statements.push(format!(
+                        "{{\"error\":{{\"severity\":{},\"code\":{},\"message\":{}}}}}",
+                        json_string(severity.unwrap_or_default()),
+                        json_string(code.unwrap_or_default()),
+                        json_string(&message.unwrap_or_default())
+                    ));
+                }
+                _ => {}
+            }
+        }
+        (
+            status,
+            format!("{{\"results\":{}}}", json_array(statements.into_iter())),
+        )
+    }
+}
+
+fn json_array<I: Iterator<Item = String>>(items: I) -> String {
+    format!("[{}]", items.collect::<Vec<String>>().join(","))
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_manager::DatabaseHandle;
+
+    fn trusted() -> HttpApi {
+        HttpApi::new(
+            Node::new(DatabaseHandle::in_memory()),
+            HostBasedAccess::parse("host all all all trust").expect("valid rules"),
+        )
+    }
+
+    fn address(address: &str) -> SocketAddr {
+        address.parse().expect("valid address")
+    }
+
+    #[cfg(test)]
+    mod request {
+        use super::*;
+
+        #[test]
+        fn post_query() {
+            assert_eq!(
+                body_len(b"POST /query HTTP/1.1\r\nHost: localhost\r\ncontent-length: 9\r\n\r\n"),
+                Ok(9)
+            );
+        }
+
+        #[test]
+        fn other_paths_and_methods() {
+            assert_eq!(
+                body_len(b"POST /healthz HTTP/1.1\r\nContent-Length: 9\r\n\r\n"),
+                Err("404 Not Found")
+            );
+            assert_eq!(body_len(b"GET /query HTTP/1.1\r\n\r\n"), Err("405 Method Not Allowed"));
+            assert_eq!(body_len(b"POST /query\r\n\r\n"), Err("400 Bad Request"));
+        }
+
+        #[test]
+        fn length_of_body() {
+            assert_eq!(body_len(b"POST /query HTTP/1.1\r\n\r\n"), Err("411 Length Required"));
+            assert_eq!(
+                body_len(b"POST /query HTTP/1.1\r\nContent-Length: nine\r\n\r\n"),
+                Err("400 Bad Request")
+            );
+            assert_eq!(
+                body_len(format!("POST /query HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_LEN + 1).as_bytes()),
+                Err("413 Payload Too Large")
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod authorization {
+        use super::*;
+
+        const HEAD: &[u8] = b"POST /query?user=alice&database=shop HTTP/1.1\r\nContent-Length: 9\r\n\r\n";
+
+        #[test]
+        fn user_and_database_of_request() {
+            assert_eq!(user_and_database(HEAD), ("alice".to_owned(), "shop".to_owned()));
+            assert_eq!(
+                user_and_database(b"POST /query?user=bob HTTP/1.1\r\n\r\n"),
+                ("bob".to_owned(), "bob".to_owned())
+            );
+            assert_eq!(
+                user_and_database(b"POST /query HTTP/1.1\r\n\r\n"),
+                (String::new(), String::new())
+            );
+        }
+
+        #[test]
+        fn percent_encoded_user_and_database() {
+            assert_eq!(
+                user_and_database(b"POST /query?user=al%69ce+smith&database=%D0%B1%D0%B4 HTTP/1.1\r\n\r\n"),
+                ("alice smith".to_owned(), "\u{431}\u{434}".to_owned())
+            );
+            assert_eq!(
+                user_and_database(b"POST /query?user=100%25&database=%zz%4 HTTP/1.1\r\n\r\n"),
+                ("100%".to_owned(), "%zz%4".to_owned())
+            );
+        }
+
+        #[test]
+        fn trusted_client() {
+            let http_api = HttpApi::new(
+                Node::new(DatabaseHandle::in_memory()),
+                HostBasedAccess::parse("host shop alice 10.0.0.0/8 trust").expect("valid rules"),
+            );
+
+            assert_eq!(http_api.authorize(address("10.0.0.1:5000"), HEAD), Ok(()));
+        }
+
+        #[test]
+        fn client_asked_for_password() {
+            let http_api = HttpApi::new(Node::new(DatabaseHandle::in_memory()), HostBasedAccess::default());
+
+            assert_eq!(
+                http_api.authorize(address("10.0.0.1:5000"), HEAD),
+                Err(QueryError::hba_rejects_connection("10.0.0.1", "alice", "shop"))
+            );
+        }
+
+        #[test]
+        fn client_without_rule() {
+            let http_api = HttpApi::new(
+                Node::new(DatabaseHandle::in_memory()),
+                HostBasedAccess::parse("host shop alice 10.0.0.0/8 trust").expect("valid rules"),
+            );
+
+            assert_eq!(
+                http_api.authorize(address("192.168.0.1:5000"), HEAD),
+                Err(QueryError::no_hba_entry("192.168.0.1", "alice", "shop"))
+            );
+        }
+
+        #[test]
+        fn refused_response() {
+            assert_eq!(
+                refused(QueryError::no_hba_entry("192.168.0.1", "alice", "shop")),
+                (
+                    "403 Forbidden",
+                    "{\"results\":[{\"error\":{\"severity\":\"FATAL\",\"code\":\"28000\",\"message\":\"no pg_hba.conf entry for host \\\"192.168.0.1\\\", user \\\"alice\\\", database \\\"shop\\\"\"}}]}"
+                        .to_owned()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn escaped_strings() {
+        assert_eq!(json_string("a\"b\\c\nd\u{1}"), "\"a\\\"b\\\\c\\nd\\u0001\"");
+    }
+
+    #[test]
+    fn selected_rows() {
+        let http_api = trusted();
+
+        assert_eq!(
+            http_api.query("create schema schema_name;".to_owned()),
+            ("200 OK", "{\"results\":[{\"command\":\"CREATE SCHEMA\"}]}".to_owned())
+        );
+        assert_eq!(
+            http_api.query("create table schema_name.table_name (col_1 smallint, col_2 varchar(10));".to_owned()),
+            ("200 OK", "{\"results\":[{\"command\":\"CREATE TABLE\"}]}".to_owned())
+        );
+        assert_eq!(
+            http_api.query("insert into schema_name.table_name values (1, 'a\"b'), (2, 'c');".to_owned()),
+            ("200 OK", "{\"results\":[{\"command\":\"INSERT 0 2\"}]}".to_owned())
+        );
+        assert_eq!(
+            http_api.query("select * from schema_name.table_name;".to_owned()),
+            (
+                "200 OK",
+                "{\"results\":[{\"command\":\"SELECT 2\",\"columns\":[\"col_1\",\"col_2\"],\"rows\":[[\"1\",\"a\\\"b\"],[\"2\",\"c\"]]}]}"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn null_values() {
+        let http_api = trusted();
+
+        assert_eq!(
+            http_api.query("select current_setting('app.missing', true);".to_owned()),
+            (
+                "200 OK",
+                "{\"results\":[{\"command\":\"SELECT 1\",\"columns\":[\"current_setting\"],\"rows\":[[null]]}]}"
+                    .to_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn failed_statement() {
+        let http_api = trusted();
+
+        assert_eq!(
+            http_api.query("drop schema non_existent;".to_owned()),
+            (
+                "400 Bad Request",
+                "{\"results\":[{\"error\":{\"severity\":\"ERROR\",\"code\":\"3F000\",\"message\":\"schema \\\"non_existent\\\" does not exist\"}}]}"
+                    .to_owned()
+            )
+        );
+    }
+}
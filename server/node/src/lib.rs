@@ -14,55 +14,268 @@
 
 extern crate log;
 
+mod audit_log;
+mod backends;
+/// Module contains sessions and workloads to benchmark query engine
+#[cfg(feature = "testing")]
+pub mod bench;
+mod evaluation;
+mod health;
+mod http_api;
+mod listeners;
 mod query_engine;
+mod result_cache;
+mod session_functions;
+mod statistics;
+/// Module contains database instances to run integration tests against
+#[cfg(feature = "testing")]
+pub mod test_cluster;
+mod ttl;
 
-use crate::query_engine::QueryEngine;
+use crate::{
+    audit_log::{AuditCategory, AuditLog},
+    backends::Backends,
+    health::Health,
+    http_api::HttpApi,
+    listeners::{Listener, Tls},
+    query_engine::QueryEngine,
+    result_cache::TableVersions,
+    statistics::StatementStatistics,
+    ttl::TtlPolicies,
+};
 use async_dup::Arc as AsyncArc;
 use async_executor::Executor;
 use async_io::Async;
 use catalog::InMemoryDatabase;
-use connection::ClientRequest;
+use connection::{ClientRequest, Sender};
 use data_manager::DatabaseHandle;
-use pg_model::{ConnSupervisor, ProtocolConfiguration};
+use pg_model::{hba::HostBasedAccess, ConnSupervisor, ProtocolConfiguration};
 use std::{
-    env,
-    net::TcpListener,
+    env, fs, io,
+    net::{SocketAddr, TcpListener},
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, Once},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 const PORT: u16 = 5432;
 const HOST: [u8; 4] = [0, 0, 0, 0];
 
+const TTL_BATCH_SIZE: usize = 1_000;
+
 const MIN_CONN_ID: i32 = 1;
 const MAX_CONN_ID: i32 = 1 << 16;
 
+static GLOBAL: Executor<'_> = Executor::new();
+/// accepts clients and answers health checks on the main thread, statements
+/// that connections run on `GLOBAL` executor don't hold them up
+static ACCEPTOR: Executor<'_> = Executor::new();
+static EXECUTOR_THREAD: Once = Once::new();
+
 pub fn start() {
     let root_path = env::var("ROOT_PATH").map(PathBuf::from).unwrap_or_default();
+    let listeners = listeners();
+
+    spawn_executor_thread();
+
+    async_io::block_on(ACCEPTOR.run(async {
+        // TODO: streaming to a standby node needs a WAL to ship records of, the
+        //       persistent storage has none of its own and relies on sled journal
+        let node = Node {
+            audit_log: Arc::new(audit_log()),
+            read_only: read_only_node(),
+            work_mem: work_mem(),
+            deterministic: deterministic_node(),
+            protocol_trace: protocol_trace(),
+            strict_compatibility: strict_compatibility(),
+            ..Node::new(DatabaseHandle::persistent(root_path.join("root_directory")).unwrap())
+        };
+
+        let health = Arc::new(Health::new(node.storage.clone()));
+        if let Some(address) = admin_address() {
+            let admin = Async::<TcpListener>::bind(address).expect("OK");
+            log::info!("health checks on {}", address);
+            ACCEPTOR.spawn(serve_health(admin, health.clone())).detach();
+        }
+
+        // TODO: Arrow Flight SQL endpoint for analytics clients needs a gRPC server,
+        //       tonic and arrow-flight run on tokio while node serves clients on
+        //       async-io executor. Results are available as JSON rows of HTTP API
+        if let Some(address) = http_address() {
+            let http = Async::<TcpListener>::bind(address).expect("OK");
+            log::info!("HTTP query API on {}", address);
+            ACCEPTOR
+                .spawn(serve_http(
+                    http,
+                    Arc::new(HttpApi::new(node.clone(), host_based_access())),
+                ))
+                .detach();
+        }
 
-    static GLOBAL: Executor<'_> = Executor::new();
+        GLOBAL
+            .spawn(expire_rows(
+                node.ttl_policies.clone(),
+                node.table_versions.clone(),
+                ttl_interval(),
+            ))
+            .detach();
 
-    std::thread::Builder::new()
-        .name("main-executor".to_owned())
-        .spawn(|| loop {
-            std::panic::catch_unwind(|| async_io::block_on(GLOBAL.run(futures_lite::future::pending::<()>()))).ok();
+        let tasks = listeners
+            .into_iter()
+            .map(|listener| {
+                let socket = Async::<TcpListener>::bind(listener.address).expect("OK");
+                log::info!("listening on {}", listener.address);
+                ACCEPTOR.spawn(node.clone().serve(socket, protocol_configuration(&listener.tls)))
+            })
+            .collect::<Vec<_>>();
+        health.set_accepting_connections(true);
+        for task in tasks {
+            task.await;
+        }
+    }));
+}
+
+/// the thread runs connections of all nodes started by the process
+fn spawn_executor_thread() {
+    EXECUTOR_THREAD.call_once(|| {
+        std::thread::Builder::new()
+            .name("main-executor".to_owned())
+            .spawn(|| loop {
+                std::panic::catch_unwind(|| async_io::block_on(GLOBAL.run(futures_lite::future::pending::<()>()))).ok();
+            })
+            .expect("cannot spawn executor thread");
+    });
+}
+
+async fn serve_health(listener: Async<TcpListener>, health: Arc<Health>) {
+    while let Ok((stream, _address)) = listener.accept().await {
+        let health = health.clone();
+        ACCEPTOR
+            .spawn(async move {
+                if let Err(io_error) = health.handle(stream).await {
+                    log::debug!("health check IO error {:?}", io_error);
+                }
+            })
+            .detach();
+    }
+}
+
+async fn serve_http(listener: Async<TcpListener>, http_api: Arc<HttpApi>) {
+    while let Ok((stream, address)) = listener.accept().await {
+        let http_api = http_api.clone();
+        GLOBAL
+            .spawn(async move {
+                if let Err(io_error) = http_api.handle(stream, address).await {
+                    log::debug!("HTTP query IO error {:?}", io_error);
+                }
+            })
+            .detach();
+    }
+}
+
+/// deletes rows of tables with TTL policies once they are expired
+async fn expire_rows(ttl_policies: Arc<TtlPolicies>, table_versions: Arc<TableVersions>, interval: Duration) {
+    loop {
+        async_io::Timer::after(interval).await;
+        let ttl_policies = ttl_policies.clone();
+        let table_versions = table_versions.clone();
+        let deleted = blocking::unblock(move || {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since_epoch| since_epoch.as_secs() as i64)
+                .unwrap_or_default();
+            ttl_policies.expire(now, TTL_BATCH_SIZE, &table_versions)
         })
-        .expect("cannot spawn executor thread");
+        .await;
+        if deleted > 0 {
+            log::debug!("{} expired rows are deleted", deleted);
+        }
+    }
+}
 
-    async_io::block_on(async {
-        let storage = Arc::new(DatabaseHandle::persistent(root_path.join("root_directory")).unwrap());
-        let listener = Async::<TcpListener>::bind((HOST, PORT)).expect("OK");
+// TODO: exporting a whole instance like pg_dumpall needs a dump of a single
+//       database first, there is none. Node also has no global objects to
+//       export: it serves one database, users are only checked against HBA
+//       rules, there are no roles or grants, and settings defaults come from
+//       environment variables rather than from the catalog
+/// state shared by clients of all listen addresses
+#[derive(Clone)]
+struct Node {
+    storage: Arc<DatabaseHandle>,
+    catalog: Arc<InMemoryDatabase>,
+    conn_supervisor: Arc<Mutex<ConnSupervisor>>,
+    statistics: Arc<StatementStatistics>,
+    table_versions: Arc<TableVersions>,
+    backends: Arc<Backends>,
+    audit_log: Arc<AuditLog>,
+    ttl_policies: Arc<TtlPolicies>,
+    read_only: bool,
+    work_mem: usize,
+    deterministic: bool,
+    protocol_trace: bool,
+    strict_compatibility: bool,
+}
 
-        let config = protocol_configuration();
-        let conn_supervisor = Arc::new(Mutex::new(ConnSupervisor::new(MIN_CONN_ID, MAX_CONN_ID)));
+impl Node {
+    fn new(storage: DatabaseHandle) -> Node {
+        let storage = Arc::new(storage);
+        Node {
+            storage: storage.clone(),
+            catalog: InMemoryDatabase::new(),
+            conn_supervisor: Arc::new(Mutex::new(ConnSupervisor::new(MIN_CONN_ID, MAX_CONN_ID))),
+            statistics: Arc::new(StatementStatistics::default()),
+            // TODO: versions count writes to every table, an autovacuum scheduler could
+            //       compare them to per-table thresholds, but there is neither VACUUM nor
+            //       ANALYZE to run: deletes remove records in place and no statistics are kept
+            table_versions: Arc::new(TableVersions::default()),
+            backends: Arc::new(Backends::default()),
+            audit_log: Arc::new(AuditLog::default()),
+            ttl_policies: Arc::new(TtlPolicies::new(storage)),
+            read_only: false,
+            work_mem: usize::MAX,
+            deterministic: false,
+            protocol_trace: false,
+            strict_compatibility: false,
+        }
+    }
+
+    /// query engine of a session with settings shared by the whole node
+    fn query_engine(&self, sender: Arc<dyn Sender>) -> QueryEngine<InMemoryDatabase> {
+        let mut query_engine = QueryEngine::new(sender, self.storage.clone(), self.catalog.clone());
+        query_engine.set_node_read_only(self.read_only);
+        query_engine.set_work_mem(self.work_mem);
+        query_engine.set_strict_compatibility(self.strict_compatibility);
+        query_engine.set_deterministic(self.deterministic);
+        query_engine.set_statement_statistics(self.statistics.clone());
+        query_engine.set_table_versions(self.table_versions.clone());
+        query_engine.set_audit_log(self.audit_log.clone());
+        query_engine
+    }
 
+    async fn serve(self, listener: Async<TcpListener>, config: ProtocolConfiguration) {
         while let Ok((tcp_stream, address)) = listener.accept().await {
             let tcp_stream = AsyncArc::new(tcp_stream);
-            match connection::accept_client_request(tcp_stream, address, &config, conn_supervisor.clone()).await {
+            match connection::accept_client_request(tcp_stream, address, &config, self.conn_supervisor.clone()).await {
+                Err(io_error) if io_error.kind() == io::ErrorKind::PermissionDenied => {
+                    self.audit_log
+                        .record(AuditCategory::Authentication, None, "-", &io_error.to_string())
+                }
                 Err(io_error) => log::error!("IO error {:?}", io_error),
                 Ok(Err(protocol_error)) => log::error!("protocol error {:?}", protocol_error),
-                Ok(Ok(ClientRequest::Connection(mut receiver, sender))) => {
-                    let mut query_engine = QueryEngine::new(sender, storage.clone(), InMemoryDatabase::new());
+                Ok(Ok(ClientRequest::Connection(conn_id, mut receiver, sender))) => {
+                    let user = property(receiver.properties(), "user").unwrap_or_default();
+                    let database = property(receiver.properties(), "database").unwrap_or_else(|| user.clone());
+                    self.audit_log.record(
+                        AuditCategory::Authentication,
+                        Some(conn_id),
+                        &user,
+                        &format!("connection to {:?} from {} is authorized", database, address),
+                    );
+                    let mut query_engine = self.query_engine(sender);
+                    query_engine.set_backends(conn_id, self.backends.clone());
+                    query_engine.set_protocol_trace(receiver.protocol_trace(), self.protocol_trace);
+                    query_engine.set_client_stalled(receiver.stalled());
+                    query_engine.set_startup_properties(receiver.properties());
                     log::debug!("ready to handle query");
                     GLOBAL
                         .spawn(async move {
@@ -88,17 +301,21 @@ pub fn start() {
                         .detach();
                 }
                 Ok(Ok(ClientRequest::QueryCancellation(conn_id))) => {
-                    // TODO: Needs to handle Cancel Request here.
                     log::debug!("cancel request of connection-{}", conn_id);
+                    // TODO: statements are interrupted only while they sleep, scans and
+                    //       writes of the executor do not check for cancel requests yet
+                    self.backends.cancel(conn_id);
                 }
             }
         }
-    });
+    }
 }
 
 fn pfx_certificate_path() -> PathBuf {
-    let file = env::var("PFX_CERTIFICATE_FILE").unwrap();
-    let path = Path::new(&file);
+    absolute_path(Path::new(&env::var("PFX_CERTIFICATE_FILE").unwrap()))
+}
+
+fn absolute_path(path: &Path) -> PathBuf {
     if path.is_absolute() {
         return path.to_path_buf();
     }
@@ -111,12 +328,124 @@ fn pfx_certificate_password() -> String {
     env::var("PFX_CERTIFICATE_PASSWORD").unwrap()
 }
 
-fn protocol_configuration() -> ProtocolConfiguration {
-    match env::var("SECURE") {
-        Ok(s) => match s.to_lowercase().as_str() {
-            "ssl_only" => ProtocolConfiguration::with_ssl(pfx_certificate_path(), pfx_certificate_password()),
+fn read_only_node() -> bool {
+    match env::var("READ_ONLY") {
+        Ok(s) => matches!(s.to_lowercase().as_str(), "on" | "true" | "1"),
+        _ => false,
+    }
+}
+
+fn deterministic_node() -> bool {
+    match env::var("DETERMINISTIC") {
+        Ok(s) => matches!(s.to_lowercase().as_str(), "on" | "true" | "1"),
+        _ => false,
+    }
+}
+
+fn protocol_trace() -> bool {
+    match env::var("PROTOCOL_TRACE") {
+        Ok(s) => matches!(s.to_lowercase().as_str(), "on" | "true" | "1"),
+        _ => false,
+    }
+}
+
+fn strict_compatibility() -> bool {
+    match env::var("COMPATIBILITY_MODE") {
+        Ok(s) => s.to_lowercase() == "strict",
+        _ => false,
+    }
+}
+
+fn work_mem() -> usize {
+    env::var("WORK_MEM")
+        .ok()
+        .and_then(|bytes| bytes.parse().ok())
+        .unwrap_or(usize::MAX)
+}
+
+fn ttl_interval() -> Duration {
+    env::var("TTL_INTERVAL")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| Duration::from_secs(60))
+}
+
+fn audit_log() -> AuditLog {
+    match env::var("AUDIT_LOG") {
+        Ok(file) => {
+            let categories = env::var("AUDIT_LOG_CATEGORIES").unwrap_or_else(|_| "all".to_owned());
+            let categories = AuditCategory::parse_list(&categories).unwrap();
+            // checksums are keyed, so that records can't be forged without the key
+            let key = env::var("AUDIT_LOG_KEY").expect("AUDIT_LOG_KEY has to be set with AUDIT_LOG");
+            let head = env::var("AUDIT_LOG_HEAD").unwrap_or_else(|_| format!("{}.head", file));
+            AuditLog::open(file, head, key.into_bytes(), categories).unwrap()
+        }
+        _ => AuditLog::default(),
+    }
+}
+
+fn property(properties: &[(String, String)], name: &str) -> Option<String> {
+    properties
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.clone())
+}
+
+fn host_based_access() -> HostBasedAccess {
+    match env::var("HBA_FILE") {
+        Ok(file) => {
+            let content = fs::read_to_string(&file).unwrap();
+            HostBasedAccess::parse(&content).unwrap_or_else(|error| panic!("invalid HBA file {}: {}", file, error))
+        }
+        _ => HostBasedAccess::default(),
+    }
+}
+
+fn listeners() -> Vec<Listener> {
+    match env::var("LISTEN_ADDRESSES") {
+        Ok(addresses) => listeners::parse(&addresses)
+            .unwrap_or_else(|error| panic!("invalid LISTEN_ADDRESSES {:?}: {}", addresses, error)),
+        _ => vec![Listener {
+            address: SocketAddr::from((HOST, PORT)),
+            tls: Tls::Default,
+        }],
+    }
+}
+
+fn admin_address() -> Option<SocketAddr> {
+    env::var("ADMIN_ADDRESS").ok().map(|address| {
+        address
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid ADMIN_ADDRESS {:?}", address))
+    })
+}
+
+fn http_address() -> Option<SocketAddr> {
+    env::var("HTTP_ADDRESS").ok().map(|address| {
+        address
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid HTTP_ADDRESS {:?}", address))
+    })
+}
+
+fn protocol_configuration(tls: &Tls) -> ProtocolConfiguration {
+    let config = match tls {
+        Tls::Default => match env::var("SECURE") {
+            Ok(s) => match s.to_lowercase().as_str() {
+                "ssl_only" => ProtocolConfiguration::with_ssl(pfx_certificate_path(), pfx_certificate_password()),
+                _ => ProtocolConfiguration::none(),
+            },
             _ => ProtocolConfiguration::none(),
         },
-        _ => ProtocolConfiguration::none(),
+        Tls::None => ProtocolConfiguration::none(),
+        Tls::SslOnly { certificate, password } => {
+            ProtocolConfiguration::with_ssl(absolute_path(certificate), password.clone())
+        }
+    };
+    let config = config.with_hba(host_based_access());
+    match env::var("SEND_TIMEOUT").map(|millis| millis.parse::<u64>()) {
+        Ok(Ok(millis)) => config.with_send_timeout(Duration::from_millis(millis)),
+        _ => config,
     }
 }
@@ -0,0 +1,140 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{net::SocketAddr, path::PathBuf};
+
+/// how clients connected to a listen address are encrypted
+#[derive(Debug, PartialEq)]
+pub(crate) enum Tls {
+    /// settings of `SECURE`, `PFX_CERTIFICATE_FILE` and `PFX_CERTIFICATE_PASSWORD`
+    Default,
+    /// clients are not encrypted
+    None,
+    /// clients are encrypted with the certificate
+    SslOnly { certificate: PathBuf, password: String },
+}
+
+/// address to accept clients on
+#[derive(Debug, PartialEq)]
+pub(crate) struct Listener {
+    pub(crate) address: SocketAddr,
+    pub(crate) tls: Tls,
+}
+
+/// `;` separated listeners, each is an address with a port optionally
+/// followed by `none` or `ssl_only <certificate file> <password>`, e.g.
+/// `0.0.0.0:5432; [::]:5433 ssl_only identity.pfx password`
+pub(crate) fn parse(listeners: &str) -> Result<Vec<Listener>, String> {
+    let mut parsed = vec![];
+    for (index, listener) in listeners
+        .split(';')
+        .map(str::trim)
+        .filter(|listener| !listener.is_empty())
+        .enumerate()
+    {
+        let fields = listener.split_whitespace().collect::<Vec<&str>>();
+        let address = fields[0]
+            .parse()
+            .map_err(|_| format!("listener {}: invalid address {:?}", index + 1, fields[0]))?;
+        let tls = match &fields[1..] {
+            [] => Tls::Default,
+            ["none"] => Tls::None,
+            ["ssl_only", certificate, password] => Tls::SslOnly {
+                certificate: PathBuf::from(certificate),
+                password: (*password).to_owned(),
+            },
+            _ => return Err(format!("listener {}: invalid TLS settings {:?}", index + 1, listener)),
+        };
+        if parsed.iter().any(|other: &Listener| other.address == address) {
+            return Err(format!("listener {}: address {} is duplicated", index + 1, address));
+        }
+        parsed.push(Listener { address, tls });
+    }
+    if parsed.is_empty() {
+        return Err("no listen addresses".to_owned());
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn ipv4_and_ipv6_addresses() {
+        assert_eq!(
+            parse("0.0.0.0:5432; [::]:5432"),
+            Ok(vec![
+                Listener {
+                    address: SocketAddr::from((Ipv4Addr::UNSPECIFIED, 5432)),
+                    tls: Tls::Default
+                },
+                Listener {
+                    address: SocketAddr::from((Ipv6Addr::UNSPECIFIED, 5432)),
+                    tls: Tls::Default
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn tls_settings_of_each_listener() {
+        assert_eq!(
+            parse("127.0.0.1:5432 none;\n[::1]:5433 ssl_only identity.pfx password;"),
+            Ok(vec![
+                Listener {
+                    address: SocketAddr::from((Ipv4Addr::LOCALHOST, 5432)),
+                    tls: Tls::None
+                },
+                Listener {
+                    address: SocketAddr::from((Ipv6Addr::LOCALHOST, 5433)),
+                    tls: Tls::SslOnly {
+                        certificate: PathBuf::from("identity.pfx"),
+                        password: "password".to_owned()
+                    }
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn address_without_port() {
+        assert_eq!(
+            parse("0.0.0.0:5432; ::1"),
+            Err("listener 2: invalid address \"::1\"".to_owned())
+        );
+    }
+
+    #[test]
+    fn invalid_tls_settings() {
+        assert_eq!(
+            parse("0.0.0.0:5432 ssl_only identity.pfx"),
+            Err("listener 1: invalid TLS settings \"0.0.0.0:5432 ssl_only identity.pfx\"".to_owned())
+        );
+    }
+
+    #[test]
+    fn duplicated_address() {
+        assert_eq!(
+            parse("0.0.0.0:5432; 0.0.0.0:5432 none"),
+            Err("listener 2: address 0.0.0.0:5432 is duplicated".to_owned())
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(parse(" ; "), Err("no listen addresses".to_owned()));
+    }
+}
@@ -12,31 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use analysis_tree::{AnalysisError, QueryAnalysis};
+use crate::{
+    audit_log::{AuditCategory, AuditLog},
+    backends::{Backend, Backends},
+    evaluation::{self, Evaluation},
+    result_cache::{ResultCache, ResultRecorder, TableVersions},
+    statistics::{self, RowCounter, StatementStatistics},
+    ttl::{TtlPolicies, TtlPolicy},
+};
+use analysis_tree::{AnalysisError, EvaluationQuery, QueryAnalysis, SchemaChange};
 use bigdecimal::BigDecimal;
 use binder::ParamBinder;
 use catalog::{CatalogDefinition, Database};
 use connection::Sender;
-use data_manager::{DataDefReader, DatabaseHandle};
+use data_manager::DatabaseHandle;
 use definition_operations::{ExecutionError, ExecutionOutcome};
-use description::{Description, DescriptionError};
 use itertools::izip;
 use pg_model::{
-    results::{QueryError, QueryEvent},
+    encoding,
+    results::{QueryError, QueryEvent, QueryNotice},
     session::Session,
+    settings::SettingValue,
     statement::PreparedStatement,
-    Command,
+    Command, ConnId,
 };
-use pg_wire::{PgFormat, PgType};
-use plan::{Plan, SelectInput};
+use pg_wire::{ColumnMetadata, PgFormat, PgType};
+use plan::Plan;
 use query_analyzer::Analyzer;
-use query_analyzer_old::Analyzer as OldAnalyzer;
 use query_executor::QueryExecutor;
 use query_planner::{PlanError, QueryPlanner};
 use schema_executor::SystemSchemaExecutor;
 use schema_planner::SystemSchemaPlanner;
-use sql_ast::{Expr, Ident, Statement, Value};
-use std::{convert::TryFrom, iter, ops::Deref, sync::Arc};
+use sql_ast::{Expr, Ident, SetVariableValue, Statement, Value};
+use std::{
+    convert::TryFrom,
+    iter,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use types::SqlType;
 
 unsafe impl<D: Database + CatalogDefinition> Send for QueryEngine<D> {}
@@ -47,34 +64,174 @@ pub(crate) struct QueryEngine<D: Database + CatalogDefinition> {
     session: Session<Statement>,
     sender: Arc<dyn Sender>,
     database: Arc<D>,
+    // other engines of tests share data of this one
+    #[cfg(test)]
     data_manager: Arc<DatabaseHandle>,
     param_binder: ParamBinder,
     query_analyzer: Analyzer<D>,
     system_planner: SystemSchemaPlanner,
     schema_executor: SystemSchemaExecutor,
-    old_query_analyzer: OldAnalyzer,
     query_planner: QueryPlanner,
     query_executor: QueryExecutor,
+    node_read_only: bool,
+    deterministic: bool,
+    statistics: Arc<StatementStatistics>,
+    row_counter: Arc<RowCounter>,
+    tracked_statement: Option<String>,
+    table_versions: Arc<TableVersions>,
+    result_cache: ResultCache,
+    result_recorder: Arc<ResultRecorder>,
+    conn_id: ConnId,
+    backends: Arc<Backends>,
+    backend: Arc<Backend>,
+    protocol_trace: Arc<AtomicBool>,
+    audit_log: Arc<AuditLog>,
+    ttl_policies: Arc<TtlPolicies>,
+}
+
+impl<D: Database + CatalogDefinition> Drop for QueryEngine<D> {
+    fn drop(&mut self) {
+        self.backends.remove(self.conn_id, &self.backend);
+    }
 }
 
 impl<D: Database + CatalogDefinition> QueryEngine<D> {
     pub(crate) fn new(sender: Arc<dyn Sender>, data_manager: Arc<DatabaseHandle>, database: Arc<D>) -> QueryEngine<D> {
+        let result_recorder = Arc::new(ResultRecorder::new(sender));
+        let row_counter = Arc::new(RowCounter::new(result_recorder.clone()));
+        let sender: Arc<dyn Sender> = row_counter.clone();
+        let backends = Arc::new(Backends::default());
+        let backend = backends.register(0);
         QueryEngine {
             session: Session::default(),
             sender: sender.clone(),
             database: database.clone(),
+            #[cfg(test)]
             data_manager: data_manager.clone(),
             param_binder: ParamBinder,
-            old_query_analyzer: OldAnalyzer::new(data_manager.clone()),
             query_analyzer: Analyzer::new(data_manager.clone(), database),
             system_planner: SystemSchemaPlanner::new(),
             schema_executor: SystemSchemaExecutor::new(data_manager.clone()),
             query_planner: QueryPlanner::new(data_manager.clone()),
-            query_executor: QueryExecutor::new(data_manager, sender),
+            query_executor: QueryExecutor::new(data_manager.clone(), sender),
+            node_read_only: false,
+            deterministic: false,
+            statistics: Arc::new(StatementStatistics::default()),
+            row_counter,
+            tracked_statement: None,
+            table_versions: Arc::new(TableVersions::default()),
+            result_cache: ResultCache::default(),
+            result_recorder,
+            conn_id: 0,
+            backends,
+            backend,
+            protocol_trace: Arc::new(AtomicBool::new(false)),
+            audit_log: Arc::new(AuditLog::default()),
+            ttl_policies: Arc::new(TtlPolicies::new(data_manager)),
+        }
+    }
+
+    /// registers the connection among the ones served by the node, so that
+    /// they could cancel its statements or terminate it
+    pub(crate) fn set_backends(&mut self, conn_id: ConnId, backends: Arc<Backends>) {
+        self.backends.remove(self.conn_id, &self.backend);
+        self.backend = backends.register(conn_id);
+        self.backends = backends;
+        self.conn_id = conn_id;
+    }
+
+    /// user, database and settings of the session that client sent on startup
+    pub(crate) fn set_startup_properties(&mut self, properties: &[(String, String)]) {
+        for error in self.session.set_startup_properties(properties) {
+            log::warn!("connection-{} ignores startup setting {:?}", self.conn_id, error);
+        }
+        let names = self
+            .session
+            .settings()
+            .all()
+            .into_iter()
+            .map(|(name, _value)| name.to_owned())
+            .collect::<Vec<String>>();
+        for name in names {
+            self.setting_changed(&name);
+        }
+    }
+
+    /// shares statistics of executed statements with other connections
+    pub(crate) fn set_statement_statistics(&mut self, statistics: Arc<StatementStatistics>) {
+        self.statistics = statistics;
+    }
+
+    /// shares versions of tables with other connections, so that their writes
+    /// invalidate results cached by this one
+    pub(crate) fn set_table_versions(&mut self, table_versions: Arc<TableVersions>) {
+        self.table_versions = table_versions;
+    }
+
+    /// bytes of rows that a single statement is allowed to hold in memory
+    pub(crate) fn set_work_mem(&mut self, work_mem: usize) {
+        self.session.settings_mut().set_default(
+            "work_mem",
+            SettingValue::Integer(i64::try_from(work_mem).unwrap_or(i64::MAX)),
+        );
+        self.setting_changed("work_mem");
+    }
+
+    /// logs protocol messages of the connection, unless session turns it off,
+    /// through the switch shared with its receiver and sender
+    pub(crate) fn set_protocol_trace(&mut self, protocol_trace: Arc<AtomicBool>, enabled: bool) {
+        self.protocol_trace = protocol_trace;
+        self.session
+            .settings_mut()
+            .set_default("protocol_trace", SettingValue::Bool(enabled));
+        self.setting_changed("protocol_trace");
+    }
+
+    /// aborts selects of the connection once its client stops reading rows,
+    /// through the switch shared with its receiver and sender
+    pub(crate) fn set_client_stalled(&mut self, stalled: Arc<AtomicBool>) {
+        self.query_executor.set_abort(stalled);
+    }
+
+    /// records changes of schema and denied statements of the connection
+    pub(crate) fn set_audit_log(&mut self, audit_log: Arc<AuditLog>) {
+        self.audit_log = audit_log;
+    }
+
+    /// makes strict type checks the default of sessions instead of casts
+    /// that PostgreSQL does implicitly
+    pub(crate) fn set_strict_compatibility(&mut self, strict: bool) {
+        let mode = if strict { "strict" } else { "postgresql" };
+        self.session
+            .settings_mut()
+            .set_default("compatibility_mode", SettingValue::String(mode.to_owned()));
+        self.setting_changed("compatibility_mode");
+    }
+
+    /// rejects statements that modify data or schema regardless of the
+    /// session settings
+    pub(crate) fn set_node_read_only(&mut self, read_only: bool) {
+        self.node_read_only = read_only;
+    }
+
+    /// fixes seed of random values and reports zero execution time of
+    /// statements, so that results of tests are reproducible
+    pub(crate) fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+        if deterministic {
+            self.session.set_seed(0.0).expect("zero is a valid seed");
         }
     }
 
     pub(crate) fn execute(&mut self, command: Command) -> Result<(), ()> {
+        if self.backend.terminated() {
+            self.sender
+                .send(Err(QueryError::admin_shutdown()))
+                .expect("To Send Error to Client");
+            return Err(());
+        }
+        // cancel requests that came in between statements have nothing to cancel
+        self.backend.interruption();
         match command {
             Command::Bind {
                 portal_name,
@@ -104,15 +261,19 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                             result_formats.as_ref(),
                         ) {
                             Ok((new_stmt, result_formats)) => {
-                                self.session.set_portal(
+                                let bound = self.session.set_portal(
                                     portal_name,
                                     statement_name.to_owned(),
                                     new_stmt,
                                     result_formats,
                                 );
-                                self.sender
-                                    .send(Ok(QueryEvent::BindComplete))
-                                    .expect("To Send Bind Complete Event");
+                                match bound {
+                                    Ok(()) => self
+                                        .sender
+                                        .send(Ok(QueryEvent::BindComplete))
+                                        .expect("To Send Bind Complete Event"),
+                                    Err(error) => self.sender.send(Err(error)).expect("To Send Error to Client"),
+                                }
                             }
                             Err(error) => log::error!("{:?}", error),
                         }
@@ -125,6 +286,21 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                 }
                 Ok(())
             }
+            // closing a statement or a portal that does not exist is not an error
+            Command::CloseStatement { name } => {
+                self.session.remove_prepared_statement(&name);
+                self.sender
+                    .send(Ok(QueryEvent::CloseComplete))
+                    .expect("To Send Close Complete to Client");
+                Ok(())
+            }
+            Command::ClosePortal { name } => {
+                self.session.remove_portal(&name);
+                self.sender
+                    .send(Ok(QueryEvent::CloseComplete))
+                    .expect("To Send Close Complete to Client");
+                Ok(())
+            }
             Command::Continue => {
                 self.sender
                     .send(Ok(QueryEvent::QueryComplete))
@@ -172,11 +348,33 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                 max_rows: _max_rows,
             } => {
                 match self.session.get_portal(&portal_name) {
-                    Some(portal) => {
-                        if let Ok(plan) = self.query_planner.plan(portal.stmt()) {
-                            self.query_executor.execute(plan);
+                    Some(portal) => match self.read_only_violation(portal.stmt()) {
+                        Some(error) => self.permission_denied(portal.stmt(), error),
+                        None => {
+                            let started = Instant::now();
+                            self.tracked_statement = Some(portal.stmt().to_string());
+                            self.row_counter.take();
+                            // TODO: portal is planned on every execution from a statement with
+                            //       parameters already substituted, a custom plan in terms of
+                            //       `plan_cache_mode`. Caching a generic plan per prepared statement
+                            //       and choosing it after several executions pays off only once
+                            //       planning depends on parameter values, e.g. to pick an index.
+                            //       Such plans could be shared by `parser::fingerprint` of statements
+                            let statement = portal.stmt().clone();
+                            match statement {
+                                Statement::SetVariable {
+                                    local, variable, value, ..
+                                } => self.set_variable(local, &variable, &value),
+                                statement => {
+                                    if let Ok(plan) = self.query_planner.plan(&statement) {
+                                        self.execute_plan(&statement, plan);
+                                    }
+                                }
+                            }
+                            self.record_statement(started);
+                            self.end_transaction();
                         }
-                    }
+                    },
                     None => {
                         self.sender
                             .send(Err(QueryError::portal_does_not_exist(portal_name)))
@@ -194,7 +392,7 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                 sql,
                 param_types,
             } => {
-                match parser::Parser::parse_sql(&parser::PreparedStatementDialect, &sql) {
+                match parser::parse_statements(&sql) {
                     Ok(mut statements) => {
                         let statement = statements.pop().expect("single statement");
                         match self.create_prepared_statement(statement_name, statement, param_types) {
@@ -213,143 +411,35 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                 Ok(())
             }
             Command::Query { sql } => {
-                match parser::Parser::parse_sql(&parser::PreparedStatementDialect, &sql) {
-                    Ok(mut statements) => match statements.pop().expect("single query") {
-                        Statement::Prepare {
-                            name,
-                            data_types,
-                            statement,
-                        } => {
-                            let Ident { value: name, .. } = name;
-                            let mut pg_types = vec![];
-                            for data_type in data_types {
-                                match SqlType::try_from(&data_type) {
-                                    Ok(sql_type) => pg_types.push(Some((&sql_type).into())),
-                                    Err(_) => {
-                                        self.sender
-                                            .send(Err(QueryError::type_does_not_exist(data_type)))
-                                            .expect("To Send Error to Client");
-                                        self.sender
-                                            .send(Ok(QueryEvent::QueryComplete))
-                                            .expect("To Send Error to Client");
-                                        return Ok(());
-                                    }
-                                }
-                            }
-                            match self.create_prepared_statement(name, *statement, pg_types) {
-                                Ok(()) => {
-                                    self.sender
-                                        .send(Ok(QueryEvent::StatementPrepared))
-                                        .expect("To Send Result");
-                                }
-                                Err(error) => {
-                                    self.sender.send(Err(error)).expect("To Send Result");
-                                    self.sender
-                                        .send(Ok(QueryEvent::QueryComplete))
-                                        .expect("To Send Error to Client");
-                                    return Ok(());
-                                }
+                self.log_query(&sql);
+                let statements = parser::split_statements(&sql);
+                self.row_counter.take_failed();
+                for (number, (offset, text)) in statements.iter().enumerate() {
+                    let started = Instant::now();
+                    match parser::parse_statements(text) {
+                        Ok(mut parsed) => {
+                            if let Some(statement) = parsed.pop() {
+                                self.execute_statement(statement);
                             }
                         }
-                        Statement::Execute { name, parameters } => {
-                            let Ident { value: name, .. } = name;
-                            match self.session.get_prepared_statement(&name) {
-                                Some(prepared_statement) => {
-                                    let param_types = prepared_statement.param_types();
-                                    if param_types.len() != parameters.len() {
-                                        let message = format!(
-                                            "Bind message supplies {actual} parameters, but prepared statement \"{name}\" requires {expected}",
-                                            name = name,
-                                            actual = parameters.len(),
-                                            expected = param_types.len()
-                                        );
-                                        self.sender
-                                            .send(Err(QueryError::protocol_violation(message)))
-                                            .expect("To Send Error to Client");
-                                    }
-                                    let mut new_stmt = prepared_statement.stmt().clone();
-                                    if let Err(error) = self.param_binder.bind(&mut new_stmt, &parameters) {
-                                        log::error!("{:?}", error);
-                                    }
-                                    match self.query_planner.plan(&new_stmt) {
-                                        Ok(plan) => self.query_executor.execute(plan),
-                                        Err(error) => log::error!("{:?}", error),
-                                    }
-                                }
-                                None => {
-                                    self.sender
-                                        .send(Err(QueryError::prepared_statement_does_not_exist(name)))
-                                        .expect("To Send Error to Client");
-                                }
-                            }
-                        }
-                        Statement::Deallocate { name, .. } => {
-                            let Ident { value: name, .. } = name;
-                            self.session.remove_prepared_statement(&name);
-                            self.sender
-                                .send(Ok(QueryEvent::StatementDeallocated))
-                                .expect("To Send Statement Deallocated Event");
-                        }
-                        statement @ Statement::CreateSchema { .. }
-                        | statement @ Statement::CreateTable { .. }
-                        | statement @ Statement::Drop { .. } => match self.query_analyzer.analyze(statement) {
-                            Ok(QueryAnalysis::DataDefinition(schema_change)) => {
-                                let operations = self.system_planner.schema_change_plan(&schema_change);
-                                let query_result = match self.database.execute(operations.clone()) {
-                                    Ok(ExecutionOutcome::SchemaCreated) => Ok(QueryEvent::SchemaCreated),
-                                    Ok(ExecutionOutcome::SchemaDropped) => Ok(QueryEvent::SchemaDropped),
-                                    Ok(ExecutionOutcome::TableCreated) => Ok(QueryEvent::TableCreated),
-                                    Ok(ExecutionOutcome::TableDropped) => Ok(QueryEvent::TableDropped),
-                                    Err(ExecutionError::SchemaAlreadyExists(schema_name)) => {
-                                        Err(QueryError::schema_already_exists(schema_name))
-                                    }
-                                    Err(ExecutionError::SchemaDoesNotExist(schema_name)) => {
-                                        Err(QueryError::schema_does_not_exist(schema_name))
-                                    }
-                                    Err(ExecutionError::TableAlreadyExists(schema_name, table_name)) => Err(
-                                        QueryError::table_already_exists(format!("{}.{}", schema_name, table_name)),
-                                    ),
-                                    Err(ExecutionError::TableDoesNotExist(schema_name, table_name)) => Err(
-                                        QueryError::table_does_not_exist(format!("{}.{}", schema_name, table_name)),
-                                    ),
-                                    Err(ExecutionError::SchemaHasDependentObjects(schema_name)) => {
-                                        Err(QueryError::schema_has_dependent_objects(schema_name))
-                                    }
-                                };
-                                if query_result.is_ok() {
-                                    self.schema_executor.execute(&schema_change, &operations).unwrap();
-                                }
-                                self.sender.send(query_result).expect("To Send Result to Client");
-                            }
-                            Err(AnalysisError::SchemaDoesNotExist(schema_name)) => self
-                                .sender
-                                .send(Err(QueryError::schema_does_not_exist(schema_name)))
-                                .expect("To Send Result to Client"),
-                            analysis => unreachable!("that couldn't happen {:?}", analysis),
-                        },
-                        statement => match self.query_planner.plan(&statement) {
-                            Ok(plan) => {
-                                self.query_executor.execute(plan);
-                            }
-                            Err(error) => {
-                                let query_error = match error {
-                                    PlanError::SchemaDoesNotExist(schema) => QueryError::schema_does_not_exist(schema),
-                                    PlanError::TableDoesNotExist(table) => QueryError::table_does_not_exist(table),
-                                    PlanError::DuplicateColumn(column) => QueryError::duplicate_column(column),
-                                    PlanError::ColumnDoesNotExist(column) => QueryError::column_does_not_exist(column),
-                                    PlanError::SyntaxError(syntax_error) => QueryError::syntax_error(syntax_error),
-                                    PlanError::FeatureNotSupported(feature_desc) => {
-                                        QueryError::feature_not_supported(feature_desc)
-                                    }
-                                };
-                                self.sender.send(Err(query_error)).expect("To Send Error to Client");
-                            }
-                        },
-                    },
-                    Err(parser_error) => {
-                        self.sender
+                        Err(parser_error) if statements.len() > 1 => self
+                            .sender
+                            .send(Err(QueryError::syntax_error(format!(
+                                "{} in statement {} at position {}",
+                                parser_error,
+                                number + 1,
+                                sql[..*offset].chars().count() + 1
+                            ))))
+                            .expect("To Send Syntax Error Event"),
+                        Err(parser_error) => self
+                            .sender
                             .send(Err(QueryError::syntax_error(parser_error)))
-                            .expect("To Send ParseComplete Event");
+                            .expect("To Send Syntax Error Event"),
+                    }
+                    self.record_statement(started);
+                    self.end_transaction();
+                    if self.row_counter.take_failed() && !self.continue_on_error() {
+                        break;
                     }
                 }
                 self.sender
@@ -357,13 +447,291 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                     .expect("To Send Query Complete to Client");
                 Ok(())
             }
+            Command::Reject { error, simple_query } => {
+                self.sender.send(Err(error)).expect("To Send Error to Client");
+                if simple_query {
+                    self.sender
+                        .send(Ok(QueryEvent::QueryComplete))
+                        .expect("To Send Query Complete to Client");
+                }
+                Ok(())
+            }
             Command::Terminate => {
+                // TODO: temporary tables of the session should be dropped here.
+                //       `CREATE TEMP TABLE` is not recognized by the parser yet
                 log::debug!("closing connection with client");
                 Err(())
             }
         }
     }
 
+    /// runs a single statement of a simple query
+    fn execute_statement(&mut self, statement: Statement) {
+        match self.tracked(statement) {
+            statement if self.read_only_violation(&statement).is_some() => {
+                if let Some(error) = self.read_only_violation(&statement) {
+                    self.permission_denied(&statement, error);
+                }
+            }
+            Statement::SetVariable {
+                local, variable, value, ..
+            } => self.set_variable(local, &variable, &value),
+            Statement::ShowVariable { variable, .. } => self.show_variable(&variable),
+            statement if statistics::selects_statements(&statement) => self
+                .statistics
+                .send_entries(self.sender.as_ref())
+                .expect("To Send Query Result to Client"),
+            statement if statistics::resets_statements(&statement) => {
+                self.statistics.reset();
+                self.sender
+                    .send(Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+                        "pg_stat_statements_reset",
+                        PgType::VarChar,
+                    )])))
+                    .expect("To Send Query Result to Client");
+                self.sender
+                    .send(Ok(QueryEvent::DataRow(vec!["".to_owned()])))
+                    .expect("To Send Query Result to Client");
+                self.sender
+                    .send(Ok(QueryEvent::RecordsSelected(1)))
+                    .expect("To Send Query Result to Client");
+            }
+            statement if evaluation::without_table(&statement) => match self.query_analyzer.analyze(statement) {
+                Ok(QueryAnalysis::Evaluation(query)) => self.evaluate(query),
+                Ok(analysis) => unreachable!("that couldn't happen {:?}", analysis),
+                Err(error) => self
+                    .sender
+                    .send(Err(analysis_error(error)))
+                    .expect("To Send Error to Client"),
+            },
+            Statement::Prepare {
+                name,
+                data_types,
+                statement,
+            } => {
+                let Ident { value: name, .. } = name;
+                let mut pg_types = vec![];
+                for data_type in data_types {
+                    match SqlType::try_from(&data_type) {
+                        Ok(sql_type) => pg_types.push(Some((&sql_type).into())),
+                        Err(_) => {
+                            self.sender
+                                .send(Err(QueryError::type_does_not_exist(data_type)))
+                                .expect("To Send Error to Client");
+                            return;
+                        }
+                    }
+                }
+                match self.create_prepared_statement(name, *statement, pg_types) {
+                    Ok(()) => {
+                        self.sender
+                            .send(Ok(QueryEvent::StatementPrepared))
+                            .expect("To Send Result");
+                    }
+                    Err(error) => {
+                        self.sender.send(Err(error)).expect("To Send Result");
+                    }
+                }
+            }
+            Statement::Execute { name, parameters } => {
+                let Ident { value: name, .. } = name;
+                match self.session.get_prepared_statement(&name) {
+                    Some(prepared_statement) => {
+                        let param_types = prepared_statement.param_types();
+                        if param_types.len() != parameters.len() {
+                            let message = format!(
+                                "Bind message supplies {actual} parameters, but prepared statement \"{name}\" requires {expected}",
+                                name = name,
+                                actual = parameters.len(),
+                                expected = param_types.len()
+                            );
+                            self.sender
+                                .send(Err(QueryError::protocol_violation(message)))
+                                .expect("To Send Error to Client");
+                        }
+                        let mut new_stmt = prepared_statement.stmt().clone();
+                        if let Err(error) = self.param_binder.bind(&mut new_stmt, &parameters) {
+                            log::error!("{:?}", error);
+                        }
+                        match self.read_only_violation(&new_stmt) {
+                            Some(error) => self.permission_denied(&new_stmt, error),
+                            None => match self.query_planner.plan(&new_stmt) {
+                                Ok(plan) => self.execute_plan(&new_stmt, plan),
+                                Err(error) => log::error!("{:?}", error),
+                            },
+                        }
+                    }
+                    None => {
+                        self.sender
+                            .send(Err(QueryError::prepared_statement_does_not_exist(name)))
+                            .expect("To Send Error to Client");
+                    }
+                }
+            }
+            Statement::Deallocate { name, .. } => {
+                let Ident { value: name, .. } = name;
+                self.session.remove_prepared_statement(&name);
+                self.sender
+                    .send(Ok(QueryEvent::StatementDeallocated))
+                    .expect("To Send Statement Deallocated Event");
+            }
+            statement @ Statement::CreateSchema { .. }
+            | statement @ Statement::CreateTable { .. }
+            | statement @ Statement::AlterTable { .. }
+            | statement @ Statement::Drop { .. } => {
+                let with_options = match &statement {
+                    Statement::CreateTable { with_options, .. } => with_options.clone(),
+                    _ => vec![],
+                };
+                for (name, truncated) in query_analyzer::truncated_names(&statement) {
+                    self.sender
+                        .send(Ok(QueryEvent::Notice(QueryNotice::identifier_truncated(
+                            name, truncated,
+                        ))))
+                        .expect("To Send Notice to Client");
+                }
+                match self.query_analyzer.analyze(statement) {
+                    Ok(QueryAnalysis::DataDefinition(schema_change)) => {
+                        let ttl_policy = match &schema_change {
+                            SchemaChange::CreateTable(create_table) => {
+                                match TtlPolicy::from_options(&with_options, &create_table.column_defs) {
+                                    Ok(policy) => policy.map(|policy| (&create_table.table_info, policy)),
+                                    Err(error) => {
+                                        self.sender.send(Err(error)).expect("To Send Result to Client");
+                                        return;
+                                    }
+                                }
+                            }
+                            _ => None,
+                        };
+                        let operations = self.system_planner.schema_change_plan(&schema_change);
+                        let query_result = match self.database.execute(operations.clone()) {
+                            Ok(ExecutionOutcome::SchemaCreated) => Ok(QueryEvent::SchemaCreated),
+                            Ok(ExecutionOutcome::SchemaDropped) => Ok(QueryEvent::SchemaDropped),
+                            Ok(ExecutionOutcome::TableCreated) => Ok(QueryEvent::TableCreated),
+                            Ok(ExecutionOutcome::TableDropped) => Ok(QueryEvent::TableDropped),
+                            Ok(ExecutionOutcome::TableAltered) => Ok(QueryEvent::TableAltered),
+                            Err(ExecutionError::SchemaAlreadyExists(schema_name)) => {
+                                Err(QueryError::schema_already_exists(schema_name))
+                            }
+                            Err(ExecutionError::SchemaDoesNotExist(schema_name)) => {
+                                Err(QueryError::schema_does_not_exist(schema_name))
+                            }
+                            Err(ExecutionError::TableAlreadyExists(schema_name, table_name)) => Err(
+                                QueryError::table_already_exists(format!("{}.{}", schema_name, table_name)),
+                            ),
+                            Err(ExecutionError::TableDoesNotExist(schema_name, table_name)) => Err(
+                                QueryError::table_does_not_exist(format!("{}.{}", schema_name, table_name)),
+                            ),
+                            Err(ExecutionError::SchemaHasDependentObjects(schema_name)) => {
+                                Err(QueryError::schema_has_dependent_objects(schema_name))
+                            }
+                        };
+                        let query_result = query_result.and_then(|event| {
+                            match self.schema_executor.execute(&schema_change, &operations) {
+                                Ok(_) => {
+                                    self.table_versions.catalog_changed();
+                                    Ok(event)
+                                }
+                                Err(error) => {
+                                    self.undo_schema_change(&schema_change);
+                                    Err(storage_error(error))
+                                }
+                            }
+                        });
+                        if query_result.is_ok() {
+                            if let Some((table_info, policy)) = ttl_policy {
+                                self.ttl_policies
+                                    .set(&table_info.schema_name, &table_info.table_name, policy);
+                            }
+                            if let Some(statement) = self.tracked_statement.as_ref() {
+                                self.audit_log.record(
+                                    AuditCategory::Ddl,
+                                    Some(self.conn_id),
+                                    self.session.user(),
+                                    statement,
+                                );
+                            }
+                        }
+                        self.sender.send(query_result).expect("To Send Result to Client");
+                    }
+                    Err(error) => self
+                        .sender
+                        .send(Err(analysis_error(error)))
+                        .expect("To Send Result to Client"),
+                    analysis => unreachable!("that couldn't happen {:?}", analysis),
+                }
+            }
+            statement => match self.query_planner.plan(&statement) {
+                Ok(plan) => self.execute_plan(&statement, plan),
+                Err(error) => {
+                    let query_error = match error {
+                        PlanError::SchemaDoesNotExist(schema) => QueryError::schema_does_not_exist(schema),
+                        PlanError::TableDoesNotExist(table) => QueryError::table_does_not_exist(table),
+                        PlanError::DuplicateColumn(column) => QueryError::duplicate_column(column),
+                        PlanError::ColumnDoesNotExist(column) => QueryError::column_does_not_exist(column),
+                        PlanError::SyntaxError(syntax_error) => QueryError::syntax_error(syntax_error),
+                        PlanError::FeatureNotSupported(feature_desc) => QueryError::feature_not_supported(feature_desc),
+                    };
+                    self.sender.send(Err(query_error)).expect("To Send Error to Client");
+                }
+            },
+        }
+    }
+
+    /// whether statements of a script are run after one of them failed
+    fn continue_on_error(&self) -> bool {
+        self.session
+            .settings()
+            .get("continue_on_error")
+            .ok()
+            .and_then(SettingValue::as_bool)
+            .unwrap_or(false)
+    }
+
+    fn evaluate(&mut self, query: EvaluationQuery) {
+        let EvaluationQuery {
+            column_names,
+            projection_items,
+        } = query;
+        let column_types = projection_items
+            .iter()
+            .map(evaluation::pg_type)
+            .collect::<Vec<PgType>>();
+        let mut evaluator = Evaluation::new(&mut self.session, self.conn_id, &self.backend, &self.backends);
+        let values = projection_items
+            .iter()
+            .map(|item| evaluator.eval(item))
+            .collect::<Result<Vec<_>, QueryError>>();
+        for name in evaluator.changed_settings() {
+            self.setting_changed(&name);
+        }
+        let values = match values {
+            Ok(values) => values,
+            Err(error) => {
+                self.sender.send(Err(error)).expect("To Send Error to Client");
+                return;
+            }
+        };
+        self.sender
+            .send(Ok(QueryEvent::RowDescription(
+                column_names
+                    .iter()
+                    .zip(column_types.into_iter())
+                    .map(|(name, pg_type)| ColumnMetadata::new(name, pg_type))
+                    .collect(),
+            )))
+            .expect("To Send Query Result to Client");
+        self.sender
+            .send(Ok(QueryEvent::NullableDataRow(
+                values.into_iter().map(evaluation::text).collect(),
+            )))
+            .expect("To Send Query Result to Client");
+        self.sender
+            .send(Ok(QueryEvent::RecordsSelected(1)))
+            .expect("To Send Query Result to Client");
+    }
+
     fn bind_prepared_statement(
         &self,
         prepared_statement: &PreparedStatement<Statement>,
@@ -388,6 +756,10 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                 None => params.push(Expr::Value(Value::Null)),
                 Some(bytes) => {
                     log::debug!("PG Type {:?}", typ);
+                    if let Some(error) = text_encoding_error(typ, &format, bytes) {
+                        self.sender.send(Err(error)).expect("To Send Error to Client");
+                        return Err(());
+                    }
                     match typ.decode(&format, &bytes) {
                         Ok(param) => params.push(value_to_expr(param)),
                         Err(msg) => {
@@ -428,76 +800,29 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
     ) -> Result<(), QueryError> {
         match self.query_planner.plan(&statement) {
             Ok(plan) => match plan {
-                Plan::Select(select_input) => {
-                    let description = self.describe(select_input);
-                    let statement = PreparedStatement::new(
-                        statement,
-                        param_types.iter().filter(|o| o.is_some()).map(|o| o.unwrap()).collect(),
-                        description,
-                    );
-                    self.session.set_prepared_statement(statement_name, statement);
-                    Ok(())
-                }
-                Plan::Insert(_insert_table) => match self.old_query_analyzer.describe(&statement) {
-                    Ok(Description::Insert(insert_statement)) => {
-                        let mut new_param_types = vec![];
-                        for index in 0..insert_statement.param_count {
-                            let param_type = match param_types.get(index) {
-                                Some(t) => *t,
-                                None => None,
-                            };
-                            let param_type = match param_type {
-                                Some(t) => t,
-                                None => match insert_statement.param_types.get(&index) {
-                                    Some(sql_type) => sql_type.into(),
-                                    None => return Err(QueryError::indeterminate_parameter_data_type(index)),
-                                },
-                            };
-                            new_param_types.push(param_type);
-                        }
-
-                        let statement = PreparedStatement::new(statement, new_param_types, vec![]);
-                        self.session.set_prepared_statement(statement_name, statement);
-                        Ok(())
-                    }
-                    Err(DescriptionError::TableDoesNotExist(table_name)) => {
-                        Err(QueryError::table_does_not_exist(table_name))
-                    }
-                    Err(DescriptionError::SchemaDoesNotExist(schema_name)) => {
-                        Err(QueryError::table_does_not_exist(schema_name))
-                    }
-                    _ => unreachable!("this should not be reached during insertions"),
-                },
-                Plan::Update(_update_table) => match self.old_query_analyzer.describe(&statement) {
-                    Ok(Description::Update(update_statement)) => {
-                        let mut new_param_types = vec![];
-                        for index in 0..update_statement.param_count {
-                            let param_type = match param_types.get(index) {
-                                Some(t) => *t,
-                                None => None,
-                            };
-                            let param_type = match param_type {
-                                Some(t) => t,
-                                None => match update_statement.param_types.get(&index) {
-                                    Some(sql_type) => sql_type.into(),
-                                    None => return Err(QueryError::indeterminate_parameter_data_type(index)),
-                                },
-                            };
-                            new_param_types.push(param_type);
-                        }
-
-                        let statement = PreparedStatement::new(statement, new_param_types, vec![]);
-                        self.session.set_prepared_statement(statement_name, statement);
-                        Ok(())
+                Plan::Select(_) | Plan::Insert(_) | Plan::Update(_) => {
+                    let description = self.query_analyzer.describe(&statement).map_err(analysis_error)?;
+                    let param_count = description.param_types.len().max(param_types.len());
+                    let mut new_param_types = vec![];
+                    for index in 0..param_count {
+                        let param_type = match (param_types.get(index), description.param_types.get(index)) {
+                            (Some(Some(pg_type)), _) => *pg_type,
+                            (_, Some(Some(sql_type))) => sql_type.into(),
+                            _ => return Err(QueryError::indeterminate_parameter_data_type(index)),
+                        };
+                        new_param_types.push(param_type);
                     }
-                    Err(DescriptionError::TableDoesNotExist(table_name)) => {
-                        Err(QueryError::table_does_not_exist(table_name))
-                    }
-                    Err(DescriptionError::SchemaDoesNotExist(schema_name)) => {
-                        Err(QueryError::table_does_not_exist(schema_name))
-                    }
-                    _ => unreachable!("this should not be reached during updates"),
-                },
+                    // TODO: table ids survive restarts now, so they could be sent as table OIDs
+                    //       of RowDescription fields. `ColumnMetadata` of pg_wire has only name
+                    //       and type of a column
+                    let result_columns = description
+                        .result_columns
+                        .into_iter()
+                        .map(|column| (column.name, (&column.sql_type).into()))
+                        .collect();
+                    let statement = PreparedStatement::new(statement, new_param_types, result_columns);
+                    self.session.set_prepared_statement(statement_name, statement)
+                }
                 Plan::NotProcessed(statement) => match statement.deref() {
                     stmt @ Statement::SetVariable { .. } => {
                         let statement = PreparedStatement::new(
@@ -505,8 +830,7 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
                             param_types.iter().filter(|o| o.is_some()).map(|o| o.unwrap()).collect(),
                             vec![],
                         );
-                        self.session.set_prepared_statement(statement_name, statement);
-                        Ok(())
+                        self.session.set_prepared_statement(statement_name, statement)
                     }
                     stmt => {
                         log::error!("Error while describing not supported extended query for {:?}", stmt);
@@ -529,12 +853,308 @@ impl<D: Database + CatalogDefinition> QueryEngine<D> {
         }
     }
 
-    fn describe(&self, select_input: SelectInput) -> pg_model::results::Description {
-        self.data_manager
-            .column_defs(&select_input.table_id, &select_input.selected_columns)
-            .into_iter()
-            .map(|column_definition| (column_definition.name(), (&column_definition.sql_type()).into()))
-            .collect()
+    /// executes the plan serving selects from the result cache when the session
+    /// enabled it. Writes change version of the table after they are done, so
+    /// results read while a write was in progress are not served later
+    fn execute_plan(&mut self, statement: &Statement, plan: Plan) {
+        let written_table = match &plan {
+            Plan::Insert(table_inserts) => Some(table_inserts.table_id.clone()),
+            Plan::Update(table_updates) => Some(table_updates.table_id.clone()),
+            Plan::Delete(table_deletes) => Some(table_deletes.table_id.clone()),
+            Plan::Select(_) | Plan::NotProcessed(_) => None,
+        };
+        match plan {
+            Plan::Select(select_input) if self.session.is_result_cache_enabled() => {
+                let query = statement.to_string();
+                let versions = self.table_versions.of(&select_input.table_id);
+                match self.result_cache.get(&query, versions) {
+                    Some(events) => {
+                        for event in events {
+                            self.sender
+                                .send(Ok(event.clone()))
+                                .expect("To Send Query Result to Client");
+                        }
+                    }
+                    None => {
+                        self.result_recorder.start();
+                        self.query_executor.execute(Plan::Select(select_input));
+                        if let Some(events) = self.result_recorder.finish() {
+                            self.result_cache.put(query, versions, events);
+                        }
+                    }
+                }
+            }
+            plan => self.query_executor.execute(plan),
+        }
+        if let Some(table_id) = written_table {
+            self.table_versions.table_written(&table_id);
+        }
+    }
+
+    fn log_query(&self, sql: &str) {
+        let application_name = self
+            .session
+            .settings()
+            .get("application_name")
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        log::debug!(
+            "connection-{} [{}] query {:016x}: {}",
+            self.conn_id,
+            application_name,
+            parser::fingerprint(sql),
+            sql
+        );
+    }
+
+    /// sends confirmation of a setting change or an error if there is no such
+    /// setting or the value does not fit it. `RESET` is parsed as setting
+    /// to `DEFAULT`
+    fn set_variable(&mut self, local: bool, variable: &Ident, value: &SetVariableValue) {
+        let result = match value {
+            SetVariableValue::Ident(Ident {
+                value: default,
+                quote_style: None,
+            }) if default.eq_ignore_ascii_case("default") => {
+                self.reset_variable(&variable.value.to_lowercase());
+                return;
+            }
+            value => self
+                .session
+                .settings_mut()
+                .set(&variable.value, &setting_text(value), local),
+        };
+        match result {
+            Ok(()) => {
+                self.setting_changed(&variable.value.to_lowercase());
+                self.sender
+                    .send(Ok(QueryEvent::VariableSet))
+                    .expect("To Send Variable Set Event");
+            }
+            Err(error) => self.sender.send(Err(error)).expect("To Send Error to Client"),
+        }
+    }
+
+    fn reset_variable(&mut self, name: &str) {
+        let changed = if name == "all" {
+            Ok(self.session.settings_mut().reset_all())
+        } else {
+            self.session.settings_mut().reset(name).map(|()| vec![name.to_owned()])
+        };
+        match changed {
+            Ok(changed) => {
+                for name in changed {
+                    self.setting_changed(&name);
+                }
+                self.sender
+                    .send(Ok(QueryEvent::VariableSet))
+                    .expect("To Send Variable Set Event");
+            }
+            Err(error) => self.sender.send(Err(error)).expect("To Send Error to Client"),
+        }
+    }
+
+    fn show_variable(&self, variable: &Ident) {
+        let settings = self.session.settings();
+        let (columns, rows) = if variable.value.eq_ignore_ascii_case("all") {
+            (
+                vec!["name", "setting"],
+                settings
+                    .all()
+                    .into_iter()
+                    .map(|(name, value)| vec![name.to_owned(), value.to_string()])
+                    .collect::<Vec<Vec<String>>>(),
+            )
+        } else {
+            match settings.get(&variable.value) {
+                Ok(value) => (vec![variable.value.as_str()], vec![vec![value.to_string()]]),
+                Err(error) => {
+                    self.sender.send(Err(error)).expect("To Send Error to Client");
+                    return;
+                }
+            }
+        };
+        self.sender
+            .send(Ok(QueryEvent::RowDescription(
+                columns
+                    .into_iter()
+                    .map(|name| ColumnMetadata::new(name.to_lowercase(), PgType::VarChar))
+                    .collect(),
+            )))
+            .expect("To Send Query Result to Client");
+        let selected = rows.len();
+        for row in rows {
+            self.sender
+                .send(Ok(QueryEvent::DataRow(row)))
+                .expect("To Send Query Result to Client");
+        }
+        self.sender
+            .send(Ok(QueryEvent::RecordsSelected(selected)))
+            .expect("To Send Query Result to Client");
+    }
+
+    /// passes current value of a setting to the components that use it
+    fn setting_changed(&mut self, name: &str) {
+        let value = match self.session.settings().get(name) {
+            Ok(value) => value.clone(),
+            Err(_) => return,
+        };
+        match name {
+            "compatibility_mode" => {
+                self.query_executor
+                    .set_strict_casts(value == SettingValue::String("strict".to_owned()));
+            }
+            "default_transaction_read_only" | "transaction_read_only" => {
+                let settings = self.session.settings();
+                let read_only = ["default_transaction_read_only", "transaction_read_only"]
+                    .iter()
+                    .any(|name| settings.get(name).ok().and_then(SettingValue::as_bool) == Some(true));
+                self.session.set_read_only(read_only);
+            }
+            "protocol_trace" => {
+                if let Some(enabled) = value.as_bool() {
+                    self.protocol_trace.store(enabled, Ordering::Relaxed);
+                }
+            }
+            "result_cache" => {
+                if let Some(enabled) = value.as_bool() {
+                    self.session.set_result_cache(enabled);
+                    self.result_cache.clear();
+                }
+            }
+            "work_mem" => {
+                if let Some(bytes) = value.as_integer() {
+                    self.query_executor
+                        .set_work_mem(usize::try_from(bytes).unwrap_or(usize::MAX));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// values of `SET LOCAL` last till the end of a transaction. There are no
+    /// transaction blocks yet, so every statement runs in its own transaction
+    fn end_transaction(&mut self) {
+        for name in self.session.settings_mut().end_transaction() {
+            self.setting_changed(&name);
+        }
+    }
+
+    /// schema changes are applied to the catalog of the session first and then
+    /// to the storage shared by all connections. Another connection could have
+    /// changed the storage in between, then the change is taken back from the
+    /// session catalog so that both of them stay in sync
+    fn undo_schema_change(&self, schema_change: &SchemaChange) {
+        if let Some(rollback) = self.system_planner.rollback_plan(schema_change) {
+            if let Err(error) = self.database.execute(rollback) {
+                log::error!("could not undo {:?} due to {:?}", schema_change, error);
+            }
+        }
+    }
+
+    fn tracked(&mut self, statement: Statement) -> Statement {
+        self.tracked_statement = Some(statement.to_string());
+        self.row_counter.take();
+        statement
+    }
+
+    fn record_statement(&mut self, started: Instant) {
+        if let Some(query) = self.tracked_statement.take() {
+            let elapsed = if self.deterministic {
+                Duration::default()
+            } else {
+                started.elapsed()
+            };
+            self.statistics
+                .record(parser::normalize(&query), elapsed, self.row_counter.take());
+        }
+    }
+
+    fn permission_denied(&self, statement: &Statement, error: QueryError) {
+        self.audit_log.record(
+            AuditCategory::Permission,
+            Some(self.conn_id),
+            self.session.user(),
+            &format!("{} is not allowed in a read-only transaction", statement),
+        );
+        self.sender.send(Err(error)).expect("To Send Error to Client");
+    }
+
+    fn read_only_violation(&self, statement: &Statement) -> Option<QueryError> {
+        if !self.node_read_only && !self.session.is_read_only() {
+            return None;
+        }
+        let statement = match statement {
+            Statement::Insert { .. } => "INSERT".to_owned(),
+            Statement::Update { .. } => "UPDATE".to_owned(),
+            Statement::Delete { .. } => "DELETE".to_owned(),
+            Statement::CreateSchema { .. } => "CREATE SCHEMA".to_owned(),
+            Statement::CreateTable { .. } => "CREATE TABLE".to_owned(),
+            Statement::AlterTable { .. } => "ALTER TABLE".to_owned(),
+            Statement::Drop { object_type, .. } => format!("DROP {}", object_type),
+            _ => return None,
+        };
+        Some(QueryError::read_only_sql_transaction(statement))
+    }
+}
+
+// parameters in text format and values of string types in any format are text
+// sent in the client encoding
+fn text_encoding_error(pg_type: &PgType, format: &PgFormat, bytes: &[u8]) -> Option<QueryError> {
+    match (pg_type, format) {
+        (_, PgFormat::Text) | (PgType::Char, _) | (PgType::VarChar, _) => encoding::decode(bytes).err(),
+        _ => None,
+    }
+}
+
+fn analysis_error(error: AnalysisError) -> QueryError {
+    match error {
+        AnalysisError::SchemaDoesNotExist(schema_name) => QueryError::schema_does_not_exist(schema_name),
+        AnalysisError::TableDoesNotExist(table_name) => QueryError::table_does_not_exist(table_name),
+        AnalysisError::ColumnNotFound(column_name) => QueryError::column_does_not_exist(column_name),
+        AnalysisError::DuplicateColumn(column_name) => QueryError::duplicate_column(column_name),
+        AnalysisError::SchemaNamingError(message)
+        | AnalysisError::TableNamingError(message)
+        | AnalysisError::SyntaxError(message) => QueryError::syntax_error(message),
+        AnalysisError::InvalidName(message) => QueryError::invalid_name(message),
+        AnalysisError::UndefinedCollation(collation) => QueryError::undefined_collation(collation),
+        AnalysisError::CollationNotSupported(sql_type) => QueryError::collation_not_supported(sql_type),
+        AnalysisError::TypeIsNotSupported(type_name) => QueryError::feature_not_supported(type_name),
+        AnalysisError::FeatureNotSupported(feature) => QueryError::feature_not_supported(format!("{:?}", feature)),
+        AnalysisError::UndefinedFunctionCall { call, .. } => QueryError::undefined_function_call(call),
+        error => QueryError::syntax_error(format!("{:?}", error)),
+    }
+}
+
+fn storage_error(error: schema_executor::ExecutionError) -> QueryError {
+    match error {
+        schema_executor::ExecutionError::SchemaAlreadyExists(schema_name) => {
+            QueryError::schema_already_exists(schema_name)
+        }
+        schema_executor::ExecutionError::SchemaDoesNotExist(schema_name) => {
+            QueryError::schema_does_not_exist(schema_name)
+        }
+        schema_executor::ExecutionError::TableAlreadyExists(schema_name, table_name) => {
+            QueryError::table_already_exists(format!("{}.{}", schema_name, table_name))
+        }
+        schema_executor::ExecutionError::TableDoesNotExists(schema_name, table_name) => {
+            QueryError::table_does_not_exist(format!("{}.{}", schema_name, table_name))
+        }
+        schema_executor::ExecutionError::TableNotRewritten(schema_name, table_name) => {
+            log::error!("rows of {}.{} could not be rewritten", schema_name, table_name);
+            QueryError::data_corrupted()
+        }
+        schema_executor::ExecutionError::TableInUse(schema_name, table_name) => {
+            QueryError::object_in_use(format!("{}.{}", schema_name, table_name))
+        }
+    }
+}
+
+fn setting_text(value: &SetVariableValue) -> String {
+    match value {
+        SetVariableValue::Ident(Ident { value, .. }) => value.clone(),
+        SetVariableValue::Literal(Value::SingleQuotedString(value)) => value.clone(),
+        SetVariableValue::Literal(value) => value.to_string(),
     }
 }
 
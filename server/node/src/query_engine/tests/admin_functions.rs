@@ -0,0 +1,175 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+fn connection(backends: &Arc<Backends>, conn_id: ConnId) -> (InMemory, ResultCollector) {
+    let collector = Collector::new();
+    let mut engine = InMemory::new(
+        collector.clone(),
+        Arc::new(DatabaseHandle::in_memory()),
+        InMemoryDatabase::new(),
+    );
+    engine.set_backends(conn_id, backends.clone());
+    (engine, collector)
+}
+
+fn single_value(name: &str, pg_type: PgType, value: &str) -> Vec<QueryResult> {
+    vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(name, pg_type)])),
+        Ok(QueryEvent::NullableDataRow(vec![Some(value.to_owned())])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]
+}
+
+#[test]
+fn backend_pid() {
+    let backends = Arc::new(Backends::default());
+    let (mut engine, collector) = connection(&backends, 10);
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_backend_pid();".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(single_value("pg_backend_pid", PgType::Integer, "10"));
+}
+
+#[test]
+fn backend_pid_in_expressions() {
+    let backends = Arc::new(Backends::default());
+    let (mut engine, collector) = connection(&backends, 10);
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_backend_pid() * 2, pg_cancel_backend(pg_backend_pid() + 1);".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("?column?", PgType::Integer),
+            ColumnMetadata::new("pg_cancel_backend", PgType::Bool),
+        ])),
+        Ok(QueryEvent::NullableDataRow(vec![
+            Some("20".to_owned()),
+            Some("f".to_owned()),
+        ])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+}
+
+#[rstest::rstest]
+fn division_by_zero(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_backend_pid() / 0;".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_single(Err(QueryError::division_by_zero()));
+}
+
+#[rstest::rstest]
+fn sleep(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_sleep(0.01);".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+            "pg_sleep",
+            PgType::VarChar,
+        )])),
+        Ok(QueryEvent::NullableDataRow(vec![None])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+}
+
+#[test]
+fn cancel_non_existent_backend() {
+    let backends = Arc::new(Backends::default());
+    let (mut engine, collector) = connection(&backends, 1);
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_cancel_backend(2);".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(single_value("pg_cancel_backend", PgType::Bool, "f"));
+}
+
+#[test]
+fn invalid_process_id() {
+    let backends = Arc::new(Backends::default());
+    let (mut engine, collector) = connection(&backends, 1);
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_cancel_backend(-1);".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value("invalid process ID: -1")));
+}
+
+#[test]
+fn terminate_other_backend() {
+    let backends = Arc::new(Backends::default());
+    let (mut engine, collector) = connection(&backends, 1);
+    let (mut other, other_collector) = connection(&backends, 2);
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_terminate_backend(2);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(single_value("pg_terminate_backend", PgType::Bool, "t"));
+
+    assert_eq!(
+        other.execute(Command::Query {
+            sql: "select pg_backend_pid();".to_owned(),
+        }),
+        Err(())
+    );
+    other_collector.assert_receive_till_this_moment(vec![Err(QueryError::admin_shutdown())]);
+}
+
+#[test]
+fn terminated_backend_is_removed() {
+    let backends = Arc::new(Backends::default());
+    let (engine, _collector) = connection(&backends, 2);
+    drop(engine);
+    let (mut other, collector) = connection(&backends, 1);
+
+    other
+        .execute(Command::Query {
+            sql: "select pg_terminate_backend(2);".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(single_value("pg_terminate_backend", PgType::Bool, "f"));
+}
@@ -0,0 +1,115 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::audit_log::{
+    tests::{audit_log, fields},
+    AuditLog,
+};
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    session::DEFAULT_USER,
+    Command,
+};
+
+fn audited(engine: &mut InMemory, audit_log: AuditLog) {
+    engine.set_backends(1, Arc::new(Backends::default()));
+    engine.set_audit_log(Arc::new(audit_log));
+}
+
+#[rstest::rstest]
+fn schema_changes(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    let (audit_log, output) = audit_log("ddl");
+    audited(&mut engine, audit_log);
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "drop schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaDropped));
+
+    assert_eq!(
+        fields(&output.content()),
+        vec![
+            vec!["ddl", "connection-1", DEFAULT_USER, "CREATE SCHEMA schema_name"],
+            vec!["ddl", "connection-1", DEFAULT_USER, "DROP SCHEMA schema_name"],
+        ]
+    );
+}
+
+#[rstest::rstest]
+fn failed_schema_change_is_not_recorded(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    let (audit_log, output) = audit_log("ddl");
+    audited(&mut engine, audit_log);
+
+    engine
+        .execute(Command::Query {
+            sql: "drop schema non_existent;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::schema_does_not_exist("non_existent")));
+
+    assert_eq!(output.content(), "");
+}
+
+#[rstest::rstest]
+fn denied_statement(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    let (audit_log, output) = audit_log("permission");
+    audited(&mut engine, audit_log);
+    engine.set_node_read_only(true);
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::read_only_sql_transaction("CREATE SCHEMA")));
+
+    assert_eq!(
+        fields(&output.content()),
+        vec![vec![
+            "permission",
+            "connection-1",
+            DEFAULT_USER,
+            "CREATE SCHEMA schema_name is not allowed in a read-only transaction"
+        ]]
+    );
+}
+
+#[rstest::rstest]
+fn disabled_category(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    let (audit_log, output) = audit_log("authentication,permission");
+    audited(&mut engine, audit_log);
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+
+    assert_eq!(output.content(), "");
+}
@@ -0,0 +1,120 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+fn strict(engine: &mut InMemory, collector: &ResultCollector) {
+    engine
+        .execute(Command::Query {
+            sql: "set compatibility_mode = strict;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+}
+
+#[rstest::rstest]
+fn string_cast_to_number_by_default(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values ('1', 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+}
+
+#[rstest::rstest]
+fn insert_string_into_number_column(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    strict(&mut engine, &collector);
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values ('1', 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::type_mismatch("1", PgType::SmallInt, "col1", 1)));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values (1, 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+}
+
+#[rstest::rstest]
+fn update_number_column_with_string(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values (1, 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+    strict(&mut engine, &collector);
+
+    engine
+        .execute(Command::Query {
+            sql: "update schema_name.table_name set col2 = '5';".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::type_mismatch("5", PgType::SmallInt, "col2", 1)));
+}
+
+#[rstest::rstest]
+fn strict_node(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    engine.set_strict_compatibility(true);
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values ('1', 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::type_mismatch("1", PgType::SmallInt, "col1", 1)));
+
+    engine
+        .execute(Command::Query {
+            sql: "set compatibility_mode = postgresql;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values ('1', 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+}
+
+#[rstest::rstest]
+fn unknown_mode(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "set compatibility_mode = mysql;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+        "invalid value for parameter \"compatibility_mode\": \"mysql\"",
+    )));
+}
@@ -0,0 +1,136 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+#[rstest::rstest]
+fn rejected_query(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Reject {
+            error: QueryError::invalid_byte_sequence("UTF8", &[0xc3, 0x28]),
+            simple_query: true,
+        })
+        .expect("query rejected");
+
+    collector.assert_receive_single(Err(QueryError::invalid_byte_sequence("UTF8", &[0xc3, 0x28])));
+}
+
+#[rstest::rstest]
+fn rejected_statement(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Reject {
+            error: QueryError::invalid_byte_sequence("UTF8", &[0xff]),
+            simple_query: false,
+        })
+        .expect("statement rejected");
+
+    collector.assert_receive_till_this_moment(vec![Err(QueryError::invalid_byte_sequence("UTF8", &[0xff]))]);
+}
+
+#[cfg(test)]
+mod parameters {
+    use super::*;
+
+    #[rstest::fixture]
+    fn prepared_insert(database_with_schema: (InMemory, ResultCollector)) -> (InMemory, ResultCollector) {
+        let (mut engine, collector) = database_with_schema;
+        engine
+            .execute(Command::Query {
+                sql: "create table schema_name.table_name (col1 varchar(10), col2 smallint);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "statement_name".to_owned(),
+                sql: "insert into schema_name.table_name values ($1, $2);".to_owned(),
+                param_types: vec![Some(PgType::VarChar), Some(PgType::SmallInt)],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+
+        (engine, collector)
+    }
+
+    #[rstest::rstest]
+    fn valid_text(prepared_insert: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = prepared_insert;
+        engine
+            .execute(Command::Bind {
+                statement_name: "statement_name".to_owned(),
+                portal_name: "portal_name".to_owned(),
+                param_formats: vec![PgFormat::Text, PgFormat::Text],
+                raw_params: vec![Some("жук".as_bytes().to_vec()), Some(b"2".to_vec())],
+                result_formats: vec![],
+            })
+            .expect("statement bound to portal");
+
+        collector.assert_receive_intermediate(Ok(QueryEvent::BindComplete));
+    }
+
+    #[rstest::rstest]
+    fn invalid_string(prepared_insert: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = prepared_insert;
+        engine
+            .execute(Command::Bind {
+                statement_name: "statement_name".to_owned(),
+                portal_name: "portal_name".to_owned(),
+                param_formats: vec![PgFormat::Binary, PgFormat::Text],
+                raw_params: vec![Some(vec![b'a', 0xe2, 0x82]), Some(b"2".to_vec())],
+                result_formats: vec![],
+            })
+            .expect("statement bound to portal");
+
+        collector.assert_receive_intermediate(Err(QueryError::invalid_byte_sequence("UTF8", &[0xe2, 0x82])));
+    }
+
+    #[rstest::rstest]
+    fn string_with_nul_character(prepared_insert: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = prepared_insert;
+        engine
+            .execute(Command::Bind {
+                statement_name: "statement_name".to_owned(),
+                portal_name: "portal_name".to_owned(),
+                param_formats: vec![PgFormat::Text, PgFormat::Text],
+                raw_params: vec![Some(b"a\0b".to_vec()), Some(b"2".to_vec())],
+                result_formats: vec![],
+            })
+            .expect("statement bound to portal");
+
+        collector.assert_receive_intermediate(Err(QueryError::invalid_byte_sequence("UTF8", &[0x00])));
+    }
+
+    #[rstest::rstest]
+    fn invalid_text_of_number(prepared_insert: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = prepared_insert;
+        engine
+            .execute(Command::Bind {
+                statement_name: "statement_name".to_owned(),
+                portal_name: "portal_name".to_owned(),
+                param_formats: vec![PgFormat::Text, PgFormat::Text],
+                raw_params: vec![Some(b"a".to_vec()), Some(vec![0xff])],
+                result_formats: vec![],
+            })
+            .expect("statement bound to portal");
+
+        collector.assert_receive_intermediate(Err(QueryError::invalid_byte_sequence("UTF8", &[0xff])));
+    }
+}
@@ -392,3 +392,174 @@ mod parse_bind_execute {
         }
     }
 }
+
+#[cfg(test)]
+mod statement_lifecycle {
+    use super::*;
+    use pg_model::results::QueryError;
+
+    #[rstest::rstest]
+    fn reparse_named_statement(database_with_table: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_table;
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "statement_name".to_owned(),
+                sql: "select * from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "statement_name".to_owned(),
+                sql: "select col1 from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Err(QueryError::duplicate_prepared_statement("statement_name")));
+    }
+
+    #[rstest::rstest]
+    fn reparse_unnamed_statement(database_with_table: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_table;
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "".to_owned(),
+                sql: "select * from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "".to_owned(),
+                sql: "select col1 from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+
+        engine
+            .execute(Command::DescribeStatement { name: "".to_owned() })
+            .expect("statement described");
+        collector.assert_receive_intermediate(Ok(QueryEvent::StatementDescription(vec![(
+            "col1".to_owned(),
+            PgType::SmallInt,
+        )])));
+        collector.assert_receive_intermediate(Ok(QueryEvent::StatementParameters(vec![])));
+    }
+
+    #[rstest::rstest]
+    fn parse_close_parse_named_statement(database_with_table: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_table;
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "statement_name".to_owned(),
+                sql: "select * from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+
+        engine
+            .execute(Command::CloseStatement {
+                name: "statement_name".to_owned(),
+            })
+            .expect("statement closed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::CloseComplete));
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "statement_name".to_owned(),
+                sql: "select col1 from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+    }
+
+    #[rstest::rstest]
+    fn closed_statement_does_not_exist(database_with_table: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_table;
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "statement_name".to_owned(),
+                sql: "select * from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+
+        engine
+            .execute(Command::CloseStatement {
+                name: "statement_name".to_owned(),
+            })
+            .expect("statement closed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::CloseComplete));
+
+        engine
+            .execute(Command::DescribeStatement {
+                name: "statement_name".to_owned(),
+            })
+            .expect("no errors");
+        collector.assert_receive_intermediate(Err(QueryError::prepared_statement_does_not_exist("statement_name")));
+    }
+
+    #[rstest::rstest]
+    fn close_portal(database_with_table: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_table;
+
+        engine
+            .execute(Command::Parse {
+                statement_name: "statement_name".to_owned(),
+                sql: "select * from schema_name.table_name;".to_owned(),
+                param_types: vec![],
+            })
+            .expect("statement parsed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+
+        engine
+            .execute(Command::Bind {
+                statement_name: "statement_name".to_owned(),
+                portal_name: "portal_name".to_owned(),
+                param_formats: vec![],
+                raw_params: vec![],
+                result_formats: vec![],
+            })
+            .expect("statement bound to portal");
+        collector.assert_receive_intermediate(Ok(QueryEvent::BindComplete));
+
+        engine
+            .execute(Command::ClosePortal {
+                name: "portal_name".to_owned(),
+            })
+            .expect("portal closed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::CloseComplete));
+
+        engine
+            .execute(Command::Execute {
+                portal_name: "portal_name".to_owned(),
+                max_rows: 0,
+            })
+            .expect("no errors");
+        collector.assert_receive_intermediate(Err(QueryError::portal_does_not_exist("portal_name")));
+    }
+
+    #[rstest::rstest]
+    fn close_non_existent_statement(database_with_table: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_table;
+
+        engine
+            .execute(Command::CloseStatement {
+                name: "non_existent".to_owned(),
+            })
+            .expect("statement closed");
+        collector.assert_receive_intermediate(Ok(QueryEvent::CloseComplete));
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+#[rstest::rstest]
+fn unquoted_names_are_folded_to_lower_case(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "create schema Schema_Name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "create table SCHEMA_NAME.Table_Name (Col smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name (COL) values (1);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+    engine
+        .execute(Command::Query {
+            sql: "select cOL from schema_name.TABLE_NAME;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+            "col",
+            PgType::SmallInt,
+        )])),
+        Ok(QueryEvent::DataRow(vec!["1".to_owned()])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+}
+
+#[rstest::rstest]
+fn quoted_schema_name_keeps_case(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "create schema \"Schema_Name\";".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "create table Schema_Name.table_name (col smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::schema_does_not_exist("schema_name")));
+
+    engine
+        .execute(Command::Query {
+            sql: "create table \"Schema_Name\".table_name (col smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+}
+
+#[rstest::rstest]
+fn quoted_and_unquoted_column_names_are_different(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.table_name (\"Col\" smallint, col smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name (COL, \"Col\") values (2, 1);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+    engine
+        .execute(Command::Query {
+            sql: "select \"Col\", Col from schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("Col", PgType::SmallInt),
+            ColumnMetadata::new("col", PgType::SmallInt),
+        ])),
+        Ok(QueryEvent::DataRow(vec!["1".to_owned(), "2".to_owned()])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+
+    engine
+        .execute(Command::Query {
+            sql: "select \"COL\" from schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::column_does_not_exist("COL")));
+}
+
+#[rstest::rstest]
+fn long_names_are_truncated(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    let long_name = "t".repeat(70);
+    engine
+        .execute(Command::Query {
+            sql: format!("create table schema_name.{} (col smallint);", long_name),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::Notice(QueryNotice::identifier_truncated(
+            long_name.as_str(),
+            "t".repeat(63),
+        ))),
+        Ok(QueryEvent::TableCreated),
+    ]);
+
+    engine
+        .execute(Command::Query {
+            sql: format!("insert into schema_name.{} values (1);", "t".repeat(63)),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+    engine
+        .execute(Command::Query {
+            sql: format!("drop table schema_name.{};", long_name),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableDropped));
+}
+
+#[rstest::rstest]
+fn empty_quoted_name(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.\"\" (col smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_name("zero-length delimited identifier")));
+}
+
+#[rstest::rstest]
+fn name_with_nul_character(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.table_name (\"col\u{0}\" smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_name(
+        "identifier \"col\\u{0}\" contains NUL character",
+    )));
+}
@@ -310,6 +310,44 @@ fn insert_and_select_different_character_types(database_with_schema: (InMemory,
     ]);
 }
 
+#[rstest::rstest]
+fn insert_and_select_string_literals_of_all_syntaxes(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.table_name (column_1 varchar(10), column_2 varchar(10), column_3 varchar(10));"
+                .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values ($$it's$$, E'a\\tb\\'c', U&'d\\0061ta');".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+    engine
+        .execute(Command::Query {
+            sql: "select * from schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("column_1", PgType::VarChar),
+            ColumnMetadata::new("column_2", PgType::VarChar),
+            ColumnMetadata::new("column_3", PgType::VarChar),
+        ])),
+        Ok(QueryEvent::DataRow(vec![
+            "it's".to_owned(),
+            "a\tb'c".to_owned(),
+            "data".to_owned(),
+        ])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+}
+
 #[rstest::rstest]
 fn insert_booleans(database_with_schema: (InMemory, ResultCollector)) {
     let (mut engine, collector) = database_with_schema;
@@ -25,26 +25,54 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+#[cfg(test)]
+mod admin_functions;
+#[cfg(test)]
+mod audit_log;
+#[cfg(test)]
+mod compatibility_mode;
 #[cfg(test)]
 mod delete;
 #[cfg(test)]
+mod encoding;
+#[cfg(test)]
 mod extended_query_flow;
 #[cfg(test)]
+mod identifiers;
+#[cfg(test)]
 mod insert;
 #[cfg(test)]
+mod read_only;
+#[cfg(test)]
+mod result_cache;
+#[cfg(test)]
 mod schema;
 #[cfg(test)]
+mod scripts;
+#[cfg(test)]
 mod select;
 #[cfg(test)]
+mod session_functions;
+#[cfg(test)]
+mod settings;
+#[cfg(test)]
 mod simple_prepared_statement;
 #[cfg(test)]
+mod sql_logic;
+#[cfg(test)]
+mod statistics;
+#[cfg(test)]
 mod table;
 #[cfg(test)]
+mod ttl;
+#[cfg(test)]
 mod type_constraints;
 #[cfg(test)]
 mod update;
 #[cfg(test)]
 mod where_clause;
+#[cfg(test)]
+mod work_mem;
 
 type InMemory = QueryEngine<InMemoryDatabase>;
 type ResultCollector = Arc<Collector>;
@@ -0,0 +1,144 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+#[rstest::rstest]
+fn create_schema_in_read_only_session(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "set default_transaction_read_only = on;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::read_only_sql_transaction("CREATE SCHEMA")));
+}
+
+#[rstest::rstest]
+fn modify_data_in_read_only_session(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    engine
+        .execute(Command::Query {
+            sql: "set transaction_read_only = true;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values (1, 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::read_only_sql_transaction("INSERT")));
+
+    engine
+        .execute(Command::Query {
+            sql: "drop table schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::read_only_sql_transaction("DROP TABLE")));
+}
+
+#[rstest::rstest]
+fn select_in_read_only_session(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    engine
+        .execute(Command::Query {
+            sql: "set default_transaction_read_only = on;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "select * from schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("col1", PgType::SmallInt),
+            ColumnMetadata::new("col2", PgType::SmallInt),
+            ColumnMetadata::new("col3", PgType::SmallInt),
+        ])),
+        Ok(QueryEvent::RecordsSelected(0)),
+    ]);
+}
+
+#[rstest::rstest]
+fn switch_read_only_session_off(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "set default_transaction_read_only = on;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "set default_transaction_read_only = off;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+}
+
+#[rstest::rstest]
+fn read_only_node(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine.set_node_read_only(true);
+    engine
+        .execute(Command::Query {
+            sql: "set default_transaction_read_only = off;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::read_only_sql_transaction("CREATE SCHEMA")));
+}
+
+#[rstest::rstest]
+fn invalid_read_only_value(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "set default_transaction_read_only = 'maybe';".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+        "parameter \"default_transaction_read_only\" requires a Boolean value",
+    )));
+}
@@ -0,0 +1,142 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+fn enable_result_cache(engine: &mut InMemory, collector: &ResultCollector) {
+    engine
+        .execute(Command::Query {
+            sql: "set result_cache = on;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+}
+
+fn insert_row(engine: &mut InMemory, collector: &ResultCollector, value: i16) {
+    engine
+        .execute(Command::Query {
+            sql: format!(
+                "insert into schema_name.table_name values ({}, {}, {});",
+                value, value, value
+            ),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+}
+
+fn assert_selected(engine: &mut InMemory, collector: &ResultCollector, values: Vec<i16>) {
+    engine
+        .execute(Command::Query {
+            sql: "select * from schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    let mut expected = vec![Ok(QueryEvent::RowDescription(vec![
+        ColumnMetadata::new("col1", PgType::SmallInt),
+        ColumnMetadata::new("col2", PgType::SmallInt),
+        ColumnMetadata::new("col3", PgType::SmallInt),
+    ]))];
+    for value in values.iter() {
+        expected.push(Ok(QueryEvent::DataRow(vec![value.to_string(); 3])));
+    }
+    expected.push(Ok(QueryEvent::RecordsSelected(values.len())));
+    collector.assert_receive_many(expected);
+}
+
+fn other_session(engine: &InMemory) -> (InMemory, ResultCollector) {
+    let collector = Collector::new();
+    (
+        InMemory::new(collector.clone(), engine.data_manager.clone(), InMemoryDatabase::new()),
+        collector,
+    )
+}
+
+#[rstest::rstest]
+fn repeated_select_is_served_from_cache(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    enable_result_cache(&mut engine, &collector);
+    insert_row(&mut engine, &collector, 1);
+    assert_selected(&mut engine, &collector, vec![1]);
+
+    // writes of a session that does not share table versions are not noticed
+    let (mut other, other_collector) = other_session(&engine);
+    insert_row(&mut other, &other_collector, 2);
+
+    assert_selected(&mut engine, &collector, vec![1]);
+}
+
+#[rstest::rstest]
+fn own_writes_invalidate_cached_results(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    enable_result_cache(&mut engine, &collector);
+    insert_row(&mut engine, &collector, 1);
+    assert_selected(&mut engine, &collector, vec![1]);
+
+    insert_row(&mut engine, &collector, 2);
+
+    assert_selected(&mut engine, &collector, vec![1, 2]);
+}
+
+#[rstest::rstest]
+fn writes_of_other_sessions_invalidate_cached_results(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    enable_result_cache(&mut engine, &collector);
+    insert_row(&mut engine, &collector, 1);
+    assert_selected(&mut engine, &collector, vec![1]);
+
+    let (mut other, other_collector) = other_session(&engine);
+    other.set_table_versions(engine.table_versions.clone());
+    insert_row(&mut other, &other_collector, 2);
+
+    assert_selected(&mut engine, &collector, vec![1, 2]);
+}
+
+#[rstest::rstest]
+fn schema_changes_invalidate_cached_results(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    enable_result_cache(&mut engine, &collector);
+    insert_row(&mut engine, &collector, 1);
+    assert_selected(&mut engine, &collector, vec![1]);
+
+    engine
+        .execute(Command::Query {
+            sql: "drop table schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableDropped));
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.table_name (col1 smallint, col2 smallint, col3 smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    assert_selected(&mut engine, &collector, vec![]);
+}
+
+#[rstest::rstest]
+fn result_cache_requires_boolean_value(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "set result_cache = sometimes;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+        "parameter \"result_cache\" requires a Boolean value",
+    )));
+}
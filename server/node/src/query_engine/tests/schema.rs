@@ -47,6 +47,31 @@ fn create_same_schema(empty_database: (InMemory, ResultCollector)) {
     collector.assert_receive_single(Err(QueryError::schema_already_exists("schema_name")));
 }
 
+#[rstest::rstest]
+fn create_schema_created_by_other_connection(database_with_schema: (InMemory, ResultCollector)) {
+    let (engine, _collector) = database_with_schema;
+    let other_collector = Collector::new();
+    let mut other = InMemory::new(
+        other_collector.clone(),
+        engine.data_manager.clone(),
+        InMemoryDatabase::new(),
+    );
+
+    other
+        .execute(Command::Query {
+            sql: "create schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    other_collector.assert_receive_single(Err(QueryError::schema_already_exists("schema_name")));
+
+    other
+        .execute(Command::Query {
+            sql: "drop schema schema_name;".to_owned(),
+        })
+        .expect("query executed");
+    other_collector.assert_receive_single(Err(QueryError::schema_does_not_exist("schema_name")));
+}
+
 #[rstest::rstest]
 fn drop_schema(empty_database: (InMemory, ResultCollector)) {
     let (mut engine, collector) = empty_database;
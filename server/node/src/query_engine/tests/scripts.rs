@@ -0,0 +1,115 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+fn syntax_error(statement: &str, number: usize, position: usize) -> QueryError {
+    let parser_error = parser::parse_statements(statement).expect_err("syntax error");
+    QueryError::syntax_error(format!(
+        "{} in statement {} at position {}",
+        parser_error, number, position
+    ))
+}
+
+#[rstest::rstest]
+fn all_statements_are_executed(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name; \
+                  create table schema_name.table_name (column_1 smallint); \
+                  insert into schema_name.table_name values (1), (2);"
+                .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::SchemaCreated),
+        Ok(QueryEvent::TableCreated),
+        Ok(QueryEvent::RecordsInserted(2)),
+    ]);
+}
+
+#[rstest::rstest]
+fn script_stops_at_failed_statement(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name; drop schema non_existent; create schema other_schema;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::SchemaCreated),
+        Err(QueryError::schema_does_not_exist("non_existent")),
+    ]);
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema other_schema;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+}
+
+#[rstest::rstest]
+fn syntax_error_is_reported_with_position(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name;\nselec 1; create schema other_schema;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![Ok(QueryEvent::SchemaCreated), Err(syntax_error("selec 1", 2, 28))]);
+}
+
+#[rstest::rstest]
+fn statements_after_error_are_executed_if_enabled(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "set continue_on_error = on;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Query {
+            sql: "create schema schema_name; selec 1; drop schema non_existent; create schema other_schema;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::SchemaCreated),
+        Err(syntax_error("selec 1", 2, 28)),
+        Err(QueryError::schema_does_not_exist("non_existent")),
+        Ok(QueryEvent::SchemaCreated),
+    ]);
+}
+
+#[rstest::rstest]
+fn script_of_comments(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "-- nothing to do;\n/* ; */".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_till_this_moment(vec![Ok(QueryEvent::QueryComplete)]);
+}
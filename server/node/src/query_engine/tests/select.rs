@@ -348,3 +348,32 @@ fn select_different_character_strings_types(database_with_schema: (InMemory, Res
         Ok(QueryEvent::RecordsSelected(3)),
     ]);
 }
+
+#[rstest::rstest]
+fn select_is_aborted_once_client_stalls(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.table_name (column_1 smallint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values (1), (2);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(2)));
+
+    engine.set_client_stalled(Arc::new(AtomicBool::new(true)));
+    engine
+        .execute(Command::Query {
+            sql: "select * from schema_name.table_name;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+        "column_1",
+        PgType::SmallInt,
+    )]))]);
+}
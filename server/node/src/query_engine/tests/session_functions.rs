@@ -0,0 +1,353 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{results::QueryEvent, Command, SERVER_VERSION};
+
+fn single_value(name: &str, value: &str) -> Vec<QueryResult> {
+    vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+            name,
+            PgType::VarChar,
+        )])),
+        Ok(QueryEvent::NullableDataRow(vec![Some(value.to_owned())])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]
+}
+
+fn null_value(name: &str) -> Vec<QueryResult> {
+    vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+            name,
+            PgType::VarChar,
+        )])),
+        Ok(QueryEvent::NullableDataRow(vec![None])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]
+}
+
+fn connected_as(user: &str, database: &str) -> (InMemory, ResultCollector) {
+    let collector = Collector::new();
+    let mut engine = InMemory::new(
+        collector.clone(),
+        Arc::new(DatabaseHandle::in_memory()),
+        InMemoryDatabase::new(),
+    );
+    engine.set_startup_properties(&[
+        ("user".to_owned(), user.to_owned()),
+        ("database".to_owned(), database.to_owned()),
+    ]);
+    (engine, collector)
+}
+
+#[test]
+fn current_user() {
+    let (mut engine, collector) = connected_as("alice", "shop");
+
+    engine
+        .execute(Command::Query {
+            sql: "select current_user;".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(single_value("current_user", "alice"));
+}
+
+#[test]
+fn current_database() {
+    let (mut engine, collector) = connected_as("alice", "shop");
+
+    engine
+        .execute(Command::Query {
+            sql: "select current_database();".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(single_value("current_database", "shop"));
+}
+
+#[rstest::rstest]
+fn current_schema(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "select current_schema();".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(single_value("current_schema", "public"));
+
+    engine
+        .execute(Command::Query {
+            sql: "select current_schema;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(single_value("current_schema", "public"));
+}
+
+#[rstest::rstest]
+fn version(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "select version();".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(single_value(
+        "version",
+        &format!(
+            "PostgreSQL {} on {}-{}, database {}",
+            SERVER_VERSION,
+            std::env::consts::ARCH,
+            std::env::consts::OS,
+            env!("CARGO_PKG_VERSION")
+        ),
+    ));
+}
+
+#[test]
+fn nested_function_calls() {
+    let (mut engine, collector) = connected_as("alice", "shop");
+
+    engine
+        .execute(Command::Query {
+            sql: "select upper(current_database()), length(current_user) as user_length;".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("upper", PgType::VarChar),
+            ColumnMetadata::new("user_length", PgType::Integer),
+        ])),
+        Ok(QueryEvent::NullableDataRow(vec![
+            Some("SHOP".to_owned()),
+            Some("5".to_owned()),
+        ])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+}
+
+#[rstest::rstest]
+fn wrong_argument_types(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    engine
+        .execute(Command::Query {
+            sql: "select current_setting(1);".to_owned(),
+        })
+        .expect("query executed");
+
+    collector.assert_receive_single(Err(pg_model::results::QueryError::undefined_function_call(
+        "current_setting(number)",
+    )));
+}
+
+#[cfg(test)]
+mod random {
+    use super::*;
+    use pg_model::results::QueryError;
+
+    fn random_value(engine: &mut InMemory, collector: &ResultCollector) -> f64 {
+        engine
+            .execute(Command::Query {
+                sql: "select random();".to_owned(),
+            })
+            .expect("query executed");
+        match collector.0.lock().expect("locked").drain(..).nth(1) {
+            Some(Ok(QueryEvent::NullableDataRow(row))) => row[0]
+                .as_deref()
+                .expect("random value")
+                .parse()
+                .expect("random value is a number"),
+            other => panic!("expected data row but got {:?}", other),
+        }
+    }
+
+    #[rstest::rstest]
+    fn value_in_range(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        let value = random_value(&mut engine, &collector);
+
+        assert!((0.0..1.0).contains(&value), "{} is out of range", value);
+    }
+
+    #[rstest::rstest]
+    fn column_type_is_taken_from_signature(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "select random() * 0;".to_owned(),
+            })
+            .expect("query executed");
+
+        assert_eq!(
+            collector.0.lock().expect("locked").drain(..).next(),
+            Some(Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+                "?column?",
+                PgType::VarChar
+            )])))
+        );
+    }
+
+    #[rstest::rstest]
+    fn same_values_after_setseed(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "select setseed(-0.25);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(null_value("setseed"));
+        let first = random_value(&mut engine, &collector);
+
+        engine
+            .execute(Command::Query {
+                sql: "select setseed(-0.25);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(null_value("setseed"));
+
+        assert_eq!(random_value(&mut engine, &collector), first);
+    }
+
+    #[rstest::rstest]
+    fn seed_out_of_range(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "select setseed(1.5);".to_owned(),
+            })
+            .expect("query executed");
+
+        collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+            "setseed parameter 1.5 is out of allowed range [-1,1]",
+        )));
+    }
+
+    #[test]
+    fn deterministic_connections() {
+        let (mut engine, collector) = connected_as("alice", "shop");
+        let (mut other, other_collector) = connected_as("bob", "shop");
+        engine.set_deterministic(true);
+        other.set_deterministic(true);
+
+        assert_eq!(
+            random_value(&mut engine, &collector),
+            random_value(&mut other, &other_collector)
+        );
+    }
+}
+
+#[cfg(test)]
+mod settings {
+    use super::*;
+    use pg_model::results::QueryError;
+
+    #[rstest::rstest]
+    fn set_config_and_current_setting(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "select set_config('app.tenant', 'acme', false);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(single_value("set_config", "acme"));
+
+        engine
+            .execute(Command::Query {
+                sql: "select current_setting('app.tenant');".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(single_value("current_setting", "acme"));
+    }
+
+    #[rstest::rstest]
+    fn current_setting_changed_by_set(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "set work_mem = 1024;".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+        engine
+            .execute(Command::Query {
+                sql: "select current_setting('work_mem');".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(single_value("current_setting", "1024"));
+    }
+
+    #[rstest::rstest]
+    fn local_setting_lasts_till_end_of_statement(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "select set_config('application_name', 'psql', true);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(single_value("set_config", "psql"));
+
+        engine
+            .execute(Command::Query {
+                sql: "select current_setting('application_name');".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(single_value("current_setting", ""));
+    }
+
+    #[rstest::rstest]
+    fn invalid_value(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "select set_config('work_mem', 'lots', false);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+            "invalid value for parameter \"work_mem\": \"lots\"",
+        )));
+    }
+
+    #[rstest::rstest]
+    fn unrecognized_setting(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+
+        engine
+            .execute(Command::Query {
+                sql: "select current_setting('not_a_setting');".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Err(QueryError::unrecognized_configuration_parameter("not_a_setting")));
+
+        engine
+            .execute(Command::Query {
+                sql: "select current_setting('not_a_setting', true);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(null_value("current_setting"));
+    }
+}
@@ -0,0 +1,310 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+fn setting(name: &str, value: &str) -> Vec<QueryResult> {
+    vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+            name,
+            PgType::VarChar,
+        )])),
+        Ok(QueryEvent::DataRow(vec![value.to_owned()])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]
+}
+
+fn query(engine: &mut InMemory, sql: &str) {
+    engine
+        .execute(Command::Query { sql: sql.to_owned() })
+        .expect("query executed");
+}
+
+#[rstest::rstest]
+fn show_default_value(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "show extra_float_digits;");
+
+    collector.assert_receive_many(setting("extra_float_digits", "1"));
+}
+
+#[rstest::rstest]
+fn set_and_show(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set application_name = 'psql';");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "show application_name;");
+    collector.assert_receive_many(setting("application_name", "psql"));
+}
+
+#[rstest::rstest]
+fn unrecognized_setting(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set not_a_setting = 1;");
+    collector.assert_receive_single(Err(QueryError::unrecognized_configuration_parameter("not_a_setting")));
+
+    query(&mut engine, "show not_a_setting;");
+    collector.assert_receive_single(Err(QueryError::unrecognized_configuration_parameter("not_a_setting")));
+}
+
+#[rstest::rstest]
+fn settings_of_drivers_on_connection(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    for sql in &[
+        "set client_encoding = 'UTF8';",
+        "set DateStyle = 'ISO';",
+        "set TimeZone = 'UTC';",
+        "set client_min_messages = warning;",
+        "set standard_conforming_strings = on;",
+        "set statement_timeout = 0;",
+    ] {
+        query(&mut engine, sql);
+        collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+    }
+
+    query(&mut engine, "show client_min_messages;");
+    collector.assert_receive_many(setting("client_min_messages", "warning"));
+}
+
+#[rstest::rstest]
+fn value_out_of_range(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set extra_float_digits = 5;");
+
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+        "5 is outside the valid range for parameter \"extra_float_digits\" (-15 .. 3)",
+    )));
+}
+
+#[rstest::rstest]
+fn reset(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set extra_float_digits = 3;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "reset extra_float_digits;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "show extra_float_digits;");
+    collector.assert_receive_many(setting("extra_float_digits", "1"));
+}
+
+#[rstest::rstest]
+fn reset_in_script(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set extra_float_digits = 3;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(
+        &mut engine,
+        "reset extra_float_digits; show extra_float_digits; set application_name = 'psql'; RESET ALL; show application_name;",
+    );
+    let mut expected = vec![Ok(QueryEvent::VariableSet)];
+    expected.extend(setting("extra_float_digits", "1"));
+    expected.extend(vec![Ok(QueryEvent::VariableSet), Ok(QueryEvent::VariableSet)]);
+    expected.extend(setting("application_name", ""));
+    collector.assert_receive_many(expected);
+}
+
+#[rstest::rstest]
+fn reset_with_extended_query(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set extra_float_digits = 3;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    engine
+        .execute(Command::Parse {
+            statement_name: "".to_owned(),
+            sql: "reset extra_float_digits".to_owned(),
+            param_types: vec![],
+        })
+        .expect("statement parsed");
+    collector.assert_receive_intermediate(Ok(QueryEvent::ParseComplete));
+    engine
+        .execute(Command::Bind {
+            portal_name: "".to_owned(),
+            statement_name: "".to_owned(),
+            param_formats: vec![],
+            raw_params: vec![],
+            result_formats: vec![],
+        })
+        .expect("statement bound");
+    collector.assert_receive_intermediate(Ok(QueryEvent::BindComplete));
+    engine
+        .execute(Command::Execute {
+            portal_name: "".to_owned(),
+            max_rows: 0,
+        })
+        .expect("portal executed");
+    collector.assert_receive_intermediate(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "show extra_float_digits;");
+    collector.assert_receive_many(setting("extra_float_digits", "1"));
+}
+
+#[rstest::rstest]
+fn set_to_default(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set extra_float_digits = 3;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "set extra_float_digits to default;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "show extra_float_digits;");
+    collector.assert_receive_many(setting("extra_float_digits", "1"));
+}
+
+#[rstest::rstest]
+fn reset_all(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set default_transaction_read_only = on;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "reset all;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "create schema schema_name;");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+}
+
+#[rstest::rstest]
+fn set_local_lasts_till_end_of_statement(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+
+    query(&mut engine, "set local default_transaction_read_only = on;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(&mut engine, "create schema schema_name;");
+    collector.assert_receive_single(Ok(QueryEvent::SchemaCreated));
+}
+
+#[rstest::rstest]
+fn work_mem_of_session(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+
+    query(&mut engine, "set work_mem = 64;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(
+        &mut engine,
+        "insert into schema_name.table_name values (1, 2, 3), (4, 5, 6), (7, 8, 9);",
+    );
+    collector.assert_receive_single(Err(QueryError::out_of_memory()));
+
+    query(&mut engine, "reset work_mem;");
+    collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+
+    query(
+        &mut engine,
+        "insert into schema_name.table_name values (1, 2, 3), (4, 5, 6), (7, 8, 9);",
+    );
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(3)));
+}
+
+#[rstest::rstest]
+fn work_mem_default_from_node(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine.set_work_mem(1024);
+
+    query(&mut engine, "show work_mem;");
+
+    collector.assert_receive_many(setting("work_mem", "1024"));
+}
+
+#[cfg(test)]
+mod startup {
+    use super::*;
+
+    #[rstest::rstest]
+    fn application_name(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+        engine.set_startup_properties(&[("application_name".to_owned(), "psql".to_owned())]);
+
+        query(&mut engine, "show application_name;");
+
+        collector.assert_receive_many(setting("application_name", "psql"));
+    }
+
+    #[rstest::rstest]
+    fn options_applied_to_executor(database_with_table: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_table;
+        engine.set_startup_properties(&[("options".to_owned(), "-c work_mem=64".to_owned())]);
+
+        query(
+            &mut engine,
+            "insert into schema_name.table_name values (1, 2, 3), (4, 5, 6), (7, 8, 9);",
+        );
+
+        collector.assert_receive_single(Err(QueryError::out_of_memory()));
+    }
+
+    #[rstest::rstest]
+    fn invalid_options_are_ignored(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+        engine.set_startup_properties(&[("options".to_owned(), "-c extra_float_digits=10".to_owned())]);
+
+        query(&mut engine, "show extra_float_digits;");
+
+        collector.assert_receive_many(setting("extra_float_digits", "1"));
+    }
+}
+
+#[cfg(test)]
+mod protocol_trace {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[rstest::rstest]
+    fn enabled_by_session(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+        let protocol_trace = Arc::new(AtomicBool::new(false));
+        engine.set_protocol_trace(protocol_trace.clone(), false);
+
+        query(&mut engine, "set protocol_trace = on;");
+        collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+        assert!(protocol_trace.load(Ordering::SeqCst));
+
+        query(&mut engine, "reset protocol_trace;");
+        collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+        assert!(!protocol_trace.load(Ordering::SeqCst));
+    }
+
+    #[rstest::rstest]
+    fn enabled_by_node(empty_database: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = empty_database;
+        let protocol_trace = Arc::new(AtomicBool::new(false));
+        engine.set_protocol_trace(protocol_trace.clone(), true);
+        assert!(protocol_trace.load(Ordering::SeqCst));
+
+        query(&mut engine, "set protocol_trace = off;");
+        collector.assert_receive_single(Ok(QueryEvent::VariableSet));
+        assert!(!protocol_trace.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! runs files of sqllogictest format, supported records are:
+//!
+//! * `statement ok` and `statement error` followed by a single statement
+//! * `query <column types> [nosort|rowsort|valuesort]` followed by a query,
+//!   `----` line and expected rows with values separated by spaces
+//! * `skipif database` and `onlyif <name>` lines before a record
+//! * `halt` that stops the file
+//!
+//! Lines starting with `#` are comments, records are separated by empty lines.
+//! Empty strings are written as `(empty)`.
+
+use super::*;
+use pg_model::{results::QueryResult, Command};
+
+const ENGINE: &str = "database";
+
+#[derive(Debug, PartialEq)]
+enum SortMode {
+    NoSort,
+    RowSort,
+    ValueSort,
+}
+
+#[derive(Debug, PartialEq)]
+enum Record {
+    Statement {
+        line: usize,
+        ok: bool,
+        sql: String,
+    },
+    Query {
+        line: usize,
+        columns: usize,
+        sort_mode: SortMode,
+        sql: String,
+        expected: Vec<String>,
+    },
+}
+
+fn parse(script: &str) -> Vec<Record> {
+    let mut records = vec![];
+    let mut lines = script
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim_end()))
+        .filter(|(_, line)| !line.starts_with('#'))
+        .peekable();
+    while let Some((line, header)) = lines.next() {
+        let mut header = header.split_whitespace().collect::<Vec<&str>>();
+        if header.is_empty() {
+            continue;
+        }
+        let mut skip = false;
+        while let ["skipif", engine] | ["onlyif", engine] = header.as_slice() {
+            skip |= (header[0] == "skipif") == (*engine == ENGINE);
+            header = match lines.next() {
+                Some((_, next)) => next.split_whitespace().collect(),
+                None => panic!("line {}: condition without a record", line),
+            };
+        }
+        let mut body = vec![];
+        while let Some((_, text)) = lines.next_if(|(_, text)| !text.is_empty()) {
+            body.push(text);
+        }
+        let record = match header.as_slice() {
+            ["halt"] if skip => continue,
+            ["halt"] => break,
+            ["statement", "ok"] => Record::Statement {
+                line,
+                ok: true,
+                sql: body.join("\n"),
+            },
+            ["statement", "error", ..] => Record::Statement {
+                line,
+                ok: false,
+                sql: body.join("\n"),
+            },
+            ["query", types, mode @ ..] => {
+                let sort_mode = match mode {
+                    [] | ["nosort"] => SortMode::NoSort,
+                    ["rowsort"] => SortMode::RowSort,
+                    ["valuesort"] => SortMode::ValueSort,
+                    other => panic!("line {}: unknown sort mode {:?}", line, other),
+                };
+                let separator = body
+                    .iter()
+                    .position(|text| *text == "----")
+                    .unwrap_or_else(|| panic!("line {}: query without `----` line", line));
+                Record::Query {
+                    line,
+                    columns: types.len(),
+                    sort_mode,
+                    sql: body[..separator].join("\n"),
+                    expected: body[separator + 1..]
+                        .iter()
+                        .map(|row| row.split_whitespace().collect::<Vec<&str>>().join(" "))
+                        .collect(),
+                }
+            }
+            other => panic!("line {}: unknown record {:?}", line, other),
+        };
+        if !skip {
+            records.push(record);
+        }
+    }
+    records
+}
+
+fn execute(engine: &mut InMemory, collector: &ResultCollector, sql: &str) -> Vec<QueryResult> {
+    engine
+        .execute(Command::Query { sql: sql.to_owned() })
+        .expect("query executed");
+    collector.0.lock().expect("locked").drain(..).collect()
+}
+
+/// executes records of the script one by one against an empty database and
+/// panics on the first one that does not produce expected results
+fn run(file: &str, script: &str) {
+    let collector = Collector::new();
+    let mut engine = InMemory::new(
+        collector.clone(),
+        Arc::new(DatabaseHandle::in_memory()),
+        InMemoryDatabase::new(),
+    );
+    engine.set_deterministic(true);
+    for record in parse(script) {
+        match record {
+            Record::Statement { line, ok, sql } => {
+                let results = execute(&mut engine, &collector, &sql);
+                let error = results.iter().find(|result| result.is_err());
+                match (ok, error) {
+                    (true, Some(error)) => panic!("{}:{}: statement failed with {:?}", file, line, error),
+                    (false, None) => panic!("{}:{}: statement succeeded, error is expected", file, line),
+                    _ => {}
+                }
+            }
+            Record::Query {
+                line,
+                columns,
+                sort_mode,
+                sql,
+                expected,
+            } => {
+                let mut rows = vec![];
+                for result in execute(&mut engine, &collector, &sql) {
+                    match result {
+                        Ok(QueryEvent::DataRow(values)) => rows.push(values),
+                        Ok(QueryEvent::NullableDataRow(values)) => rows.push(
+                            values
+                                .into_iter()
+                                .map(|value| value.unwrap_or_else(|| "NULL".to_owned()))
+                                .collect(),
+                        ),
+                        Ok(_) => {}
+                        Err(error) => panic!("{}:{}: query failed with {:?}", file, line, error),
+                    }
+                }
+                if let Some(row) = rows.iter().find(|row| row.len() != columns) {
+                    panic!("{}:{}: {} columns are expected in {:?}", file, line, columns, row);
+                }
+                let mut actual = match sort_mode {
+                    SortMode::ValueSort => rows.into_iter().flatten().map(|value| vec![value]).collect(),
+                    _ => rows,
+                }
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|value| if value.is_empty() { "(empty)".to_owned() } else { value })
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                })
+                .collect::<Vec<String>>();
+                let mut expected = expected;
+                if sort_mode == SortMode::ValueSort {
+                    expected = expected
+                        .iter()
+                        .flat_map(|row| row.split(' ').map(str::to_owned))
+                        .collect();
+                }
+                if sort_mode != SortMode::NoSort {
+                    actual.sort();
+                    expected.sort();
+                }
+                assert_eq!(actual, expected, "{}:{}: unexpected query results", file, line);
+            }
+        }
+    }
+}
+
+macro_rules! sql_logic_test {
+    ($name:ident) => {
+        #[test]
+        fn $name() {
+            let file = concat!(stringify!($name), ".slt");
+            run(
+                file,
+                include_str!(concat!("sql_logic/", stringify!($name), ".slt")),
+            );
+        }
+    };
+}
+
+sql_logic_test!(data_manipulation);
+sql_logic_test!(schema_definition);
+
+#[cfg(test)]
+mod runner {
+    use super::*;
+
+    #[test]
+    fn records() {
+        assert_eq!(
+            parse(
+                "# comment\n\
+                 statement ok\n\
+                 create schema s\n\
+                 \n\
+                 skipif database\n\
+                 statement error\n\
+                 select 1\n\
+                 \n\
+                 query II rowsort\n\
+                 select *\n\
+                 from s.t\n\
+                 ----\n\
+                 1   2\n\
+                 \n\
+                 halt\n\
+                 \n\
+                 statement ok\n\
+                 drop schema s\n"
+            ),
+            vec![
+                Record::Statement {
+                    line: 2,
+                    ok: true,
+                    sql: "create schema s".to_owned()
+                },
+                Record::Query {
+                    line: 9,
+                    columns: 2,
+                    sort_mode: SortMode::RowSort,
+                    sql: "select *\nfrom s.t".to_owned(),
+                    expected: vec!["1 2".to_owned()]
+                }
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "test.slt:4: statement succeeded, error is expected")]
+    fn unexpected_success() {
+        run(
+            "test.slt",
+            "statement ok\ncreate schema s\n\nstatement error\ncreate schema s2\n",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "test.slt:7: unexpected query results")]
+    fn unexpected_rows() {
+        run(
+            "test.slt",
+            "statement ok\ncreate schema s\n\n\
+             statement ok\ncreate table s.t (a smallint)\n\n\
+             query I\nselect * from s.t\n----\n1\n",
+        );
+    }
+}
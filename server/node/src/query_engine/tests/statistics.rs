@@ -0,0 +1,154 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::statistics::StatementEntry;
+use pg_model::{results::QueryEvent, Command};
+use std::time::Duration;
+
+fn recorded(engine: &InMemory) -> Vec<(String, u64, usize)> {
+    engine
+        .statistics
+        .entries()
+        .into_iter()
+        .map(|StatementEntry { query, calls, rows, .. }| (query, calls, rows))
+        .collect()
+}
+
+#[rstest::rstest]
+fn statements_are_counted_with_rows(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    engine.statistics.reset();
+
+    for _ in 0..2 {
+        engine
+            .execute(Command::Query {
+                sql: "insert into schema_name.table_name values (1, 2, 3), (4, 5, 6);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(2)));
+    }
+
+    assert_eq!(
+        recorded(&engine),
+        vec![(
+            "insert into schema_name.table_name values ($1, $2, $3), ($4, $5, $6)".to_owned(),
+            2,
+            4
+        )]
+    );
+}
+
+#[rstest::rstest]
+fn statements_with_different_literals_are_aggregated(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    engine.statistics.reset();
+
+    for sql in &[
+        "insert into schema_name.table_name values (1, 2, 3);",
+        "INSERT INTO schema_name.table_name VALUES (4, 5, 6);",
+    ] {
+        engine
+            .execute(Command::Query { sql: sql.to_string() })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+    }
+
+    assert_eq!(
+        recorded(&engine),
+        vec![(
+            "insert into schema_name.table_name values ($1, $2, $3)".to_owned(),
+            2,
+            2
+        )]
+    );
+}
+
+#[rstest::rstest]
+fn select_from_empty_statements_view(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .execute(Command::Query {
+            sql: "select * from system.statements;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("query", PgType::VarChar),
+            ColumnMetadata::new("calls", PgType::BigInt),
+            ColumnMetadata::new("total_time_us", PgType::BigInt),
+            ColumnMetadata::new("mean_time_us", PgType::BigInt),
+            ColumnMetadata::new("rows", PgType::BigInt),
+        ])),
+        Ok(QueryEvent::RecordsSelected(0)),
+    ]);
+}
+
+#[rstest::rstest]
+fn select_from_statements_view(empty_database: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = empty_database;
+    engine
+        .statistics
+        .record("SELECT 1".to_owned(), Duration::from_micros(30), 1);
+    engine
+        .statistics
+        .record("SELECT 1".to_owned(), Duration::from_micros(10), 1);
+
+    engine
+        .execute(Command::Query {
+            sql: "select * from system.statements;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("query", PgType::VarChar),
+            ColumnMetadata::new("calls", PgType::BigInt),
+            ColumnMetadata::new("total_time_us", PgType::BigInt),
+            ColumnMetadata::new("mean_time_us", PgType::BigInt),
+            ColumnMetadata::new("rows", PgType::BigInt),
+        ])),
+        Ok(QueryEvent::DataRow(vec![
+            "SELECT 1".to_owned(),
+            "2".to_owned(),
+            "40".to_owned(),
+            "20".to_owned(),
+            "2".to_owned(),
+        ])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+}
+
+#[rstest::rstest]
+fn reset_statements(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+
+    engine
+        .execute(Command::Query {
+            sql: "select pg_stat_statements_reset();".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+            "pg_stat_statements_reset",
+            PgType::VarChar,
+        )])),
+        Ok(QueryEvent::DataRow(vec!["".to_owned()])),
+        Ok(QueryEvent::RecordsSelected(1)),
+    ]);
+
+    assert_eq!(
+        recorded(&engine),
+        vec![("select pg_stat_statements_reset()".to_owned(), 1, 1)]
+    );
+}
@@ -175,3 +175,169 @@ mod different_types {
         collector.assert_receive_single(Ok(QueryEvent::TableCreated));
     }
 }
+
+#[cfg(test)]
+mod collations {
+    use super::*;
+
+    #[rstest::rstest]
+    fn strings_with_collations(database_with_schema: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_schema;
+        engine
+            .execute(Command::Query {
+                sql: "create table schema_name.table_name (\
+            column_c char(10) collate \"C\",\
+            column_vc varchar(10) collate unicode\
+            );"
+                .to_owned(),
+            })
+            .expect("query executed");
+
+        collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+    }
+
+    #[rstest::rstest]
+    fn undefined_collation(database_with_schema: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_schema;
+        engine
+            .execute(Command::Query {
+                sql: "create table schema_name.table_name (column_vc varchar(10) collate de_de);".to_owned(),
+            })
+            .expect("query executed");
+
+        collector.assert_receive_single(Err(QueryError::undefined_collation("de_de")));
+    }
+
+    #[rstest::rstest]
+    fn collation_of_integer_column(database_with_schema: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_schema;
+        engine
+            .execute(Command::Query {
+                sql: "create table schema_name.table_name (column_i integer collate \"C\");".to_owned(),
+            })
+            .expect("query executed");
+
+        collector.assert_receive_single(Err(QueryError::collation_not_supported("integer")));
+    }
+}
+
+#[cfg(test)]
+mod alter {
+    use super::*;
+
+    #[rstest::rstest]
+    fn add_column_to_table_with_rows(database_with_schema: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_schema;
+        engine
+            .execute(Command::Query {
+                sql: "create table schema_name.table_name (col_1 smallint);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+        engine
+            .execute(Command::Query {
+                sql: "insert into schema_name.table_name values (1);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+        engine
+            .execute(Command::Query {
+                sql: "alter table schema_name.table_name add column col_2 integer;".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::TableAltered));
+
+        engine
+            .execute(Command::Query {
+                sql: "insert into schema_name.table_name values (2, 3);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+        engine
+            .execute(Command::Query {
+                sql: "select * from schema_name.table_name;".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_many(vec![
+            Ok(QueryEvent::RowDescription(vec![
+                ColumnMetadata::new("col_1", PgType::SmallInt),
+                ColumnMetadata::new("col_2", PgType::Integer),
+            ])),
+            Ok(QueryEvent::DataRow(vec!["1".to_owned(), "NULL".to_owned()])),
+            Ok(QueryEvent::DataRow(vec!["2".to_owned(), "3".to_owned()])),
+            Ok(QueryEvent::RecordsSelected(2)),
+        ]);
+    }
+
+    #[rstest::rstest]
+    fn add_existing_column(database_with_schema: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_schema;
+        engine
+            .execute(Command::Query {
+                sql: "create table schema_name.table_name (col_1 smallint);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+        engine
+            .execute(Command::Query {
+                sql: "alter table schema_name.table_name add column col_1 integer;".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Err(QueryError::duplicate_column("col_1")));
+    }
+
+    #[rstest::rstest]
+    fn add_column_to_non_existent_table(database_with_schema: (InMemory, ResultCollector)) {
+        let (mut engine, collector) = database_with_schema;
+        engine
+            .execute(Command::Query {
+                sql: "alter table schema_name.non_existent add column col_1 integer;".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Err(QueryError::table_does_not_exist("schema_name.non_existent")));
+    }
+
+    #[test]
+    fn add_column_while_table_is_scanned() {
+        use data_manager::DataDefReader;
+
+        let collector = Collector::new();
+        let data_manager = Arc::new(DatabaseHandle::in_memory());
+        let mut engine = InMemory::new(collector.clone(), data_manager.clone(), InMemoryDatabase::new());
+        engine
+            .execute(Command::Query {
+                sql: "create schema schema_name; create table schema_name.table_name (col_1 smallint);".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_till_this_moment(vec![
+            Ok(QueryEvent::SchemaCreated),
+            Ok(QueryEvent::TableCreated),
+            Ok(QueryEvent::QueryComplete),
+        ]);
+        let full_table_id = match data_manager.table_exists("schema_name", "table_name") {
+            Some((schema_id, Some(table_id))) => (schema_id, table_id),
+            other => panic!("expected table but got {:?}", other),
+        };
+        // scan of a portal suspended on the executor thread the statement runs on
+        let scan = data_manager.full_scan(&full_table_id).expect("table exists");
+
+        engine
+            .execute(Command::Query {
+                sql: "alter table schema_name.table_name add column col_2 integer;".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Err(QueryError::object_in_use("schema_name.table_name")));
+
+        drop(scan);
+        engine
+            .execute(Command::Query {
+                sql: "alter table schema_name.table_name add column col_2 integer;".to_owned(),
+            })
+            .expect("query executed");
+        collector.assert_receive_single(Ok(QueryEvent::TableAltered));
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use pg_model::{
+    results::{QueryError, QueryEvent},
+    Command,
+};
+
+#[rstest::rstest]
+fn expired_rows_are_deleted(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.events (id integer, created_at bigint) \
+                  with (ttl_column = 'created_at', ttl = '1 hour');"
+                .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.events values (1, 1000), (2, 5000), (3, 9000), (4, 12000);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(4)));
+
+    assert_eq!(engine.ttl_policies.expire(9000, 1, &engine.table_versions), 2);
+
+    engine
+        .execute(Command::Query {
+            sql: "select id from schema_name.events;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_many(vec![
+        Ok(QueryEvent::RowDescription(vec![ColumnMetadata::new(
+            "id",
+            PgType::Integer,
+        )])),
+        Ok(QueryEvent::DataRow(vec!["3".to_owned()])),
+        Ok(QueryEvent::DataRow(vec!["4".to_owned()])),
+        Ok(QueryEvent::RecordsSelected(2)),
+    ]);
+}
+
+#[rstest::rstest]
+fn expired_rows_are_deleted_in_full_and_last_batches(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql:
+                "create table schema_name.events (created_at bigint) with (ttl_column = 'created_at', ttl = '1 hour');"
+                    .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.events values (1000), (12000), (2000), (3000), (4000), (5000);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(6)));
+
+    assert_eq!(engine.ttl_policies.expire(9000, 2, &engine.table_versions), 5);
+    assert_eq!(engine.ttl_policies.expire(9000, 2, &engine.table_versions), 0);
+}
+
+#[rstest::rstest]
+fn tables_without_policy_are_kept(database_with_table: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_table;
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.table_name values (1, 2, 3);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+    assert_eq!(engine.ttl_policies.expire(i64::MAX, 1, &engine.table_versions), 0);
+}
+
+#[rstest::rstest]
+fn policy_of_dropped_table_is_removed(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.events (created_at bigint) with (ttl_column = 'created_at', ttl = '1 day');"
+                .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "drop table schema_name.events;".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableDropped));
+
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.events (created_at bigint);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::TableCreated));
+
+    engine
+        .execute(Command::Query {
+            sql: "insert into schema_name.events values (0);".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Ok(QueryEvent::RecordsInserted(1)));
+
+    assert_eq!(engine.ttl_policies.expire(i64::MAX, 1, &engine.table_versions), 0);
+}
+
+#[rstest::rstest]
+fn ttl_column_does_not_exist(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.events (id integer) with (ttl_column = 'created_at', ttl = '1 day');"
+                .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::column_does_not_exist("created_at")));
+}
+
+#[rstest::rstest]
+fn ttl_column_of_not_integer_type(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.events (created_at varchar(10)) \
+                  with (ttl_column = 'created_at', ttl = '1 day');"
+                .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+        "column \"created_at\" of parameter \"ttl_column\" has to be of integer type",
+    )));
+}
+
+#[rstest::rstest]
+fn invalid_ttl(database_with_schema: (InMemory, ResultCollector)) {
+    let (mut engine, collector) = database_with_schema;
+    engine
+        .execute(Command::Query {
+            sql:
+                "create table schema_name.events (created_at bigint) with (ttl_column = 'created_at', ttl = 'forever');"
+                    .to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+        "invalid value for parameter \"ttl\": \"forever\"",
+    )));
+
+    engine
+        .execute(Command::Query {
+            sql: "create table schema_name.events (created_at bigint) with (ttl = '1 day');".to_owned(),
+        })
+        .expect("query executed");
+    collector.assert_receive_single(Err(QueryError::invalid_parameter_value(
+        "parameter \"ttl\" requires parameter \"ttl_column\"",
+    )));
+}
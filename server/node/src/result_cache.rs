@@ -0,0 +1,182 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use connection::Sender;
+use pg_model::results::{QueryEvent, QueryResult};
+use plan::FullTableId;
+use std::{
+    collections::HashMap,
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+const CACHED_STATEMENTS: usize = 128;
+
+/// catalog version and version of a table at the moment its rows were read
+pub(crate) type Versions = (u64, u64);
+
+/// versions of the catalog and tables shared by all connections to the node.
+/// A version changes after every write, so results read under an older one
+/// are stale
+#[derive(Default)]
+pub(crate) struct TableVersions {
+    catalog: AtomicU64,
+    tables: Mutex<HashMap<FullTableId, u64>>,
+}
+
+impl TableVersions {
+    pub(crate) fn of(&self, table_id: &FullTableId) -> Versions {
+        let table = self
+            .tables
+            .lock()
+            .expect("versions lock is not poisoned")
+            .get(table_id)
+            .copied()
+            .unwrap_or_default();
+        (self.catalog.load(Ordering::SeqCst), table)
+    }
+
+    pub(crate) fn table_written(&self, table_id: &FullTableId) {
+        *self
+            .tables
+            .lock()
+            .expect("versions lock is not poisoned")
+            .entry(table_id.clone())
+            .or_default() += 1;
+    }
+
+    pub(crate) fn catalog_changed(&self) {
+        self.catalog.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+struct CachedResult {
+    versions: Versions,
+    events: Vec<QueryEvent>,
+}
+
+/// results of selects executed by a session keyed by statement text,
+/// parameters are part of the text once they are bound
+#[derive(Default)]
+pub(crate) struct ResultCache {
+    entries: HashMap<String, CachedResult>,
+}
+
+impl ResultCache {
+    /// events of the result if it was read under the same versions
+    pub(crate) fn get(&self, query: &str, versions: Versions) -> Option<&[QueryEvent]> {
+        match self.entries.get(query) {
+            Some(cached) if cached.versions == versions => Some(cached.events.as_slice()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn put(&mut self, query: String, versions: Versions, events: Vec<QueryEvent>) {
+        if self.entries.len() >= CACHED_STATEMENTS && !self.entries.contains_key(&query) {
+            self.entries.clear();
+        }
+        self.entries.insert(query, CachedResult { versions, events });
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// passes results through to the client keeping a copy of them while
+/// recording is started
+pub(crate) struct ResultRecorder {
+    sender: Arc<dyn Sender>,
+    recorded: Mutex<Option<Vec<QueryEvent>>>,
+}
+
+impl ResultRecorder {
+    pub(crate) fn new(sender: Arc<dyn Sender>) -> ResultRecorder {
+        ResultRecorder {
+            sender,
+            recorded: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn start(&self) {
+        *self.recorded.lock().expect("recorder lock is not poisoned") = Some(vec![]);
+    }
+
+    /// events sent since recording was started, `None` if any of them was an error
+    pub(crate) fn finish(&self) -> Option<Vec<QueryEvent>> {
+        self.recorded.lock().expect("recorder lock is not poisoned").take()
+    }
+}
+
+impl Sender for ResultRecorder {
+    fn flush(&self) -> io::Result<()> {
+        self.sender.flush()
+    }
+
+    fn send(&self, query_result: QueryResult) -> io::Result<()> {
+        let mut recorded = self.recorded.lock().expect("recorder lock is not poisoned");
+        match (recorded.as_mut(), &query_result) {
+            (Some(events), Ok(event)) => events.push(event.clone()),
+            (Some(_), Err(_)) => *recorded = None,
+            (None, _) => {}
+        }
+        self.sender.send(query_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table_id() -> FullTableId {
+        FullTableId::from((0, 0))
+    }
+
+    #[test]
+    fn writes_change_versions_of_table() {
+        let versions = TableVersions::default();
+        let before = versions.of(&table_id());
+
+        versions.table_written(&table_id());
+
+        assert_ne!(versions.of(&table_id()), before);
+        assert_eq!(versions.of(&FullTableId::from((0, 1))), (0, 0));
+    }
+
+    #[test]
+    fn catalog_changes_change_versions_of_all_tables() {
+        let versions = TableVersions::default();
+        let before = versions.of(&table_id());
+
+        versions.catalog_changed();
+
+        assert_ne!(versions.of(&table_id()), before);
+    }
+
+    #[test]
+    fn stale_results_are_not_served() {
+        let mut cache = ResultCache::default();
+        cache.put("SELECT 1".to_owned(), (0, 1), vec![QueryEvent::RecordsSelected(0)]);
+
+        assert_eq!(
+            cache.get("SELECT 1", (0, 1)),
+            Some(&[QueryEvent::RecordsSelected(0)][..])
+        );
+        assert_eq!(cache.get("SELECT 1", (0, 2)), None);
+        assert_eq!(cache.get("SELECT 2", (0, 1)), None);
+    }
+}
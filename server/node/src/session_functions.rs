@@ -0,0 +1,242 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bigdecimal::BigDecimal;
+use expr_operators::{Bool, ScalarValue};
+use pg_model::{results::QueryError, session::Session, SERVER_VERSION};
+use std::{env, str::FromStr};
+
+const DEFAULT_SCHEMA: &str = "public";
+
+/// functions that read or change state of a session, ORMs and drivers call
+/// some of them right after they connect
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum SessionFunction {
+    Version,
+    CurrentUser,
+    CurrentDatabase,
+    CurrentSchema,
+    Random,
+    SetSeed(f64),
+    CurrentSetting { name: String, missing_ok: bool },
+    SetConfig { name: String, value: String, local: bool },
+}
+
+impl SessionFunction {
+    /// session function called with the arguments of types its signature
+    /// has, `None` if the function isn't a session one
+    pub(crate) fn from_call(name: &str, args: &[ScalarValue]) -> Option<SessionFunction> {
+        match (name, args) {
+            ("version", []) => Some(SessionFunction::Version),
+            ("current_user", []) => Some(SessionFunction::CurrentUser),
+            ("current_database", []) => Some(SessionFunction::CurrentDatabase),
+            ("current_schema", []) => Some(SessionFunction::CurrentSchema),
+            ("random", []) => Some(SessionFunction::Random),
+            ("setseed", [ScalarValue::Number(seed)]) => seed.to_string().parse().ok().map(SessionFunction::SetSeed),
+            ("current_setting", [ScalarValue::String(name)]) => Some(SessionFunction::CurrentSetting {
+                name: name.clone(),
+                missing_ok: false,
+            }),
+            ("current_setting", [ScalarValue::String(name), ScalarValue::Bool(Bool(missing_ok))]) => {
+                Some(SessionFunction::CurrentSetting {
+                    name: name.clone(),
+                    missing_ok: *missing_ok,
+                })
+            }
+            ("set_config", [ScalarValue::String(name), ScalarValue::String(value), ScalarValue::Bool(Bool(local))]) => {
+                Some(SessionFunction::SetConfig {
+                    name: name.clone(),
+                    value: value.clone(),
+                    local: *local,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// value of the function for a session, `setseed()` returns `NULL`
+    pub(crate) fn value<S>(&self, session: &mut Session<S>) -> Result<ScalarValue, QueryError> {
+        match self {
+            SessionFunction::Version => Ok(ScalarValue::String(version())),
+            SessionFunction::CurrentUser => Ok(ScalarValue::String(session.user().to_owned())),
+            SessionFunction::CurrentDatabase => Ok(ScalarValue::String(session.database().to_owned())),
+            SessionFunction::CurrentSchema => Ok(ScalarValue::String(DEFAULT_SCHEMA.to_owned())),
+            SessionFunction::Random => Ok(ScalarValue::Number(
+                BigDecimal::from_str(&session.random().to_string()).expect("random value is a number"),
+            )),
+            SessionFunction::SetSeed(seed) => session.set_seed(*seed).map(|()| ScalarValue::Null),
+            SessionFunction::CurrentSetting { name, missing_ok } => match session.settings().get(name) {
+                Ok(value) => Ok(ScalarValue::String(value.to_string())),
+                Err(_) if *missing_ok => Ok(ScalarValue::Null),
+                Err(error) => Err(error),
+            },
+            SessionFunction::SetConfig { name, value, local } => {
+                session.settings_mut().set(name, value, *local)?;
+                session
+                    .settings()
+                    .get(name)
+                    .map(|value| ScalarValue::String(value.to_string()))
+            }
+        }
+    }
+}
+
+fn version() -> String {
+    format!(
+        "PostgreSQL {} on {}-{}, database {}",
+        SERVER_VERSION,
+        env::consts::ARCH,
+        env::consts::OS,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(properties: &[(&str, &str)]) -> Session<()> {
+        let mut session = Session::default();
+        session.set_startup_properties(
+            &properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<(String, String)>>(),
+        );
+        session
+    }
+
+    #[test]
+    fn values_from_startup_properties() {
+        let mut session = session(&[("user", "alice"), ("database", "shop")]);
+
+        assert_eq!(
+            SessionFunction::CurrentUser.value(&mut session),
+            Ok(ScalarValue::String("alice".to_owned()))
+        );
+        assert_eq!(
+            SessionFunction::CurrentDatabase.value(&mut session),
+            Ok(ScalarValue::String("shop".to_owned()))
+        );
+        assert_eq!(
+            SessionFunction::CurrentSchema.value(&mut session),
+            Ok(ScalarValue::String("public".to_owned()))
+        );
+    }
+
+    #[test]
+    fn version_reports_server_version() {
+        let mut session = session(&[]);
+
+        assert!(SessionFunction::Version
+            .value(&mut session)
+            .expect("version")
+            .to_string()
+            .starts_with(&format!("PostgreSQL {} on ", SERVER_VERSION)));
+    }
+
+    #[test]
+    fn set_and_read_setting() {
+        let mut session = session(&[]);
+        let set_config = SessionFunction::SetConfig {
+            name: "app.tenant".to_owned(),
+            value: "acme".to_owned(),
+            local: false,
+        };
+
+        assert_eq!(
+            set_config.value(&mut session),
+            Ok(ScalarValue::String("acme".to_owned()))
+        );
+        assert_eq!(
+            SessionFunction::CurrentSetting {
+                name: "app.tenant".to_owned(),
+                missing_ok: false
+            }
+            .value(&mut session),
+            Ok(ScalarValue::String("acme".to_owned()))
+        );
+    }
+
+    #[test]
+    fn missing_setting() {
+        let mut session = session(&[]);
+
+        assert_eq!(
+            SessionFunction::CurrentSetting {
+                name: "app.tenant".to_owned(),
+                missing_ok: false
+            }
+            .value(&mut session),
+            Err(QueryError::unrecognized_configuration_parameter("app.tenant"))
+        );
+        assert_eq!(
+            SessionFunction::CurrentSetting {
+                name: "app.tenant".to_owned(),
+                missing_ok: true
+            }
+            .value(&mut session),
+            Ok(ScalarValue::Null)
+        );
+    }
+
+    #[test]
+    fn random_values_repeat_after_setseed() {
+        let mut session = session(&[]);
+
+        assert_eq!(
+            SessionFunction::SetSeed(-0.5).value(&mut session),
+            Ok(ScalarValue::Null)
+        );
+        let first = SessionFunction::Random.value(&mut session);
+        assert_eq!(
+            SessionFunction::SetSeed(-0.5).value(&mut session),
+            Ok(ScalarValue::Null)
+        );
+
+        assert_eq!(SessionFunction::Random.value(&mut session), first);
+    }
+
+    #[test]
+    fn call_with_arguments() {
+        assert_eq!(
+            SessionFunction::from_call(
+                "current_setting",
+                &[
+                    ScalarValue::String("app.tenant".to_owned()),
+                    ScalarValue::Bool(Bool(true))
+                ]
+            ),
+            Some(SessionFunction::CurrentSetting {
+                name: "app.tenant".to_owned(),
+                missing_ok: true
+            })
+        );
+        assert_eq!(
+            SessionFunction::from_call(
+                "setseed",
+                &[ScalarValue::Number(BigDecimal::from_str("-0.5").expect("number"))]
+            ),
+            Some(SessionFunction::SetSeed(-0.5))
+        );
+    }
+
+    #[test]
+    fn not_a_session_function() {
+        assert_eq!(
+            SessionFunction::from_call("upper", &[ScalarValue::String("a".to_owned())]),
+            None
+        );
+    }
+}
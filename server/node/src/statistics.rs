@@ -0,0 +1,238 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use connection::Sender;
+use pg_model::results::{QueryEvent, QueryResult};
+use pg_wire::{ColumnMetadata, PgType};
+use sql_ast::{Expr, Function, Query, Select, SelectItem, SetExpr, Statement, TableFactor, TableWithJoins};
+use std::{
+    collections::HashMap,
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+const STATEMENTS_VIEW: &str = "system.statements";
+const RESET_FUNCTION: &str = "pg_stat_statements_reset";
+
+/// aggregated executions of a statement
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StatementEntry {
+    pub(crate) query: String,
+    pub(crate) calls: u64,
+    pub(crate) total_time: Duration,
+    pub(crate) rows: usize,
+}
+
+impl StatementEntry {
+    pub(crate) fn mean_time(&self) -> Duration {
+        self.total_time / self.calls as u32
+    }
+}
+
+/// statistics of statements executed by all connections to the node
+#[derive(Default)]
+pub(crate) struct StatementStatistics {
+    entries: Mutex<HashMap<String, StatementEntry>>,
+}
+
+impl StatementStatistics {
+    pub(crate) fn record(&self, query: String, elapsed: Duration, rows: usize) {
+        let mut entries = self.entries.lock().expect("statistics lock is not poisoned");
+        let entry = entries.entry(query.clone()).or_insert_with(|| StatementEntry {
+            query,
+            calls: 0,
+            total_time: Duration::default(),
+            rows: 0,
+        });
+        entry.calls += 1;
+        entry.total_time += elapsed;
+        entry.rows += rows;
+    }
+
+    /// entries sorted by query text
+    pub(crate) fn entries(&self) -> Vec<StatementEntry> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("statistics lock is not poisoned")
+            .values()
+            .cloned()
+            .collect::<Vec<StatementEntry>>();
+        entries.sort_by(|left, right| left.query.cmp(&right.query));
+        entries
+    }
+
+    pub(crate) fn reset(&self) {
+        self.entries.lock().expect("statistics lock is not poisoned").clear();
+    }
+
+    /// sends content of `system.statements` as a result of a select query
+    pub(crate) fn send_entries(&self, sender: &dyn Sender) -> io::Result<()> {
+        sender.send(Ok(QueryEvent::RowDescription(vec![
+            ColumnMetadata::new("query", PgType::VarChar),
+            ColumnMetadata::new("calls", PgType::BigInt),
+            ColumnMetadata::new("total_time_us", PgType::BigInt),
+            ColumnMetadata::new("mean_time_us", PgType::BigInt),
+            ColumnMetadata::new("rows", PgType::BigInt),
+        ])))?;
+        let entries = self.entries();
+        for entry in entries.iter() {
+            sender.send(Ok(QueryEvent::DataRow(vec![
+                entry.query.clone(),
+                entry.calls.to_string(),
+                entry.total_time.as_micros().to_string(),
+                entry.mean_time().as_micros().to_string(),
+                entry.rows.to_string(),
+            ])))?;
+        }
+        sender.send(Ok(QueryEvent::RecordsSelected(entries.len())))
+    }
+}
+
+/// whether the statement is `SELECT * FROM system.statements`
+pub(crate) fn selects_statements(statement: &Statement) -> bool {
+    match single_select(statement) {
+        Some(Select { projection, from, .. }) => match (projection.as_slice(), from.as_slice()) {
+            (
+                [SelectItem::Wildcard],
+                [TableWithJoins {
+                    relation: TableFactor::Table { name, .. },
+                    joins,
+                }],
+            ) => joins.is_empty() && name.to_string().to_lowercase() == STATEMENTS_VIEW,
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+/// whether the statement is `SELECT pg_stat_statements_reset()`
+pub(crate) fn resets_statements(statement: &Statement) -> bool {
+    match single_select(statement) {
+        Some(Select { projection, from, .. }) => match projection.as_slice() {
+            [SelectItem::UnnamedExpr(Expr::Function(Function { name, args, .. }))] => {
+                from.is_empty() && args.is_empty() && name.to_string().to_lowercase() == RESET_FUNCTION
+            }
+            _ => false,
+        },
+        None => false,
+    }
+}
+
+pub(crate) fn single_select(statement: &Statement) -> Option<&Select> {
+    match statement {
+        Statement::Query(query) => match &**query {
+            Query {
+                body: SetExpr::Select(select),
+                ..
+            } => Some(select),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// passes results through to the client remembering the number of rows
+/// that the last statement reported as affected or selected and whether
+/// it failed
+pub(crate) struct RowCounter {
+    sender: Arc<dyn Sender>,
+    rows: AtomicUsize,
+    failed: AtomicBool,
+}
+
+impl RowCounter {
+    pub(crate) fn new(sender: Arc<dyn Sender>) -> RowCounter {
+        RowCounter {
+            sender,
+            rows: AtomicUsize::new(0),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// number of rows counted since previous call
+    pub(crate) fn take(&self) -> usize {
+        self.rows.swap(0, Ordering::SeqCst)
+    }
+
+    /// whether an error was sent since previous call
+    pub(crate) fn take_failed(&self) -> bool {
+        self.failed.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl Sender for RowCounter {
+    fn flush(&self) -> io::Result<()> {
+        self.sender.flush()
+    }
+
+    fn send(&self, query_result: QueryResult) -> io::Result<()> {
+        match &query_result {
+            Ok(QueryEvent::RecordsInserted(rows))
+            | Ok(QueryEvent::RecordsSelected(rows))
+            | Ok(QueryEvent::RecordsUpdated(rows))
+            | Ok(QueryEvent::RecordsDeleted(rows)) => self.rows.store(*rows, Ordering::SeqCst),
+            Err(_) => self.failed.store(true, Ordering::SeqCst),
+            _ => {}
+        }
+        self.sender.send(query_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_executions_of_same_query() {
+        let statistics = StatementStatistics::default();
+        statistics.record("select 1".to_owned(), Duration::from_micros(10), 1);
+        statistics.record("select 2".to_owned(), Duration::from_micros(5), 1);
+        statistics.record("select 1".to_owned(), Duration::from_micros(30), 1);
+
+        let entries = statistics.entries();
+        assert_eq!(
+            entries,
+            vec![
+                StatementEntry {
+                    query: "select 1".to_owned(),
+                    calls: 2,
+                    total_time: Duration::from_micros(40),
+                    rows: 2
+                },
+                StatementEntry {
+                    query: "select 2".to_owned(),
+                    calls: 1,
+                    total_time: Duration::from_micros(5),
+                    rows: 1
+                }
+            ]
+        );
+        assert_eq!(entries[0].mean_time(), Duration::from_micros(20));
+    }
+
+    #[test]
+    fn reset() {
+        let statistics = StatementStatistics::default();
+        statistics.record("select 1".to_owned(), Duration::from_micros(10), 1);
+
+        statistics.reset();
+
+        assert_eq!(statistics.entries(), vec![]);
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{spawn_executor_thread, Node, GLOBAL};
+use async_executor::Task;
+use async_io::Async;
+use data_manager::DatabaseHandle;
+use pg_model::{hba::HostBasedAccess, ProtocolConfiguration};
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr, TcpListener},
+    path::Path,
+};
+use tempfile::TempDir;
+
+/// database instance of a `TestCluster` with its own data directory, clients
+/// from the local host are trusted
+pub struct TestNode {
+    _task: Task<()>,
+    address: SocketAddr,
+    data_dir: TempDir,
+}
+
+impl TestNode {
+    fn start() -> io::Result<TestNode> {
+        let data_dir = tempfile::tempdir()?;
+        let storage = DatabaseHandle::persistent(data_dir.path().join("root_directory"))
+            .map_err(|()| io::Error::new(io::ErrorKind::Other, "storage could not be initialized"))?;
+        let listener = Async::<TcpListener>::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let address = listener.get_ref().local_addr()?;
+        let config = ProtocolConfiguration::none()
+            .with_hba(HostBasedAccess::parse("host all all 127.0.0.1/32 trust").expect("valid rules"));
+        Ok(TestNode {
+            _task: GLOBAL.spawn(Node::new(storage).serve(listener, config)),
+            address,
+            data_dir,
+        })
+    }
+
+    /// local address with an ephemeral port the node accepts clients on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        self.data_dir.path()
+    }
+}
+
+// TODO: nodes are independent of each other, replication and failover could be
+//       tested once a node could stream its changes to a standby
+/// nodes running in the current process, they stop accepting clients and their
+/// data directories are removed when the cluster is dropped
+pub struct TestCluster {
+    nodes: Vec<TestNode>,
+}
+
+impl TestCluster {
+    pub fn start(size: usize) -> io::Result<TestCluster> {
+        spawn_executor_thread();
+        let nodes = (0..size)
+            .map(|_| TestNode::start())
+            .collect::<io::Result<Vec<TestNode>>>()?;
+        Ok(TestCluster { nodes })
+    }
+
+    pub fn nodes(&self) -> &[TestNode] {
+        &self.nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+    };
+
+    fn startup_message(user: &str) -> Vec<u8> {
+        let mut body = vec![0, 3, 0, 0];
+        body.extend_from_slice(b"user\0");
+        body.extend_from_slice(user.as_bytes());
+        body.extend_from_slice(b"\0\0");
+        let mut message = ((body.len() + 4) as u32).to_be_bytes().to_vec();
+        message.extend(body);
+        message
+    }
+
+    #[test]
+    fn nodes_accept_clients_on_their_own_ports() {
+        let cluster = TestCluster::start(2).expect("cluster started");
+
+        let nodes = cluster.nodes();
+        assert_eq!(nodes.len(), 2);
+        assert_ne!(nodes[0].address(), nodes[1].address());
+        assert_ne!(nodes[0].data_dir(), nodes[1].data_dir());
+
+        for node in nodes {
+            let mut stream = TcpStream::connect(node.address()).expect("connected");
+            stream.write_all(&startup_message("test")).expect("sent");
+            let mut authentication_ok = [0; 9];
+            stream.read_exact(&mut authentication_ok).expect("received");
+            assert_eq!(authentication_ok, [b'R', 0, 0, 0, 8, 0, 0, 0, 0]);
+        }
+    }
+}
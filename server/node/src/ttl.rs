@@ -0,0 +1,326 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::result_cache::TableVersions;
+use analysis_tree::ColumnInfo;
+use data_manager::{DataDefReader, DatabaseHandle};
+use pg_model::results::QueryError;
+use plan::FullTableId;
+use repr::Datum;
+use sql_ast::{ident_name, SqlOption, Value};
+use std::{mem, sync::Arc, time::Duration};
+use types::SqlType;
+
+const TTL_COLUMN_OPTION: &str = "ttl_column";
+const TTL_OPTION: &str = "ttl";
+
+/// rows of a table expire once value of the column is older than `ttl`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TtlPolicy {
+    column: String,
+    ttl: Duration,
+}
+
+impl TtlPolicy {
+    /// policy declared by `WITH (ttl_column = '...', ttl = '...')` options of
+    /// `CREATE TABLE`, other options are ignored.
+    // TODO: there is no timestamp type yet, so the column holds seconds since
+    //       unix epoch and has to be of one of integer types
+    pub(crate) fn from_options(options: &[SqlOption], columns: &[ColumnInfo]) -> Result<Option<TtlPolicy>, QueryError> {
+        let mut column = None;
+        let mut ttl = None;
+        for option in options {
+            let name = ident_name(&option.name);
+            if name == TTL_COLUMN_OPTION {
+                column = Some(string_option(TTL_COLUMN_OPTION, &option.value)?);
+            } else if name == TTL_OPTION {
+                ttl = Some(string_option(TTL_OPTION, &option.value)?);
+            }
+        }
+        let (column, ttl) = match (column, ttl) {
+            (None, None) => return Ok(None),
+            (Some(column), Some(ttl)) => (column, ttl),
+            (Some(_), None) => {
+                return Err(QueryError::invalid_parameter_value(
+                    "parameter \"ttl_column\" requires parameter \"ttl\"",
+                ))
+            }
+            (None, Some(_)) => {
+                return Err(QueryError::invalid_parameter_value(
+                    "parameter \"ttl\" requires parameter \"ttl_column\"",
+                ))
+            }
+        };
+        match columns.iter().find(|info| info.name == column) {
+            None => return Err(QueryError::column_does_not_exist(column)),
+            Some(info) => match info.sql_type {
+                SqlType::SmallInt | SqlType::Integer | SqlType::BigInt => {}
+                _ => {
+                    return Err(QueryError::invalid_parameter_value(format!(
+                        "column \"{}\" of parameter \"ttl_column\" has to be of integer type",
+                        column
+                    )))
+                }
+            },
+        }
+        match parse_interval(&ttl) {
+            Some(ttl) => Ok(Some(TtlPolicy { column, ttl })),
+            None => Err(QueryError::invalid_parameter_value(format!(
+                "invalid value for parameter \"ttl\": \"{}\"",
+                ttl
+            ))),
+        }
+    }
+
+    /// whether row with the value of the column is expired at `now` seconds
+    /// since unix epoch, rows with `NULL` never expire
+    fn expired(&self, value: &Datum, now: i64) -> bool {
+        let created_at = match value {
+            Datum::Int16(value) => *value as i64,
+            Datum::Int32(value) => *value as i64,
+            Datum::Int64(value) => *value,
+            _ => return false,
+        };
+        created_at < now.saturating_sub(self.ttl.as_secs() as i64)
+    }
+}
+
+fn string_option(name: &str, value: &Value) -> Result<String, QueryError> {
+    match value {
+        Value::SingleQuotedString(value) => Ok(value.clone()),
+        value => Err(QueryError::invalid_parameter_value(format!(
+            "invalid value for parameter \"{}\": {}",
+            name, value
+        ))),
+    }
+}
+
+/// parses intervals like `7 days` or `1 hour 30 minutes`
+fn parse_interval(interval: &str) -> Option<Duration> {
+    let words = interval.split_whitespace().collect::<Vec<&str>>();
+    if words.is_empty() || words.len() % 2 != 0 {
+        return None;
+    }
+    let mut seconds = 0u64;
+    for pair in words.chunks(2) {
+        let quantity = pair[0].parse::<u64>().ok()?;
+        let unit = match pair[1].to_lowercase().as_str() {
+            "second" | "seconds" | "sec" | "secs" | "s" => 1,
+            "minute" | "minutes" | "min" | "mins" | "m" => 60,
+            "hour" | "hours" | "h" => 60 * 60,
+            "day" | "days" | "d" => 24 * 60 * 60,
+            "week" | "weeks" | "w" => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(quantity.checked_mul(unit)?)?;
+    }
+    if seconds == 0 || seconds > i64::MAX as u64 {
+        None
+    } else {
+        Some(Duration::from_secs(seconds))
+    }
+}
+
+/// TTL policies of tables of the node, policies are stored in the catalog
+/// as options of tables
+pub(crate) struct TtlPolicies {
+    storage: Arc<DatabaseHandle>,
+}
+
+impl TtlPolicies {
+    pub(crate) fn new(storage: Arc<DatabaseHandle>) -> TtlPolicies {
+        TtlPolicies { storage }
+    }
+
+    pub(crate) fn set(&self, schema_name: &str, table_name: &str, policy: TtlPolicy) {
+        let table_id = match self.storage.table_exists(schema_name, table_name) {
+            Some((schema_id, Some(table_id))) => (schema_id, table_id),
+            _ => return,
+        };
+        let options = vec![
+            (TTL_COLUMN_OPTION.to_owned(), policy.column),
+            (TTL_OPTION.to_owned(), format!("{} seconds", policy.ttl.as_secs())),
+        ];
+        if self.storage.set_table_options(&table_id, options).is_err() {
+            log::error!("could not store TTL policy of {}.{}", schema_name, table_name);
+        }
+    }
+
+    /// policies of tables that still exist
+    fn policies(&self) -> Vec<(FullTableId, TtlPolicy)> {
+        let tables = match self.storage.table_options() {
+            Ok(tables) => tables,
+            Err(()) => {
+                log::error!("could not read TTL policies");
+                return vec![];
+            }
+        };
+        let mut policies = vec![];
+        for table in tables {
+            let (schema_id, table_id) = table.full_table_id;
+            if self.storage.table_exists(&table.schema_name, &table.table_name) != Some((schema_id, Some(table_id))) {
+                continue;
+            }
+            let option = |name: &str| {
+                table
+                    .options
+                    .iter()
+                    .find(|(option, _value)| option == name)
+                    .map(|(_option, value)| value)
+            };
+            let ttl = option(TTL_OPTION).and_then(|ttl| parse_interval(ttl));
+            if let (Some(column), Some(ttl)) = (option(TTL_COLUMN_OPTION), ttl) {
+                policies.push((
+                    FullTableId::from(table.full_table_id),
+                    TtlPolicy {
+                        column: column.clone(),
+                        ttl,
+                    },
+                ));
+            }
+        }
+        policies
+    }
+
+    /// deletes rows expired at `now` seconds since unix epoch, at most
+    /// `batch_size` rows at a time. A batch is deleted as soon as the scan
+    /// finds enough expired rows to fill it. Rows are checked again when they
+    /// are deleted, so rows updated after the scan are kept. Returns number of
+    /// deleted rows
+    pub(crate) fn expire(&self, now: i64, batch_size: usize, table_versions: &TableVersions) -> usize {
+        let storage = &self.storage;
+        let batch_size = batch_size.max(1);
+        let mut deleted = 0;
+        for (table_id, policy) in self.policies() {
+            let column = match storage
+                .table_columns(&table_id)
+                .map(|columns| columns.iter().position(|(_id, column)| column.has_name(&policy.column)))
+            {
+                Ok(Some(column)) => column,
+                _ => continue,
+            };
+            let cursor = match storage.full_scan(&table_id) {
+                Ok(cursor) => cursor,
+                Err(()) => continue,
+            };
+            let delete_batch = |batch| -> Result<usize, ()> {
+                let deleted_batch = storage.delete_from_if(&table_id, batch, |values| {
+                    policy.expired(&values.unpack_columns(&[column])[0], now)
+                });
+                match deleted_batch {
+                    Ok(0) => Ok(0),
+                    Ok(size) => {
+                        table_versions.table_written(&table_id);
+                        Ok(size)
+                    }
+                    Err(()) => {
+                        log::error!("could not delete expired rows of {:?}", table_id);
+                        Err(())
+                    }
+                }
+            };
+            let mut expired = Vec::with_capacity(batch_size);
+            let mut failed = false;
+            for row in cursor {
+                match row {
+                    Ok(Ok((key, values))) => {
+                        if policy.expired(&values.unpack_columns(&[column])[0], now) {
+                            expired.push(key);
+                        }
+                    }
+                    Ok(Err(error)) => log::error!("could not read row of {:?} due to {:?}", table_id, error),
+                    Err(io_error) => log::error!("could not read row of {:?} due to {:?}", table_id, io_error),
+                }
+                if expired.len() == batch_size {
+                    match delete_batch(mem::replace(&mut expired, Vec::with_capacity(batch_size))) {
+                        Ok(size) => deleted += size,
+                        Err(()) => {
+                            failed = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            if !failed && !expired.is_empty() {
+                if let Ok(size) = delete_batch(expired) {
+                    deleted += size;
+                }
+            }
+        }
+        deleted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(test)]
+    mod interval {
+        use super::*;
+
+        #[test]
+        fn single_unit() {
+            assert_eq!(parse_interval("7 days"), Some(Duration::from_secs(7 * 24 * 60 * 60)));
+            assert_eq!(parse_interval("1 hour"), Some(Duration::from_secs(60 * 60)));
+            assert_eq!(parse_interval("30 s"), Some(Duration::from_secs(30)));
+        }
+
+        #[test]
+        fn many_units() {
+            assert_eq!(
+                parse_interval("1 Day 12 hours 30 minutes"),
+                Some(Duration::from_secs(36 * 60 * 60 + 30 * 60))
+            );
+        }
+
+        #[test]
+        fn invalid() {
+            assert_eq!(parse_interval(""), None);
+            assert_eq!(parse_interval("7"), None);
+            assert_eq!(parse_interval("7 fortnights"), None);
+            assert_eq!(parse_interval("-1 day"), None);
+            assert_eq!(parse_interval("0 days"), None);
+        }
+    }
+
+    #[cfg(test)]
+    mod expiration {
+        use super::*;
+
+        fn policy() -> TtlPolicy {
+            TtlPolicy {
+                column: "created_at".to_owned(),
+                ttl: Duration::from_secs(100),
+            }
+        }
+
+        #[test]
+        fn older_values_are_expired() {
+            assert!(policy().expired(&Datum::from_i64(899), 1000));
+            assert!(policy().expired(&Datum::from_i16(10), 1000));
+        }
+
+        #[test]
+        fn recent_values_are_kept() {
+            assert!(!policy().expired(&Datum::from_i64(900), 1000));
+            assert!(!policy().expired(&Datum::from_i32(2000), 1000));
+        }
+
+        #[test]
+        fn null_never_expires() {
+            assert!(!policy().expired(&Datum::from_null(), 1000));
+        }
+    }
+}
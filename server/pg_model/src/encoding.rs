@@ -0,0 +1,105 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::results::QueryError;
+
+/// the only encoding that server uses to exchange text with clients
+pub const CLIENT_ENCODING: &str = "UTF8";
+
+/// checks whether `name` of an encoding requested by a client is the
+/// `CLIENT_ENCODING` or one of its aliases
+pub fn is_supported(name: &str) -> bool {
+    matches!(
+        name.to_uppercase().replace(|c| c == '-' || c == '_', "").as_str(),
+        "UTF8" | "UNICODE"
+    )
+}
+
+/// validates that `bytes` are a text encoded with `CLIENT_ENCODING`. Text
+/// values can't contain NUL characters as it is done in PostgreSQL
+pub fn decode(bytes: &[u8]) -> Result<&str, QueryError> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => match text.find('\0') {
+            None => Ok(text),
+            Some(index) => Err(QueryError::invalid_byte_sequence(
+                CLIENT_ENCODING,
+                &bytes[index..=index],
+            )),
+        },
+        Err(error) => {
+            let start = error.valid_up_to();
+            let end = bytes.len().min(start + sequence_len(bytes[start]));
+            Err(QueryError::invalid_byte_sequence(CLIENT_ENCODING, &bytes[start..end]))
+        }
+    }
+}
+
+// number of bytes in a character that starts with `leading` byte, used to
+// report the whole invalid character as PostgreSQL does
+fn sequence_len(leading: u8) -> usize {
+    match leading {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_text() {
+        assert_eq!(decode("select 'ж';".as_bytes()), Ok("select 'ж';"));
+    }
+
+    #[test]
+    fn invalid_continuation_byte() {
+        assert_eq!(
+            decode(&[b'a', 0xC3, 0x28, b'b']),
+            Err(QueryError::invalid_byte_sequence("UTF8", &[0xC3, 0x28]))
+        );
+    }
+
+    #[test]
+    fn invalid_leading_byte() {
+        assert_eq!(decode(&[0xFF]), Err(QueryError::invalid_byte_sequence("UTF8", &[0xFF])));
+    }
+
+    #[test]
+    fn incomplete_character_at_the_end() {
+        assert_eq!(
+            decode(&[b'a', 0xE2, 0x82]),
+            Err(QueryError::invalid_byte_sequence("UTF8", &[0xE2, 0x82]))
+        );
+    }
+
+    #[test]
+    fn nul_character() {
+        assert_eq!(
+            decode(&[b'a', 0x00, b'b']),
+            Err(QueryError::invalid_byte_sequence("UTF8", &[0x00]))
+        );
+    }
+
+    #[test]
+    fn encoding_names() {
+        assert!(is_supported("UTF8"));
+        assert!(is_supported("utf-8"));
+        assert!(is_supported("unicode"));
+        assert!(!is_supported("LATIN1"));
+        assert!(!is_supported("SQL_ASCII"));
+    }
+}
@@ -0,0 +1,435 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+};
+
+/// how a client that matched a rule is authenticated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthMethod {
+    /// connection is allowed without asking for a password
+    Trust,
+    /// client is asked for a cleartext password
+    Password,
+    /// connection is refused
+    Reject,
+}
+
+impl Display for AuthMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthMethod::Trust => write!(f, "trust"),
+            AuthMethod::Password => write!(f, "password"),
+            AuthMethod::Reject => write!(f, "reject"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConnectionType {
+    Host,
+    HostSsl,
+    HostNoSsl,
+}
+
+impl ConnectionType {
+    fn matches(&self, ssl: bool) -> bool {
+        match self {
+            ConnectionType::Host => true,
+            ConnectionType::HostSsl => ssl,
+            ConnectionType::HostNoSsl => !ssl,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AddressRange {
+    network: IpAddr,
+    prefix: u32,
+}
+
+impl AddressRange {
+    fn parse(cidr: &str) -> Result<AddressRange, String> {
+        let (address, prefix) = match cidr.find('/') {
+            Some(index) => (&cidr[..index], Some(&cidr[index + 1..])),
+            None => (cidr, None),
+        };
+        let network = address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("invalid IP address \"{}\"", address))?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            None => max,
+            Some(prefix) => match prefix.parse::<u32>() {
+                Ok(prefix) if prefix <= max => prefix,
+                _ => return Err(format!("invalid CIDR mask in address \"{}\"", cidr)),
+            },
+        };
+        // ranges within `::ffff:0:0/96` are kept as IPv4 ones, so that they are
+        // matched against clients the same way whatever socket they connect to
+        match unmapped(network) {
+            IpAddr::V4(network) if prefix >= MAPPED_PREFIX => Ok(AddressRange {
+                network: IpAddr::V4(network),
+                prefix: prefix - MAPPED_PREFIX,
+            }),
+            _ => Ok(AddressRange { network, prefix }),
+        }
+    }
+
+    fn contains(&self, address: IpAddr) -> bool {
+        match (self.network, unmapped(address)) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix).unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), address) => {
+                let address = match address {
+                    IpAddr::V4(address) => address.to_ipv6_mapped(),
+                    IpAddr::V6(address) => address,
+                };
+                let mask = u128::MAX.checked_shl(128 - self.prefix).unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            (IpAddr::V4(_), IpAddr::V6(_)) => false,
+        }
+    }
+}
+
+/// length of `::ffff:0:0/96` prefix of IPv4 addresses mapped to IPv6 ones
+const MAPPED_PREFIX: u32 = 96;
+
+/// clients connected over IPv6 to a dual stack socket have IPv4 addresses
+/// mapped as `::ffff:a.b.c.d`. IPv4 compatible addresses like `::1` are not
+/// IPv4 ones, so `Ipv6Addr::to_ipv4` that converts them too isn't used
+fn unmapped(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(address),
+        address => address,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    connection_type: ConnectionType,
+    databases: Option<Vec<String>>,
+    users: Option<Vec<String>>,
+    address: Option<AddressRange>,
+    method: AuthMethod,
+}
+
+impl Rule {
+    fn matches(&self, address: IpAddr, ssl: bool, database: &str, user: &str) -> bool {
+        self.connection_type.matches(ssl)
+            && self
+                .address
+                .as_ref()
+                .map(|range| range.contains(address))
+                .unwrap_or(true)
+            && names_match(&self.databases, database)
+            && names_match(&self.users, user)
+    }
+}
+
+fn names_match(names: &Option<Vec<String>>, name: &str) -> bool {
+    match names {
+        None => true,
+        Some(names) => names.iter().any(|n| n == name),
+    }
+}
+
+fn names(field: &str) -> Option<Vec<String>> {
+    if field == "all" {
+        None
+    } else {
+        Some(field.split(',').map(ToOwned::to_owned).collect())
+    }
+}
+
+/// list of rules similar to PostgreSQL `pg_hba.conf` that are checked in order
+/// when a client connects, the first rule that matches connection type, client
+/// address, database and user decides how the client is authenticated
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostBasedAccess {
+    rules: Vec<Rule>,
+}
+
+impl HostBasedAccess {
+    /// parses rules in `pg_hba.conf` format, one rule per line:
+    /// `host|hostssl|hostnossl database user address method`, where database
+    /// and user are `all` or comma separated names, address is `all` or in
+    /// CIDR notation and method is one of `trust`, `password` or `reject`
+    pub fn parse(content: &str) -> Result<HostBasedAccess, String> {
+        let mut rules = vec![];
+        for (index, line) in content.lines().enumerate() {
+            let line = match line.find('#') {
+                Some(comment) => &line[..comment],
+                None => line,
+            };
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            if fields.is_empty() {
+                continue;
+            }
+            let rule = Self::rule(&fields).map_err(|error| format!("line {}: {}", index + 1, error))?;
+            rules.push(rule);
+        }
+        Ok(HostBasedAccess { rules })
+    }
+
+    fn rule(fields: &[&str]) -> Result<Rule, String> {
+        let connection_type = match fields[0] {
+            "host" => ConnectionType::Host,
+            "hostssl" => ConnectionType::HostSsl,
+            "hostnossl" => ConnectionType::HostNoSsl,
+            other => return Err(format!("invalid connection type \"{}\"", other)),
+        };
+        if fields.len() != 5 {
+            return Err(format!("expected 5 fields, found {}", fields.len()));
+        }
+        let address = match fields[3] {
+            "all" => None,
+            cidr => Some(AddressRange::parse(cidr)?),
+        };
+        let method = match fields[4] {
+            "trust" => AuthMethod::Trust,
+            "password" => AuthMethod::Password,
+            "reject" => AuthMethod::Reject,
+            other => return Err(format!("invalid authentication method \"{}\"", other)),
+        };
+        Ok(Rule {
+            connection_type,
+            databases: names(fields[1]),
+            users: names(fields[2]),
+            address,
+            method,
+        })
+    }
+
+    /// authentication method of the first matching rule, `None` if no rule
+    /// matches and connection has to be refused
+    pub fn method(&self, address: IpAddr, ssl: bool, database: &str, user: &str) -> Option<AuthMethod> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(address, ssl, database, user))
+            .map(|rule| rule.method)
+    }
+}
+
+impl Default for HostBasedAccess {
+    /// any client is asked for a password
+    fn default() -> HostBasedAccess {
+        HostBasedAccess::parse("host all all 0.0.0.0/0 password\nhost all all ::/0 password")
+            .expect("default rules are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[cfg(test)]
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn comments_and_empty_lines() {
+            let hba = HostBasedAccess::parse("# comment\n\n  host all all all trust # trust everyone\n");
+
+            assert_eq!(
+                hba.map(|hba| hba.method(ip("10.0.0.1"), false, "db", "user")),
+                Ok(Some(AuthMethod::Trust))
+            );
+        }
+
+        #[test]
+        fn no_rules() {
+            assert_eq!(
+                HostBasedAccess::parse("").map(|hba| hba.method(ip("127.0.0.1"), false, "db", "user")),
+                Ok(None)
+            );
+        }
+
+        #[test]
+        fn unsupported_connection_type() {
+            assert_eq!(
+                HostBasedAccess::parse("host all all all trust\nlocal all all trust"),
+                Err("line 2: invalid connection type \"local\"".to_owned())
+            );
+        }
+
+        #[test]
+        fn wrong_number_of_fields() {
+            assert_eq!(
+                HostBasedAccess::parse("host all all trust"),
+                Err("line 1: expected 5 fields, found 4".to_owned())
+            );
+        }
+
+        #[test]
+        fn invalid_address() {
+            assert_eq!(
+                HostBasedAccess::parse("host all all localhost trust"),
+                Err("line 1: invalid IP address \"localhost\"".to_owned())
+            );
+        }
+
+        #[test]
+        fn invalid_mask() {
+            assert_eq!(
+                HostBasedAccess::parse("host all all 10.0.0.0/33 trust"),
+                Err("line 1: invalid CIDR mask in address \"10.0.0.0/33\"".to_owned())
+            );
+        }
+
+        #[test]
+        fn unsupported_method() {
+            assert_eq!(
+                HostBasedAccess::parse("host all all all md5"),
+                Err("line 1: invalid authentication method \"md5\"".to_owned())
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod method {
+        use super::*;
+
+        #[test]
+        fn default_asks_for_password() {
+            let hba = HostBasedAccess::default();
+
+            assert_eq!(
+                hba.method(ip("192.168.1.1"), false, "db", "user"),
+                Some(AuthMethod::Password)
+            );
+            assert_eq!(hba.method(ip("::1"), true, "db", "user"), Some(AuthMethod::Password));
+        }
+
+        #[test]
+        fn first_matching_rule() {
+            let hba = HostBasedAccess::parse(
+                "host all all 127.0.0.1/32 trust\n\
+                 host all all 10.0.0.0/8 password\n\
+                 host all all all reject",
+            )
+            .unwrap();
+
+            assert_eq!(
+                hba.method(ip("127.0.0.1"), false, "db", "user"),
+                Some(AuthMethod::Trust)
+            );
+            assert_eq!(
+                hba.method(ip("10.20.30.40"), false, "db", "user"),
+                Some(AuthMethod::Password)
+            );
+            assert_eq!(
+                hba.method(ip("11.0.0.1"), false, "db", "user"),
+                Some(AuthMethod::Reject)
+            );
+        }
+
+        #[test]
+        fn database_and_user() {
+            let hba = HostBasedAccess::parse(
+                "host reports,audit analyst all password\n\
+                 host all admin all trust",
+            )
+            .unwrap();
+
+            assert_eq!(
+                hba.method(ip("10.0.0.1"), false, "audit", "analyst"),
+                Some(AuthMethod::Password)
+            );
+            assert_eq!(hba.method(ip("10.0.0.1"), false, "sales", "analyst"), None);
+            assert_eq!(
+                hba.method(ip("10.0.0.1"), false, "sales", "admin"),
+                Some(AuthMethod::Trust)
+            );
+        }
+
+        #[test]
+        fn ssl() {
+            let hba = HostBasedAccess::parse(
+                "hostssl all all all password\n\
+                 hostnossl all all 127.0.0.1 trust",
+            )
+            .unwrap();
+
+            assert_eq!(
+                hba.method(ip("10.0.0.1"), true, "db", "user"),
+                Some(AuthMethod::Password)
+            );
+            assert_eq!(hba.method(ip("10.0.0.1"), false, "db", "user"), None);
+            assert_eq!(
+                hba.method(ip("127.0.0.1"), false, "db", "user"),
+                Some(AuthMethod::Trust)
+            );
+        }
+
+        #[test]
+        fn ipv6() {
+            let hba = HostBasedAccess::parse("host all all fe80::/10 trust").unwrap();
+
+            assert_eq!(hba.method(ip("fe80::1"), false, "db", "user"), Some(AuthMethod::Trust));
+            assert_eq!(hba.method(ip("::1"), false, "db", "user"), None);
+            assert_eq!(hba.method(ip("10.0.0.1"), false, "db", "user"), None);
+        }
+
+        #[test]
+        fn ipv4_mapped_to_ipv6() {
+            let hba = HostBasedAccess::parse("host all all 192.168.0.0/16 trust").unwrap();
+
+            assert_eq!(
+                hba.method(ip("::ffff:192.168.5.5"), false, "db", "user"),
+                Some(AuthMethod::Trust)
+            );
+        }
+
+        #[test]
+        fn ipv4_mapped_range() {
+            let hba = HostBasedAccess::parse("host all all ::ffff:192.168.0.0/112 trust").unwrap();
+
+            assert_eq!(
+                hba.method(ip("192.168.5.5"), false, "db", "user"),
+                Some(AuthMethod::Trust)
+            );
+            assert_eq!(
+                hba.method(ip("::ffff:192.168.5.5"), false, "db", "user"),
+                Some(AuthMethod::Trust)
+            );
+            assert_eq!(hba.method(ip("10.0.0.1"), false, "db", "user"), None);
+        }
+
+        #[test]
+        fn ipv6_range_with_mapped_addresses() {
+            let hba = HostBasedAccess::parse("host all all ::/0 trust").unwrap();
+
+            assert_eq!(hba.method(ip("10.0.0.1"), false, "db", "user"), Some(AuthMethod::Trust));
+            assert_eq!(hba.method(ip("fe80::1"), false, "db", "user"), Some(AuthMethod::Trust));
+        }
+
+        #[test]
+        fn ipv6_loopback_is_not_ipv4() {
+            let hba = HostBasedAccess::parse("host all all ::1/128 trust").unwrap();
+
+            assert_eq!(hba.method(ip("::1"), false, "db", "user"), Some(AuthMethod::Trust));
+            assert_eq!(hba.method(ip("0.0.0.1"), false, "db", "user"), None);
+        }
+    }
+}
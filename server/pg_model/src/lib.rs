@@ -12,20 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::hba::HostBasedAccess;
 use pg_wire::{Error, PgFormat, PgType, Result};
 use rand::Rng;
 use std::{
     collections::{HashMap, VecDeque},
     path::PathBuf,
+    time::Duration,
 };
 
+/// Module contains functionality to validate text received from clients
+pub mod encoding;
+/// Module contains functionality to decide which clients can connect
+pub mod hba;
 /// Module contains functionality to represent query result
 pub mod results;
 /// Module contains functionality to represent server side client session
 pub mod session;
+/// Module contains functionality to hold settings of a session
+pub mod settings;
 /// Module contains functionality to hold data about `PreparedStatement`
 pub mod statement;
 
+/// Version of PostgreSQL that server is compatible with
+pub const SERVER_VERSION: &str = "12.4";
+
 /// Connection ID
 pub type ConnId = i32;
 /// Connection secret key
@@ -107,6 +118,16 @@ pub enum Command {
         /// The desired formats for the columns in the result set.
         result_formats: Vec<PgFormat>,
     },
+    /// Client commands to close a portal
+    ClosePortal {
+        /// The name of the portal to close.
+        name: String,
+    },
+    /// Client commands to close a prepared statement and portals bound from it
+    CloseStatement {
+        /// The name of the prepared statement to close.
+        name: String,
+    },
     /// Nothing needs to handle on client, just to receive next message
     Continue,
     /// Client commands to describe a prepared statement
@@ -146,6 +167,15 @@ pub enum Command {
         /// The SQL to execute.
         sql: String,
     },
+    /// Client message that can't be handled, e.g. its text is not valid in
+    /// the client encoding
+    Reject {
+        /// The error to send back to the client.
+        error: results::QueryError,
+        /// Whether the message was a simple query that has to be completed
+        /// after the error.
+        simple_query: bool,
+    },
     /// Client commands to terminate current connection
     Terminate,
 }
@@ -171,22 +201,35 @@ impl Into<&'_ [u8]> for Encryption {
 /// PostgreSQL Wire Protocol supports `ssl`/`tls` and `gss` encryption
 pub struct ProtocolConfiguration {
     ssl_conf: Option<(PathBuf, String)>,
+    hba: HostBasedAccess,
+    send_timeout: Option<Duration>,
 }
 
 #[allow(dead_code)]
 impl ProtocolConfiguration {
     /// Creates configuration that support neither `ssl` nor `gss` encryption
     pub fn none() -> Self {
-        Self { ssl_conf: None }
+        Self {
+            ssl_conf: None,
+            hba: HostBasedAccess::default(),
+            send_timeout: None,
+        }
     }
 
     /// Creates configuration that support only `ssl`
     pub fn with_ssl(cert: PathBuf, password: String) -> Self {
         Self {
             ssl_conf: Some((cert, password)),
+            hba: HostBasedAccess::default(),
+            send_timeout: None,
         }
     }
 
+    /// replaces rules that decide which clients can connect
+    pub fn with_hba(self, hba: HostBasedAccess) -> Self {
+        Self { hba, ..self }
+    }
+
     /// returns `true` if support `ssl` connection
     pub fn ssl_support(&self) -> bool {
         self.ssl_conf.is_some()
@@ -197,6 +240,24 @@ impl ProtocolConfiguration {
         self.ssl_conf.as_ref()
     }
 
+    /// how long sending results waits for a client to read them before the
+    /// statement is cancelled and connection is closed
+    pub fn with_send_timeout(self, send_timeout: Duration) -> Self {
+        Self {
+            send_timeout: Some(send_timeout),
+            ..self
+        }
+    }
+
+    pub fn send_timeout(&self) -> Option<Duration> {
+        self.send_timeout
+    }
+
+    /// rules to authenticate clients
+    pub fn hba(&self) -> &HostBasedAccess {
+        &self.hba
+    }
+
     /// returns `true` if support `gss` encrypted connection
     pub fn gssenc_support(&self) -> bool {
         false
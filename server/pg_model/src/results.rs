@@ -31,6 +31,8 @@ pub enum QueryEvent {
     TableCreated,
     /// Table successfully dropped
     TableDropped,
+    /// Table successfully altered
+    TableAltered,
     /// Variable successfully set
     VariableSet,
     /// Transaction is started
@@ -41,6 +43,8 @@ pub enum QueryEvent {
     RowDescription(Vec<ColumnMetadata>),
     /// Row data
     DataRow(Vec<String>),
+    /// Row data where `None` values are `NULL`
+    NullableDataRow(Vec<Option<String>>),
     /// Records selected from database
     RecordsSelected(usize),
     /// Number of records updated into a table
@@ -61,6 +65,10 @@ pub enum QueryEvent {
     ParseComplete,
     /// Binding the extended query is complete
     BindComplete,
+    /// Closing a prepared statement or a portal is complete
+    CloseComplete,
+    /// Notice that the client is informed about while the query is processed
+    Notice(QueryNotice),
 }
 
 impl Into<BackendMessage> for QueryEvent {
@@ -70,11 +78,16 @@ impl Into<BackendMessage> for QueryEvent {
             QueryEvent::SchemaDropped => BackendMessage::CommandComplete("DROP SCHEMA".to_owned()),
             QueryEvent::TableCreated => BackendMessage::CommandComplete("CREATE TABLE".to_owned()),
             QueryEvent::TableDropped => BackendMessage::CommandComplete("DROP TABLE".to_owned()),
+            QueryEvent::TableAltered => BackendMessage::CommandComplete("ALTER TABLE".to_owned()),
             QueryEvent::VariableSet => BackendMessage::CommandComplete("SET".to_owned()),
             QueryEvent::TransactionStarted => BackendMessage::CommandComplete("BEGIN".to_owned()),
             QueryEvent::RecordsInserted(records) => BackendMessage::CommandComplete(format!("INSERT 0 {}", records)),
             QueryEvent::RowDescription(description) => BackendMessage::RowDescription(description),
             QueryEvent::DataRow(data) => BackendMessage::DataRow(data),
+            // pg_wire can't encode `NULL` values, they are empty strings here, use `nullable_data_row`
+            QueryEvent::NullableDataRow(data) => {
+                BackendMessage::DataRow(data.into_iter().map(Option::unwrap_or_default).collect())
+            }
             QueryEvent::RecordsSelected(records) => BackendMessage::CommandComplete(format!("SELECT {}", records)),
             QueryEvent::RecordsUpdated(records) => BackendMessage::CommandComplete(format!("UPDATE {}", records)),
             QueryEvent::RecordsDeleted(records) => BackendMessage::CommandComplete(format!("DELETE {}", records)),
@@ -96,6 +109,9 @@ impl Into<BackendMessage> for QueryEvent {
             QueryEvent::QueryComplete => BackendMessage::ReadyForQuery,
             QueryEvent::ParseComplete => BackendMessage::ParseComplete,
             QueryEvent::BindComplete => BackendMessage::BindComplete,
+            QueryEvent::CloseComplete => BackendMessage::CloseComplete,
+            // pg_wire can't encode fields of a notice, use `QueryNotice::as_vec`
+            QueryEvent::Notice(_) => BackendMessage::NoticeResponse,
         }
     }
 }
@@ -142,11 +158,19 @@ pub(crate) enum QueryErrorKind {
         param_index: usize,
     },
     InvalidParameterValue(String),
+    InvalidRegularExpression(String),
     PreparedStatementDoesNotExist(String),
+    DuplicatePreparedStatement(String),
     PortalDoesNotExist(String),
+    DuplicatePortal(String),
     TypeDoesNotExist(String),
     ProtocolViolation(String),
+    InvalidAuthorizationSpecification(String),
     FeatureNotSupported(String),
+    ReadOnlySqlTransaction(String),
+    ObjectInUse(String),
+    DataCorrupted,
+    OutOfMemory,
     TooManyInsertExpressions,
     NumericTypeOutOfRange {
         pg_type: PgType,
@@ -182,6 +206,18 @@ pub(crate) enum QueryErrorKind {
         value: String,
     },
     DuplicateColumn(String),
+    InvalidName(String),
+    UndefinedCollation(String),
+    CollationNotSupported(String),
+    InvalidByteSequence {
+        encoding: String,
+        bytes: Vec<u8>,
+    },
+    QueryCanceled,
+    AdminShutdown,
+    UnrecognizedConfigurationParameter(String),
+    UndefinedFunctionCall(String),
+    DivisionByZero,
 }
 
 impl QueryErrorKind {
@@ -195,11 +231,19 @@ impl QueryErrorKind {
             Self::ColumnDoesNotExist(_) => "42703",
             Self::IndeterminateParameterDataType { .. } => "42P18",
             Self::InvalidParameterValue(_) => "22023",
+            Self::InvalidRegularExpression(_) => "2201B",
             Self::PreparedStatementDoesNotExist(_) => "26000",
+            Self::DuplicatePreparedStatement(_) => "42P05",
             Self::PortalDoesNotExist(_) => "26000",
+            Self::DuplicatePortal(_) => "42P03",
             Self::TypeDoesNotExist(_) => "42704",
             Self::ProtocolViolation(_) => "08P01",
+            Self::InvalidAuthorizationSpecification(_) => "28000",
             Self::FeatureNotSupported(_) => "0A000",
+            Self::ReadOnlySqlTransaction(_) => "25006",
+            Self::ObjectInUse(_) => "55006",
+            Self::DataCorrupted => "XX001",
+            Self::OutOfMemory => "53200",
             Self::TooManyInsertExpressions => "42601",
             Self::NumericTypeOutOfRange { .. } => "22003",
             Self::DataTypeMismatch { .. } => "2200G",
@@ -210,6 +254,15 @@ impl QueryErrorKind {
             Self::SyntaxError(_) => "42601",
             Self::InvalidTextRepresentation { .. } => "22P02",
             Self::DuplicateColumn(_) => "42701",
+            Self::InvalidName(_) => "42602",
+            Self::UndefinedCollation(_) => "42704",
+            Self::CollationNotSupported(_) => "42804",
+            Self::InvalidByteSequence { .. } => "22021",
+            Self::QueryCanceled => "57014",
+            Self::AdminShutdown => "57P01",
+            Self::UnrecognizedConfigurationParameter(_) => "42704",
+            Self::UndefinedFunctionCall(_) => "42883",
+            Self::DivisionByZero => "22012",
         }
     }
 }
@@ -229,15 +282,31 @@ impl Display for QueryErrorKind {
                 write!(f, "could not determine data type of parameter ${}", param_index + 1)
             }
             Self::InvalidParameterValue(message) => write!(f, "{}", message),
+            Self::InvalidRegularExpression(message) => write!(f, "invalid regular expression: {}", message),
             Self::PreparedStatementDoesNotExist(statement_name) => {
                 write!(f, "prepared statement {} does not exist", statement_name)
             }
+            Self::DuplicatePreparedStatement(statement_name) => {
+                write!(f, "prepared statement \"{}\" already exists", statement_name)
+            }
             Self::PortalDoesNotExist(portal_name) => write!(f, "portal {} does not exist", portal_name),
+            Self::DuplicatePortal(portal_name) => write!(f, "portal \"{}\" already exists", portal_name),
             Self::TypeDoesNotExist(type_name) => write!(f, "type \"{}\" does not exist", type_name),
             Self::ProtocolViolation(message) => write!(f, "{}", message),
+            Self::InvalidAuthorizationSpecification(message) => write!(f, "{}", message),
             Self::FeatureNotSupported(raw_sql_query) => {
                 write!(f, "Currently, Query '{}' can't be executed", raw_sql_query)
             }
+            Self::ReadOnlySqlTransaction(statement) => {
+                write!(f, "cannot execute {} in a read-only transaction", statement)
+            }
+            Self::ObjectInUse(table_name) => write!(
+                f,
+                "cannot ALTER TABLE \"{}\" because it is being used by active queries",
+                table_name
+            ),
+            Self::DataCorrupted => write!(f, "could not read row, stored data is corrupted"),
+            Self::OutOfMemory => write!(f, "out of memory for query"),
             Self::TooManyInsertExpressions => write!(f, "INSERT has more expressions than target columns"),
             Self::NumericTypeOutOfRange {
                 pg_type,
@@ -284,6 +353,25 @@ impl Display for QueryErrorKind {
                 write!(f, "invalid input syntax for type {}: \"{}\"", pg_type, value)
             }
             Self::DuplicateColumn(name) => write!(f, "column \"{}\" specified more than once", name),
+            Self::InvalidName(message) => write!(f, "{}", message),
+            Self::UndefinedCollation(collation) => {
+                write!(f, "collation \"{}\" for encoding \"UTF8\" does not exist", collation)
+            }
+            Self::CollationNotSupported(type_name) => write!(f, "collations are not supported by type {}", type_name),
+            Self::InvalidByteSequence { encoding, bytes } => {
+                write!(f, "invalid byte sequence for encoding \"{}\":", encoding)?;
+                for byte in bytes {
+                    write!(f, " 0x{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            Self::QueryCanceled => write!(f, "canceling statement due to user request"),
+            Self::AdminShutdown => write!(f, "terminating connection due to administrator command"),
+            Self::UnrecognizedConfigurationParameter(name) => {
+                write!(f, "unrecognized configuration parameter \"{}\"", name)
+            }
+            Self::UndefinedFunctionCall(call) => write!(f, "function {} does not exist", call),
+            Self::DivisionByZero => write!(f, "division by zero"),
         }
     }
 }
@@ -381,6 +469,14 @@ impl QueryError {
         }
     }
 
+    /// invalid regular expression error constructor
+    pub fn invalid_regular_expression<S: ToString>(message: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::InvalidRegularExpression(message.to_string()),
+        }
+    }
+
     /// prepared statement does not exist error constructor
     pub fn prepared_statement_does_not_exist<S: ToString>(statement_name: S) -> QueryError {
         QueryError {
@@ -389,6 +485,14 @@ impl QueryError {
         }
     }
 
+    /// prepared statement already exists error constructor
+    pub fn duplicate_prepared_statement<S: ToString>(statement_name: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::DuplicatePreparedStatement(statement_name.to_string()),
+        }
+    }
+
     /// portal does not exist error constructor
     pub fn portal_does_not_exist<S: ToString>(portal_name: S) -> QueryError {
         QueryError {
@@ -397,6 +501,14 @@ impl QueryError {
         }
     }
 
+    /// portal already exists error constructor
+    pub fn duplicate_portal<S: ToString>(portal_name: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::DuplicatePortal(portal_name.to_string()),
+        }
+    }
+
     /// type does not exist error constructor
     pub fn type_does_not_exist<S: ToString>(type_name: S) -> QueryError {
         QueryError {
@@ -413,6 +525,41 @@ impl QueryError {
         }
     }
 
+    /// message declares a length that can't be read, the rest of the stream
+    /// could not be split into messages and connection is closed
+    pub fn invalid_message_length(len: usize) -> QueryError {
+        QueryError {
+            severity: Severity::Fatal,
+            kind: QueryErrorKind::ProtocolViolation(format!("invalid message length {}", len)),
+        }
+    }
+
+    /// no host based access rule matches the connecting client
+    pub fn no_hba_entry<H: ToString, U: ToString, D: ToString>(host: H, user: U, database: D) -> QueryError {
+        QueryError {
+            severity: Severity::Fatal,
+            kind: QueryErrorKind::InvalidAuthorizationSpecification(format!(
+                "no pg_hba.conf entry for host \"{}\", user \"{}\", database \"{}\"",
+                host.to_string(),
+                user.to_string(),
+                database.to_string()
+            )),
+        }
+    }
+
+    /// host based access rule matching the connecting client rejects it
+    pub fn hba_rejects_connection<H: ToString, U: ToString, D: ToString>(host: H, user: U, database: D) -> QueryError {
+        QueryError {
+            severity: Severity::Fatal,
+            kind: QueryErrorKind::InvalidAuthorizationSpecification(format!(
+                "pg_hba.conf rejects connection for host \"{}\", user \"{}\", database \"{}\"",
+                host.to_string(),
+                user.to_string(),
+                database.to_string()
+            )),
+        }
+    }
+
     /// not supported operation error constructor
     pub fn feature_not_supported<S: ToString>(feature_description: S) -> QueryError {
         QueryError {
@@ -421,6 +568,38 @@ impl QueryError {
         }
     }
 
+    /// data modification in read-only transaction error constructor
+    pub fn read_only_sql_transaction<S: ToString>(statement: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::ReadOnlySqlTransaction(statement.to_string()),
+        }
+    }
+
+    /// table that is altered while its scans are open error constructor
+    pub fn object_in_use<S: ToString>(table_name: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::ObjectInUse(table_name.to_string()),
+        }
+    }
+
+    /// stored data checksum mismatch error constructor
+    pub fn data_corrupted() -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::DataCorrupted,
+        }
+    }
+
+    /// query needs more memory than `work_mem` allows error constructor
+    pub fn out_of_memory() -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::OutOfMemory,
+        }
+    }
+
     /// too many insert expressions errors constructors
     pub fn too_many_insert_expressions() -> QueryError {
         QueryError {
@@ -530,6 +709,137 @@ impl QueryError {
             kind: QueryErrorKind::DuplicateColumn(column.to_string()),
         }
     }
+
+    /// invalid name of created object
+    pub fn invalid_name<S: ToString>(message: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::InvalidName(message.to_string()),
+        }
+    }
+
+    /// collation does not exist
+    pub fn undefined_collation<S: ToString>(collation: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::UndefinedCollation(collation.to_string()),
+        }
+    }
+
+    /// collation is applied to a type that can't have one
+    pub fn collation_not_supported<S: ToString>(type_name: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::CollationNotSupported(type_name.to_string()),
+        }
+    }
+
+    /// text received from a client has bytes that are not valid in its encoding
+    pub fn invalid_byte_sequence<S: ToString>(encoding: S, bytes: &[u8]) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::InvalidByteSequence {
+                encoding: encoding.to_string(),
+                bytes: bytes.to_vec(),
+            },
+        }
+    }
+
+    /// running statement was canceled by a client request
+    pub fn query_canceled() -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::QueryCanceled,
+        }
+    }
+
+    /// connection was terminated from another one
+    pub fn admin_shutdown() -> QueryError {
+        QueryError {
+            severity: Severity::Fatal,
+            kind: QueryErrorKind::AdminShutdown,
+        }
+    }
+
+    /// session has no setting with the name
+    pub fn unrecognized_configuration_parameter<S: ToString>(name: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::UnrecognizedConfigurationParameter(name.to_string()),
+        }
+    }
+
+    /// no function with the name accepts arguments of the call
+    pub fn undefined_function_call<S: ToString>(call: S) -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::UndefinedFunctionCall(call.to_string()),
+        }
+    }
+
+    /// divisor of `/` or `%` operator is zero
+    pub fn division_by_zero() -> QueryError {
+        QueryError {
+            severity: Severity::Error,
+            kind: QueryErrorKind::DivisionByZero,
+        }
+    }
+}
+
+const DATA_ROW: u8 = b'D';
+const NOTICE_RESPONSE: u8 = b'N';
+
+/// binary representation of `DataRow` message, `NULL` values have -1 length
+/// and no bytes
+pub fn nullable_data_row(values: &[Option<String>]) -> Vec<u8> {
+    let mut fields = vec![];
+    for value in values {
+        match value {
+            Some(value) => {
+                fields.extend_from_slice(&(value.len() as i32).to_be_bytes());
+                fields.extend_from_slice(value.as_bytes());
+            }
+            None => fields.extend_from_slice(&(-1i32).to_be_bytes()),
+        }
+    }
+    let mut bytes = vec![DATA_ROW];
+    bytes.extend_from_slice(&(6 + fields.len() as i32).to_be_bytes());
+    bytes.extend_from_slice(&(values.len() as i16).to_be_bytes());
+    bytes.extend_from_slice(&fields);
+    bytes
+}
+
+/// Represents notice that is sent to the client along with results of a query
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryNotice {
+    severity: Severity,
+    code: &'static str,
+    message: String,
+}
+
+impl QueryNotice {
+    /// identifier of a created object is longer than the maximum length
+    pub fn identifier_truncated<N: ToString, T: ToString>(name: N, truncated: T) -> QueryNotice {
+        QueryNotice {
+            severity: Severity::Notice,
+            code: "42622",
+            message: format!(
+                "identifier \"{}\" will be truncated to \"{}\"",
+                name.to_string(),
+                truncated.to_string()
+            ),
+        }
+    }
+
+    /// binary representation of `NoticeResponse` message, its fields are the
+    /// same as of `ErrorResponse`
+    pub fn as_vec(&self) -> Vec<u8> {
+        let mut bytes =
+            BackendMessage::ErrorResponse(Some(self.severity.into()), Some(self.code), Some(self.message.clone()))
+                .as_vec();
+        bytes[0] = NOTICE_RESPONSE;
+        bytes
+    }
 }
 
 #[cfg(test)]
@@ -564,6 +874,12 @@ mod tests {
             assert_eq!(message, BackendMessage::CommandComplete("DROP TABLE".to_owned()));
         }
 
+        #[test]
+        fn alter_table() {
+            let message: BackendMessage = QueryEvent::TableAltered.into();
+            assert_eq!(message, BackendMessage::CommandComplete("ALTER TABLE".to_owned()));
+        }
+
         #[test]
         fn insert_record() {
             let records_number = 3;
@@ -597,6 +913,21 @@ mod tests {
             assert_eq!(message, BackendMessage::DataRow(vec!["1".to_owned(), "2".to_owned()]))
         }
 
+        #[test]
+        fn data_row_with_null_values() {
+            let message: BackendMessage = QueryEvent::NullableDataRow(vec![Some("1".to_owned()), None]).into();
+            assert_eq!(message, BackendMessage::DataRow(vec!["1".to_owned(), "".to_owned()]));
+
+            assert_eq!(
+                nullable_data_row(&[Some("1".to_owned()), Some("2".to_owned())]),
+                BackendMessage::DataRow(vec!["1".to_owned(), "2".to_owned()]).as_vec()
+            );
+            assert_eq!(
+                nullable_data_row(&[Some("1".to_owned()), None]),
+                vec![b'D', 0, 0, 0, 15, 0, 2, 0, 0, 0, 1, b'1', 255, 255, 255, 255]
+            );
+        }
+
         #[test]
         fn select_records() {
             let message: BackendMessage = QueryEvent::RecordsSelected(2).into();
@@ -668,6 +999,41 @@ mod tests {
             let message: BackendMessage = QueryEvent::BindComplete.into();
             assert_eq!(message, BackendMessage::BindComplete)
         }
+
+        #[test]
+        fn complete_close() {
+            let message: BackendMessage = QueryEvent::CloseComplete.into();
+            assert_eq!(message, BackendMessage::CloseComplete)
+        }
+    }
+
+    #[cfg(test)]
+    mod query_notice {
+        use super::*;
+
+        #[test]
+        fn identifier_truncated() {
+            let notice = QueryNotice::identifier_truncated("t".repeat(70), "t".repeat(63));
+
+            let mut expected = BackendMessage::ErrorResponse(
+                Some("NOTICE"),
+                Some("42622"),
+                Some(format!(
+                    "identifier \"{}\" will be truncated to \"{}\"",
+                    "t".repeat(70),
+                    "t".repeat(63)
+                )),
+            )
+            .as_vec();
+            expected[0] = b'N';
+            assert_eq!(notice.as_vec(), expected);
+        }
+
+        #[test]
+        fn notice_event() {
+            let message: BackendMessage = QueryEvent::Notice(QueryNotice::identifier_truncated("a", "b")).into();
+            assert_eq!(message, BackendMessage::NoticeResponse);
+        }
     }
 
     #[cfg(test)]
@@ -765,6 +1131,19 @@ mod tests {
             )
         }
 
+        #[test]
+        fn invalid_regular_expression() {
+            let message: BackendMessage = QueryError::invalid_regular_expression("unclosed group").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("2201B"),
+                    Some("invalid regular expression: unclosed group".to_owned())
+                )
+            )
+        }
+
         #[test]
         fn prepared_statement_does_not_exists() {
             let message: BackendMessage = QueryError::prepared_statement_does_not_exist("statement_name").into();
@@ -778,6 +1157,19 @@ mod tests {
             )
         }
 
+        #[test]
+        fn duplicate_prepared_statement() {
+            let message: BackendMessage = QueryError::duplicate_prepared_statement("statement_name").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("42P05"),
+                    Some("prepared statement \"statement_name\" already exists".to_owned()),
+                )
+            )
+        }
+
         #[test]
         fn portal_does_not_exists() {
             let message: BackendMessage = QueryError::portal_does_not_exist("portal_name").into();
@@ -791,6 +1183,19 @@ mod tests {
             )
         }
 
+        #[test]
+        fn duplicate_portal() {
+            let message: BackendMessage = QueryError::duplicate_portal("portal_name").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("42P03"),
+                    Some("portal \"portal_name\" already exists".to_owned()),
+                )
+            )
+        }
+
         #[test]
         fn type_does_not_exists() {
             let message: BackendMessage = QueryError::type_does_not_exist("type_name").into();
@@ -804,6 +1209,48 @@ mod tests {
             )
         }
 
+        #[test]
+        fn invalid_message_length() {
+            let message: BackendMessage = QueryError::invalid_message_length(2).into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("FATAL"),
+                    Some("08P01"),
+                    Some("invalid message length 2".to_owned())
+                )
+            )
+        }
+
+        #[test]
+        fn no_hba_entry() {
+            let message: BackendMessage = QueryError::no_hba_entry("10.0.0.1", "user", "db").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("FATAL"),
+                    Some("28000"),
+                    Some("no pg_hba.conf entry for host \"10.0.0.1\", user \"user\", database \"db\"".to_owned())
+                )
+            )
+        }
+
+        #[test]
+        fn hba_rejects_connection() {
+            let message: BackendMessage = QueryError::hba_rejects_connection("10.0.0.1", "user", "db").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("FATAL"),
+                    Some("28000"),
+                    Some(
+                        "pg_hba.conf rejects connection for host \"10.0.0.1\", user \"user\", database \"db\""
+                            .to_owned()
+                    )
+                )
+            )
+        }
+
         #[test]
         fn protocol_violation() {
             let message: BackendMessage = QueryError::protocol_violation("Wrong protocol data").into();
@@ -827,6 +1274,57 @@ mod tests {
             )
         }
 
+        #[test]
+        fn read_only_sql_transaction() {
+            let message: BackendMessage = QueryError::read_only_sql_transaction("INSERT").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("25006"),
+                    Some("cannot execute INSERT in a read-only transaction".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn object_in_use() {
+            let message: BackendMessage = QueryError::object_in_use("schema_name.table_name").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("55006"),
+                    Some(
+                        "cannot ALTER TABLE \"schema_name.table_name\" because it is being used by active queries"
+                            .to_owned()
+                    ),
+                )
+            )
+        }
+
+        #[test]
+        fn data_corrupted() {
+            let message: BackendMessage = QueryError::data_corrupted().into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("XX001"),
+                    Some("could not read row, stored data is corrupted".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn out_of_memory() {
+            let message: BackendMessage = QueryError::out_of_memory().into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(Some("ERROR"), Some("53200"), Some("out of memory for query".to_owned()),)
+            )
+        }
+
         #[test]
         fn too_many_insert_expressions() {
             let message: BackendMessage = QueryError::too_many_insert_expressions().into();
@@ -919,6 +1417,119 @@ mod tests {
                 )
             )
         }
+
+        #[test]
+        fn invalid_name() {
+            let message: BackendMessage = QueryError::invalid_name("zero-length delimited identifier").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("42602"),
+                    Some("zero-length delimited identifier".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn undefined_collation() {
+            let message: BackendMessage = QueryError::undefined_collation("de_de").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("42704"),
+                    Some("collation \"de_de\" for encoding \"UTF8\" does not exist".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn collation_not_supported() {
+            let message: BackendMessage = QueryError::collation_not_supported("integer").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("42804"),
+                    Some("collations are not supported by type integer".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn invalid_byte_sequence() {
+            let message: BackendMessage = QueryError::invalid_byte_sequence("UTF8", &[0xc3, 0x28]).into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("22021"),
+                    Some("invalid byte sequence for encoding \"UTF8\": 0xc3 0x28".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn query_canceled() {
+            let message: BackendMessage = QueryError::query_canceled().into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("57014"),
+                    Some("canceling statement due to user request".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn admin_shutdown() {
+            let message: BackendMessage = QueryError::admin_shutdown().into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("FATAL"),
+                    Some("57P01"),
+                    Some("terminating connection due to administrator command".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn unrecognized_configuration_parameter() {
+            let message: BackendMessage = QueryError::unrecognized_configuration_parameter("not_a_setting").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("42704"),
+                    Some("unrecognized configuration parameter \"not_a_setting\"".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn undefined_function_call() {
+            let message: BackendMessage = QueryError::undefined_function_call("abs(string)").into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(
+                    Some("ERROR"),
+                    Some("42883"),
+                    Some("function abs(string) does not exist".to_owned()),
+                )
+            )
+        }
+
+        #[test]
+        fn division_by_zero() {
+            let message: BackendMessage = QueryError::division_by_zero().into();
+            assert_eq!(
+                message,
+                BackendMessage::ErrorResponse(Some("ERROR"), Some("22012"), Some("division by zero".to_owned()),)
+            )
+        }
     }
 
     #[cfg(test)]
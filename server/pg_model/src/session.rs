@@ -12,17 +12,44 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::statement::{Portal, PreparedStatement};
+use crate::{
+    results::QueryError,
+    settings::{self, Settings},
+    statement::{Portal, PreparedStatement},
+};
 use pg_wire::PgFormat;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::collections::HashMap;
 
+/// Name of the unnamed prepared statement and of the unnamed portal
+pub const UNNAMED: &str = "";
+/// user of a session if client did not send one on startup
+pub const DEFAULT_USER: &str = "postgres";
+
 /// A `Session` holds SQL state that is attached to a session.
+///
+/// Named prepared statements and portals live until they are explicitly
+/// closed and can't be redefined before that. The unnamed prepared statement
+/// and the unnamed portal are replaced by the next `Parse` or `Bind` that
+/// targets them.
 #[derive(Clone, Debug)]
 pub struct Session<S> {
     /// A map from statement names to parameterized statements
     prepared_statements: HashMap<String, PreparedStatement<S>>,
     /// A map from statement names to bound statements
     portals: HashMap<String, Portal<S>>,
+    /// Whether statements that modify data or schema are rejected
+    read_only: bool,
+    /// Whether results of repeated selects are served from a cache
+    result_cache: bool,
+    /// User that client connected as
+    user: String,
+    /// Database that client connected to
+    database: String,
+    /// Generator of `random()` values, reseeded by `setseed()`
+    random: StdRng,
+    /// Values of settings changed by `SET` and `RESET`
+    settings: Settings,
 }
 
 impl<S> Default for Session<S> {
@@ -30,6 +57,12 @@ impl<S> Default for Session<S> {
         Session {
             prepared_statements: HashMap::default(),
             portals: HashMap::default(),
+            read_only: false,
+            result_cache: false,
+            user: DEFAULT_USER.to_owned(),
+            database: DEFAULT_USER.to_owned(),
+            random: StdRng::from_entropy(),
+            settings: Settings::default(),
         }
     }
 }
@@ -40,14 +73,22 @@ impl<S> Session<S> {
         self.prepared_statements.get(name)
     }
 
-    /// save `PreparedStatement` associated with a name
-    pub fn set_prepared_statement(&mut self, name: String, statement: PreparedStatement<S>) {
+    /// save `PreparedStatement` associated with a name, the unnamed statement
+    /// is replaced while a named one has to be removed first
+    pub fn set_prepared_statement(&mut self, name: String, statement: PreparedStatement<S>) -> Result<(), QueryError> {
+        if name != UNNAMED && self.prepared_statements.contains_key(&name) {
+            return Err(QueryError::duplicate_prepared_statement(name));
+        }
         self.prepared_statements.insert(name, statement);
+        Ok(())
     }
 
-    /// remove `PreparedStatement` by its name
+    /// remove `PreparedStatement` by its name together with portals that were
+    /// bound from it
     pub fn remove_prepared_statement(&mut self, name: &str) {
-        self.prepared_statements.remove(name);
+        if self.prepared_statements.remove(name).is_some() {
+            self.portals.retain(|_, portal| portal.stmt_name() != name);
+        }
     }
 
     /// get `Portal` by its name
@@ -55,9 +96,402 @@ impl<S> Session<S> {
         self.portals.get(name)
     }
 
-    /// save `Portal` associated with a name
-    pub fn set_portal(&mut self, portal_name: String, statement_name: String, stmt: S, result_formats: Vec<PgFormat>) {
+    /// save `Portal` associated with a name, the unnamed portal is replaced
+    /// while a named one has to be removed first
+    pub fn set_portal(
+        &mut self,
+        portal_name: String,
+        statement_name: String,
+        stmt: S,
+        result_formats: Vec<PgFormat>,
+    ) -> Result<(), QueryError> {
+        if portal_name != UNNAMED && self.portals.contains_key(&portal_name) {
+            return Err(QueryError::duplicate_portal(portal_name));
+        }
         let new_portal = Portal::new(statement_name, stmt, result_formats);
         self.portals.insert(portal_name, new_portal);
+        Ok(())
+    }
+
+    /// remove `Portal` by its name
+    pub fn remove_portal(&mut self, name: &str) {
+        self.portals.remove(name);
+    }
+
+    /// whether statements that modify data or schema are rejected
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// switch session between read-only and read-write modes
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// whether results of repeated selects are served from a cache
+    pub fn is_result_cache_enabled(&self) -> bool {
+        self.result_cache
+    }
+
+    /// switch caching of select results on or off
+    pub fn set_result_cache(&mut self, enabled: bool) {
+        self.result_cache = enabled;
+    }
+
+    /// user that client connected as
+    pub fn user(&self) -> &str {
+        self.user.as_str()
+    }
+
+    /// database that client connected to
+    pub fn database(&self) -> &str {
+        self.database.as_str()
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+
+    /// next random value in range `[0, 1)`
+    pub fn random(&mut self) -> f64 {
+        self.random.gen()
+    }
+
+    /// seeds generator of random values, sequence of values is the same
+    /// for the same seed
+    pub fn set_seed(&mut self, seed: f64) -> Result<(), QueryError> {
+        if !(-1.0..=1.0).contains(&seed) {
+            return Err(QueryError::invalid_parameter_value(format!(
+                "setseed parameter {} is out of allowed range [-1,1]",
+                seed
+            )));
+        }
+        self.random = StdRng::seed_from_u64(seed.to_bits());
+        Ok(())
+    }
+
+    /// sets session user, database and settings from properties that client
+    /// sent on startup. Database has the same name as the user unless
+    /// specified. Returns errors of settings that could not be applied
+    pub fn set_startup_properties(&mut self, properties: &[(String, String)]) -> Vec<QueryError> {
+        let property = |name: &str| {
+            properties
+                .iter()
+                .find(|(key, _value)| key == name)
+                .map(|(_key, value)| value.clone())
+        };
+        self.user = property("user").unwrap_or_else(|| DEFAULT_USER.to_owned());
+        self.database = property("database").unwrap_or_else(|| self.user.clone());
+
+        let mut startup_settings = vec![];
+        if let Some(application_name) = property("application_name") {
+            startup_settings.push(("application_name".to_owned(), application_name));
+        }
+        let mut errors = vec![];
+        match property("options").map(|options| settings::startup_options(&options)) {
+            Some(Ok(options)) => startup_settings.extend(options),
+            Some(Err(error)) => errors.push(error),
+            None => {}
+        }
+        for (name, value) in startup_settings {
+            if let Err(error) = self.settings.set(&name, &value, false) {
+                errors.push(error);
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement(stmt: &str) -> PreparedStatement<String> {
+        PreparedStatement::new(stmt.to_owned(), vec![], vec![])
+    }
+
+    #[cfg(test)]
+    mod prepared_statements {
+        use super::*;
+
+        #[test]
+        fn unnamed_statement_is_replaced() {
+            let mut session = Session::default();
+
+            assert_eq!(
+                session.set_prepared_statement(UNNAMED.to_owned(), statement("first")),
+                Ok(())
+            );
+            assert_eq!(
+                session.set_prepared_statement(UNNAMED.to_owned(), statement("second")),
+                Ok(())
+            );
+
+            assert_eq!(session.get_prepared_statement(UNNAMED), Some(&statement("second")));
+        }
+
+        #[test]
+        fn named_statement_can_not_be_redefined() {
+            let mut session = Session::default();
+
+            assert_eq!(
+                session.set_prepared_statement("name".to_owned(), statement("first")),
+                Ok(())
+            );
+            assert_eq!(
+                session.set_prepared_statement("name".to_owned(), statement("second")),
+                Err(QueryError::duplicate_prepared_statement("name"))
+            );
+
+            assert_eq!(session.get_prepared_statement("name"), Some(&statement("first")));
+        }
+
+        #[test]
+        fn named_statement_can_be_redefined_after_removal() {
+            let mut session = Session::default();
+
+            assert_eq!(
+                session.set_prepared_statement("name".to_owned(), statement("first")),
+                Ok(())
+            );
+            session.remove_prepared_statement("name");
+            assert_eq!(
+                session.set_prepared_statement("name".to_owned(), statement("second")),
+                Ok(())
+            );
+
+            assert_eq!(session.get_prepared_statement("name"), Some(&statement("second")));
+        }
+
+        #[test]
+        fn removing_statement_closes_its_portals() {
+            let mut session = Session::default();
+
+            assert_eq!(
+                session.set_prepared_statement("name".to_owned(), statement("stmt")),
+                Ok(())
+            );
+            assert_eq!(
+                session.set_portal("portal".to_owned(), "name".to_owned(), "stmt".to_owned(), vec![]),
+                Ok(())
+            );
+            session.remove_prepared_statement("name");
+
+            assert!(session.get_portal("portal").is_none());
+        }
+    }
+
+    #[cfg(test)]
+    mod portals {
+        use super::*;
+
+        #[test]
+        fn unnamed_portal_is_replaced() {
+            let mut session = Session::default();
+
+            assert_eq!(
+                session.set_portal(UNNAMED.to_owned(), UNNAMED.to_owned(), "first".to_owned(), vec![]),
+                Ok(())
+            );
+            assert_eq!(
+                session.set_portal(UNNAMED.to_owned(), UNNAMED.to_owned(), "second".to_owned(), vec![]),
+                Ok(())
+            );
+
+            assert_eq!(
+                session.get_portal(UNNAMED).map(Portal::stmt),
+                Some(&"second".to_owned())
+            );
+        }
+
+        #[test]
+        fn named_portal_can_not_be_redefined() {
+            let mut session = Session::default();
+
+            assert_eq!(
+                session.set_portal("portal".to_owned(), UNNAMED.to_owned(), "first".to_owned(), vec![]),
+                Ok(())
+            );
+            assert_eq!(
+                session.set_portal("portal".to_owned(), UNNAMED.to_owned(), "second".to_owned(), vec![]),
+                Err(QueryError::duplicate_portal("portal"))
+            );
+
+            assert_eq!(
+                session.get_portal("portal").map(Portal::stmt),
+                Some(&"first".to_owned())
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod read_only {
+        use super::*;
+
+        #[test]
+        fn read_write_by_default() {
+            let session = Session::<String>::default();
+
+            assert_eq!(session.is_read_only(), false);
+        }
+
+        #[test]
+        fn switch_to_read_only() {
+            let mut session = Session::<String>::default();
+
+            session.set_read_only(true);
+            assert_eq!(session.is_read_only(), true);
+
+            session.set_read_only(false);
+            assert_eq!(session.is_read_only(), false);
+        }
+    }
+
+    #[cfg(test)]
+    mod result_cache {
+        use super::*;
+
+        #[test]
+        fn disabled_by_default() {
+            let session = Session::<String>::default();
+
+            assert_eq!(session.is_result_cache_enabled(), false);
+        }
+
+        #[test]
+        fn enable_and_disable() {
+            let mut session = Session::<String>::default();
+
+            session.set_result_cache(true);
+            assert_eq!(session.is_result_cache_enabled(), true);
+
+            session.set_result_cache(false);
+            assert_eq!(session.is_result_cache_enabled(), false);
+        }
+    }
+
+    #[cfg(test)]
+    mod startup_properties {
+        use super::*;
+        use crate::settings::SettingValue;
+
+        fn properties(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        }
+
+        #[test]
+        fn default_user_and_database() {
+            let mut session = Session::<String>::default();
+
+            assert_eq!(session.set_startup_properties(&[]), vec![]);
+
+            assert_eq!(session.user(), DEFAULT_USER);
+            assert_eq!(session.database(), DEFAULT_USER);
+        }
+
+        #[test]
+        fn database_named_after_user() {
+            let mut session = Session::<String>::default();
+
+            session.set_startup_properties(&properties(&[("user", "alice")]));
+
+            assert_eq!(session.user(), "alice");
+            assert_eq!(session.database(), "alice");
+        }
+
+        #[test]
+        fn user_and_database() {
+            let mut session = Session::<String>::default();
+
+            session.set_startup_properties(&properties(&[
+                ("user", "alice"),
+                ("database", "shop"),
+                ("client_encoding", "UTF8"),
+            ]));
+
+            assert_eq!(session.user(), "alice");
+            assert_eq!(session.database(), "shop");
+        }
+
+        #[test]
+        fn application_name_and_options() {
+            let mut session = Session::<String>::default();
+
+            let errors = session.set_startup_properties(&properties(&[
+                ("application_name", "psql"),
+                ("options", "-c work_mem=1024 --result-cache=on"),
+            ]));
+
+            assert_eq!(errors, vec![]);
+            assert_eq!(
+                session.settings().get("application_name"),
+                Ok(&SettingValue::String("psql".to_owned()))
+            );
+            assert_eq!(session.settings().get("work_mem"), Ok(&SettingValue::Integer(1024)));
+            assert_eq!(session.settings().get("result_cache"), Ok(&SettingValue::Bool(true)));
+        }
+
+        #[test]
+        fn invalid_options_are_reported() {
+            let mut session = Session::<String>::default();
+
+            let errors =
+                session.set_startup_properties(&properties(&[("options", "-c not_a_setting=1 -c work_mem=64")]));
+
+            assert_eq!(
+                errors,
+                vec![QueryError::unrecognized_configuration_parameter("not_a_setting")]
+            );
+            assert_eq!(session.settings().get("work_mem"), Ok(&SettingValue::Integer(64)));
+        }
+    }
+
+    #[cfg(test)]
+    mod random {
+        use super::*;
+
+        #[test]
+        fn values_in_range() {
+            let mut session = Session::<String>::default();
+
+            for _ in 0..100 {
+                let value = session.random();
+                assert!((0.0..1.0).contains(&value), "{} is out of range", value);
+            }
+        }
+
+        #[test]
+        fn same_seed_same_values() {
+            let mut session = Session::<String>::default();
+            let mut other = Session::<String>::default();
+
+            assert_eq!(session.set_seed(0.5), Ok(()));
+            assert_eq!(other.set_seed(0.5), Ok(()));
+
+            let values = (0..3).map(|_| session.random()).collect::<Vec<f64>>();
+            assert_eq!(values, (0..3).map(|_| other.random()).collect::<Vec<f64>>());
+
+            assert_eq!(session.set_seed(0.5), Ok(()));
+            assert_eq!(values, (0..3).map(|_| session.random()).collect::<Vec<f64>>());
+        }
+
+        #[test]
+        fn seed_out_of_range() {
+            let mut session = Session::<String>::default();
+
+            assert_eq!(
+                session.set_seed(2.0),
+                Err(QueryError::invalid_parameter_value(
+                    "setseed parameter 2 is out of allowed range [-1,1]"
+                ))
+            );
+        }
     }
 }
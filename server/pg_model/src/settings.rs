@@ -0,0 +1,684 @@
+// Copyright 2020 - present Alex Dukhno
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{encoding, results::QueryError};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+};
+
+/// type of values that a setting accepts
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SettingType {
+    Bool,
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    String,
+    Enum(&'static [&'static str]),
+    /// milliseconds given as a number or with one of `ms`, `s`, `min`, `h`
+    /// or `d` units
+    Duration {
+        max: i64,
+    },
+    /// name of the `encoding::CLIENT_ENCODING` or one of its aliases
+    Encoding,
+}
+
+/// value of a setting
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    Bool(bool),
+    Integer(i64),
+    String(String),
+}
+
+impl SettingValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SettingValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            SettingValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SettingValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingValue::Bool(true) => write!(f, "on"),
+            SettingValue::Bool(false) => write!(f, "off"),
+            SettingValue::Integer(value) => write!(f, "{}", value),
+            SettingValue::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Setting {
+    setting_type: SettingType,
+    default: SettingValue,
+}
+
+impl Setting {
+    fn parse(&self, name: &str, value: &str) -> Result<SettingValue, QueryError> {
+        match self.setting_type {
+            SettingType::Bool => match value.to_lowercase().as_str() {
+                "on" | "true" | "yes" | "1" => Ok(SettingValue::Bool(true)),
+                "off" | "false" | "no" | "0" => Ok(SettingValue::Bool(false)),
+                _ => Err(QueryError::invalid_parameter_value(format!(
+                    "parameter \"{}\" requires a Boolean value",
+                    name
+                ))),
+            },
+            SettingType::Integer { min, max } => match value.parse::<i64>() {
+                Ok(integer) if integer < min || integer > max => Err(QueryError::invalid_parameter_value(format!(
+                    "{} is outside the valid range for parameter \"{}\" ({} .. {})",
+                    integer, name, min, max
+                ))),
+                Ok(integer) => Ok(SettingValue::Integer(integer)),
+                Err(_) => Err(QueryError::invalid_parameter_value(format!(
+                    "invalid value for parameter \"{}\": \"{}\"",
+                    name, value
+                ))),
+            },
+            SettingType::String => Ok(SettingValue::String(value.to_owned())),
+            SettingType::Enum(values) => match values.iter().find(|v| v.eq_ignore_ascii_case(value)) {
+                Some(value) => Ok(SettingValue::String((*value).to_owned())),
+                None => Err(QueryError::invalid_parameter_value(format!(
+                    "invalid value for parameter \"{}\": \"{}\"",
+                    name, value
+                ))),
+            },
+            SettingType::Duration { max } => match milliseconds(value) {
+                Some(millis) if millis < 0 || millis > max => Err(QueryError::invalid_parameter_value(format!(
+                    "{} ms is outside the valid range for parameter \"{}\" (0 .. {})",
+                    millis, name, max
+                ))),
+                Some(millis) => Ok(SettingValue::Integer(millis)),
+                None => Err(QueryError::invalid_parameter_value(format!(
+                    "invalid value for parameter \"{}\": \"{}\"",
+                    name, value
+                ))),
+            },
+            SettingType::Encoding if encoding::is_supported(value) => {
+                Ok(SettingValue::String(encoding::CLIENT_ENCODING.to_owned()))
+            }
+            SettingType::Encoding => Err(QueryError::invalid_parameter_value(format!(
+                "invalid value for parameter \"{}\": \"{}\"",
+                name, value
+            ))),
+        }
+    }
+}
+
+fn milliseconds(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit() && c != '-')
+        .unwrap_or(value.len());
+    let number = value[..unit_start].parse::<i64>().ok()?;
+    let factor = match value[unit_start..].trim() {
+        "" | "ms" => 1,
+        "s" => 1_000,
+        "min" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        _ => return None,
+    };
+    number.checked_mul(factor)
+}
+
+/// Settings of a session in the way of PostgreSQL GUC parameters.
+///
+/// A value set by `SET LOCAL` overrides one set by `SET` until the end of
+/// current transaction, which in turn overrides the default that node
+/// configuration could change. Names are case insensitive, the ones with
+/// a prefix like `app.tenant` are custom string settings created when they
+/// are set for the first time
+#[derive(Debug, Clone)]
+pub struct Settings {
+    registered: HashMap<String, Setting>,
+    session: HashMap<String, SettingValue>,
+    local: HashMap<String, SettingValue>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        let mut settings = Settings {
+            registered: HashMap::default(),
+            session: HashMap::default(),
+            local: HashMap::default(),
+        };
+        settings.register(
+            "application_name",
+            SettingType::String,
+            SettingValue::String("".to_owned()),
+        );
+        settings.register(
+            "compatibility_mode",
+            SettingType::Enum(&["postgresql", "strict"]),
+            SettingValue::String("postgresql".to_owned()),
+        );
+        settings.register("continue_on_error", SettingType::Bool, SettingValue::Bool(false));
+        settings.register(
+            "default_transaction_read_only",
+            SettingType::Bool,
+            SettingValue::Bool(false),
+        );
+        settings.register(
+            "extra_float_digits",
+            SettingType::Integer { min: -15, max: 3 },
+            SettingValue::Integer(1),
+        );
+        settings.register("protocol_trace", SettingType::Bool, SettingValue::Bool(false));
+        settings.register("result_cache", SettingType::Bool, SettingValue::Bool(false));
+        settings.register(
+            "search_path",
+            SettingType::String,
+            SettingValue::String("\"$user\", public".to_owned()),
+        );
+        settings.register("transaction_read_only", SettingType::Bool, SettingValue::Bool(false));
+        settings.register(
+            "work_mem",
+            SettingType::Integer { min: 0, max: i64::MAX },
+            SettingValue::Integer(i64::MAX),
+        );
+        // TODO: drivers and ORMs set these when they connect, they are accepted
+        //       and shown back but values other than defaults change nothing.
+        //       There are neither date/time types nor timeouts of statements,
+        //       notices are sent regardless of their level and literals are
+        //       always parsed as standard conforming strings
+        settings.register(
+            "bytea_output",
+            SettingType::Enum(&["hex", "escape"]),
+            SettingValue::String("hex".to_owned()),
+        );
+        settings.register(
+            "client_encoding",
+            SettingType::Encoding,
+            SettingValue::String(encoding::CLIENT_ENCODING.to_owned()),
+        );
+        settings.register(
+            "client_min_messages",
+            SettingType::Enum(&[
+                "debug5", "debug4", "debug3", "debug2", "debug1", "log", "notice", "warning", "error",
+            ]),
+            SettingValue::String("notice".to_owned()),
+        );
+        settings.register(
+            "datestyle",
+            SettingType::String,
+            SettingValue::String("ISO, MDY".to_owned()),
+        );
+        settings.register(
+            "default_transaction_isolation",
+            SettingType::Enum(&["serializable", "repeatable read", "read committed", "read uncommitted"]),
+            SettingValue::String("read committed".to_owned()),
+        );
+        settings.register("escape_string_warning", SettingType::Bool, SettingValue::Bool(true));
+        settings.register(
+            "idle_in_transaction_session_timeout",
+            SettingType::Duration { max: i32::MAX as i64 },
+            SettingValue::Integer(0),
+        );
+        settings.register(
+            "intervalstyle",
+            SettingType::Enum(&["postgres", "postgres_verbose", "sql_standard", "iso_8601"]),
+            SettingValue::String("postgres".to_owned()),
+        );
+        settings.register(
+            "lock_timeout",
+            SettingType::Duration { max: i32::MAX as i64 },
+            SettingValue::Integer(0),
+        );
+        settings.register(
+            "standard_conforming_strings",
+            SettingType::Bool,
+            SettingValue::Bool(true),
+        );
+        settings.register(
+            "statement_timeout",
+            SettingType::Duration { max: i32::MAX as i64 },
+            SettingValue::Integer(0),
+        );
+        settings.register(
+            "synchronous_commit",
+            SettingType::Enum(&["on", "off", "local", "remote_write", "remote_apply"]),
+            SettingValue::String("on".to_owned()),
+        );
+        settings.register("timezone", SettingType::String, SettingValue::String("UTC".to_owned()));
+        settings
+    }
+}
+
+impl Settings {
+    fn register(&mut self, name: &str, setting_type: SettingType, default: SettingValue) {
+        self.registered
+            .insert(name.to_owned(), Setting { setting_type, default });
+    }
+
+    fn setting(&self, name: &str) -> Result<(String, &Setting), QueryError> {
+        let name = name.to_lowercase();
+        match self.registered.get(&name) {
+            Some(setting) => Ok((name, setting)),
+            None => Err(QueryError::unrecognized_configuration_parameter(name)),
+        }
+    }
+
+    /// changes value that the setting has unless a session sets its own,
+    /// e.g. from node configuration
+    pub fn set_default(&mut self, name: &str, value: SettingValue) {
+        match self.registered.get_mut(&name.to_lowercase()) {
+            Some(setting) => setting.default = value,
+            None => panic!("setting {} is not registered", name),
+        }
+    }
+
+    /// current value of the setting
+    pub fn get(&self, name: &str) -> Result<&SettingValue, QueryError> {
+        let (name, setting) = self.setting(name)?;
+        Ok(self
+            .local
+            .get(&name)
+            .or_else(|| self.session.get(&name))
+            .unwrap_or(&setting.default))
+    }
+
+    /// names and current values of all settings ordered by name
+    pub fn all(&self) -> Vec<(&str, &SettingValue)> {
+        self.registered
+            .keys()
+            .map(|name| (name.as_str(), self.get(name).expect("setting is registered")))
+            .collect::<BTreeMap<&str, &SettingValue>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// sets the setting from its text representation till the end of
+    /// current transaction if `local` or of the session otherwise
+    pub fn set(&mut self, name: &str, value: &str, local: bool) -> Result<(), QueryError> {
+        let custom = name.to_lowercase();
+        if custom.contains('.') && !self.registered.contains_key(&custom) {
+            self.register(&custom, SettingType::String, SettingValue::String("".to_owned()));
+        }
+        let (name, setting) = self.setting(name)?;
+        let value = setting.parse(&name, value)?;
+        if local {
+            self.local.insert(name, value);
+        } else {
+            self.local.remove(&name);
+            self.session.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// brings the setting back to its default
+    pub fn reset(&mut self, name: &str) -> Result<(), QueryError> {
+        let (name, _setting) = self.setting(name)?;
+        self.local.remove(&name);
+        self.session.remove(&name);
+        Ok(())
+    }
+
+    /// brings all settings back to their defaults, returns names of the
+    /// ones that were changed
+    pub fn reset_all(&mut self) -> Vec<String> {
+        let mut changed = self.end_transaction();
+        changed.extend(self.session.drain().map(|(name, _value)| name));
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+
+    /// drops values of `SET LOCAL`, returns names of the settings they had
+    pub fn end_transaction(&mut self) -> Vec<String> {
+        self.local.drain().map(|(name, _value)| name).collect()
+    }
+}
+
+/// settings from `options` parameter of a startup message, given as
+/// `-c name=value` or `--name=value`. Whitespace separates options unless
+/// it is escaped by a backslash
+pub fn startup_options(options: &str) -> Result<Vec<(String, String)>, QueryError> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut chars = options.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+
+    let mut settings = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let setting = if arg == "-c" {
+            args.next().unwrap_or_default()
+        } else if let Some(setting) = arg.strip_prefix("-c") {
+            setting.to_owned()
+        } else if let Some(setting) = arg.strip_prefix("--") {
+            setting.to_owned()
+        } else {
+            return Err(QueryError::invalid_parameter_value(format!(
+                "invalid command-line argument for server process: {}",
+                arg
+            )));
+        };
+        match setting.find('=') {
+            Some(index) => settings.push((setting[..index].replace('-', "_"), setting[index + 1..].to_owned())),
+            None => {
+                return Err(QueryError::invalid_parameter_value(format!(
+                    "-c {} requires a value",
+                    setting
+                )))
+            }
+        }
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_value() {
+        let settings = Settings::default();
+
+        assert_eq!(settings.get("result_cache"), Ok(&SettingValue::Bool(false)));
+    }
+
+    #[test]
+    fn case_insensitive_names() {
+        let mut settings = Settings::default();
+
+        assert_eq!(settings.set("Application_Name", "psql", false), Ok(()));
+
+        assert_eq!(
+            settings.get("APPLICATION_NAME"),
+            Ok(&SettingValue::String("psql".to_owned()))
+        );
+    }
+
+    #[test]
+    fn enum_setting() {
+        let mut settings = Settings::default();
+
+        assert_eq!(settings.set("compatibility_mode", "STRICT", false), Ok(()));
+        assert_eq!(
+            settings.get("compatibility_mode"),
+            Ok(&SettingValue::String("strict".to_owned()))
+        );
+        assert_eq!(
+            settings.set("compatibility_mode", "mysql", false),
+            Err(QueryError::invalid_parameter_value(
+                "invalid value for parameter \"compatibility_mode\": \"mysql\""
+            ))
+        );
+    }
+
+    #[test]
+    fn unrecognized_setting() {
+        let mut settings = Settings::default();
+
+        assert_eq!(
+            settings.get("not_a_setting"),
+            Err(QueryError::unrecognized_configuration_parameter("not_a_setting"))
+        );
+        assert_eq!(
+            settings.set("not_a_setting", "on", false),
+            Err(QueryError::unrecognized_configuration_parameter("not_a_setting"))
+        );
+        assert_eq!(
+            settings.reset("not_a_setting"),
+            Err(QueryError::unrecognized_configuration_parameter("not_a_setting"))
+        );
+    }
+
+    #[test]
+    fn typed_values() {
+        let mut settings = Settings::default();
+
+        assert_eq!(settings.set("result_cache", "yes", false), Ok(()));
+        assert_eq!(settings.set("work_mem", "1024", false), Ok(()));
+
+        assert_eq!(settings.get("result_cache"), Ok(&SettingValue::Bool(true)));
+        assert_eq!(settings.get("work_mem"), Ok(&SettingValue::Integer(1024)));
+    }
+
+    #[test]
+    fn invalid_values() {
+        let mut settings = Settings::default();
+
+        assert_eq!(
+            settings.set("result_cache", "sometimes", false),
+            Err(QueryError::invalid_parameter_value(
+                "parameter \"result_cache\" requires a Boolean value"
+            ))
+        );
+        assert_eq!(
+            settings.set("work_mem", "lots", false),
+            Err(QueryError::invalid_parameter_value(
+                "invalid value for parameter \"work_mem\": \"lots\""
+            ))
+        );
+        assert_eq!(
+            settings.set("extra_float_digits", "4", false),
+            Err(QueryError::invalid_parameter_value(
+                "4 is outside the valid range for parameter \"extra_float_digits\" (-15 .. 3)"
+            ))
+        );
+    }
+
+    #[test]
+    fn settings_of_drivers() {
+        let mut settings = Settings::default();
+
+        assert_eq!(settings.set("client_encoding", "utf-8", false), Ok(()));
+        assert_eq!(settings.set("DateStyle", "ISO", false), Ok(()));
+        assert_eq!(settings.set("TimeZone", "Europe/Kyiv", false), Ok(()));
+        assert_eq!(settings.set("client_min_messages", "WARNING", false), Ok(()));
+        assert_eq!(settings.set("standard_conforming_strings", "on", false), Ok(()));
+
+        assert_eq!(
+            settings.get("client_encoding"),
+            Ok(&SettingValue::String("UTF8".to_owned()))
+        );
+        assert_eq!(settings.get("datestyle"), Ok(&SettingValue::String("ISO".to_owned())));
+        assert_eq!(
+            settings.get("timezone"),
+            Ok(&SettingValue::String("Europe/Kyiv".to_owned()))
+        );
+        assert_eq!(
+            settings.get("client_min_messages"),
+            Ok(&SettingValue::String("warning".to_owned()))
+        );
+        assert_eq!(
+            settings.set("client_encoding", "LATIN1", false),
+            Err(QueryError::invalid_parameter_value(
+                "invalid value for parameter \"client_encoding\": \"LATIN1\""
+            ))
+        );
+    }
+
+    #[test]
+    fn durations() {
+        let mut settings = Settings::default();
+
+        assert_eq!(settings.set("statement_timeout", "0", false), Ok(()));
+        assert_eq!(settings.get("statement_timeout"), Ok(&SettingValue::Integer(0)));
+        assert_eq!(settings.set("statement_timeout", "1500", false), Ok(()));
+        assert_eq!(settings.get("statement_timeout"), Ok(&SettingValue::Integer(1500)));
+        assert_eq!(settings.set("statement_timeout", "30s", false), Ok(()));
+        assert_eq!(settings.get("statement_timeout"), Ok(&SettingValue::Integer(30_000)));
+        assert_eq!(settings.set("lock_timeout", "2 min", false), Ok(()));
+        assert_eq!(settings.get("lock_timeout"), Ok(&SettingValue::Integer(120_000)));
+
+        assert_eq!(
+            settings.set("statement_timeout", "5 weeks", false),
+            Err(QueryError::invalid_parameter_value(
+                "invalid value for parameter \"statement_timeout\": \"5 weeks\""
+            ))
+        );
+        assert_eq!(
+            settings.set("statement_timeout", "-1", false),
+            Err(QueryError::invalid_parameter_value(
+                "-1 ms is outside the valid range for parameter \"statement_timeout\" (0 .. 2147483647)"
+            ))
+        );
+    }
+
+    #[test]
+    fn default_from_node_configuration() {
+        let mut settings = Settings::default();
+
+        settings.set_default("work_mem", SettingValue::Integer(4096));
+        assert_eq!(settings.get("work_mem"), Ok(&SettingValue::Integer(4096)));
+
+        assert_eq!(settings.set("work_mem", "1024", false), Ok(()));
+        assert_eq!(settings.reset("work_mem"), Ok(()));
+        assert_eq!(settings.get("work_mem"), Ok(&SettingValue::Integer(4096)));
+    }
+
+    #[test]
+    fn local_value_till_end_of_transaction() {
+        let mut settings = Settings::default();
+
+        assert_eq!(settings.set("application_name", "session", false), Ok(()));
+        assert_eq!(settings.set("application_name", "local", true), Ok(()));
+        assert_eq!(
+            settings.get("application_name"),
+            Ok(&SettingValue::String("local".to_owned()))
+        );
+
+        assert_eq!(settings.end_transaction(), vec!["application_name".to_owned()]);
+        assert_eq!(
+            settings.get("application_name"),
+            Ok(&SettingValue::String("session".to_owned()))
+        );
+    }
+
+    #[test]
+    fn custom_setting_with_prefix() {
+        let mut settings = Settings::default();
+
+        assert_eq!(
+            settings.get("app.tenant"),
+            Err(QueryError::unrecognized_configuration_parameter("app.tenant"))
+        );
+
+        assert_eq!(settings.set("App.Tenant", "acme", false), Ok(()));
+        assert_eq!(settings.get("app.tenant"), Ok(&SettingValue::String("acme".to_owned())));
+
+        assert_eq!(settings.reset("app.tenant"), Ok(()));
+        assert_eq!(settings.get("app.tenant"), Ok(&SettingValue::String("".to_owned())));
+    }
+
+    #[test]
+    fn reset_all() {
+        let mut settings = Settings::default();
+
+        assert_eq!(settings.set("work_mem", "1024", false), Ok(()));
+        assert_eq!(settings.set("result_cache", "on", true), Ok(()));
+        assert_eq!(settings.set("work_mem", "2048", true), Ok(()));
+
+        assert_eq!(
+            settings.reset_all(),
+            vec!["result_cache".to_owned(), "work_mem".to_owned()]
+        );
+        assert_eq!(settings.get("work_mem"), Ok(&SettingValue::Integer(i64::MAX)));
+        assert_eq!(settings.get("result_cache"), Ok(&SettingValue::Bool(false)));
+    }
+
+    #[test]
+    fn all_settings_ordered_by_name() {
+        let settings = Settings::default();
+
+        let names = settings
+            .all()
+            .into_iter()
+            .map(|(name, _value)| name)
+            .collect::<Vec<&str>>();
+        let mut sorted = names.clone();
+        sorted.sort();
+
+        assert_eq!(names, sorted);
+        assert!(names.contains(&"work_mem"));
+    }
+
+    #[cfg(test)]
+    mod startup_options {
+        use super::*;
+
+        #[test]
+        fn empty() {
+            assert_eq!(startup_options(""), Ok(vec![]));
+        }
+
+        #[test]
+        fn separated_and_joined_switches() {
+            assert_eq!(
+                startup_options("-c work_mem=1024 -cresult_cache=on --extra-float-digits=3"),
+                Ok(vec![
+                    ("work_mem".to_owned(), "1024".to_owned()),
+                    ("result_cache".to_owned(), "on".to_owned()),
+                    ("extra_float_digits".to_owned(), "3".to_owned()),
+                ])
+            );
+        }
+
+        #[test]
+        fn escaped_whitespace() {
+            assert_eq!(
+                startup_options("-c application_name=my\\ app"),
+                Ok(vec![("application_name".to_owned(), "my app".to_owned())])
+            );
+        }
+
+        #[test]
+        fn unknown_switch() {
+            assert_eq!(
+                startup_options("-d 5"),
+                Err(QueryError::invalid_parameter_value(
+                    "invalid command-line argument for server process: -d"
+                ))
+            );
+        }
+
+        #[test]
+        fn setting_without_value() {
+            assert_eq!(
+                startup_options("-c work_mem"),
+                Err(QueryError::invalid_parameter_value("-c work_mem requires a value"))
+            );
+        }
+    }
+}
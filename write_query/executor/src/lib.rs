@@ -26,6 +26,10 @@ impl<D: Database> Executor<D> {
         Executor { database }
     }
 
+    // TODO: BEFORE/AFTER triggers should fire here around writes with OLD/NEW
+    //       rows bound to trigger function arguments. It is blocked on
+    //       `CREATE TRIGGER` support in the parser and on executing SQL bodies
+    //       of trigger functions, while writes are still not executed here
     pub fn execute(&self, _write_query: Write) {}
 }
 